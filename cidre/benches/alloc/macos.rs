@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::{ns, objc::ar_pool};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let num = i64::MAX - 1;
@@ -76,4 +78,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
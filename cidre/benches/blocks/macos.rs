@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::{blocks, dispatch, ns, objc::ar_pool};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     let _d = ns::Data::new();
@@ -61,4 +63,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
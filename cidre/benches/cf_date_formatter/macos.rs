@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::{cf, ns, objc::ar_pool};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 // TODO: investigate why ns::Iso8601DateFormatter is faster...
 
@@ -146,4 +148,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
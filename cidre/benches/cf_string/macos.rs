@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::cf;
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("cf::String::from_str tagged", |b| {
@@ -32,4 +34,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
@@ -1,7 +1,9 @@
+#![cfg(target_os = "macos")]
+
 use std::ffi::c_void;
 
 use cidre::dispatch;
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 extern "C-unwind" fn work(_ctx: *mut u8) {}
 
@@ -27,4 +29,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
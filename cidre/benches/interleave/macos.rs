@@ -1,10 +1,12 @@
+#![cfg(target_os = "macos")]
+
 use std::{ffi::c_void, hint::black_box};
 
 use cidre::{
     at::{self, au, audio},
     av, os, vdsp,
 };
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 const N: usize = 1024;
 pub fn criterion_benchmark(c: &mut Criterion) {
@@ -118,6 +120,16 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 
     lr.fill(0.0f32);
 
+    let mut converter = audio::PcmConverter::new(&src_asbd, &dst_asbd).unwrap();
+    c.bench_function("interleave with audio::PcmConverter", |b| {
+        b.iter(|| {
+            unsafe { converter.convert(N, &list_a, &mut list_b) }.unwrap();
+        });
+        assert_eq!(lr, res);
+    });
+
+    lr.fill(0.0f32);
+
     let comp = lr.as_mut_ptr() as *mut vdsp::Complex<f32>;
     let mut comp = unsafe { std::slice::from_raw_parts_mut(comp, N) };
     c.bench_function("interleave vDSP", |b| {
@@ -129,4 +141,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
@@ -0,0 +1,7 @@
+mod macos;
+
+#[cfg(target_os = "macos")]
+criterion::criterion_main!(macos::benches);
+
+#[cfg(not(target_os = "macos"))]
+fn main() {}
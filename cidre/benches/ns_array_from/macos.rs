@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::{arc, ns, objc::ar_pool};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("array_with_buf_inside", |b| {
@@ -58,4 +60,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
@@ -1,5 +1,7 @@
+#![cfg(target_os = "macos")]
+
 use cidre::{cf, ns};
-use criterion::{criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, Criterion};
 
 pub fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("cf::uuid", |b| {
@@ -46,4 +48,3 @@ pub fn criterion_benchmark(c: &mut Criterion) {
 }
 
 criterion_group!(benches, criterion_benchmark);
-criterion_main!(benches);
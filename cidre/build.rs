@@ -27,24 +27,22 @@ impl Version {
 
     fn with_str(str: &str) -> Option<Self> {
         let mut res = Self { major: 0, minor: 0 };
-        let mut iter = str.split("_").into_iter();
-        let Some(s) = iter.next() else {
-            return None;
-        };
+        let mut iter = str.split("_");
+        let s = iter.next()?;
 
         res.major = str::parse(s).unwrap();
 
-        let Some(s) = iter.next() else {
-            return None;
-        };
+        let s = iter.next()?;
 
         res.minor = str::parse(s).unwrap();
 
         Some(res)
     }
+}
 
-    fn to_string(&self) -> String {
-        format!("{}.{}", self.major, self.minor)
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
     }
 }
 
@@ -150,8 +148,8 @@ fn parse_deployment_targets() -> DeploymentTargets {
     let path = PathBuf::from_str(&path).unwrap();
     let path = path.join("Cargo.toml");
     let str = read_to_string(path).unwrap();
-    let mut lines_iter = str.lines().into_iter();
-    while let Some(line) = lines_iter.next() {
+    let mut lines_iter = str.lines();
+    for line in lines_iter.by_ref() {
         if !line.starts_with("# deployment targets") {
             continue;
         }
@@ -165,7 +163,7 @@ fn parse_deployment_targets() -> DeploymentTargets {
     let mut watchos = Vec::new();
     let mut visionos = Vec::new();
 
-    while let Some(line) = lines_iter.next() {
+    for line in lines_iter {
         if line.starts_with("# end of deployment targets") {
             break;
         }
@@ -207,29 +205,35 @@ fn parse_deployment_targets() -> DeploymentTargets {
     let mut res = DeploymentTargets::default();
 
     if let Some(v) = Version::with_table(&macos) {
-        res.macos = format!("MACOSX_DEPLOYMENT_TARGET={}", v.to_string());
+        res.macos = format!("MACOSX_DEPLOYMENT_TARGET={v}");
     }
     if let Some(v) = Version::with_table(&ios) {
-        res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={}", v.to_string());
+        res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={v}");
     }
     if let Some(v) = Version::with_table(&tvos) {
-        res.tvos = format!("TVOS_DEPLOYMENT_TARGET={}", v.to_string());
+        res.tvos = format!("TVOS_DEPLOYMENT_TARGET={v}");
     }
     if let Some(v) = Version::with_table(&watchos) {
-        res.watchos = format!("WATCHOS_DEPLOYMENT_TARGET={}", v.to_string());
+        res.watchos = format!("WATCHOS_DEPLOYMENT_TARGET={v}");
     }
     if let Some(v) = Version::with_table(&maccatalyst) {
         // TODO: investigate
-        res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={}", v.to_string());
+        res.ios = format!("IPHONEOS_DEPLOYMENT_TARGET={v}");
     }
     if let Some(v) = Version::with_table(&visionos) {
-        res.visionos = format!("XROS_DEPLOYMENT_TARGET={}", v.to_string());
+        res.visionos = format!("XROS_DEPLOYMENT_TARGET={v}");
     }
 
     res
 }
 
 fn main() {
+    // Off Apple platforms lib.rs compiles only the portable modules (plist, usbmux,
+    // vdsp_portable...), there is nothing to build or link there.
+    if env::var("CARGO_CFG_TARGET_VENDOR").as_deref() != Ok("apple") {
+        return;
+    }
+
     let versions = parse_deployment_targets();
 
     let sdk = match env::var("TARGET").unwrap().as_ref() {
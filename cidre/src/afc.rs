@@ -0,0 +1,1274 @@
+//! Apple File Conduit client without MobileDevice.framework.
+//!
+//! AFC serves the media directory of a device as `com.apple.afc`, crash
+//! logs as `com.apple.crashreportcopymobile` and app containers through
+//! house arrest. Any byte stream of such a service works: a usbmux tunnel
+//! to the port of `lockdown::Client::start_service` or the socket of
+//! `am::ServiceConnection`.
+//!
+//! ```no_run
+//! use std::io::Read;
+//!
+//! # fn stream() -> std::net::TcpStream { unimplemented!() }
+//! let mut afc = cidre::afc::Client::new(stream());
+//! for name in afc.read_dir("/DCIM").unwrap() {
+//!     println!("{name}");
+//! }
+//! let mut file = afc.open("/iTunes_Control/iTunes/Photos", cidre::afc::OpenMode::READ).unwrap();
+//! let mut buf = Vec::new();
+//! file.read_to_end(&mut buf).unwrap();
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::{Component, Path},
+    time::{Duration, SystemTime},
+};
+
+/// Service of the media directory.
+pub const SERVICE_NAME: &str = "com.apple.afc";
+
+/// Service of crash logs.
+pub const CRASH_REPORT_SERVICE_NAME: &str = "com.apple.crashreportcopymobile";
+
+const MAGIC: &[u8; 8] = b"CFA6LPAA";
+
+const HEADER_LEN: usize = 40;
+
+/// Writes bigger than this are split.
+const MAX_WRITE: usize = 1 << 15;
+
+/// Reads bigger than this are split.
+const MAX_READ: usize = 1 << 16;
+
+/// AFC packets carry file chunks, anything bigger is a broken stream.
+const MAX_PACKET_LEN: usize = 64 << 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+struct Op(u64);
+
+impl Op {
+    const STATUS: Self = Self(0x01);
+    const DATA: Self = Self(0x02);
+    const READ_DIR: Self = Self(0x03);
+    const REMOVE_PATH: Self = Self(0x08);
+    const MAKE_DIR: Self = Self(0x09);
+    const GET_FILE_INFO: Self = Self(0x0a);
+    const GET_DEV_INFO: Self = Self(0x0b);
+    const FILE_OPEN: Self = Self(0x0d);
+    const FILE_OPEN_RES: Self = Self(0x0e);
+    const FILE_READ: Self = Self(0x0f);
+    const FILE_WRITE: Self = Self(0x10);
+    const FILE_SEEK: Self = Self(0x11);
+    const FILE_TELL: Self = Self(0x12);
+    const FILE_TELL_RES: Self = Self(0x13);
+    const FILE_CLOSE: Self = Self(0x14);
+    const FILE_SET_SIZE: Self = Self(0x15);
+    const RENAME_PATH: Self = Self(0x18);
+    const REMOVE_PATH_AND_CONTENTS: Self = Self(0x22);
+}
+
+/// Error code of `STATUS` replies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Status(pub u64);
+
+impl Status {
+    pub const SUCCESS: Self = Self(0);
+    pub const UNKNOWN_ERROR: Self = Self(1);
+    pub const OP_HEADER_INVALID: Self = Self(2);
+    pub const NO_RESOURCES: Self = Self(3);
+    pub const READ_ERROR: Self = Self(4);
+    pub const WRITE_ERROR: Self = Self(5);
+    pub const UNKNOWN_PACKET_TYPE: Self = Self(6);
+    pub const INVALID_ARG: Self = Self(7);
+    pub const OBJECT_NOT_FOUND: Self = Self(8);
+    pub const OBJECT_IS_DIR: Self = Self(9);
+    pub const PERM_DENIED: Self = Self(10);
+    pub const SERVICE_NOT_CONNECTED: Self = Self(11);
+    pub const OP_TIMEOUT: Self = Self(12);
+    pub const TOO_MUCH_DATA: Self = Self(13);
+    pub const END_OF_DATA: Self = Self(14);
+    pub const OP_NOT_SUPPORTED: Self = Self(15);
+    pub const OBJECT_EXISTS: Self = Self(16);
+    pub const OBJECT_BUSY: Self = Self(17);
+    pub const NO_SPACE_LEFT: Self = Self(18);
+    pub const OP_WOULD_BLOCK: Self = Self(19);
+    pub const IO_ERROR: Self = Self(20);
+    pub const OP_INTERRUPTED: Self = Self(21);
+    pub const OP_IN_PROGRESS: Self = Self(22);
+    pub const INTERNAL_ERROR: Self = Self(23);
+    pub const DIR_NOT_EMPTY: Self = Self(33);
+
+    fn io_kind(self) -> io::ErrorKind {
+        match self {
+            Self::OBJECT_NOT_FOUND => io::ErrorKind::NotFound,
+            Self::PERM_DENIED => io::ErrorKind::PermissionDenied,
+            Self::OBJECT_EXISTS => io::ErrorKind::AlreadyExists,
+            Self::INVALID_ARG => io::ErrorKind::InvalidInput,
+            Self::OP_TIMEOUT => io::ErrorKind::TimedOut,
+            Self::OP_WOULD_BLOCK => io::ErrorKind::WouldBlock,
+            Self::OP_INTERRUPTED => io::ErrorKind::Interrupted,
+            Self::OP_NOT_SUPPORTED => io::ErrorKind::Unsupported,
+            _ => io::ErrorKind::Other,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+
+    /// Malformed packet or reply of an unexpected shape.
+    Protocol(&'static str),
+
+    /// `STATUS` reply with an error code.
+    Status(Status),
+}
+
+impl Error {
+    /// Status the device replied with.
+    pub fn status(&self) -> Option<Status> {
+        match self {
+            Self::Status(status) => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Protocol(what) => write!(f, "afc: {what}"),
+            Self::Status(status) => write!(f, "afc: status {}", status.0),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(value: Error) -> Self {
+        match value {
+            Error::Io(e) => e,
+            Error::Protocol(_) => io::Error::new(io::ErrorKind::InvalidData, value),
+            Error::Status(status) => io::Error::new(status.io_kind(), value),
+        }
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// How [`Client::open`] opens a file, like `fopen` modes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct OpenMode(pub u64);
+
+impl OpenMode {
+    /// `r`
+    pub const READ: Self = Self(1);
+    /// `r+`
+    pub const READ_WRITE: Self = Self(2);
+    /// `w`, creates or truncates.
+    pub const WRITE: Self = Self(3);
+    /// `w+`, creates or truncates.
+    pub const WRITE_READ: Self = Self(4);
+    /// `a`
+    pub const APPEND: Self = Self(5);
+    /// `a+`
+    pub const READ_APPEND: Self = Self(6);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileType {
+    File,
+    Dir,
+    Symlink,
+    /// Other `st_ifmt`, like `S_IFCHR`.
+    Other(String),
+}
+
+/// [`Client::stat`] of a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    pub file_type: FileType,
+    pub len: u64,
+    pub blocks: u64,
+    pub nlink: u64,
+    pub modified: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+    /// Target of symlinks.
+    pub link_target: Option<String>,
+    /// All keys as the device sent them.
+    pub raw: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    fn from_raw(raw: BTreeMap<String, String>) -> Result<Self> {
+        let int = |key: &str| raw.get(key).and_then(|v| v.parse::<u64>().ok());
+        let time = |key: &str| int(key).map(|ns| SystemTime::UNIX_EPOCH + Duration::from_nanos(ns));
+        let file_type = match raw.get("st_ifmt").map(String::as_str) {
+            Some("S_IFREG") => FileType::File,
+            Some("S_IFDIR") => FileType::Dir,
+            Some("S_IFLNK") => FileType::Symlink,
+            Some(other) => FileType::Other(other.to_string()),
+            None => return Err(Error::Protocol("file info without st_ifmt")),
+        };
+        Ok(Self {
+            file_type,
+            len: int("st_size").unwrap_or(0),
+            blocks: int("st_blocks").unwrap_or(0),
+            nlink: int("st_nlink").unwrap_or(0),
+            modified: time("st_mtime"),
+            created: time("st_birthtime"),
+            link_target: raw.get("LinkTarget").cloned(),
+            raw,
+        })
+    }
+
+    #[inline]
+    pub fn is_dir(&self) -> bool {
+        self.file_type == FileType::Dir
+    }
+
+    #[inline]
+    pub fn is_file(&self) -> bool {
+        self.file_type == FileType::File
+    }
+
+    #[inline]
+    pub fn is_symlink(&self) -> bool {
+        self.file_type == FileType::Symlink
+    }
+}
+
+/// [`Client::device_info`] of the file system.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    pub model: String,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    pub block_size: u64,
+    /// All keys as the device sent them.
+    pub raw: BTreeMap<String, String>,
+}
+
+/// Progress of [`Client::upload`] and [`Client::download`], reported after
+/// each chunk and once per file before the first.
+#[derive(Debug, Clone, Copy)]
+pub struct Progress<'a> {
+    pub local: &'a Path,
+    pub remote: &'a str,
+    pub file_done: u64,
+    pub file_len: u64,
+    /// Bytes of all files so far.
+    pub total_done: u64,
+}
+
+/// NUL separated strings of replies.
+fn strings(data: &[u8]) -> Result<Vec<String>> {
+    data.split(|b| *b == 0)
+        .filter(|s| !s.is_empty())
+        .map(|s| {
+            String::from_utf8(s.to_vec()).map_err(|_| Error::Protocol("reply string isn't UTF-8"))
+        })
+        .collect()
+}
+
+/// Key value pairs of info replies.
+fn pairs(data: &[u8]) -> Result<BTreeMap<String, String>> {
+    let strings = strings(data)?;
+    if strings.len() & 1 != 0 {
+        return Err(Error::Protocol("info reply with a key without value"));
+    }
+    let mut iter = strings.into_iter();
+    let mut res = BTreeMap::new();
+    while let (Some(k), Some(v)) = (iter.next(), iter.next()) {
+        res.insert(k, v);
+    }
+    Ok(res)
+}
+
+fn path_arg(path: &str) -> Vec<u8> {
+    let mut res = Vec::with_capacity(path.len() + 1);
+    res.extend_from_slice(path.as_bytes());
+    res.push(0);
+    res
+}
+
+fn u64_at(data: &[u8], i: usize) -> Result<u64> {
+    data.get(i * 8..i * 8 + 8)
+        .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+        .ok_or(Error::Protocol("reply too short"))
+}
+
+fn write_packet<W: Write>(w: &mut W, num: u64, op: Op, header: &[u8], data: &[u8]) -> Result {
+    let this_len = HEADER_LEN + header.len();
+    let mut buf = Vec::with_capacity(this_len + data.len());
+    buf.extend_from_slice(MAGIC);
+    for v in [(this_len + data.len()) as u64, this_len as u64, num, op.0] {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+    buf.extend_from_slice(header);
+    buf.extend_from_slice(data);
+    w.write_all(&buf)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a packet, header arguments and data joined.
+fn read_packet<R: Read>(r: &mut R) -> Result<(u64, Op, Vec<u8>)> {
+    let mut header = [0u8; HEADER_LEN];
+    r.read_exact(&mut header)?;
+    if &header[..8] != MAGIC {
+        return Err(Error::Protocol("bad packet magic"));
+    }
+    let field = |i: usize| u64::from_le_bytes(header[8 + i * 8..16 + i * 8].try_into().unwrap());
+    let (entire_len, this_len) = (field(0) as usize, field(1) as usize);
+    if this_len < HEADER_LEN || entire_len < this_len || entire_len > MAX_PACKET_LEN {
+        return Err(Error::Protocol("bad packet length"));
+    }
+    let mut body = vec![0u8; entire_len - HEADER_LEN];
+    r.read_exact(&mut body)?;
+    Ok((field(2), Op(field(3)), body))
+}
+
+/// Blocking AFC connection.
+pub struct Client<S> {
+    stream: S,
+    packet_num: u64,
+}
+
+impl<S: Read + Write> Client<S> {
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            packet_num: 0,
+        }
+    }
+
+    /// Sends a request and reads its reply, error statuses are errors.
+    fn request(&mut self, op: Op, header: &[u8], data: &[u8]) -> Result<(Op, Vec<u8>)> {
+        let num = self.packet_num;
+        self.packet_num += 1;
+        write_packet(&mut self.stream, num, op, header, data)?;
+        let (reply_num, op, body) = read_packet(&mut self.stream)?;
+        if reply_num != num {
+            return Err(Error::Protocol("reply to another request"));
+        }
+        if op == Op::STATUS {
+            let status = Status(u64_at(&body, 0)?);
+            if status != Status::SUCCESS {
+                return Err(Error::Status(status));
+            }
+        }
+        Ok((op, body))
+    }
+
+    /// Request answered with a success status.
+    fn command(&mut self, op: Op, header: &[u8]) -> Result {
+        match self.request(op, header, &[])? {
+            (Op::STATUS, _) => Ok(()),
+            _ => Err(Error::Protocol("unexpected reply")),
+        }
+    }
+
+    /// Request answered with data.
+    fn data(&mut self, op: Op, header: &[u8]) -> Result<Vec<u8>> {
+        match self.request(op, header, &[])? {
+            (Op::DATA, data) => Ok(data),
+            _ => Err(Error::Protocol("unexpected reply")),
+        }
+    }
+
+    pub fn device_info(&mut self) -> Result<DeviceInfo> {
+        let raw = pairs(&self.data(Op::GET_DEV_INFO, &[])?)?;
+        let int = |key: &str| raw.get(key).and_then(|v| v.parse().ok()).unwrap_or(0);
+        Ok(DeviceInfo {
+            model: raw.get("Model").cloned().unwrap_or_default(),
+            total_bytes: int("FSTotalBytes"),
+            free_bytes: int("FSFreeBytes"),
+            block_size: int("FSBlockSize"),
+            raw,
+        })
+    }
+
+    /// Names in a directory without `.` and `..`.
+    pub fn read_dir(&mut self, path: &str) -> Result<Vec<String>> {
+        let mut names = strings(&self.data(Op::READ_DIR, &path_arg(path))?)?;
+        names.retain(|n| n != "." && n != "..");
+        Ok(names)
+    }
+
+    /// Metadata of a path, symlinks aren't followed.
+    pub fn stat(&mut self, path: &str) -> Result<Metadata> {
+        Metadata::from_raw(pairs(&self.data(Op::GET_FILE_INFO, &path_arg(path))?)?)
+    }
+
+    /// Whether a path exists.
+    pub fn exists(&mut self, path: &str) -> Result<bool> {
+        match self.stat(path) {
+            Ok(_) => Ok(true),
+            Err(Error::Status(Status::OBJECT_NOT_FOUND)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Creates a directory and missing parents.
+    pub fn mkdir(&mut self, path: &str) -> Result {
+        self.command(Op::MAKE_DIR, &path_arg(path))
+    }
+
+    /// Removes a file or an empty directory.
+    pub fn remove(&mut self, path: &str) -> Result {
+        self.command(Op::REMOVE_PATH, &path_arg(path))
+    }
+
+    /// Removes a path and everything in it.
+    pub fn remove_all(&mut self, path: &str) -> Result {
+        self.command(Op::REMOVE_PATH_AND_CONTENTS, &path_arg(path))
+    }
+
+    pub fn rename(&mut self, from: &str, to: &str) -> Result {
+        let args = [path_arg(from), path_arg(to)].concat();
+        self.command(Op::RENAME_PATH, &args)
+    }
+
+    pub fn open(&mut self, path: &str, mode: OpenMode) -> Result<File<'_, S>> {
+        let args = [&mode.0.to_le_bytes()[..], &path_arg(path)].concat();
+        let handle = match self.request(Op::FILE_OPEN, &args, &[])? {
+            (Op::FILE_OPEN_RES, body) => u64_at(&body, 0)?,
+            _ => return Err(Error::Protocol("unexpected reply")),
+        };
+        Ok(File {
+            client: self,
+            handle,
+            closed: false,
+        })
+    }
+
+    /// Contents of a file.
+    pub fn read(&mut self, path: &str) -> Result<Vec<u8>> {
+        let mut file = self.open(path, OpenMode::READ)?;
+        let mut res = Vec::new();
+        file.read_to_end(&mut res)?;
+        file.close()?;
+        Ok(res)
+    }
+
+    /// Creates or replaces a file.
+    pub fn write(&mut self, path: &str, contents: &[u8]) -> Result {
+        let mut file = self.open(path, OpenMode::WRITE)?;
+        file.write_all(contents)?;
+        file.close()
+    }
+
+    /// Copies a local file or directory tree to `remote`.
+    pub fn upload(
+        &mut self,
+        local: &Path,
+        remote: &str,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result {
+        let mut total = 0;
+        self.upload_path(local, remote, progress, &mut total)
+    }
+
+    fn upload_path(
+        &mut self,
+        local: &Path,
+        remote: &str,
+        progress: &mut dyn FnMut(Progress),
+        total: &mut u64,
+    ) -> Result {
+        if local.is_dir() {
+            self.mkdir(remote)?;
+            let mut entries = std::fs::read_dir(local)?
+                .map(|e| e.map(|e| e.file_name()))
+                .collect::<io::Result<Vec<_>>>()?;
+            entries.sort();
+            for name in entries {
+                let name_str = name
+                    .to_str()
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "non UTF-8 name"))?;
+                let remote = join(remote, name_str);
+                self.upload_path(&local.join(&name), &remote, progress, total)?;
+            }
+            return Ok(());
+        }
+
+        let mut src = std::fs::File::open(local)?;
+        let file_len = src.metadata()?.len();
+        let mut report = |file_done, total_done| {
+            progress(Progress {
+                local,
+                remote,
+                file_done,
+                file_len,
+                total_done,
+            })
+        };
+        report(0, *total);
+        let mut dst = self.open(remote, OpenMode::WRITE)?;
+        let mut buf = vec![0u8; MAX_WRITE];
+        let mut done = 0;
+        loop {
+            let n = src.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            dst.write_all(&buf[..n])?;
+            done += n as u64;
+            *total += n as u64;
+            report(done, *total);
+        }
+        dst.close()
+    }
+
+    /// Copies a remote file or directory tree to `local`, symlinks are
+    /// skipped.
+    pub fn download(
+        &mut self,
+        remote: &str,
+        local: &Path,
+        progress: &mut dyn FnMut(Progress),
+    ) -> Result {
+        let mut total = 0;
+        self.download_path(remote, local, progress, &mut total)
+    }
+
+    fn download_path(
+        &mut self,
+        remote: &str,
+        local: &Path,
+        progress: &mut dyn FnMut(Progress),
+        total: &mut u64,
+    ) -> Result {
+        let meta = self.stat(remote)?;
+        match meta.file_type {
+            FileType::Dir => {
+                std::fs::create_dir_all(local)?;
+                let mut names = self.read_dir(remote)?;
+                names.sort();
+                for name in names {
+                    if !plain_name(&name) {
+                        return Err(Error::Protocol("directory entry isn't a plain name"));
+                    }
+                    let remote = join(remote, &name);
+                    self.download_path(&remote, &local.join(&name), progress, total)?;
+                }
+                return Ok(());
+            }
+            FileType::File => {}
+            _ => return Ok(()),
+        }
+
+        let file_len = meta.len;
+        let mut report = |file_done, total_done| {
+            progress(Progress {
+                local,
+                remote,
+                file_done,
+                file_len,
+                total_done,
+            })
+        };
+        report(0, *total);
+        let mut dst = std::fs::File::create(local)?;
+        let mut src = self.open(remote, OpenMode::READ)?;
+        let mut buf = vec![0u8; MAX_READ];
+        let mut done = 0;
+        loop {
+            let n = src.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            dst.write_all(&buf[..n])?;
+            done += n as u64;
+            *total += n as u64;
+            report(done, *total);
+        }
+        src.close()
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+/// Whether a directory entry of the device stays in the local directory
+/// it's joined to, no separators, `..` or roots.
+fn plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    !name.contains(['/', '\0'])
+        && matches!(components.next(), Some(Component::Normal(_)))
+        && components.next().is_none()
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.ends_with('/') {
+        format!("{dir}{name}")
+    } else {
+        format!("{dir}/{name}")
+    }
+}
+
+/// Open file of a [`Client`], closed on drop.
+pub struct File<'a, S: Read + Write> {
+    client: &'a mut Client<S>,
+    handle: u64,
+    closed: bool,
+}
+
+impl<S: Read + Write> File<'_, S> {
+    /// Truncates or extends the file.
+    pub fn set_len(&mut self, len: u64) -> Result {
+        let args = [self.handle.to_le_bytes(), len.to_le_bytes()].concat();
+        self.client.command(Op::FILE_SET_SIZE, &args)
+    }
+
+    /// Current position.
+    pub fn tell(&mut self) -> Result<u64> {
+        match self
+            .client
+            .request(Op::FILE_TELL, &self.handle.to_le_bytes(), &[])?
+        {
+            (Op::FILE_TELL_RES, body) => u64_at(&body, 0),
+            _ => Err(Error::Protocol("unexpected reply")),
+        }
+    }
+
+    /// Closes the file reporting errors drop ignores.
+    pub fn close(mut self) -> Result {
+        self.closed = true;
+        self.client
+            .command(Op::FILE_CLOSE, &self.handle.to_le_bytes())
+    }
+}
+
+impl<S: Read + Write> Read for File<'_, S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(MAX_READ) as u64;
+        let args = [self.handle.to_le_bytes(), len.to_le_bytes()].concat();
+        match self.client.request(Op::FILE_READ, &args, &[])? {
+            (Op::DATA, data) if data.len() <= buf.len() => {
+                buf[..data.len()].copy_from_slice(&data);
+                Ok(data.len())
+            }
+            _ => Err(Error::Protocol("unexpected reply").into()),
+        }
+    }
+}
+
+impl<S: Read + Write> Write for File<'_, S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let chunk = &buf[..buf.len().min(MAX_WRITE)];
+        self.client
+            .request(Op::FILE_WRITE, &self.handle.to_le_bytes(), chunk)?;
+        Ok(chunk.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<S: Read + Write> Seek for File<'_, S> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (whence, offset) = match pos {
+            SeekFrom::Start(v) => (
+                0u64,
+                i64::try_from(v).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "seek offset over i64::MAX")
+                })?,
+            ),
+            SeekFrom::Current(v) => (1, v),
+            SeekFrom::End(v) => (2, v),
+        };
+        let args = [
+            self.handle.to_le_bytes(),
+            whence.to_le_bytes(),
+            offset.to_le_bytes(),
+        ]
+        .concat();
+        self.client.command(Op::FILE_SEEK, &args)?;
+        Ok(self.tell()?)
+    }
+}
+
+impl<S: Read + Write> Drop for File<'_, S> {
+    fn drop(&mut self) {
+        if !self.closed {
+            let _ = self
+                .client
+                .command(Op::FILE_CLOSE, &self.handle.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::{BTreeMap, HashMap},
+        io::{self, Read, Seek, SeekFrom, Write},
+        net::{TcpListener, TcpStream},
+        path::Path,
+        thread,
+    };
+
+    use crate::afc::{self, Client, Error, FileType, Op, OpenMode, Status};
+
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Node {
+        Dir,
+        File(Vec<u8>),
+    }
+
+    /// In memory AFC server, enough of it to script transfers.
+    #[derive(Default)]
+    struct Mock {
+        nodes: BTreeMap<String, Node>,
+        handles: HashMap<u64, (String, usize, bool)>,
+        next_handle: u64,
+        /// Names `READ_DIR` adds to a directory, no nodes behind them.
+        entries: HashMap<String, Vec<String>>,
+    }
+
+    fn norm(path: &str) -> String {
+        format!("/{}", path.trim_matches('/'))
+    }
+
+    fn parent(path: &str) -> String {
+        norm(&path[..path.rfind('/').unwrap_or(0)])
+    }
+
+    fn is_under(path: &str, dir: &str) -> bool {
+        path != dir && (dir == "/" || path.starts_with(&format!("{dir}/")))
+    }
+
+    fn arg(body: &[u8], from: usize) -> String {
+        let end = body[from..].iter().position(|b| *b == 0).unwrap() + from;
+        norm(std::str::from_utf8(&body[from..end]).unwrap())
+    }
+
+    fn u64_at(body: &[u8], i: usize) -> u64 {
+        afc::u64_at(body, i).unwrap()
+    }
+
+    fn pairs(pairs: &[(&str, String)]) -> Vec<u8> {
+        let mut res = Vec::new();
+        for (k, v) in pairs {
+            res.extend_from_slice(afc::path_arg(k).as_slice());
+            res.extend_from_slice(afc::path_arg(v).as_slice());
+        }
+        res
+    }
+
+    impl Mock {
+        fn new(files: &[(&str, &[u8])]) -> Self {
+            let mut res = Self::default();
+            res.nodes.insert("/".to_string(), Node::Dir);
+            for (path, contents) in files {
+                res.mkdir(&parent(path));
+                res.nodes.insert(norm(path), Node::File(contents.to_vec()));
+            }
+            res
+        }
+
+        fn mkdir(&mut self, path: &str) {
+            if path != "/" {
+                self.mkdir(&parent(path));
+            }
+            self.nodes.entry(path.to_string()).or_insert(Node::Dir);
+        }
+
+        fn file(&mut self, handle: u64) -> Result<(&mut Vec<u8>, &mut usize), Status> {
+            let (path, pos, _) = self.handles.get_mut(&handle).ok_or(Status::INVALID_ARG)?;
+            match self.nodes.get_mut(path.as_str()) {
+                Some(Node::File(contents)) => Ok((contents, pos)),
+                _ => Err(Status::OBJECT_NOT_FOUND),
+            }
+        }
+
+        /// Reply operation, header arguments and data.
+        fn handle(&mut self, op: Op, body: &[u8]) -> Result<(Op, Vec<u8>, Vec<u8>), Status> {
+            let ok = Ok((Op::STATUS, 0u64.to_le_bytes().to_vec(), vec![]));
+            match op {
+                Op::GET_DEV_INFO => {
+                    let info = pairs(&[
+                        ("Model", "iPhone15,2".to_string()),
+                        ("FSTotalBytes", "128000000000".to_string()),
+                        ("FSFreeBytes", "64000000000".to_string()),
+                        ("FSBlockSize", "4096".to_string()),
+                    ]);
+                    Ok((Op::DATA, vec![], info))
+                }
+                Op::READ_DIR => {
+                    let path = arg(body, 0);
+                    match self.nodes.get(&path) {
+                        Some(Node::Dir) => {}
+                        Some(_) => return Err(Status::INVALID_ARG),
+                        None => return Err(Status::OBJECT_NOT_FOUND),
+                    }
+                    let mut names = b".\0..\0".to_vec();
+                    for child in self.nodes.keys().filter(|p| *p != "/" && parent(p) == path) {
+                        names.extend_from_slice(&afc::path_arg(
+                            &child[child.rfind('/').unwrap() + 1..],
+                        ));
+                    }
+                    for name in self.entries.get(&path).into_iter().flatten() {
+                        names.extend_from_slice(&afc::path_arg(name));
+                    }
+                    Ok((Op::DATA, vec![], names))
+                }
+                Op::GET_FILE_INFO => {
+                    let (ifmt, size) = match self.nodes.get(&arg(body, 0)) {
+                        Some(Node::Dir) => ("S_IFDIR", 64),
+                        Some(Node::File(contents)) => ("S_IFREG", contents.len()),
+                        None => return Err(Status::OBJECT_NOT_FOUND),
+                    };
+                    let info = pairs(&[
+                        ("st_size", size.to_string()),
+                        ("st_blocks", size.div_ceil(512).to_string()),
+                        ("st_nlink", "1".to_string()),
+                        ("st_ifmt", ifmt.to_string()),
+                        ("st_mtime", "1700000000000000000".to_string()),
+                        ("st_birthtime", "1600000000000000000".to_string()),
+                    ]);
+                    Ok((Op::DATA, vec![], info))
+                }
+                Op::MAKE_DIR => {
+                    let path = arg(body, 0);
+                    if let Some(Node::File(_)) = self.nodes.get(&path) {
+                        return Err(Status::OBJECT_EXISTS);
+                    }
+                    self.mkdir(&path);
+                    ok
+                }
+                Op::REMOVE_PATH => {
+                    let path = arg(body, 0);
+                    if !self.nodes.contains_key(&path) {
+                        return Err(Status::OBJECT_NOT_FOUND);
+                    }
+                    if self.nodes.keys().any(|p| is_under(p, &path)) {
+                        return Err(Status::DIR_NOT_EMPTY);
+                    }
+                    self.nodes.remove(&path);
+                    ok
+                }
+                Op::REMOVE_PATH_AND_CONTENTS => {
+                    let path = arg(body, 0);
+                    if !self.nodes.contains_key(&path) {
+                        return Err(Status::OBJECT_NOT_FOUND);
+                    }
+                    self.nodes.retain(|p, _| *p != path && !is_under(p, &path));
+                    ok
+                }
+                Op::RENAME_PATH => {
+                    let from = arg(body, 0);
+                    let to = arg(body, body.iter().position(|b| *b == 0).unwrap() + 1);
+                    if !self.nodes.contains_key(&from) {
+                        return Err(Status::OBJECT_NOT_FOUND);
+                    }
+                    let moved: Vec<_> = self
+                        .nodes
+                        .keys()
+                        .filter(|p| **p == from || is_under(p, &from))
+                        .cloned()
+                        .collect();
+                    for path in moved {
+                        let node = self.nodes.remove(&path).unwrap();
+                        self.nodes
+                            .insert(format!("{to}{}", &path[from.len()..]), node);
+                    }
+                    ok
+                }
+                Op::FILE_OPEN => {
+                    let (mode, path) = (OpenMode(u64_at(body, 0)), arg(body, 8));
+                    let append = matches!(mode, OpenMode::APPEND | OpenMode::READ_APPEND);
+                    match (self.nodes.get_mut(&path), mode) {
+                        (Some(Node::Dir), _) => return Err(Status::OBJECT_IS_DIR),
+                        (Some(Node::File(contents)), OpenMode::WRITE | OpenMode::WRITE_READ) => {
+                            contents.clear()
+                        }
+                        (Some(Node::File(_)), _) => {}
+                        (None, OpenMode::READ | OpenMode::READ_WRITE) => {
+                            return Err(Status::OBJECT_NOT_FOUND)
+                        }
+                        (None, _) => {
+                            if self.nodes.get(&parent(&path)) != Some(&Node::Dir) {
+                                return Err(Status::OBJECT_NOT_FOUND);
+                            }
+                            self.nodes.insert(path.clone(), Node::File(vec![]));
+                        }
+                    }
+                    self.next_handle += 1;
+                    self.handles.insert(self.next_handle, (path, 0, append));
+                    Ok((
+                        Op::FILE_OPEN_RES,
+                        self.next_handle.to_le_bytes().to_vec(),
+                        vec![],
+                    ))
+                }
+                Op::FILE_READ => {
+                    let len = u64_at(body, 1) as usize;
+                    let (contents, pos) = self.file(u64_at(body, 0))?;
+                    let start = (*pos).min(contents.len());
+                    let end = (start + len).min(contents.len());
+                    *pos = end;
+                    Ok((Op::DATA, vec![], contents[start..end].to_vec()))
+                }
+                Op::FILE_WRITE => {
+                    let handle = u64_at(body, 0);
+                    let append = self.handles.get(&handle).is_some_and(|h| h.2);
+                    let (contents, pos) = self.file(handle)?;
+                    if append {
+                        *pos = contents.len();
+                    }
+                    let data = &body[8..];
+                    if contents.len() < *pos + data.len() {
+                        contents.resize(*pos + data.len(), 0);
+                    }
+                    contents[*pos..*pos + data.len()].copy_from_slice(data);
+                    *pos += data.len();
+                    ok
+                }
+                Op::FILE_SEEK => {
+                    let (whence, offset) = (u64_at(body, 1), u64_at(body, 2) as i64);
+                    let (contents, pos) = self.file(u64_at(body, 0))?;
+                    let base = match whence {
+                        0 => 0,
+                        1 => *pos as i64,
+                        2 => contents.len() as i64,
+                        _ => return Err(Status::INVALID_ARG),
+                    };
+                    if base + offset < 0 {
+                        return Err(Status::INVALID_ARG);
+                    }
+                    *pos = (base + offset) as usize;
+                    ok
+                }
+                Op::FILE_TELL => {
+                    let (_, pos) = self.file(u64_at(body, 0))?;
+                    Ok((
+                        Op::FILE_TELL_RES,
+                        (*pos as u64).to_le_bytes().to_vec(),
+                        vec![],
+                    ))
+                }
+                Op::FILE_SET_SIZE => {
+                    let len = u64_at(body, 1) as usize;
+                    let (contents, _) = self.file(u64_at(body, 0))?;
+                    contents.resize(len, 0);
+                    ok
+                }
+                Op::FILE_CLOSE => {
+                    self.handles
+                        .remove(&u64_at(body, 0))
+                        .ok_or(Status::INVALID_ARG)?;
+                    ok
+                }
+                _ => Err(Status::UNKNOWN_PACKET_TYPE),
+            }
+        }
+
+        fn run(mut self, mut stream: TcpStream) -> Self {
+            loop {
+                let (num, op, body) = match afc::read_packet(&mut stream) {
+                    Ok(packet) => packet,
+                    Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => return self,
+                    Err(e) => panic!("{e}"),
+                };
+                let (op, header, data) = self
+                    .handle(op, &body)
+                    .unwrap_or_else(|status| (Op::STATUS, status.0.to_le_bytes().to_vec(), vec![]));
+                afc::write_packet(&mut stream, num, op, &header, &data).unwrap();
+            }
+        }
+    }
+
+    fn serve(mock: Mock) -> (Client<TcpStream>, thread::JoinHandle<Mock>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || mock.run(listener.accept().unwrap().0));
+        (Client::new(TcpStream::connect(addr).unwrap()), server)
+    }
+
+    #[test]
+    fn paths() {
+        let (mut afc, server) = serve(Mock::new(&[
+            ("/DCIM/100APPLE/IMG_0001.HEIC", b"heic"),
+            ("/Downloads/a.txt", b"hello"),
+        ]));
+
+        let info = afc.device_info().unwrap();
+        assert_eq!(info.model, "iPhone15,2");
+        assert_eq!(info.block_size, 4096);
+        assert_eq!(info.free_bytes, 64_000_000_000);
+
+        assert_eq!(afc.read_dir("/").unwrap(), ["DCIM", "Downloads"]);
+        assert_eq!(afc.read_dir("/DCIM/100APPLE").unwrap(), ["IMG_0001.HEIC"]);
+
+        let meta = afc.stat("/Downloads/a.txt").unwrap();
+        assert!(meta.is_file());
+        assert_eq!(meta.len, 5);
+        assert_eq!(meta.raw["st_nlink"], "1");
+        assert_eq!(
+            meta.modified.unwrap(),
+            std::time::UNIX_EPOCH + std::time::Duration::from_secs(1_700_000_000)
+        );
+        assert_eq!(afc.stat("/DCIM").unwrap().file_type, FileType::Dir);
+
+        afc.mkdir("/Books/Sample").unwrap();
+        assert!(afc.stat("/Books").unwrap().is_dir());
+        afc.rename("/Downloads/a.txt", "/Books/Sample/b.txt")
+            .unwrap();
+        assert_eq!(afc.read("/Books/Sample/b.txt").unwrap(), b"hello");
+        assert!(!afc.exists("/Downloads/a.txt").unwrap());
+
+        let err = afc.remove("/Books").unwrap_err();
+        assert_eq!(err.status(), Some(Status::DIR_NOT_EMPTY));
+        afc.remove_all("/Books").unwrap();
+        afc.remove("/Downloads").unwrap();
+        assert_eq!(afc.read_dir("/").unwrap(), ["DCIM"]);
+
+        let err = afc.stat("/Downloads").unwrap_err();
+        assert_eq!(err.status(), Some(Status::OBJECT_NOT_FOUND));
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+
+        drop(afc);
+        let mock = server.join().unwrap();
+        assert_eq!(mock.nodes.len(), 4);
+    }
+
+    #[test]
+    fn files() {
+        let (mut afc, server) = serve(Mock::new(&[("/log.txt", b"first\n")]));
+
+        let contents: Vec<u8> = (0..100_000u32).map(|i| (i % 251) as u8).collect();
+        afc.write("/big.bin", &contents).unwrap();
+        assert_eq!(afc.stat("/big.bin").unwrap().len, 100_000);
+        assert_eq!(afc.read("/big.bin").unwrap(), contents);
+
+        let mut file = afc.open("/big.bin", OpenMode::READ_WRITE).unwrap();
+        assert_eq!(file.seek(SeekFrom::Start(1000)).unwrap(), 1000);
+        let mut buf = [0u8; 4];
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(buf, contents[1000..1004]);
+        assert_eq!(file.seek(SeekFrom::Current(-2)).unwrap(), 1002);
+        file.write_all(b"xy").unwrap();
+        file.seek(SeekFrom::Start(1000)).unwrap();
+        file.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf[2..], b"xy");
+        assert_eq!(file.seek(SeekFrom::End(-10)).unwrap(), 99_990);
+        file.set_len(10).unwrap();
+        assert_eq!(file.seek(SeekFrom::End(0)).unwrap(), 10);
+        assert_eq!(file.read(&mut buf).unwrap(), 0);
+        assert!(file.seek(SeekFrom::Current(-20)).is_err());
+        let err = file.seek(SeekFrom::Start(u64::MAX)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(file.stream_position().unwrap(), 10);
+        drop(file);
+
+        let mut file = afc.open("/log.txt", OpenMode::APPEND).unwrap();
+        file.write_all(b"second\n").unwrap();
+        file.close().unwrap();
+        assert_eq!(afc.read("/log.txt").unwrap(), b"first\nsecond\n");
+
+        let err = afc.open("/missing/x", OpenMode::WRITE).err().unwrap();
+        assert_eq!(err.status(), Some(Status::OBJECT_NOT_FOUND));
+
+        drop(afc);
+        let mock = server.join().unwrap();
+        assert!(mock.handles.is_empty());
+        assert_eq!(mock.nodes["/big.bin"], Node::File(contents[..10].to_vec()));
+    }
+
+    fn read_tree(dir: &Path, prefix: &str, res: &mut Vec<(String, Vec<u8>)>) {
+        let mut entries: Vec<_> = std::fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        entries.sort();
+        for path in entries {
+            let name = format!("{prefix}/{}", path.file_name().unwrap().to_str().unwrap());
+            if path.is_dir() {
+                read_tree(&path, &name, res);
+            } else {
+                res.push((name, std::fs::read(&path).unwrap()));
+            }
+        }
+    }
+
+    #[test]
+    fn transfer() {
+        let big = vec![7u8; 200_000];
+        let (mut afc, server) = serve(Mock::new(&[
+            (
+                "/Retired/JetsamEvent-2024-01-01.ips",
+                b"{\"bug_type\":\"298\"}",
+            ),
+            ("/Retired/Panics/panic-full.ips", &big),
+            ("/stacks.ips", b"stacks"),
+        ]));
+
+        let dir = std::env::temp_dir().join(format!("cidre-afc-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut reports = Vec::new();
+        afc.download("/", &dir.join("logs"), &mut |p| {
+            reports.push((p.remote.to_string(), p.file_done, p.file_len, p.total_done))
+        })
+        .unwrap();
+        let mut tree = Vec::new();
+        read_tree(&dir.join("logs"), "", &mut tree);
+        assert_eq!(
+            tree,
+            [
+                (
+                    "/Retired/JetsamEvent-2024-01-01.ips".to_string(),
+                    b"{\"bug_type\":\"298\"}".to_vec()
+                ),
+                ("/Retired/Panics/panic-full.ips".to_string(), big.clone()),
+                ("/stacks.ips".to_string(), b"stacks".to_vec()),
+            ]
+        );
+        let total = 18 + 200_000 + 6;
+        assert_eq!(
+            reports.first().unwrap(),
+            &("/Retired/JetsamEvent-2024-01-01.ips".to_string(), 0, 18, 0)
+        );
+        assert_eq!(
+            reports.last().unwrap(),
+            &("/stacks.ips".to_string(), 6, 6, total)
+        );
+        let panic: Vec<_> = reports
+            .iter()
+            .filter(|r| r.0.ends_with("panic-full.ips"))
+            .collect();
+        assert_eq!(panic.len(), 1 + 200_000usize.div_ceil(afc::MAX_READ));
+        assert!(panic.windows(2).all(|w| w[0].1 < w[1].1 && w[0].3 < w[1].3));
+
+        let mut reports = Vec::new();
+        afc.upload(&dir.join("logs/Retired"), "/Documents/Copy", &mut |p| {
+            reports.push((p.file_done, p.total_done))
+        })
+        .unwrap();
+        assert_eq!(reports.last().unwrap(), &(200_000, 200_018));
+        assert_eq!(
+            afc.read_dir("/Documents/Copy").unwrap(),
+            ["JetsamEvent-2024-01-01.ips", "Panics"]
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        drop(afc);
+        let mock = server.join().unwrap();
+        assert_eq!(
+            mock.nodes["/Documents/Copy/Panics/panic-full.ips"],
+            Node::File(big)
+        );
+        assert!(mock.handles.is_empty());
+    }
+
+    #[test]
+    fn download_names() {
+        let dir = std::env::temp_dir().join(format!("cidre-afc-names-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let local = dir.join("a/b");
+        let abs = dir.join("abs");
+        for name in [
+            "../escape",
+            "../../escape",
+            "sub/../../escape",
+            "sub/x",
+            abs.to_str().unwrap(),
+        ] {
+            let mut mock = Mock::new(&[("/Books/a.txt", b"a")]);
+            mock.entries
+                .insert("/Books".to_string(), vec![name.to_string()]);
+            let (mut afc, server) = serve(mock);
+            let err = afc.download("/", &local, &mut |_| {}).unwrap_err();
+            assert!(matches!(err, Error::Protocol(_)), "{name}: {err}");
+            drop(afc);
+            server.join().unwrap();
+        }
+        let mut tree = Vec::new();
+        read_tree(&dir, "", &mut tree);
+        assert_eq!(tree, [("/a/b/Books/a.txt".to_string(), b"a".to_vec())]);
+        assert!(!abs.exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(afc::plain_name("a.txt"));
+        assert!(afc::plain_name("..."));
+        for name in ["", ".", "..", "a/b", "/a", "a\0b"] {
+            assert!(!afc::plain_name(name), "{name}");
+        }
+    }
+
+    #[test]
+    fn framing() {
+        let mut buf = Vec::new();
+        afc::write_packet(&mut buf, 3, Op::FILE_WRITE, &9u64.to_le_bytes(), b"data").unwrap();
+        assert_eq!(&buf[..8], b"CFA6LPAA");
+        assert_eq!(u64::from_le_bytes(buf[8..16].try_into().unwrap()), 52);
+        assert_eq!(u64::from_le_bytes(buf[16..24].try_into().unwrap()), 48);
+        let (num, op, body) = afc::read_packet(&mut buf.as_slice()).unwrap();
+        assert_eq!((num, op), (3, Op::FILE_WRITE));
+        assert_eq!(body, [&9u64.to_le_bytes()[..], b"data"].concat());
+
+        let mut bad = buf.clone();
+        bad[0] = b'X';
+        assert!(matches!(
+            afc::read_packet(&mut bad.as_slice()),
+            Err(Error::Protocol(_))
+        ));
+        let mut bad = buf.clone();
+        bad[16..24].copy_from_slice(&60u64.to_le_bytes());
+        assert!(matches!(
+            afc::read_packet(&mut bad.as_slice()),
+            Err(Error::Protocol(_))
+        ));
+
+        // a reply with a stale packet number
+        let mut reply = Vec::new();
+        afc::write_packet(&mut reply, 5, Op::STATUS, &0u64.to_le_bytes(), &[]).unwrap();
+        let stream = Duplex(io::Cursor::new(reply), Vec::new());
+        let mut afc = Client::new(stream);
+        assert!(matches!(afc.mkdir("/a"), Err(Error::Protocol(_))));
+    }
+
+    struct Duplex(io::Cursor<Vec<u8>>, Vec<u8>);
+
+    impl Read for Duplex {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    impl Write for Duplex {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.1.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -415,6 +415,16 @@ impl FileId {
         self.prop(PropId::ESTIMATED_DURATION)
     }
 
+    #[inline]
+    pub fn packet_table_info(&self) -> os::Result<audio::container::PacketTableInfo> {
+        self.prop(PropId::PACKET_TABLE_INFO)
+    }
+
+    #[inline]
+    pub fn set_packet_table_info(&mut self, val: &audio::container::PacketTableInfo) -> os::Result {
+        self.set_prop(PropId::PACKET_TABLE_INFO, val)
+    }
+
     #[inline]
     pub fn info_dictionary(&self) -> os::Result<arc::R<cf::Dictionary>> {
         self.prop(PropId::INFO_DICTIONARY)
@@ -581,12 +591,14 @@ pub mod err {
     pub const DOES_NOT_ALLOW64_BIT_DATA_SIZE: Error = Error::from_be_bytes(*b"off?");
 
     /// 0x70636B3F, 1885563711
-    /// The file offset was too large for the file type. The AIFF and WAVE file format types have 32-bit file size limits.
+    /// A packet offset was past the end of the file, or not at the end of the file when a VBR format was written,
+    /// or a corrupt packet size was read when the packet table was built.
     #[doc(alias = "kAudioFileInvalidPacketOffsetError")]
     pub const INVALID_PACKET_OFFSET: Error = Error::from_be_bytes(*b"pck?");
 
     /// 0x6465703F, 1684369471
-    /// The file offset was too large for the file type. The AIFF and WAVE file format types have 32-bit file size limits.
+    /// Either the packet dependency info that's necessary for the audio format has not been provided,
+    /// or the provided packet dependency info indicates dependency on a packet that's unavailable.
     #[doc(alias = "kAudioFileInvalidPacketDependencyError")]
     pub const INVALID_PACKET_DEPENDENCY: Error = Error::from_be_bytes(*b"dep?");
 
@@ -596,7 +608,7 @@ pub mod err {
     pub const INVALID_FILE: Error = Error::from_be_bytes(*b"dta?");
 
     /// 0x6F703F3F
-    /// The file is malformed, or otherwise not a valid instance of an audio file of its type.
+    /// The operation cannot be performed.
     #[doc(alias = "kAudioFileOperationNotSupportedError")]
     pub const OPERATION_NOT_SUPPORTED: Error = Error::from_be_bytes(*b"op??");
 
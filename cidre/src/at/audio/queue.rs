@@ -23,30 +23,80 @@ pub struct QueueTimeline(NonNull<c_void>);
 pub mod err {
     use crate::os::Error;
 
+    /// The specified buffer does not belong to the audio queue.
+    #[doc(alias = "kAudioQueueErr_InvalidBuffer")]
     pub const INVALID_BUFFER: Error = Error::new_unchecked(-66687);
+    /// The buffer is empty (that is, the mAudioDataByteSize field = 0).
+    #[doc(alias = "kAudioQueueErr_BufferEmpty")]
     pub const BUFFER_EMPTY: Error = Error::new_unchecked(-66686);
+    /// The function cannot act on the audio queue because it is being asynchronously disposed of.
+    #[doc(alias = "kAudioQueueErr_DisposalPending")]
     pub const DISPOSAL_PENDING: Error = Error::new_unchecked(-66685);
+    /// The specified property ID is invalid.
+    #[doc(alias = "kAudioQueueErr_InvalidProperty")]
     pub const INVALID_PROPERTY: Error = Error::new_unchecked(-66684);
+    /// The size of the specified property is invalid.
+    #[doc(alias = "kAudioQueueErr_InvalidPropertySize")]
     pub const INVALID_PROPERTY_SIZE: Error = Error::new_unchecked(-66683);
+    /// The specified parameter ID is invalid.
+    #[doc(alias = "kAudioQueueErr_InvalidParameter")]
     pub const INVALID_PARAMETER: Error = Error::new_unchecked(-66682);
+    /// The audio queue has encountered a problem and cannot start.
+    #[doc(alias = "kAudioQueueErr_CannotStart")]
     pub const CANNOT_START: Error = Error::new_unchecked(-66681);
+    /// The device assigned to the queue could not be located, or is not properly configured.
+    #[doc(alias = "kAudioQueueErr_InvalidDevice")]
     pub const INVALID_DEVICE: Error = Error::new_unchecked(-66680);
+    /// The buffer cannot be disposed of when it is enqueued.
+    #[doc(alias = "kAudioQueueErr_BufferInQueue")]
     pub const BUFFER_IN_QUEUE: Error = Error::new_unchecked(-66679);
+    /// The queue is running but the function can only operate on the queue when it is stopped, or vice versa.
+    #[doc(alias = "kAudioQueueErr_InvalidRunState")]
     pub const INVALID_RUN_STATE: Error = Error::new_unchecked(-66678);
+    /// The queue is an input queue but the function can only operate on an output queue, or vice versa.
+    #[doc(alias = "kAudioQueueErr_InvalidQueueType")]
     pub const INVALID_QUEUE_TYPE: Error = Error::new_unchecked(-66677);
+    /// You do not have the required permissions to call the function.
+    #[doc(alias = "kAudioQueueErr_Permissions")]
     pub const PERMISSIONS: Error = Error::new_unchecked(-66676);
+    /// The specified property value is invalid.
+    #[doc(alias = "kAudioQueueErr_InvalidPropertyValue")]
     pub const INVALID_PROPERTY_VALUE: Error = Error::new_unchecked(-66675);
+    /// During Prime, the queue's AudioConverter failed to convert the requested number of sample frames.
+    #[doc(alias = "kAudioQueueErr_PrimeTimedOut")]
     pub const PRIME_TIMED_OUT: Error = Error::new_unchecked(-66674);
+    /// The required audio codec was not found.
+    #[doc(alias = "kAudioQueueErr_CodecNotFound")]
     pub const CODEC_NOT_FOUND: Error = Error::new_unchecked(-66673);
+    /// Access to the required codec is not permitted.
+    #[doc(alias = "kAudioQueueErr_InvalidCodecAccess")]
     pub const INVALID_CODEC_ACCESS: Error = Error::new_unchecked(-66672);
+    /// The audio server has exited, causing the audio queue to become invalid.
+    #[doc(alias = "kAudioQueueErr_QueueInvalidated")]
     pub const QUEUE_INVALIDATED: Error = Error::new_unchecked(-66671);
+    /// There can only be one processing tap per audio queue.
+    #[doc(alias = "kAudioQueueErr_TooManyTaps")]
     pub const TOO_MANY_TAPS: Error = Error::new_unchecked(-66670);
+    /// GetSourceAudio can only be called from the tap's callback.
+    #[doc(alias = "kAudioQueueErr_InvalidTapContext")]
     pub const INVALID_TAP_CONTEXT: Error = Error::new_unchecked(-66669);
+    /// During recording, data was lost because there was no enqueued buffer into which to store it.
+    #[doc(alias = "kAudioQueueErr_RecordUnderrun")]
     pub const RECORD_UNDERRUN: Error = Error::new_unchecked(-66668);
+    /// The tap type is not supported for this queue.
+    #[doc(alias = "kAudioQueueErr_InvalidTapType")]
     pub const INVALID_TAP_TYPE: Error = Error::new_unchecked(-66667);
+    /// A buffer was enqueued a second time before it was returned to the client.
+    #[doc(alias = "kAudioQueueErr_BufferEnqueuedTwice")]
     pub const BUFFER_ENQUEUED_TWICE: Error = Error::new_unchecked(-66666);
+    /// The audio queue cannot start because of an audio session interruption.
+    #[doc(alias = "kAudioQueueErr_CannotStartYet")]
     pub const CANNOT_START_YET: Error = Error::new_unchecked(-66665);
+    /// A buffer was enqueued during a reset, stop, or dispose operation.
+    #[doc(alias = "kAudioQueueErr_EnqueueDuringReset")]
     pub const ENQUEUE_DURING_RESET: Error = Error::new_unchecked(-66632);
+    /// The operation requires the queue to be in offline mode but it is not, or vice versa.
+    #[doc(alias = "kAudioQueueErr_InvalidOfflineMode")]
     pub const INVALID_OFFLINE_MODE: Error = Error::new_unchecked(-66626);
 }
 
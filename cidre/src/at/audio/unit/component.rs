@@ -619,7 +619,7 @@ pub mod err {
     /// AudioComponentInstantiate or AudioComponentInstanceNew when rebuilding
     /// against the macOS 11 or later SDK.
     #[doc(alias = "kAudioUnitErr_ComponentManagerNotSupported")]
-    pub const COMPONENT_MANAGER_NOT_SUPPORTED: Error = Error::new_unchecked(-66749);
+    pub const COMPONENT_MANAGER_NOT_SUPPORTED: Error = Error::new_unchecked(-66740);
 
     /// On some platforms, this error is returned when a client attempts to initialize
     /// a voice processor instance while another is initialized
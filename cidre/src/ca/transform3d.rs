@@ -57,9 +57,27 @@ impl Transform3d {
         unsafe { CATransform3DMakeRotation(angle, x, y, z) }
     }
 
+    /// Returns a transform with the same effect as affine transform 'm'.
     #[inline]
     pub fn from_cg_affine_transform(m: cg::AffineTransform) -> Self {
-        unsafe { CATransform3DMakeAffineTransform(m) }
+        Self {
+            m11: m.a,
+            m12: m.b,
+            m13: 0.0,
+            m14: 0.0,
+            m21: m.c,
+            m22: m.d,
+            m23: 0.0,
+            m24: 0.0,
+            m31: 0.0,
+            m32: 0.0,
+            m33: 1.0,
+            m34: 0.0,
+            m41: m.tx,
+            m42: m.ty,
+            m43: 0.0,
+            m44: 1.0,
+        }
     }
 
     /// Returns true if 'self' is the identity transform.
@@ -88,14 +106,27 @@ impl Transform3d {
         unsafe { CATransform3DConcat(*self, *other) }
     }
 
+    /// Returns true if 'self' can be exactly represented by an affine transform.
     #[inline]
     pub fn is_affine(&self) -> bool {
-        unsafe { CATransform3DIsAffine(*self) }
+        self.m13 == 0.0
+            && self.m14 == 0.0
+            && self.m23 == 0.0
+            && self.m24 == 0.0
+            && self.m31 == 0.0
+            && self.m32 == 0.0
+            && self.m33 == 1.0
+            && self.m34 == 0.0
+            && self.m43 == 0.0
+            && self.m44 == 1.0
     }
 
+    /// Returns the affine transform represented by 'self'. If 'self' can not
+    /// be exactly represented as an affine transform the returned value is
+    /// undefined.
     #[inline]
     pub fn to_affine_transform(&self) -> cg::AffineTransform {
-        unsafe { CATransform3DGetAffineTransform(*self) }
+        cg::AffineTransform::new(self.m11, self.m12, self.m21, self.m22, self.m41, self.m42)
     }
 }
 
@@ -110,7 +141,4 @@ extern "C" {
     fn CATransform3DScale(t: Transform3d, sx: f64, sy: f64, sz: f64) -> Transform3d;
     fn CATransform3DRotate(t: Transform3d, angle: f64, x: f64, y: f64, z: f64) -> Transform3d;
     fn CATransform3DConcat(a: Transform3d, b: Transform3d) -> Transform3d;
-    fn CATransform3DMakeAffineTransform(m: cg::AffineTransform) -> Transform3d;
-    fn CATransform3DIsAffine(t: Transform3d) -> bool;
-    fn CATransform3DGetAffineTransform(t: Transform3d) -> cg::AffineTransform;
 }
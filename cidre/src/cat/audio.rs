@@ -1,6 +1,25 @@
 mod base_types;
 pub use base_types::*;
 
+mod stream_basic_desc;
+pub use stream_basic_desc::Builder as StreamBasicDescBuilder;
+pub use stream_basic_desc::Error as StreamBasicDescError;
+
+mod channel_layout;
+pub use channel_layout::channel_map;
+pub use channel_layout::mix_matrix;
+pub use channel_layout::ChannelLayoutN;
+
+pub mod container;
+pub use container::Error as ContainerError;
+
+mod pcm_converter;
+pub use pcm_converter::Error as PcmConverterError;
+pub use pcm_converter::PcmConverter;
+
+mod smpte_time;
+pub use smpte_time::ParseError as SMPTETimeParseError;
+
 mod session_types;
 pub use session_types::ErrorCode as SessionErrorCode;
 pub use session_types::SessionId;
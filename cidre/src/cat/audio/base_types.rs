@@ -3,9 +3,9 @@ use std::{
     ptr::{slice_from_raw_parts, slice_from_raw_parts_mut},
 };
 
-use crate::{define_opts, four_cc_to_str, os};
+use crate::{define_opts, os, FourCc};
 
-#[cfg(feature = "ns")]
+#[cfg(all(target_vendor = "apple", feature = "ns"))]
 use crate::ns;
 
 /// These are the error codes returned from the APIs found through Core Audio related frameworks.
@@ -105,6 +105,15 @@ impl BufListN {
         bufs
     }
 
+    pub fn buffers_mut(&mut self) -> &mut [Buf] {
+        let (_pre, bufs, _post) = unsafe { self.inner[4..].align_to_mut() };
+        bufs
+    }
+
+    pub(crate) fn set_number_buffers(&mut self, val: usize) {
+        self.inner[..4].copy_from_slice(&(val as u32).to_le_bytes());
+    }
+
     pub fn as_mut_ptr(&mut self) -> *mut u8 {
         self.inner.as_mut_ptr()
     }
@@ -205,7 +214,7 @@ impl<const N: usize> BufList<N> {
         unsafe { &mut *slice_from_raw_parts_mut(self.buffers.as_mut_ptr(), N) }
     }
 
-    pub fn cursor(&mut self) -> BufListCursor<N> {
+    pub fn cursor(&mut self) -> BufListCursor<'_, N> {
         BufListCursor::new(self)
     }
 }
@@ -218,11 +227,21 @@ pub struct Format(pub u32);
 
 impl std::fmt::Debug for Format {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut be = self.0.to_be_bytes();
-        f.debug_struct("Format")
-            .field("raw", &self.0)
-            .field("fcc", &four_cc_to_str(&mut be))
-            .finish()
+        f.debug_tuple("Format").field(&FourCc(self.0)).finish()
+    }
+}
+
+impl From<FourCc> for Format {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<Format> for FourCc {
+    #[inline]
+    fn from(value: Format) -> Self {
+        Self(value.0)
     }
 }
 
@@ -408,7 +427,7 @@ impl Format {
     #[doc(alias = "kAudioFormatAPAC")]
     pub const APAC: Self = Self(u32::from_be_bytes(*b"apac"));
 
-    #[cfg(feature = "ns")]
+    #[cfg(all(target_vendor = "apple", feature = "ns"))]
     pub fn to_ns_number(self) -> &'static ns::Number {
         ns::Number::tagged_i32(self.0 as _)
     }
@@ -416,8 +435,8 @@ impl Format {
 
 define_opts!(pub FormatFlags(u32));
 
-/// ios app audio - IS_BIG_ENDIAN | IS_SIGNED_INTEGER | IS_PACKED
-/// mic - IS_SIGNED_INTEGER | IS_PACKED
+// ios app audio - IS_BIG_ENDIAN | IS_SIGNED_INTEGER | IS_PACKED
+// mic - IS_SIGNED_INTEGER | IS_PACKED
 
 /// These are the standard AudioFormatFlags for use in the mFormatFlags field of the
 /// AudioStreamBasicDescription structure.
@@ -704,7 +723,7 @@ pub struct StreamPacketDesc {
 #[doc(alias = "kAudioStreamAnyRate")]
 pub const STREAM_ANY_RATE: f64 = 0.0;
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
 #[repr(transparent)]
 pub struct SMPTETimeType(pub u32);
 
@@ -746,7 +765,7 @@ impl SMPTETimeType {
     pub const _23_98: Self = Self(11);
 }
 
-#[derive(Debug, Copy, Clone, Default)]
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct SMPTETimeFlags(pub u32);
 
@@ -756,7 +775,7 @@ impl SMPTETimeFlags {
     pub const RUNNING: Self = Self(1u32 << 1);
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
 pub struct SMPTETime {
     pub subframes: i16,
@@ -1052,7 +1071,7 @@ impl ChannelLabel {
 /// These constants are for use in the mChannelBitmap field of an
 /// AudioChannelLayout structure
 #[doc(alias = "AudioChannelBitmap")]
-#[derive(Debug, PartialEq, Eq, Default)]
+#[derive(Debug, PartialEq, Eq, Default, Copy, Clone)]
 #[repr(transparent)]
 pub struct ChannelBitmap(pub u32);
 
@@ -1218,7 +1237,7 @@ impl ChannelLayoutTag {
     /// top left, top right, top rear left, top rear right
     pub const CUBE: Self = Self((112u32 << 16) | 8);
 
-    ///  MPEG defined layouts
+    //  MPEG defined layouts
 
     /// C
     pub const MPEG_1_0: Self = Self::MONO;
@@ -1364,30 +1383,30 @@ impl ChannelLayoutTag {
     /// L R C LFE Ls Rs Lc Rc
     pub const AUDIO_UNIT_7_1_FRONT: Self = Self::MPEG_7_1_A;
 
-    pub const AAC_3_0: Self = Self::MPEG_3_0_B;
     /// C L R
-    pub const AAC_QUADRAPHONIC: Self = Self::QUADRAPHONIC;
+    pub const AAC_3_0: Self = Self::MPEG_3_0_B;
     /// L R Ls Rs
-    pub const AAC_4_0: Self = Self::MPEG_4_0_B;
+    pub const AAC_QUADRAPHONIC: Self = Self::QUADRAPHONIC;
     /// C L R Cs
-    pub const AAC_5_0: Self = Self::MPEG_5_0_D;
+    pub const AAC_4_0: Self = Self::MPEG_4_0_B;
     /// C L R Ls Rs
-    pub const AAC_5_1: Self = Self::MPEG_5_1_D;
+    pub const AAC_5_0: Self = Self::MPEG_5_0_D;
     /// C L R Ls Rs Lfe
-    pub const AAC_6_0: Self = Self((141u32 << 16) | 6);
+    pub const AAC_5_1: Self = Self::MPEG_5_1_D;
     /// C L R Ls Rs Cs
-    pub const AAC_6_1: Self = Self((142u32 << 16) | 7);
+    pub const AAC_6_0: Self = Self((141u32 << 16) | 6);
     /// C L R Ls Rs Cs Lfe
-    pub const AAC_7_0: Self = Self((143u32 << 16) | 7);
+    pub const AAC_6_1: Self = Self((142u32 << 16) | 7);
     /// C L R Ls Rs Rls Rrs
-    pub const AAC_7_1: Self = Self::MPEG_7_1_B;
+    pub const AAC_7_0: Self = Self((143u32 << 16) | 7);
     /// C Lc Rc L R Ls Rs Lfe
-    pub const AAC_7_1_B: Self = Self((183u32 << 16) | 8);
+    pub const AAC_7_1: Self = Self::MPEG_7_1_B;
     /// C L R Ls Rs Rls Rrs LFE
-    pub const AAC_7_1_C: Self = Self((184u32 << 16) | 8);
+    pub const AAC_7_1_B: Self = Self((183u32 << 16) | 8);
     /// C L R Ls Rs LFE Vhl Vhr
-    pub const AAC_OCTAGONAL: Self = Self((144u32 << 16) | 8);
+    pub const AAC_7_1_C: Self = Self((184u32 << 16) | 8);
     /// C L R Ls Rs Rls Rrs Cs
+    pub const AAC_OCTAGONAL: Self = Self((144u32 << 16) | 8);
 
     /// L R C Vhc Lsd Rsd Ls Rs Vhl Vhr Lw Rw Csd Cs LFE1 LFE2
     pub const TMH_10_2_STD: Self = Self((145u32 << 16) | 16);
@@ -1597,7 +1616,7 @@ impl ChannelLayoutTag {
 
 /// This structure describes a single channel.
 #[doc(alias = "AudioChannelDescription")]
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
 #[repr(C)]
 pub struct ChannelDesc {
     /// The AudioChannelLabel that describes the channel.
@@ -1664,11 +1683,11 @@ impl Mpeg4Object {
 
 #[cfg(test)]
 mod tests {
-    use crate::at;
+    use crate::cat;
 
     #[test]
     fn basics() {
-        let asbd = at::audio::StreamBasicDesc::common_f32(44100.0, 2, false);
+        let asbd = cat::audio::StreamBasicDesc::common_f32(44100.0, 2, false);
         assert_eq!(asbd.interleaved_channels_num(), 1);
         assert!(!asbd.is_interleaved());
         assert!(asbd.is_common_f32());
@@ -0,0 +1,702 @@
+//! Table driven channel layout engine.
+//!
+//! Expands [`ChannelLayoutTag`]s into ordered [`ChannelLabel`]s, converts
+//! between tags, bitmaps and description lists, and builds reorder maps and
+//! downmix matrices without going through `AudioFormatGetProperty`.
+
+use super::{
+    ChannelBitmap, ChannelDesc, ChannelFlags, ChannelLabel, ChannelLayout, ChannelLayoutTag,
+};
+
+const L: ChannelLabel = ChannelLabel::LEFT;
+const R: ChannelLabel = ChannelLabel::RIGHT;
+const C: ChannelLabel = ChannelLabel::CENTER;
+const LFE: ChannelLabel = ChannelLabel::LFE_SCREEN;
+const LS: ChannelLabel = ChannelLabel::LEFT_SURROUND;
+const RS: ChannelLabel = ChannelLabel::RIGHT_SURROUND;
+const LC: ChannelLabel = ChannelLabel::LEFT_CENTER;
+const RC: ChannelLabel = ChannelLabel::RIGHT_CENTER;
+const CS: ChannelLabel = ChannelLabel::CENTER_SURROUND;
+const LSD: ChannelLabel = ChannelLabel::LEFT_SURROUND_DIRECT;
+const RSD: ChannelLabel = ChannelLabel::RIGHT_SURROUND_DIRECT;
+const TS: ChannelLabel = ChannelLabel::TOP_CENTER_SURROUND;
+const VHL: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_LEFT;
+const VHC: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_CENTER;
+const VHR: ChannelLabel = ChannelLabel::VERTICAL_HEIGHT_RIGHT;
+const TBL: ChannelLabel = ChannelLabel::TOP_BACK_LEFT;
+const TBR: ChannelLabel = ChannelLabel::TOP_BACK_RIGHT;
+const RLS: ChannelLabel = ChannelLabel::REAR_SURROUND_LEFT;
+const RRS: ChannelLabel = ChannelLabel::REAR_SURROUND_RIGHT;
+const LW: ChannelLabel = ChannelLabel::LEFT_WIDE;
+const RW: ChannelLabel = ChannelLabel::RIGHT_WIDE;
+const LFE2: ChannelLabel = ChannelLabel::LFE2;
+const LT: ChannelLabel = ChannelLabel::LEFT_TOTAL;
+const RT: ChannelLabel = ChannelLabel::RIGHT_TOTAL;
+const HI: ChannelLabel = ChannelLabel::HEARING_IMPAIRED;
+const VI: ChannelLabel = ChannelLabel::NARRATION;
+const CSD: ChannelLabel = ChannelLabel::CENTER_SURROUND_DIRECT;
+const LTM: ChannelLabel = ChannelLabel::LEFT_TOP_MIDDLE;
+const RTM: ChannelLabel = ChannelLabel::RIGHT_TOP_MIDDLE;
+const LTR: ChannelLabel = ChannelLabel::LEFT_TOP_REAR;
+const CTR: ChannelLabel = ChannelLabel::CENTER_TOP_REAR;
+const RTR: ChannelLabel = ChannelLabel::RIGHT_TOP_REAR;
+const TBC: ChannelLabel = ChannelLabel::TOP_BACK_CENTER;
+
+const H: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Fixed layouts in the order of `CoreAudioBaseTypes.h`. Aliased tags
+/// (`ITU_3_2`, `DVD_9`, ...) share the values listed here.
+#[rustfmt::skip]
+const LAYOUTS: &[(ChannelLayoutTag, &[ChannelLabel])] = {
+    use ChannelLabel as Label;
+    use ChannelLayoutTag as T;
+    &[
+        (T::MONO, &[Label::MONO]),
+        (T::STEREO, &[L, R]),
+        (T::STEREO_HEADPHONES, &[Label::HEADPHONES_LEFT, Label::HEADPHONES_RIGHT]),
+        (T::MATRIX_STEREO, &[LT, RT]),
+        (T::MID_SIDE, &[Label::MS_MID, Label::MS_SIDE]),
+        (T::XY, &[Label::XY_X, Label::XY_Y]),
+        (T::BINAURAL, &[Label::BINAURAL_LEFT, Label::BINAURAL_RIGHT]),
+        (T::AMBISONIC_B_FORMAT, &[Label::AMBISONIC_W, Label::AMBISONIC_X, Label::AMBISONIC_Y, Label::AMBISONIC_Z]),
+        (T::QUADRAPHONIC, &[L, R, LS, RS]),
+        (T::PENTAGONAL, &[L, R, LS, RS, C]),
+        (T::HEXAGONAL, &[L, R, LS, RS, C, CS]),
+        (T::OCTAGONAL, &[L, R, LS, RS, C, CS, LW, RW]),
+        (T::CUBE, &[L, R, LS, RS, VHL, VHR, TBL, TBR]),
+        (T::MPEG_3_0_A, &[L, R, C]),
+        (T::MPEG_3_0_B, &[C, L, R]),
+        (T::MPEG_4_0_A, &[L, R, C, CS]),
+        (T::MPEG_4_0_B, &[C, L, R, CS]),
+        (T::MPEG_5_0_A, &[L, R, C, LS, RS]),
+        (T::MPEG_5_0_B, &[L, R, LS, RS, C]),
+        (T::MPEG_5_0_C, &[L, C, R, LS, RS]),
+        (T::MPEG_5_0_D, &[C, L, R, LS, RS]),
+        (T::MPEG_5_1_A, &[L, R, C, LFE, LS, RS]),
+        (T::MPEG_5_1_B, &[L, R, LS, RS, C, LFE]),
+        (T::MPEG_5_1_C, &[L, C, R, LS, RS, LFE]),
+        (T::MPEG_5_1_D, &[C, L, R, LS, RS, LFE]),
+        (T::MPEG_6_1_A, &[L, R, C, LFE, LS, RS, CS]),
+        (T::MPEG_7_1_A, &[L, R, C, LFE, LS, RS, LC, RC]),
+        (T::MPEG_7_1_B, &[C, LC, RC, L, R, LS, RS, LFE]),
+        (T::MPEG_7_1_C, &[L, R, C, LFE, LS, RS, RLS, RRS]),
+        (T::EMAGIC_DEFAULT_7_1, &[L, R, LS, RS, C, LFE, LC, RC]),
+        (T::SMPTE_DTV, &[L, R, C, LFE, LS, RS, LT, RT]),
+        (T::ITU_2_1, &[L, R, CS]),
+        (T::ITU_2_2, &[L, R, LS, RS]),
+        (T::DVD_4, &[L, R, LFE]),
+        (T::DVD_5, &[L, R, LFE, CS]),
+        (T::DVD_6, &[L, R, LFE, LS, RS]),
+        (T::DVD_10, &[L, R, C, LFE]),
+        (T::DVD_11, &[L, R, C, LFE, CS]),
+        (T::DVD_18, &[L, R, LS, RS, LFE]),
+        (T::AUDIO_UNIT_6_0, &[L, R, LS, RS, C, CS]),
+        (T::AUDIO_UNIT_7_0, &[L, R, LS, RS, C, RLS, RRS]),
+        (T::AUDIO_UNIT_7_0_FRONT, &[L, R, LS, RS, C, LC, RC]),
+        (T::AAC_6_0, &[C, L, R, LS, RS, CS]),
+        (T::AAC_6_1, &[C, L, R, LS, RS, CS, LFE]),
+        (T::AAC_7_0, &[C, L, R, LS, RS, RLS, RRS]),
+        (T::AAC_7_1_B, &[C, L, R, LS, RS, RLS, RRS, LFE]),
+        (T::AAC_7_1_C, &[C, L, R, LS, RS, LFE, VHL, VHR]),
+        (T::AAC_OCTAGONAL, &[C, L, R, LS, RS, RLS, RRS, CS]),
+        (T::TMH_10_2_STD, &[L, R, C, VHC, LSD, RSD, LS, RS, VHL, VHR, LW, RW, CSD, CS, LFE, LFE2]),
+        (T::TMH_10_2_FULL, &[L, R, C, VHC, LSD, RSD, LS, RS, VHL, VHR, LW, RW, CSD, CS, LFE, LFE2, LC, RC, HI, VI, Label::HAPTIC]),
+        (T::AC3_1_0_1, &[C, LFE]),
+        (T::AC3_3_0, &[L, C, R]),
+        (T::AC3_3_1, &[L, C, R, CS]),
+        (T::AC3_3_0_1, &[L, C, R, LFE]),
+        (T::AC3_2_1_1, &[L, R, CS, LFE]),
+        (T::AC3_3_1_1, &[L, C, R, CS, LFE]),
+        (T::EAC_6_0_A, &[L, C, R, LS, RS, CS]),
+        (T::EAC_7_0_A, &[L, C, R, LS, RS, RLS, RRS]),
+        (T::EAC3_6_1_A, &[L, C, R, LS, RS, LFE, CS]),
+        (T::EAC3_6_1_B, &[L, C, R, LS, RS, LFE, TS]),
+        (T::EAC3_6_1_C, &[L, C, R, LS, RS, LFE, VHC]),
+        (T::EAC3_7_1_A, &[L, C, R, LS, RS, LFE, RLS, RRS]),
+        (T::EAC3_7_1_B, &[L, C, R, LS, RS, LFE, LC, RC]),
+        (T::EAC3_7_1_C, &[L, C, R, LS, RS, LFE, LSD, RSD]),
+        (T::EAC3_7_1_D, &[L, C, R, LS, RS, LFE, LW, RW]),
+        (T::EAC3_7_1_E, &[L, C, R, LS, RS, LFE, VHL, VHR]),
+        (T::EAC3_7_1_F, &[L, C, R, LS, RS, LFE, CS, TS]),
+        (T::EAC3_7_1_G, &[L, C, R, LS, RS, LFE, CS, VHC]),
+        (T::EAC3_7_1_H, &[L, C, R, LS, RS, LFE, TS, VHC]),
+        (T::DTS_3_1, &[C, L, R, LFE]),
+        (T::DTS_4_1, &[C, L, R, CS, LFE]),
+        (T::DTS_6_0_A, &[LC, RC, L, R, LS, RS]),
+        (T::DTS_6_0_B, &[C, L, R, RLS, RRS, TS]),
+        (T::DTS_6_0_C, &[C, CS, L, R, RLS, RRS]),
+        (T::DTS_6_1_A, &[LC, RC, L, R, LS, RS, LFE]),
+        (T::DTS_6_1_B, &[C, L, R, RLS, RRS, TS, LFE]),
+        (T::DTS_6_1_C, &[C, CS, L, R, RLS, RRS, LFE]),
+        (T::DTS_7_0, &[LC, C, RC, L, R, LS, RS]),
+        (T::DTS_7_1, &[LC, C, RC, L, R, LS, RS, LFE]),
+        (T::DTS_8_0_A, &[LC, RC, L, R, LS, RS, RLS, RRS]),
+        (T::DTS_8_0_B, &[LC, C, RC, L, R, LS, CS, RS]),
+        (T::DTS_8_1_A, &[LC, RC, L, R, LS, RS, RLS, RRS, LFE]),
+        (T::DTS_8_1_B, &[LC, C, RC, L, R, LS, CS, RS, LFE]),
+        (T::DTS_6_1_D, &[C, L, R, LS, RS, LFE, CS]),
+        (T::WAVE_4_0_B, &[L, R, RLS, RRS]),
+        (T::WAVE_5_0_B, &[L, R, C, RLS, RRS]),
+        (T::WAVE_5_1_B, &[L, R, C, LFE, RLS, RRS]),
+        (T::WAVE_6_1, &[L, R, C, LFE, CS, LS, RS]),
+        (T::WAVE_7_1, &[L, R, C, LFE, RLS, RRS, LS, RS]),
+        (T::ATMOS_5_1_2, &[L, R, C, LFE, LS, RS, LTM, RTM]),
+        (T::ATMOS_5_1_4, &[L, R, C, LFE, LS, RS, VHL, VHR, LTR, RTR]),
+        (T::ATMOS_7_1_2, &[L, R, C, LFE, LS, RS, RLS, RRS, LTM, RTM]),
+        (T::ATMOS_7_1_4, &[L, R, C, LFE, LS, RS, RLS, RRS, VHL, VHR, LTR, RTR]),
+        (T::ATMOS_9_1_6, &[L, R, C, LFE, LS, RS, RLS, RRS, LW, RW, VHL, VHR, LTM, RTM, LTR, RTR]),
+        (T::LOGIC_4_0_C, &[L, R, CS, C]),
+        (T::LOGIC_6_0_B, &[L, R, LS, RS, CS, C]),
+        (T::LOGIC_6_1_B, &[L, R, LS, RS, CS, C, LFE]),
+        (T::LOGIC_6_1_D, &[L, C, R, LS, CS, RS, LFE]),
+        (T::LOGIC_7_1_B, &[L, R, LS, RS, RLS, RRS, C, LFE]),
+        (T::LOGIC_ATMOS_7_1_4_B, &[L, R, RLS, RRS, LS, RS, C, LFE, VHL, VHR, LTR, RTR]),
+        (T::LOGIC_ATMOS_7_1_6, &[L, R, RLS, RRS, LS, RS, C, LFE, VHL, VHR, LTM, RTM, LTR, RTR]),
+    ]
+};
+
+/// Where a source channel goes when the destination lacks its label, in
+/// order of preference. The first alternative whose labels are all present
+/// in the destination wins. Gains follow ITU-R BS.775 where it has an opinion.
+fn routes(label: ChannelLabel) -> &'static [&'static [(ChannelLabel, f32)]] {
+    use ChannelLabel as Label;
+    match label {
+        Label::MONO => &[&[(C, 1.0)], &[(L, 1.0), (R, 1.0)]],
+        L | LT | Label::HEADPHONES_LEFT | Label::BINAURAL_LEFT => &[&[(L, 1.0)], &[(C, 1.0)]],
+        R | RT | Label::HEADPHONES_RIGHT | Label::BINAURAL_RIGHT => &[&[(R, 1.0)], &[(C, 1.0)]],
+        C => &[&[(C, 1.0)], &[(L, H), (R, H)]],
+        LC | LW => &[&[(L, 1.0)], &[(C, 1.0)]],
+        RC | RW => &[&[(R, 1.0)], &[(C, 1.0)]],
+        LS | LSD => &[&[(LS, 1.0)], &[(CS, H)], &[(L, H)]],
+        RS | RSD => &[&[(RS, 1.0)], &[(CS, H)], &[(R, H)]],
+        RLS => &[&[(LS, H)], &[(CS, H)], &[(L, H)]],
+        RRS => &[&[(RS, H)], &[(CS, H)], &[(R, H)]],
+        CS | CSD => &[&[(CS, 1.0)], &[(LS, H), (RS, H)], &[(L, 0.5), (R, 0.5)]],
+        VHL | LTM | LTR | TBL => &[&[(L, H)], &[(C, H)]],
+        VHR | RTM | RTR | TBR => &[&[(R, H)], &[(C, H)]],
+        VHC | TS | CTR | TBC => &[&[(C, H)], &[(L, 0.5), (R, 0.5)]],
+        _ => &[],
+    }
+}
+
+/// Labels that are dropped rather than mixed when the destination lacks them.
+fn is_auxiliary(label: ChannelLabel) -> bool {
+    use ChannelLabel as Label;
+    matches!(
+        label,
+        LFE | LFE2
+            | HI
+            | VI
+            | Label::UNUSED
+            | Label::HAPTIC
+            | Label::CLICK_TRACK
+            | Label::FOREIGN_LANGUAGE
+            | Label::DIALOG_CENTRIC_MIX
+    )
+}
+
+/// For each `dst` channel, the index of the `src` channel with the same label.
+///
+/// This is the format of `kAudioConverterChannelMap`, with `None` for `-1`.
+///
+/// ```
+/// use cidre::cat::audio::{self, ChannelLayoutTag as Tag};
+///
+/// let src = Tag::MPEG_5_1_A.labels().unwrap(); // L R C LFE Ls Rs
+/// let dst = Tag::MPEG_5_1_D.labels().unwrap(); // C L R Ls Rs LFE
+/// let map = audio::channel_map(&src, &dst);
+/// assert_eq!(map, [Some(2), Some(0), Some(1), Some(4), Some(5), Some(3)]);
+/// ```
+pub fn channel_map(src: &[ChannelLabel], dst: &[ChannelLabel]) -> Vec<Option<usize>> {
+    dst.iter()
+        .map(|label| src.iter().position(|l| l == label))
+        .collect()
+}
+
+/// Row-major `dst.len()` × `src.len()` mix matrix.
+///
+/// Channels present in both layouts pass through, others are folded down
+/// with ITU-R BS.775 coefficients: centre and surrounds go into the front
+/// pair at -3 dB and mono is `(L' + R') / √2`. LFE and auxiliary channels
+/// are dropped. Returns `None` if a source channel can't be placed, e.g.
+/// discrete or ambisonic channels into a speaker layout.
+///
+/// ```
+/// use cidre::cat::audio::{self, ChannelLabel as L};
+///
+/// let h = std::f32::consts::FRAC_1_SQRT_2;
+/// let src = [L::LEFT, L::RIGHT, L::CENTER, L::LFE_SCREEN, L::LEFT_SURROUND, L::RIGHT_SURROUND];
+/// let m = audio::mix_matrix(&src, &[L::LEFT, L::RIGHT]).unwrap();
+/// assert_eq!(m, [
+///     1.0, 0.0, h, 0.0, h, 0.0,
+///     0.0, 1.0, h, 0.0, 0.0, h,
+/// ]);
+/// ```
+pub fn mix_matrix(src: &[ChannelLabel], dst: &[ChannelLabel]) -> Option<Vec<f32>> {
+    if dst == [ChannelLabel::MONO] && src != dst {
+        let stereo = mix_matrix(src, &[L, R])?;
+        let (l, r) = stereo.split_at(src.len());
+        return Some(
+            src.iter()
+                .zip(l.iter().zip(r))
+                .map(|(label, (l, r))| {
+                    if *label == ChannelLabel::MONO {
+                        1.0
+                    } else {
+                        H * (l + r)
+                    }
+                })
+                .collect(),
+        );
+    }
+    let mut res = vec![0.0f32; dst.len() * src.len()];
+    for (s, label) in src.iter().enumerate() {
+        let exact: &[(ChannelLabel, f32)] = &[(*label, 1.0)];
+        let route = std::iter::once(exact)
+            .chain(routes(*label).iter().copied())
+            .find(|route| route.iter().all(|(l, _)| dst.contains(l)));
+        match route {
+            Some(route) => {
+                for (l, gain) in route {
+                    let d = dst.iter().position(|x| x == l).unwrap();
+                    res[d * src.len() + s] += gain;
+                }
+            }
+            None if is_auxiliary(*label) => {}
+            None => return None,
+        }
+    }
+    Some(res)
+}
+
+impl ChannelLabel {
+    /// Bitmap bit of the label, `None` for labels bitmaps can't express.
+    pub const fn bitmap(self) -> Option<ChannelBitmap> {
+        match self.0 {
+            1..=18 => Some(ChannelBitmap(1 << (self.0 - 1))),
+            49 | 51..=54 => Some(ChannelBitmap(1 << (self.0 - 28))),
+            _ => None,
+        }
+    }
+}
+
+impl ChannelBitmap {
+    /// Labels of the set bits in bit order, which is also the WAVE channel order.
+    pub fn labels(&self) -> Vec<ChannelLabel> {
+        (0..32)
+            .filter(|bit| self.0 & (1 << bit) != 0)
+            .filter_map(|bit| match bit {
+                0..=17 => Some(ChannelLabel(bit + 1)),
+                21 | 23..=26 => Some(ChannelLabel(bit + 28)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// `None` if a label has no bit or appears twice.
+    pub fn with_labels(labels: &[ChannelLabel]) -> Option<Self> {
+        let mut res = 0u32;
+        for label in labels {
+            let bit = label.bitmap()?.0;
+            if res & bit != 0 {
+                return None;
+            }
+            res |= bit;
+        }
+        Some(Self(res))
+    }
+
+    #[inline]
+    pub const fn channels_num(&self) -> u32 {
+        self.0.count_ones()
+    }
+}
+
+impl ChannelLayoutTag {
+    /// Ordered channel labels of the layout, like
+    /// `kAudioFormatProperty_ChannelLayoutForTag`.
+    ///
+    /// `DISCRETE_IN_ORDER`, `HOA_ACN_SN3D`, `HOA_ACN_N3D` and `UNKNOWN` expand
+    /// by their channel count. `None` for `USE_CHANNEL_DESCRIPTIONS`,
+    /// `USE_CHANNEL_BITMAP` and unknown tags.
+    ///
+    /// ```
+    /// use cidre::cat::audio::{ChannelLabel as L, ChannelLayoutTag as Tag};
+    ///
+    /// assert_eq!(
+    ///     Tag::MPEG_5_1_D.labels().unwrap(),
+    ///     [L::CENTER, L::LEFT, L::RIGHT, L::LEFT_SURROUND, L::RIGHT_SURROUND, L::LFE_SCREEN]
+    /// );
+    /// assert_eq!(Tag::DISCRETE_IN_ORDER.with_channels(2).labels().unwrap(), [L::DISCRETE_0, L::DISCRETE_1]);
+    /// ```
+    pub fn labels(self) -> Option<Vec<ChannelLabel>> {
+        let n = self.number_of_channels();
+        let family = |base: u32| Some((0..n).map(|i| ChannelLabel(base | i)).collect());
+        match self.layout() {
+            Self::DISCRETE_IN_ORDER => family(ChannelLabel::DISCRETE_0.0),
+            Self::HOA_ACN_SN3D => family(ChannelLabel::HOA_SN3D.0),
+            Self::HOA_ACN_N3D => family(ChannelLabel::HOA_N3D.0),
+            Self::UNKNOWN => Some(vec![ChannelLabel::UNKNOWN; n as usize]),
+            _ => self.fixed_labels().map(<[ChannelLabel]>::to_vec),
+        }
+    }
+
+    fn fixed_labels(self) -> Option<&'static [ChannelLabel]> {
+        LAYOUTS
+            .iter()
+            .find(|(tag, _)| *tag == self)
+            .map(|(_, labels)| *labels)
+    }
+
+    /// Layout part of the tag, channel count cleared.
+    #[inline]
+    pub const fn layout(self) -> Self {
+        Self(self.0 & 0xFFFF0000)
+    }
+
+    /// Tag for parametric layouts like `DISCRETE_IN_ORDER` or `HOA_ACN_SN3D`.
+    #[inline]
+    pub const fn with_channels(self, n: u16) -> Self {
+        Self(self.layout().0 | n as u32)
+    }
+
+    /// Channel descriptions of the layout, without coordinates.
+    pub fn descs(self) -> Option<Vec<ChannelDesc>> {
+        Some(
+            self.labels()?
+                .into_iter()
+                .map(ChannelDesc::with_label)
+                .collect(),
+        )
+    }
+
+    /// Like `kAudioFormatProperty_BitmapForLayoutTag`, `None` if a channel
+    /// has no bitmap bit. Bitmaps have no order, so this loses it.
+    pub fn bitmap(self) -> Option<ChannelBitmap> {
+        ChannelBitmap::with_labels(&self.labels()?)
+    }
+
+    /// Fixed layout with exactly these labels in this order.
+    pub fn with_labels(labels: &[ChannelLabel]) -> Option<Self> {
+        LAYOUTS
+            .iter()
+            .find(|(_, l)| *l == labels)
+            .map(|(tag, _)| *tag)
+    }
+
+    /// Fixed layout with the bitmap channels in bitmap (WAVE) order.
+    ///
+    /// ```
+    /// use cidre::cat::audio::{ChannelBitmap as B, ChannelLayoutTag as Tag};
+    ///
+    /// let bitmap = Tag::MPEG_5_1_A.bitmap().unwrap();
+    /// assert_eq!(bitmap, B(0b111111));
+    /// assert_eq!(Tag::with_bitmap(bitmap), Some(Tag::MPEG_5_1_A));
+    /// ```
+    pub fn with_bitmap(bitmap: ChannelBitmap) -> Option<Self> {
+        Self::with_labels(&bitmap.labels())
+    }
+
+    /// [`channel_map`] between two layouts.
+    pub fn channel_map(self, dst: Self) -> Option<Vec<Option<usize>>> {
+        Some(channel_map(&self.labels()?, &dst.labels()?))
+    }
+
+    /// [`mix_matrix`] between two layouts.
+    pub fn mix_matrix(self, dst: Self) -> Option<Vec<f32>> {
+        mix_matrix(&self.labels()?, &dst.labels()?)
+    }
+}
+
+impl ChannelDesc {
+    #[inline]
+    pub const fn with_label(label: ChannelLabel) -> Self {
+        Self {
+            channel_label: label,
+            channel_flags: ChannelFlags::ALL_OFF,
+            coordinates: [0.0; 3],
+        }
+    }
+}
+
+impl<const N: usize> ChannelLayout<N> {
+    /// Ordered labels whichever way the layout is described.
+    pub fn labels(&self) -> Option<Vec<ChannelLabel>> {
+        match self.channel_layout_tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => {
+                let n = (self.number_channel_descriptions as usize).min(N);
+                Some(
+                    self.channel_descriptions[..n]
+                        .iter()
+                        .map(|d| d.channel_label)
+                        .collect(),
+                )
+            }
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => Some(self.channel_bitmap.labels()),
+            tag => tag.labels(),
+        }
+    }
+
+    /// Number of channels whichever way the layout is described.
+    #[doc(alias = "AudioChannelLayout_GetNumberOfChannels")]
+    pub fn channels_num(&self) -> u32 {
+        match self.channel_layout_tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => self.number_channel_descriptions,
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => self.channel_bitmap.channels_num(),
+            tag => tag.number_of_channels(),
+        }
+    }
+}
+
+/// [`ChannelLayout`] with any number of channel descriptions, for layouts
+/// read from or written to files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelLayoutN {
+    pub channel_layout_tag: ChannelLayoutTag,
+    pub channel_bitmap: ChannelBitmap,
+    pub channel_descriptions: Vec<ChannelDesc>,
+}
+
+impl ChannelLayoutN {
+    #[inline]
+    pub const fn with_tag(tag: ChannelLayoutTag) -> Self {
+        Self {
+            channel_layout_tag: tag,
+            channel_bitmap: ChannelBitmap(0),
+            channel_descriptions: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn with_bitmap(bitmap: ChannelBitmap) -> Self {
+        Self {
+            channel_layout_tag: ChannelLayoutTag::USE_CHANNEL_BITMAP,
+            channel_bitmap: bitmap,
+            channel_descriptions: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub const fn with_descs(descs: Vec<ChannelDesc>) -> Self {
+        Self {
+            channel_layout_tag: ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS,
+            channel_bitmap: ChannelBitmap(0),
+            channel_descriptions: descs,
+        }
+    }
+
+    /// Ordered labels whichever way the layout is described.
+    pub fn labels(&self) -> Option<Vec<ChannelLabel>> {
+        match self.channel_layout_tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => Some(
+                self.channel_descriptions
+                    .iter()
+                    .map(|d| d.channel_label)
+                    .collect(),
+            ),
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => Some(self.channel_bitmap.labels()),
+            tag => tag.labels(),
+        }
+    }
+
+    /// Number of channels whichever way the layout is described.
+    pub fn channels_num(&self) -> u32 {
+        match self.channel_layout_tag {
+            ChannelLayoutTag::USE_CHANNEL_DESCRIPTIONS => self.channel_descriptions.len() as u32,
+            ChannelLayoutTag::USE_CHANNEL_BITMAP => self.channel_bitmap.channels_num(),
+            tag => tag.number_of_channels(),
+        }
+    }
+
+    /// Fixed size layout, `None` if there are more than `N` descriptions.
+    pub fn to_layout<const N: usize>(&self) -> Option<ChannelLayout<N>> {
+        let descs = &self.channel_descriptions;
+        if descs.len() > N {
+            return None;
+        }
+        let mut channel_descriptions = [ChannelDesc::default(); N];
+        channel_descriptions[..descs.len()].copy_from_slice(descs);
+        Some(ChannelLayout {
+            channel_layout_tag: self.channel_layout_tag,
+            channel_bitmap: self.channel_bitmap,
+            number_channel_descriptions: descs.len() as u32,
+            channel_descriptions,
+        })
+    }
+}
+
+impl<const N: usize> From<&ChannelLayout<N>> for ChannelLayoutN {
+    fn from(value: &ChannelLayout<N>) -> Self {
+        let n = (value.number_channel_descriptions as usize).min(N);
+        Self {
+            channel_layout_tag: value.channel_layout_tag,
+            channel_bitmap: value.channel_bitmap,
+            channel_descriptions: value.channel_descriptions[..n].to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{
+        self, ChannelBitmap, ChannelDesc, ChannelLabel, ChannelLayout, ChannelLayoutN,
+        ChannelLayoutTag as Tag,
+    };
+
+    use super::{H, LAYOUTS};
+
+    #[test]
+    fn table() {
+        for (i, (tag, labels)) in LAYOUTS.iter().enumerate() {
+            assert_eq!(tag.number_of_channels() as usize, labels.len(), "{tag:?}");
+            assert!(
+                LAYOUTS[..i].iter().all(|(t, _)| t != tag),
+                "duplicate {tag:?}"
+            );
+            for (j, label) in labels.iter().enumerate() {
+                assert!(!labels[..j].contains(label), "{tag:?} repeats {label:?}");
+            }
+            let found = Tag::with_labels(labels).unwrap();
+            assert_eq!(found.labels().unwrap(), *labels);
+        }
+        assert_eq!(Tag::ITU_3_2_1, Tag::MPEG_5_1_A);
+        assert_eq!(Tag::USE_CHANNEL_BITMAP.labels(), None);
+        assert_eq!(Tag(0x0FFF_0002).labels(), None);
+
+        let hoa = Tag::HOA_ACN_SN3D.with_channels(4).labels().unwrap();
+        assert_eq!(hoa[3], ChannelLabel::HOA_ACN_3);
+        assert_eq!(
+            Tag::UNKNOWN.with_channels(3).labels().unwrap(),
+            [ChannelLabel::UNKNOWN; 3]
+        );
+    }
+
+    #[test]
+    fn bitmaps() {
+        for label in [
+            ChannelLabel::LEFT,
+            ChannelLabel::TOP_BACK_RIGHT,
+            ChannelLabel::LEFT_TOP_MIDDLE,
+            ChannelLabel::RIGHT_TOP_MIDDLE,
+            ChannelLabel::LEFT_TOP_REAR,
+            ChannelLabel::RIGHT_TOP_REAR,
+        ] {
+            let bit = label.bitmap().unwrap();
+            assert_eq!(bit.labels(), [label]);
+        }
+        assert_eq!(
+            ChannelLabel::LEFT_TOP_MIDDLE.bitmap(),
+            Some(ChannelBitmap::LEFT_TOP_MIDDLE)
+        );
+        assert_eq!(
+            ChannelLabel::CENTER_TOP_REAR.bitmap(),
+            Some(ChannelBitmap::CENTER_TOP_REAR)
+        );
+        assert_eq!(ChannelLabel::LFE2.bitmap(), None);
+        assert_eq!(ChannelLabel::MONO.bitmap(), None);
+
+        assert_eq!(Tag::MONO.bitmap(), None);
+        assert_eq!(Tag::STEREO.bitmap(), Some(ChannelBitmap(0b11)));
+        let b = Tag::MPEG_5_1_D.bitmap().unwrap();
+        assert_eq!(b.channels_num(), 6);
+        // bitmap order is WAVE order
+        assert_eq!(Tag::with_bitmap(b), Some(Tag::MPEG_5_1_A));
+        assert_eq!(
+            Tag::with_bitmap(Tag::WAVE_6_1.bitmap().unwrap()),
+            Some(Tag::MPEG_6_1_A)
+        );
+        assert_eq!(Tag::WAVE_7_1.bitmap(), None);
+        assert_eq!(Tag::with_bitmap(ChannelBitmap(1 << 20)), None);
+        assert_eq!(
+            ChannelBitmap::with_labels(&[ChannelLabel::LEFT, ChannelLabel::LEFT]),
+            None
+        );
+    }
+
+    #[test]
+    fn layouts() {
+        let mut layout = ChannelLayout::<3> {
+            channel_layout_tag: Tag::USE_CHANNEL_DESCRIPTIONS,
+            channel_bitmap: ChannelBitmap(0),
+            number_channel_descriptions: 2,
+            channel_descriptions: [ChannelDesc::with_label(ChannelLabel::CENTER); 3],
+        };
+        assert_eq!(layout.channels_num(), 2);
+        assert_eq!(layout.labels().unwrap(), [ChannelLabel::CENTER; 2]);
+
+        layout.channel_layout_tag = Tag::USE_CHANNEL_BITMAP;
+        layout.channel_bitmap = ChannelBitmap(0b101);
+        assert_eq!(layout.channels_num(), 2);
+        assert_eq!(
+            layout.labels().unwrap(),
+            [ChannelLabel::LEFT, ChannelLabel::CENTER]
+        );
+
+        layout.channel_layout_tag = Tag::AC3_3_1_1;
+        assert_eq!(layout.channels_num(), 5);
+        let descs = Tag::AC3_3_1_1.descs().unwrap();
+        assert_eq!(descs.len(), 5);
+        assert_eq!(descs[3].channel_label, ChannelLabel::CENTER_SURROUND);
+
+        let layout = ChannelLayoutN::with_descs(descs);
+        assert_eq!(layout.channels_num(), 5);
+        assert_eq!(layout.labels(), Tag::AC3_3_1_1.labels());
+        assert!(layout.to_layout::<4>().is_none());
+        let fixed = layout.to_layout::<8>().unwrap();
+        assert_eq!(fixed.labels(), layout.labels());
+        assert_eq!(ChannelLayoutN::from(&fixed), layout);
+    }
+
+    #[test]
+    fn maps() {
+        assert_eq!(
+            Tag::WAVE_7_1.channel_map(Tag::MPEG_7_1_C).unwrap(),
+            [0, 1, 2, 3, 6, 7, 4, 5].map(Some)
+        );
+        assert_eq!(
+            Tag::STEREO.channel_map(Tag::MPEG_3_0_B).unwrap(),
+            [None, Some(0), Some(1)]
+        );
+    }
+
+    #[test]
+    fn mixes() {
+        let m = Tag::MPEG_5_1_A.mix_matrix(Tag::MONO).unwrap();
+        let expected = [H, H, 1.0, 0.0, 0.5, 0.5];
+        for (a, b) in m.iter().zip(expected) {
+            assert!((a - b).abs() < 1e-6, "{m:?}");
+        }
+
+        assert_eq!(Tag::MONO.mix_matrix(Tag::STEREO).unwrap(), [1.0, 1.0]);
+        assert_eq!(Tag::MONO.mix_matrix(Tag::MONO).unwrap(), [1.0]);
+
+        // 3/2 -> 2/1
+        #[rustfmt::skip]
+        assert_eq!(Tag::MPEG_5_0_A.mix_matrix(Tag::ITU_2_1).unwrap(), [
+            1.0, 0.0, H, 0.0, 0.0,
+            0.0, 1.0, H, 0.0, 0.0,
+            0.0, 0.0, 0.0, H, H,
+        ]);
+
+        // 7.1 -> 5.1 folds rear surrounds into the side pair
+        let m = Tag::MPEG_7_1_C.mix_matrix(Tag::MPEG_5_1_A).unwrap();
+        assert_eq!(&m[4 * 8..5 * 8], &[0.0, 0.0, 0.0, 0.0, 1.0, 0.0, H, 0.0]);
+
+        // Atmos heights fold into the front pair
+        let m = Tag::ATMOS_5_1_4.mix_matrix(Tag::MPEG_5_1_A).unwrap();
+        assert_eq!(m[6], H);
+        assert_eq!(m[8], H);
+
+        assert_eq!(
+            Tag::DISCRETE_IN_ORDER
+                .with_channels(2)
+                .mix_matrix(Tag::STEREO),
+            None
+        );
+        assert!(audio::mix_matrix(
+            &[ChannelLabel::LFE_SCREEN, ChannelLabel::HAPTIC],
+            &[ChannelLabel::LEFT]
+        )
+        .is_some());
+    }
+}
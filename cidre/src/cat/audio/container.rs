@@ -0,0 +1,817 @@
+//! Audio container files without AudioToolbox.
+//!
+//! Reads and writes CAF, WAVE (including `WAVE_FORMAT_EXTENSIBLE`),
+//! RF64/BW64 and AIFF/AIFC, surfacing what `at::audio::FileId` exposes as
+//! properties: the [`StreamBasicDesc`], channel layout, magic cookie, packet
+//! descriptions and table info, markers, regions and the info dictionary.
+//!
+//! ```
+//! use std::io::Cursor;
+//! use cidre::cat::audio::{self, container};
+//!
+//! let asbd = audio::StreamBasicDescBuilder::lpcm(48_000.0, 2)
+//!     .int(16)
+//!     .build()
+//!     .unwrap();
+//! let header = container::Header::new(container::FileType::Wave, asbd);
+//! let mut writer = container::Writer::new(Cursor::new(Vec::new()), header).unwrap();
+//! writer.write_packets(&[0u8; 4 * 100], &[]).unwrap();
+//! let file = writer.finish().unwrap().into_inner();
+//!
+//! let header = container::Header::read(&mut Cursor::new(&file)).unwrap();
+//! assert_eq!(header.file_type, container::FileType::Wave);
+//! assert_eq!(header.asbd, asbd);
+//! assert_eq!(header.frames_num(), 100);
+//! ```
+
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::{define_opts, FourCc};
+
+use super::{
+    ChannelBitmap, ChannelDesc, ChannelFlags, ChannelLabel, ChannelLayoutN, ChannelLayoutTag,
+    StreamBasicDesc, StreamPacketDesc,
+};
+
+mod aiff;
+mod caf;
+mod wave;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+
+    /// Not a CAF, WAVE, RF64, BW64, AIFF or AIFC stream.
+    FileType,
+
+    /// Chunk ends past its fields or past the end of the file.
+    Truncated(FourCc),
+
+    /// Required chunk is missing.
+    Missing(FourCc),
+
+    Invalid(FourCc, &'static str),
+
+    /// Stream format or metadata the file type can't carry.
+    Unsupported(FileType, &'static str),
+
+    /// Written packets don't match the stream format.
+    Packets(&'static str),
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::FileType => write!(f, "unknown audio file type"),
+            Self::Truncated(id) => write!(f, "{id} chunk is truncated"),
+            Self::Missing(id) => write!(f, "{id} chunk is missing"),
+            Self::Invalid(id, what) => write!(f, "{id} chunk has invalid {what}"),
+            Self::Unsupported(typ, what) => write!(f, "{typ:?} files can't carry {what}"),
+            Self::Packets(what) => write!(f, "{what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum FileType {
+    /// Core Audio Format.
+    #[default]
+    #[doc(alias = "kAudioFileCAFType")]
+    Caf,
+
+    #[doc(alias = "kAudioFileWAVEType")]
+    Wave,
+
+    /// 64-bit WAVE of EBU Tech 3306.
+    #[doc(alias = "kAudioFileRF64Type")]
+    Rf64,
+
+    /// 64-bit WAVE of ITU-R BS.2088.
+    #[doc(alias = "kAudioFileBW64Type")]
+    Bw64,
+
+    #[doc(alias = "kAudioFileAIFFType")]
+    Aiff,
+
+    #[doc(alias = "kAudioFileAIFCType")]
+    Aifc,
+}
+
+impl FileType {
+    /// Same value as the matching `at::audio::FileTypeId`.
+    pub const fn four_cc(self) -> FourCc {
+        FourCc::from_bytes(match self {
+            Self::Caf => *b"caff",
+            Self::Wave => *b"WAVE",
+            Self::Rf64 => *b"RF64",
+            Self::Bw64 => *b"BW64",
+            Self::Aiff => *b"AIFF",
+            Self::Aifc => *b"AIFC",
+        })
+    }
+}
+
+/// Frames the encoder added at both ends of the stream.
+///
+/// `valid_frames + priming_frames + remainder_frames` is the number of frames
+/// in all packets.
+#[doc(alias = "AudioFilePacketTableInfo")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct PacketTableInfo {
+    pub valid_frames: i64,
+    pub priming_frames: i32,
+    pub remainder_frames: i32,
+}
+
+#[doc(alias = "CAFMarkerType")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct MarkerType(pub u32);
+
+impl MarkerType {
+    #[doc(alias = "kCAFMarkerType_Generic")]
+    pub const GENERIC: Self = Self(0);
+
+    #[doc(alias = "kCAFMarkerType_ProgramStart")]
+    pub const PROGRAM_START: Self = Self(u32::from_be_bytes(*b"pbeg"));
+
+    #[doc(alias = "kCAFMarkerType_ProgramEnd")]
+    pub const PROGRAM_END: Self = Self(u32::from_be_bytes(*b"pend"));
+
+    #[doc(alias = "kCAFMarkerType_TrackStart")]
+    pub const TRACK_START: Self = Self(u32::from_be_bytes(*b"tbeg"));
+
+    #[doc(alias = "kCAFMarkerType_TrackEnd")]
+    pub const TRACK_END: Self = Self(u32::from_be_bytes(*b"tend"));
+
+    #[doc(alias = "kCAFMarkerType_Index")]
+    pub const INDEX: Self = Self(u32::from_be_bytes(*b"indx"));
+
+    #[doc(alias = "kCAFMarkerType_RegionStart")]
+    pub const REGION_START: Self = Self(u32::from_be_bytes(*b"rbeg"));
+
+    #[doc(alias = "kCAFMarkerType_RegionEnd")]
+    pub const REGION_END: Self = Self(u32::from_be_bytes(*b"rend"));
+
+    #[doc(alias = "kCAFMarkerType_RegionSyncPoint")]
+    pub const REGION_SYNC_POINT: Self = Self(u32::from_be_bytes(*b"rsyc"));
+
+    #[doc(alias = "kCAFMarkerType_SelectionStart")]
+    pub const SELECTION_START: Self = Self(u32::from_be_bytes(*b"sbeg"));
+
+    #[doc(alias = "kCAFMarkerType_SelectionEnd")]
+    pub const SELECTION_END: Self = Self(u32::from_be_bytes(*b"send"));
+
+    #[doc(alias = "kCAFMarkerType_SustainLoopStart")]
+    pub const SUSTAIN_LOOP_START: Self = Self(u32::from_be_bytes(*b"slbg"));
+
+    #[doc(alias = "kCAFMarkerType_SustainLoopEnd")]
+    pub const SUSTAIN_LOOP_END: Self = Self(u32::from_be_bytes(*b"slen"));
+
+    #[doc(alias = "kCAFMarkerType_ReleaseLoopStart")]
+    pub const RELEASE_LOOP_START: Self = Self(u32::from_be_bytes(*b"rlbg"));
+
+    #[doc(alias = "kCAFMarkerType_ReleaseLoopEnd")]
+    pub const RELEASE_LOOP_END: Self = Self(u32::from_be_bytes(*b"rlen"));
+}
+
+#[doc(alias = "AudioFileMarker")]
+#[doc(alias = "CAFMarker")]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Marker {
+    /// Frame the marker points at, CAF allows fractional positions.
+    pub frame_pos: f64,
+    pub name: String,
+
+    /// CAF stores names by this id, so marker and region ids of a file
+    /// should not collide.
+    pub id: u32,
+    pub kind: MarkerType,
+
+    /// 1 based channel, 0 for all channels.
+    pub channel: u32,
+}
+
+define_opts!(
+    #[doc(alias = "CAFRegionFlags")]
+    pub RegionFlags(u32)
+);
+
+impl RegionFlags {
+    #[doc(alias = "kCAFRegionFlag_LoopEnable")]
+    pub const LOOP_ENABLE: Self = Self(1);
+
+    #[doc(alias = "kCAFRegionFlag_PlayForward")]
+    pub const PLAY_FORWARD: Self = Self(2);
+
+    #[doc(alias = "kCAFRegionFlag_PlayBackward")]
+    pub const PLAY_BACKWARD: Self = Self(4);
+}
+
+#[doc(alias = "AudioFileRegion")]
+#[doc(alias = "CAFRegion")]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Region {
+    pub id: u32,
+    pub name: String,
+    pub flags: RegionFlags,
+    pub markers: Vec<Marker>,
+}
+
+/// Everything in an audio file but the audio data.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Header {
+    pub file_type: FileType,
+
+    /// `kAudioFilePropertyDataFormat`
+    pub asbd: StreamBasicDesc,
+
+    /// `kAudioFilePropertyChannelLayout`
+    pub channel_layout: Option<ChannelLayoutN>,
+
+    /// `kAudioFilePropertyMagicCookieData`, empty if the format has none.
+    pub magic_cookie: Vec<u8>,
+
+    /// `kAudioFilePropertyPacketTableInfo`
+    pub packet_table_info: Option<PacketTableInfo>,
+
+    /// Descriptions of variable bitrate packets with offsets relative to
+    /// the audio data. Empty for constant bitrate formats.
+    pub packets: Vec<StreamPacketDesc>,
+
+    /// `kAudioFilePropertyMarkerList`
+    pub markers: Vec<Marker>,
+
+    /// `kAudioFilePropertyRegionList`
+    pub regions: Vec<Region>,
+
+    /// `kAudioFilePropertyInfoDictionary` entries in file order, keyed like
+    /// `kAFInfoDictionary_*` ("title", "artist", "comments", ...).
+    pub info: Vec<(String, String)>,
+
+    /// `kAudioFilePropertyDataOffset`
+    pub data_offset: u64,
+
+    /// `kAudioFilePropertyAudioDataByteCount`
+    pub data_len: u64,
+}
+
+impl Header {
+    #[inline]
+    pub fn new(file_type: FileType, asbd: StreamBasicDesc) -> Self {
+        Self {
+            file_type,
+            asbd,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the header of the file starting at the current position and
+    /// skips chunks it doesn't know.
+    pub fn read<R: Read + Seek>(r: &mut R) -> Result<Self, Error> {
+        let start = r.stream_position()?;
+        let end = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(start))?;
+        let mut head = [0u8; 12];
+        r.read_exact(&mut head).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => Error::FileType,
+            _ => Error::Io(e),
+        })?;
+        let mut header = match (&head[..4], &head[8..]) {
+            (b"caff", _) => caf::read(r, start, end)?,
+            (b"RIFF" | b"RF64" | b"BW64", b"WAVE") => wave::read(r, start, end, &head)?,
+            (b"FORM", b"AIFF" | b"AIFC") => aiff::read(r, start, end, &head)?,
+            _ => return Err(Error::FileType),
+        };
+        if header.data_offset == 0 {
+            return Err(Error::Missing(match header.file_type {
+                FileType::Caf | FileType::Wave | FileType::Rf64 | FileType::Bw64 => {
+                    FourCc::from_bytes(*b"data")
+                }
+                FileType::Aiff | FileType::Aifc => FourCc::from_bytes(*b"SSND"),
+            }));
+        }
+        if header.asbd.bytes_per_packet != 0 {
+            header.data_len -= header.data_len % header.asbd.bytes_per_packet as u64;
+        }
+        Ok(header)
+    }
+
+    /// Value of an info dictionary entry.
+    pub fn info(&self, key: &str) -> Option<&str> {
+        self.info
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// `kAudioFilePropertyAudioDataPacketCount`
+    pub fn packets_num(&self) -> u64 {
+        match self.asbd.bytes_per_packet {
+            0 => self.packets.len() as u64,
+            n => self.data_len / n as u64,
+        }
+    }
+
+    /// Frames in all packets, priming and remainder frames included.
+    pub fn total_frames(&self) -> u64 {
+        match self.asbd.frames_per_packet {
+            0 => self
+                .packets
+                .iter()
+                .map(|p| p.variable_frames_in_packet as u64)
+                .sum(),
+            n => self.packets_num() * n as u64,
+        }
+    }
+
+    /// Playable frames, what's left of the total after priming and remainder.
+    pub fn frames_num(&self) -> u64 {
+        match self.packet_table_info {
+            Some(info) => info.valid_frames.max(0) as u64,
+            None => self.total_frames(),
+        }
+    }
+
+    /// Playable duration in seconds.
+    pub fn duration(&self) -> f64 {
+        if self.asbd.sample_rate > 0.0 {
+            self.frames_num() as f64 / self.asbd.sample_rate
+        } else {
+            0.0
+        }
+    }
+
+    /// Description of a packet with the offset relative to the audio data.
+    pub fn packet(&self, index: u64) -> Option<StreamPacketDesc> {
+        if index >= self.packets_num() {
+            return None;
+        }
+        match self.asbd.bytes_per_packet {
+            0 => self.packets.get(index as usize).copied(),
+            n => Some(StreamPacketDesc {
+                start_offset: (index * n as u64) as i64,
+                variable_frames_in_packet: 0,
+                data_byte_size: n,
+            }),
+        }
+    }
+
+    /// Reads up to `count` packets from `first` into `buf`, like
+    /// `AudioFileReadPacketData`. Returned descriptions are relative to `buf`.
+    pub fn read_packets<R: Read + Seek>(
+        &self,
+        r: &mut R,
+        first: u64,
+        count: usize,
+        buf: &mut Vec<u8>,
+    ) -> Result<Vec<StreamPacketDesc>, Error> {
+        buf.clear();
+        let end = self.packets_num().min(first.saturating_add(count as u64));
+        let mut descs: Vec<_> = (first..end).filter_map(|i| self.packet(i)).collect();
+        let (Some(head), Some(last)) = (descs.first().copied(), descs.last()) else {
+            return Ok(descs);
+        };
+        let len = (last.start_offset - head.start_offset) as u64 + last.data_byte_size as u64;
+        r.seek(SeekFrom::Start(self.data_offset + head.start_offset as u64))?;
+        r.take(len).read_to_end(buf)?;
+        if (buf.len() as u64) < len {
+            return Err(Error::Truncated(FourCc::from_bytes(*b"data")));
+        }
+        for desc in descs.iter_mut() {
+            desc.start_offset -= head.start_offset;
+        }
+        Ok(descs)
+    }
+}
+
+/// Streams audio data into a new file.
+///
+/// Everything known upfront goes before the audio data, sizes, frame counts
+/// and the CAF packet table are written by [`Writer::finish`]. WAVE files
+/// that grow past 4 GiB become RF64.
+///
+/// WAVE and AIFF keep the markers and the info entries they have chunks for,
+/// regions and other info entries are dropped.
+pub struct Writer<W: Write + Seek> {
+    out: W,
+    header: Header,
+    start: u64,
+}
+
+impl<W: Write + Seek> Writer<W> {
+    /// Writes everything up to the audio data. Packets, data offset and
+    /// length of `header` are ignored, packet table info only has to carry
+    /// priming and remainder frames.
+    pub fn new(mut out: W, mut header: Header) -> Result<Self, Error> {
+        header.packets.clear();
+        header.data_len = 0;
+        let start = out.stream_position()?;
+        let mut buf = Vec::new();
+        match header.file_type {
+            FileType::Caf => caf::write_header(&mut buf, &header)?,
+            FileType::Wave | FileType::Rf64 | FileType::Bw64 => {
+                wave::write_header(&mut buf, &header)?
+            }
+            FileType::Aiff | FileType::Aifc => aiff::write_header(&mut buf, &header)?,
+        }
+        out.write_all(&buf)?;
+        header.data_offset = start + buf.len() as u64;
+        Ok(Self { out, header, start })
+    }
+
+    /// Header so far, data length and packets included.
+    #[inline]
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Appends whole packets. `descs` describe the packets in `data` for
+    /// variable bitrate formats and are ignored for constant bitrate ones.
+    pub fn write_packets(&mut self, data: &[u8], descs: &[StreamPacketDesc]) -> Result<(), Error> {
+        let asbd = &self.header.asbd;
+        if asbd.bytes_per_packet != 0 {
+            if data.len() % asbd.bytes_per_packet as usize != 0 {
+                return Err(Error::Packets("data is not a whole number of packets"));
+            }
+        } else {
+            let mut end = 0;
+            for desc in descs {
+                if desc.start_offset < end as i64
+                    || desc.start_offset as u64 + desc.data_byte_size as u64 > data.len() as u64
+                {
+                    return Err(Error::Packets("packet descriptions are out of data bounds"));
+                }
+                if asbd.frames_per_packet == 0 && desc.variable_frames_in_packet == 0 {
+                    return Err(Error::Packets("packet descriptions miss frames count"));
+                }
+                end = desc.start_offset as usize + desc.data_byte_size as usize;
+            }
+            if end != data.len() {
+                return Err(Error::Packets("packet descriptions don't cover data"));
+            }
+            let base = self.header.data_len as i64;
+            self.header
+                .packets
+                .extend(descs.iter().map(|d| StreamPacketDesc {
+                    start_offset: base + d.start_offset,
+                    ..*d
+                }));
+        }
+        self.out.write_all(data)?;
+        self.header.data_len += data.len() as u64;
+        Ok(())
+    }
+
+    /// Patches sizes and frame counts, appends trailing chunks and returns
+    /// the output positioned at the end of the file.
+    pub fn finish(mut self) -> Result<W, Error> {
+        let total_frames = self.header.total_frames() as i64;
+        if let Some(info) = self.header.packet_table_info.as_mut() {
+            info.valid_frames =
+                total_frames - info.priming_frames as i64 - info.remainder_frames as i64;
+        }
+        match self.header.file_type {
+            FileType::Caf => caf::finish(&mut self.out, &self.header)?,
+            FileType::Wave | FileType::Rf64 | FileType::Bw64 => {
+                wave::finish(&mut self.out, &self.header, self.start)?
+            }
+            FileType::Aiff | FileType::Aifc => {
+                aiff::finish(&mut self.out, &self.header, self.start)?
+            }
+        }
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+/// Cursor over a chunk payload.
+#[derive(Debug, Clone)]
+struct Reader<'a> {
+    id: FourCc,
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(id: FourCc, data: &'a [u8]) -> Self {
+        Self { id, data }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < n {
+            return Err(Error::Truncated(self.id));
+        }
+        let (res, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(res)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn be_u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn be_u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn be_i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn be_i64(&mut self) -> Result<i64, Error> {
+        Ok(i64::from_be_bytes(self.array()?))
+    }
+
+    fn be_f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_be_bytes(self.array()?))
+    }
+
+    fn be_f64(&mut self) -> Result<f64, Error> {
+        Ok(f64::from_be_bytes(self.array()?))
+    }
+
+    fn le_u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn le_u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn le_u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn four_cc(&mut self) -> Result<FourCc, Error> {
+        Ok(FourCc::from_bytes(self.array()?))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.data)
+    }
+}
+
+/// Text up to the first NUL, lossy for invalid UTF-8.
+fn c_str(bytes: &[u8]) -> String {
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..len]).into_owned()
+}
+
+/// Reads a whole chunk payload, files are trusted no further than their length.
+fn read_chunk<R: Read>(r: &mut R, id: FourCc, size: u64) -> Result<Vec<u8>, Error> {
+    let mut buf = Vec::new();
+    r.take(size).read_to_end(&mut buf)?;
+    if (buf.len() as u64) < size {
+        return Err(Error::Truncated(id));
+    }
+    Ok(buf)
+}
+
+fn patch<W: Write + Seek>(out: &mut W, pos: u64, bytes: &[u8]) -> io::Result<()> {
+    out.seek(SeekFrom::Start(pos))?;
+    out.write_all(bytes)
+}
+
+/// Big-endian `AudioChannelLayout` of CAF `chan` and AIFF `CHAN` chunks.
+fn read_layout(mut r: Reader) -> Result<ChannelLayoutN, Error> {
+    let tag = ChannelLayoutTag(r.be_u32()?);
+    let bitmap = ChannelBitmap(r.be_u32()?);
+    let n = r.be_u32()?;
+    let mut descs = Vec::with_capacity((n as usize).min(r.data.len() / 20));
+    for _ in 0..n {
+        descs.push(ChannelDesc {
+            channel_label: ChannelLabel(r.be_u32()?),
+            channel_flags: ChannelFlags(r.be_u32()?),
+            coordinates: [r.be_f32()?, r.be_f32()?, r.be_f32()?],
+        });
+    }
+    Ok(ChannelLayoutN {
+        channel_layout_tag: tag,
+        channel_bitmap: bitmap,
+        channel_descriptions: descs,
+    })
+}
+
+fn write_layout(out: &mut Vec<u8>, layout: &ChannelLayoutN) {
+    out.extend_from_slice(&layout.channel_layout_tag.0.to_be_bytes());
+    out.extend_from_slice(&layout.channel_bitmap.0.to_be_bytes());
+    out.extend_from_slice(&(layout.channel_descriptions.len() as u32).to_be_bytes());
+    for desc in &layout.channel_descriptions {
+        out.extend_from_slice(&desc.channel_label.0.to_be_bytes());
+        out.extend_from_slice(&desc.channel_flags.0.to_be_bytes());
+        for c in desc.coordinates {
+            out.extend_from_slice(&c.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use crate::cat::audio::{
+        self,
+        container::{self, FileType, Header, Marker, MarkerType, PacketTableInfo},
+        ChannelLayoutN, ChannelLayoutTag, StreamPacketDesc,
+    };
+
+    fn write(header: Header, data: &[u8], descs: &[StreamPacketDesc]) -> Vec<u8> {
+        let mut w = container::Writer::new(Cursor::new(Vec::new()), header).unwrap();
+        w.write_packets(data, descs).unwrap();
+        w.finish().unwrap().into_inner()
+    }
+
+    fn read(file: &[u8]) -> Header {
+        container::Header::read(&mut Cursor::new(file)).unwrap()
+    }
+
+    #[test]
+    fn lpcm_roundtrips() {
+        let data: Vec<u8> = (0..6 * 4 * 10).map(|i| i as u8).collect();
+        let layout = ChannelLayoutN::with_tag(ChannelLayoutTag::MPEG_5_1_A);
+        let builders = [
+            audio::StreamBasicDescBuilder::lpcm(48_000.0, 6).int(24),
+            audio::StreamBasicDescBuilder::lpcm(44_100.0, 6).float(32),
+            audio::StreamBasicDescBuilder::lpcm(96_000.0, 6).int(16),
+        ];
+        for file_type in [
+            FileType::Caf,
+            FileType::Wave,
+            FileType::Rf64,
+            FileType::Bw64,
+            FileType::Aiff,
+            FileType::Aifc,
+        ] {
+            for builder in builders {
+                let big_endian = matches!(file_type, FileType::Aiff | FileType::Aifc);
+                if file_type == FileType::Aiff && builder.build_unchecked().is_float() {
+                    continue;
+                }
+                let asbd = builder.big_endian(big_endian).build().unwrap();
+                let mut header = Header::new(file_type, asbd);
+                header.channel_layout = Some(layout.clone());
+                header.markers.push(Marker {
+                    frame_pos: 3.0,
+                    name: "cue".to_string(),
+                    id: 1,
+                    ..Default::default()
+                });
+                header
+                    .info
+                    .push(("title".to_string(), "take 1".to_string()));
+                let len =
+                    data.len() / asbd.bytes_per_frame as usize * asbd.bytes_per_frame as usize;
+                let file = write(header, &data[..len], &[]);
+
+                let header = read(&file);
+                assert_eq!(header.file_type, file_type);
+                assert_eq!(header.asbd, asbd, "{file_type:?}");
+                assert_eq!(
+                    header.frames_num(),
+                    len as u64 / asbd.bytes_per_frame as u64
+                );
+                assert_eq!(
+                    header.channel_layout.as_ref().unwrap().labels(),
+                    layout.labels(),
+                    "{file_type:?}"
+                );
+                assert_eq!(header.markers.len(), 1);
+                assert_eq!(header.markers[0].frame_pos, 3.0);
+                assert_eq!(header.markers[0].name, "cue");
+                assert_eq!(header.info("title"), Some("take 1"));
+
+                let mut buf = Vec::new();
+                let descs = header
+                    .read_packets(&mut Cursor::new(&file), 2, 3, &mut buf)
+                    .unwrap();
+                assert_eq!(descs.len(), 3);
+                let bpf = asbd.bytes_per_frame as usize;
+                assert_eq!(buf, &data[2 * bpf..5 * bpf]);
+            }
+        }
+    }
+
+    #[test]
+    fn caf_vbr() {
+        let asbd =
+            audio::StreamBasicDescBuilder::compressed(audio::Format::MPEG4_AAC, 48_000.0, 2, 1024)
+                .build_unchecked();
+        let mut header = Header::new(FileType::Caf, asbd);
+        header.magic_cookie = vec![0x03, 0x80, 0x80, 0x80, 0x22];
+        header.packet_table_info = Some(PacketTableInfo {
+            valid_frames: 0,
+            priming_frames: 2112,
+            remainder_frames: 448,
+        });
+        header.regions.push(container::Region {
+            id: 7,
+            name: "verse".to_string(),
+            flags: container::RegionFlags::LOOP_ENABLE,
+            markers: vec![
+                Marker {
+                    frame_pos: 0.0,
+                    kind: MarkerType::REGION_START,
+                    ..Default::default()
+                },
+                Marker {
+                    frame_pos: 2048.0,
+                    kind: MarkerType::REGION_END,
+                    ..Default::default()
+                },
+            ],
+        });
+        let sizes = [200u32, 300, 1, 70000];
+        let mut data = Vec::new();
+        let mut descs = Vec::new();
+        for (i, size) in sizes.iter().enumerate() {
+            descs.push(StreamPacketDesc {
+                start_offset: data.len() as i64,
+                variable_frames_in_packet: 0,
+                data_byte_size: *size,
+            });
+            data.resize(data.len() + *size as usize, i as u8);
+        }
+
+        let mut w = container::Writer::new(Cursor::new(Vec::new()), header.clone()).unwrap();
+        w.write_packets(&data[..500], &descs[..2]).unwrap();
+        let rest: Vec<_> = descs[2..]
+            .iter()
+            .map(|d| StreamPacketDesc {
+                start_offset: d.start_offset - 500,
+                ..*d
+            })
+            .collect();
+        w.write_packets(&data[500..], &rest).unwrap();
+        assert!(w.write_packets(&data[..10], &descs[..1]).is_err());
+        let file = w.finish().unwrap().into_inner();
+
+        let read = read(&file);
+        assert_eq!(read.asbd, asbd);
+        assert_eq!(read.magic_cookie, header.magic_cookie);
+        assert_eq!(read.packets, descs);
+        assert_eq!(read.packets_num(), 4);
+        assert_eq!(read.total_frames(), 4096);
+        assert_eq!(read.frames_num(), 4096 - 2112 - 448);
+        assert_eq!(read.regions, header.regions);
+
+        let mut buf = Vec::new();
+        let got = read
+            .read_packets(&mut Cursor::new(&file), 1, 10, &mut buf)
+            .unwrap();
+        assert_eq!(got.len(), 3);
+        assert_eq!(got[2].start_offset, 301);
+        assert_eq!(buf, &data[200..]);
+    }
+
+    #[test]
+    fn unsupported() {
+        let asbd = audio::StreamBasicDescBuilder::lpcm(48_000.0, 2)
+            .int(16)
+            .non_interleaved()
+            .build()
+            .unwrap();
+        for file_type in [FileType::Caf, FileType::Wave, FileType::Aiff] {
+            assert!(matches!(
+                container::Writer::new(Cursor::new(Vec::new()), Header::new(file_type, asbd)),
+                Err(container::Error::Unsupported(..))
+            ));
+        }
+        assert!(matches!(
+            container::Header::read(&mut Cursor::new(b"RIFF\0\0\0\0AVI ")),
+            Err(container::Error::FileType)
+        ));
+        assert!(matches!(
+            container::Header::read(&mut Cursor::new(b"caff\0\x01\0\0free\0\0\0\0\0\0\0\0")),
+            Err(container::Error::Missing(_))
+        ));
+    }
+}
@@ -0,0 +1,368 @@
+//! Audio Interchange File Format and its AIFC revision.
+
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use crate::{
+    cat::audio::{Format, FormatFlags, StreamBasicDesc, StreamBasicDescBuilder},
+    FourCc,
+};
+
+use super::{
+    patch, read_chunk, read_layout, write_layout, Error, FileType, Header, Marker, Reader,
+};
+
+/// `FVER` timestamp of the AIFC draft every reader expects.
+const AIFC_VERSION1: u32 = 0xA2805140;
+
+const COMM: FourCc = FourCc::from_bytes(*b"COMM");
+
+/// Text chunks and the info dictionary keys they carry.
+const INFO_KEYS: &[(&[u8; 4], &str)] = &[
+    (b"NAME", "title"),
+    (b"AUTH", "artist"),
+    (b"(c) ", "copyright"),
+    (b"ANNO", "comments"),
+];
+
+pub(super) fn read<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    end: u64,
+    head: &[u8; 12],
+) -> Result<Header, Error> {
+    let file_type = match &head[8..] {
+        b"AIFC" => FileType::Aifc,
+        _ => FileType::Aiff,
+    };
+    let mut header = Header {
+        file_type,
+        ..Default::default()
+    };
+    let mut frames = None;
+    let mut pos = start + 12;
+    while pos + 8 <= end {
+        r.seek(SeekFrom::Start(pos))?;
+        let mut chunk_head = [0u8; 8];
+        r.read_exact(&mut chunk_head)?;
+        let id = FourCc::from_bytes(chunk_head[..4].try_into().unwrap());
+        let body = pos + 8;
+        let size = u32::from_be_bytes(chunk_head[4..].try_into().unwrap()) as u64;
+
+        if &id.to_bytes() == b"SSND" {
+            // writers that never came back to patch sizes leave them short or open
+            let size = size.min(end - body);
+            let mut fields = [0u8; 8];
+            r.read_exact(&mut fields)
+                .map_err(|_| Error::Truncated(id))?;
+            let offset = u32::from_be_bytes(fields[..4].try_into().unwrap()) as u64;
+            if size < 8 + offset {
+                return Err(Error::Truncated(id));
+            }
+            header.data_offset = body + 8 + offset;
+            header.data_len = size - 8 - offset;
+            pos = body + size + (size & 1);
+            continue;
+        }
+        if size > end - body {
+            return Err(Error::Truncated(id));
+        }
+        pos = body + size + (size & 1);
+
+        let known = matches!(&id.to_bytes(), b"COMM" | b"CHAN" | b"MARK")
+            || INFO_KEYS.iter().any(|(k, _)| **k == id.to_bytes());
+        if !known {
+            continue;
+        }
+        let payload = read_chunk(r, id, size)?;
+        let mut c = Reader::new(id, &payload);
+        match &id.to_bytes() {
+            b"COMM" => {
+                let (asbd, n) = read_comm(&mut c, file_type)?;
+                header.asbd = asbd;
+                frames = Some(n);
+            }
+            b"CHAN" => header.channel_layout = Some(read_layout(c)?),
+            b"MARK" => {
+                let n = c.be_u16()?;
+                for _ in 0..n {
+                    let id = c.be_u16()? as u32;
+                    let position = c.be_u32()?;
+                    let name = read_pstring(&mut c)?;
+                    header.markers.push(Marker {
+                        frame_pos: position as f64,
+                        name,
+                        id,
+                        ..Default::default()
+                    });
+                }
+            }
+            text => {
+                let (_, key) = INFO_KEYS.iter().find(|(k, _)| *k == text).unwrap();
+                let value = String::from_utf8_lossy(&payload);
+                header
+                    .info
+                    .push((key.to_string(), value.trim_end_matches('\0').to_string()));
+            }
+        }
+    }
+    let Some(frames) = frames else {
+        return Err(Error::Missing(COMM));
+    };
+    // SSND may be padded past the frames COMM counts
+    let len = frames * header.asbd.bytes_per_frame as u64;
+    header.data_len = header.data_len.min(len);
+    Ok(header)
+}
+
+fn read_comm(c: &mut Reader, file_type: FileType) -> Result<(StreamBasicDesc, u64), Error> {
+    let channels = c.be_u16()? as u32;
+    let frames = c.be_u32()? as u64;
+    let bits = c.be_u16()? as u32;
+    let sample_rate = ext_to_f64(c.array()?);
+    let compression = match file_type {
+        FileType::Aifc => c.array()?,
+        _ => *b"NONE",
+    };
+    if channels == 0 {
+        return Err(Error::Invalid(COMM, "channels"));
+    }
+
+    let builder = StreamBasicDescBuilder::lpcm(sample_rate, channels);
+    let asbd = match &compression {
+        b"NONE" | b"twos" => builder
+            .int(bits)
+            .aligned_high(bits.div_ceil(8))
+            .big_endian(true),
+        b"sowt" => builder.int(bits).aligned_high(bits.div_ceil(8)),
+        b"fl32" | b"FL32" => builder.float(32).big_endian(true),
+        b"fl64" | b"FL64" => builder.float(64).big_endian(true),
+        b"ulaw" | b"ULAW" | b"alaw" | b"ALAW" => {
+            let format = match &compression {
+                b"ulaw" | b"ULAW" => Format::U_LAW,
+                _ => Format::A_LAW,
+            };
+            let asbd = StreamBasicDesc {
+                sample_rate,
+                format,
+                format_flags: FormatFlags(0),
+                bytes_per_packet: channels,
+                frames_per_packet: 1,
+                bytes_per_frame: channels,
+                channels_per_frame: channels,
+                bits_per_channel: 8,
+                reserved: 0,
+            };
+            return Ok((asbd, frames));
+        }
+        _ => return Err(Error::Unsupported(file_type, "compressed formats")),
+    };
+    Ok((asbd.build_unchecked(), frames))
+}
+
+/// Count byte followed by text, padded to an even length.
+fn read_pstring(c: &mut Reader) -> Result<String, Error> {
+    let len = c.u8()? as usize;
+    let text = String::from_utf8_lossy(c.bytes(len)?).into_owned();
+    if len & 1 == 0 {
+        c.u8()?;
+    }
+    Ok(text)
+}
+
+fn write_pstring(out: &mut Vec<u8>, text: &str) {
+    let mut len = text.len().min(255);
+    while !text.is_char_boundary(len) {
+        len -= 1;
+    }
+    out.push(len as u8);
+    out.extend_from_slice(&text.as_bytes()[..len]);
+    if len & 1 == 0 {
+        out.push(0);
+    }
+}
+
+/// 80-bit IEEE 754 extended precision, the sample rate format of `COMM`.
+fn ext_to_f64(bytes: [u8; 10]) -> f64 {
+    let exp = u16::from_be_bytes([bytes[0], bytes[1]]);
+    let mantissa = u64::from_be_bytes(bytes[2..].try_into().unwrap());
+    if mantissa == 0 {
+        return 0.0;
+    }
+    let val = mantissa as f64 * 2f64.powi((exp & 0x7fff) as i32 - 16383 - 63);
+    if exp & 0x8000 != 0 {
+        -val
+    } else {
+        val
+    }
+}
+
+fn f64_to_ext(val: f64) -> [u8; 10] {
+    let mut res = [0u8; 10];
+    if val == 0.0 || !val.is_normal() {
+        return res;
+    }
+    let bits = val.to_bits();
+    let sign = ((bits >> 63) as u16) << 15;
+    let exp = ((bits >> 52) & 0x7ff) as i32 - 1023 + 16383;
+    let mantissa = ((bits & ((1 << 52) - 1)) | (1 << 52)) << 11;
+    res[..2].copy_from_slice(&(sign | exp as u16).to_be_bytes());
+    res[2..].copy_from_slice(&mantissa.to_be_bytes());
+    res
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], f: impl FnOnce(&mut Vec<u8>)) {
+    out.extend_from_slice(id);
+    let size_pos = out.len();
+    out.extend_from_slice(&[0; 4]);
+    f(out);
+    let size = out.len() - size_pos - 4;
+    out[size_pos..size_pos + 4].copy_from_slice(&(size as u32).to_be_bytes());
+    if size & 1 == 1 {
+        out.push(0);
+    }
+}
+
+/// `AIFC` compression type and name.
+fn compression(header: &Header) -> Result<(&'static [u8; 4], &'static str), Error> {
+    let file_type = header.file_type;
+    let asbd = &header.asbd;
+    match asbd.format {
+        Format::U_LAW if file_type == FileType::Aifc => return Ok((b"ulaw", "\u{b5}Law 2:1")),
+        Format::A_LAW if file_type == FileType::Aifc => return Ok((b"alaw", "ALaw 2:1")),
+        Format::LINEAR_PCM => {}
+        _ => return Err(Error::Unsupported(file_type, "compressed formats")),
+    }
+    if !asbd.is_interleaved() && asbd.channels_per_frame > 1 {
+        return Err(Error::Unsupported(file_type, "non-interleaved audio"));
+    }
+    if !asbd.is_packed() && !asbd.is_aligned_high() {
+        return Err(Error::Unsupported(file_type, "low aligned samples"));
+    }
+    if asbd.sample_word_size() != asbd.bits_per_channel.div_ceil(8) {
+        return Err(Error::Unsupported(file_type, "padded samples"));
+    }
+    let res = match (asbd.is_float(), asbd.is_big_endian(), asbd.bits_per_channel) {
+        (false, _, _) if !asbd.is_signed_int() => {
+            return Err(Error::Unsupported(file_type, "unsigned samples"))
+        }
+        (false, true, _) => (b"NONE", "not compressed"),
+        (false, false, _) => (b"sowt", ""),
+        (true, true, 32) => (b"fl32", "32-bit floating point"),
+        (true, true, 64) => (b"fl64", "64-bit floating point"),
+        (true, ..) => return Err(Error::Unsupported(file_type, "these float samples")),
+    };
+    if file_type == FileType::Aiff && res.0 != b"NONE" {
+        return Err(Error::Unsupported(
+            file_type,
+            "little-endian or float samples, use AIFC",
+        ));
+    }
+    Ok(res)
+}
+
+pub(super) fn write_header(out: &mut Vec<u8>, header: &Header) -> Result<(), Error> {
+    let file_type = header.file_type;
+    let asbd = &header.asbd;
+    if !header.magic_cookie.is_empty() {
+        return Err(Error::Unsupported(file_type, "magic cookies"));
+    }
+    let (compression, name) = compression(header)?;
+    if asbd.channels_per_frame > u16::MAX as u32 {
+        return Err(Error::Unsupported(file_type, "this channel count"));
+    }
+    if header
+        .markers
+        .iter()
+        .any(|m| m.id > u16::MAX as u32 || !(0.0..=u32::MAX as f64).contains(&m.frame_pos))
+    {
+        return Err(Error::Unsupported(
+            file_type,
+            "marker ids past 2^16 or positions past 2^32 frames",
+        ));
+    }
+
+    out.extend_from_slice(b"FORM");
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&file_type.four_cc().to_bytes());
+    if file_type == FileType::Aifc {
+        chunk(out, b"FVER", |out| {
+            out.extend_from_slice(&AIFC_VERSION1.to_be_bytes())
+        });
+    }
+    chunk(out, b"COMM", |out| {
+        out.extend_from_slice(&(asbd.channels_per_frame as u16).to_be_bytes());
+        // frames are patched by finish
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(asbd.bits_per_channel as u16).to_be_bytes());
+        out.extend_from_slice(&f64_to_ext(asbd.sample_rate));
+        if file_type == FileType::Aifc {
+            out.extend_from_slice(compression);
+            write_pstring(out, name);
+        }
+    });
+    if let Some(layout) = &header.channel_layout {
+        chunk(out, b"CHAN", |out| write_layout(out, layout));
+    }
+    for (key, value) in &header.info {
+        if let Some((id, _)) = INFO_KEYS.iter().find(|(_, k)| k == key) {
+            chunk(out, id, |out| out.extend_from_slice(value.as_bytes()));
+        }
+    }
+    if !header.markers.is_empty() {
+        chunk(out, b"MARK", |out| {
+            out.extend_from_slice(&(header.markers.len() as u16).to_be_bytes());
+            for marker in &header.markers {
+                out.extend_from_slice(&(marker.id as u16).to_be_bytes());
+                out.extend_from_slice(&(marker.frame_pos.round() as u32).to_be_bytes());
+                write_pstring(out, &marker.name);
+            }
+        });
+    }
+
+    // size is patched by finish, offset and block size are 0
+    out.extend_from_slice(b"SSND");
+    out.extend_from_slice(&[0; 12]);
+    Ok(())
+}
+
+pub(super) fn finish<W: Write + Seek>(
+    out: &mut W,
+    header: &Header,
+    start: u64,
+) -> Result<(), Error> {
+    let data_end = header.data_offset + header.data_len;
+    let end = data_end + (header.data_len & 1);
+    let form_size = end - start - 8;
+    if form_size > u32::MAX as u64 {
+        return Err(Error::Unsupported(header.file_type, "more than 4 GiB"));
+    }
+    let frames = header.total_frames();
+    if end != data_end {
+        patch(out, data_end, &[0])?;
+    }
+    patch(out, start + 4, &(form_size as u32).to_be_bytes())?;
+    let comm = match header.file_type {
+        FileType::Aifc => start + 12 + 12,
+        _ => start + 12,
+    };
+    patch(out, comm + 8 + 2, &(frames as u32).to_be_bytes())?;
+    let ssnd_size = header.data_len as u32 + 8;
+    patch(out, header.data_offset - 12, &ssnd_size.to_be_bytes())?;
+    out.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ext_to_f64, f64_to_ext};
+
+    #[test]
+    fn extended() {
+        let rate_44100 = [0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0];
+        assert_eq!(f64_to_ext(44_100.0), rate_44100);
+        assert_eq!(ext_to_f64(rate_44100), 44_100.0);
+        for v in [8_000.0, 48_000.0, 96_000.0, 22_050.0, 11_025.5, 0.0] {
+            assert_eq!(ext_to_f64(f64_to_ext(v)), v);
+        }
+    }
+}
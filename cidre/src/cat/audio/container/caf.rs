@@ -0,0 +1,421 @@
+//! Core Audio Format, version 1.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    cat::audio::{Format, FormatFlags, StreamBasicDesc, StreamBasicDescBuilder, StreamPacketDesc},
+    FourCc,
+};
+
+use super::{
+    c_str, patch, read_chunk, read_layout, write_layout, Error, FileType, Header, Marker,
+    MarkerType, PacketTableInfo, Reader, Region, RegionFlags,
+};
+
+#[doc(alias = "kCAFLinearPCMFormatFlagIsFloat")]
+const LPCM_IS_FLOAT: u32 = 1 << 0;
+
+#[doc(alias = "kCAFLinearPCMFormatFlagIsLittleEndian")]
+const LPCM_IS_LITTLE_ENDIAN: u32 = 1 << 1;
+
+const DESC: FourCc = FourCc::from_bytes(*b"desc");
+const DATA: FourCc = FourCc::from_bytes(*b"data");
+const STRG: FourCc = FourCc::from_bytes(*b"strg");
+
+pub(super) fn read<R: Read + Seek>(r: &mut R, start: u64, end: u64) -> Result<Header, Error> {
+    let mut header = Header {
+        file_type: FileType::Caf,
+        ..Default::default()
+    };
+    let mut has_desc = false;
+    let mut names = HashMap::new();
+    let mut pos = start + 8;
+    while pos + 12 <= end {
+        r.seek(SeekFrom::Start(pos))?;
+        let mut head = [0u8; 12];
+        r.read_exact(&mut head)?;
+        let id = FourCc::from_bytes(head[..4].try_into().unwrap());
+        let body = pos + 12;
+        let size = match i64::from_be_bytes(head[4..].try_into().unwrap()) {
+            // only the last data chunk may leave its size open
+            -1 if id == DATA => end - body,
+            size if size >= 0 && size as u64 <= end - body => size as u64,
+            _ => return Err(Error::Truncated(id)),
+        };
+        pos = body + size;
+
+        if !has_desc && id != DESC {
+            return Err(Error::Missing(DESC));
+        }
+        if id == DATA {
+            if size < 4 {
+                return Err(Error::Truncated(id));
+            }
+            // skipping edit count
+            header.data_offset = body + 4;
+            header.data_len = size - 4;
+            continue;
+        }
+        let known = matches!(
+            &id.to_bytes(),
+            b"desc" | b"chan" | b"kuki" | b"pakt" | b"mark" | b"regn" | b"strg" | b"info"
+        );
+        if !known {
+            continue;
+        }
+        let payload = read_chunk(r, id, size)?;
+        let mut c = Reader::new(id, &payload);
+        match &id.to_bytes() {
+            b"desc" => {
+                header.asbd = read_desc(&mut c)?;
+                has_desc = true;
+            }
+            b"chan" => header.channel_layout = Some(read_layout(c)?),
+            b"kuki" => header.magic_cookie = payload,
+            b"pakt" => read_pakt(&mut c, &mut header)?,
+            b"mark" => {
+                let _smpte_time_type = c.be_u32()?;
+                let n = c.be_u32()?;
+                for _ in 0..n {
+                    header.markers.push(read_marker(&mut c)?);
+                }
+            }
+            b"regn" => {
+                let _smpte_time_type = c.be_u32()?;
+                let n = c.be_u32()?;
+                for _ in 0..n {
+                    let mut region = Region {
+                        id: c.be_u32()?,
+                        flags: RegionFlags(c.be_u32()?),
+                        ..Default::default()
+                    };
+                    let n = c.be_u32()?;
+                    for _ in 0..n {
+                        region.markers.push(read_marker(&mut c)?);
+                    }
+                    header.regions.push(region);
+                }
+            }
+            b"strg" => {
+                let n = c.be_u32()?;
+                let entries = c.bytes(n as usize * 12)?;
+                let strings = c.rest();
+                for entry in entries.chunks_exact(12) {
+                    let id = u32::from_be_bytes(entry[..4].try_into().unwrap());
+                    let offset = i64::from_be_bytes(entry[4..].try_into().unwrap());
+                    match strings.get(offset as usize..) {
+                        Some(s) if offset >= 0 => names.insert(id, c_str(s)),
+                        _ => return Err(Error::Invalid(STRG, "string offset")),
+                    };
+                }
+            }
+            b"info" => {
+                let n = c.be_u32()?;
+                let mut parts = c.rest().split(|&b| b == 0).map(c_str);
+                for _ in 0..n {
+                    let (Some(key), Some(value)) = (parts.next(), parts.next()) else {
+                        return Err(Error::Truncated(id));
+                    };
+                    header.info.push((key, value));
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+    if !has_desc {
+        return Err(Error::Missing(DESC));
+    }
+
+    let name = |id: u32| names.get(&id).cloned().unwrap_or_default();
+    for marker in header.markers.iter_mut() {
+        marker.name = name(marker.id);
+    }
+    for region in header.regions.iter_mut() {
+        region.name = name(region.id);
+        for marker in region.markers.iter_mut() {
+            marker.name = name(marker.id);
+        }
+    }
+    Ok(header)
+}
+
+fn read_desc(c: &mut Reader) -> Result<StreamBasicDesc, Error> {
+    let sample_rate = c.be_f64()?;
+    let format = Format(c.be_u32()?);
+    let flags = c.be_u32()?;
+    let bytes_per_packet = c.be_u32()?;
+    let frames_per_packet = c.be_u32()?;
+    let channels = c.be_u32()?;
+    let bits = c.be_u32()?;
+
+    if format != Format::LINEAR_PCM {
+        return Ok(StreamBasicDesc {
+            sample_rate,
+            format,
+            format_flags: FormatFlags(flags),
+            bytes_per_packet,
+            frames_per_packet,
+            bytes_per_frame: 0,
+            channels_per_frame: channels,
+            bits_per_channel: bits,
+            reserved: 0,
+        });
+    }
+    if channels == 0 || bytes_per_packet % channels != 0 || frames_per_packet != 1 {
+        return Err(Error::Invalid(DESC, "linear PCM layout"));
+    }
+    let builder = StreamBasicDescBuilder::lpcm(sample_rate, channels);
+    let builder = if flags & LPCM_IS_FLOAT != 0 {
+        builder.float(bits)
+    } else {
+        builder.int(bits)
+    };
+    Ok(builder
+        .aligned_high(bytes_per_packet / channels)
+        .big_endian(flags & LPCM_IS_LITTLE_ENDIAN == 0)
+        .build_unchecked())
+}
+
+fn read_pakt(c: &mut Reader, header: &mut Header) -> Result<(), Error> {
+    let packets_num = c.be_i64()?;
+    header.packet_table_info = Some(PacketTableInfo {
+        valid_frames: c.be_i64()?,
+        priming_frames: c.be_i32()?,
+        remainder_frames: c.be_i32()?,
+    });
+    let asbd = &header.asbd;
+    if asbd.bytes_per_packet != 0 && asbd.frames_per_packet != 0 {
+        return Ok(());
+    }
+    let mut start_offset = 0i64;
+    for _ in 0..packets_num {
+        let data_byte_size = match asbd.bytes_per_packet {
+            0 => read_vlq(c)? as u32,
+            n => n,
+        };
+        let variable_frames_in_packet = match asbd.frames_per_packet {
+            0 => read_vlq(c)? as u32,
+            _ => 0,
+        };
+        header.packets.push(StreamPacketDesc {
+            start_offset,
+            variable_frames_in_packet,
+            data_byte_size,
+        });
+        start_offset += data_byte_size as i64;
+    }
+    Ok(())
+}
+
+/// Big-endian base 128 integer, high bit set on all but the last byte.
+fn read_vlq(c: &mut Reader) -> Result<u64, Error> {
+    let mut res = 0u64;
+    loop {
+        let b = c.u8()?;
+        res = (res << 7) | (b & 0x7f) as u64;
+        if b & 0x80 == 0 {
+            return Ok(res);
+        }
+    }
+}
+
+fn write_vlq(out: &mut Vec<u8>, mut val: u64) {
+    let mut bytes = [0u8; 10];
+    let mut i = bytes.len();
+    loop {
+        i -= 1;
+        bytes[i] = (val & 0x7f) as u8 | if i + 1 == bytes.len() { 0 } else { 0x80 };
+        val >>= 7;
+        if val == 0 {
+            break;
+        }
+    }
+    out.extend_from_slice(&bytes[i..]);
+}
+
+fn read_marker(c: &mut Reader) -> Result<Marker, Error> {
+    let kind = MarkerType(c.be_u32()?);
+    let frame_pos = c.be_f64()?;
+    let id = c.be_u32()?;
+    let _smpte_time = c.bytes(8)?;
+    let channel = c.be_u32()?;
+    Ok(Marker {
+        frame_pos,
+        name: String::new(),
+        id,
+        kind,
+        channel,
+    })
+}
+
+fn write_marker(out: &mut Vec<u8>, marker: &Marker) {
+    out.extend_from_slice(&marker.kind.0.to_be_bytes());
+    out.extend_from_slice(&marker.frame_pos.to_be_bytes());
+    out.extend_from_slice(&marker.id.to_be_bytes());
+    // SMPTE time is ignored with kCAF_SMPTE_TimeTypeNone
+    out.extend_from_slice(&[0; 8]);
+    out.extend_from_slice(&marker.channel.to_be_bytes());
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], f: impl FnOnce(&mut Vec<u8>)) {
+    out.extend_from_slice(id);
+    let size_pos = out.len();
+    out.extend_from_slice(&[0; 8]);
+    f(out);
+    let size = (out.len() - size_pos - 8) as i64;
+    out[size_pos..size_pos + 8].copy_from_slice(&size.to_be_bytes());
+}
+
+pub(super) fn write_header(out: &mut Vec<u8>, header: &Header) -> Result<(), Error> {
+    let asbd = &header.asbd;
+    let flags = if asbd.is_pcm() {
+        if !asbd.is_interleaved() && asbd.channels_per_frame > 1 {
+            return Err(Error::Unsupported(FileType::Caf, "non-interleaved audio"));
+        }
+        if !asbd.is_float() && !asbd.is_signed_int() {
+            return Err(Error::Unsupported(FileType::Caf, "unsigned samples"));
+        }
+        let mut flags = 0;
+        if asbd.is_float() {
+            flags |= LPCM_IS_FLOAT;
+        }
+        if !asbd.is_big_endian() {
+            flags |= LPCM_IS_LITTLE_ENDIAN;
+        }
+        flags
+    } else {
+        asbd.format_flags.0
+    };
+
+    out.extend_from_slice(b"caff");
+    out.extend_from_slice(&1u16.to_be_bytes());
+    out.extend_from_slice(&0u16.to_be_bytes());
+
+    chunk(out, b"desc", |out| {
+        out.extend_from_slice(&asbd.sample_rate.to_be_bytes());
+        for v in [
+            asbd.format.0,
+            flags,
+            asbd.bytes_per_packet,
+            asbd.frames_per_packet,
+            asbd.channels_per_frame,
+            asbd.bits_per_channel,
+        ] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+    });
+    if let Some(layout) = &header.channel_layout {
+        chunk(out, b"chan", |out| write_layout(out, layout));
+    }
+    if !header.magic_cookie.is_empty() {
+        chunk(out, b"kuki", |out| {
+            out.extend_from_slice(&header.magic_cookie)
+        });
+    }
+    if !header.info.is_empty() {
+        chunk(out, b"info", |out| {
+            out.extend_from_slice(&(header.info.len() as u32).to_be_bytes());
+            for (key, value) in &header.info {
+                for s in [key, value] {
+                    out.extend_from_slice(s.as_bytes());
+                    out.push(0);
+                }
+            }
+        });
+    }
+
+    let named = header
+        .markers
+        .iter()
+        .chain(header.regions.iter().flat_map(|r| &r.markers))
+        .map(|m| (m.id, &m.name))
+        .chain(header.regions.iter().map(|r| (r.id, &r.name)))
+        .filter(|(_, name)| !name.is_empty());
+    let mut strings = Vec::new();
+    let mut entries = Vec::new();
+    for (id, name) in named {
+        entries.push((id, strings.len() as i64));
+        strings.extend_from_slice(name.as_bytes());
+        strings.push(0);
+    }
+    if !entries.is_empty() {
+        chunk(out, b"strg", |out| {
+            out.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+            for (id, offset) in entries {
+                out.extend_from_slice(&id.to_be_bytes());
+                out.extend_from_slice(&offset.to_be_bytes());
+            }
+            out.extend_from_slice(&strings);
+        });
+    }
+    if !header.markers.is_empty() {
+        chunk(out, b"mark", |out| {
+            out.extend_from_slice(&0u32.to_be_bytes());
+            out.extend_from_slice(&(header.markers.len() as u32).to_be_bytes());
+            for marker in &header.markers {
+                write_marker(out, marker);
+            }
+        });
+    }
+    if !header.regions.is_empty() {
+        chunk(out, b"regn", |out| {
+            out.extend_from_slice(&0u32.to_be_bytes());
+            out.extend_from_slice(&(header.regions.len() as u32).to_be_bytes());
+            for region in &header.regions {
+                out.extend_from_slice(&region.id.to_be_bytes());
+                out.extend_from_slice(&region.flags.0.to_be_bytes());
+                out.extend_from_slice(&(region.markers.len() as u32).to_be_bytes());
+                for marker in &region.markers {
+                    write_marker(out, marker);
+                }
+            }
+        });
+    }
+
+    // open size until finish, edit count 0
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&(-1i64).to_be_bytes());
+    out.extend_from_slice(&0u32.to_be_bytes());
+    Ok(())
+}
+
+pub(super) fn finish<W: Write + Seek>(out: &mut W, header: &Header) -> Result<(), Error> {
+    let asbd = &header.asbd;
+    let mut end = header.data_offset + header.data_len;
+    let vbr = asbd.bytes_per_packet == 0 || asbd.frames_per_packet == 0;
+    if vbr || header.packet_table_info.is_some() {
+        let info = header.packet_table_info.unwrap_or(PacketTableInfo {
+            valid_frames: header.total_frames() as i64,
+            priming_frames: 0,
+            remainder_frames: 0,
+        });
+        let mut buf = Vec::new();
+        chunk(&mut buf, b"pakt", |out| {
+            let packets_num = if vbr { header.packets.len() as u64 } else { 0 };
+            out.extend_from_slice(&packets_num.to_be_bytes());
+            out.extend_from_slice(&info.valid_frames.to_be_bytes());
+            out.extend_from_slice(&info.priming_frames.to_be_bytes());
+            out.extend_from_slice(&info.remainder_frames.to_be_bytes());
+            if !vbr {
+                return;
+            }
+            for p in &header.packets {
+                if asbd.bytes_per_packet == 0 {
+                    write_vlq(out, p.data_byte_size as u64);
+                }
+                if asbd.frames_per_packet == 0 {
+                    write_vlq(out, p.variable_frames_in_packet as u64);
+                }
+            }
+        });
+        patch(out, end, &buf)?;
+        end += buf.len() as u64;
+    }
+    let size = header.data_len as i64 + 4;
+    patch(out, header.data_offset - 12, &size.to_be_bytes())?;
+    out.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
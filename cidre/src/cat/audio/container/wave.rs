@@ -0,0 +1,450 @@
+//! RIFF WAVE with `WAVE_FORMAT_EXTENSIBLE`, and its 64-bit RF64 and BW64
+//! variants.
+
+use std::{
+    collections::HashMap,
+    io::{Read, Seek, SeekFrom, Write},
+};
+
+use crate::{
+    cat::audio::{
+        ChannelBitmap, ChannelLayoutN, ChannelLayoutTag, Format, FormatFlags, StreamBasicDesc,
+        StreamBasicDescBuilder,
+    },
+    FourCc,
+};
+
+use super::{c_str, patch, read_chunk, Error, FileType, Header, Marker, Reader};
+
+#[doc(alias = "WAVE_FORMAT_PCM")]
+const FORMAT_PCM: u16 = 0x0001;
+
+#[doc(alias = "WAVE_FORMAT_IEEE_FLOAT")]
+const FORMAT_IEEE_FLOAT: u16 = 0x0003;
+
+#[doc(alias = "WAVE_FORMAT_ALAW")]
+const FORMAT_ALAW: u16 = 0x0006;
+
+#[doc(alias = "WAVE_FORMAT_MULAW")]
+const FORMAT_MULAW: u16 = 0x0007;
+
+#[doc(alias = "WAVE_FORMAT_EXTENSIBLE")]
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// `KSDATAFORMAT_SUBTYPE_*` GUIDs are the format tag followed by these bytes.
+const SUBTYPE_TAIL: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xAA, 0x00, 0x38, 0x9B, 0x71,
+];
+
+/// `ds64` payload without a chunk size table.
+const DS64_LEN: usize = 28;
+
+const FMT: FourCc = FourCc::from_bytes(*b"fmt ");
+const DS64: FourCc = FourCc::from_bytes(*b"ds64");
+
+/// `LIST/INFO` chunk ids and the info dictionary keys they carry.
+const INFO_KEYS: &[(&[u8; 4], &str)] = &[
+    (b"INAM", "title"),
+    (b"IART", "artist"),
+    (b"IPRD", "album"),
+    (b"IGNR", "genre"),
+    (b"ICMT", "comments"),
+    (b"ICOP", "copyright"),
+    (b"ICRD", "recorded date"),
+    (b"ISFT", "encoding application"),
+    (b"ITRK", "track number"),
+];
+
+pub(super) fn read<R: Read + Seek>(
+    r: &mut R,
+    start: u64,
+    end: u64,
+    head: &[u8; 12],
+) -> Result<Header, Error> {
+    let file_type = match &head[..4] {
+        b"RF64" => FileType::Rf64,
+        b"BW64" => FileType::Bw64,
+        _ => FileType::Wave,
+    };
+    let mut header = Header {
+        file_type,
+        ..Default::default()
+    };
+    let mut has_fmt = false;
+    let mut data_size64 = None;
+    let mut labels = HashMap::new();
+    let mut pos = start + 12;
+    while pos + 8 <= end {
+        r.seek(SeekFrom::Start(pos))?;
+        let mut chunk_head = [0u8; 8];
+        r.read_exact(&mut chunk_head)?;
+        let id = FourCc::from_bytes(chunk_head[..4].try_into().unwrap());
+        let body = pos + 8;
+        let size = u32::from_le_bytes(chunk_head[4..].try_into().unwrap()) as u64;
+
+        if &id.to_bytes() == b"data" {
+            let size = match data_size64 {
+                Some(size64) if size == u32::MAX as u64 => size64,
+                _ => size,
+            };
+            // writers that never came back to patch sizes leave them short or open
+            let size = size.min(end - body);
+            header.data_offset = body;
+            header.data_len = size;
+            pos = body + size + (size & 1);
+            continue;
+        }
+        if size > end - body {
+            return Err(Error::Truncated(id));
+        }
+        pos = body + size + (size & 1);
+
+        match &id.to_bytes() {
+            b"ds64" => {
+                let payload = read_chunk(r, id, size)?;
+                let mut c = Reader::new(id, &payload);
+                let _riff_size = c.le_u64()?;
+                data_size64 = Some(c.le_u64()?);
+            }
+            b"fmt " => {
+                let payload = read_chunk(r, id, size)?;
+                read_fmt(&mut Reader::new(id, &payload), &mut header)?;
+                has_fmt = true;
+            }
+            b"cue " => {
+                let payload = read_chunk(r, id, size)?;
+                let mut c = Reader::new(id, &payload);
+                let n = c.le_u32()?;
+                for _ in 0..n {
+                    let id = c.le_u32()?;
+                    let _position = c.le_u32()?;
+                    let _chunk_id = c.four_cc()?;
+                    let _chunk_start = c.le_u32()?;
+                    let _block_start = c.le_u32()?;
+                    let sample_offset = c.le_u32()?;
+                    header.markers.push(Marker {
+                        frame_pos: sample_offset as f64,
+                        id,
+                        ..Default::default()
+                    });
+                }
+            }
+            b"LIST" => {
+                let payload = read_chunk(r, id, size)?;
+                let mut c = Reader::new(id, &payload);
+                let list_type = c.four_cc()?;
+                while !c.is_empty() {
+                    let sub_id = c.four_cc()?;
+                    let sub_size = c.le_u32()? as usize;
+                    let sub = c.bytes(sub_size)?;
+                    if sub_size & 1 == 1 && !c.is_empty() {
+                        c.u8()?;
+                    }
+                    match (&list_type.to_bytes(), &sub_id.to_bytes()) {
+                        (b"adtl", b"labl") if sub.len() >= 4 => {
+                            let cue_id = u32::from_le_bytes(sub[..4].try_into().unwrap());
+                            labels.insert(cue_id, c_str(&sub[4..]));
+                        }
+                        (b"INFO", key) => {
+                            if let Some((_, key)) = INFO_KEYS.iter().find(|(id, _)| *id == key) {
+                                header.info.push((key.to_string(), c_str(sub)));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if !has_fmt {
+        return Err(Error::Missing(FMT));
+    }
+    if file_type != FileType::Wave && data_size64.is_none() {
+        return Err(Error::Missing(DS64));
+    }
+    for marker in header.markers.iter_mut() {
+        if let Some(label) = labels.remove(&marker.id) {
+            marker.name = label;
+        }
+    }
+    Ok(header)
+}
+
+fn read_fmt(c: &mut Reader, header: &mut Header) -> Result<(), Error> {
+    let mut tag = c.le_u16()?;
+    let channels = c.le_u16()? as u32;
+    let sample_rate = c.le_u32()? as f64;
+    let _avg_bytes_per_sec = c.le_u32()?;
+    let block_align = c.le_u16()? as u32;
+    let container_bits = c.le_u16()? as u32;
+    let mut bits = container_bits;
+    if tag == FORMAT_EXTENSIBLE {
+        let _extra_len = c.le_u16()?;
+        let valid_bits = c.le_u16()? as u32;
+        let mask = ChannelBitmap(c.le_u32()?);
+        let subtype = c.le_u16()?;
+        if c.array::<14>()? != SUBTYPE_TAIL {
+            return Err(Error::Unsupported(header.file_type, "non-PCM formats"));
+        }
+        tag = subtype;
+        if valid_bits != 0 {
+            bits = valid_bits;
+        }
+        if mask.0 != 0 {
+            header.channel_layout = Some(match ChannelLayoutTag::with_bitmap(mask) {
+                Some(tag) => ChannelLayoutN::with_tag(tag),
+                None => ChannelLayoutN::with_bitmap(mask),
+            });
+        }
+    }
+    if channels == 0 || block_align % channels != 0 {
+        return Err(Error::Invalid(FMT, "block align"));
+    }
+
+    let builder = StreamBasicDescBuilder::lpcm(sample_rate, channels);
+    let word = block_align / channels;
+    header.asbd = match tag {
+        FORMAT_PCM if bits <= 8 => builder.uint(bits).aligned_high(word).build_unchecked(),
+        FORMAT_PCM => builder.int(bits).aligned_high(word).build_unchecked(),
+        FORMAT_IEEE_FLOAT => builder.float(bits).aligned_high(word).build_unchecked(),
+        FORMAT_ALAW | FORMAT_MULAW => StreamBasicDesc {
+            sample_rate,
+            format: if tag == FORMAT_ALAW {
+                Format::A_LAW
+            } else {
+                Format::U_LAW
+            },
+            format_flags: FormatFlags(0),
+            bytes_per_packet: block_align,
+            frames_per_packet: 1,
+            bytes_per_frame: block_align,
+            channels_per_frame: channels,
+            bits_per_channel: 8,
+            reserved: 0,
+        },
+        _ => return Err(Error::Unsupported(header.file_type, "non-PCM formats")),
+    };
+    Ok(())
+}
+
+fn chunk(out: &mut Vec<u8>, id: &[u8; 4], f: impl FnOnce(&mut Vec<u8>)) {
+    out.extend_from_slice(id);
+    let size_pos = out.len();
+    out.extend_from_slice(&[0; 4]);
+    f(out);
+    let size = out.len() - size_pos - 4;
+    out[size_pos..size_pos + 4].copy_from_slice(&(size as u32).to_le_bytes());
+    if size & 1 == 1 {
+        out.push(0);
+    }
+}
+
+/// Format tag and whether it goes into `WAVE_FORMAT_EXTENSIBLE`.
+fn format_tag(header: &Header) -> Result<(u16, bool), Error> {
+    let file_type = header.file_type;
+    let asbd = &header.asbd;
+    let tag = match asbd.format {
+        Format::A_LAW => FORMAT_ALAW,
+        Format::U_LAW => FORMAT_MULAW,
+        Format::LINEAR_PCM => {
+            if !asbd.is_interleaved() && asbd.channels_per_frame > 1 {
+                return Err(Error::Unsupported(file_type, "non-interleaved audio"));
+            }
+            if asbd.is_big_endian() {
+                return Err(Error::Unsupported(file_type, "big-endian samples"));
+            }
+            if !asbd.is_packed() && !asbd.is_aligned_high() {
+                return Err(Error::Unsupported(file_type, "low aligned samples"));
+            }
+            if asbd.is_float() {
+                FORMAT_IEEE_FLOAT
+            } else if asbd.is_signed_int() == (asbd.bits_per_channel > 8) {
+                FORMAT_PCM
+            } else {
+                return Err(Error::Unsupported(
+                    file_type,
+                    "signed 8-bit or unsigned wider samples",
+                ));
+            }
+        }
+        _ => return Err(Error::Unsupported(file_type, "non-PCM formats")),
+    };
+    let channels = asbd.channels_per_frame;
+    if channels == 0 || channels > u16::MAX as u32 {
+        return Err(Error::Unsupported(file_type, "this channel count"));
+    }
+    let container_bits = asbd.bytes_per_frame / channels * 8;
+    let extensible = matches!(tag, FORMAT_PCM | FORMAT_IEEE_FLOAT)
+        && (channels > 2 || container_bits > 16 || asbd.bits_per_channel != container_bits);
+    Ok((tag, extensible))
+}
+
+/// Non-PCM and extensible formats need a `fact` chunk, it goes right
+/// before `data`.
+fn has_fact(tag: u16, extensible: bool) -> bool {
+    extensible || !matches!(tag, FORMAT_PCM | FORMAT_IEEE_FLOAT)
+}
+
+pub(super) fn write_header(out: &mut Vec<u8>, header: &Header) -> Result<(), Error> {
+    let file_type = header.file_type;
+    let asbd = &header.asbd;
+    if !header.magic_cookie.is_empty() {
+        return Err(Error::Unsupported(file_type, "magic cookies"));
+    }
+    let (tag, extensible) = format_tag(header)?;
+    let channels = asbd.channels_per_frame;
+    let block_align = asbd.bytes_per_frame;
+    let container_bits = block_align / channels * 8;
+    let valid_bits = asbd.bits_per_channel;
+
+    out.extend_from_slice(match file_type {
+        FileType::Rf64 => b"RF64",
+        FileType::Bw64 => b"BW64",
+        _ => b"RIFF",
+    });
+    out.extend_from_slice(&u32::MAX.to_le_bytes());
+    out.extend_from_slice(b"WAVE");
+    // JUNK becomes ds64 if the file grows too large for RIFF
+    chunk(
+        out,
+        if file_type == FileType::Wave {
+            b"JUNK"
+        } else {
+            b"ds64"
+        },
+        |out| out.extend_from_slice(&[0; DS64_LEN]),
+    );
+    chunk(out, b"fmt ", |out| {
+        out.extend_from_slice(&(if extensible { FORMAT_EXTENSIBLE } else { tag }).to_le_bytes());
+        out.extend_from_slice(&(channels as u16).to_le_bytes());
+        out.extend_from_slice(&(asbd.sample_rate.round() as u32).to_le_bytes());
+        out.extend_from_slice(&((asbd.sample_rate.round() as u32) * block_align).to_le_bytes());
+        out.extend_from_slice(&(block_align as u16).to_le_bytes());
+        out.extend_from_slice(&(container_bits as u16).to_le_bytes());
+        if extensible {
+            let mask = header
+                .channel_layout
+                .as_ref()
+                .and_then(|layout| {
+                    let labels = layout.labels()?;
+                    let mask = ChannelBitmap::with_labels(&labels)?;
+                    // WAVE channels are in bitmap order
+                    (mask.labels() == labels).then_some(mask)
+                })
+                .unwrap_or_default();
+            out.extend_from_slice(&22u16.to_le_bytes());
+            out.extend_from_slice(&(valid_bits as u16).to_le_bytes());
+            out.extend_from_slice(&mask.0.to_le_bytes());
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&SUBTYPE_TAIL);
+        } else if tag != FORMAT_PCM {
+            out.extend_from_slice(&0u16.to_le_bytes());
+        }
+    });
+
+    let info: Vec<_> = header
+        .info
+        .iter()
+        .filter_map(|(key, value)| {
+            let (id, _) = INFO_KEYS.iter().find(|(_, k)| k == key)?;
+            Some((id, value))
+        })
+        .collect();
+    if !info.is_empty() {
+        chunk(out, b"LIST", |out| {
+            out.extend_from_slice(b"INFO");
+            for (id, value) in info {
+                chunk(out, id, |out| {
+                    out.extend_from_slice(value.as_bytes());
+                    out.push(0);
+                });
+            }
+        });
+    }
+
+    if !header.markers.is_empty() {
+        if header
+            .markers
+            .iter()
+            .any(|m| !(0.0..=u32::MAX as f64).contains(&m.frame_pos))
+        {
+            return Err(Error::Unsupported(file_type, "markers past 2^32 frames"));
+        }
+        chunk(out, b"cue ", |out| {
+            out.extend_from_slice(&(header.markers.len() as u32).to_le_bytes());
+            for (i, marker) in header.markers.iter().enumerate() {
+                let pos = marker.frame_pos.round() as u32;
+                out.extend_from_slice(&marker.id.to_le_bytes());
+                out.extend_from_slice(&(i as u32).to_le_bytes());
+                out.extend_from_slice(b"data");
+                out.extend_from_slice(&[0; 8]);
+                out.extend_from_slice(&pos.to_le_bytes());
+            }
+        });
+        if header.markers.iter().any(|m| !m.name.is_empty()) {
+            chunk(out, b"LIST", |out| {
+                out.extend_from_slice(b"adtl");
+                for marker in header.markers.iter().filter(|m| !m.name.is_empty()) {
+                    chunk(out, b"labl", |out| {
+                        out.extend_from_slice(&marker.id.to_le_bytes());
+                        out.extend_from_slice(marker.name.as_bytes());
+                        out.push(0);
+                    });
+                }
+            });
+        }
+    }
+
+    if has_fact(tag, extensible) {
+        chunk(out, b"fact", |out| out.extend_from_slice(&[0; 4]));
+    }
+    out.extend_from_slice(b"data");
+    out.extend_from_slice(&u32::MAX.to_le_bytes());
+    Ok(())
+}
+
+pub(super) fn finish<W: Write + Seek>(
+    out: &mut W,
+    header: &Header,
+    start: u64,
+) -> Result<(), Error> {
+    let data_end = header.data_offset + header.data_len;
+    let end = data_end + (header.data_len & 1);
+    if end != data_end {
+        patch(out, data_end, &[0])?;
+    }
+    let riff_size = end - start - 8;
+    let frames = header.total_frames();
+
+    let (tag, extensible) = format_tag(header)?;
+    if has_fact(tag, extensible) {
+        let frames = frames.min(u32::MAX as u64) as u32;
+        patch(out, header.data_offset - 12, &frames.to_le_bytes())?;
+    }
+
+    if header.file_type == FileType::Wave && riff_size <= u32::MAX as u64 {
+        patch(out, start + 4, &(riff_size as u32).to_le_bytes())?;
+        patch(
+            out,
+            header.data_offset - 4,
+            &(header.data_len as u32).to_le_bytes(),
+        )?;
+    } else {
+        let id = match header.file_type {
+            FileType::Bw64 => b"BW64",
+            _ => b"RF64",
+        };
+        patch(out, start, id)?;
+        let mut ds64 = Vec::with_capacity(8 + DS64_LEN);
+        ds64.extend_from_slice(b"ds64");
+        ds64.extend_from_slice(&(DS64_LEN as u32).to_le_bytes());
+        ds64.extend_from_slice(&riff_size.to_le_bytes());
+        ds64.extend_from_slice(&header.data_len.to_le_bytes());
+        ds64.extend_from_slice(&frames.to_le_bytes());
+        ds64.extend_from_slice(&0u32.to_le_bytes());
+        patch(out, start + 12, &ds64)?;
+    }
+    out.seek(SeekFrom::Start(end))?;
+    Ok(())
+}
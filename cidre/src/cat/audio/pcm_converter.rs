@@ -0,0 +1,1301 @@
+//! Pure-Rust linear PCM converter.
+//!
+//! Converts between any linear PCM layouts [`StreamBasicDesc`] can express
+//! (integer width, float, fixed-point, endianness, packed or aligned samples,
+//! interleaved or not) and between channel layouts. Sample rate conversion is
+//! out of scope.
+//!
+//! Layout-only changes (copy, interleave, deinterleave) move bytes and are
+//! bit-exact. Everything else goes through `f32` samples, like the canonical
+//! format of `AudioConverter`, so integers wider than 24 bits are rounded.
+
+use super::{
+    channel_layout, Buf, BufList, BufListN, ChannelLabel, ChannelLayoutTag, StreamBasicDesc,
+    StreamBasicDescError,
+};
+
+/// Number of frames converted per pass through the scratch buffers.
+const CHUNK: usize = 512;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Source format is not linear PCM.
+    SrcNotPcm,
+
+    /// Destination format is not linear PCM.
+    DstNotPcm,
+
+    InvalidSrc(StreamBasicDescError),
+
+    InvalidDst(StreamBasicDescError),
+
+    /// Sample rate conversion is not supported.
+    SampleRate {
+        src: f64,
+        dst: f64,
+    },
+
+    /// Integer samples wider than 32 bits or 4 bytes containers.
+    UnsupportedSample {
+        bits: u32,
+        bytes: u32,
+    },
+
+    /// No known mix between these layouts.
+    UnsupportedLayout(ChannelLayoutTag),
+
+    /// Channel layout tag doesn't match channels of the format.
+    LayoutChannels {
+        tag: ChannelLayoutTag,
+        channels: u32,
+    },
+
+    /// Wrong number of buffers in a buffer list.
+    BufsNum {
+        expected: usize,
+        actual: usize,
+    },
+
+    /// Buffer at `index` is null or smaller than `expected` bytes.
+    Buf {
+        index: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SrcNotPcm => f.write_str("source format is not linear pcm"),
+            Self::DstNotPcm => f.write_str("destination format is not linear pcm"),
+            Self::InvalidSrc(e) => write!(f, "invalid source format: {e}"),
+            Self::InvalidDst(e) => write!(f, "invalid destination format: {e}"),
+            Self::SampleRate { src, dst } => {
+                write!(f, "sample rate conversion {src} -> {dst} is not supported")
+            }
+            Self::UnsupportedSample { bits, bytes } => {
+                write!(f, "{bits} bits samples in {bytes} bytes are not supported")
+            }
+            Self::UnsupportedLayout(tag) => write!(f, "unsupported channel layout {tag:?}"),
+            Self::LayoutChannels { tag, channels } => {
+                write!(
+                    f,
+                    "channel layout {tag:?} doesn't match {channels} channels"
+                )
+            }
+            Self::BufsNum { expected, actual } => {
+                write!(f, "{actual} buffers, expected {expected}")
+            }
+            Self::Buf {
+                index,
+                expected,
+                actual,
+            } => write!(f, "buffer {index} has {actual} bytes, expected {expected}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Sample encoding, independent of channels and interleaving.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Sample {
+    F32 {
+        swap: bool,
+    },
+    F64 {
+        swap: bool,
+    },
+    Int {
+        bytes: usize,
+        bits: u32,
+        signed: bool,
+        fraction_bits: u32,
+        aligned_high: bool,
+        big_endian: bool,
+    },
+}
+
+impl Sample {
+    fn new(asbd: &StreamBasicDesc) -> Result<Self, Error> {
+        let bytes = asbd.sample_word_size();
+        let swap = asbd.is_big_endian() != cfg!(target_endian = "big");
+        if asbd.is_float() {
+            return Ok(if asbd.bits_per_channel == 32 {
+                Self::F32 { swap }
+            } else {
+                Self::F64 { swap }
+            });
+        }
+        if asbd.bits_per_channel > 32 || bytes > 4 {
+            return Err(Error::UnsupportedSample {
+                bits: asbd.bits_per_channel,
+                bytes,
+            });
+        }
+        Ok(Self::Int {
+            bytes: bytes as usize,
+            bits: asbd.bits_per_channel,
+            signed: asbd.is_signed_int(),
+            fraction_bits: asbd.fraction_bits(),
+            aligned_high: asbd.is_aligned_high() && !asbd.is_packed(),
+            big_endian: asbd.is_big_endian(),
+        })
+    }
+
+    fn bytes(&self) -> usize {
+        match *self {
+            Self::F32 { .. } => 4,
+            Self::F64 { .. } => 8,
+            Self::Int { bytes, .. } => bytes,
+        }
+    }
+
+    fn is_native_i16(&self) -> bool {
+        *self
+            == Self::Int {
+                bytes: 2,
+                bits: 16,
+                signed: true,
+                fraction_bits: 0,
+                aligned_high: false,
+                big_endian: cfg!(target_endian = "big"),
+            }
+    }
+
+    /// Decodes `dst.len()` contiguous samples.
+    fn decode(&self, src: &[u8], dst: &mut [f32]) {
+        debug_assert_eq!(src.len(), dst.len() * self.bytes());
+        match *self {
+            Self::F32 { swap: false } => {
+                for (d, s) in dst.iter_mut().zip(src.chunks_exact(4)) {
+                    *d = f32::from_ne_bytes([s[0], s[1], s[2], s[3]]);
+                }
+            }
+            Self::F32 { swap: true } => {
+                for (d, s) in dst.iter_mut().zip(src.chunks_exact(4)) {
+                    *d = f32::from_bits(u32::from_ne_bytes([s[0], s[1], s[2], s[3]]).swap_bytes());
+                }
+            }
+            Self::F64 { swap } => {
+                for (d, s) in dst.iter_mut().zip(src.chunks_exact(8)) {
+                    let mut b = [0u8; 8];
+                    b.copy_from_slice(s);
+                    let bits = u64::from_ne_bytes(b);
+                    *d = f64::from_bits(if swap { bits.swap_bytes() } else { bits }) as f32;
+                }
+            }
+            _ if self.is_native_i16() => {
+                let done = unsafe { arch::i16_to_f32(src.as_ptr(), dst.as_mut_ptr(), dst.len()) };
+                for (d, s) in dst[done..].iter_mut().zip(src[done * 2..].chunks_exact(2)) {
+                    *d = i16::from_ne_bytes([s[0], s[1]]) as f32 * (1.0 / 32768.0);
+                }
+            }
+            Self::Int {
+                bytes,
+                bits,
+                signed,
+                fraction_bits,
+                aligned_high,
+                big_endian,
+            } => {
+                let container = bytes as u32 * 8;
+                let mask = u64::MAX >> (64 - bits);
+                let shift = if aligned_high { container - bits } else { 0 };
+                let scale = 1.0 / (1u64 << int_scale_bits(bits, fraction_bits)) as f64;
+                let offset = if signed || fraction_bits != 0 {
+                    0
+                } else {
+                    1i64 << (bits - 1)
+                };
+                for (d, s) in dst.iter_mut().zip(src.chunks_exact(bytes)) {
+                    let mut raw = 0u64;
+                    if big_endian {
+                        for b in s {
+                            raw = (raw << 8) | *b as u64;
+                        }
+                    } else {
+                        for b in s.iter().rev() {
+                            raw = (raw << 8) | *b as u64;
+                        }
+                    }
+                    let raw = (raw >> shift) & mask;
+                    let val = if signed {
+                        ((raw << (64 - bits)) as i64) >> (64 - bits)
+                    } else {
+                        raw as i64 - offset
+                    };
+                    *d = (val as f64 * scale) as f32;
+                }
+            }
+        }
+    }
+
+    /// Encodes `src.len()` contiguous samples.
+    fn encode(&self, src: &[f32], dst: &mut [u8]) {
+        debug_assert_eq!(dst.len(), src.len() * self.bytes());
+        match *self {
+            Self::F32 { swap } => {
+                for (d, s) in dst.chunks_exact_mut(4).zip(src) {
+                    let bits = s.to_bits();
+                    let bits = if swap { bits.swap_bytes() } else { bits };
+                    d.copy_from_slice(&bits.to_ne_bytes());
+                }
+            }
+            Self::F64 { swap } => {
+                for (d, s) in dst.chunks_exact_mut(8).zip(src) {
+                    let bits = (*s as f64).to_bits();
+                    let bits = if swap { bits.swap_bytes() } else { bits };
+                    d.copy_from_slice(&bits.to_ne_bytes());
+                }
+            }
+            _ if self.is_native_i16() => {
+                let done = unsafe { arch::f32_to_i16(src.as_ptr(), dst.as_mut_ptr(), src.len()) };
+                for (d, s) in dst[done * 2..].chunks_exact_mut(2).zip(&src[done..]) {
+                    d.copy_from_slice(&f32_to_i16(*s).to_ne_bytes());
+                }
+            }
+            Self::Int {
+                bytes,
+                bits,
+                signed,
+                fraction_bits,
+                aligned_high,
+                big_endian,
+            } => {
+                let container = bytes as u32 * 8;
+                let mask = u64::MAX >> (64 - bits);
+                let shift = if aligned_high { container - bits } else { 0 };
+                let scale = (1u64 << int_scale_bits(bits, fraction_bits)) as f64;
+                let (min, max, offset) = if signed {
+                    (-(1i64 << (bits - 1)), (1i64 << (bits - 1)) - 1, 0)
+                } else if fraction_bits != 0 {
+                    (0, mask as i64, 0)
+                } else {
+                    (
+                        -(1i64 << (bits - 1)),
+                        (1i64 << (bits - 1)) - 1,
+                        1i64 << (bits - 1),
+                    )
+                };
+                let (min, max) = (min as f64, max as f64);
+                for (d, s) in dst.chunks_exact_mut(bytes).zip(src) {
+                    let val = (*s as f64 * scale).round_ties_even().clamp(min, max) as i64;
+                    let raw = (((val + offset) as u64) & mask) << shift;
+                    if big_endian {
+                        for (i, b) in d.iter_mut().rev().enumerate() {
+                            *b = (raw >> (i * 8)) as u8;
+                        }
+                    } else {
+                        for (i, b) in d.iter_mut().enumerate() {
+                            *b = (raw >> (i * 8)) as u8;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Power of two mapping integer samples to `[-1, 1)`, or the fixed-point
+/// fraction bits.
+fn int_scale_bits(bits: u32, fraction_bits: u32) -> u32 {
+    if fraction_bits != 0 {
+        fraction_bits
+    } else {
+        bits - 1
+    }
+}
+
+#[inline]
+fn f32_to_i16(val: f32) -> i16 {
+    (val * 32768.0).clamp(-32768.0, 32767.0).round_ties_even() as i16
+}
+
+/// [`channel_layout::mix_matrix`], except mono destinations get the average
+/// of the stereo downmix rather than BS.775's `(L' + R') / √2`, which clips
+/// on correlated input.
+fn mix_matrix(src: &[ChannelLabel], dst: &[ChannelLabel]) -> Option<Vec<f32>> {
+    if dst != [ChannelLabel::MONO] {
+        return channel_layout::mix_matrix(src, dst);
+    }
+    let stereo = channel_layout::mix_matrix(src, &[ChannelLabel::LEFT, ChannelLabel::RIGHT])?;
+    let (l, r) = stereo.split_at(src.len());
+    Some(l.iter().zip(r).map(|(l, r)| 0.5 * (l + r)).collect())
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Path {
+    /// Same bytes layout, plain copy.
+    Copy,
+    /// Same samples, only interleaving differs.
+    Shuffle,
+    /// Decode, mix, encode.
+    Generic,
+}
+
+/// Converts linear PCM between [`StreamBasicDesc`] layouts.
+///
+/// ```
+/// use cidre::cat::audio;
+///
+/// let src = audio::StreamBasicDescBuilder::lpcm(48_000.0, 2).int(16).build().unwrap();
+/// let dst = audio::StreamBasicDesc::common_f32(48_000.0, 2, false);
+/// let mut converter = audio::PcmConverter::new(&src, &dst).unwrap();
+///
+/// let input: [i16; 4] = [16384, -16384, 8192, -8192];
+/// let mut l = [0f32; 2];
+/// let mut r = [0f32; 2];
+///
+/// let mut src_list = audio::BufList::<1>::new();
+/// src_list.buffers[0].number_channels = 2;
+/// src_list.buffers[0].data = input.as_ptr() as _;
+/// src_list.buffers[0].data_bytes_size = 8;
+///
+/// let mut dst_list = audio::BufList::<2>::new();
+/// dst_list.buffers[0].data = l.as_mut_ptr() as _;
+/// dst_list.buffers[0].data_bytes_size = 8;
+/// dst_list.buffers[1].data = r.as_mut_ptr() as _;
+/// dst_list.buffers[1].data_bytes_size = 8;
+///
+/// // Safety: buffers point to live arrays of `data_bytes_size` bytes.
+/// unsafe { converter.convert(2, &src_list, &mut dst_list) }.unwrap();
+/// assert_eq!(l, [0.5, 0.25]);
+/// assert_eq!(r, [-0.5, -0.25]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PcmConverter {
+    src: StreamBasicDesc,
+    dst: StreamBasicDesc,
+    src_sample: Sample,
+    dst_sample: Sample,
+    path: Path,
+    /// `None` for identity.
+    matrix: Option<Vec<f32>>,
+    scratch: Vec<f32>,
+}
+
+impl PcmConverter {
+    /// Converter between formats with the same number of channels, or
+    /// between mono and stereo.
+    pub fn new(src: &StreamBasicDesc, dst: &StreamBasicDesc) -> Result<Self, Error> {
+        let default_tag = |asbd: &StreamBasicDesc| match asbd.channels_per_frame {
+            1 => ChannelLayoutTag::MONO,
+            2 => ChannelLayoutTag::STEREO,
+            n => ChannelLayoutTag(ChannelLayoutTag::DISCRETE_IN_ORDER.0 | n),
+        };
+        Self::with_layouts(src, default_tag(src), dst, default_tag(dst))
+    }
+
+    /// Converter which also remixes channels from `src_layout` to `dst_layout`.
+    pub fn with_layouts(
+        src: &StreamBasicDesc,
+        src_layout: ChannelLayoutTag,
+        dst: &StreamBasicDesc,
+        dst_layout: ChannelLayoutTag,
+    ) -> Result<Self, Error> {
+        if !src.is_pcm() {
+            return Err(Error::SrcNotPcm);
+        }
+        if !dst.is_pcm() {
+            return Err(Error::DstNotPcm);
+        }
+        src.validate().map_err(Error::InvalidSrc)?;
+        dst.validate().map_err(Error::InvalidDst)?;
+        if src.sample_rate != dst.sample_rate {
+            return Err(Error::SampleRate {
+                src: src.sample_rate,
+                dst: dst.sample_rate,
+            });
+        }
+        for (tag, asbd) in [(src_layout, src), (dst_layout, dst)] {
+            if tag.number_of_channels() != asbd.channels_per_frame {
+                return Err(Error::LayoutChannels {
+                    tag,
+                    channels: asbd.channels_per_frame,
+                });
+            }
+        }
+
+        let matrix = if src_layout == dst_layout {
+            None
+        } else {
+            let resizes = src.channels_per_frame != dst.channels_per_frame;
+            match (src_layout.labels(), dst_layout.labels()) {
+                (Some(s), Some(d)) => match mix_matrix(&s, &d) {
+                    None if resizes => return Err(Error::UnsupportedLayout(src_layout)),
+                    m => m,
+                },
+                (None, _) if resizes => return Err(Error::UnsupportedLayout(src_layout)),
+                (_, None) if resizes => return Err(Error::UnsupportedLayout(dst_layout)),
+                _ => None,
+            }
+        };
+
+        let src_sample = Sample::new(src)?;
+        let dst_sample = Sample::new(dst)?;
+
+        let path = if matrix.is_some() || src_sample != dst_sample {
+            Path::Generic
+        } else if src.is_interleaved() == dst.is_interleaved() || src.channels_per_frame == 1 {
+            Path::Copy
+        } else {
+            Path::Shuffle
+        };
+
+        let scratch = if path == Path::Generic {
+            let src_ch = src.channels_per_frame as usize;
+            let dst_ch = dst.channels_per_frame as usize;
+            vec![0.0f32; CHUNK * (src_ch + dst_ch + src_ch.max(dst_ch))]
+        } else {
+            Vec::new()
+        };
+
+        Ok(Self {
+            src: *src,
+            dst: *dst,
+            src_sample,
+            dst_sample,
+            path,
+            matrix,
+            scratch,
+        })
+    }
+
+    #[inline]
+    pub fn src_desc(&self) -> &StreamBasicDesc {
+        &self.src
+    }
+
+    #[inline]
+    pub fn dst_desc(&self) -> &StreamBasicDesc {
+        &self.dst
+    }
+
+    /// Mix matrix, `dst` channels rows by `src` channels columns. `None` if
+    /// channels are passed through.
+    #[inline]
+    pub fn mix_matrix(&self) -> Option<&[f32]> {
+        self.matrix.as_deref()
+    }
+
+    /// Converts `frames` frames between byte slices, one slice per buffer of
+    /// the source and destination layouts.
+    ///
+    /// Slices may be longer than `frames` need, only the leading bytes are
+    /// read and written.
+    pub fn convert_slices(
+        &mut self,
+        frames: usize,
+        src: &[&[u8]],
+        dst: &mut [&mut [u8]],
+    ) -> Result<(), Error> {
+        let src_bytes = frames * self.src.bytes_per_frame as usize;
+        let dst_bytes = frames * self.dst.bytes_per_frame as usize;
+        check_lens(
+            src.iter().map(|s| s.len()),
+            src.len(),
+            self.src.buf_list_bufs_num(),
+            src_bytes,
+        )?;
+        check_lens(
+            dst.iter().map(|d| d.len()),
+            dst.len(),
+            self.dst.buf_list_bufs_num(),
+            dst_bytes,
+        )?;
+
+        let src: Vec<&[u8]> = src.iter().map(|s| &s[..src_bytes]).collect();
+        let mut dst: Vec<&mut [u8]> = dst.iter_mut().map(|d| &mut d[..dst_bytes]).collect();
+        self.run(frames, &src, &mut dst);
+        Ok(())
+    }
+
+    /// Converts `frames` frames. Destination buffer sizes are set to the
+    /// converted data size.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::convert_bufs`].
+    pub unsafe fn convert<const N: usize, const M: usize>(
+        &mut self,
+        frames: usize,
+        src: &BufList<N>,
+        dst: &mut BufList<M>,
+    ) -> Result<(), Error> {
+        let src_n = (src.number_buffers as usize).min(N);
+        let dst_n = (dst.number_buffers as usize).min(M);
+        self.convert_bufs(frames, &src.buffers[..src_n], &mut dst.buffers[..dst_n])
+    }
+
+    /// Same as [`Self::convert`] for variable length buffer lists.
+    ///
+    /// # Safety
+    ///
+    /// See [`Self::convert_bufs`].
+    pub unsafe fn convert_n(
+        &mut self,
+        frames: usize,
+        src: &BufListN,
+        dst: &mut BufListN,
+    ) -> Result<(), Error> {
+        let src_n = src.number_buffers().min(src.buffers().len());
+        let dst_n = dst.number_buffers().min(dst.buffers().len());
+        self.convert_bufs(
+            frames,
+            &src.buffers()[..src_n],
+            &mut dst.buffers_mut()[..dst_n],
+        )
+    }
+
+    /// Converts `frames` frames between audio buffers. Destination buffer
+    /// sizes are set to the converted data size.
+    ///
+    /// Counts, null pointers and `data_bytes_size` are checked, the memory
+    /// behind the pointers can't be. Use [`Self::convert_slices`] when the
+    /// data is already in Rust slices.
+    ///
+    /// # Safety
+    ///
+    /// Every non null `data` must be valid for reads (`src`) or writes
+    /// (`dst`) of `data_bytes_size` bytes for the duration of the call.
+    /// Destination memory must not overlap source memory or the memory of
+    /// another destination buffer, debug builds assert this.
+    pub unsafe fn convert_bufs(
+        &mut self,
+        frames: usize,
+        src: &[Buf],
+        dst: &mut [Buf],
+    ) -> Result<(), Error> {
+        let src_bytes = frames * self.src.bytes_per_frame as usize;
+        let dst_bytes = frames * self.dst.bytes_per_frame as usize;
+        check_bufs(src, self.src.buf_list_bufs_num(), src_bytes)?;
+        check_bufs(dst, self.dst.buf_list_bufs_num(), dst_bytes)?;
+        debug_assert!(
+            writes_disjoint(src, src_bytes, dst, dst_bytes),
+            "destination buffers overlap"
+        );
+
+        let src: Vec<&[u8]> = src
+            .iter()
+            .map(|b| std::slice::from_raw_parts(b.data, src_bytes))
+            .collect();
+        let mut dst: Vec<&mut [u8]> = dst
+            .iter_mut()
+            .map(|b| {
+                b.data_bytes_size = dst_bytes as u32;
+                std::slice::from_raw_parts_mut(b.data, dst_bytes)
+            })
+            .collect();
+        self.run(frames, &src, &mut dst);
+        Ok(())
+    }
+
+    /// Slices are checked and cut to `frames` by the callers.
+    fn run(&mut self, frames: usize, src: &[&[u8]], dst: &mut [&mut [u8]]) {
+        match self.path {
+            Path::Copy => {
+                for (d, s) in dst.iter_mut().zip(src) {
+                    d.copy_from_slice(s);
+                }
+            }
+            Path::Shuffle => {
+                let bytes = self.src_sample.bytes();
+                if self.src.is_interleaved() {
+                    deinterleave_bytes(src[0], dst, bytes);
+                } else {
+                    interleave_bytes(src, &mut *dst[0], bytes);
+                }
+            }
+            Path::Generic => self.convert_generic(frames, src, dst),
+        }
+    }
+
+    fn convert_generic(&mut self, frames: usize, src: &[&[u8]], dst: &mut [&mut [u8]]) {
+        let src_ch = self.src.channels_per_frame as usize;
+        let dst_ch = self.dst.channels_per_frame as usize;
+        let src_word = self.src_sample.bytes();
+        let dst_word = self.dst_sample.bytes();
+        let (src_planar, rest) = self.scratch.split_at_mut(CHUNK * src_ch);
+        let (dst_planar, tmp) = rest.split_at_mut(CHUNK * dst_ch);
+
+        let mut start = 0;
+        while start < frames {
+            let n = CHUNK.min(frames - start);
+
+            if self.src.is_interleaved() {
+                let bpf = self.src.bytes_per_frame as usize;
+                let tmp = &mut tmp[..n * src_ch];
+                self.src_sample
+                    .decode(&src[0][start * bpf..(start + n) * bpf], tmp);
+                deinterleave_f32(tmp, src_planar, src_ch, n);
+            } else {
+                for (c, s) in src.iter().enumerate() {
+                    self.src_sample.decode(
+                        &s[start * src_word..(start + n) * src_word],
+                        &mut src_planar[c * CHUNK..c * CHUNK + n],
+                    );
+                }
+            }
+
+            let planar: &[f32] = match &self.matrix {
+                Some(matrix) => {
+                    mix(matrix, src_planar, dst_planar, src_ch, dst_ch, n);
+                    dst_planar
+                }
+                None => src_planar,
+            };
+
+            if self.dst.is_interleaved() {
+                let bpf = self.dst.bytes_per_frame as usize;
+                let tmp = &mut tmp[..n * dst_ch];
+                interleave_f32(planar, tmp, dst_ch, n);
+                self.dst_sample
+                    .encode(tmp, &mut dst[0][start * bpf..(start + n) * bpf]);
+            } else {
+                for (c, d) in dst.iter_mut().enumerate() {
+                    self.dst_sample.encode(
+                        &planar[c * CHUNK..c * CHUNK + n],
+                        &mut d[start * dst_word..(start + n) * dst_word],
+                    );
+                }
+            }
+
+            start += n;
+        }
+    }
+}
+
+fn check_lens(
+    lens: impl Iterator<Item = usize>,
+    count: usize,
+    expected: usize,
+    bytes: usize,
+) -> Result<(), Error> {
+    if count != expected {
+        return Err(Error::BufsNum {
+            expected,
+            actual: count,
+        });
+    }
+    for (index, actual) in lens.enumerate() {
+        if actual < bytes {
+            return Err(Error::Buf {
+                index,
+                expected: bytes,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn check_bufs(bufs: &[Buf], expected: usize, bytes: usize) -> Result<(), Error> {
+    if bufs.len() != expected {
+        return Err(Error::BufsNum {
+            expected,
+            actual: bufs.len(),
+        });
+    }
+    for (index, buf) in bufs.iter().enumerate() {
+        let actual = if buf.data.is_null() {
+            0
+        } else {
+            buf.data_bytes_size as usize
+        };
+        if actual < bytes || buf.data.is_null() {
+            return Err(Error::Buf {
+                index,
+                expected: bytes,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Whether no destination range overlaps a source range or another
+/// destination range.
+fn writes_disjoint(src: &[Buf], src_bytes: usize, dst: &[Buf], dst_bytes: usize) -> bool {
+    let overlap = |a: *const u8, a_len: usize, b: *const u8, b_len: usize| {
+        let (a, b) = (a as usize, b as usize);
+        a < b + b_len && b < a + a_len
+    };
+    dst.iter().enumerate().all(|(i, d)| {
+        dst[i + 1..]
+            .iter()
+            .all(|o| !overlap(d.data, dst_bytes, o.data, dst_bytes))
+            && src
+                .iter()
+                .all(|s| !overlap(d.data, dst_bytes, s.data, src_bytes))
+    })
+}
+
+/// Channel `c` of the planar scratch starts at `c * CHUNK`.
+fn deinterleave_f32(src: &[f32], planar: &mut [f32], channels: usize, n: usize) {
+    if channels == 1 {
+        planar[..n].copy_from_slice(&src[..n]);
+        return;
+    }
+    if channels == 2 {
+        let (l, r) = planar.split_at_mut(CHUNK);
+        let done = unsafe {
+            arch::deinterleave2_f32(
+                src.as_ptr() as _,
+                l.as_mut_ptr() as _,
+                r.as_mut_ptr() as _,
+                n,
+            )
+        };
+        for i in done..n {
+            l[i] = src[i * 2];
+            r[i] = src[i * 2 + 1];
+        }
+        return;
+    }
+    for (i, frame) in src.chunks_exact(channels).take(n).enumerate() {
+        for (c, s) in frame.iter().enumerate() {
+            planar[c * CHUNK + i] = *s;
+        }
+    }
+}
+
+fn interleave_f32(planar: &[f32], dst: &mut [f32], channels: usize, n: usize) {
+    if channels == 1 {
+        dst[..n].copy_from_slice(&planar[..n]);
+        return;
+    }
+    if channels == 2 {
+        let (l, r) = planar.split_at(CHUNK);
+        let done = unsafe {
+            arch::interleave2_f32(l.as_ptr() as _, r.as_ptr() as _, dst.as_mut_ptr() as _, n)
+        };
+        for i in done..n {
+            dst[i * 2] = l[i];
+            dst[i * 2 + 1] = r[i];
+        }
+        return;
+    }
+    for (i, frame) in dst.chunks_exact_mut(channels).take(n).enumerate() {
+        for (c, d) in frame.iter_mut().enumerate() {
+            *d = planar[c * CHUNK + i];
+        }
+    }
+}
+
+fn mix(matrix: &[f32], src: &[f32], dst: &mut [f32], src_ch: usize, dst_ch: usize, n: usize) {
+    for o in 0..dst_ch {
+        let out = &mut dst[o * CHUNK..o * CHUNK + n];
+        out.fill(0.0);
+        for i in 0..src_ch {
+            let gain = matrix[o * src_ch + i];
+            if gain == 0.0 {
+                continue;
+            }
+            let input = &src[i * CHUNK..i * CHUNK + n];
+            for (d, s) in out.iter_mut().zip(input) {
+                *d += gain * s;
+            }
+        }
+    }
+}
+
+fn deinterleave_bytes(src: &[u8], dst: &mut [&mut [u8]], word: usize) {
+    let channels = dst.len();
+    if channels == 2 && word == 4 {
+        let n = src.len() / 8;
+        let (l, r) = dst.split_at_mut(1);
+        let (l, r) = (&mut *l[0], &mut *r[0]);
+        let done =
+            unsafe { arch::deinterleave2_f32(src.as_ptr(), l.as_mut_ptr(), r.as_mut_ptr(), n) };
+        for i in done..n {
+            l[i * 4..i * 4 + 4].copy_from_slice(&src[i * 8..i * 8 + 4]);
+            r[i * 4..i * 4 + 4].copy_from_slice(&src[i * 8 + 4..i * 8 + 8]);
+        }
+        return;
+    }
+    for (i, frame) in src.chunks_exact(word * channels).enumerate() {
+        for (d, s) in dst.iter_mut().zip(frame.chunks_exact(word)) {
+            d[i * word..(i + 1) * word].copy_from_slice(s);
+        }
+    }
+}
+
+fn interleave_bytes(src: &[&[u8]], dst: &mut [u8], word: usize) {
+    let channels = src.len();
+    if channels == 2 && word == 4 {
+        let n = dst.len() / 8;
+        let (l, r) = (src[0], src[1]);
+        let done = unsafe { arch::interleave2_f32(l.as_ptr(), r.as_ptr(), dst.as_mut_ptr(), n) };
+        for i in done..n {
+            dst[i * 8..i * 8 + 4].copy_from_slice(&l[i * 4..i * 4 + 4]);
+            dst[i * 8 + 4..i * 8 + 8].copy_from_slice(&r[i * 4..i * 4 + 4]);
+        }
+        return;
+    }
+    for (i, frame) in dst.chunks_exact_mut(word * channels).enumerate() {
+        for (d, s) in frame.chunks_exact_mut(word).zip(src) {
+            d.copy_from_slice(&s[i * word..(i + 1) * word]);
+        }
+    }
+}
+
+/// SIMD kernels. Each returns the number of elements processed, the caller
+/// finishes the tail. Pointers may be unaligned.
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    use std::arch::aarch64::*;
+
+    pub unsafe fn i16_to_f32(src: *const u8, dst: *mut f32, n: usize) -> usize {
+        let scale = vdupq_n_f32(1.0 / 32768.0);
+        let mut i = 0;
+        while i + 8 <= n {
+            let v = vld1q_s16(src.add(i * 2) as *const i16);
+            let lo = vcvtq_f32_s32(vmovl_s16(vget_low_s16(v)));
+            let hi = vcvtq_f32_s32(vmovl_high_s16(v));
+            vst1q_f32(dst.add(i), vmulq_f32(lo, scale));
+            vst1q_f32(dst.add(i + 4), vmulq_f32(hi, scale));
+            i += 8;
+        }
+        i
+    }
+
+    pub unsafe fn f32_to_i16(src: *const f32, dst: *mut u8, n: usize) -> usize {
+        let scale = vdupq_n_f32(32768.0);
+        let min = vdupq_n_f32(-32768.0);
+        let max = vdupq_n_f32(32767.0);
+        let mut i = 0;
+        while i + 8 <= n {
+            let a = vmulq_f32(vld1q_f32(src.add(i)), scale);
+            let b = vmulq_f32(vld1q_f32(src.add(i + 4)), scale);
+            let a = vcvtnq_s32_f32(vminq_f32(vmaxq_f32(a, min), max));
+            let b = vcvtnq_s32_f32(vminq_f32(vmaxq_f32(b, min), max));
+            vst1q_s16(
+                dst.add(i * 2) as *mut i16,
+                vcombine_s16(vqmovn_s32(a), vqmovn_s32(b)),
+            );
+            i += 8;
+        }
+        i
+    }
+
+    pub unsafe fn interleave2_f32(l: *const u8, r: *const u8, dst: *mut u8, n: usize) -> usize {
+        let (l, r, dst) = (l as *const f32, r as *const f32, dst as *mut f32);
+        let mut i = 0;
+        while i + 4 <= n {
+            let v = float32x4x2_t(vld1q_f32(l.add(i)), vld1q_f32(r.add(i)));
+            vst2q_f32(dst.add(i * 2), v);
+            i += 4;
+        }
+        i
+    }
+
+    pub unsafe fn deinterleave2_f32(src: *const u8, l: *mut u8, r: *mut u8, n: usize) -> usize {
+        let (src, l, r) = (src as *const f32, l as *mut f32, r as *mut f32);
+        let mut i = 0;
+        while i + 4 <= n {
+            let v = vld2q_f32(src.add(i * 2));
+            vst1q_f32(l.add(i), v.0);
+            vst1q_f32(r.add(i), v.1);
+            i += 4;
+        }
+        i
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use std::arch::x86_64::*;
+
+    pub unsafe fn i16_to_f32(src: *const u8, dst: *mut f32, n: usize) -> usize {
+        let scale = _mm_set1_ps(1.0 / 32768.0);
+        let mut i = 0;
+        while i + 8 <= n {
+            let v = _mm_loadu_si128(src.add(i * 2) as *const __m128i);
+            let lo = _mm_srai_epi32::<16>(_mm_unpacklo_epi16(v, v));
+            let hi = _mm_srai_epi32::<16>(_mm_unpackhi_epi16(v, v));
+            _mm_storeu_ps(dst.add(i), _mm_mul_ps(_mm_cvtepi32_ps(lo), scale));
+            _mm_storeu_ps(dst.add(i + 4), _mm_mul_ps(_mm_cvtepi32_ps(hi), scale));
+            i += 8;
+        }
+        i
+    }
+
+    pub unsafe fn f32_to_i16(src: *const f32, dst: *mut u8, n: usize) -> usize {
+        let scale = _mm_set1_ps(32768.0);
+        let min = _mm_set1_ps(-32768.0);
+        let max = _mm_set1_ps(32767.0);
+        let mut i = 0;
+        while i + 8 <= n {
+            let a = _mm_mul_ps(_mm_loadu_ps(src.add(i)), scale);
+            let b = _mm_mul_ps(_mm_loadu_ps(src.add(i + 4)), scale);
+            let a = _mm_cvtps_epi32(_mm_min_ps(_mm_max_ps(a, min), max));
+            let b = _mm_cvtps_epi32(_mm_min_ps(_mm_max_ps(b, min), max));
+            _mm_storeu_si128(dst.add(i * 2) as *mut __m128i, _mm_packs_epi32(a, b));
+            i += 8;
+        }
+        i
+    }
+
+    pub unsafe fn interleave2_f32(l: *const u8, r: *const u8, dst: *mut u8, n: usize) -> usize {
+        let (l, r, dst) = (l as *const f32, r as *const f32, dst as *mut f32);
+        let mut i = 0;
+        while i + 4 <= n {
+            let a = _mm_loadu_ps(l.add(i));
+            let b = _mm_loadu_ps(r.add(i));
+            _mm_storeu_ps(dst.add(i * 2), _mm_unpacklo_ps(a, b));
+            _mm_storeu_ps(dst.add(i * 2 + 4), _mm_unpackhi_ps(a, b));
+            i += 4;
+        }
+        i
+    }
+
+    pub unsafe fn deinterleave2_f32(src: *const u8, l: *mut u8, r: *mut u8, n: usize) -> usize {
+        let (src, l, r) = (src as *const f32, l as *mut f32, r as *mut f32);
+        let mut i = 0;
+        while i + 4 <= n {
+            let a = _mm_loadu_ps(src.add(i * 2));
+            let b = _mm_loadu_ps(src.add(i * 2 + 4));
+            _mm_storeu_ps(l.add(i), _mm_shuffle_ps::<0b10_00_10_00>(a, b));
+            _mm_storeu_ps(r.add(i), _mm_shuffle_ps::<0b11_01_11_01>(a, b));
+            i += 4;
+        }
+        i
+    }
+}
+
+#[cfg(not(any(target_arch = "aarch64", target_arch = "x86_64")))]
+mod arch {
+    pub unsafe fn i16_to_f32(_src: *const u8, _dst: *mut f32, _n: usize) -> usize {
+        0
+    }
+
+    pub unsafe fn f32_to_i16(_src: *const f32, _dst: *mut u8, _n: usize) -> usize {
+        0
+    }
+
+    pub unsafe fn interleave2_f32(_l: *const u8, _r: *const u8, _dst: *mut u8, _n: usize) -> usize {
+        0
+    }
+
+    pub unsafe fn deinterleave2_f32(_src: *const u8, _l: *mut u8, _r: *mut u8, _n: usize) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{
+        Buf, BufListN, ChannelLayoutTag, PcmConverter, PcmConverterError as Error, StreamBasicDesc,
+        StreamBasicDescBuilder as Builder,
+    };
+
+    fn buf<T>(data: &mut [T]) -> Buf {
+        Buf {
+            number_channels: 1,
+            data_bytes_size: std::mem::size_of_val(data) as u32,
+            data: data.as_mut_ptr() as _,
+        }
+    }
+
+    #[test]
+    fn interleave_f32() {
+        let src = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        let dst = StreamBasicDesc::common_f32(48_000.0, 2, true);
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+
+        const N: usize = 1027;
+        let mut l: Vec<f32> = (0..N).map(|i| i as f32).collect();
+        let mut r: Vec<f32> = (0..N).map(|i| -(i as f32)).collect();
+        let mut lr = vec![0.0f32; N * 2];
+        let mut out = [buf(&mut lr)];
+        unsafe { conv.convert_bufs(N, &[buf(&mut l), buf(&mut r)], &mut out) }.unwrap();
+        for i in 0..N {
+            assert_eq!(lr[i * 2], l[i]);
+            assert_eq!(lr[i * 2 + 1], r[i]);
+        }
+
+        let mut back = PcmConverter::new(&dst, &src).unwrap();
+        let mut l2 = vec![0.0f32; N];
+        let mut r2 = vec![0.0f32; N];
+        unsafe { back.convert_bufs(N, &[buf(&mut lr)], &mut [buf(&mut l2), buf(&mut r2)]) }
+            .unwrap();
+        assert_eq!(l, l2);
+        assert_eq!(r, r2);
+    }
+
+    #[test]
+    fn i16_simd_matches_scalar() {
+        let src = Builder::lpcm(44_100.0, 2).int(16).build().unwrap();
+        let dst = StreamBasicDesc::common_f32(44_100.0, 2, false);
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+        let mut back = PcmConverter::new(&dst, &src).unwrap();
+
+        let mut input: Vec<i16> = (i16::MIN..=i16::MAX).step_by(7).collect();
+        input.truncate(input.len() / 2 * 2);
+        let frames = input.len() / 2;
+        let mut l = vec![0.0f32; frames];
+        let mut r = vec![0.0f32; frames];
+        unsafe { conv.convert_bufs(frames, &[buf(&mut input)], &mut [buf(&mut l), buf(&mut r)]) }
+            .unwrap();
+        for i in 0..frames {
+            assert_eq!(l[i], input[i * 2] as f32 / 32768.0);
+            assert_eq!(r[i], input[i * 2 + 1] as f32 / 32768.0);
+        }
+
+        l[0] = 2.0;
+        r[0] = -2.0;
+        l[1] = 0.5 / 32768.0;
+        r[1] = 1.5 / 32768.0;
+        let mut output = vec![0i16; frames * 2];
+        unsafe { back.convert_bufs(frames, &[buf(&mut l), buf(&mut r)], &mut [buf(&mut output)]) }
+            .unwrap();
+        assert_eq!(&output[..4], &[i16::MAX, i16::MIN, 0, 2]);
+        assert_eq!(&output[4..], &input[4..]);
+    }
+
+    #[test]
+    fn i24_big_endian() {
+        let src = Builder::lpcm(96_000.0, 1)
+            .int(24)
+            .big_endian(true)
+            .build()
+            .unwrap();
+        assert_eq!(src.bytes_per_frame, 3);
+        let dst = StreamBasicDesc::common_f32(96_000.0, 1, true);
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+
+        let mut input = [0x40u8, 0, 0, 0xc0, 0, 0, 0, 0, 1, 0x7f, 0xff, 0xff];
+        let mut output = [0f32; 4];
+        unsafe { conv.convert_bufs(4, &[buf(&mut input)], &mut [buf(&mut output)]) }.unwrap();
+        assert_eq!(output, [0.5, -0.5, 1.0 / 8388608.0, 8388607.0 / 8388608.0]);
+
+        let mut back = PcmConverter::new(&dst, &src).unwrap();
+        let mut round_trip = [0u8; 12];
+        unsafe { back.convert_bufs(4, &[buf(&mut output)], &mut [buf(&mut round_trip)]) }.unwrap();
+        assert_eq!(round_trip, input);
+    }
+
+    #[test]
+    fn aligned_high_and_fixed_point() {
+        let src = Builder::lpcm(48_000.0, 1)
+            .int(24)
+            .aligned_high(4)
+            .build()
+            .unwrap();
+        let dst = Builder::lpcm(48_000.0, 1).fixed_8_24().build().unwrap();
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+
+        // 0.5 in 24 bits aligned high is 0x40_0000 << 8
+        let mut input = [0x4000_0000u32, 0xc000_0000];
+        let mut output = [0i32; 2];
+        unsafe { conv.convert_bufs(2, &[buf(&mut input)], &mut [buf(&mut output)]) }.unwrap();
+        assert_eq!(output, [1 << 23, -(1 << 23)]);
+
+        let u8_desc = Builder::lpcm(48_000.0, 1).uint(8).build().unwrap();
+        let mut conv = PcmConverter::new(&dst, &u8_desc).unwrap();
+        let mut fixed = [1 << 23, -(1 << 23), 4 << 24];
+        let mut bytes = [0u8; 3];
+        unsafe { conv.convert_bufs(3, &[buf(&mut fixed)], &mut [buf(&mut bytes)]) }.unwrap();
+        assert_eq!(bytes, [192, 64, 255]);
+    }
+
+    #[test]
+    fn mix() {
+        let mono = StreamBasicDesc::common_f32(48_000.0, 1, false);
+        let stereo = StreamBasicDesc::common_f32(48_000.0, 2, false);
+
+        let mut conv = PcmConverter::new(&mono, &stereo).unwrap();
+        assert_eq!(conv.mix_matrix(), Some(&[1.0, 1.0][..]));
+        let mut m = [0.25f32; 3];
+        let mut l = [0f32; 3];
+        let mut r = [0f32; 3];
+        unsafe { conv.convert_bufs(3, &[buf(&mut m)], &mut [buf(&mut l), buf(&mut r)]) }.unwrap();
+        assert_eq!(l, m);
+        assert_eq!(r, m);
+
+        let conv = PcmConverter::new(&stereo, &mono).unwrap();
+        assert_eq!(conv.mix_matrix(), Some(&[0.5, 0.5][..]));
+
+        let surround = StreamBasicDesc::common_f32(48_000.0, 6, true);
+        assert_eq!(
+            PcmConverter::new(&surround, &stereo).unwrap_err(),
+            Error::UnsupportedLayout(ChannelLayoutTag(ChannelLayoutTag::DISCRETE_IN_ORDER.0 | 6))
+        );
+
+        let mut conv = PcmConverter::with_layouts(
+            &surround,
+            ChannelLayoutTag::MPEG_5_1_A,
+            &stereo,
+            ChannelLayoutTag::STEREO,
+        )
+        .unwrap();
+        let h = std::f32::consts::FRAC_1_SQRT_2;
+        #[rustfmt::skip]
+        assert_eq!(conv.mix_matrix().unwrap(), &[
+            1.0, 0.0, h, 0.0, h, 0.0,
+            0.0, 1.0, h, 0.0, 0.0, h,
+        ]);
+
+        // L R C LFE Ls Rs
+        let mut input = [0.1f32, 0.2, 0.3, 1.0, 0.4, 0.5];
+        unsafe { conv.convert_bufs(1, &[buf(&mut input)], &mut [buf(&mut l), buf(&mut r)]) }
+            .unwrap();
+        assert_eq!(l[0], 0.1 + h * 0.3 + h * 0.4);
+        assert_eq!(r[0], 0.2 + h * 0.3 + h * 0.5);
+
+        assert_eq!(
+            PcmConverter::with_layouts(
+                &surround,
+                ChannelLayoutTag::MPEG_5_1_A,
+                &stereo,
+                ChannelLayoutTag::MONO
+            )
+            .unwrap_err(),
+            Error::LayoutChannels {
+                tag: ChannelLayoutTag::MONO,
+                channels: 2
+            }
+        );
+    }
+
+    #[test]
+    fn buf_list_n() {
+        let src = Builder::lpcm(48_000.0, 3).int(16).build().unwrap();
+        let dst = Builder::lpcm(48_000.0, 3)
+            .float(64)
+            .non_interleaved()
+            .build()
+            .unwrap();
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+
+        let frames = 1000;
+        let mut input: Vec<i16> = (0..frames * 3).map(|i| (i % 3) as i16 * 1024).collect();
+        let mut planes = vec![vec![1.0f64; frames]; 3];
+
+        let mut src_list = BufListN::with_desc(&src, frames);
+        src_list.buffers_mut()[0].data = input.as_mut_ptr() as _;
+        let mut dst_list = BufListN::with_desc(&dst, frames);
+        for (b, p) in dst_list.buffers_mut().iter_mut().zip(&mut planes) {
+            b.data = p.as_mut_ptr() as _;
+        }
+        unsafe { conv.convert_n(frames, &src_list, &mut dst_list) }.unwrap();
+        for (c, plane) in planes.iter().enumerate() {
+            assert!(plane.iter().all(|v| *v == c as f64 / 32.0));
+        }
+
+        let mut small = BufListN::with_desc(&dst, frames - 1);
+        for (b, p) in small.buffers_mut().iter_mut().zip(&mut planes) {
+            b.data = p.as_mut_ptr() as _;
+        }
+        assert_eq!(
+            unsafe { conv.convert_n(frames, &src_list, &mut small) },
+            Err(Error::Buf {
+                index: 0,
+                expected: frames * 8,
+                actual: (frames - 1) * 8
+            })
+        );
+    }
+
+    #[test]
+    fn slices() {
+        let src = Builder::lpcm(48_000.0, 2).int(16).build().unwrap();
+        let dst = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        let mut conv = PcmConverter::new(&src, &dst).unwrap();
+
+        let input: Vec<u8> = [16384i16, -16384, 8192, -8192]
+            .iter()
+            .flat_map(|v| v.to_ne_bytes())
+            .collect();
+        let mut l = [0u8; 12];
+        let mut r = [0u8; 12];
+        conv.convert_slices(2, &[&input], &mut [&mut l, &mut r])
+            .unwrap();
+        assert_eq!(
+            &l[..8],
+            [0.5f32.to_ne_bytes(), 0.25f32.to_ne_bytes()].concat()
+        );
+        assert_eq!(
+            &r[..8],
+            [(-0.5f32).to_ne_bytes(), (-0.25f32).to_ne_bytes()].concat()
+        );
+        assert_eq!(&l[8..], [0; 4]);
+
+        assert_eq!(
+            conv.convert_slices(3, &[&input], &mut [&mut l, &mut r]),
+            Err(Error::Buf {
+                index: 0,
+                expected: 12,
+                actual: 8
+            })
+        );
+        assert_eq!(
+            conv.convert_slices(2, &[&input], &mut [&mut l[..7], &mut r]),
+            Err(Error::Buf {
+                index: 0,
+                expected: 8,
+                actual: 7
+            })
+        );
+        assert_eq!(
+            conv.convert_slices(2, &[&input], &mut [&mut l]),
+            Err(Error::BufsNum {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn overlapping_bufs() {
+        let src = Builder::lpcm(48_000.0, 2).int(16).build().unwrap();
+        let dst = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        let input = [0i16; 4];
+        let mut planes = [0f32; 4];
+        let raw = |data: *mut u8, size: usize| Buf {
+            number_channels: 1,
+            data_bytes_size: size as u32,
+            data,
+        };
+        let src_bufs = [Buf {
+            number_channels: 2,
+            data_bytes_size: 8,
+            data: input.as_ptr() as *mut u8,
+        }];
+        let base = planes.as_mut_ptr() as *mut u8;
+        let disjoint = [raw(base, 8), raw(unsafe { base.add(8) }, 8)];
+        assert!(super::writes_disjoint(&src_bufs, 8, &disjoint, 8));
+        let shared = [raw(base, 8), raw(unsafe { base.add(4) }, 8)];
+        assert!(!super::writes_disjoint(&src_bufs, 8, &shared, 8));
+        let aliased = [raw(base, 8), raw(input.as_ptr() as *mut u8, 8)];
+        assert!(!super::writes_disjoint(&src_bufs, 8, &aliased, 8));
+
+        if cfg!(debug_assertions) {
+            let mut conv = PcmConverter::new(&src, &dst).unwrap();
+            let mut shared = shared;
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| unsafe {
+                conv.convert_bufs(2, &src_bufs, &mut shared)
+            }));
+            assert!(res.is_err());
+        }
+    }
+
+    #[test]
+    fn rejects() {
+        let f32 = StreamBasicDesc::common_f32(48_000.0, 2, true);
+        let aac = Builder::compressed(crate::cat::audio::Format::MPEG4_AAC, 48_000.0, 2, 1024)
+            .build()
+            .unwrap();
+        assert_eq!(PcmConverter::new(&aac, &f32).unwrap_err(), Error::SrcNotPcm);
+        let f32_44 = StreamBasicDesc::common_f32(44_100.0, 2, true);
+        assert_eq!(
+            PcmConverter::new(&f32, &f32_44).unwrap_err(),
+            Error::SampleRate {
+                src: 48_000.0,
+                dst: 44_100.0
+            }
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! SMPTE timecode arithmetic.
+//!
+//! Timecodes are counted in frames since `00:00:00:00` and wrap at 24 hours.
+//! Drop-frame types skip frame numbers `00` and `01` (`00`..`03` at 60 fps) at
+//! the start of every minute not divisible by ten, so that the label keeps
+//! up with wall clock time at the NTSC rates.
+
+#[cfg(feature = "cm")]
+use crate::cm;
+
+use super::{SMPTETime, SMPTETimeFlags, SMPTETimeType};
+
+/// Subframes per frame used when converting from sample times, one per bit
+/// of an LTC frame.
+const SUBFRAMES: i16 = 80;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Not `HH:MM:SS:FF`.
+    Syntax,
+
+    /// A field is out of range for the time type.
+    Range,
+
+    /// Frame number skipped by drop-frame counting.
+    Dropped,
+
+    /// Unknown [`SMPTETimeType`].
+    Type(SMPTETimeType),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax => f.write_str("expected HH:MM:SS:FF"),
+            Self::Range => f.write_str("timecode field out of range"),
+            Self::Dropped => f.write_str("frame number does not exist in drop-frame timecode"),
+            Self::Type(ty) => write!(f, "unknown SMPTE time type {}", ty.0),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl SMPTETimeType {
+    /// Frames counted per timecode second: 24, 25, 30, 50 or 60.
+    pub const fn fps(self) -> Option<u32> {
+        Some(match self {
+            Self::_24 | Self::_23_98 => 24,
+            Self::_25 => 25,
+            Self::_30_DROP | Self::_30 | Self::_29_97 | Self::_29_97_DROP => 30,
+            Self::_50 => 50,
+            Self::_60 | Self::_59_94 | Self::_60_DROP | Self::_59_94_DROP => 60,
+            _ => return None,
+        })
+    }
+
+    /// Real frame rate as `(numerator, denominator)`, `30000 / 1001` for 29.97.
+    ///
+    /// `_30_DROP` and `_60_DROP` count drop-frame labels at integer rates.
+    pub const fn frame_rate(self) -> Option<(u32, u32)> {
+        Some(match self {
+            Self::_23_98 => (24000, 1001),
+            Self::_29_97 | Self::_29_97_DROP => (30000, 1001),
+            Self::_59_94 | Self::_59_94_DROP => (60000, 1001),
+            _ => match self.fps() {
+                Some(fps) => (fps, 1),
+                None => return None,
+            },
+        })
+    }
+
+    #[inline]
+    pub const fn is_drop_frame(self) -> bool {
+        self.dropped_frames() != 0
+    }
+
+    /// Frame numbers skipped at the start of each minute not divisible by ten.
+    pub const fn dropped_frames(self) -> u32 {
+        match self {
+            Self::_30_DROP | Self::_29_97_DROP => 2,
+            Self::_60_DROP | Self::_59_94_DROP => 4,
+            _ => 0,
+        }
+    }
+
+    /// Frames between `00:00:00:00` and `24:00:00:00`.
+    pub const fn frames_per_day(self) -> Option<i64> {
+        match self.fps() {
+            Some(fps) => Some(144 * frames_per_10_min(fps as i64, self.dropped_frames() as i64)),
+            None => None,
+        }
+    }
+
+    /// Duration of one frame, as stored in time code format descriptions.
+    #[cfg(feature = "cm")]
+    pub const fn frame_duration(self) -> Option<cm::Time> {
+        match self.frame_rate() {
+            Some((num, den)) => Some(cm::Time::new(den as i64, num as i32)),
+            None => None,
+        }
+    }
+
+    /// Frame duration, frame quanta and flags for
+    /// [`cm::TimeCodeFormatDesc::time_code`].
+    #[cfg(all(target_vendor = "apple", feature = "cm"))]
+    pub fn time_code_desc_params(self) -> Option<(cm::Time, u32, cm::TimeCodeFlags)> {
+        let mut flags = cm::TimeCodeFlags::_24_HOUR_MAX;
+        if self.is_drop_frame() {
+            flags |= cm::TimeCodeFlags::DROP_FRAME;
+        }
+        Some((self.frame_duration()?, self.fps()?, flags))
+    }
+
+    /// Time type matching a time code format description.
+    ///
+    /// ```
+    /// use cidre::{cat::audio::SMPTETimeType, cm};
+    ///
+    /// let ty = SMPTETimeType::with_time_code_desc(
+    ///     cm::Time::new(1001, 30000),
+    ///     30,
+    ///     cm::TimeCodeFlags::DROP_FRAME,
+    /// );
+    /// assert_eq!(ty, Some(SMPTETimeType::_29_97_DROP));
+    /// ```
+    #[cfg(all(target_vendor = "apple", feature = "cm"))]
+    pub fn with_time_code_desc(
+        frame_duration: cm::Time,
+        frame_quanta: u32,
+        flags: cm::TimeCodeFlags,
+    ) -> Option<Self> {
+        if frame_duration.value <= 0 || frame_duration.scale <= 0 {
+            return None;
+        }
+        let drop = flags.contains(cm::TimeCodeFlags::DROP_FRAME);
+        (0..12).map(Self).find(|ty| {
+            let (num, den) = ty.frame_rate().unwrap();
+            ty.fps() == Some(frame_quanta)
+                && ty.is_drop_frame() == drop
+                && frame_duration.value as i128 * num as i128
+                    == frame_duration.scale as i128 * den as i128
+        })
+    }
+}
+
+const fn frames_per_10_min(fps: i64, drop: i64) -> i64 {
+    fps * 600 - drop * 9
+}
+
+impl SMPTETime {
+    /// Timecode `frames` frames after `00:00:00:00`, wrapped to 24 hours.
+    ///
+    /// ```
+    /// use cidre::cat::audio::{SMPTETime, SMPTETimeType};
+    ///
+    /// let tc = SMPTETime::with_frames(SMPTETimeType::_29_97_DROP, 1800).unwrap();
+    /// assert_eq!(tc.to_string(), "00:01:00;02");
+    /// ```
+    pub fn with_frames(ty: SMPTETimeType, frames: i64) -> Option<Self> {
+        let fps = ty.fps()? as i64;
+        let drop = ty.dropped_frames() as i64;
+        let mut frames = frames.rem_euclid(ty.frames_per_day()?);
+        if drop != 0 {
+            let per_10_min = frames_per_10_min(fps, drop);
+            let per_min = fps * 60 - drop;
+            let tens = frames / per_10_min;
+            let rem = frames % per_10_min;
+            frames += 9 * drop * tens;
+            if rem >= drop {
+                frames += drop * ((rem - drop) / per_min);
+            }
+        }
+        let secs = frames / fps;
+        Some(Self {
+            r#type: ty,
+            flags: SMPTETimeFlags::VALID,
+            hours: (secs / 3600) as i16,
+            minutes: (secs / 60 % 60) as i16,
+            seconds: (secs % 60) as i16,
+            frames: (frames % fps) as i16,
+            ..Default::default()
+        })
+    }
+
+    /// Frames since `00:00:00:00`, subframes ignored.
+    pub fn frame_count(&self) -> Option<i64> {
+        let fps = self.r#type.fps()? as i64;
+        let drop = self.r#type.dropped_frames() as i64;
+        let mins = self.hours as i64 * 60 + self.minutes as i64;
+        let secs = mins * 60 + self.seconds as i64;
+        Some(secs * fps + self.frames as i64 - drop * (mins - mins / 10))
+    }
+
+    /// Offsets the timecode by `frames`, wrapping at 24 hours. Subframes are kept.
+    ///
+    /// ```
+    /// use cidre::cat::audio::{SMPTETime, SMPTETimeType};
+    ///
+    /// let tc = SMPTETime::parse(SMPTETimeType::_29_97_DROP, "00:00:59;29").unwrap();
+    /// assert_eq!(tc.add_frames(1).unwrap().to_string(), "00:01:00;02");
+    /// assert_eq!(tc.add_frames(1).unwrap().add_frames(-1), Some(tc));
+    /// ```
+    pub fn add_frames(&self, frames: i64) -> Option<Self> {
+        let mut res = Self::with_frames(self.r#type, self.frame_count()? + frames)?;
+        res.subframes = self.subframes;
+        res.subframes_divisor = self.subframes_divisor;
+        res.counter = self.counter;
+        res.flags = self.flags;
+        Some(res)
+    }
+
+    /// Frames from `other` to `self`, `None` if the types differ.
+    pub fn frames_since(&self, other: &Self) -> Option<i64> {
+        if self.r#type != other.r#type {
+            return None;
+        }
+        Some(self.frame_count()? - other.frame_count()?)
+    }
+
+    /// Position of the timecode in frames, including subframes.
+    fn frame_pos(&self) -> Option<f64> {
+        let mut pos = self.frame_count()? as f64;
+        if self.subframes_divisor > 0 {
+            pos += self.subframes as f64 / self.subframes_divisor as f64;
+        }
+        Some(pos)
+    }
+
+    /// Timecode at `sample_time`, counting from `00:00:00:00` at sample zero.
+    ///
+    /// The fraction of a frame goes into subframes, with 80 subframes per frame.
+    pub fn with_sample_time(ty: SMPTETimeType, sample_time: f64, sample_rate: f64) -> Option<Self> {
+        let (num, den) = ty.frame_rate()?;
+        let pos = sample_time * num as f64 / (den as f64 * sample_rate);
+        if !pos.is_finite() {
+            return None;
+        }
+        let subframes = (pos * SUBFRAMES as f64).round() as i64;
+        let mut res = Self::with_frames(ty, subframes.div_euclid(SUBFRAMES as i64))?;
+        res.subframes = subframes.rem_euclid(SUBFRAMES as i64) as i16;
+        res.subframes_divisor = SUBFRAMES;
+        Some(res)
+    }
+
+    /// Sample time of the timecode at `sample_rate`, inverse of [`Self::with_sample_time`].
+    pub fn sample_time(&self, sample_rate: f64) -> Option<f64> {
+        let (num, den) = self.r#type.frame_rate()?;
+        Some(self.frame_pos()? * den as f64 * sample_rate / num as f64)
+    }
+
+    /// Timecode at `time`, rounded down to a whole frame.
+    ///
+    /// ```
+    /// use cidre::{cat::audio::{SMPTETime, SMPTETimeType}, cm};
+    ///
+    /// let tc = SMPTETime::with_cm_time(SMPTETimeType::_23_98, cm::Time::new(1001, 24000)).unwrap();
+    /// assert_eq!(tc.to_string(), "00:00:00:01");
+    /// assert_eq!(tc.cm_time().unwrap().value, 1001);
+    /// ```
+    #[cfg(feature = "cm")]
+    pub fn with_cm_time(ty: SMPTETimeType, time: cm::Time) -> Option<Self> {
+        if !time.is_numeric() || time.scale <= 0 {
+            return None;
+        }
+        let (num, den) = ty.frame_rate()?;
+        let frames =
+            (time.value as i128 * num as i128).div_euclid(time.scale as i128 * den as i128);
+        Self::with_frames(ty, frames.try_into().ok()?)
+    }
+
+    /// Time of the frame since `00:00:00:00`, in the frame rate timescale.
+    #[cfg(feature = "cm")]
+    pub fn cm_time(&self) -> Option<cm::Time> {
+        let (num, den) = self.r#type.frame_rate()?;
+        Some(cm::Time::new(self.frame_count()? * den as i64, num as i32))
+    }
+
+    /// Timecode from a `TIME_CODE` media sample: a big-endian `i32` frame
+    /// number for `tmcd` or `i64` for `tc64`.
+    pub fn with_time_code_sample(ty: SMPTETimeType, sample: &[u8]) -> Option<Self> {
+        let frames = match *sample {
+            [a, b, c, d] => i32::from_be_bytes([a, b, c, d]) as i64,
+            [a, b, c, d, e, f, g, h] => i64::from_be_bytes([a, b, c, d, e, f, g, h]),
+            _ => return None,
+        };
+        Self::with_frames(ty, frames)
+    }
+
+    /// Sample payload for `cm::TimeCodeFormat::TIME_CODE_32`.
+    pub fn time_code_sample_32(&self) -> Option<[u8; 4]> {
+        Some(i32::try_from(self.frame_count()?).ok()?.to_be_bytes())
+    }
+
+    /// Sample payload for `cm::TimeCodeFormat::TIME_CODE_64`.
+    pub fn time_code_sample_64(&self) -> Option<[u8; 8]> {
+        Some(self.frame_count()?.to_be_bytes())
+    }
+
+    /// Parses `HH:MM:SS:FF`. The last separator may be any of `:;.,`,
+    /// drop-frame is decided by `ty` alone.
+    ///
+    /// ```
+    /// use cidre::cat::audio::{SMPTETime, SMPTETimeType, SMPTETimeParseError};
+    ///
+    /// let ty = SMPTETimeType::_29_97_DROP;
+    /// let tc = SMPTETime::parse(ty, "01:00:00;00").unwrap();
+    /// assert_eq!(tc.frame_count(), Some(107892));
+    /// assert_eq!(SMPTETime::parse(ty, "00:01:00;00"), Err(SMPTETimeParseError::Dropped));
+    /// ```
+    pub fn parse(ty: SMPTETimeType, s: &str) -> Result<Self, ParseError> {
+        let fps = ty.fps().ok_or(ParseError::Type(ty))? as i16;
+        let drop = ty.dropped_frames() as i16;
+        let field = |part: &str| match part.len() {
+            1 | 2 if part.bytes().all(|b| b.is_ascii_digit()) => Ok(part.parse().unwrap()),
+            _ => Err(ParseError::Syntax),
+        };
+        let (hms, frames) = s
+            .rsplit_once([':', ';', '.', ','])
+            .ok_or(ParseError::Syntax)?;
+        let mut hms = hms.split(':').map(field);
+        let (Some(hours), Some(minutes), Some(seconds), None) =
+            (hms.next(), hms.next(), hms.next(), hms.next())
+        else {
+            return Err(ParseError::Syntax);
+        };
+        let fields: [i16; 4] = [hours?, minutes?, seconds?, field(frames)?];
+        let [hours, minutes, seconds, frames] = fields;
+        if hours >= 24 || minutes >= 60 || seconds >= 60 || frames >= fps {
+            return Err(ParseError::Range);
+        }
+        if seconds == 0 && minutes % 10 != 0 && frames < drop {
+            return Err(ParseError::Dropped);
+        }
+        Ok(Self {
+            r#type: ty,
+            flags: SMPTETimeFlags::VALID,
+            hours,
+            minutes,
+            seconds,
+            frames,
+            ..Default::default()
+        })
+    }
+}
+
+/// `HH:MM:SS:FF`, or `HH:MM:SS;FF` for drop-frame types.
+impl std::fmt::Display for SMPTETime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let sep = if self.r#type.is_drop_frame() {
+            ';'
+        } else {
+            ':'
+        };
+        write!(
+            f,
+            "{:02}:{:02}:{:02}{sep}{:02}",
+            self.hours, self.minutes, self.seconds, self.frames
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{SMPTETime, SMPTETimeParseError, SMPTETimeType as T};
+
+    const ALL: [T; 12] = [
+        T::_24,
+        T::_25,
+        T::_30_DROP,
+        T::_30,
+        T::_29_97,
+        T::_29_97_DROP,
+        T::_60,
+        T::_59_94,
+        T::_60_DROP,
+        T::_59_94_DROP,
+        T::_50,
+        T::_23_98,
+    ];
+
+    #[test]
+    fn frames_round_trip() {
+        for ty in ALL {
+            let day = ty.frames_per_day().unwrap();
+            let mut prev = None;
+            for frames in (0..day).step_by(997).chain([day - 1, day, -1]) {
+                let tc = SMPTETime::with_frames(ty, frames).unwrap();
+                assert_eq!(tc.frame_count(), Some(frames.rem_euclid(day)), "{ty:?}");
+                let parsed = SMPTETime::parse(ty, &tc.to_string()).unwrap();
+                assert_eq!(parsed, tc);
+                // labels grow with the frame count
+                let label = (tc.hours, tc.minutes, tc.seconds, tc.frames);
+                if frames < day {
+                    assert!(prev < Some(label), "{ty:?} {tc}");
+                }
+                prev = Some(label);
+            }
+        }
+    }
+
+    #[test]
+    fn drop_frame() {
+        let ty = T::_29_97_DROP;
+        assert_eq!(ty.frames_per_day(), Some(2_589_408));
+        let tc = |s| SMPTETime::parse(ty, s).unwrap();
+
+        assert_eq!(tc("00:00:59;29").add_frames(1).unwrap(), tc("00:01:00;02"));
+        assert_eq!(tc("00:09:59;29").add_frames(1).unwrap(), tc("00:10:00;00"));
+        assert_eq!(tc("00:10:00;00").add_frames(-1).unwrap(), tc("00:09:59;29"));
+        assert_eq!(tc("00:01:00;02").add_frames(-1).unwrap(), tc("00:00:59;29"));
+        assert_eq!(tc("00:00:00;00").add_frames(-1).unwrap(), tc("23:59:59;29"));
+        assert_eq!(tc("00:10:00;00").frame_count(), Some(17982));
+        assert_eq!(tc("00:11:00;02").frames_since(&tc("00:10:59;29")), Some(1));
+
+        let ty = T::_59_94_DROP;
+        let a = SMPTETime::parse(ty, "00:00:59;59").unwrap();
+        assert_eq!(a.add_frames(1).unwrap().to_string(), "00:01:00;04");
+        assert_eq!(
+            SMPTETime::parse(ty, "00:01:00;03"),
+            Err(SMPTETimeParseError::Dropped)
+        );
+
+        // non-drop types count every frame
+        let a = SMPTETime::parse(T::_29_97, "00:00:59:29").unwrap();
+        assert_eq!(a.add_frames(1).unwrap().to_string(), "00:01:00:00");
+    }
+
+    #[test]
+    fn parse_errors() {
+        use SMPTETimeParseError as E;
+        let p = |s| SMPTETime::parse(T::_25, s);
+        assert_eq!(p("10:00:00:25"), Err(E::Range));
+        assert_eq!(p("24:00:00:00"), Err(E::Range));
+        assert_eq!(p("10:00:00"), Err(E::Syntax));
+        assert_eq!(p("10:00:00:00:00"), Err(E::Syntax));
+        assert_eq!(p("10;00:00:00"), Err(E::Syntax));
+        assert_eq!(p("1a:00:00:00"), Err(E::Syntax));
+        assert_eq!(p("+1:00:00:00"), Err(E::Syntax));
+        assert_eq!(SMPTETime::parse(T(42), "00:00:00:00"), Err(E::Type(T(42))));
+        assert_eq!(p("1:2:3.4").unwrap().to_string(), "01:02:03:04");
+    }
+
+    #[test]
+    fn sample_time() {
+        let tc = SMPTETime::parse(T::_29_97_DROP, "00:10:00;00").unwrap();
+        let samples = tc.sample_time(48_000.0).unwrap();
+        assert_eq!(samples, 17982.0 * 1001.0 * 48_000.0 / 30_000.0);
+        let back = SMPTETime::with_sample_time(T::_29_97_DROP, samples, 48_000.0).unwrap();
+        assert_eq!(back.to_string(), "00:10:00;00");
+        assert_eq!((back.subframes, back.subframes_divisor), (0, 80));
+
+        let half = SMPTETime::with_sample_time(T::_25, 960.0, 48_000.0).unwrap();
+        assert_eq!((half.frames, half.subframes), (0, 40));
+        assert_eq!(half.sample_time(48_000.0), Some(960.0));
+    }
+
+    #[test]
+    fn time_code_samples() {
+        let tc = SMPTETime::parse(T::_24, "01:00:00:00").unwrap();
+        let sample = tc.time_code_sample_32().unwrap();
+        assert_eq!(sample, 86400i32.to_be_bytes());
+        assert_eq!(SMPTETime::with_time_code_sample(T::_24, &sample), Some(tc));
+        let sample = tc.time_code_sample_64().unwrap();
+        assert_eq!(SMPTETime::with_time_code_sample(T::_24, &sample), Some(tc));
+        assert_eq!(SMPTETime::with_time_code_sample(T::_24, &[0; 3]), None);
+    }
+
+    #[cfg(all(target_vendor = "apple", feature = "cm"))]
+    #[test]
+    fn cm_time() {
+        use crate::cm;
+
+        for ty in ALL {
+            let (duration, quanta, flags) = ty.time_code_desc_params().unwrap();
+            assert_eq!(T::with_time_code_desc(duration, quanta, flags), Some(ty));
+        }
+
+        let tc = SMPTETime::parse(T::_59_94, "00:00:01:00").unwrap();
+        let time = tc.cm_time().unwrap();
+        assert_eq!((time.value, time.scale), (60 * 1001, 60000));
+        assert_eq!(SMPTETime::with_cm_time(T::_59_94, time), Some(tc));
+        // just before a frame boundary rounds down
+        let early = cm::Time::new(time.value - 1, time.scale);
+        assert_eq!(
+            SMPTETime::with_cm_time(T::_59_94, early)
+                .unwrap()
+                .to_string(),
+            "00:00:00:59"
+        );
+        assert_eq!(SMPTETime::with_cm_time(T::_25, cm::Time::invalid()), None);
+    }
+}
@@ -0,0 +1,791 @@
+//! Validation, construction and size arithmetic for [`StreamBasicDesc`].
+//!
+//! Everything here is plain Rust, modelled after the `CAStreamBasicDescription`
+//! helper class from Apple's Core Audio utility sources.
+
+use super::{Buf, BufList, BufListN, Format, FormatFlags, StreamBasicDesc};
+
+/// Reason why a [`StreamBasicDesc`] is not self-consistent.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Error {
+    /// Sample rate is zero, negative or not finite.
+    SampleRate(f64),
+
+    /// `channels_per_frame` is zero.
+    ZeroChannels,
+
+    /// Linear PCM with `bits_per_channel` set to zero.
+    ZeroBitsPerChannel,
+
+    /// Float samples must be 32 or 64 bits wide.
+    FloatBits(u32),
+
+    /// Both `IS_FLOAT` and `IS_SIGNED_INTEGER` are set.
+    FloatSignedInteger,
+
+    /// Float samples with non zero fixed-point fraction bits.
+    FloatFractionBits(u32),
+
+    /// More fixed-point fraction bits than bits per channel.
+    FractionBits {
+        fraction_bits: u32,
+        bits_per_channel: u32,
+    },
+
+    /// `IS_PACKED` and `IS_ALIGNED_HIGH` are both set. Alignment is only
+    /// meaningful when the sample does not fill its container.
+    PackedAlignedHigh,
+
+    /// Packed samples must use a whole number of bytes.
+    PackedBits(u32),
+
+    /// Non-interleaved linear PCM with `frames_per_packet` != 1.
+    NonInterleavedFramesPerPacket(u32),
+
+    /// Linear PCM with `frames_per_packet` != 1.
+    PcmFramesPerPacket(u32),
+
+    /// `bytes_per_frame` is inconsistent with channels × bits.
+    BytesPerFrame { expected: u32, actual: u32 },
+
+    /// `bytes_per_packet` != `bytes_per_frame` × `frames_per_packet`.
+    BytesPerPacket { expected: u32, actual: u32 },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SampleRate(sr) => write!(f, "invalid sample rate {sr}"),
+            Self::ZeroChannels => f.write_str("zero channels per frame"),
+            Self::ZeroBitsPerChannel => f.write_str("linear pcm with zero bits per channel"),
+            Self::FloatBits(bits) => write!(f, "{bits} bits float samples"),
+            Self::FloatSignedInteger => f.write_str("both float and signed integer flags are set"),
+            Self::FloatFractionBits(n) => write!(f, "float samples with {n} fraction bits"),
+            Self::FractionBits {
+                fraction_bits,
+                bits_per_channel,
+            } => write!(
+                f,
+                "{fraction_bits} fraction bits in {bits_per_channel} bits sample"
+            ),
+            Self::PackedAlignedHigh => f.write_str("both packed and aligned high flags are set"),
+            Self::PackedBits(bits) => write!(f, "packed {bits} bits samples"),
+            Self::NonInterleavedFramesPerPacket(n) => {
+                write!(f, "non-interleaved buffer with {n} frames per packet")
+            }
+            Self::PcmFramesPerPacket(n) => write!(f, "linear pcm with {n} frames per packet"),
+            Self::BytesPerFrame { expected, actual } => {
+                write!(f, "bytes per frame is {actual}, expected {expected}")
+            }
+            Self::BytesPerPacket { expected, actual } => {
+                write!(f, "bytes per packet is {actual}, expected {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Sample {
+    Int { signed: bool, fraction_bits: u32 },
+    Float,
+}
+
+/// Typed builder for [`StreamBasicDesc`].
+///
+/// Derived fields (`bytes_per_frame`, `bytes_per_packet`, flags) are computed
+/// by [`Builder::build`] which also validates the result.
+///
+/// ```
+/// use cidre::cat::audio;
+///
+/// let asbd = audio::StreamBasicDescBuilder::lpcm(48_000.0, 2)
+///     .int(24)
+///     .aligned_high(4)
+///     .non_interleaved()
+///     .build()
+///     .unwrap();
+///
+/// assert_eq!(asbd.bytes_per_frame, 4);
+/// assert!(asbd.is_aligned_high());
+/// assert_eq!(asbd.buf_list_bufs_num(), 2);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Builder {
+    format: Format,
+    sample_rate: f64,
+    channels: u32,
+    sample: Sample,
+    bits: u32,
+    container: u32,
+    aligned_high: bool,
+    interleaved: bool,
+    big_endian: bool,
+    frames_per_packet: u32,
+    bytes_per_packet: u32,
+    flags: FormatFlags,
+}
+
+impl Builder {
+    /// Linear PCM, native endian, interleaved, packed 32 bits float.
+    pub const fn lpcm(sample_rate: f64, channels: u32) -> Self {
+        Self {
+            format: Format::LINEAR_PCM,
+            sample_rate,
+            channels,
+            sample: Sample::Float,
+            bits: 32,
+            container: 0,
+            aligned_high: false,
+            interleaved: true,
+            big_endian: false,
+            frames_per_packet: 1,
+            bytes_per_packet: 0,
+            flags: FormatFlags(0),
+        }
+    }
+
+    /// Compressed format with `frames_per_packet` frames in each packet
+    /// (0 for formats with variable frames per packet).
+    ///
+    /// Bytes per packet default to 0 (variable bitrate).
+    pub const fn compressed(
+        format: Format,
+        sample_rate: f64,
+        channels: u32,
+        frames_per_packet: u32,
+    ) -> Self {
+        let mut res = Self::lpcm(sample_rate, channels);
+        res.format = format;
+        res.bits = 0;
+        res.frames_per_packet = frames_per_packet;
+        res
+    }
+
+    /// Signed integer samples.
+    pub const fn int(mut self, bits: u32) -> Self {
+        self.sample = Sample::Int {
+            signed: true,
+            fraction_bits: 0,
+        };
+        self.bits = bits;
+        self
+    }
+
+    /// Unsigned integer samples.
+    pub const fn uint(mut self, bits: u32) -> Self {
+        self.sample = Sample::Int {
+            signed: false,
+            fraction_bits: 0,
+        };
+        self.bits = bits;
+        self
+    }
+
+    /// IEEE float samples, 32 or 64 bits.
+    pub const fn float(mut self, bits: u32) -> Self {
+        self.sample = Sample::Float;
+        self.bits = bits;
+        self
+    }
+
+    /// Signed fixed-point samples with `fraction_bits` bits after the point.
+    pub const fn fixed(mut self, bits: u32, fraction_bits: u32) -> Self {
+        self.sample = Sample::Int {
+            signed: true,
+            fraction_bits,
+        };
+        self.bits = bits;
+        self
+    }
+
+    /// 8.24 fixed-point, the canonical audio unit sample format of older systems.
+    pub const fn fixed_8_24(self) -> Self {
+        self.fixed(32, 24)
+    }
+
+    /// Samples occupy exactly `bits / 8` bytes (the default).
+    pub const fn packed(mut self) -> Self {
+        self.container = 0;
+        self.aligned_high = false;
+        self
+    }
+
+    /// Samples occupy the high bits of `bytes` wide containers.
+    pub const fn aligned_high(mut self, bytes: u32) -> Self {
+        self.container = bytes;
+        self.aligned_high = true;
+        self
+    }
+
+    /// Samples occupy the low bits of `bytes` wide containers.
+    pub const fn aligned_low(mut self, bytes: u32) -> Self {
+        self.container = bytes;
+        self.aligned_high = false;
+        self
+    }
+
+    pub const fn interleaved(mut self, val: bool) -> Self {
+        self.interleaved = val;
+        self
+    }
+
+    /// Each channel goes to its own buffer.
+    pub const fn non_interleaved(self) -> Self {
+        self.interleaved(false)
+    }
+
+    pub const fn big_endian(mut self, val: bool) -> Self {
+        self.big_endian = val;
+        self
+    }
+
+    /// Constant bitrate compressed formats.
+    pub const fn bytes_per_packet(mut self, val: u32) -> Self {
+        self.bytes_per_packet = val;
+        self
+    }
+
+    /// Format specific flags, combined with flags derived by the builder.
+    pub const fn flags(mut self, val: FormatFlags) -> Self {
+        self.flags = val;
+        self
+    }
+
+    /// Desc without validation.
+    pub const fn build_unchecked(&self) -> StreamBasicDesc {
+        let mut flags = self.flags.0;
+        if self.big_endian {
+            flags |= FormatFlags::IS_BIG_ENDIAN.0;
+        }
+        if !self.interleaved {
+            flags |= FormatFlags::IS_NON_INTERLEAVED.0;
+        }
+
+        if self.format.0 != Format::LINEAR_PCM.0 {
+            return StreamBasicDesc {
+                sample_rate: self.sample_rate,
+                format: self.format,
+                format_flags: FormatFlags(flags),
+                bytes_per_packet: self.bytes_per_packet,
+                frames_per_packet: self.frames_per_packet,
+                bytes_per_frame: 0,
+                channels_per_frame: self.channels,
+                bits_per_channel: self.bits,
+                reserved: 0,
+            };
+        }
+
+        match self.sample {
+            Sample::Float => flags |= FormatFlags::IS_FLOAT.0,
+            Sample::Int {
+                signed,
+                fraction_bits,
+            } => {
+                if signed {
+                    flags |= FormatFlags::IS_SIGNED_INTEGER.0;
+                }
+                flags |= fraction_bits << FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_SHIFT.0;
+            }
+        }
+
+        let word = if self.container == 0 {
+            self.bits.div_ceil(8)
+        } else {
+            self.container
+        };
+        if word * 8 == self.bits {
+            flags |= FormatFlags::IS_PACKED.0;
+        } else if self.aligned_high {
+            flags |= FormatFlags::IS_ALIGNED_HIGH.0;
+        }
+
+        let bytes_per_frame = if self.interleaved {
+            word * self.channels
+        } else {
+            word
+        };
+
+        StreamBasicDesc {
+            sample_rate: self.sample_rate,
+            format: self.format,
+            format_flags: FormatFlags(flags),
+            bytes_per_packet: bytes_per_frame,
+            frames_per_packet: 1,
+            bytes_per_frame,
+            channels_per_frame: self.channels,
+            bits_per_channel: self.bits,
+            reserved: 0,
+        }
+    }
+
+    pub fn build(&self) -> Result<StreamBasicDesc, Error> {
+        let res = self.build_unchecked();
+        res.validate()?;
+        Ok(res)
+    }
+}
+
+impl StreamBasicDesc {
+    /// Checks the desc for internal consistency.
+    ///
+    /// Reports the first problem found.
+    pub fn validate(&self) -> Result<(), Error> {
+        if !(self.sample_rate.is_finite() && self.sample_rate > 0.0) {
+            return Err(Error::SampleRate(self.sample_rate));
+        }
+        if self.channels_per_frame == 0 {
+            return Err(Error::ZeroChannels);
+        }
+        if !self.is_pcm() {
+            return Ok(());
+        }
+
+        let bits = self.bits_per_channel;
+        if bits == 0 {
+            return Err(Error::ZeroBitsPerChannel);
+        }
+        let fraction_bits = self.fraction_bits();
+        if self.is_float() {
+            if self.is_signed_int() {
+                return Err(Error::FloatSignedInteger);
+            }
+            if bits != 32 && bits != 64 {
+                return Err(Error::FloatBits(bits));
+            }
+            if fraction_bits != 0 {
+                return Err(Error::FloatFractionBits(fraction_bits));
+            }
+        } else if fraction_bits > bits {
+            return Err(Error::FractionBits {
+                fraction_bits,
+                bits_per_channel: bits,
+            });
+        }
+        if self.is_packed() {
+            if self.is_aligned_high() {
+                return Err(Error::PackedAlignedHigh);
+            }
+            if bits % 8 != 0 {
+                return Err(Error::PackedBits(bits));
+            }
+        }
+
+        if self.frames_per_packet != 1 {
+            if !self.is_interleaved() {
+                return Err(Error::NonInterleavedFramesPerPacket(self.frames_per_packet));
+            }
+            return Err(Error::PcmFramesPerPacket(self.frames_per_packet));
+        }
+
+        let n = self.interleaved_channels_num();
+        let expected = if self.is_packed() {
+            n * bits / 8
+        } else {
+            n * bits.div_ceil(8)
+        };
+        let actual = self.bytes_per_frame;
+        let consistent = if self.is_packed() {
+            actual == expected
+        } else {
+            actual >= expected && actual % n == 0
+        };
+        if !consistent {
+            return Err(Error::BytesPerFrame { expected, actual });
+        }
+
+        let expected = self.bytes_per_frame * self.frames_per_packet;
+        if self.bytes_per_packet != expected {
+            return Err(Error::BytesPerPacket {
+                expected,
+                actual: self.bytes_per_packet,
+            });
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.validate().is_ok()
+    }
+
+    #[inline]
+    pub fn is_pcm(&self) -> bool {
+        self.format == Format::LINEAR_PCM
+    }
+
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_FLOAT)
+    }
+
+    #[inline]
+    pub fn is_signed_int(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_SIGNED_INTEGER)
+    }
+
+    #[inline]
+    pub fn is_big_endian(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_BIG_ENDIAN)
+    }
+
+    #[inline]
+    pub fn is_packed(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_PACKED)
+    }
+
+    #[inline]
+    pub fn is_aligned_high(&self) -> bool {
+        self.format_flags.contains(FormatFlags::IS_ALIGNED_HIGH)
+    }
+
+    /// Number of fixed-point fraction bits, 24 for 8.24 samples.
+    #[inline]
+    pub fn fraction_bits(&self) -> u32 {
+        (self.format_flags.0 & FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_MASK.0)
+            >> FormatFlags::LINEAR_PCM_SAMPLE_FRACTION_SHIFT.0
+    }
+
+    /// Packets with constant bytes and frames count.
+    #[inline]
+    pub fn is_cbr(&self) -> bool {
+        self.bytes_per_packet != 0 && self.frames_per_packet != 0
+    }
+
+    /// Bytes per sample container of linear PCM. 0 for compressed formats.
+    #[inline]
+    pub fn sample_word_size(&self) -> u32 {
+        match self.interleaved_channels_num() {
+            0 => 0,
+            n => self.bytes_per_frame / n,
+        }
+    }
+
+    /// Number of buffers in an audio buffer list holding this format.
+    #[inline]
+    pub fn buf_list_bufs_num(&self) -> usize {
+        if self.is_interleaved() {
+            1
+        } else {
+            self.channels_per_frame as usize
+        }
+    }
+
+    /// Size of a single buffer holding `frames` frames.
+    #[inline]
+    pub fn frames_to_bytes(&self, frames: usize) -> Option<usize> {
+        if self.bytes_per_frame == 0 {
+            return None;
+        }
+        frames.checked_mul(self.bytes_per_frame as usize)
+    }
+
+    /// Number of whole frames in a single buffer of `bytes` bytes.
+    #[inline]
+    pub fn bytes_to_frames(&self, bytes: usize) -> Option<usize> {
+        match self.bytes_per_frame {
+            0 => None,
+            n => Some(bytes / n as usize),
+        }
+    }
+
+    /// Number of packets needed for `frames` frames (rounds up).
+    #[inline]
+    pub fn frames_to_packets(&self, frames: usize) -> Option<usize> {
+        match self.frames_per_packet {
+            0 => None,
+            n => Some(frames.div_ceil(n as usize)),
+        }
+    }
+
+    #[inline]
+    pub fn packets_to_frames(&self, packets: usize) -> Option<usize> {
+        match self.frames_per_packet {
+            0 => None,
+            n => packets.checked_mul(n as usize),
+        }
+    }
+
+    #[inline]
+    pub fn packets_to_bytes(&self, packets: usize) -> Option<usize> {
+        match self.bytes_per_packet {
+            0 => None,
+            n => packets.checked_mul(n as usize),
+        }
+    }
+
+    /// Number of whole packets in `bytes` bytes.
+    #[inline]
+    pub fn bytes_to_packets(&self, bytes: usize) -> Option<usize> {
+        match self.bytes_per_packet {
+            0 => None,
+            n => Some(bytes / n as usize),
+        }
+    }
+
+    #[inline]
+    pub fn frames_to_secs(&self, frames: usize) -> Option<f64> {
+        if self.sample_rate > 0.0 {
+            Some(frames as f64 / self.sample_rate)
+        } else {
+            None
+        }
+    }
+
+    /// Number of frames covering `secs` seconds (rounds up).
+    #[inline]
+    pub fn secs_to_frames(&self, secs: f64) -> Option<usize> {
+        if self.sample_rate > 0.0 && secs.is_finite() && secs >= 0.0 {
+            Some((secs * self.sample_rate).ceil() as usize)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn packets_to_secs(&self, packets: usize) -> Option<f64> {
+        self.frames_to_secs(self.packets_to_frames(packets)?)
+    }
+
+    /// Number of packets covering `secs` seconds (rounds up).
+    #[inline]
+    pub fn secs_to_packets(&self, secs: f64) -> Option<usize> {
+        self.frames_to_packets(self.secs_to_frames(secs)?)
+    }
+
+    /// Size in bytes of an audio buffer list header (without sample data)
+    /// for this format.
+    #[inline]
+    pub fn buf_list_size(&self) -> usize {
+        std::mem::size_of::<BufList<0>>() + self.buf_list_bufs_num() * std::mem::size_of::<Buf>()
+    }
+
+    /// Total sample data size of an audio buffer list holding `frames` frames.
+    #[inline]
+    pub fn buf_list_data_size(&self, frames: usize) -> Option<usize> {
+        self.frames_to_bytes(frames)?
+            .checked_mul(self.buf_list_bufs_num())
+    }
+
+    /// Number of frames all buffers of the list can hold.
+    ///
+    /// `None` if the list layout doesn't match this format.
+    pub fn buf_list_frames(&self, list: &BufListN) -> Option<usize> {
+        let bufs = list.buffers();
+        if list.number_buffers() != self.buf_list_bufs_num() || bufs.len() < list.number_buffers() {
+            return None;
+        }
+        let mut frames = usize::MAX;
+        for buf in &bufs[..list.number_buffers()] {
+            if buf.number_channels != self.interleaved_channels_num() {
+                return None;
+            }
+            frames = frames.min(self.bytes_to_frames(buf.data_bytes_size as usize)?);
+        }
+        Some(frames)
+    }
+}
+
+impl BufListN {
+    /// Buffer list layout for `frames` frames of `asbd`.
+    ///
+    /// Buffer data pointers are null.
+    pub fn with_desc(asbd: &StreamBasicDesc, frames: usize) -> Self {
+        let mut res = Self::new(asbd.buf_list_size());
+        let n = asbd.buf_list_bufs_num();
+        let bytes = asbd.frames_to_bytes(frames).unwrap_or(0);
+        res.set_number_buffers(n);
+        for buf in &mut res.buffers_mut()[..n] {
+            *buf = Buf {
+                number_channels: asbd.interleaved_channels_num(),
+                data_bytes_size: bytes as _,
+                data: std::ptr::null_mut(),
+            };
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cat::audio::{
+        BufListN, Format, FormatFlags, StreamBasicDesc, StreamBasicDescBuilder as Builder,
+        StreamBasicDescError as Error,
+    };
+
+    #[test]
+    fn builder_lpcm() {
+        let asbd = Builder::lpcm(44_100.0, 2).int(16).build().unwrap();
+        assert_eq!(asbd.bytes_per_frame, 4);
+        assert_eq!(asbd.bytes_per_packet, 4);
+        assert_eq!(asbd.frames_per_packet, 1);
+        assert_eq!(
+            asbd.format_flags,
+            FormatFlags(FormatFlags::IS_SIGNED_INTEGER.0 | FormatFlags::IS_PACKED.0)
+        );
+
+        let asbd = Builder::lpcm(48_000.0, 2)
+            .non_interleaved()
+            .build()
+            .unwrap();
+        assert_eq!(asbd, StreamBasicDesc::common_f32(48_000.0, 2, false));
+        assert!(asbd.is_common_f32());
+
+        let asbd = Builder::lpcm(48_000.0, 6).float(64).build().unwrap();
+        assert_eq!(asbd.bytes_per_frame, 48);
+        assert_eq!(asbd.sample_word_size(), 8);
+
+        let asbd = Builder::lpcm(48_000.0, 2)
+            .uint(8)
+            .big_endian(true)
+            .build()
+            .unwrap();
+        assert!(!asbd.is_signed_int());
+        assert!(asbd.is_big_endian());
+        assert!(!asbd.is_native_endian());
+
+        let asbd = Builder::lpcm(48_000.0, 2)
+            .int(24)
+            .aligned_low(4)
+            .build()
+            .unwrap();
+        assert_eq!(asbd.bytes_per_frame, 8);
+        assert!(!asbd.is_packed());
+        assert!(!asbd.is_aligned_high());
+
+        let asbd = Builder::lpcm(48_000.0, 2).int(24).build().unwrap();
+        assert_eq!(asbd.bytes_per_frame, 6);
+        assert!(asbd.is_packed());
+
+        let asbd = Builder::lpcm(44_100.0, 2)
+            .fixed_8_24()
+            .non_interleaved()
+            .build()
+            .unwrap();
+        assert_eq!(asbd.fraction_bits(), 24);
+        assert_eq!(asbd.format_flags.0, 0xc2c);
+        assert_eq!(asbd.bytes_per_frame, 4);
+    }
+
+    #[test]
+    fn builder_compressed() {
+        let aac = Builder::compressed(Format::MPEG4_AAC, 44_100.0, 2, 1024)
+            .build()
+            .unwrap();
+        assert_eq!(aac.bytes_per_packet, 0);
+        assert_eq!(aac.bytes_per_frame, 0);
+        assert!(!aac.is_cbr());
+        assert_eq!(aac.frames_to_packets(4097), Some(5));
+        assert_eq!(aac.frames_to_bytes(1), None);
+
+        let ima = Builder::compressed(Format::APPLE_IMA4, 44_100.0, 1, 64)
+            .bytes_per_packet(34)
+            .build()
+            .unwrap();
+        assert!(ima.is_cbr());
+        assert_eq!(ima.packets_to_bytes(10), Some(340));
+        assert_eq!(ima.bytes_to_packets(100), Some(2));
+    }
+
+    #[test]
+    fn validate() {
+        let good = Builder::lpcm(44_100.0, 2).int(16).build_unchecked();
+
+        let mut asbd = good;
+        asbd.bytes_per_frame = 6;
+        assert_eq!(
+            asbd.validate(),
+            Err(Error::BytesPerFrame {
+                expected: 4,
+                actual: 6
+            })
+        );
+
+        let mut asbd = good;
+        asbd.bytes_per_packet = 8;
+        assert_eq!(
+            asbd.validate(),
+            Err(Error::BytesPerPacket {
+                expected: 4,
+                actual: 8
+            })
+        );
+
+        let mut asbd = StreamBasicDesc::common_f32(48_000.0, 2, false);
+        asbd.frames_per_packet = 2;
+        assert_eq!(
+            asbd.validate(),
+            Err(Error::NonInterleavedFramesPerPacket(2))
+        );
+
+        let mut asbd = good;
+        asbd.frames_per_packet = 4;
+        assert_eq!(asbd.validate(), Err(Error::PcmFramesPerPacket(4)));
+
+        let mut asbd = good;
+        asbd.sample_rate = 0.0;
+        assert_eq!(asbd.validate(), Err(Error::SampleRate(0.0)));
+
+        let mut asbd = good;
+        asbd.channels_per_frame = 0;
+        assert_eq!(asbd.validate(), Err(Error::ZeroChannels));
+
+        let mut asbd = good;
+        asbd.format_flags = FormatFlags(asbd.format_flags.0 | FormatFlags::IS_ALIGNED_HIGH.0);
+        assert_eq!(asbd.validate(), Err(Error::PackedAlignedHigh));
+
+        assert_eq!(
+            Builder::lpcm(44_100.0, 1).float(16).build(),
+            Err(Error::FloatBits(16))
+        );
+        assert_eq!(
+            Builder::lpcm(44_100.0, 1).fixed(16, 24).build(),
+            Err(Error::FractionBits {
+                fraction_bits: 24,
+                bits_per_channel: 16
+            })
+        );
+
+        let mut asbd = StreamBasicDesc::common_f32(48_000.0, 1, true);
+        asbd.format_flags = FormatFlags(asbd.format_flags.0 | FormatFlags::IS_SIGNED_INTEGER.0);
+        assert_eq!(asbd.validate(), Err(Error::FloatSignedInteger));
+
+        assert!(StreamBasicDesc::default().validate().is_err());
+    }
+
+    #[test]
+    fn durations() {
+        let asbd = Builder::compressed(Format::MPEG4_AAC, 48_000.0, 2, 1024).build_unchecked();
+        assert_eq!(asbd.packets_to_secs(375), Some(8.0));
+        assert_eq!(asbd.secs_to_packets(8.0), Some(375));
+        assert_eq!(asbd.secs_to_packets(8.01), Some(376));
+        assert_eq!(asbd.secs_to_frames(-1.0), None);
+
+        let pcm = StreamBasicDesc::common_f32(48_000.0, 2, true);
+        assert_eq!(pcm.frames_to_secs(24_000), Some(0.5));
+        assert_eq!(pcm.frames_to_bytes(10), Some(80));
+        assert_eq!(pcm.bytes_to_frames(81), Some(10));
+    }
+
+    #[test]
+    fn buf_list() {
+        let asbd = StreamBasicDesc::common_f32(48_000.0, 3, false);
+        assert_eq!(asbd.buf_list_size(), 8 + 3 * 16);
+        assert_eq!(asbd.buf_list_data_size(512), Some(3 * 512 * 4));
+
+        let list = BufListN::with_desc(&asbd, 512);
+        assert_eq!(list.number_buffers(), 3);
+        assert_eq!(list.buffers()[2].data_bytes_size, 2048);
+        assert_eq!(asbd.buf_list_frames(&list), Some(512));
+
+        let interleaved = StreamBasicDesc::common_f32(48_000.0, 3, true);
+        assert_eq!(interleaved.buf_list_frames(&list), None);
+
+        let list = BufListN::with_desc(&interleaved, 100);
+        assert_eq!(list.number_buffers(), 1);
+        assert_eq!(list.buffers()[0].number_channels, 3);
+        assert_eq!(interleaved.buf_list_frames(&list), Some(100));
+    }
+}
@@ -28,6 +28,9 @@ pub use number::Boolean;
 pub use number::Number;
 pub use number::NumberType;
 
+#[cfg(feature = "private")]
+pub use number::I128Parts as NumberI128Parts;
+
 pub mod string;
 pub use string::str;
 pub use string::CompareFlags as StringCompareFlags;
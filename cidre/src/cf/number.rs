@@ -112,6 +112,39 @@ impl NumberType {
     pub const NS_INTEGER: Self = Self(15);
     pub const CG_FLOAT: Self = Self(16);
     pub const MAX: Self = Self(16);
+
+    /// 128-bit integer of binary plists, laid out as [`I128Parts`].
+    #[doc(alias = "kCFNumberSInt128Type")]
+    #[cfg(feature = "private")]
+    pub const I128: Self = Self(17);
+}
+
+#[doc(alias = "CFSInt128Struct")]
+#[cfg(feature = "private")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+#[repr(C)]
+pub struct I128Parts {
+    pub high: i64,
+    pub low: u64,
+}
+
+#[cfg(feature = "private")]
+impl From<i128> for I128Parts {
+    #[inline]
+    fn from(value: i128) -> Self {
+        Self {
+            high: (value >> 64) as i64,
+            low: value as u64,
+        }
+    }
+}
+
+#[cfg(feature = "private")]
+impl From<I128Parts> for i128 {
+    #[inline]
+    fn from(value: I128Parts) -> Self {
+        (value.high as i128) << 64 | value.low as i128
+    }
 }
 
 define_cf_type!(
@@ -253,6 +286,26 @@ impl Number {
         }
     }
 
+    /// ```
+    /// use cidre::cf;
+    ///
+    /// let num = cf::Number::from_i128(u64::MAX as i128 + 1);
+    /// assert_eq!(num.to_i128(), Some(u64::MAX as i128 + 1));
+    /// assert_eq!(num.to_i64(), None);
+    /// ```
+    #[cfg(feature = "private")]
+    #[inline]
+    pub fn to_i128(&self) -> Option<i128> {
+        unsafe {
+            let mut value = I128Parts::default();
+            if CFNumberGetValue(self, NumberType::I128, &mut value as *mut _ as *mut _) {
+                Some(value.into())
+            } else {
+                None
+            }
+        }
+    }
+
     /// ```
     /// use cidre::cf;
     ///
@@ -402,6 +455,15 @@ impl Number {
         unsafe { Self::create_in(NumberType::I64, &val as *const _ as _, None).unwrap_unchecked() }
     }
 
+    #[cfg(feature = "private")]
+    #[inline]
+    pub fn from_i128(val: i128) -> arc::R<Self> {
+        let parts = I128Parts::from(val);
+        unsafe {
+            Self::create_in(NumberType::I128, &parts as *const _ as _, None).unwrap_unchecked()
+        }
+    }
+
     #[inline]
     pub fn from_usize(val: usize) -> arc::R<Self> {
         unsafe { Self::create_in(NumberType::I64, &val as *const _ as _, None).unwrap_unchecked() }
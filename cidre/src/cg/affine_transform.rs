@@ -38,69 +38,240 @@ pub struct Components {
 }
 
 impl AffineTransform {
+    #[doc(alias = "CGAffineTransformIdentity")]
+    pub const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+    #[doc(alias = "CGAffineTransformMake")]
+    #[inline]
+    pub const fn new(a: f64, b: f64, c: f64, d: f64, tx: f64, ty: f64) -> Self {
+        Self { a, b, c, d, tx, ty }
+    }
+
+    /// Transform from scale, shear, rotation and translation components.
+    #[doc(alias = "CGAffineTransformMakeWithComponents")]
     #[inline]
     pub fn with_components(components: Components) -> Self {
-        unsafe { CGAffineTransformMakeWithComponents(components) }
+        let Components {
+            scale,
+            horizontal_shear: sh,
+            rotation,
+            translation,
+        } = components;
+        let (sin, cos) = rotation.sin_cos();
+        Self {
+            a: scale.width * cos,
+            b: scale.width * sin,
+            c: scale.height * (sh * cos - sin),
+            d: scale.height * (sh * sin + cos),
+            tx: translation.dx,
+            ty: translation.dy,
+        }
     }
 
     #[inline]
-    pub fn identity() -> Self {
-        unsafe { CGAffineTransformIdentity }
+    pub const fn identity() -> Self {
+        Self::IDENTITY
     }
 
+    #[doc(alias = "CGAffineTransformMakeTranslation")]
     #[inline]
-    pub fn new_translation(tx: f64, ty: f64) -> Self {
-        unsafe { CGAffineTransformMakeTranslation(tx, ty) }
+    pub const fn new_translation(tx: f64, ty: f64) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
     }
 
+    #[doc(alias = "CGAffineTransformMakeScale")]
     #[inline]
-    pub fn new_scale(sx: f64, sy: f64) -> Self {
-        unsafe { CGAffineTransformMakeScale(sx, sy) }
+    pub const fn new_scale(sx: f64, sy: f64) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
     }
 
+    #[doc(alias = "CGAffineTransformMakeRotation")]
     #[inline]
     pub fn new_rotation(angle: f64) -> Self {
-        unsafe { CGAffineTransformMakeRotation(angle) }
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
     }
 
+    #[doc(alias = "CGAffineTransformIsIdentity")]
     #[inline]
     pub fn is_identity(&self) -> bool {
-        unsafe { CGAffineTransformIsIdentity(*self) }
+        self.equal_to(&Self::IDENTITY)
     }
 
+    /// Translates then applies `self`.
+    #[doc(alias = "CGAffineTransformTranslate")]
     #[inline]
     pub fn translate(&self, tx: f64, ty: f64) -> Self {
-        unsafe { CGAffineTransformTranslate(*self, tx, ty) }
+        Self {
+            tx: tx * self.a + ty * self.c + self.tx,
+            ty: tx * self.b + ty * self.d + self.ty,
+            ..*self
+        }
     }
 
+    /// Scales then applies `self`.
+    #[doc(alias = "CGAffineTransformScale")]
     #[inline]
     pub fn scale(&self, sx: f64, sy: f64) -> Self {
-        unsafe { CGAffineTransformScale(*self, sx, sy) }
+        Self {
+            a: self.a * sx,
+            b: self.b * sx,
+            c: self.c * sy,
+            d: self.d * sy,
+            ..*self
+        }
     }
 
+    /// Rotates then applies `self`.
+    #[doc(alias = "CGAffineTransformRotate")]
     #[inline]
     pub fn rotate(&self, angle: f64) -> Self {
-        unsafe { CGAffineTransformRotate(*self, angle) }
+        Self::new_rotation(angle).concat(self)
     }
 
+    /// Inverted transform or `self` if it can't be inverted.
+    #[doc(alias = "CGAffineTransformInvert")]
     #[inline]
     pub fn invert(&self) -> Self {
-        unsafe { CGAffineTransformInvert(*self) }
+        self.try_invert().unwrap_or(*self)
+    }
+
+    /// Inverted transform, `None` for singular matrices.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let t = cg::AffineTransform::new_scale(2.0, 4.0).translate(1.0, 1.0);
+    /// assert_eq!(t * t.try_invert().unwrap(), cg::AffineTransform::identity());
+    /// assert!(cg::AffineTransform::new_scale(0.0, 1.0).try_invert().is_none());
+    /// ```
+    pub fn try_invert(&self) -> Option<Self> {
+        let det = self.determinant();
+        if det == 0.0 || !det.is_finite() {
+            return None;
+        }
+        Some(Self {
+            a: self.d / det,
+            b: -self.b / det,
+            c: -self.c / det,
+            d: self.a / det,
+            tx: (self.c * self.ty - self.d * self.tx) / det,
+            ty: (self.b * self.tx - self.a * self.ty) / det,
+        })
+    }
+
+    #[inline]
+    pub fn determinant(&self) -> f64 {
+        self.a * self.d - self.b * self.c
     }
 
+    /// `self` followed by `other`.
+    #[doc(alias = "CGAffineTransformConcat")]
     #[inline]
     pub fn concat(&self, other: &Self) -> Self {
-        unsafe { CGAffineTransformConcat(*self, *other) }
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.tx * other.a + self.ty * other.c + other.tx,
+            ty: self.tx * other.b + self.ty * other.d + other.ty,
+        }
     }
 
+    #[doc(alias = "CGAffineTransformEqualToTransform")]
     #[inline]
     pub fn equal_to(&self, other: &Self) -> bool {
-        unsafe { CGAffineTransformEqualToTransform(*self, *other) }
+        self.a == other.a
+            && self.b == other.b
+            && self.c == other.c
+            && self.d == other.d
+            && self.tx == other.tx
+            && self.ty == other.ty
     }
 
-    #[inline]
+    /// Splits the transform into scale, shear, rotation and translation.
+    ///
+    /// Rotation is in `(-π, π]`, a flip goes to the y scale.
+    #[doc(alias = "CGAffineTransformDecompose")]
     pub fn decompose(&self) -> Components {
-        unsafe { CGAffineTransformDecompose(*self) }
+        let sx = self.a.hypot(self.b);
+        let translation = cg::Vector::new(self.tx, self.ty);
+        if sx == 0.0 {
+            let shear = if self.d == 0.0 { 0.0 } else { self.c / self.d };
+            return Components {
+                scale: cg::Size::new(0.0, self.d),
+                horizontal_shear: shear,
+                rotation: 0.0,
+                translation,
+            };
+        }
+        let det = self.determinant();
+        let sy = det / sx;
+        let shear = if det == 0.0 {
+            0.0
+        } else {
+            (self.a * self.c + self.b * self.d) / det
+        };
+        Components {
+            scale: cg::Size::new(sx, sy),
+            horizontal_shear: shear,
+            rotation: self.b.atan2(self.a),
+            translation,
+        }
+    }
+
+    /// Interpolates decomposed components, rotating along the shortest arc.
+    /// `t` of 0 gives `self`, 1 gives `other`.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let a = cg::AffineTransform::new_translation(0.0, 0.0);
+    /// let b = cg::AffineTransform::new_translation(10.0, 20.0).scale(3.0, 3.0);
+    /// let mid = a.interpolate(&b, 0.5);
+    /// assert_eq!(mid, cg::AffineTransform::new_translation(5.0, 10.0).scale(2.0, 2.0));
+    /// ```
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Self::with_components(self.decompose().interpolate(&other.decompose(), t))
+    }
+
+    /// Transforms a point, size or rect.
+    #[inline]
+    pub fn apply<T: Apply>(&self, val: &T) -> T {
+        val.apply_affine_transform(self)
+    }
+}
+
+impl Components {
+    pub fn interpolate(&self, other: &Self, t: f64) -> Self {
+        let lerp = |a: f64, b: f64| a + (b - a) * t;
+        let tau = std::f64::consts::TAU;
+        let mut delta = (other.rotation - self.rotation) % tau;
+        if delta > std::f64::consts::PI {
+            delta -= tau;
+        } else if delta < -std::f64::consts::PI {
+            delta += tau;
+        }
+        Self {
+            scale: cg::Size::new(
+                lerp(self.scale.width, other.scale.width),
+                lerp(self.scale.height, other.scale.height),
+            ),
+            horizontal_shear: lerp(self.horizontal_shear, other.horizontal_shear),
+            rotation: self.rotation + delta * t,
+            translation: cg::Vector::new(
+                lerp(self.translation.dx, other.translation.dx),
+                lerp(self.translation.dy, other.translation.dy),
+            ),
+        }
+    }
+}
+
+impl Default for AffineTransform {
+    #[inline]
+    fn default() -> Self {
+        Self::IDENTITY
     }
 }
 
@@ -118,7 +289,30 @@ impl PartialEq for AffineTransform {
     }
 }
 
+/// Same as [`AffineTransform::concat`], `a * b` applies `a` first.
+impl std::ops::Mul for AffineTransform {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: Self) -> Self {
+        self.concat(&rhs)
+    }
+}
+
+impl std::ops::MulAssign for AffineTransform {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = self.concat(&rhs);
+    }
+}
+
+/// Values [`AffineTransform`] can be applied to.
+pub trait Apply {
+    fn apply_affine_transform(&self, t: &AffineTransform) -> Self;
+}
+
 impl cg::Point {
+    #[doc(alias = "CGPointApplyAffineTransform")]
     #[inline]
     pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
         cg::Point {
@@ -129,6 +323,7 @@ impl cg::Point {
 }
 
 impl cg::Size {
+    #[doc(alias = "CGSizeApplyAffineTransform")]
     #[inline]
     pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
         cg::Size {
@@ -139,27 +334,286 @@ impl cg::Size {
 }
 
 impl cg::Rect {
-    #[inline]
+    /// Bounding box of the transformed corners.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let r = cg::Rect::new(0.0, 0.0, 2.0, 1.0);
+    /// let t = cg::AffineTransform::new_scale(-1.0, 3.0);
+    /// assert_eq!(r.apply_affine_transform(&t), cg::Rect::new(-2.0, 0.0, 2.0, 3.0));
+    /// ```
+    #[doc(alias = "CGRectApplyAffineTransform")]
     pub fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
-        unsafe { CGRectApplyAffineTransform(*self, *t) }
+        if self.is_null() {
+            return *self;
+        }
+        let r = self.standardized();
+        let corners = [
+            cg::Point::new(r.min_x(), r.min_y()),
+            cg::Point::new(r.max_x(), r.min_y()),
+            cg::Point::new(r.min_x(), r.max_y()),
+            cg::Point::new(r.max_x(), r.max_y()),
+        ]
+        .map(|p| p.apply_affine_transform(t));
+        let mut min = corners[0];
+        let mut max = corners[0];
+        for p in &corners[1..] {
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+        cg::Rect::with_points(min, max)
+    }
+}
+
+impl Apply for cg::Point {
+    #[inline]
+    fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        cg::Point::apply_affine_transform(self, t)
+    }
+}
+
+impl Apply for cg::Size {
+    #[inline]
+    fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        cg::Size::apply_affine_transform(self, t)
+    }
+}
+
+impl Apply for cg::Rect {
+    #[inline]
+    fn apply_affine_transform(&self, t: &AffineTransform) -> Self {
+        cg::Rect::apply_affine_transform(self, t)
+    }
+}
+
+#[cfg(feature = "simd")]
+impl AffineTransform {
+    /// Row-vector matrix `[a b 0; c d 0; tx ty 1]` in column-major layout,
+    /// the same as [`simd::f32x3x3::translate`](crate::simd::f32x3x3::translate).
+    pub fn to_f32x3x3(&self) -> crate::simd::f32x3x3 {
+        use crate::simd::{f32x3, f32x3x3};
+        f32x3x3([
+            f32x3::with_xyz_f32(self.a as f32, self.c as f32, self.tx as f32),
+            f32x3::with_xyz_f32(self.b as f32, self.d as f32, self.ty as f32),
+            f32x3::with_xyz_f32(0.0, 0.0, 1.0),
+        ])
+    }
+
+    /// `None` if the last column is not `(0, 0, 1)`.
+    pub fn with_f32x3x3(m: &crate::simd::f32x3x3) -> Option<Self> {
+        let [c0, c1, c2] = m.0;
+        if c2.x() != 0.0 || c2.y() != 0.0 || c2.z() != 1.0 {
+            return None;
+        }
+        Some(Self::new(
+            c0.x() as f64,
+            c1.x() as f64,
+            c0.y() as f64,
+            c1.y() as f64,
+            c0.z() as f64,
+            c1.z() as f64,
+        ))
+    }
+}
+
+#[cfg(feature = "simd")]
+impl From<AffineTransform> for crate::simd::f32x3x3 {
+    #[inline]
+    fn from(val: AffineTransform) -> Self {
+        val.to_f32x3x3()
+    }
+}
+
+#[cfg(all(target_vendor = "apple", feature = "ca"))]
+impl AffineTransform {
+    #[inline]
+    pub fn to_transform3d(&self) -> crate::ca::Transform3d {
+        crate::ca::Transform3d::from_cg_affine_transform(*self)
+    }
+
+    /// `None` if `t` is not affine.
+    #[inline]
+    pub fn with_transform3d(t: &crate::ca::Transform3d) -> Option<Self> {
+        if t.is_affine() {
+            Some(t.to_affine_transform())
+        } else {
+            None
+        }
     }
 }
 
-extern "C" {
-    static CGAffineTransformIdentity: AffineTransform;
+#[cfg(all(target_vendor = "apple", feature = "ca"))]
+impl From<AffineTransform> for crate::ca::Transform3d {
+    #[inline]
+    fn from(val: AffineTransform) -> Self {
+        val.to_transform3d()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cg;
+
+    fn assert_close(a: &cg::AffineTransform, b: &cg::AffineTransform) {
+        let a = [a.a, a.b, a.c, a.d, a.tx, a.ty];
+        let b = [b.a, b.b, b.c, b.d, b.tx, b.ty];
+        for (x, y) in a.iter().zip(b.iter()) {
+            assert!((x - y).abs() < 1e-9, "{a:?} != {b:?}");
+        }
+    }
+
+    #[test]
+    fn basics() {
+        let t = cg::AffineTransform::new_translation(10.0, 20.0)
+            .scale(2.0, 3.0)
+            .rotate(std::f64::consts::FRAC_PI_2);
+        let p = cg::Point::new(1.0, 0.0);
+        // rotate first, then scale, then translate
+        let q = t.apply(&p);
+        assert!((q.x - 10.0).abs() < 1e-12);
+        assert!((q.y - 23.0).abs() < 1e-12);
+
+        let s = cg::AffineTransform::new_scale(2.0, 3.0);
+        let m = cg::AffineTransform::new_translation(1.0, 1.0);
+        assert_eq!(
+            s * m,
+            cg::AffineTransform::new(2.0, 0.0, 0.0, 3.0, 1.0, 1.0)
+        );
+        assert_eq!(
+            m * s,
+            cg::AffineTransform::new(2.0, 0.0, 0.0, 3.0, 2.0, 3.0)
+        );
+        assert_eq!(s.apply(&cg::Size::new(1.0, 1.0)), cg::Size::new(2.0, 3.0));
+
+        let singular = cg::AffineTransform::new(1.0, 2.0, 2.0, 4.0, 5.0, 6.0);
+        assert!(singular.try_invert().is_none());
+        assert_eq!(singular.invert(), singular);
+        assert_close(&(t * t.invert()), &cg::AffineTransform::IDENTITY);
+    }
+
+    #[test]
+    fn rect() {
+        let r = cg::Rect::new(0.0, 0.0, 1.0, 1.0);
+        let t = cg::AffineTransform::new_rotation(std::f64::consts::FRAC_PI_4);
+        let bb = t.apply(&r);
+        let h = std::f64::consts::FRAC_1_SQRT_2;
+        assert!((bb.origin.x + h).abs() < 1e-12);
+        assert!((bb.size.width - 2.0 * h).abs() < 1e-12);
+        assert!((bb.size.height - 2.0 * h).abs() < 1e-12);
+        assert!(t.apply(&cg::Rect::NULL).is_null());
+    }
+
+    #[test]
+    fn decompose() {
+        let cases = [
+            cg::AffineTransform::IDENTITY,
+            cg::AffineTransform::new(2.0, 0.5, -0.25, 3.0, 7.0, -8.0),
+            cg::AffineTransform::new_scale(-1.0, 1.0).rotate(1.0),
+            cg::AffineTransform::new_rotation(-2.5).scale(0.5, -4.0),
+            cg::AffineTransform::new(0.0, 0.0, 1.0, 2.0, 0.0, 0.0),
+        ];
+        for t in cases {
+            let c = t.decompose();
+            assert_close(&cg::AffineTransform::with_components(c), &t);
+        }
 
-    fn CGAffineTransformMakeTranslation(tx: f64, ty: f64) -> AffineTransform;
-    fn CGAffineTransformMakeScale(sx: f64, sy: f64) -> AffineTransform;
-    fn CGAffineTransformMakeRotation(angle: f64) -> AffineTransform;
-    fn CGAffineTransformIsIdentity(t: AffineTransform) -> bool;
-    fn CGAffineTransformTranslate(t: AffineTransform, tx: f64, ty: f64) -> AffineTransform;
-    fn CGAffineTransformScale(t: AffineTransform, sx: f64, sy: f64) -> AffineTransform;
-    fn CGAffineTransformRotate(t: AffineTransform, angle: f64) -> AffineTransform;
-    fn CGAffineTransformInvert(t: AffineTransform) -> AffineTransform;
-    fn CGAffineTransformConcat(t: AffineTransform, other: AffineTransform) -> AffineTransform;
-    fn CGAffineTransformEqualToTransform(t: AffineTransform, other: AffineTransform) -> bool;
-    fn CGRectApplyAffineTransform(rect: cg::Rect, t: AffineTransform) -> cg::Rect;
-    fn CGAffineTransformDecompose(t: AffineTransform) -> Components;
-    fn CGAffineTransformMakeWithComponents(components: Components) -> AffineTransform;
+        // scale first, then rotation
+        let c = cg::AffineTransform::new_rotation(0.5)
+            .scale(2.0, 3.0)
+            .decompose();
+        assert!((c.rotation - 0.5).abs() < 1e-12);
+        assert!((c.scale.width - 2.0).abs() < 1e-12);
+        assert!((c.scale.height - 3.0).abs() < 1e-12);
+        assert!(c.horizontal_shear.abs() < 1e-12);
+    }
+
+    #[test]
+    fn interpolate() {
+        let a = cg::AffineTransform::new_rotation(3.0);
+        let b = cg::AffineTransform::new_rotation(-3.0);
+        // shortest arc goes through π
+        let mid = a.interpolate(&b, 0.5);
+        assert_close(
+            &mid,
+            &cg::AffineTransform::new_rotation(std::f64::consts::PI),
+        );
+        assert_close(&a.interpolate(&b, 0.0), &a);
+        assert_close(&a.interpolate(&b, 1.0), &b);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd() {
+        let t = cg::AffineTransform::new(1.5, 2.0, -3.0, 4.25, 5.0, -6.5);
+        let m: crate::simd::f32x3x3 = t.into();
+        assert_eq!(cg::AffineTransform::with_f32x3x3(&m), Some(t));
+
+        let m = crate::simd::f32x3x3::translate(3.0, 4.0);
+        assert_eq!(
+            cg::AffineTransform::with_f32x3x3(&m),
+            Some(cg::AffineTransform::new_translation(3.0, 4.0))
+        );
+        assert!(
+            cg::AffineTransform::with_f32x3x3(&crate::simd::f32x3x3::diagonal(
+                crate::simd::f32x3::with_xyz(1.0, 1.0, 2.0)
+            ))
+            .is_none()
+        );
+    }
+
+    #[cfg(all(target_vendor = "apple", feature = "ca"))]
+    #[test]
+    fn transform3d() {
+        use crate::ca;
+
+        let t = cg::AffineTransform::new(1.5, 2.0, -3.0, 4.25, 5.0, -6.5);
+        let t3: ca::Transform3d = t.into();
+        assert!(t3.is_affine());
+        assert_eq!(cg::AffineTransform::with_transform3d(&t3), Some(t));
 
+        let mut t3 = t3;
+        t3.m34 = -1.0 / 500.0;
+        assert!(cg::AffineTransform::with_transform3d(&t3).is_none());
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn matches_core_graphics() {
+        use super::{AffineTransform, Components};
+
+        extern "C" {
+            fn CGAffineTransformRotate(t: AffineTransform, angle: f64) -> AffineTransform;
+            fn CGAffineTransformInvert(t: AffineTransform) -> AffineTransform;
+            fn CGAffineTransformConcat(t: AffineTransform, o: AffineTransform) -> AffineTransform;
+            fn CGAffineTransformTranslate(t: AffineTransform, tx: f64, ty: f64) -> AffineTransform;
+            fn CGAffineTransformScale(t: AffineTransform, sx: f64, sy: f64) -> AffineTransform;
+            fn CGRectApplyAffineTransform(r: cg::Rect, t: AffineTransform) -> cg::Rect;
+            fn CGAffineTransformDecompose(t: AffineTransform) -> Components;
+        }
+
+        let ts = [
+            AffineTransform::new(2.0, 0.5, -0.25, 3.0, 7.0, -8.0),
+            AffineTransform::new_rotation(0.3).scale(-2.0, 0.5),
+            AffineTransform::new(1.0, 2.0, 2.0, 4.0, 5.0, 6.0),
+        ];
+        let r = cg::Rect::new(1.0, 2.0, 3.0, 4.0);
+        for t in ts {
+            unsafe {
+                assert_eq!(t.rotate(0.7), CGAffineTransformRotate(t, 0.7));
+                assert_eq!(
+                    t.translate(3.0, -1.0),
+                    CGAffineTransformTranslate(t, 3.0, -1.0)
+                );
+                assert_eq!(t.scale(3.0, -1.0), CGAffineTransformScale(t, 3.0, -1.0));
+                assert_eq!(t * ts[0], CGAffineTransformConcat(t, ts[0]));
+                assert_close(&t.invert(), &CGAffineTransformInvert(t));
+                assert_eq!(t.apply(&r), CGRectApplyAffineTransform(r, t));
+                let c = CGAffineTransformDecompose(t);
+                assert_close(&AffineTransform::with_components(c), &t);
+            }
+        }
+    }
 }
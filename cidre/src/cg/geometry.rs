@@ -1,3 +1,4 @@
+#[cfg(target_vendor = "apple")]
 use crate::{arc, cf};
 
 // #[cfg(target_os = "watchos")]
@@ -14,6 +15,9 @@ pub struct Point {
 }
 
 impl Point {
+    #[doc(alias = "CGPointZero")]
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
     pub fn zero() -> Self {
         Default::default()
     }
@@ -24,14 +28,21 @@ impl Point {
     /// let d = cg::Point::zero().dictionary_representaion();
     /// assert_eq!(d.len(), 2);
     /// ```
+    #[cfg(target_vendor = "apple")]
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGPointCreateDictionaryRepresentation(*self) }
     }
 
+    #[doc(alias = "CGPointMake")]
     #[inline]
-    pub fn new(x: Float, y: Float) -> Self {
+    pub const fn new(x: Float, y: Float) -> Self {
         Self { x, y }
     }
+
+    #[inline]
+    pub fn distance(&self, other: &Self) -> Float {
+        (*other - *self).length()
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
@@ -42,6 +53,9 @@ pub struct Size {
 }
 
 impl Size {
+    #[doc(alias = "CGSizeZero")]
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
     pub fn zero() -> Self {
         Default::default()
     }
@@ -52,12 +66,14 @@ impl Size {
     /// let d = cg::Size::zero().dictionary_representaion();
     /// assert_eq!(d.len(), 2);
     /// ```
+    #[cfg(target_vendor = "apple")]
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGSizeCreateDictionaryRepresentation(*self) }
     }
 
+    #[doc(alias = "CGSizeMake")]
     #[inline]
-    pub fn new(width: Float, height: Float) -> Self {
+    pub const fn new(width: Float, height: Float) -> Self {
         Self { width, height }
     }
 }
@@ -70,6 +86,20 @@ pub struct Rect {
 }
 
 impl Rect {
+    #[doc(alias = "CGRectZero")]
+    pub const ZERO: Self = Self::new(0.0, 0.0, 0.0, 0.0);
+
+    /// The null rectangle. This is the rectangle returned when, for example,
+    /// you intersect two disjoint rectangles. Note that the null rect is not
+    /// the same as the zero rect.
+    #[doc(alias = "CGRectNull")]
+    pub const NULL: Self = Self::new(Float::INFINITY, Float::INFINITY, 0.0, 0.0);
+
+    /// The infinite rectangle.
+    #[doc(alias = "CGRectInfinite")]
+    pub const INFINITE: Self =
+        Self::new(-Float::MAX / 2.0, -Float::MAX / 2.0, Float::MAX, Float::MAX);
+
     #[inline]
     pub fn zero() -> Self {
         Default::default()
@@ -81,12 +111,14 @@ impl Rect {
     /// let d = cg::Rect::zero().dictionary_representaion();
     /// assert_eq!(d.len(), 4);
     /// ```
+    #[cfg(target_vendor = "apple")]
     pub fn dictionary_representaion(&self) -> arc::R<cf::Dictionary> {
         unsafe { CGRectCreateDictionaryRepresentation(*self) }
     }
 
+    #[doc(alias = "CGRectMake")]
     #[inline]
-    pub fn new(x: Float, y: Float, width: Float, height: Float) -> Self {
+    pub const fn new(x: Float, y: Float, width: Float, height: Float) -> Self {
         Self {
             origin: Point { x, y },
             size: Size { width, height },
@@ -94,12 +126,294 @@ impl Rect {
     }
 
     #[inline]
-    pub fn with_size(width: Float, height: Float) -> Self {
+    pub const fn with_size(width: Float, height: Float) -> Self {
         Self {
-            origin: Point::zero(),
+            origin: Point::ZERO,
             size: Size { width, height },
         }
     }
+
+    #[inline]
+    pub const fn with_origin_size(origin: Point, size: Size) -> Self {
+        Self { origin, size }
+    }
+
+    /// Smallest rect containing both points.
+    #[inline]
+    pub fn with_points(a: Point, b: Point) -> Self {
+        let x = a.x.min(b.x);
+        let y = a.y.min(b.y);
+        Self::new(x, y, a.x.max(b.x) - x, a.y.max(b.y) - y)
+    }
+
+    #[doc(alias = "CGRectGetMinX")]
+    #[inline]
+    pub fn min_x(&self) -> Float {
+        self.origin.x.min(self.origin.x + self.size.width)
+    }
+
+    #[doc(alias = "CGRectGetMidX")]
+    #[inline]
+    pub fn mid_x(&self) -> Float {
+        self.origin.x + self.size.width / 2.0
+    }
+
+    #[doc(alias = "CGRectGetMaxX")]
+    #[inline]
+    pub fn max_x(&self) -> Float {
+        self.origin.x.max(self.origin.x + self.size.width)
+    }
+
+    #[doc(alias = "CGRectGetMinY")]
+    #[inline]
+    pub fn min_y(&self) -> Float {
+        self.origin.y.min(self.origin.y + self.size.height)
+    }
+
+    #[doc(alias = "CGRectGetMidY")]
+    #[inline]
+    pub fn mid_y(&self) -> Float {
+        self.origin.y + self.size.height / 2.0
+    }
+
+    #[doc(alias = "CGRectGetMaxY")]
+    #[inline]
+    pub fn max_y(&self) -> Float {
+        self.origin.y.max(self.origin.y + self.size.height)
+    }
+
+    /// Width of the standardized rect.
+    #[doc(alias = "CGRectGetWidth")]
+    #[inline]
+    pub fn width(&self) -> Float {
+        self.size.width.abs()
+    }
+
+    /// Height of the standardized rect.
+    #[doc(alias = "CGRectGetHeight")]
+    #[inline]
+    pub fn height(&self) -> Float {
+        self.size.height.abs()
+    }
+
+    #[inline]
+    pub fn center(&self) -> Point {
+        Point::new(self.mid_x(), self.mid_y())
+    }
+
+    /// Null rect has an infinite origin.
+    #[doc(alias = "CGRectIsNull")]
+    #[inline]
+    pub fn is_null(&self) -> bool {
+        self.origin.x == Float::INFINITY || self.origin.y == Float::INFINITY
+    }
+
+    /// Null rect or a rect with zero width or height.
+    #[doc(alias = "CGRectIsEmpty")]
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.is_null() || self.size.width == 0.0 || self.size.height == 0.0
+    }
+
+    #[doc(alias = "CGRectIsInfinite")]
+    #[inline]
+    pub fn is_infinite(&self) -> bool {
+        self.standardized() == Self::INFINITE
+    }
+
+    /// Equivalent rect with non-negative width and height.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let r = cg::Rect::new(10.0, 10.0, -5.0, -20.0);
+    /// assert_eq!(r.standardized(), cg::Rect::new(5.0, -10.0, 5.0, 20.0));
+    /// ```
+    #[doc(alias = "CGRectStandardize")]
+    #[inline]
+    pub fn standardized(&self) -> Self {
+        if self.is_null() {
+            return Self::NULL;
+        }
+        Self::new(self.min_x(), self.min_y(), self.width(), self.height())
+    }
+
+    /// Smallest rect with integral origin and size containing this rect.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let r = cg::Rect::new(0.5, 1.2, 2.0, 2.9);
+    /// assert_eq!(r.integral(), cg::Rect::new(0.0, 1.0, 3.0, 4.0));
+    /// ```
+    #[doc(alias = "CGRectIntegral")]
+    pub fn integral(&self) -> Self {
+        if self.is_null() {
+            return Self::NULL;
+        }
+        let x = self.min_x().floor();
+        let y = self.min_y().floor();
+        Self::new(x, y, self.max_x().ceil() - x, self.max_y().ceil() - y)
+    }
+
+    /// Standardized rect with sides moved towards the center by `dx` and `dy`.
+    /// Negative values grow the rect. Returns [`Self::NULL`] when the result
+    /// has negative size.
+    #[doc(alias = "CGRectInset")]
+    pub fn inset(&self, dx: Float, dy: Float) -> Self {
+        if self.is_null() {
+            return Self::NULL;
+        }
+        let r = self.standardized();
+        let width = r.size.width - 2.0 * dx;
+        let height = r.size.height - 2.0 * dy;
+        if width < 0.0 || height < 0.0 {
+            return Self::NULL;
+        }
+        Self::new(r.origin.x + dx, r.origin.y + dy, width, height)
+    }
+
+    #[doc(alias = "CGRectOffset")]
+    #[inline]
+    pub fn offset(&self, dx: Float, dy: Float) -> Self {
+        if self.is_null() {
+            return Self::NULL;
+        }
+        Self::with_origin_size(
+            Point::new(self.origin.x + dx, self.origin.y + dy),
+            self.size,
+        )
+    }
+
+    /// Smallest rect containing both rects. Null rects are ignored.
+    #[doc(alias = "CGRectUnion")]
+    pub fn union(&self, other: &Self) -> Self {
+        if self.is_null() {
+            return other.standardized();
+        }
+        if other.is_null() {
+            return self.standardized();
+        }
+        let x = self.min_x().min(other.min_x());
+        let y = self.min_y().min(other.min_y());
+        Self::new(
+            x,
+            y,
+            self.max_x().max(other.max_x()) - x,
+            self.max_y().max(other.max_y()) - y,
+        )
+    }
+
+    /// Common part of both rects, [`Self::NULL`] if they don't intersect.
+    /// Rects sharing an edge give an empty rect on that edge.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let a = cg::Rect::new(0.0, 0.0, 10.0, 10.0);
+    /// let b = cg::Rect::new(5.0, 5.0, 10.0, 10.0);
+    /// assert_eq!(a.intersection(&b), cg::Rect::new(5.0, 5.0, 5.0, 5.0));
+    /// assert!(a.intersection(&b.offset(20.0, 0.0)).is_null());
+    /// ```
+    #[doc(alias = "CGRectIntersection")]
+    pub fn intersection(&self, other: &Self) -> Self {
+        if self.is_null() || other.is_null() {
+            return Self::NULL;
+        }
+        let x0 = self.min_x().max(other.min_x());
+        let y0 = self.min_y().max(other.min_y());
+        let x1 = self.max_x().min(other.max_x());
+        let y1 = self.max_y().min(other.max_y());
+        if x1 < x0 || y1 < y0 {
+            return Self::NULL;
+        }
+        Self::new(x0, y0, x1 - x0, y1 - y0)
+    }
+
+    /// Splits the standardized rect in two. `slice` is `amount` wide (clamped
+    /// to the rect) and touches `edge`, `remainder` is the rest.
+    ///
+    /// ```
+    /// use cidre::cg;
+    ///
+    /// let r = cg::Rect::new(0.0, 0.0, 100.0, 50.0);
+    /// let (slice, rest) = r.divide(30.0, cg::RectEdge::MaxX);
+    /// assert_eq!(slice, cg::Rect::new(70.0, 0.0, 30.0, 50.0));
+    /// assert_eq!(rest, cg::Rect::new(0.0, 0.0, 70.0, 50.0));
+    /// ```
+    #[doc(alias = "CGRectDivide")]
+    pub fn divide(&self, amount: Float, edge: RectEdge) -> (Self, Self) {
+        if self.is_null() {
+            return (Self::NULL, Self::NULL);
+        }
+        let r = self.standardized();
+        let (x, y, w, h) = (r.origin.x, r.origin.y, r.size.width, r.size.height);
+        match edge {
+            RectEdge::MinX => {
+                let a = amount.max(0.0).min(w);
+                (Self::new(x, y, a, h), Self::new(x + a, y, w - a, h))
+            }
+            RectEdge::MaxX => {
+                let a = amount.max(0.0).min(w);
+                (Self::new(x + w - a, y, a, h), Self::new(x, y, w - a, h))
+            }
+            RectEdge::MinY => {
+                let a = amount.max(0.0).min(h);
+                (Self::new(x, y, w, a), Self::new(x, y + a, w, h - a))
+            }
+            RectEdge::MaxY => {
+                let a = amount.max(0.0).min(h);
+                (Self::new(x, y + h - a, w, a), Self::new(x, y, w, h - a))
+            }
+        }
+    }
+
+    /// Point is inside the rect. Max edges are exclusive.
+    #[doc(alias = "CGRectContainsPoint")]
+    #[inline]
+    pub fn contains_point(&self, point: Point) -> bool {
+        !self.is_null()
+            && point.x >= self.min_x()
+            && point.x < self.max_x()
+            && point.y >= self.min_y()
+            && point.y < self.max_y()
+    }
+
+    /// Union of both rects is this rect.
+    #[doc(alias = "CGRectContainsRect")]
+    #[inline]
+    pub fn contains_rect(&self, other: &Self) -> bool {
+        self.union(other) == self.standardized()
+    }
+
+    /// Rects have a non-empty intersection.
+    #[doc(alias = "CGRectIntersectsRect")]
+    #[inline]
+    pub fn intersects(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+
+    /// Rects are equal once standardized.
+    #[doc(alias = "CGRectEqualToRect")]
+    #[inline]
+    pub fn equal_to_rect(&self, other: &Self) -> bool {
+        self.standardized() == other.standardized()
+    }
+}
+
+/// Coordinates that establish the edges of a rectangle.
+#[doc(alias = "CGRectEdge")]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[repr(u32)]
+pub enum RectEdge {
+    #[doc(alias = "CGRectMinXEdge")]
+    MinX,
+    #[doc(alias = "CGRectMinYEdge")]
+    MinY,
+    #[doc(alias = "CGRectMaxXEdge")]
+    MaxX,
+    #[doc(alias = "CGRectMaxYEdge")]
+    MaxY,
 }
 
 #[derive(Clone, Copy, PartialEq, PartialOrd, Debug, Default)]
@@ -109,8 +423,441 @@ pub struct Vector {
     pub dy: Float,
 }
 
+impl Vector {
+    pub const ZERO: Self = Self::new(0.0, 0.0);
+
+    #[doc(alias = "CGVectorMake")]
+    #[inline]
+    pub const fn new(dx: Float, dy: Float) -> Self {
+        Self { dx, dy }
+    }
+
+    #[inline]
+    pub fn length(&self) -> Float {
+        self.dx.hypot(self.dy)
+    }
+
+    #[inline]
+    pub fn dot(&self, other: &Self) -> Float {
+        self.dx * other.dx + self.dy * other.dy
+    }
+}
+
+macro_rules! impl_ops {
+    ($t:ty, $a:ident, $b:ident) => {
+        impl std::ops::Mul<Float> for $t {
+            type Output = Self;
+
+            #[inline]
+            fn mul(self, rhs: Float) -> Self {
+                Self {
+                    $a: self.$a * rhs,
+                    $b: self.$b * rhs,
+                }
+            }
+        }
+
+        impl std::ops::MulAssign<Float> for $t {
+            #[inline]
+            fn mul_assign(&mut self, rhs: Float) {
+                *self = *self * rhs;
+            }
+        }
+
+        impl std::ops::Div<Float> for $t {
+            type Output = Self;
+
+            #[inline]
+            fn div(self, rhs: Float) -> Self {
+                Self {
+                    $a: self.$a / rhs,
+                    $b: self.$b / rhs,
+                }
+            }
+        }
+
+        impl std::ops::DivAssign<Float> for $t {
+            #[inline]
+            fn div_assign(&mut self, rhs: Float) {
+                *self = *self / rhs;
+            }
+        }
+
+        impl std::ops::Neg for $t {
+            type Output = Self;
+
+            #[inline]
+            fn neg(self) -> Self {
+                Self {
+                    $a: -self.$a,
+                    $b: -self.$b,
+                }
+            }
+        }
+    };
+}
+
+impl_ops!(Point, x, y);
+impl_ops!(Size, width, height);
+impl_ops!(Vector, dx, dy);
+
+impl std::ops::Add for Size {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.width + rhs.width, self.height + rhs.height)
+    }
+}
+
+impl std::ops::Sub for Size {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.width - rhs.width, self.height - rhs.height)
+    }
+}
+
+impl std::ops::Add for Vector {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.dx + rhs.dx, self.dy + rhs.dy)
+    }
+}
+
+impl std::ops::AddAssign for Vector {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub for Vector {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.dx - rhs.dx, self.dy - rhs.dy)
+    }
+}
+
+impl std::ops::SubAssign for Vector {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+/// Vector from `rhs` to `self`.
+impl std::ops::Sub for Point {
+    type Output = Vector;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Vector {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl std::ops::Add<Vector> for Point {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Vector) -> Self {
+        Self::new(self.x + rhs.dx, self.y + rhs.dy)
+    }
+}
+
+impl std::ops::AddAssign<Vector> for Point {
+    #[inline]
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub<Vector> for Point {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Vector) -> Self {
+        Self::new(self.x - rhs.dx, self.y - rhs.dy)
+    }
+}
+
+impl std::ops::SubAssign<Vector> for Point {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+/// Same as [`Rect::offset`].
+impl std::ops::Add<Vector> for Rect {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Vector) -> Self {
+        self.offset(rhs.dx, rhs.dy)
+    }
+}
+
+impl std::ops::AddAssign<Vector> for Rect {
+    #[inline]
+    fn add_assign(&mut self, rhs: Vector) {
+        *self = *self + rhs;
+    }
+}
+
+impl std::ops::Sub<Vector> for Rect {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Vector) -> Self {
+        self.offset(-rhs.dx, -rhs.dy)
+    }
+}
+
+impl std::ops::SubAssign<Vector> for Rect {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Vector) {
+        *self = *self - rhs;
+    }
+}
+
+/// Same as [`Rect::union`].
+impl std::ops::BitOr for Rect {
+    type Output = Self;
+
+    #[inline]
+    fn bitor(self, rhs: Self) -> Self {
+        self.union(&rhs)
+    }
+}
+
+/// Same as [`Rect::intersection`].
+impl std::ops::BitAnd for Rect {
+    type Output = Self;
+
+    #[inline]
+    fn bitand(self, rhs: Self) -> Self {
+        self.intersection(&rhs)
+    }
+}
+
+#[cfg(feature = "simd")]
+mod simd_conv {
+    use super::{Float, Point, Rect, Size, Vector};
+    use crate::simd;
+
+    macro_rules! conv2 {
+        ($t:ty, $a:ident, $b:ident, $s:ty, $f:ty) => {
+            impl From<$t> for $s {
+                #[inline]
+                fn from(val: $t) -> Self {
+                    Self::with_xy(val.$a as $f, val.$b as $f)
+                }
+            }
+
+            impl From<$s> for $t {
+                #[inline]
+                fn from(val: $s) -> Self {
+                    Self {
+                        $a: val.x() as Float,
+                        $b: val.y() as Float,
+                    }
+                }
+            }
+        };
+    }
+
+    conv2!(Point, x, y, simd::f32x2, f32);
+    conv2!(Point, x, y, simd::f64x2, f64);
+    conv2!(Size, width, height, simd::f32x2, f32);
+    conv2!(Size, width, height, simd::f64x2, f64);
+    conv2!(Vector, dx, dy, simd::f32x2, f32);
+    conv2!(Vector, dx, dy, simd::f64x2, f64);
+
+    macro_rules! conv4 {
+        ($s:ty, $f:ty) => {
+            /// `(x, y, width, height)`
+            impl From<Rect> for $s {
+                #[inline]
+                fn from(val: Rect) -> Self {
+                    Self::with_xyzw(
+                        val.origin.x as $f,
+                        val.origin.y as $f,
+                        val.size.width as $f,
+                        val.size.height as $f,
+                    )
+                }
+            }
+
+            /// `(x, y, width, height)`
+            impl From<$s> for Rect {
+                #[inline]
+                fn from(val: $s) -> Self {
+                    Self::new(
+                        val.x() as Float,
+                        val.y() as Float,
+                        val.z() as Float,
+                        val.w() as Float,
+                    )
+                }
+            }
+        };
+    }
+
+    conv4!(simd::f32x4, f32);
+    conv4!(simd::f64x4, f64);
+}
+
+#[cfg(target_vendor = "apple")]
 extern "C" {
     fn CGPointCreateDictionaryRepresentation(point: Point) -> arc::R<cf::Dictionary>;
     fn CGSizeCreateDictionaryRepresentation(size: Size) -> arc::R<cf::Dictionary>;
     fn CGRectCreateDictionaryRepresentation(rect: Rect) -> arc::R<cf::Dictionary>;
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cg;
+
+    #[test]
+    fn null_and_empty() {
+        let null = cg::Rect::NULL;
+        assert!(null.is_null());
+        assert!(null.is_empty());
+        assert!(!null.is_infinite());
+        assert!(!cg::Rect::ZERO.is_null());
+        assert!(cg::Rect::ZERO.is_empty());
+        assert!(cg::Rect::INFINITE.is_infinite());
+        assert!(!cg::Rect::INFINITE.is_empty());
+        assert!(cg::Rect::new(1.0, 1.0, 0.0, 5.0).is_empty());
+
+        assert_eq!(null.standardized(), null);
+        assert_eq!(null.integral(), null);
+        assert_eq!(null.inset(1.0, 1.0), null);
+        assert_eq!(null.offset(1.0, 1.0), null);
+        assert_eq!(null.divide(1.0, cg::RectEdge::MinX), (null, null));
+        assert!(!null.contains_point(cg::Point::ZERO));
+    }
+
+    #[test]
+    fn accessors() {
+        let r = cg::Rect::new(10.0, 20.0, -4.0, 6.0);
+        assert_eq!(r.min_x(), 6.0);
+        assert_eq!(r.mid_x(), 8.0);
+        assert_eq!(r.max_x(), 10.0);
+        assert_eq!(r.min_y(), 20.0);
+        assert_eq!(r.mid_y(), 23.0);
+        assert_eq!(r.max_y(), 26.0);
+        assert_eq!(r.width(), 4.0);
+        assert_eq!(r.height(), 6.0);
+        assert!(r.equal_to_rect(&cg::Rect::new(6.0, 20.0, 4.0, 6.0)));
+        assert_ne!(r, cg::Rect::new(6.0, 20.0, 4.0, 6.0));
+    }
+
+    #[test]
+    fn set_ops() {
+        let a = cg::Rect::new(0.0, 0.0, 10.0, 10.0);
+        let b = cg::Rect::new(10.0, 0.0, 10.0, 10.0);
+
+        assert_eq!(a | b, cg::Rect::new(0.0, 0.0, 20.0, 10.0));
+        assert_eq!(a | cg::Rect::NULL, a);
+        assert_eq!(cg::Rect::NULL | b, b);
+
+        // touching rects intersect in an empty rect
+        assert_eq!(a & b, cg::Rect::new(10.0, 0.0, 0.0, 10.0));
+        assert!(!a.intersects(&b));
+        assert!(a.intersects(&a.inset(2.0, 2.0)));
+        assert!((a & cg::Rect::NULL).is_null());
+
+        assert!(a.contains_rect(&a.inset(1.0, 1.0)));
+        assert!(a.contains_rect(&a));
+        assert!(!a.contains_rect(&b));
+        assert!(a.contains_point(cg::Point::new(0.0, 0.0)));
+        assert!(!a.contains_point(cg::Point::new(10.0, 5.0)));
+    }
+
+    #[test]
+    fn inset_offset_divide() {
+        let r = cg::Rect::new(0.0, 0.0, 10.0, 4.0);
+        assert_eq!(r.inset(1.0, 1.0), cg::Rect::new(1.0, 1.0, 8.0, 2.0));
+        assert_eq!(r.inset(-1.0, 0.0), cg::Rect::new(-1.0, 0.0, 12.0, 4.0));
+        assert!(r.inset(0.0, 3.0).is_null());
+        assert_eq!(r.inset(0.0, 2.0), cg::Rect::new(0.0, 2.0, 10.0, 0.0));
+
+        assert_eq!(
+            r + cg::Vector::new(1.0, -1.0),
+            cg::Rect::new(1.0, -1.0, 10.0, 4.0)
+        );
+
+        let (slice, rest) = r.divide(3.0, cg::RectEdge::MinX);
+        assert_eq!(slice, cg::Rect::new(0.0, 0.0, 3.0, 4.0));
+        assert_eq!(rest, cg::Rect::new(3.0, 0.0, 7.0, 4.0));
+
+        let (slice, rest) = r.divide(100.0, cg::RectEdge::MinY);
+        assert_eq!(slice, r);
+        assert_eq!(rest, cg::Rect::new(0.0, 4.0, 10.0, 0.0));
+
+        let (slice, rest) = r.divide(-1.0, cg::RectEdge::MaxY);
+        assert_eq!(slice, cg::Rect::new(0.0, 4.0, 10.0, 0.0));
+        assert_eq!(rest, r);
+    }
+
+    #[test]
+    fn divide_nan() {
+        let nan = cg::Float::NAN;
+        let r = cg::Rect::new(0.0, 0.0, nan, nan);
+        for edge in [
+            cg::RectEdge::MinX,
+            cg::RectEdge::MaxX,
+            cg::RectEdge::MinY,
+            cg::RectEdge::MaxY,
+        ] {
+            let (slice, rest) = r.divide(3.0, edge);
+            assert!(slice.size.width.is_nan() || slice.size.width == 3.0);
+            assert!(rest.size.width.is_nan() && rest.size.height.is_nan());
+        }
+
+        let r = cg::Rect::new(0.0, 0.0, 10.0, 5.0);
+        let (slice, rest) = r.divide(nan, cg::RectEdge::MinX);
+        assert_eq!(slice, cg::Rect::new(0.0, 0.0, 0.0, 5.0));
+        assert_eq!(rest, r);
+    }
+
+    #[test]
+    fn ops() {
+        let p = cg::Point::new(1.0, 2.0);
+        let q = cg::Point::new(4.0, 6.0);
+        assert_eq!(q - p, cg::Vector::new(3.0, 4.0));
+        assert_eq!(p.distance(&q), 5.0);
+        assert_eq!(p + (q - p), q);
+        assert_eq!(-p * 2.0, cg::Point::new(-2.0, -4.0));
+        assert_eq!(
+            cg::Size::new(2.0, 3.0) + cg::Size::new(1.0, 1.0),
+            cg::Size::new(3.0, 4.0)
+        );
+        assert_eq!(
+            cg::Vector::new(1.0, 0.0).dot(&cg::Vector::new(0.0, 1.0)),
+            0.0
+        );
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd() {
+        use crate::simd;
+
+        let r = cg::Rect::new(1.0, 2.0, 3.0, 4.0);
+        let v: simd::f32x4 = r.into();
+        assert_eq!(v, simd::f32x4::with_xyzw(1.0, 2.0, 3.0, 4.0));
+        assert_eq!(cg::Rect::from(v), r);
+
+        let p: simd::f64x2 = cg::Point::new(0.5, 1.5).into();
+        assert_eq!(p.y(), 1.5);
+        assert_eq!(cg::Size::from(p), cg::Size::new(0.5, 1.5));
+    }
+}
@@ -24,7 +24,7 @@ pub mod err {
 
     /// A required resource could not be created
     #[doc(alias = "kCGImageAnimationStatus_AllocationFailure")]
-    pub const ALLOCATION_FAILURE: Error = Error::new_unchecked(-22143);
+    pub const ALLOCATION_FAILURE: Error = Error::new_unchecked(-22144);
 }
 
 define_cf_type!(OptKey(cf::String));
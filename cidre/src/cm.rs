@@ -1,26 +1,67 @@
+#[cfg(target_vendor = "apple")]
 mod base;
+#[cfg(target_vendor = "apple")]
 pub use base::ItemCount;
+#[cfg(target_vendor = "apple")]
 pub use base::ItemIndex;
+#[cfg(target_vendor = "apple")]
 pub use base::PersistentTrackId;
 
+pub mod codec_config;
+pub use codec_config::Avcc;
+pub use codec_config::Error as CodecConfigError;
+pub use codec_config::Hvcc;
+
+#[cfg(target_vendor = "apple")]
 mod format_description;
+#[cfg(target_vendor = "apple")]
 pub use format_description::AudioFormatDesc;
+#[cfg(target_vendor = "apple")]
 pub use format_description::FormatDesc;
+#[cfg(target_vendor = "apple")]
 pub use format_description::FormatDescExtKey;
+#[cfg(target_vendor = "apple")]
 pub use format_description::LogTransferFn;
+#[cfg(target_vendor = "apple")]
 pub use format_description::MediaType;
+#[cfg(target_vendor = "apple")]
 pub use format_description::PixelFormat;
+#[cfg(target_vendor = "apple")]
+pub use format_description::TimeCodeFlags;
+#[cfg(target_vendor = "apple")]
+pub use format_description::TimeCodeFormat;
+#[cfg(target_vendor = "apple")]
+pub use format_description::TimeCodeFormatDesc;
+#[cfg(target_vendor = "apple")]
 pub use format_description::VideoCodec;
+#[cfg(target_vendor = "apple")]
 pub use format_description::VideoDimensions;
+#[cfg(target_vendor = "apple")]
 pub use format_description::VideoFormatDesc;
 
+pub mod nal;
+
+pub mod iso_bmff;
+pub use iso_bmff::Error as IsoBmffError;
+
+pub mod param_sets;
+pub use param_sets::Error as ParamSetError;
+
+#[cfg(target_vendor = "apple")]
 mod format_description_bridge;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::err as format_desc_bridge_err;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::swap_be_image_desc_to_host;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::swap_be_sound_desc_to_host;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::swap_host_image_desc_to_be;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::swap_host_sound_desc_to_be;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::ImageDescFlavor;
+#[cfg(target_vendor = "apple")]
 pub use format_description_bridge::SoundDescFlavor;
 
 mod time;
@@ -33,37 +74,60 @@ pub use time::TimeRoundingMethod;
 pub use time::TimeScale;
 pub use time::TimeValue;
 
+#[cfg(target_vendor = "apple")]
 pub mod sample_buffer;
 
+#[cfg(target_vendor = "apple")]
 pub use sample_buffer::err as sample_buf_err;
-#[cfg(feature = "cat")]
+#[cfg(all(target_vendor = "apple", feature = "cat"))]
 pub use sample_buffer::BlockBufAudioBufList;
+#[cfg(target_vendor = "apple")]
 pub use sample_buffer::Flags as SampleBufFlags;
+#[cfg(target_vendor = "apple")]
 pub use sample_buffer::SampleBuf;
+#[cfg(target_vendor = "apple")]
 pub use sample_buffer::SampleTimingInfo;
 
+#[cfg(target_vendor = "apple")]
 pub mod attachment;
+#[cfg(target_vendor = "apple")]
 pub use attachment::Bearer as AttachBearer;
+#[cfg(target_vendor = "apple")]
 pub use attachment::Mode as AttachMode;
 
+#[cfg(target_vendor = "apple")]
 pub mod block_buffer;
+#[cfg(target_vendor = "apple")]
 pub use block_buffer::err as block_buf_err;
+#[cfg(target_vendor = "apple")]
 pub use block_buffer::BlockBuf;
+#[cfg(target_vendor = "apple")]
 pub use block_buffer::Flags as BlockBufFlags;
 
+#[cfg(target_vendor = "apple")]
 pub mod sync;
+#[cfg(target_vendor = "apple")]
 pub use sync::Clock;
+#[cfg(target_vendor = "apple")]
 pub use sync::ClockOrTimebase;
+#[cfg(target_vendor = "apple")]
 pub use sync::Timebase;
 
+#[cfg(target_vendor = "apple")]
 pub mod memory_pool;
+#[cfg(target_vendor = "apple")]
 pub use memory_pool::keys as memory_pool_options;
+#[cfg(target_vendor = "apple")]
 pub use memory_pool::MemPool;
 
+#[cfg(target_vendor = "apple")]
 pub mod simple_queue;
+#[cfg(target_vendor = "apple")]
 pub use simple_queue::err as simple_queue_err;
+#[cfg(target_vendor = "apple")]
 pub use simple_queue::SimpleQueue;
 
+#[cfg(target_vendor = "apple")]
 #[link(name = "CoreMedia", kind = "framework")]
 extern "C" {}
 
@@ -255,6 +255,15 @@ impl BlockBuf {
         }
     }
 
+    /// Copies bytes from a cm::BlockBuf into `dst`, whether or not they are contiguous.
+    #[doc(alias = "CMBlockBufferCopyDataBytes")]
+    #[inline]
+    pub fn copy_data_bytes(&self, offset: usize, dst: &mut [u8]) -> os::Result {
+        unsafe {
+            CMBlockBufferCopyDataBytes(self, offset, dst.len(), dst.as_mut_ptr().cast()).result()
+        }
+    }
+
     #[inline]
     pub fn with_buf_ref(
         buf_reference: &BlockBuf,
@@ -344,6 +353,13 @@ extern "C-unwind" {
         data_pointer_out: *mut *mut u8,
     ) -> os::Status;
 
+    fn CMBlockBufferCopyDataBytes(
+        the_source_buffer: &BlockBuf,
+        offset_to_data: usize,
+        data_length: usize,
+        destination: *mut c_void,
+    ) -> os::Status;
+
     fn CMBlockBufferCreateWithBufferReference(
         structure_allocator: Option<&cf::Allocator>,
         buffer_reference: &BlockBuf,
@@ -362,31 +378,41 @@ pub mod err {
 
     /// Returned when a cm::BlockBuffer-creating API gets a failure
     /// from the cf::Allocator provided for cm::BlockBuffer construction.
+    #[doc(alias = "kCMBlockBufferStructureAllocationFailedErr")]
     pub const STRUCTURE_ALLOCATION_FAILED: Error = Error::new_unchecked(-12700);
 
     /// Returned when the allocator provided to allocate a memory block
     /// (as distinct from cm::BlockBuffer structures) fails.
+    #[doc(alias = "kCMBlockBufferBlockAllocationFailedErr")]
     pub const BLOCK_ALLOCATION_FAILED: Error = Error::new_unchecked(-12701);
 
     /// The custom block source’s Allocate() routine was NULL when an allocation was attempted.
+    #[doc(alias = "kCMBlockBufferBadCustomBlockSourceErr")]
     pub const BAD_CUSTOM_BLOCK_SOURCE: Error = Error::new_unchecked(-12702);
 
     /// The offset provided to an API is out of the range of the relevent cm::BlockBuffer
+    #[doc(alias = "kCMBlockBufferBadOffsetParameterErr")]
     pub const BAD_OFFSET_PARAMETER: Error = Error::new_unchecked(-12703);
 
     /// The length provided to an API is out of the range of the relevent cm::BlockBuffer,
     /// or is not allowed to be zero.
+    #[doc(alias = "kCMBlockBufferBadLengthParameterErr")]
     pub const BAD_LENGTH_PARAMETER: Error = Error::new_unchecked(-12704);
 
     /// A pointer parameter (e.g. cm::BlockBuffer reference, destination memory) is NULL
     /// or otherwise invalid.
+    #[doc(alias = "kCMBlockBufferBadPointerParameterErr")]
     pub const BAD_POINTER_PARAMETER: Error = Error::new_unchecked(-12705);
 
     /// Expected a non-empty cm::BlockBuffer.
+    #[doc(alias = "kCMBlockBufferEmptyBBufErr")]
     pub const EMPTY_BBUF: Error = Error::new_unchecked(-12706);
 
     /// An unallocated memory block was encountered.
+    #[doc(alias = "kCMBlockBufferUnallocatedBlockErr")]
     pub const UNALLOCATED_BLOCK: Error = Error::new_unchecked(-12707);
 
+    /// The requested operation needs more space than is available.
+    #[doc(alias = "kCMBlockBufferInsufficientSpaceErr")]
     pub const INSUFFICIENT_SPACE: Error = Error::new_unchecked(-12708);
 }
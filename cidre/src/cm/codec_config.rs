@@ -0,0 +1,586 @@
+//! AVC and HEVC decoder configuration records.
+//!
+//! `avcC` (ISO/IEC 14496-15 5.3.3.1) and `hvcC` (ISO/IEC 14496-15 8.3.3.1)
+//! are the sample description extension atoms CoreMedia keeps in
+//! [`cm::FormatDesc::avcc`] and [`cm::FormatDesc::hvcc`]. Parsing and writing
+//! them is plain byte work and doesn't touch CoreMedia.
+//!
+//! [`cm::FormatDesc::avcc`]: crate::cm::FormatDesc::avcc
+//! [`cm::FormatDesc::hvcc`]: crate::cm::FormatDesc::hvcc
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Record ends before the structure does.
+    Truncated,
+
+    /// `configurationVersion` is not 1.
+    Version(u8),
+
+    /// More than 31 SPS or 255 PPS in `avcC`, or a parameter set
+    /// longer than 65535 bytes.
+    TooMany,
+
+    /// NAL unit length size other than 1, 2 or 4.
+    NalUnitLen(u8),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("decoder configuration record is truncated"),
+            Self::Version(v) => write!(f, "unsupported configuration version {v}"),
+            Self::TooMany => f.write_str("too many or too large parameter sets"),
+            Self::NalUnitLen(n) => write!(f, "invalid NAL unit length size {n}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.0.len() < n {
+            return Err(Error::Truncated);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u48(&mut self) -> Result<u64, Error> {
+        let b = self.bytes(6)?;
+        Ok(b.iter().fold(0, |acc, b| acc << 8 | *b as u64))
+    }
+
+    /// `u16` length followed by bytes.
+    fn nal_unit(&mut self) -> Result<Vec<u8>, Error> {
+        let len = self.u16()? as usize;
+        Ok(self.bytes(len)?.to_vec())
+    }
+}
+
+fn nal_unit_len(len_minus_one: u8) -> Result<u8, Error> {
+    match len_minus_one + 1 {
+        n @ (1 | 2 | 4) => Ok(n),
+        n => Err(Error::NalUnitLen(n)),
+    }
+}
+
+fn check_nal_unit_len(n: u8) -> Result<(), Error> {
+    match n {
+        1 | 2 | 4 => Ok(()),
+        n => Err(Error::NalUnitLen(n)),
+    }
+}
+
+fn write_nal_units(buf: &mut Vec<u8>, nal_units: &[Vec<u8>]) -> Result<(), Error> {
+    for nal_unit in nal_units {
+        let len = u16::try_from(nal_unit.len()).map_err(|_| Error::TooMany)?;
+        buf.extend_from_slice(&len.to_be_bytes());
+        buf.extend_from_slice(nal_unit);
+    }
+    Ok(())
+}
+
+/// Chroma format and bit depth trailer of `avcC`, present for the high
+/// profiles (100, 110, 122 and 144).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AvccHighExt {
+    pub chroma_format: u8,
+    pub bit_depth_luma_minus8: u8,
+    pub bit_depth_chroma_minus8: u8,
+    pub sps_ext: Vec<Vec<u8>>,
+}
+
+/// `AVCDecoderConfigurationRecord`
+///
+/// ```
+/// use cidre::cm::codec_config::Avcc;
+///
+/// let sps = vec![0x67, 0x42, 0xc0, 0x1e, 0xda, 0x02, 0x80, 0xbf, 0xe5, 0x84];
+/// let pps = vec![0x68, 0xce, 0x3c, 0x80];
+/// let avcc = Avcc::with_param_sets(vec![sps], vec![pps], 4).unwrap();
+/// assert_eq!(avcc.profile_idc, 0x42);
+/// assert_eq!(avcc.level_idc, 0x1e);
+///
+/// let bytes = avcc.to_bytes().unwrap();
+/// assert_eq!(Avcc::parse(&bytes).unwrap(), avcc);
+/// ```
+#[doc(alias = "AVCDecoderConfigurationRecord")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Avcc {
+    pub profile_idc: u8,
+    pub profile_compat: u8,
+    pub level_idc: u8,
+
+    /// Size of the length prefix of each NAL unit in samples: 1, 2 or 4.
+    pub nal_unit_len: u8,
+    pub sps: Vec<Vec<u8>>,
+    pub pps: Vec<Vec<u8>>,
+    pub high_ext: Option<AvccHighExt>,
+}
+
+impl Avcc {
+    /// Record for the parameter sets with profile and level copied from the first SPS.
+    pub fn with_param_sets(
+        sps: Vec<Vec<u8>>,
+        pps: Vec<Vec<u8>>,
+        nal_unit_len: u8,
+    ) -> Result<Self, Error> {
+        check_nal_unit_len(nal_unit_len)?;
+        let first = sps.first().ok_or(Error::Truncated)?;
+        if first.len() < 4 {
+            return Err(Error::Truncated);
+        }
+        Ok(Self {
+            profile_idc: first[1],
+            profile_compat: first[2],
+            level_idc: first[3],
+            nal_unit_len,
+            sps,
+            pps,
+            high_ext: None,
+        })
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader(bytes);
+        let version = r.u8()?;
+        if version != 1 {
+            return Err(Error::Version(version));
+        }
+        let profile_idc = r.u8()?;
+        let profile_compat = r.u8()?;
+        let level_idc = r.u8()?;
+        let nal_unit_len = nal_unit_len(r.u8()? & 0b11)?;
+        let sps_count = r.u8()? & 0b11111;
+        let sps = (0..sps_count)
+            .map(|_| r.nal_unit())
+            .collect::<Result<_, _>>()?;
+        let pps_count = r.u8()?;
+        let pps = (0..pps_count)
+            .map(|_| r.nal_unit())
+            .collect::<Result<_, _>>()?;
+
+        // Plenty of muxers omit the trailer even for high profiles.
+        let high_ext = if matches!(profile_idc, 100 | 110 | 122 | 144) && r.0.len() >= 4 {
+            let chroma_format = r.u8()? & 0b11;
+            let bit_depth_luma_minus8 = r.u8()? & 0b111;
+            let bit_depth_chroma_minus8 = r.u8()? & 0b111;
+            let count = r.u8()?;
+            let sps_ext = (0..count).map(|_| r.nal_unit()).collect::<Result<_, _>>()?;
+            Some(AvccHighExt {
+                chroma_format,
+                bit_depth_luma_minus8,
+                bit_depth_chroma_minus8,
+                sps_ext,
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            profile_idc,
+            profile_compat,
+            level_idc,
+            nal_unit_len,
+            sps,
+            pps,
+            high_ext,
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        check_nal_unit_len(self.nal_unit_len)?;
+        if self.sps.len() > 31 || self.pps.len() > 255 {
+            return Err(Error::TooMany);
+        }
+        buf.extend_from_slice(&[
+            1,
+            self.profile_idc,
+            self.profile_compat,
+            self.level_idc,
+            0b1111_1100 | (self.nal_unit_len - 1),
+            0b1110_0000 | self.sps.len() as u8,
+        ]);
+        write_nal_units(buf, &self.sps)?;
+        buf.push(self.pps.len() as u8);
+        write_nal_units(buf, &self.pps)?;
+        if let Some(ext) = &self.high_ext {
+            let count = u8::try_from(ext.sps_ext.len()).map_err(|_| Error::TooMany)?;
+            buf.extend_from_slice(&[
+                0b1111_1100 | ext.chroma_format,
+                0b1111_1000 | ext.bit_depth_luma_minus8,
+                0b1111_1000 | ext.bit_depth_chroma_minus8,
+                count,
+            ]);
+            write_nal_units(buf, &ext.sps_ext)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// SPS followed by PPS, the order `CMVideoFormatDescriptionCreateFromH264ParameterSets` expects.
+    pub fn param_sets(&self) -> impl Iterator<Item = &[u8]> {
+        self.sps.iter().chain(self.pps.iter()).map(Vec::as_slice)
+    }
+}
+
+/// One `NAL_unit_type` group of `hvcC`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HvccArray {
+    /// All NAL units of this type are in the record, none in samples.
+    pub complete: bool,
+    pub nal_unit_type: u8,
+    pub nal_units: Vec<Vec<u8>>,
+}
+
+/// `HEVCDecoderConfigurationRecord`
+///
+/// ```
+/// use cidre::cm::codec_config::Hvcc;
+///
+/// let vps = vec![0x40, 0x01, 0x0c, 0x01];
+/// let sps = vec![0x42, 0x01, 0x01, 0x01];
+/// let pps = vec![0x44, 0x01, 0xc1, 0x72];
+/// let mut hvcc = Hvcc::default();
+/// hvcc.push(vps.clone());
+/// hvcc.push(sps);
+/// hvcc.push(pps);
+/// assert_eq!(hvcc.nal_units(Hvcc::VPS).next(), Some(&vps[..]));
+///
+/// let bytes = hvcc.to_bytes().unwrap();
+/// assert_eq!(Hvcc::parse(&bytes).unwrap(), hvcc);
+/// ```
+#[doc(alias = "HEVCDecoderConfigurationRecord")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hvcc {
+    pub general_profile_space: u8,
+    pub general_tier_flag: bool,
+    pub general_profile_idc: u8,
+    pub general_profile_compat_flags: u32,
+
+    /// 48 bits.
+    pub general_constraint_indicator_flags: u64,
+    pub general_level_idc: u8,
+    pub min_spatial_segmentation_idc: u16,
+    pub parallelism_type: u8,
+    pub chroma_format_idc: u8,
+    pub bit_depth_luma_minus8: u8,
+    pub bit_depth_chroma_minus8: u8,
+
+    /// Frames per 256 seconds, 0 if unspecified.
+    pub avg_frame_rate: u16,
+    pub constant_frame_rate: u8,
+    pub num_temporal_layers: u8,
+    pub temporal_id_nested: bool,
+
+    /// Size of the length prefix of each NAL unit in samples: 1, 2 or 4.
+    pub nal_unit_len: u8,
+    pub arrays: Vec<HvccArray>,
+}
+
+impl Default for Hvcc {
+    fn default() -> Self {
+        Self {
+            general_profile_space: 0,
+            general_tier_flag: false,
+            general_profile_idc: 0,
+            general_profile_compat_flags: 0,
+            general_constraint_indicator_flags: 0,
+            general_level_idc: 0,
+            min_spatial_segmentation_idc: 0,
+            parallelism_type: 0,
+            chroma_format_idc: 1,
+            bit_depth_luma_minus8: 0,
+            bit_depth_chroma_minus8: 0,
+            avg_frame_rate: 0,
+            constant_frame_rate: 0,
+            num_temporal_layers: 0,
+            temporal_id_nested: false,
+            nal_unit_len: 4,
+            arrays: Vec::new(),
+        }
+    }
+}
+
+impl Hvcc {
+    pub const VPS: u8 = 32;
+    pub const SPS: u8 = 33;
+    pub const PPS: u8 = 34;
+    pub const PREFIX_SEI: u8 = 39;
+    pub const SUFFIX_SEI: u8 = 40;
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader(bytes);
+        let version = r.u8()?;
+        if version != 1 {
+            return Err(Error::Version(version));
+        }
+        let b = r.u8()?;
+        let general_profile_compat_flags = r.u32()?;
+        let general_constraint_indicator_flags = r.u48()?;
+        let general_level_idc = r.u8()?;
+        let min_spatial_segmentation_idc = r.u16()? & 0x0fff;
+        let parallelism_type = r.u8()? & 0b11;
+        let chroma_format_idc = r.u8()? & 0b11;
+        let bit_depth_luma_minus8 = r.u8()? & 0b111;
+        let bit_depth_chroma_minus8 = r.u8()? & 0b111;
+        let avg_frame_rate = r.u16()?;
+        let flags = r.u8()?;
+        let count = r.u8()?;
+        let mut arrays = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            let t = r.u8()?;
+            let n = r.u16()?;
+            arrays.push(HvccArray {
+                complete: t & 0x80 != 0,
+                nal_unit_type: t & 0x3f,
+                nal_units: (0..n).map(|_| r.nal_unit()).collect::<Result<_, _>>()?,
+            });
+        }
+        Ok(Self {
+            general_profile_space: b >> 6,
+            general_tier_flag: b & 0x20 != 0,
+            general_profile_idc: b & 0x1f,
+            general_profile_compat_flags,
+            general_constraint_indicator_flags,
+            general_level_idc,
+            min_spatial_segmentation_idc,
+            parallelism_type,
+            chroma_format_idc,
+            bit_depth_luma_minus8,
+            bit_depth_chroma_minus8,
+            avg_frame_rate,
+            constant_frame_rate: flags >> 6,
+            num_temporal_layers: (flags >> 3) & 0b111,
+            temporal_id_nested: flags & 0b100 != 0,
+            nal_unit_len: nal_unit_len(flags & 0b11)?,
+            arrays,
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut Vec<u8>) -> Result<(), Error> {
+        check_nal_unit_len(self.nal_unit_len)?;
+        let count = u8::try_from(self.arrays.len()).map_err(|_| Error::TooMany)?;
+        buf.push(1);
+        buf.push(
+            self.general_profile_space << 6
+                | (self.general_tier_flag as u8) << 5
+                | self.general_profile_idc & 0x1f,
+        );
+        buf.extend_from_slice(&self.general_profile_compat_flags.to_be_bytes());
+        buf.extend_from_slice(&self.general_constraint_indicator_flags.to_be_bytes()[2..]);
+        buf.push(self.general_level_idc);
+        buf.extend_from_slice(&(0xf000 | self.min_spatial_segmentation_idc).to_be_bytes());
+        buf.extend_from_slice(&[
+            0b1111_1100 | self.parallelism_type,
+            0b1111_1100 | self.chroma_format_idc,
+            0b1111_1000 | self.bit_depth_luma_minus8,
+            0b1111_1000 | self.bit_depth_chroma_minus8,
+        ]);
+        buf.extend_from_slice(&self.avg_frame_rate.to_be_bytes());
+        buf.push(
+            self.constant_frame_rate << 6
+                | (self.num_temporal_layers & 0b111) << 3
+                | (self.temporal_id_nested as u8) << 2
+                | (self.nal_unit_len - 1),
+        );
+        buf.push(count);
+        for array in &self.arrays {
+            let n = u16::try_from(array.nal_units.len()).map_err(|_| Error::TooMany)?;
+            buf.push((array.complete as u8) << 7 | array.nal_unit_type & 0x3f);
+            buf.extend_from_slice(&n.to_be_bytes());
+            write_nal_units(buf, &array.nal_units)?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut buf = Vec::new();
+        self.write_to(&mut buf)?;
+        Ok(buf)
+    }
+
+    /// Appends a NAL unit to the array of its type, creating a complete
+    /// array if there is none yet.
+    pub fn push(&mut self, nal_unit: Vec<u8>) {
+        let nal_unit_type = nal_unit.first().map_or(0, |b| (b >> 1) & 0x3f);
+        match self
+            .arrays
+            .iter_mut()
+            .find(|a| a.nal_unit_type == nal_unit_type)
+        {
+            Some(array) => array.nal_units.push(nal_unit),
+            None => self.arrays.push(HvccArray {
+                complete: true,
+                nal_unit_type,
+                nal_units: vec![nal_unit],
+            }),
+        }
+    }
+
+    /// NAL units of `nal_unit_type`, e.g. [`Self::SPS`].
+    pub fn nal_units(&self, nal_unit_type: u8) -> impl Iterator<Item = &[u8]> {
+        self.arrays
+            .iter()
+            .filter(move |a| a.nal_unit_type == nal_unit_type)
+            .flat_map(|a| a.nal_units.iter().map(Vec::as_slice))
+    }
+
+    /// VPS, SPS and PPS, in the order `CMVideoFormatDescriptionCreateFromHEVCParameterSets`
+    /// expects. SEI arrays are skipped.
+    pub fn param_sets(&self) -> impl Iterator<Item = &[u8]> {
+        self.nal_units(Self::VPS)
+            .chain(self.nal_units(Self::SPS))
+            .chain(self.nal_units(Self::PPS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cm::codec_config::{Avcc, AvccHighExt, Error, Hvcc, HvccArray};
+
+    /// `avcC` laid out the way VideoToolbox writes it for High profile,
+    /// reserved bits set and the chroma/bit depth trailer present.
+    const AVCC_HIGH: &[u8] = &[
+        0x01, 0x64, 0x00, 0x1f, 0xff, 0xe1, 0x00, 0x10, 0x67, 0x64, 0x00, 0x1f, 0xac, 0xd9, 0x40,
+        0x50, 0x05, 0xbb, 0x01, 0x6a, 0x02, 0x02, 0x02, 0x80, 0x01, 0x00, 0x04, 0x68, 0xee, 0x3c,
+        0xb0, 0xfd, 0xf8, 0xf8, 0x00,
+    ];
+
+    /// `hvcC` of a 1920x1080 Main profile, level 4.1 stream. The VPS, SPS
+    /// and PPS are complete NAL units that parse down to the trailing bits.
+    const HVCC_MAIN: &[u8] = &[
+        0x01, 0x01, 0x60, 0x00, 0x00, 0x00, 0xb0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x7b, 0xf0, 0x00,
+        0xfc, 0xfd, 0xf8, 0xf8, 0x00, 0x00, 0x0f, 0x03, 0xa0, 0x00, 0x01, 0x00, 0x17, 0x40, 0x01,
+        0x0c, 0x01, 0xff, 0xff, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00, 0x00, 0x03, 0x00,
+        0x00, 0x03, 0x00, 0x7b, 0xac, 0x09, 0xa1, 0x00, 0x01, 0x00, 0x22, 0x42, 0x01, 0x01, 0x01,
+        0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00, 0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x7b, 0xa0,
+        0x03, 0xc0, 0x80, 0x10, 0xe5, 0x8d, 0xae, 0x49, 0x32, 0xf4, 0xdc, 0x04, 0x04, 0x04, 0x02,
+        0xa2, 0x00, 0x01, 0x00, 0x06, 0x44, 0x01, 0xc1, 0x73, 0xd1, 0x89,
+    ];
+
+    /// Parameter sets of [`HVCC_MAIN`], `cm::param_sets::hevc` tests parse
+    /// the same bytes.
+    const VPS: &[u8] = &[
+        0x40, 0x01, 0x0c, 0x01, 0xff, 0xff, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00, 0x00,
+        0x03, 0x00, 0x00, 0x03, 0x00, 0x7b, 0xac, 0x09,
+    ];
+    const SPS: &[u8] = &[
+        0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00, 0x00, 0x03, 0x00, 0x00,
+        0x03, 0x00, 0x7b, 0xa0, 0x03, 0xc0, 0x80, 0x10, 0xe5, 0x8d, 0xae, 0x49, 0x32, 0xf4, 0xdc,
+        0x04, 0x04, 0x04, 0x02,
+    ];
+    const PPS: &[u8] = &[0x44, 0x01, 0xc1, 0x73, 0xd1, 0x89];
+
+    #[test]
+    fn avcc() {
+        let avcc = Avcc::parse(AVCC_HIGH).unwrap();
+        assert_eq!(avcc.profile_idc, 100);
+        assert_eq!(avcc.level_idc, 31);
+        assert_eq!(avcc.nal_unit_len, 4);
+        assert_eq!(avcc.sps.len(), 1);
+        assert_eq!(avcc.sps[0].len(), 16);
+        assert_eq!(avcc.pps, [vec![0x68, 0xee, 0x3c, 0xb0]]);
+        assert_eq!(
+            avcc.high_ext,
+            Some(AvccHighExt {
+                chroma_format: 1,
+                bit_depth_luma_minus8: 0,
+                bit_depth_chroma_minus8: 0,
+                sps_ext: vec![],
+            })
+        );
+        assert_eq!(avcc.to_bytes().unwrap(), AVCC_HIGH);
+        assert_eq!(avcc.param_sets().count(), 2);
+
+        // trailer is optional
+        let short = Avcc::parse(&AVCC_HIGH[..AVCC_HIGH.len() - 4]).unwrap();
+        assert_eq!(short.high_ext, None);
+
+        assert_eq!(Avcc::parse(&AVCC_HIGH[..10]), Err(Error::Truncated));
+        assert_eq!(Avcc::parse(&[0, 0x64]), Err(Error::Version(0)));
+        let mut bad = AVCC_HIGH.to_vec();
+        bad[4] = 0xfe;
+        assert_eq!(Avcc::parse(&bad), Err(Error::NalUnitLen(3)));
+
+        let mut two = avcc.clone();
+        two.nal_unit_len = 2;
+        let bytes = two.to_bytes().unwrap();
+        assert_eq!(bytes[4], 0xfd);
+        assert_eq!(Avcc::parse(&bytes).unwrap(), two);
+        two.nal_unit_len = 3;
+        assert_eq!(two.to_bytes(), Err(Error::NalUnitLen(3)));
+    }
+
+    #[test]
+    fn hvcc() {
+        let hvcc = Hvcc::parse(HVCC_MAIN).unwrap();
+        assert_eq!(hvcc.general_profile_idc, 1);
+        assert!(!hvcc.general_tier_flag);
+        assert_eq!(hvcc.general_profile_compat_flags, 0x6000_0000);
+        assert_eq!(hvcc.general_constraint_indicator_flags, 0xb000_0000_0000);
+        assert_eq!(hvcc.general_level_idc, 123);
+        assert_eq!(hvcc.chroma_format_idc, 1);
+        assert_eq!(hvcc.num_temporal_layers, 1);
+        assert!(hvcc.temporal_id_nested);
+        assert_eq!(hvcc.nal_unit_len, 4);
+        assert_eq!(hvcc.arrays.len(), 3);
+        assert!(hvcc.arrays.iter().all(|a| a.complete));
+        assert_eq!(hvcc.nal_units(Hvcc::VPS).collect::<Vec<_>>(), [VPS]);
+        assert_eq!(hvcc.nal_units(Hvcc::SPS).collect::<Vec<_>>(), [SPS]);
+        assert_eq!(hvcc.nal_units(Hvcc::PPS).collect::<Vec<_>>(), [PPS]);
+        let types: Vec<_> = hvcc.param_sets().map(|n| (n[0] >> 1) & 0x3f).collect();
+        assert_eq!(types, [32, 33, 34]);
+        assert_eq!(hvcc.to_bytes().unwrap(), HVCC_MAIN);
+
+        let array = |nal_unit_type, nal_unit: &[u8]| HvccArray {
+            complete: true,
+            nal_unit_type,
+            nal_units: vec![nal_unit.to_vec()],
+        };
+        let built = Hvcc {
+            general_profile_idc: 1,
+            general_profile_compat_flags: 0x6000_0000,
+            general_constraint_indicator_flags: 0xb000_0000_0000,
+            general_level_idc: 123,
+            num_temporal_layers: 1,
+            temporal_id_nested: true,
+            arrays: vec![
+                array(Hvcc::VPS, VPS),
+                array(Hvcc::SPS, SPS),
+                array(Hvcc::PPS, PPS),
+            ],
+            ..Hvcc::default()
+        };
+        assert_eq!(built, hvcc);
+        assert_eq!(built.to_bytes().unwrap(), HVCC_MAIN);
+
+        assert_eq!(
+            Hvcc::parse(&HVCC_MAIN[..HVCC_MAIN.len() - 1]),
+            Err(Error::Truncated)
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::{ffi::c_void, mem::transmute};
 use crate::{
     api, arc,
     cf::{self, Allocator},
-    define_cf_type, os, FourCharCode,
+    cm, define_cf_type, define_opts, os, FourCc, FourCharCode,
 };
 
 #[cfg(feature = "cv")]
@@ -13,7 +13,7 @@ use crate::cv;
 use crate::cat;
 
 #[doc(alias = "CMPixelFormatType")]
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Eq, PartialEq, Copy, Clone)]
 #[repr(transparent)]
 pub struct PixelFormat(pub FourCharCode);
 
@@ -57,6 +57,28 @@ impl PixelFormat {
     }
 }
 
+impl std::fmt::Debug for PixelFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("cm::PixelFormat")
+            .field(&FourCc(self.0))
+            .finish()
+    }
+}
+
+impl From<FourCc> for PixelFormat {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<PixelFormat> for FourCc {
+    #[inline]
+    fn from(value: PixelFormat) -> Self {
+        Self(value.0)
+    }
+}
+
 #[doc(alias = "CMVideoDimensions")]
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 #[repr(C)]
@@ -66,7 +88,7 @@ pub struct VideoDimensions {
 }
 
 #[doc(alias = "CMMediaType")]
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Eq, PartialEq, Copy, Clone)]
 #[repr(transparent)]
 pub struct MediaType(pub FourCharCode);
 
@@ -106,8 +128,30 @@ impl MediaType {
     }
 }
 
+impl std::fmt::Debug for MediaType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("cm::MediaType")
+            .field(&FourCc(self.0))
+            .finish()
+    }
+}
+
+impl From<FourCc> for MediaType {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<MediaType> for FourCc {
+    #[inline]
+    fn from(value: MediaType) -> Self {
+        Self(value.0)
+    }
+}
+
 #[doc(alias = "CMVideoCodecType")]
-#[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[derive(Eq, PartialEq, Clone, Copy)]
 #[repr(transparent)]
 pub struct VideoCodec(FourCharCode);
 
@@ -171,6 +215,28 @@ impl VideoCodec {
     }
 }
 
+impl std::fmt::Debug for VideoCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("cm::VideoCodec")
+            .field(&FourCc(self.0))
+            .finish()
+    }
+}
+
+impl From<FourCc> for VideoCodec {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        Self(value.0)
+    }
+}
+
+impl From<VideoCodec> for FourCc {
+    #[inline]
+    fn from(value: VideoCodec) -> Self {
+        Self(value.0)
+    }
+}
+
 define_cf_type!(
     #[doc(alias = "CMFormatDescriptionRef")]
     FormatDesc(cf::Type)
@@ -454,6 +520,42 @@ impl VideoFormatDesc {
         }
     }
 
+    /// Video format description from a parsed `avcC` record.
+    #[doc(alias = "CMVideoFormatDescriptionCreateFromH264ParameterSets")]
+    pub fn with_avcc(avcc: &cm::codec_config::Avcc) -> os::Result<arc::R<VideoFormatDesc>> {
+        let (pointers, sizes): (Vec<_>, Vec<_>) =
+            avcc.param_sets().map(|p| (p.as_ptr(), p.len())).unzip();
+        unsafe {
+            os::result_unchecked(|res| {
+                CMVideoFormatDescriptionCreateFromH264ParameterSets(
+                    None,
+                    pointers.len(),
+                    pointers.as_ptr(),
+                    sizes.as_ptr(),
+                    avcc.nal_unit_len as i32,
+                    res,
+                )
+            })
+        }
+    }
+
+    /// Video format description from a parsed `hvcC` record.
+    #[doc(alias = "CMVideoFormatDescriptionCreateFromHEVCParameterSets")]
+    pub fn with_hvcc(
+        hvcc: &cm::codec_config::Hvcc,
+        extensions: Option<&cf::DictionaryOf<FormatDescExtKey, cf::Type>>,
+    ) -> os::Result<arc::R<VideoFormatDesc>> {
+        let (pointers, sizes): (Vec<_>, Vec<_>) =
+            hvcc.param_sets().map(|p| (p.as_ptr(), p.len())).unzip();
+        Self::with_hevc_param_sets(
+            pointers.len(),
+            &pointers,
+            &sizes,
+            hvcc.nal_unit_len as i32,
+            extensions,
+        )
+    }
+
     #[doc(alias = "CMVideoFormatDescriptionGetH264ParameterSetAtIndex")]
     #[inline]
     pub fn h264_params_count_and_header_len(&self) -> os::Result<(usize, i32)> {
@@ -606,6 +708,123 @@ impl AudioFormatDesc {
     }
 }
 
+#[doc(alias = "CMTimeCodeFormatType")]
+#[derive(Eq, PartialEq, Copy, Clone)]
+#[repr(transparent)]
+pub struct TimeCodeFormat(pub FourCharCode);
+
+impl TimeCodeFormat {
+    /// Sample payload is a big-endian `i32` frame number.
+    #[doc(alias = "kCMTimeCodeFormatType_TimeCode32")]
+    pub const TIME_CODE_32: Self = Self::from_be_bytes(b"tmcd");
+
+    /// Sample payload is a big-endian `i64` frame number.
+    #[doc(alias = "kCMTimeCodeFormatType_TimeCode64")]
+    pub const TIME_CODE_64: Self = Self::from_be_bytes(b"tc64");
+
+    #[doc(alias = "kCMTimeCodeFormatType_Counter32")]
+    pub const COUNTER_32: Self = Self::from_be_bytes(b"cn32");
+
+    #[doc(alias = "kCMTimeCodeFormatType_Counter64")]
+    pub const COUNTER_64: Self = Self::from_be_bytes(b"cn64");
+
+    const fn from_be_bytes(bytes: &[u8; 4]) -> Self {
+        Self(FourCharCode::from_be_bytes(*bytes))
+    }
+}
+
+impl std::fmt::Debug for TimeCodeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("cm::TimeCodeFormat")
+            .field(&FourCc(self.0))
+            .finish()
+    }
+}
+
+define_opts!(
+    #[doc(alias = "kCMTimeCodeFlag_DropFrame")]
+    pub TimeCodeFlags(u32)
+);
+
+impl TimeCodeFlags {
+    #[doc(alias = "kCMTimeCodeFlag_DropFrame")]
+    pub const DROP_FRAME: Self = Self(1 << 0);
+
+    #[doc(alias = "kCMTimeCodeFlag_24HourMax")]
+    pub const _24_HOUR_MAX: Self = Self(1 << 1);
+
+    #[doc(alias = "kCMTimeCodeFlag_NegTimesOK")]
+    pub const NEG_TIMES_OK: Self = Self(1 << 2);
+}
+
+pub type TimeCodeFormatDesc = FormatDesc;
+
+impl TimeCodeFormatDesc {
+    pub fn time_code(
+        format: TimeCodeFormat,
+        frame_duration: cm::Time,
+        frame_quanta: u32,
+        flags: TimeCodeFlags,
+        extensions: Option<&cf::DictionaryOf<FormatDescExtKey, cf::Type>>,
+    ) -> os::Result<arc::R<Self>> {
+        unsafe {
+            os::result_unchecked(|res| {
+                Self::create_time_code_in(
+                    format,
+                    frame_duration,
+                    frame_quanta,
+                    flags,
+                    extensions,
+                    res,
+                    None,
+                )
+            })
+        }
+    }
+
+    #[doc(alias = "CMTimeCodeFormatDescriptionCreate")]
+    pub fn create_time_code_in(
+        format: TimeCodeFormat,
+        frame_duration: cm::Time,
+        frame_quanta: u32,
+        flags: TimeCodeFlags,
+        extensions: Option<&cf::DictionaryOf<FormatDescExtKey, cf::Type>>,
+        format_description_out: *mut Option<arc::R<Self>>,
+        allocator: Option<&Allocator>,
+    ) -> os::Result {
+        unsafe {
+            CMTimeCodeFormatDescriptionCreate(
+                allocator,
+                format,
+                frame_duration,
+                frame_quanta,
+                flags,
+                extensions,
+                format_description_out,
+            )
+            .result()
+        }
+    }
+
+    #[doc(alias = "CMTimeCodeFormatDescriptionGetFrameDuration")]
+    #[inline]
+    pub fn frame_duration(&self) -> cm::Time {
+        unsafe { CMTimeCodeFormatDescriptionGetFrameDuration(self) }
+    }
+
+    #[doc(alias = "CMTimeCodeFormatDescriptionGetFrameQuanta")]
+    #[inline]
+    pub fn frame_quanta(&self) -> u32 {
+        unsafe { CMTimeCodeFormatDescriptionGetFrameQuanta(self) }
+    }
+
+    #[doc(alias = "CMTimeCodeFormatDescriptionGetTimeCodeFlags")]
+    #[inline]
+    pub fn time_code_flags(&self) -> TimeCodeFlags {
+        unsafe { CMTimeCodeFormatDescriptionGetTimeCodeFlags(self) }
+    }
+}
+
 define_cf_type!(
     #[doc(alias = "CMFormatDescription.Extensions.Key")]
     #[doc(alias = "CMFormatDescriptionExtension")]
@@ -988,6 +1207,20 @@ extern "C-unwind" {
         desc: &AudioFormatDesc,
     ) -> Option<&cat::audio::StreamBasicDesc>;
 
+    fn CMTimeCodeFormatDescriptionCreate(
+        allocator: Option<&cf::Allocator>,
+        time_code_format_type: TimeCodeFormat,
+        frame_duration: cm::Time,
+        frame_quanta: u32,
+        flags: TimeCodeFlags,
+        extensions: Option<&cf::DictionaryOf<FormatDescExtKey, cf::Type>>,
+        format_description_out: *mut Option<arc::R<TimeCodeFormatDesc>>,
+    ) -> os::Status;
+
+    fn CMTimeCodeFormatDescriptionGetFrameDuration(desc: &TimeCodeFormatDesc) -> cm::Time;
+    fn CMTimeCodeFormatDescriptionGetFrameQuanta(desc: &TimeCodeFormatDesc) -> u32;
+    fn CMTimeCodeFormatDescriptionGetTimeCodeFlags(desc: &TimeCodeFormatDesc) -> TimeCodeFlags;
+
     fn CMFormatDescriptionCreate(
         allocator: Option<&cf::Allocator>,
         media_type: MediaType,
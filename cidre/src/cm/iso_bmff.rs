@@ -0,0 +1,694 @@
+//! ISO base media file format (ISO/IEC 14496-12) boxes.
+//!
+//! Enough of MP4 and fragmented MP4 to inspect, validate and rewrite what
+//! [`crate::av::AssetWriter`] produces without AVFoundation: a zero-copy
+//! box iterator ([`atoms`]), an owned box tree for rewriting ([`Node`]) and
+//! typed boxes for the movie header, sample tables and movie fragments.
+//!
+//! ```
+//! use cidre::{cm::iso_bmff::{self, IsoBox}, fcc};
+//!
+//! let mut file = Vec::new();
+//! iso_bmff::Ftyp {
+//!     major_brand: fcc!("iso6"),
+//!     minor_version: 0,
+//!     compatible_brands: vec![fcc!("iso6"), fcc!("cmfc")],
+//! }
+//! .write_to(&mut file);
+//! iso_bmff::write_box(&mut file, fcc!("free"), |_| {});
+//!
+//! let types: Vec<_> = iso_bmff::atoms(&file).map(|a| a.unwrap().typ).collect();
+//! assert_eq!(types, [fcc!("ftyp"), fcc!("free")]);
+//!
+//! let ftyp: iso_bmff::Ftyp = iso_bmff::atoms(&file).next().unwrap().unwrap().parse().unwrap();
+//! assert_eq!(ftyp.compatible_brands[1], fcc!("cmfc"));
+//! ```
+
+use crate::{cm, fcc, FourCc};
+
+mod moov;
+pub use moov::Co64;
+pub use moov::Ctts;
+pub use moov::Ftyp;
+pub use moov::Hdlr;
+pub use moov::Mdhd;
+pub use moov::Moov;
+pub use moov::Mvhd;
+pub use moov::SampleEntry;
+pub use moov::SampleTable;
+pub use moov::SampleToChunk;
+pub use moov::Samples;
+pub use moov::Stco;
+pub use moov::Stsc;
+pub use moov::Stsd;
+pub use moov::Stss;
+pub use moov::Stsz;
+pub use moov::Stts;
+pub use moov::Styp;
+pub use moov::Tkhd;
+pub use moov::Track;
+pub use moov::Trex;
+
+mod moof;
+pub use moof::write_fragment;
+pub use moof::Emsg;
+pub use moof::EmsgTime;
+pub use moof::Mfhd;
+pub use moof::Moof;
+pub use moof::SampleFlags;
+pub use moof::Sidx;
+pub use moof::SidxRef;
+pub use moof::Tfdt;
+pub use moof::Tfhd;
+pub use moof::Traf;
+pub use moof::TrafSamples;
+pub use moof::Trun;
+pub use moof::TrunSample;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Data ends inside a box.
+    Truncated(FourCc),
+
+    /// Box size smaller than its header or larger than its parent.
+    Size(FourCc, u64),
+
+    /// Full box version this module doesn't know.
+    Version(FourCc, u8),
+
+    /// Required child box is missing.
+    Missing(FourCc),
+
+    /// Box of another type where a specific one was asked for.
+    Type {
+        expected: FourCc,
+        found: FourCc,
+    },
+
+    Invalid(FourCc, &'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated(typ) => write!(f, "{typ} box is truncated"),
+            Self::Size(typ, size) => write!(f, "{typ} box has invalid size {size}"),
+            Self::Version(typ, v) => write!(f, "{typ} box version {v} is not supported"),
+            Self::Missing(typ) => write!(f, "{typ} box is missing"),
+            Self::Type { expected, found } => write!(f, "expected {expected} box, found {found}"),
+            Self::Invalid(typ, what) => write!(f, "{typ} box has invalid {what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Big-endian cursor over a box payload.
+#[derive(Debug, Clone)]
+struct Reader<'a> {
+    typ: FourCc,
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(typ: FourCc, data: &'a [u8]) -> Self {
+        Self { typ, data }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < n {
+            return Err(Error::Truncated(self.typ));
+        }
+        let (res, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(res)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_be_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_be_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32, Error> {
+        Ok(i32::from_be_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_be_bytes(self.array()?))
+    }
+
+    fn fcc(&mut self) -> Result<FourCc, Error> {
+        Ok(FourCc::from_bytes(self.array()?))
+    }
+
+    /// 64 bit for version 1 boxes, 32 bit otherwise.
+    fn uint(&mut self, version: u8) -> Result<u64, Error> {
+        if version == 1 {
+            self.u64()
+        } else {
+            self.u32().map(u64::from)
+        }
+    }
+
+    /// Full box version and flags, failing for versions above `max`.
+    fn full(&mut self, max: u8) -> Result<(u8, u32), Error> {
+        let v = self.u32()?;
+        let version = (v >> 24) as u8;
+        if version > max {
+            return Err(Error::Version(self.typ, version));
+        }
+        Ok((version, v & 0xff_ffff))
+    }
+
+    /// Null terminated UTF-8 string, or the rest of the box if unterminated.
+    fn cstr(&mut self) -> String {
+        let end = self.data.iter().position(|b| *b == 0);
+        let s = String::from_utf8_lossy(&self.data[..end.unwrap_or(self.data.len())]);
+        self.data = &self.data[end.map_or(self.data.len(), |e| e + 1)..];
+        s.into_owned()
+    }
+
+    /// Entry count that has to fit the remaining bytes at `entry_len` each.
+    fn count(&mut self, entry_len: usize) -> Result<usize, Error> {
+        let n = self.u32()? as usize;
+        if n.saturating_mul(entry_len) > self.data.len() {
+            return Err(Error::Truncated(self.typ));
+        }
+        Ok(n)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        std::mem::take(&mut self.data)
+    }
+}
+
+/// Timing and location of one sample, from a sample table or a track fragment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sample {
+    /// Position in the track or fragment, from zero.
+    pub index: u32,
+    pub dts: cm::Time,
+    pub pts: cm::Time,
+    pub duration: cm::Time,
+
+    /// File offset of the sample data.
+    pub offset: u64,
+    pub size: u32,
+    pub sync: bool,
+
+    /// One based index into `stsd`.
+    pub desc_index: u32,
+}
+
+impl Sample {
+    /// File range of the sample data, `None` if it ends past `u64::MAX`.
+    #[inline]
+    pub fn range(&self) -> Option<std::ops::Range<u64>> {
+        Some(self.offset..self.offset.checked_add(self.size as u64)?)
+    }
+}
+
+/// A box inside a borrowed buffer.
+#[doc(alias = "box")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Atom<'a> {
+    pub typ: FourCc,
+
+    /// Offset of the box header from the start of the data passed to [`atoms`].
+    pub offset: usize,
+    pub header_len: usize,
+
+    /// Everything after the header, starting with the extended type for `uuid` boxes.
+    pub payload: &'a [u8],
+}
+
+impl<'a> Atom<'a> {
+    /// Size of the whole box, header included.
+    #[inline]
+    pub fn size(&self) -> usize {
+        self.header_len + self.payload.len()
+    }
+
+    /// Boxes inside the payload after `skip` bytes of fields, with offsets
+    /// relative to the same data as `self.offset`.
+    pub fn children_after(&self, skip: usize) -> Atoms<'a> {
+        let payload = self.payload.get(skip..).unwrap_or_default();
+        Atoms {
+            data: payload,
+            offset: self.offset + self.header_len + skip,
+        }
+    }
+
+    #[inline]
+    pub fn children(&self) -> Atoms<'a> {
+        self.children_after(0)
+    }
+
+    /// First child of type `typ`.
+    pub fn child(&self, typ: FourCc) -> Result<Option<Atom<'a>>, Error> {
+        for atom in self.children() {
+            let atom = atom?;
+            if atom.typ == typ {
+                return Ok(Some(atom));
+            }
+        }
+        Ok(None)
+    }
+
+    /// [`Self::child`] that must be present.
+    pub fn req(&self, typ: FourCc) -> Result<Atom<'a>, Error> {
+        self.child(typ)?.ok_or(Error::Missing(typ))
+    }
+
+    /// Parses the payload as `T`, checking the box type.
+    pub fn parse<T: IsoBox>(&self) -> Result<T, Error> {
+        if self.typ != T::TYPE {
+            return Err(Error::Type {
+                expected: T::TYPE,
+                found: self.typ,
+            });
+        }
+        T::parse(self.payload)
+    }
+}
+
+/// Iterates the boxes of `data`, stopping after the first error.
+pub fn atoms(data: &[u8]) -> Atoms<'_> {
+    Atoms { data, offset: 0 }
+}
+
+/// Finds a box by path from the top level, e.g. `[moov, trak, mdia]`.
+pub fn find<'a>(data: &'a [u8], path: &[FourCc]) -> Result<Option<Atom<'a>>, Error> {
+    let mut atoms = self::atoms(data);
+    let mut found = None;
+    for typ in path {
+        found = None;
+        for atom in atoms {
+            let atom = atom?;
+            if atom.typ == *typ {
+                found = Some(atom);
+                break;
+            }
+        }
+        let Some(atom) = found else {
+            return Ok(None);
+        };
+        atoms = atom.children();
+    }
+    Ok(found)
+}
+
+#[derive(Debug, Clone)]
+pub struct Atoms<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Atoms<'a> {
+    fn read(&mut self) -> Result<Atom<'a>, Error> {
+        let mut r = Reader::new(FourCc(0), self.data);
+        let size = r.u32()? as u64;
+        let typ = r.fcc()?;
+        r.typ = typ;
+        let (size, header_len) = match size {
+            0 => (self.data.len() as u64, 8),
+            1 => (r.u64()?, 16),
+            size => (size, 8),
+        };
+        if size < header_len as u64 || size > self.data.len() as u64 {
+            return Err(Error::Size(typ, size));
+        }
+        let size = size as usize;
+        let atom = Atom {
+            typ,
+            offset: self.offset,
+            header_len,
+            payload: &self.data[header_len..size],
+        };
+        self.data = &self.data[size..];
+        self.offset += size;
+        Ok(atom)
+    }
+}
+
+impl<'a> Iterator for Atoms<'a> {
+    type Item = Result<Atom<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let res = self.read();
+        if res.is_err() {
+            self.data = &[];
+        }
+        Some(res)
+    }
+}
+
+/// Appends a box of type `typ` whose payload `body` writes, filling in the
+/// size afterwards (switching to a 64 bit size if it needs one).
+pub fn write_box(out: &mut Vec<u8>, typ: FourCc, body: impl FnOnce(&mut Vec<u8>)) {
+    let start = out.len();
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&typ.to_bytes());
+    body(out);
+    let size = out.len() - start;
+    if let Ok(size) = u32::try_from(size) {
+        out[start..start + 4].copy_from_slice(&size.to_be_bytes());
+    } else {
+        let size = size as u64 + 8;
+        out[start..start + 4].copy_from_slice(&1u32.to_be_bytes());
+        out.splice(start + 8..start + 8, size.to_be_bytes());
+    }
+}
+
+/// [`write_box`] with a full box version and flags.
+pub fn write_full_box(
+    out: &mut Vec<u8>,
+    typ: FourCc,
+    version: u8,
+    flags: u32,
+    body: impl FnOnce(&mut Vec<u8>),
+) {
+    write_box(out, typ, |out| {
+        out.extend_from_slice(&((version as u32) << 24 | flags & 0xff_ffff).to_be_bytes());
+        body(out);
+    })
+}
+
+/// A box with a typed payload.
+pub trait IsoBox: Sized {
+    const TYPE: FourCc;
+
+    /// Parses the payload, i.e. everything after the box header.
+    fn parse(payload: &[u8]) -> Result<Self, Error>;
+
+    /// Writes the payload, full box version and flags included.
+    fn write_payload(&self, out: &mut Vec<u8>);
+
+    fn write_to(&self, out: &mut Vec<u8>) {
+        write_box(out, Self::TYPE, |out| self.write_payload(out))
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::new();
+        self.write_to(&mut res);
+        res
+    }
+}
+
+/// Bytes of fields before the child boxes of container types.
+fn children_offset(typ: FourCc) -> Option<usize> {
+    const CONTAINERS: [FourCc; 15] = [
+        fcc!("moov"),
+        fcc!("trak"),
+        fcc!("edts"),
+        fcc!("mdia"),
+        fcc!("minf"),
+        fcc!("dinf"),
+        fcc!("stbl"),
+        fcc!("mvex"),
+        fcc!("moof"),
+        fcc!("traf"),
+        fcc!("mfra"),
+        fcc!("udta"),
+        fcc!("sinf"),
+        fcc!("schi"),
+        fcc!("tref"),
+    ];
+    match typ {
+        t if CONTAINERS.contains(&t) => Some(0),
+        t if t == fcc!("meta") => Some(4),
+        // full box header and entry count
+        t if t == fcc!("stsd") || t == fcc!("dref") => Some(8),
+        _ => None,
+    }
+}
+
+/// Owned box tree, for rewriting files box by box.
+///
+/// Container boxes (`moov`, `trak`, `stbl`, `moof`, `traf`, `stsd`, ...) are
+/// split into children, everything else stays opaque bytes. Writing an
+/// unmodified tree reproduces the input, except that 64 bit sizes are only
+/// kept where needed.
+///
+/// ```
+/// use cidre::{cm::iso_bmff::{self, IsoBox, Node}, fcc};
+///
+/// let mut segment = Vec::new();
+/// iso_bmff::write_box(&mut segment, fcc!("moof"), |out| {
+///     iso_bmff::write_box(out, fcc!("traf"), |out| {
+///         iso_bmff::Tfdt { version: 1, base_media_decode_time: 9000 }.write_to(out)
+///     })
+/// });
+///
+/// let mut tree = Node::parse_all(&segment).unwrap();
+/// let tfdt = tree[0].find_mut(&[fcc!("traf"), fcc!("tfdt")]).unwrap();
+/// let mut t: iso_bmff::Tfdt = tfdt.parse().unwrap();
+/// t.base_media_decode_time += 90000;
+/// tfdt.set(&t);
+///
+/// let patched = Node::write_all(&tree);
+/// let tfdt = iso_bmff::find(&patched, &[fcc!("moof"), fcc!("traf"), fcc!("tfdt")]).unwrap().unwrap();
+/// assert_eq!(tfdt.parse::<iso_bmff::Tfdt>().unwrap().base_media_decode_time, 99000);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub typ: FourCc,
+    pub body: Body,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Body {
+    Data(Vec<u8>),
+    Children {
+        /// Fields before the children, like the full box header of `meta`.
+        fields: Vec<u8>,
+        children: Vec<Node>,
+    },
+}
+
+impl Node {
+    pub fn new(typ: FourCc, data: Vec<u8>) -> Self {
+        Self {
+            typ,
+            body: Body::Data(data),
+        }
+    }
+
+    pub fn container(typ: FourCc, children: Vec<Node>) -> Self {
+        Self {
+            typ,
+            body: Body::Children {
+                fields: Vec::new(),
+                children,
+            },
+        }
+    }
+
+    pub fn with_atom(atom: &Atom) -> Result<Self, Error> {
+        let body = match children_offset(atom.typ) {
+            Some(skip) if atom.payload.len() >= skip => Body::Children {
+                fields: atom.payload[..skip].to_vec(),
+                children: atom
+                    .children_after(skip)
+                    .map(|a| Self::with_atom(&a?))
+                    .collect::<Result<_, _>>()?,
+            },
+            _ => Body::Data(atom.payload.to_vec()),
+        };
+        Ok(Self {
+            typ: atom.typ,
+            body,
+        })
+    }
+
+    pub fn parse_all(data: &[u8]) -> Result<Vec<Self>, Error> {
+        atoms(data).map(|a| Self::with_atom(&a?)).collect()
+    }
+
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        write_box(out, self.typ, |out| match &self.body {
+            Body::Data(data) => out.extend_from_slice(data),
+            Body::Children { fields, children } => {
+                out.extend_from_slice(fields);
+                for child in children {
+                    child.write_to(out);
+                }
+            }
+        })
+    }
+
+    pub fn write_all(nodes: &[Self]) -> Vec<u8> {
+        let mut res = Vec::new();
+        for node in nodes {
+            node.write_to(&mut res);
+        }
+        res
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        Self::write_all(std::slice::from_ref(self))
+    }
+
+    /// Payload of a leaf box.
+    pub fn data(&self) -> Option<&[u8]> {
+        match &self.body {
+            Body::Data(data) => Some(data),
+            Body::Children { .. } => None,
+        }
+    }
+
+    pub fn children(&self) -> &[Node] {
+        match &self.body {
+            Body::Data(_) => &[],
+            Body::Children { children, .. } => children,
+        }
+    }
+
+    pub fn children_mut(&mut self) -> Option<&mut Vec<Node>> {
+        match &mut self.body {
+            Body::Data(_) => None,
+            Body::Children { children, .. } => Some(children),
+        }
+    }
+
+    /// Descendant by path relative to this box.
+    pub fn find(&self, path: &[FourCc]) -> Option<&Node> {
+        let Some((typ, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        self.children().iter().find(|c| c.typ == *typ)?.find(rest)
+    }
+
+    pub fn find_mut(&mut self, path: &[FourCc]) -> Option<&mut Node> {
+        let Some((typ, rest)) = path.split_first() else {
+            return Some(self);
+        };
+        self.children_mut()?
+            .iter_mut()
+            .find(|c| c.typ == *typ)?
+            .find_mut(rest)
+    }
+
+    /// Parses a leaf box as `T`.
+    pub fn parse<T: IsoBox>(&self) -> Result<T, Error> {
+        if self.typ != T::TYPE {
+            return Err(Error::Type {
+                expected: T::TYPE,
+                found: self.typ,
+            });
+        }
+        T::parse(self.data().ok_or(Error::Invalid(self.typ, "payload"))?)
+    }
+
+    /// Replaces this box with `val`.
+    pub fn set<T: IsoBox>(&mut self, val: &T) {
+        let mut data = Vec::new();
+        val.write_payload(&mut data);
+        self.typ = T::TYPE;
+        self.body = Body::Data(data);
+    }
+}
+
+impl<T: IsoBox> From<&T> for Node {
+    fn from(value: &T) -> Self {
+        let mut data = Vec::new();
+        value.write_payload(&mut data);
+        Self::new(T::TYPE, data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cm::iso_bmff::{self, Error, IsoBox, Node},
+        fcc,
+    };
+
+    #[test]
+    fn header_sizes() {
+        // 64 bit size, then a size 0 box running to the end
+        let data = [
+            0, 0, 0, 1, b'f', b'r', b'e', b'e', 0, 0, 0, 0, 0, 0, 0, 18, 0xaa, 0xbb, //
+            0, 0, 0, 0, b'm', b'd', b'a', b't', 1, 2, 3,
+        ];
+        let atoms: Vec<_> = iso_bmff::atoms(&data).map(Result::unwrap).collect();
+        assert_eq!(atoms[0].typ, fcc!("free"));
+        assert_eq!(
+            (atoms[0].header_len, atoms[0].payload),
+            (16, &[0xaa, 0xbb][..])
+        );
+        assert_eq!((atoms[1].offset, atoms[1].payload), (18, &[1, 2, 3][..]));
+
+        // round trip drops the unneeded 64 bit size
+        let tree = Node::parse_all(&data).unwrap();
+        let out = Node::write_all(&tree);
+        assert_eq!(
+            &out[..10],
+            [0, 0, 0, 10, b'f', b'r', b'e', b'e', 0xaa, 0xbb]
+        );
+        assert_eq!(&out[10..], [0, 0, 0, 11, b'm', b'd', b'a', b't', 1, 2, 3]);
+    }
+
+    #[test]
+    fn bad_sizes() {
+        let data = [0, 0, 0, 7, b'f', b'r', b'e', b'e'];
+        let res: Vec<_> = iso_bmff::atoms(&data).collect();
+        assert_eq!(res, [Err(Error::Size(fcc!("free"), 7))]);
+
+        let data = [0, 0, 0, 9, b'f', b'r', b'e', b'e'];
+        assert_eq!(
+            iso_bmff::atoms(&data).next(),
+            Some(Err(Error::Size(fcc!("free"), 9)))
+        );
+
+        assert_eq!(
+            iso_bmff::atoms(&[0, 0, 0]).next(),
+            Some(Err(Error::Truncated(crate::FourCc(0))))
+        );
+    }
+
+    #[test]
+    fn tree() {
+        let mut data = Vec::new();
+        iso_bmff::write_box(&mut data, fcc!("moov"), |out| {
+            iso_bmff::write_full_box(out, fcc!("meta"), 0, 0, |out| {
+                iso_bmff::write_box(out, fcc!("hdlr"), |out| out.push(1));
+            });
+            iso_bmff::write_box(out, fcc!("trak"), |_| {});
+        });
+        let tree = Node::parse_all(&data).unwrap();
+        assert_eq!(Node::write_all(&tree), data);
+
+        let hdlr = tree[0].find(&[fcc!("meta"), fcc!("hdlr")]).unwrap();
+        assert_eq!(hdlr.data(), Some(&[1][..]));
+        assert!(tree[0].find(&[fcc!("trak"), fcc!("mdia")]).is_none());
+
+        let atom = iso_bmff::find(&data, &[fcc!("moov"), fcc!("trak")])
+            .unwrap()
+            .unwrap();
+        assert_eq!((atom.offset, atom.size()), (29, 8));
+        assert_eq!(
+            atom.parse::<iso_bmff::Tfdt>(),
+            Err(Error::Type {
+                expected: fcc!("tfdt"),
+                found: fcc!("trak")
+            })
+        );
+
+        let tfdt = iso_bmff::Tfdt {
+            version: 0,
+            base_media_decode_time: 7,
+        };
+        assert_eq!(Node::from(&tfdt).to_bytes(), tfdt.to_bytes());
+    }
+}
@@ -0,0 +1,1009 @@
+//! Movie fragment, segment index and event message boxes.
+
+use crate::{
+    cm::{
+        self,
+        iso_bmff::{atoms, write_box, Error, IsoBox, Reader, Sample, Trex},
+    },
+    fcc, FourCc,
+};
+
+/// Sample flags of `trex`, `tfhd` and `trun`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+#[repr(transparent)]
+pub struct SampleFlags(pub u32);
+
+impl SampleFlags {
+    pub const NON_SYNC: Self = Self(0x1_0000);
+
+    /// `sample_depends_on` = 2, what a sync sample of a video track gets.
+    pub const SYNC: Self = Self(0x0200_0000);
+
+    /// `sample_depends_on` = 1 and `sample_is_non_sync_sample`.
+    pub const DEPENDENT: Self = Self(0x0101_0000);
+
+    #[inline]
+    pub fn is_sync(self) -> bool {
+        self.0 & Self::NON_SYNC.0 == 0
+    }
+
+    /// `1` depends on others, `2` doesn't, `0` unknown.
+    #[inline]
+    pub fn depends_on(self) -> u8 {
+        (self.0 >> 24 & 3) as u8
+    }
+}
+
+/// Movie fragment header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mfhd {
+    pub sequence_number: u32,
+}
+
+impl IsoBox for Mfhd {
+    const TYPE: FourCc = fcc!("mfhd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        Ok(Self {
+            sequence_number: r.u32()?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&self.sequence_number.to_be_bytes());
+    }
+}
+
+/// Track fragment header. Unset defaults fall back to `trex`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tfhd {
+    pub track_id: u32,
+    pub base_data_offset: Option<u64>,
+    pub sample_desc_index: Option<u32>,
+    pub default_sample_duration: Option<u32>,
+    pub default_sample_size: Option<u32>,
+    pub default_sample_flags: Option<SampleFlags>,
+    pub duration_is_empty: bool,
+
+    /// Data offsets are relative to the enclosing `moof`, as CMAF requires.
+    pub default_base_is_moof: bool,
+}
+
+impl Tfhd {
+    const BASE_DATA_OFFSET: u32 = 0x1;
+    const SAMPLE_DESC_INDEX: u32 = 0x2;
+    const DEFAULT_DURATION: u32 = 0x8;
+    const DEFAULT_SIZE: u32 = 0x10;
+    const DEFAULT_FLAGS: u32 = 0x20;
+    const DURATION_IS_EMPTY: u32 = 0x1_0000;
+    const DEFAULT_BASE_IS_MOOF: u32 = 0x2_0000;
+
+    fn flags(&self) -> u32 {
+        let mut flags = 0;
+        for (set, flag) in [
+            (self.base_data_offset.is_some(), Self::BASE_DATA_OFFSET),
+            (self.sample_desc_index.is_some(), Self::SAMPLE_DESC_INDEX),
+            (
+                self.default_sample_duration.is_some(),
+                Self::DEFAULT_DURATION,
+            ),
+            (self.default_sample_size.is_some(), Self::DEFAULT_SIZE),
+            (self.default_sample_flags.is_some(), Self::DEFAULT_FLAGS),
+            (self.duration_is_empty, Self::DURATION_IS_EMPTY),
+            (self.default_base_is_moof, Self::DEFAULT_BASE_IS_MOOF),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+}
+
+impl IsoBox for Tfhd {
+    const TYPE: FourCc = fcc!("tfhd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (_, flags) = r.full(0)?;
+        let has = |flag| flags & flag != 0;
+        Ok(Self {
+            track_id: r.u32()?,
+            base_data_offset: has(Self::BASE_DATA_OFFSET).then(|| r.u64()).transpose()?,
+            sample_desc_index: has(Self::SAMPLE_DESC_INDEX).then(|| r.u32()).transpose()?,
+            default_sample_duration: has(Self::DEFAULT_DURATION).then(|| r.u32()).transpose()?,
+            default_sample_size: has(Self::DEFAULT_SIZE).then(|| r.u32()).transpose()?,
+            default_sample_flags: has(Self::DEFAULT_FLAGS)
+                .then(|| r.u32().map(SampleFlags))
+                .transpose()?,
+            duration_is_empty: has(Self::DURATION_IS_EMPTY),
+            default_base_is_moof: has(Self::DEFAULT_BASE_IS_MOOF),
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.flags().to_be_bytes());
+        out.extend_from_slice(&self.track_id.to_be_bytes());
+        if let Some(offset) = self.base_data_offset {
+            out.extend_from_slice(&offset.to_be_bytes());
+        }
+        for v in [
+            self.sample_desc_index,
+            self.default_sample_duration,
+            self.default_sample_size,
+            self.default_sample_flags.map(|f| f.0),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+}
+
+/// Track fragment decode time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tfdt {
+    /// `1` for a 64 bit time. Raised on write if the time doesn't fit.
+    pub version: u8,
+    pub base_media_decode_time: u64,
+}
+
+impl IsoBox for Tfdt {
+    const TYPE: FourCc = fcc!("tfdt");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (version, _) = r.full(1)?;
+        Ok(Self {
+            version,
+            base_media_decode_time: r.uint(version)?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let version = self
+            .version
+            .max((self.base_media_decode_time > u32::MAX as u64) as u8);
+        out.extend_from_slice(&((version as u32) << 24).to_be_bytes());
+        if version == 1 {
+            out.extend_from_slice(&self.base_media_decode_time.to_be_bytes());
+        } else {
+            out.extend_from_slice(&(self.base_media_decode_time as u32).to_be_bytes());
+        }
+    }
+}
+
+/// One `trun` sample. Unset fields fall back to `tfhd` and `trex` defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct TrunSample {
+    pub duration: Option<u32>,
+    pub size: Option<u32>,
+    pub flags: Option<SampleFlags>,
+
+    /// Composition time offset.
+    pub cto: Option<i32>,
+}
+
+/// Track fragment run.
+///
+/// Which per sample fields are written is decided by the first sample,
+/// the others have to set the same ones.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Trun {
+    /// `1` for signed composition offsets.
+    pub version: u8,
+
+    /// Offset of the first sample from the base data offset.
+    pub data_offset: Option<i32>,
+    pub first_sample_flags: Option<SampleFlags>,
+    pub samples: Vec<TrunSample>,
+}
+
+impl Trun {
+    const DATA_OFFSET: u32 = 0x1;
+    const FIRST_SAMPLE_FLAGS: u32 = 0x4;
+    const DURATION: u32 = 0x100;
+    const SIZE: u32 = 0x200;
+    const FLAGS: u32 = 0x400;
+    const CTO: u32 = 0x800;
+
+    fn flags(&self) -> u32 {
+        let first = self.samples.first().copied().unwrap_or_default();
+        let mut flags = 0;
+        for (set, flag) in [
+            (self.data_offset.is_some(), Self::DATA_OFFSET),
+            (self.first_sample_flags.is_some(), Self::FIRST_SAMPLE_FLAGS),
+            (first.duration.is_some(), Self::DURATION),
+            (first.size.is_some(), Self::SIZE),
+            (first.flags.is_some(), Self::FLAGS),
+            (first.cto.is_some(), Self::CTO),
+        ] {
+            if set {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+
+    /// Bytes of sample data the run covers, if every sample has a size.
+    pub fn data_len(&self) -> Option<u64> {
+        self.samples.iter().map(|s| s.size.map(u64::from)).sum()
+    }
+}
+
+impl IsoBox for Trun {
+    const TYPE: FourCc = fcc!("trun");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (version, flags) = r.full(1)?;
+        let has = |flag| flags & flag != 0;
+        let sample_len = [Self::DURATION, Self::SIZE, Self::FLAGS, Self::CTO]
+            .iter()
+            .filter(|f| has(**f))
+            .count()
+            * 4;
+        let count = r.u32()? as usize;
+        let data_offset = has(Self::DATA_OFFSET).then(|| r.i32()).transpose()?;
+        let first_sample_flags = has(Self::FIRST_SAMPLE_FLAGS)
+            .then(|| r.u32().map(SampleFlags))
+            .transpose()?;
+        if count.saturating_mul(sample_len) > r.data.len() {
+            return Err(Error::Truncated(Self::TYPE));
+        }
+        let samples = (0..count)
+            .map(|_| {
+                Ok(TrunSample {
+                    duration: has(Self::DURATION).then(|| r.u32()).transpose()?,
+                    size: has(Self::SIZE).then(|| r.u32()).transpose()?,
+                    flags: has(Self::FLAGS)
+                        .then(|| r.u32().map(SampleFlags))
+                        .transpose()?,
+                    cto: has(Self::CTO).then(|| r.i32()).transpose()?,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self {
+            version,
+            data_offset,
+            first_sample_flags,
+            samples,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let flags = self.flags();
+        out.extend_from_slice(&((self.version as u32) << 24 | flags).to_be_bytes());
+        out.extend_from_slice(&(self.samples.len() as u32).to_be_bytes());
+        if let Some(offset) = self.data_offset {
+            out.extend_from_slice(&offset.to_be_bytes());
+        }
+        if let Some(f) = self.first_sample_flags {
+            out.extend_from_slice(&f.0.to_be_bytes());
+        }
+        for s in &self.samples {
+            for (flag, v) in [
+                (Self::DURATION, s.duration),
+                (Self::SIZE, s.size),
+                (Self::FLAGS, s.flags.map(|f| f.0)),
+                (Self::CTO, s.cto.map(|c| c as u32)),
+            ] {
+                if flags & flag != 0 {
+                    out.extend_from_slice(&v.unwrap_or_default().to_be_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Track fragment.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Traf {
+    pub tfhd: Tfhd,
+    pub tfdt: Option<Tfdt>,
+    pub truns: Vec<Trun>,
+}
+
+impl Traf {
+    /// Samples of all runs in decode order.
+    ///
+    /// `moof_offset` is the file offset of the enclosing `moof`, `timescale`
+    /// the `mdhd` one, `trex` the track defaults from the init segment.
+    pub fn samples(
+        &self,
+        moof_offset: u64,
+        timescale: u32,
+        trex: Option<&Trex>,
+    ) -> TrafSamples<'_> {
+        let trex = trex.copied().unwrap_or_default();
+        let tfhd = &self.tfhd;
+        TrafSamples {
+            traf: self,
+            timescale: timescale as i32,
+            moof_offset,
+            run: 0,
+            index_in_run: 0,
+            index: 0,
+            dts: self.tfdt.map_or(0, |t| t.base_media_decode_time),
+            offset: tfhd.base_data_offset.unwrap_or(moof_offset),
+            duration: tfhd
+                .default_sample_duration
+                .unwrap_or(trex.default_sample_duration),
+            size: tfhd.default_sample_size.unwrap_or(trex.default_sample_size),
+            flags: tfhd
+                .default_sample_flags
+                .unwrap_or(SampleFlags(trex.default_sample_flags)),
+            desc_index: tfhd
+                .sample_desc_index
+                .unwrap_or(trex.default_sample_desc_index),
+        }
+    }
+}
+
+impl IsoBox for Traf {
+    const TYPE: FourCc = fcc!("traf");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut tfhd = None;
+        let mut res = Self::default();
+        for atom in atoms(payload) {
+            let atom = atom?;
+            match atom.typ {
+                t if t == Tfhd::TYPE => tfhd = Some(atom.parse()?),
+                t if t == Tfdt::TYPE => res.tfdt = Some(atom.parse()?),
+                t if t == Trun::TYPE => res.truns.push(atom.parse()?),
+                _ => {}
+            }
+        }
+        res.tfhd = tfhd.ok_or(Error::Missing(Tfhd::TYPE))?;
+        Ok(res)
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.tfhd.write_to(out);
+        if let Some(tfdt) = &self.tfdt {
+            tfdt.write_to(out);
+        }
+        for trun in &self.truns {
+            trun.write_to(out);
+        }
+    }
+}
+
+/// Samples of a [`Traf`], see [`Traf::samples`].
+#[derive(Debug, Clone)]
+pub struct TrafSamples<'a> {
+    traf: &'a Traf,
+    timescale: i32,
+    moof_offset: u64,
+    run: usize,
+    index_in_run: usize,
+    index: u32,
+    dts: u64,
+    offset: u64,
+    duration: u32,
+    size: u32,
+    flags: SampleFlags,
+    desc_index: u32,
+}
+
+impl TrafSamples<'_> {
+    fn sample(&mut self, trun: &Trun, s: &TrunSample) -> Result<Sample, Error> {
+        let invalid = |what| Error::Invalid(Trun::TYPE, what);
+        if self.index_in_run == 0 {
+            if let Some(data_offset) = trun.data_offset {
+                let base = self.traf.tfhd.base_data_offset.unwrap_or(self.moof_offset);
+                self.offset = base
+                    .checked_add_signed(data_offset as i64)
+                    .ok_or(invalid("data offset"))?;
+            }
+        }
+        let flags = match trun.first_sample_flags {
+            Some(f) if self.index_in_run == 0 => f,
+            _ => s.flags.unwrap_or(self.flags),
+        };
+        let duration = s.duration.unwrap_or(self.duration);
+        let size = s.size.unwrap_or(self.size);
+        let cto = s.cto.unwrap_or(0) as i64;
+
+        let dts = i64::try_from(self.dts).map_err(|_| invalid("decode time"))?;
+        let pts = dts.checked_add(cto).ok_or(invalid("composition offset"))?;
+        let next_dts = self
+            .dts
+            .checked_add(duration as u64)
+            .ok_or(invalid("decode time"))?;
+        let next_offset = self
+            .offset
+            .checked_add(size as u64)
+            .ok_or(invalid("sample offset"))?;
+
+        let sample = Sample {
+            index: self.index,
+            dts: cm::Time::new(dts, self.timescale),
+            pts: cm::Time::new(pts, self.timescale),
+            duration: cm::Time::new(duration as i64, self.timescale),
+            offset: self.offset,
+            size,
+            sync: flags.is_sync(),
+            desc_index: self.desc_index,
+        };
+        self.index += 1;
+        self.index_in_run += 1;
+        self.dts = next_dts;
+        self.offset = next_offset;
+        Ok(sample)
+    }
+}
+
+impl Iterator for TrafSamples<'_> {
+    type Item = Result<Sample, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let traf = self.traf;
+        let (trun, s) = loop {
+            let trun = traf.truns.get(self.run)?;
+            if let Some(s) = trun.samples.get(self.index_in_run) {
+                break (trun, s);
+            }
+            self.run += 1;
+            self.index_in_run = 0;
+        };
+        let res = self.sample(trun, s);
+        if res.is_err() {
+            self.run = traf.truns.len();
+        }
+        Some(res)
+    }
+}
+
+/// Movie fragment.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Moof {
+    pub mfhd: Mfhd,
+    pub trafs: Vec<Traf>,
+}
+
+impl Moof {
+    /// Points the runs at sample data that follows this box in a single
+    /// `mdat`, in traf and run order, with offsets relative to the `moof`.
+    ///
+    /// Every run gets a data offset and every sample needs a size.
+    pub fn set_data_offsets(&mut self) -> Result<(), Error> {
+        // fix the layout first so the size doesn't change below
+        for traf in &mut self.trafs {
+            traf.tfhd.base_data_offset = None;
+            traf.tfhd.default_base_is_moof = true;
+            for trun in &mut traf.truns {
+                trun.data_offset.get_or_insert(0);
+            }
+        }
+        let mut offset = self.to_bytes().len() as u64 + 8;
+        for traf in &mut self.trafs {
+            for trun in &mut traf.truns {
+                let len = trun
+                    .data_len()
+                    .ok_or(Error::Invalid(Trun::TYPE, "sample size"))?;
+                trun.data_offset =
+                    Some(i32::try_from(offset).map_err(|_| Error::Size(Self::TYPE, offset))?);
+                offset = offset
+                    .checked_add(len)
+                    .ok_or(Error::Size(Self::TYPE, offset))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Samples of the fragment for track `track_id`, stopping after the
+    /// first error.
+    pub fn samples(
+        &self,
+        track_id: u32,
+        moof_offset: u64,
+        timescale: u32,
+        trex: Option<&Trex>,
+    ) -> impl Iterator<Item = Result<Sample, Error>> + '_ {
+        let trex = trex.copied();
+        self.trafs
+            .iter()
+            .filter(move |t| t.tfhd.track_id == track_id)
+            .flat_map(move |t| t.samples(moof_offset, timescale, trex.as_ref()))
+            .scan(false, |failed, s| {
+                if *failed {
+                    return None;
+                }
+                *failed = s.is_err();
+                Some(s)
+            })
+    }
+}
+
+impl IsoBox for Moof {
+    const TYPE: FourCc = fcc!("moof");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut mfhd = None;
+        let mut trafs = Vec::new();
+        for atom in atoms(payload) {
+            let atom = atom?;
+            match atom.typ {
+                t if t == Mfhd::TYPE => mfhd = Some(atom.parse()?),
+                t if t == Traf::TYPE => trafs.push(atom.parse()?),
+                _ => {}
+            }
+        }
+        Ok(Self {
+            mfhd: mfhd.ok_or(Error::Missing(Mfhd::TYPE))?,
+            trafs,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.mfhd.write_to(out);
+        for traf in &self.trafs {
+            traf.write_to(out);
+        }
+    }
+}
+
+/// Segment index reference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SidxRef {
+    /// Points at another `sidx` instead of media.
+    pub reference_type: bool,
+
+    /// 31 bits.
+    pub size: u32,
+    pub duration: u32,
+    pub starts_with_sap: bool,
+
+    /// 3 bits.
+    pub sap_type: u8,
+
+    /// 28 bits.
+    pub sap_delta_time: u32,
+}
+
+/// Segment index.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Sidx {
+    pub reference_id: u32,
+    pub timescale: u32,
+    pub earliest_presentation_time: u64,
+
+    /// From the end of this box to the first referenced byte.
+    pub first_offset: u64,
+    pub references: Vec<SidxRef>,
+}
+
+impl IsoBox for Sidx {
+    const TYPE: FourCc = fcc!("sidx");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (version, _) = r.full(1)?;
+        let reference_id = r.u32()?;
+        let timescale = r.u32()?;
+        let earliest_presentation_time = r.uint(version)?;
+        let first_offset = r.uint(version)?;
+        r.u16()?;
+        let n = r.u16()?;
+        if n as usize * 12 > r.data.len() {
+            return Err(Error::Truncated(Self::TYPE));
+        }
+        let references = (0..n)
+            .map(|_| {
+                let a = r.u32()?;
+                let duration = r.u32()?;
+                let b = r.u32()?;
+                Ok(SidxRef {
+                    reference_type: a >> 31 == 1,
+                    size: a & 0x7fff_ffff,
+                    duration,
+                    starts_with_sap: b >> 31 == 1,
+                    sap_type: (b >> 28 & 7) as u8,
+                    sap_delta_time: b & 0xfff_ffff,
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self {
+            reference_id,
+            timescale,
+            earliest_presentation_time,
+            first_offset,
+            references,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let version = (self.earliest_presentation_time > u32::MAX as u64
+            || self.first_offset > u32::MAX as u64) as u8;
+        out.extend_from_slice(&((version as u32) << 24).to_be_bytes());
+        out.extend_from_slice(&self.reference_id.to_be_bytes());
+        out.extend_from_slice(&self.timescale.to_be_bytes());
+        for v in [self.earliest_presentation_time, self.first_offset] {
+            if version == 1 {
+                out.extend_from_slice(&v.to_be_bytes());
+            } else {
+                out.extend_from_slice(&(v as u32).to_be_bytes());
+            }
+        }
+        out.extend_from_slice(&[0; 2]);
+        out.extend_from_slice(&(self.references.len() as u16).to_be_bytes());
+        for r in &self.references {
+            let a = (r.reference_type as u32) << 31 | r.size & 0x7fff_ffff;
+            let b = (r.starts_with_sap as u32) << 31
+                | (r.sap_type as u32 & 7) << 28
+                | r.sap_delta_time & 0xfff_ffff;
+            out.extend_from_slice(&a.to_be_bytes());
+            out.extend_from_slice(&r.duration.to_be_bytes());
+            out.extend_from_slice(&b.to_be_bytes());
+        }
+    }
+}
+
+/// Event time of an [`Emsg`], which also picks the box version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmsgTime {
+    /// Version 0, from the earliest presentation time of the segment.
+    Delta(u32),
+
+    /// Version 1, on the track timeline.
+    Absolute(u64),
+}
+
+/// Event message, e.g. SCTE-35 or ID3 in DASH and CMAF.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Emsg {
+    pub scheme_id_uri: String,
+    pub value: String,
+    pub timescale: u32,
+    pub time: EmsgTime,
+
+    /// `0xffff_ffff` for unknown.
+    pub event_duration: u32,
+    pub id: u32,
+    pub message_data: Vec<u8>,
+}
+
+impl IsoBox for Emsg {
+    const TYPE: FourCc = fcc!("emsg");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (version, _) = r.full(1)?;
+        if version == 0 {
+            let scheme_id_uri = r.cstr();
+            let value = r.cstr();
+            Ok(Self {
+                scheme_id_uri,
+                value,
+                timescale: r.u32()?,
+                time: EmsgTime::Delta(r.u32()?),
+                event_duration: r.u32()?,
+                id: r.u32()?,
+                message_data: r.rest().to_vec(),
+            })
+        } else {
+            let timescale = r.u32()?;
+            let time = EmsgTime::Absolute(r.u64()?);
+            let event_duration = r.u32()?;
+            let id = r.u32()?;
+            Ok(Self {
+                scheme_id_uri: r.cstr(),
+                value: r.cstr(),
+                timescale,
+                time,
+                event_duration,
+                id,
+                message_data: r.rest().to_vec(),
+            })
+        }
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let cstr = |out: &mut Vec<u8>, s: &str| {
+            out.extend_from_slice(s.as_bytes());
+            out.push(0);
+        };
+        match self.time {
+            EmsgTime::Delta(delta) => {
+                out.extend_from_slice(&[0; 4]);
+                cstr(out, &self.scheme_id_uri);
+                cstr(out, &self.value);
+                out.extend_from_slice(&self.timescale.to_be_bytes());
+                out.extend_from_slice(&delta.to_be_bytes());
+            }
+            EmsgTime::Absolute(time) => {
+                out.extend_from_slice(&(1u32 << 24).to_be_bytes());
+                out.extend_from_slice(&self.timescale.to_be_bytes());
+                out.extend_from_slice(&time.to_be_bytes());
+            }
+        }
+        out.extend_from_slice(&self.event_duration.to_be_bytes());
+        out.extend_from_slice(&self.id.to_be_bytes());
+        if let EmsgTime::Absolute(_) = self.time {
+            cstr(out, &self.scheme_id_uri);
+            cstr(out, &self.value);
+        }
+        out.extend_from_slice(&self.message_data);
+    }
+}
+
+/// Writes `moof` followed by an `mdat` holding `data`, after pointing the
+/// runs at it with [`Moof::set_data_offsets`].
+pub fn write_fragment(out: &mut Vec<u8>, moof: &mut Moof, data: &[u8]) -> Result<(), Error> {
+    moof.set_data_offsets()?;
+    moof.write_to(out);
+    write_box(out, fcc!("mdat"), |out| out.extend_from_slice(data));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cm::iso_bmff::{
+            self, Emsg, EmsgTime, IsoBox, Mfhd, Moof, SampleFlags, Sidx, SidxRef, Tfdt, Tfhd, Traf,
+            Trex, Trun, TrunSample,
+        },
+        fcc,
+    };
+
+    fn trun(sizes: &[u32]) -> Trun {
+        Trun {
+            version: 1,
+            data_offset: None,
+            first_sample_flags: Some(SampleFlags::SYNC),
+            samples: sizes
+                .iter()
+                .map(|s| TrunSample {
+                    size: Some(*s),
+                    cto: Some(-512),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn fragment() {
+        let mut moof = Moof {
+            mfhd: Mfhd { sequence_number: 3 },
+            trafs: vec![Traf {
+                tfhd: Tfhd {
+                    track_id: 1,
+                    default_sample_flags: Some(SampleFlags::DEPENDENT),
+                    ..Default::default()
+                },
+                tfdt: Some(Tfdt {
+                    version: 1,
+                    base_media_decode_time: 10240,
+                }),
+                truns: vec![trun(&[10, 20]), trun(&[30])],
+            }],
+        };
+        let mut file = vec![0; 100];
+        iso_bmff::write_fragment(&mut file, &mut moof, &[0xab; 60]).unwrap();
+
+        let atoms: Vec<_> = iso_bmff::atoms(&file[100..]).map(Result::unwrap).collect();
+        assert_eq!(atoms[1].typ, fcc!("mdat"));
+        let parsed: Moof = atoms[0].parse().unwrap();
+        assert_eq!(parsed, moof);
+        assert!(parsed.trafs[0].tfhd.default_base_is_moof);
+
+        let trex = Trex {
+            track_id: 1,
+            default_sample_desc_index: 1,
+            default_sample_duration: 512,
+            ..Default::default()
+        };
+        let samples: Vec<_> = parsed
+            .samples(1, 100, 12800, Some(&trex))
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(samples.len(), 3);
+        let data_start = 100 + atoms[0].size() as u64 + 8;
+        let ranges: Vec<_> = samples.iter().map(|s| s.range().unwrap()).collect();
+        assert_eq!(
+            ranges,
+            [
+                data_start..data_start + 10,
+                data_start + 10..data_start + 30,
+                data_start + 30..data_start + 60
+            ]
+        );
+        assert!(ranges.iter().all(|r| file[r.start as usize..r.end as usize]
+            .iter()
+            .all(|b| *b == 0xab)));
+
+        let dts: Vec<_> = samples.iter().map(|s| s.dts.value).collect();
+        assert_eq!(dts, [10240, 10752, 11264]);
+        assert_eq!(samples[0].pts.value, 10240 - 512);
+        let sync: Vec<_> = samples.iter().map(|s| s.sync).collect();
+        assert_eq!(sync, [true, false, true]);
+        assert_eq!(parsed.samples(2, 100, 12800, None).count(), 0);
+    }
+
+    #[test]
+    fn overflow() {
+        let invalid = |what| Err(iso_bmff::Error::Invalid(fcc!("trun"), what));
+        let traf = |base: u64, data_offset: i32, dts: u64, sizes: &[u32]| Traf {
+            tfhd: Tfhd {
+                track_id: 1,
+                base_data_offset: Some(base),
+                default_sample_duration: Some(512),
+                ..Default::default()
+            },
+            tfdt: Some(Tfdt {
+                version: 1,
+                base_media_decode_time: dts,
+            }),
+            truns: vec![Trun {
+                data_offset: Some(data_offset),
+                ..trun(sizes)
+            }],
+        };
+
+        let t = traf(u64::MAX, 1, 0, &[1]);
+        let samples: Vec<_> = t.samples(0, 100, None).collect();
+        assert_eq!(samples, [invalid("data offset")]);
+
+        let t = traf(u64::MAX - 10, 0, 0, &[5, 10, 20]);
+        let samples: Vec<_> = t.samples(0, 100, None).collect();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(
+            samples[0].as_ref().unwrap().range(),
+            Some(u64::MAX - 10..u64::MAX - 5)
+        );
+        assert_eq!(samples[1], invalid("sample offset"));
+
+        let t = traf(0, 0, u64::MAX, &[1, 1]);
+        let samples: Vec<_> = t.samples(0, 100, None).collect();
+        assert_eq!(samples, [invalid("decode time")]);
+
+        let mut t = traf(0, 0, i64::MAX as u64, &[1]);
+        t.truns[0].samples[0].cto = Some(1);
+        let samples: Vec<_> = t.samples(0, 100, None).collect();
+        assert_eq!(samples, [invalid("composition offset")]);
+
+        // the fragment stops at the first error, later trafs included
+        let moof = Moof {
+            mfhd: Mfhd { sequence_number: 1 },
+            trafs: vec![traf(u64::MAX, 1, 0, &[1]), traf(0, 0, 0, &[1])],
+        };
+        assert_eq!(moof.samples(1, 0, 100, None).count(), 1);
+
+        // every combination either yields in range samples or ends in an error
+        let bases = [0, 1, i64::MAX as u64, u64::MAX - 1, u64::MAX];
+        let offsets = [i32::MIN, -1, 0, 1, i32::MAX];
+        let sizes = [0, 1, u32::MAX];
+        for base in bases {
+            for offset in offsets {
+                for size in sizes {
+                    for dts in bases {
+                        let t = traf(base, offset, dts, &[size, size, size]);
+                        let samples: Vec<_> = t.samples(base, 100, None).collect();
+                        let errors = samples.iter().filter(|s| s.is_err()).count();
+                        assert!(errors <= 1 && (errors == 0 || samples.last().unwrap().is_err()));
+                        for s in samples.iter().flatten() {
+                            let range = s.range().unwrap();
+                            assert_eq!(range.end - range.start, size as u64);
+                            assert!(s.dts.value >= 0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mutated() {
+        let mut moof = Moof {
+            mfhd: Mfhd { sequence_number: 1 },
+            trafs: vec![Traf {
+                tfhd: Tfhd {
+                    track_id: 1,
+                    ..Default::default()
+                },
+                tfdt: Some(Tfdt {
+                    version: 1,
+                    base_media_decode_time: 1024,
+                }),
+                truns: vec![trun(&[10, 20, 30])],
+            }],
+        };
+        let mut file = Vec::new();
+        iso_bmff::write_fragment(&mut file, &mut moof, &[0; 60]).unwrap();
+        let moof_len = moof.to_bytes().len();
+
+        // overwrite runs of bytes with 0xff and 0x00, parse what is left
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for _ in 0..2000 {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            let mut bytes = file[..moof_len].to_vec();
+            let at = (state % moof_len as u64) as usize;
+            let len = ((state >> 32) % 9) as usize;
+            let fill = if state & 1 == 0 { 0xff } else { 0 };
+            let end = (at + len).min(moof_len);
+            bytes[at..end].fill(fill);
+            for atom in iso_bmff::atoms(&bytes) {
+                let Ok(atom) = atom else { continue };
+                let Ok(parsed) = atom.parse::<Moof>() else {
+                    continue;
+                };
+                for s in parsed.samples(1, u64::MAX - 64, 12800, None).flatten() {
+                    assert!(s.range().is_some());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn sidx() {
+        let sidx = Sidx {
+            reference_id: 1,
+            timescale: 90000,
+            earliest_presentation_time: 1 << 33,
+            first_offset: 0,
+            references: vec![SidxRef {
+                reference_type: false,
+                size: 12345,
+                duration: 180000,
+                starts_with_sap: true,
+                sap_type: 1,
+                sap_delta_time: 0,
+            }],
+        };
+        let bytes = sidx.to_bytes();
+        assert_eq!(bytes.len(), 8 + 4 + 8 + 16 + 4 + 12);
+        assert_eq!(&bytes[bytes.len() - 4..], [0x90, 0, 0, 0]);
+        assert_eq!(Sidx::parse(&bytes[8..]).unwrap(), sidx);
+    }
+
+    #[test]
+    fn emsg() {
+        for time in [EmsgTime::Delta(90), EmsgTime::Absolute(900_000)] {
+            let emsg = Emsg {
+                scheme_id_uri: "urn:scte:scte35:2013:bin".into(),
+                value: String::new(),
+                timescale: 90000,
+                time,
+                event_duration: 0xffff_ffff,
+                id: 7,
+                message_data: vec![0xfc, 0x30],
+            };
+            let bytes = emsg.to_bytes();
+            assert_eq!(bytes[8], matches!(time, EmsgTime::Absolute(_)) as u8);
+            assert_eq!(Emsg::parse(&bytes[8..]).unwrap(), emsg);
+        }
+    }
+
+    #[test]
+    fn tfhd_defaults() {
+        let tfhd = Tfhd {
+            track_id: 2,
+            base_data_offset: Some(1 << 40),
+            default_sample_size: Some(4),
+            duration_is_empty: true,
+            ..Default::default()
+        };
+        let bytes = tfhd.to_bytes();
+        assert_eq!(&bytes[8..12], [0, 1, 0, 0x11]);
+        assert_eq!(Tfhd::parse(&bytes[8..]).unwrap(), tfhd);
+
+        let tfdt = Tfdt {
+            version: 0,
+            base_media_decode_time: 1 << 32,
+        };
+        assert_eq!(Tfdt::parse(&tfdt.to_bytes()[8..]).unwrap().version, 1);
+        assert_eq!(
+            Tfdt::parse(&[2, 0, 0, 0]),
+            Err(iso_bmff::Error::Version(fcc!("tfdt"), 2))
+        );
+    }
+}
@@ -0,0 +1,1317 @@
+//! File type, movie header and sample table boxes.
+
+use crate::{
+    cm::{
+        self,
+        iso_bmff::{atoms, write_box, write_full_box, Atom, Error, IsoBox, Reader, Sample},
+    },
+    fcc, FourCc,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ftyp {
+    pub major_brand: FourCc,
+    pub minor_version: u32,
+    pub compatible_brands: Vec<FourCc>,
+}
+
+impl IsoBox for Ftyp {
+    const TYPE: FourCc = fcc!("ftyp");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let major_brand = r.fcc()?;
+        let minor_version = r.u32()?;
+        let rest = r.rest();
+        Ok(Self {
+            major_brand,
+            minor_version,
+            compatible_brands: rest
+                .chunks_exact(4)
+                .map(|b| FourCc::from_bytes(b.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.major_brand.to_bytes());
+        out.extend_from_slice(&self.minor_version.to_be_bytes());
+        for brand in &self.compatible_brands {
+            out.extend_from_slice(&brand.to_bytes());
+        }
+    }
+}
+
+/// Segment type, laid out like `ftyp`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Styp(pub Ftyp);
+
+impl IsoBox for Styp {
+    const TYPE: FourCc = fcc!("styp");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        Ftyp::parse(payload).map(Self)
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.0.write_payload(out)
+    }
+}
+
+const UNITY_MATRIX: [u32; 9] = [0x10000, 0, 0, 0, 0x10000, 0, 0, 0, 0x4000_0000];
+
+fn write_matrix(out: &mut Vec<u8>) {
+    for v in UNITY_MATRIX {
+        out.extend_from_slice(&v.to_be_bytes());
+    }
+}
+
+/// Version 1 when any of `vals` needs 64 bits.
+fn version_for(vals: &[u64]) -> u8 {
+    vals.iter().any(|v| *v > u32::MAX as u64) as u8
+}
+
+fn write_uint(out: &mut Vec<u8>, version: u8, val: u64) {
+    if version == 1 {
+        out.extend_from_slice(&val.to_be_bytes());
+    } else {
+        out.extend_from_slice(&(val as u32).to_be_bytes());
+    }
+}
+
+/// Movie header. Rate, volume and matrix are written as the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Mvhd {
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+    pub next_track_id: u32,
+}
+
+impl IsoBox for Mvhd {
+    const TYPE: FourCc = fcc!("mvhd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (v, _) = r.full(1)?;
+        let creation_time = r.uint(v)?;
+        let modification_time = r.uint(v)?;
+        let timescale = r.u32()?;
+        let duration = r.uint(v)?;
+        // rate, volume, reserved, matrix, pre_defined
+        r.bytes(76)?;
+        Ok(Self {
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            next_track_id: r.u32()?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let v = version_for(&[self.creation_time, self.modification_time, self.duration]);
+        out.extend_from_slice(&((v as u32) << 24).to_be_bytes());
+        write_uint(out, v, self.creation_time);
+        write_uint(out, v, self.modification_time);
+        out.extend_from_slice(&self.timescale.to_be_bytes());
+        write_uint(out, v, self.duration);
+        out.extend_from_slice(&0x10000u32.to_be_bytes());
+        out.extend_from_slice(&0x100u16.to_be_bytes());
+        out.extend_from_slice(&[0; 10]);
+        write_matrix(out);
+        out.extend_from_slice(&[0; 24]);
+        out.extend_from_slice(&self.next_track_id.to_be_bytes());
+    }
+}
+
+/// Track header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Tkhd {
+    /// `1` enabled, `2` in movie, `4` in preview.
+    pub flags: u32,
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub track_id: u32,
+    pub duration: u64,
+
+    /// 8.8 fixed point, `0x100` for audio tracks.
+    pub volume: u16,
+
+    /// 16.16 fixed point.
+    pub width: u32,
+    pub height: u32,
+}
+
+impl IsoBox for Tkhd {
+    const TYPE: FourCc = fcc!("tkhd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (v, flags) = r.full(1)?;
+        let creation_time = r.uint(v)?;
+        let modification_time = r.uint(v)?;
+        let track_id = r.u32()?;
+        r.u32()?;
+        let duration = r.uint(v)?;
+        // reserved, layer, alternate_group
+        r.bytes(12)?;
+        let volume = r.u16()?;
+        // reserved, matrix
+        r.bytes(38)?;
+        Ok(Self {
+            flags,
+            creation_time,
+            modification_time,
+            track_id,
+            duration,
+            volume,
+            width: r.u32()?,
+            height: r.u32()?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let v = version_for(&[self.creation_time, self.modification_time, self.duration]);
+        out.extend_from_slice(&((v as u32) << 24 | self.flags & 0xff_ffff).to_be_bytes());
+        write_uint(out, v, self.creation_time);
+        write_uint(out, v, self.modification_time);
+        out.extend_from_slice(&self.track_id.to_be_bytes());
+        out.extend_from_slice(&[0; 4]);
+        write_uint(out, v, self.duration);
+        out.extend_from_slice(&[0; 12]);
+        out.extend_from_slice(&self.volume.to_be_bytes());
+        out.extend_from_slice(&[0; 2]);
+        write_matrix(out);
+        out.extend_from_slice(&self.width.to_be_bytes());
+        out.extend_from_slice(&self.height.to_be_bytes());
+    }
+}
+
+/// Media header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mdhd {
+    pub creation_time: u64,
+    pub modification_time: u64,
+    pub timescale: u32,
+    pub duration: u64,
+
+    /// ISO 639-2/T code, `*b"und"` when unknown.
+    pub language: [u8; 3],
+}
+
+impl Default for Mdhd {
+    fn default() -> Self {
+        Self {
+            creation_time: 0,
+            modification_time: 0,
+            timescale: 0,
+            duration: 0,
+            language: *b"und",
+        }
+    }
+}
+
+impl IsoBox for Mdhd {
+    const TYPE: FourCc = fcc!("mdhd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (v, _) = r.full(1)?;
+        let creation_time = r.uint(v)?;
+        let modification_time = r.uint(v)?;
+        let timescale = r.u32()?;
+        let duration = r.uint(v)?;
+        let lang = r.u16()?;
+        let language = [10, 5, 0].map(|shift| (lang >> shift & 0x1f) as u8 + 0x60);
+        Ok(Self {
+            creation_time,
+            modification_time,
+            timescale,
+            duration,
+            language,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        let v = version_for(&[self.creation_time, self.modification_time, self.duration]);
+        out.extend_from_slice(&((v as u32) << 24).to_be_bytes());
+        write_uint(out, v, self.creation_time);
+        write_uint(out, v, self.modification_time);
+        out.extend_from_slice(&self.timescale.to_be_bytes());
+        write_uint(out, v, self.duration);
+        let lang = self
+            .language
+            .iter()
+            .fold(0u16, |l, c| l << 5 | (c.wrapping_sub(0x60) & 0x1f) as u16);
+        out.extend_from_slice(&lang.to_be_bytes());
+        out.extend_from_slice(&[0; 2]);
+    }
+}
+
+/// Handler reference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hdlr {
+    /// `vide`, `soun`, `text`, `meta`, ...
+    pub handler_type: FourCc,
+    pub name: String,
+}
+
+impl Hdlr {
+    pub const VIDEO: FourCc = fcc!("vide");
+    pub const SOUND: FourCc = fcc!("soun");
+}
+
+impl IsoBox for Hdlr {
+    const TYPE: FourCc = fcc!("hdlr");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        r.u32()?;
+        let handler_type = r.fcc()?;
+        r.bytes(12)?;
+        Ok(Self {
+            handler_type,
+            name: r.cstr(),
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 8]);
+        out.extend_from_slice(&self.handler_type.to_bytes());
+        out.extend_from_slice(&[0; 12]);
+        out.extend_from_slice(self.name.as_bytes());
+        out.push(0);
+    }
+}
+
+/// A sample entry box from `stsd`, kept whole.
+///
+/// The bytes are the same as [`cm::FormatDesc::verbatim_iso_sample_entry`],
+/// so entries move between files and format descriptions unchanged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleEntry {
+    bytes: Vec<u8>,
+}
+
+impl SampleEntry {
+    /// Fields of `VisualSampleEntry` before its child boxes.
+    pub const VISUAL_FIELDS_LEN: usize = 78;
+
+    /// Fields of a version 0 `AudioSampleEntry` before its child boxes.
+    pub const AUDIO_FIELDS_LEN: usize = 28;
+
+    /// Takes a complete sample entry box.
+    pub fn with_bytes(bytes: Vec<u8>) -> Result<Self, Error> {
+        let mut it = atoms(&bytes);
+        let atom = it.next().ok_or(Error::Truncated(FourCc(0)))??;
+        if atom.size() != bytes.len() || atom.payload.len() < 8 {
+            return Err(Error::Size(atom.typ, bytes.len() as u64));
+        }
+        Ok(Self { bytes })
+    }
+
+    /// `VisualSampleEntry` with default resolution, depth and no compressor name.
+    pub fn visual(format: FourCc, width: u16, height: u16, children: &[u8]) -> Self {
+        let mut bytes = Vec::with_capacity(8 + Self::VISUAL_FIELDS_LEN + children.len());
+        write_box(&mut bytes, format, |out| {
+            out.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+            out.extend_from_slice(&[0; 16]);
+            out.extend_from_slice(&width.to_be_bytes());
+            out.extend_from_slice(&height.to_be_bytes());
+            out.extend_from_slice(&0x48_0000u32.to_be_bytes());
+            out.extend_from_slice(&0x48_0000u32.to_be_bytes());
+            out.extend_from_slice(&[0, 0, 0, 0, 0, 1]);
+            out.extend_from_slice(&[0; 32]);
+            out.extend_from_slice(&[0, 0x18, 0xff, 0xff]);
+            out.extend_from_slice(children);
+        });
+        Self { bytes }
+    }
+
+    /// Version 0 `AudioSampleEntry`, rates above 65535 go in a child box.
+    pub fn audio(
+        format: FourCc,
+        channels: u16,
+        sample_size: u16,
+        sample_rate: u16,
+        children: &[u8],
+    ) -> Self {
+        let mut bytes = Vec::with_capacity(8 + Self::AUDIO_FIELDS_LEN + children.len());
+        write_box(&mut bytes, format, |out| {
+            out.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 1]);
+            out.extend_from_slice(&[0; 8]);
+            out.extend_from_slice(&channels.to_be_bytes());
+            out.extend_from_slice(&sample_size.to_be_bytes());
+            out.extend_from_slice(&[0; 4]);
+            out.extend_from_slice(&((sample_rate as u32) << 16).to_be_bytes());
+            out.extend_from_slice(children);
+        });
+        Self { bytes }
+    }
+
+    /// The whole box, header included.
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    #[inline]
+    pub fn format(&self) -> FourCc {
+        FourCc::from_bytes(self.bytes[4..8].try_into().unwrap())
+    }
+
+    fn payload(&self) -> &[u8] {
+        match u32::from_be_bytes(self.bytes[..4].try_into().unwrap()) {
+            1 => &self.bytes[16..],
+            _ => &self.bytes[8..],
+        }
+    }
+
+    #[inline]
+    pub fn data_ref_index(&self) -> u16 {
+        u16::from_be_bytes(self.payload()[6..8].try_into().unwrap())
+    }
+
+    fn atom(&self) -> Atom<'_> {
+        let payload = self.payload();
+        Atom {
+            typ: self.format(),
+            offset: 0,
+            header_len: self.bytes.len() - payload.len(),
+            payload,
+        }
+    }
+
+    /// Width and height of a visual entry.
+    pub fn dimensions(&self) -> Option<(u16, u16)> {
+        let p = self.payload().get(24..28)?;
+        Some((
+            u16::from_be_bytes([p[0], p[1]]),
+            u16::from_be_bytes([p[2], p[3]]),
+        ))
+    }
+
+    /// Child box of a visual entry, like `avcC`, `hvcC`, `colr` or `pasp`.
+    pub fn visual_child(&self, typ: FourCc) -> Result<Option<Atom<'_>>, Error> {
+        find_child(self.atom(), Self::VISUAL_FIELDS_LEN, typ)
+    }
+
+    /// Child box of an audio entry, like `esds` or `dOps`, accounting for
+    /// the longer QuickTime version 1 and 2 sound descriptions.
+    pub fn audio_child(&self, typ: FourCc) -> Result<Option<Atom<'_>>, Error> {
+        let version = self
+            .payload()
+            .get(8..10)
+            .map_or(0, |v| u16::from_be_bytes([v[0], v[1]]));
+        let extra = match version {
+            1 => 16,
+            2 => 36,
+            _ => 0,
+        };
+        find_child(self.atom(), Self::AUDIO_FIELDS_LEN + extra, typ)
+    }
+}
+
+fn find_child(atom: Atom<'_>, skip: usize, typ: FourCc) -> Result<Option<Atom<'_>>, Error> {
+    for child in atom.children_after(skip) {
+        let child = child?;
+        if child.typ == typ {
+            return Ok(Some(child));
+        }
+    }
+    Ok(None)
+}
+
+/// Sample descriptions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stsd {
+    pub entries: Vec<SampleEntry>,
+}
+
+impl IsoBox for Stsd {
+    const TYPE: FourCc = fcc!("stsd");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let count = r.count(8)?;
+        let entries = atoms(r.rest())
+            .take(count)
+            .map(|a| {
+                let a = a?;
+                let mut bytes = Vec::with_capacity(a.size());
+                write_box(&mut bytes, a.typ, |out| out.extend_from_slice(a.payload));
+                SampleEntry::with_bytes(bytes)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if entries.len() != count {
+            return Err(Error::Invalid(Self::TYPE, "entry_count"));
+        }
+        Ok(Self { entries })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+        for e in &self.entries {
+            out.extend_from_slice(&e.bytes);
+        }
+    }
+}
+
+/// Decoding time to sample, as `(sample_count, sample_delta)` runs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stts {
+    pub entries: Vec<(u32, u32)>,
+}
+
+impl IsoBox for Stts {
+    const TYPE: FourCc = fcc!("stts");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let n = r.count(8)?;
+        let entries = (0..n)
+            .map(|_| Ok((r.u32()?, r.u32()?)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { entries })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+        for (count, delta) in &self.entries {
+            out.extend_from_slice(&count.to_be_bytes());
+            out.extend_from_slice(&delta.to_be_bytes());
+        }
+    }
+}
+
+/// Composition time offsets, as `(sample_count, sample_offset)` runs.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Ctts {
+    /// `1` allows negative offsets.
+    pub version: u8,
+    pub entries: Vec<(u32, i32)>,
+}
+
+impl IsoBox for Ctts {
+    const TYPE: FourCc = fcc!("ctts");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        let (version, _) = r.full(1)?;
+        let n = r.count(8)?;
+        let entries = (0..n)
+            .map(|_| Ok((r.u32()?, r.i32()?)))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { version, entries })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&((self.version as u32) << 24).to_be_bytes());
+        out.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+        for (count, offset) in &self.entries {
+            out.extend_from_slice(&count.to_be_bytes());
+            out.extend_from_slice(&offset.to_be_bytes());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleToChunk {
+    /// One based.
+    pub first_chunk: u32,
+    pub samples_per_chunk: u32,
+
+    /// One based index into `stsd`.
+    pub sample_desc_index: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stsc {
+    pub entries: Vec<SampleToChunk>,
+}
+
+impl IsoBox for Stsc {
+    const TYPE: FourCc = fcc!("stsc");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let n = r.count(12)?;
+        let entries = (0..n)
+            .map(|_| {
+                Ok(SampleToChunk {
+                    first_chunk: r.u32()?,
+                    samples_per_chunk: r.u32()?,
+                    sample_desc_index: r.u32()?,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if entries.first().is_some_and(|e| e.first_chunk != 1)
+            || entries
+                .windows(2)
+                .any(|w| w[0].first_chunk >= w[1].first_chunk)
+        {
+            return Err(Error::Invalid(Self::TYPE, "first_chunk"));
+        }
+        Ok(Self { entries })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.entries.len() as u32).to_be_bytes());
+        for e in &self.entries {
+            out.extend_from_slice(&e.first_chunk.to_be_bytes());
+            out.extend_from_slice(&e.samples_per_chunk.to_be_bytes());
+            out.extend_from_slice(&e.sample_desc_index.to_be_bytes());
+        }
+    }
+}
+
+/// Sample sizes.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stsz {
+    /// Size of every sample, or `0` when they are listed in `sizes`.
+    pub sample_size: u32,
+    pub sample_count: u32,
+    pub sizes: Vec<u32>,
+}
+
+impl Stsz {
+    pub fn with_sizes(sizes: Vec<u32>) -> Self {
+        Self {
+            sample_size: 0,
+            sample_count: sizes.len() as u32,
+            sizes,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self, index: usize) -> Option<u32> {
+        match self.sample_size {
+            0 => self.sizes.get(index).copied(),
+            size if index < self.sample_count as usize => Some(size),
+            _ => None,
+        }
+    }
+}
+
+impl IsoBox for Stsz {
+    const TYPE: FourCc = fcc!("stsz");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let sample_size = r.u32()?;
+        if sample_size != 0 {
+            return Ok(Self {
+                sample_size,
+                sample_count: r.u32()?,
+                sizes: Vec::new(),
+            });
+        }
+        let n = r.count(4)?;
+        let sizes = (0..n).map(|_| r.u32()).collect::<Result<_, _>>()?;
+        Ok(Self {
+            sample_size,
+            sample_count: n as u32,
+            sizes,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&self.sample_size.to_be_bytes());
+        out.extend_from_slice(&self.sample_count.to_be_bytes());
+        if self.sample_size == 0 {
+            for size in &self.sizes {
+                out.extend_from_slice(&size.to_be_bytes());
+            }
+        }
+    }
+}
+
+/// 32 bit chunk offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stco {
+    pub offsets: Vec<u32>,
+}
+
+impl IsoBox for Stco {
+    const TYPE: FourCc = fcc!("stco");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let n = r.count(4)?;
+        let offsets = (0..n).map(|_| r.u32()).collect::<Result<_, _>>()?;
+        Ok(Self { offsets })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.offsets.len() as u32).to_be_bytes());
+        for o in &self.offsets {
+            out.extend_from_slice(&o.to_be_bytes());
+        }
+    }
+}
+
+/// 64 bit chunk offsets.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Co64 {
+    pub offsets: Vec<u64>,
+}
+
+impl IsoBox for Co64 {
+    const TYPE: FourCc = fcc!("co64");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let n = r.count(8)?;
+        let offsets = (0..n).map(|_| r.u64()).collect::<Result<_, _>>()?;
+        Ok(Self { offsets })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.offsets.len() as u32).to_be_bytes());
+        for o in &self.offsets {
+            out.extend_from_slice(&o.to_be_bytes());
+        }
+    }
+}
+
+/// Sync samples, one based. Without it every sample is a sync sample.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stss {
+    pub samples: Vec<u32>,
+}
+
+impl IsoBox for Stss {
+    const TYPE: FourCc = fcc!("stss");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        let n = r.count(4)?;
+        let samples = (0..n).map(|_| r.u32()).collect::<Result<_, _>>()?;
+        Ok(Self { samples })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(self.samples.len() as u32).to_be_bytes());
+        for s in &self.samples {
+            out.extend_from_slice(&s.to_be_bytes());
+        }
+    }
+}
+
+/// The `stbl` box. Empty for fragmented files, where samples live in `moof`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SampleTable {
+    pub stsd: Stsd,
+    pub stts: Stts,
+    pub ctts: Option<Ctts>,
+    pub stsc: Stsc,
+    pub stsz: Stsz,
+
+    /// From `stco` or `co64`, written as `co64` only when needed.
+    pub chunk_offsets: Vec<u64>,
+    pub stss: Option<Stss>,
+}
+
+impl SampleTable {
+    /// Samples in decode order with times in `timescale` (the `mdhd` one).
+    pub fn samples(&self, timescale: u32) -> Samples<'_> {
+        Samples {
+            table: self,
+            timescale: timescale as i32,
+            index: 0,
+            dts: 0,
+            stts: (0, 0),
+            ctts: (0, 0),
+            stsc: 0,
+            chunk: 0,
+            left_in_chunk: 0,
+            offset: 0,
+            failed: false,
+        }
+    }
+}
+
+impl IsoBox for SampleTable {
+    const TYPE: FourCc = fcc!("stbl");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut res = Self::default();
+        let (mut stsd, mut stts, mut stsc, mut stsz, mut offsets) =
+            (false, false, false, false, false);
+        for atom in atoms(payload) {
+            let atom = atom?;
+            match atom.typ {
+                t if t == Stsd::TYPE => (res.stsd, stsd) = (atom.parse()?, true),
+                t if t == Stts::TYPE => (res.stts, stts) = (atom.parse()?, true),
+                t if t == Ctts::TYPE => res.ctts = Some(atom.parse()?),
+                t if t == Stsc::TYPE => (res.stsc, stsc) = (atom.parse()?, true),
+                t if t == Stsz::TYPE => (res.stsz, stsz) = (atom.parse()?, true),
+                t if t == Stss::TYPE => res.stss = Some(atom.parse()?),
+                t if t == Stco::TYPE => {
+                    let stco: Stco = atom.parse()?;
+                    res.chunk_offsets = stco.offsets.into_iter().map(u64::from).collect();
+                    offsets = true;
+                }
+                t if t == Co64::TYPE => {
+                    res.chunk_offsets = atom.parse::<Co64>()?.offsets;
+                    offsets = true;
+                }
+                _ => {}
+            }
+        }
+        for (present, typ) in [
+            (stsd, Stsd::TYPE),
+            (stts, Stts::TYPE),
+            (stsc, Stsc::TYPE),
+            (stsz, Stsz::TYPE),
+            (offsets, Stco::TYPE),
+        ] {
+            if !present {
+                return Err(Error::Missing(typ));
+            }
+        }
+        Ok(res)
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.stsd.write_to(out);
+        self.stts.write_to(out);
+        if let Some(ctts) = &self.ctts {
+            ctts.write_to(out);
+        }
+        self.stsc.write_to(out);
+        self.stsz.write_to(out);
+        if self.chunk_offsets.iter().any(|o| *o > u32::MAX as u64) {
+            Co64 {
+                offsets: self.chunk_offsets.clone(),
+            }
+            .write_to(out);
+        } else {
+            Stco {
+                offsets: self.chunk_offsets.iter().map(|o| *o as u32).collect(),
+            }
+            .write_to(out);
+        }
+        if let Some(stss) = &self.stss {
+            stss.write_to(out);
+        }
+    }
+}
+
+/// Samples of a [`SampleTable`], see [`SampleTable::samples`].
+///
+/// Ends early if the tables disagree on the number of samples, and after
+/// the first error.
+#[derive(Debug, Clone)]
+pub struct Samples<'a> {
+    table: &'a SampleTable,
+    timescale: i32,
+    index: u32,
+    dts: u64,
+    stts: (usize, u32),
+    ctts: (usize, u32),
+    stsc: usize,
+    /// One based, zero before the first.
+    chunk: u32,
+    left_in_chunk: u32,
+    offset: u64,
+    failed: bool,
+}
+
+/// Value of the next sample in `(count, value)` runs, `state` being the
+/// index of the next run and the samples left in the current one.
+fn next_in_runs<T: Copy>(runs: &[(u32, T)], state: &mut (usize, u32)) -> Option<T> {
+    while state.1 == 0 {
+        state.1 = runs.get(state.0)?.0;
+        state.0 += 1;
+    }
+    state.1 -= 1;
+    Some(runs[state.0 - 1].1)
+}
+
+impl Samples<'_> {
+    fn next_chunk(&mut self) -> Option<()> {
+        let entries = &self.table.stsc.entries;
+        while self.left_in_chunk == 0 {
+            self.chunk += 1;
+            while entries
+                .get(self.stsc + 1)
+                .is_some_and(|e| e.first_chunk <= self.chunk)
+            {
+                self.stsc += 1;
+            }
+            self.left_in_chunk = entries.get(self.stsc)?.samples_per_chunk;
+            self.offset = *self.table.chunk_offsets.get(self.chunk as usize - 1)?;
+        }
+        Some(())
+    }
+}
+
+impl Iterator for Samples<'_> {
+    type Item = Result<Sample, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let size = self.table.stsz.size(self.index as usize)?;
+        self.next_chunk()?;
+
+        let delta = next_in_runs(&self.table.stts.entries, &mut self.stts)?;
+        let cto = match &self.table.ctts {
+            Some(ctts) => next_in_runs(&ctts.entries, &mut self.ctts).unwrap_or(0),
+            None => 0,
+        };
+
+        let res = self.sample(size, delta, cto);
+        self.failed = res.is_err();
+        Some(res)
+    }
+}
+
+impl Samples<'_> {
+    fn sample(&mut self, size: u32, delta: u32, cto: i32) -> Result<Sample, Error> {
+        let dts = i64::try_from(self.dts).map_err(|_| Error::Invalid(Stts::TYPE, "decode time"))?;
+        let pts = dts
+            .checked_add(cto as i64)
+            .ok_or(Error::Invalid(Ctts::TYPE, "composition offset"))?;
+        let next_dts = self
+            .dts
+            .checked_add(delta as u64)
+            .ok_or(Error::Invalid(Stts::TYPE, "decode time"))?;
+        let next_offset = self
+            .offset
+            .checked_add(size as u64)
+            .ok_or(Error::Invalid(Stsz::TYPE, "sample offset"))?;
+
+        let sample = Sample {
+            index: self.index,
+            dts: cm::Time::new(dts, self.timescale),
+            pts: cm::Time::new(pts, self.timescale),
+            duration: cm::Time::new(delta as i64, self.timescale),
+            offset: self.offset,
+            size,
+            sync: match &self.table.stss {
+                Some(stss) => stss.samples.binary_search(&(self.index + 1)).is_ok(),
+                None => true,
+            },
+            desc_index: self.table.stsc.entries[self.stsc].sample_desc_index,
+        };
+        self.index += 1;
+        self.dts = next_dts;
+        self.offset = next_offset;
+        self.left_in_chunk -= 1;
+        Ok(sample)
+    }
+}
+
+/// Track extends defaults for fragments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Trex {
+    pub track_id: u32,
+    pub default_sample_desc_index: u32,
+    pub default_sample_duration: u32,
+    pub default_sample_size: u32,
+    pub default_sample_flags: u32,
+}
+
+impl IsoBox for Trex {
+    const TYPE: FourCc = fcc!("trex");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut r = Reader::new(Self::TYPE, payload);
+        r.full(0)?;
+        Ok(Self {
+            track_id: r.u32()?,
+            default_sample_desc_index: r.u32()?,
+            default_sample_duration: r.u32()?,
+            default_sample_size: r.u32()?,
+            default_sample_flags: r.u32()?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&[0; 4]);
+        for v in [
+            self.track_id,
+            self.default_sample_desc_index,
+            self.default_sample_duration,
+            self.default_sample_size,
+            self.default_sample_flags,
+        ] {
+            out.extend_from_slice(&v.to_be_bytes());
+        }
+    }
+}
+
+/// The `trak` box with the parts needed to locate and decode samples.
+///
+/// Writing produces a minimal `trak`: `tkhd`, then `mdia` with `mdhd`,
+/// `hdlr` and `minf` (media header for the handler, a self-contained
+/// `dref`, and the sample table). Edit lists and other boxes are not kept.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Track {
+    pub tkhd: Tkhd,
+    pub mdhd: Mdhd,
+    pub hdlr: Hdlr,
+    pub stbl: SampleTable,
+}
+
+impl Track {
+    #[inline]
+    pub fn samples(&self) -> Samples<'_> {
+        self.stbl.samples(self.mdhd.timescale)
+    }
+}
+
+impl IsoBox for Track {
+    const TYPE: FourCc = fcc!("trak");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let trak = Atom {
+            typ: Self::TYPE,
+            offset: 0,
+            header_len: 0,
+            payload,
+        };
+        let mdia = trak.req(fcc!("mdia"))?;
+        Ok(Self {
+            tkhd: trak.req(Tkhd::TYPE)?.parse()?,
+            mdhd: mdia.req(Mdhd::TYPE)?.parse()?,
+            hdlr: mdia.req(Hdlr::TYPE)?.parse()?,
+            stbl: mdia.req(fcc!("minf"))?.req(SampleTable::TYPE)?.parse()?,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.tkhd.write_to(out);
+        write_box(out, fcc!("mdia"), |out| {
+            self.mdhd.write_to(out);
+            self.hdlr.write_to(out);
+            write_box(out, fcc!("minf"), |out| {
+                match self.hdlr.handler_type {
+                    Hdlr::VIDEO => write_full_box(out, fcc!("vmhd"), 0, 1, |out| {
+                        out.extend_from_slice(&[0; 8])
+                    }),
+                    Hdlr::SOUND => write_full_box(out, fcc!("smhd"), 0, 0, |out| {
+                        out.extend_from_slice(&[0; 4])
+                    }),
+                    _ => write_full_box(out, fcc!("nmhd"), 0, 0, |_| {}),
+                }
+                write_box(out, fcc!("dinf"), |out| {
+                    write_full_box(out, fcc!("dref"), 0, 0, |out| {
+                        out.extend_from_slice(&1u32.to_be_bytes());
+                        write_full_box(out, fcc!("url "), 0, 1, |_| {});
+                    })
+                });
+                self.stbl.write_to(out);
+            });
+        });
+    }
+}
+
+/// The `moov` box: movie header, tracks and fragment defaults.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Moov {
+    pub mvhd: Mvhd,
+    pub tracks: Vec<Track>,
+
+    /// From `mvex`, present in fragmented files.
+    pub trex: Vec<Trex>,
+}
+
+impl Moov {
+    pub fn track(&self, track_id: u32) -> Option<&Track> {
+        self.tracks.iter().find(|t| t.tkhd.track_id == track_id)
+    }
+
+    pub fn trex(&self, track_id: u32) -> Option<&Trex> {
+        self.trex.iter().find(|t| t.track_id == track_id)
+    }
+}
+
+impl IsoBox for Moov {
+    const TYPE: FourCc = fcc!("moov");
+
+    fn parse(payload: &[u8]) -> Result<Self, Error> {
+        let mut mvhd = None;
+        let mut tracks = Vec::new();
+        let mut trex = Vec::new();
+        for atom in atoms(payload) {
+            let atom = atom?;
+            match atom.typ {
+                t if t == Mvhd::TYPE => mvhd = Some(atom.parse()?),
+                t if t == Track::TYPE => tracks.push(atom.parse()?),
+                t if t == fcc!("mvex") => {
+                    for child in atom.children() {
+                        let child = child?;
+                        if child.typ == Trex::TYPE {
+                            trex.push(child.parse()?);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(Self {
+            mvhd: mvhd.ok_or(Error::Missing(Mvhd::TYPE))?,
+            tracks,
+            trex,
+        })
+    }
+
+    fn write_payload(&self, out: &mut Vec<u8>) {
+        self.mvhd.write_to(out);
+        for track in &self.tracks {
+            track.write_to(out);
+        }
+        if !self.trex.is_empty() {
+            write_box(out, fcc!("mvex"), |out| {
+                for trex in &self.trex {
+                    trex.write_to(out);
+                }
+            });
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl SampleEntry {
+    /// The entry CoreMedia keeps for a format description read from or
+    /// written to an ISO file.
+    pub fn with_format_desc(desc: &cm::FormatDesc) -> Option<Result<Self, Error>> {
+        let data = desc.verbatim_iso_sample_entry()?;
+        Some(Self::with_bytes(data.as_slice().to_vec()))
+    }
+
+    /// A video format description for this entry, interpreted by CoreMedia
+    /// with ISO family rules.
+    pub fn video_format_desc(&self) -> crate::os::Result<crate::arc::R<cm::VideoFormatDesc>> {
+        cm::VideoFormatDesc::from_be_image_desc_data(
+            &self.bytes,
+            crate::cf::StringEncoding::sys_encoding(),
+            Some(cm::ImageDescFlavor::iso_family()),
+        )
+    }
+
+    /// An audio format description for this entry, interpreted by CoreMedia
+    /// with ISO family rules.
+    pub fn audio_format_desc(&self) -> crate::os::Result<crate::arc::R<cm::AudioFormatDesc>> {
+        cm::AudioFormatDesc::from_be_sound_desc_data(
+            &self.bytes,
+            Some(cm::SoundDescFlavor::iso_family()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cm::iso_bmff::{
+            self, Ctts, Hdlr, IsoBox, Mdhd, Moov, Mvhd, SampleEntry, SampleTable, SampleToChunk,
+            Stsc, Stsd, Stss, Stsz, Stts, Tkhd, Track,
+        },
+        fcc,
+    };
+
+    fn video_track() -> Track {
+        let avcc = [0, 0, 0, 9, b'a', b'v', b'c', b'C', 1];
+        Track {
+            tkhd: Tkhd {
+                flags: 3,
+                track_id: 1,
+                duration: 5 * 512,
+                width: 640 << 16,
+                height: 360 << 16,
+                ..Default::default()
+            },
+            mdhd: Mdhd {
+                timescale: 12800,
+                duration: 5 * 512,
+                ..Default::default()
+            },
+            hdlr: Hdlr {
+                handler_type: Hdlr::VIDEO,
+                name: "VideoHandler".into(),
+            },
+            stbl: SampleTable {
+                stsd: Stsd {
+                    entries: vec![SampleEntry::visual(fcc!("avc1"), 640, 360, &avcc)],
+                },
+                stts: Stts {
+                    entries: vec![(5, 512)],
+                },
+                // I P B B P in decode order
+                ctts: Some(Ctts {
+                    version: 0,
+                    entries: vec![(1, 512), (1, 1536), (2, 0), (1, 512)],
+                }),
+                stsc: Stsc {
+                    entries: vec![
+                        SampleToChunk {
+                            first_chunk: 1,
+                            samples_per_chunk: 2,
+                            sample_desc_index: 1,
+                        },
+                        SampleToChunk {
+                            first_chunk: 2,
+                            samples_per_chunk: 3,
+                            sample_desc_index: 1,
+                        },
+                    ],
+                },
+                stsz: Stsz::with_sizes(vec![1000, 200, 100, 100, 300]),
+                chunk_offsets: vec![48, 5000],
+                stss: Some(Stss { samples: vec![1] }),
+            },
+        }
+    }
+
+    #[test]
+    fn samples() {
+        let track = video_track();
+        let samples: Vec<_> = track.samples().map(Result::unwrap).collect();
+        assert_eq!(samples.len(), 5);
+
+        let ranges: Vec<_> = samples.iter().map(|s| s.range().unwrap()).collect();
+        assert_eq!(
+            ranges,
+            [48..1048, 1048..1248, 5000..5100, 5100..5200, 5200..5500]
+        );
+
+        let dts: Vec<_> = samples.iter().map(|s| s.dts.value).collect();
+        assert_eq!(dts, [0, 512, 1024, 1536, 2048]);
+        let pts: Vec<_> = samples.iter().map(|s| s.pts.value).collect();
+        assert_eq!(pts, [512, 2048, 1024, 1536, 2560]);
+        assert!(samples
+            .iter()
+            .all(|s| s.dts.scale == 12800 && s.duration.value == 512));
+
+        let sync: Vec<_> = samples.iter().map(|s| s.sync).collect();
+        assert_eq!(sync, [true, false, false, false, false]);
+    }
+
+    #[test]
+    fn samples_overflow() {
+        let mut track = video_track();
+        track.stbl.chunk_offsets = vec![u64::MAX - 1200, u64::MAX - 250];
+        let samples: Vec<_> = track.samples().collect();
+        assert_eq!(samples.len(), 5);
+        assert_eq!(
+            samples[1].as_ref().unwrap().range(),
+            Some(u64::MAX - 200..u64::MAX)
+        );
+        assert!(samples[..4].iter().all(|s| s.is_ok()));
+        assert_eq!(
+            samples[4],
+            Err(iso_bmff::Error::Invalid(fcc!("stsz"), "sample offset"))
+        );
+    }
+
+    #[test]
+    fn moov_round_trip() {
+        let moov = Moov {
+            mvhd: Mvhd {
+                timescale: 1000,
+                duration: 200,
+                next_track_id: 2,
+                ..Default::default()
+            },
+            tracks: vec![video_track()],
+            trex: vec![],
+        };
+        let bytes = moov.to_bytes();
+        let stsd = iso_bmff::find(
+            &bytes,
+            &[
+                fcc!("moov"),
+                fcc!("trak"),
+                fcc!("mdia"),
+                fcc!("minf"),
+                fcc!("stbl"),
+                fcc!("stsd"),
+            ],
+        )
+        .unwrap()
+        .unwrap();
+        let stsd: Stsd = stsd.parse().unwrap();
+        let entry = &stsd.entries[0];
+        assert_eq!(entry.format(), fcc!("avc1"));
+        assert_eq!(entry.data_ref_index(), 1);
+        assert_eq!(entry.dimensions(), Some((640, 360)));
+        assert_eq!(entry.as_bytes().len(), 8 + 78 + 9);
+        let avcc = entry.visual_child(fcc!("avcC")).unwrap().unwrap();
+        assert_eq!(avcc.payload, [1]);
+
+        let atom = iso_bmff::atoms(&bytes).next().unwrap().unwrap();
+        let parsed: Moov = atom.parse().unwrap();
+        assert_eq!(parsed, moov);
+        assert_eq!(parsed.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn headers() {
+        let mdhd = Mdhd {
+            timescale: 48000,
+            duration: u32::MAX as u64 + 1,
+            language: *b"eng",
+            ..Default::default()
+        };
+        let bytes = mdhd.to_bytes();
+        // version 1 for the 64 bit duration
+        assert_eq!(bytes[8], 1);
+        assert_eq!(&bytes[bytes.len() - 4..bytes.len() - 2], [0x15, 0xc7]);
+        assert_eq!(Mdhd::parse(&bytes[8..]).unwrap(), mdhd);
+
+        let tkhd = Tkhd {
+            flags: 7,
+            track_id: 2,
+            volume: 0x100,
+            ..Default::default()
+        };
+        assert_eq!(tkhd.to_bytes().len(), 92);
+        assert_eq!(Tkhd::parse(&tkhd.to_bytes()[8..]).unwrap(), tkhd);
+        assert_eq!(Mvhd::default().to_bytes().len(), 108);
+
+        let entry = SampleEntry::audio(
+            fcc!("mp4a"),
+            2,
+            16,
+            48000,
+            &[0, 0, 0, 8, b'e', b's', b'd', b's'],
+        );
+        assert_eq!(entry.as_bytes().len(), 8 + 28 + 8);
+        assert!(entry.audio_child(fcc!("esds")).unwrap().is_some());
+        assert!(entry.visual_child(fcc!("esds")).unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_boxes() {
+        let mut stbl = video_track().stbl.to_bytes();
+        // drop stco and stss
+        stbl.truncate(stbl.len() - 44);
+        let len = stbl.len() as u32;
+        stbl[..4].copy_from_slice(&len.to_be_bytes());
+        assert_eq!(
+            SampleTable::parse(&stbl[8..]),
+            Err(iso_bmff::Error::Missing(fcc!("stco")))
+        );
+    }
+}
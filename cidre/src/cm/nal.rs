@@ -0,0 +1,554 @@
+//! NAL unit framing for H.264 and HEVC.
+//!
+//! CoreMedia and VideoToolbox carry NAL units length prefixed (the `avcC`/`hvcC`
+//! layout), with the prefix size given by the format description. Network
+//! protocols and raw elementary streams use Annex-B start codes and carry
+//! parameter sets in band. The functions here move between the two without
+//! touching the NAL unit payloads beyond what start codes require.
+
+use std::borrow::Cow;
+
+use crate::os;
+
+#[cfg(target_vendor = "apple")]
+use crate::cm;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Data ends inside a length prefix or NAL unit.
+    Truncated,
+
+    /// Length prefix size other than 1, 2 or 4.
+    HeaderLen(usize),
+
+    /// NAL unit of this length doesn't fit the length prefix.
+    TooLong(usize),
+
+    Os(os::Error),
+}
+
+impl From<os::Error> for Error {
+    fn from(value: os::Error) -> Self {
+        Self::Os(value)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("NAL unit data is truncated"),
+            Self::HeaderLen(len) => write!(f, "invalid NAL unit header length {len}"),
+            Self::TooLong(len) => write!(f, "NAL unit of {len} bytes doesn't fit its length"),
+            Self::Os(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    H264,
+    Hevc,
+}
+
+impl Codec {
+    /// `nal_unit_type` from the NAL unit header.
+    #[inline]
+    pub fn nal_type(self, unit: &[u8]) -> Option<u8> {
+        let header = *unit.first()?;
+        Some(match self {
+            Self::H264 => header & 0x1f,
+            Self::Hevc => header >> 1 & 0x3f,
+        })
+    }
+
+    /// SPS and PPS, plus VPS for HEVC.
+    pub fn is_param_set(self, unit: &[u8]) -> bool {
+        matches!(
+            (self, self.nal_type(unit)),
+            (Self::H264, Some(7 | 8)) | (Self::Hevc, Some(32..=34))
+        )
+    }
+
+    /// IDR slice for H.264, any IRAP picture (BLA, IDR, CRA) for HEVC.
+    pub fn is_keyframe(self, unit: &[u8]) -> bool {
+        matches!(
+            (self, self.nal_type(unit)),
+            (Self::H264, Some(5)) | (Self::Hevc, Some(16..=23))
+        )
+    }
+}
+
+fn check_header_len(header_len: usize) -> Result<(), Error> {
+    match header_len {
+        1 | 2 | 4 => Ok(()),
+        _ => Err(Error::HeaderLen(header_len)),
+    }
+}
+
+fn read_len(header: &[u8]) -> usize {
+    header.iter().fold(0, |len, b| len << 8 | *b as usize)
+}
+
+/// NAL units of an Annex-B byte stream, without start codes.
+///
+/// ```
+/// use cidre::cm::nal;
+///
+/// let stream = [0, 0, 0, 1, 0x67, 0x42, 0, 0, 1, 0x68, 0xce, 0, 0, 0, 0, 1, 0x65, 0x88];
+/// let units: Vec<_> = nal::annex_b_units(&stream).collect();
+/// assert_eq!(units, [&[0x67, 0x42][..], &[0x68, 0xce], &[0x65, 0x88]]);
+/// ```
+#[inline]
+pub fn annex_b_units(data: &[u8]) -> AnnexBUnits<'_> {
+    AnnexBUnits { data }
+}
+
+#[derive(Debug, Clone)]
+pub struct AnnexBUnits<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for AnnexBUnits<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.data.is_empty() {
+            let (unit, rest) = match self.data.windows(3).position(|w| w == [0, 0, 1]) {
+                Some(pos) => (&self.data[..pos], &self.data[pos + 3..]),
+                None => (self.data, &[][..]),
+            };
+            self.data = rest;
+            // trailing_zero_8bits and the zero_byte of a four byte start code
+            let end = unit.iter().rposition(|b| *b != 0).map_or(0, |i| i + 1);
+            if end > 0 {
+                return Some(&unit[..end]);
+            }
+        }
+        None
+    }
+}
+
+/// NAL units of length prefixed data, such as a `cm::SampleBuf` from
+/// VideoToolbox, with `header_len` byte big-endian prefixes.
+///
+/// ```
+/// use cidre::cm::nal;
+///
+/// let data = [0, 2, 0x09, 0xf0, 0, 3, 0x65, 0x88, 0x84];
+/// let units: Vec<_> = nal::len_prefixed_units(&data, 2).unwrap().collect();
+/// assert_eq!(units, [Ok(&[0x09, 0xf0][..]), Ok(&[0x65, 0x88, 0x84][..])]);
+///
+/// assert_eq!(nal::len_prefixed_units(&data, 3).err(), Some(nal::Error::HeaderLen(3)));
+/// ```
+pub fn len_prefixed_units(data: &[u8], header_len: usize) -> Result<LenPrefixedUnits<'_>, Error> {
+    check_header_len(header_len)?;
+    Ok(LenPrefixedUnits { data, header_len })
+}
+
+#[derive(Debug, Clone)]
+pub struct LenPrefixedUnits<'a> {
+    data: &'a [u8],
+    header_len: usize,
+}
+
+impl<'a> Iterator for LenPrefixedUnits<'a> {
+    type Item = Result<&'a [u8], Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let res = self
+            .data
+            .get(..self.header_len)
+            .map(read_len)
+            .and_then(|len| self.data.get(self.header_len..self.header_len + len))
+            .ok_or(Error::Truncated);
+        match res {
+            Ok(unit) => self.data = &self.data[self.header_len + unit.len()..],
+            Err(_) => self.data = &[],
+        }
+        Some(res)
+    }
+}
+
+/// Inserts emulation prevention bytes where `unit` would otherwise contain a
+/// start code prefix (`00 00 00`, `00 00 01`, `00 00 02`) or end with a zero
+/// byte, so it survives Annex-B framing.
+///
+/// Escapes already present are left alone, so well-formed NAL units come
+/// back borrowed.
+///
+/// ```
+/// use cidre::cm::nal;
+///
+/// assert_eq!(&nal::escape_start_codes(&[0x65, 0, 0, 1, 0, 0, 3, 0])[..], [0x65, 0, 0, 3, 1, 0, 0, 3, 0, 3]);
+/// assert!(matches!(nal::escape_start_codes(&[0x65, 0, 0, 3, 1]), std::borrow::Cow::Borrowed(_)));
+/// ```
+pub fn escape_start_codes(unit: &[u8]) -> Cow<'_, [u8]> {
+    let mut zeros = 0;
+    let clean = unit.iter().all(|b| {
+        let ok = zeros < 2 || *b > 2;
+        zeros = if *b == 0 { zeros + 1 } else { 0 };
+        ok
+    });
+    if clean && unit.last() != Some(&0) {
+        return Cow::Borrowed(unit);
+    }
+    let mut res = Vec::with_capacity(unit.len() + unit.len() / 64 + 2);
+    zeros = 0;
+    for b in unit {
+        if zeros >= 2 && *b <= 2 {
+            res.push(3);
+            zeros = 0;
+        }
+        zeros = if *b == 0 { zeros + 1 } else { 0 };
+        res.push(*b);
+    }
+    if res.last() == Some(&0) {
+        res.push(3);
+    }
+    Cow::Owned(res)
+}
+
+/// Appends `unit` to `out` behind a `header_len` byte big-endian length.
+pub fn write_len_prefixed(unit: &[u8], header_len: usize, out: &mut Vec<u8>) -> Result<(), Error> {
+    check_header_len(header_len)?;
+    if header_len < 4 && unit.len() >> (header_len * 8) != 0 || unit.len() > u32::MAX as usize {
+        return Err(Error::TooLong(unit.len()));
+    }
+    out.extend_from_slice(&(unit.len() as u32).to_be_bytes()[4 - header_len..]);
+    out.extend_from_slice(unit);
+    Ok(())
+}
+
+fn write_annex_b<U: AsRef<[u8]>>(
+    units: impl Iterator<Item = Result<U, Error>>,
+    codec: Codec,
+    param_sets: &[&[u8]],
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let mut has_param_sets = false;
+    for unit in units {
+        let unit = unit?;
+        let unit = unit.as_ref();
+        if codec.is_param_set(unit) {
+            has_param_sets = true;
+        } else if !has_param_sets && codec.is_keyframe(unit) {
+            for ps in param_sets {
+                out.extend_from_slice(&[0, 0, 0, 1]);
+                out.extend_from_slice(&escape_start_codes(ps));
+            }
+            has_param_sets = true;
+        }
+        out.extend_from_slice(&[0, 0, 0, 1]);
+        out.extend_from_slice(&escape_start_codes(unit));
+    }
+    Ok(())
+}
+
+/// Converts one length prefixed access unit to Annex-B, appending to `out`.
+///
+/// `param_sets` (VPS, SPS, PPS as stored in the format description) are
+/// written before the first keyframe NAL unit unless the access unit already
+/// carries parameter sets of its own, so decoders can join at any keyframe.
+///
+/// ```
+/// use cidre::cm::nal;
+///
+/// let avcc = [0, 0, 0, 3, 0x65, 0x88, 0x84, 0, 0, 0, 2, 0x41, 0x9a];
+/// let sps = [0x67, 0x42, 0x00, 0x1e];
+/// let pps = [0x68, 0xce, 0x3c, 0x80];
+///
+/// let mut annex_b = Vec::new();
+/// nal::to_annex_b(&avcc, 4, nal::Codec::H264, &[&sps, &pps], &mut annex_b).unwrap();
+/// assert_eq!(
+///     annex_b,
+///     [
+///         0, 0, 0, 1, 0x67, 0x42, 0x00, 0x1e,
+///         0, 0, 0, 1, 0x68, 0xce, 0x3c, 0x80,
+///         0, 0, 0, 1, 0x65, 0x88, 0x84,
+///         0, 0, 0, 1, 0x41, 0x9a,
+///     ]
+/// );
+/// ```
+pub fn to_annex_b(
+    src: &[u8],
+    header_len: usize,
+    codec: Codec,
+    param_sets: &[&[u8]],
+    out: &mut Vec<u8>,
+) -> Result<(), Error> {
+    write_annex_b(len_prefixed_units(src, header_len)?, codec, param_sets, out)
+}
+
+/// Converts an Annex-B access unit to length prefixed NAL units, appending
+/// to `out`.
+///
+/// Parameter sets are left out of `out` and returned in stream order, since
+/// CoreMedia wants them in the format description instead
+/// (see [`cm::VideoFormatDesc::with_h264_param_sets`]).
+///
+/// ```
+/// use cidre::cm::nal;
+///
+/// let annex_b = [
+///     0, 0, 0, 1, 0x40, 0x01, 0x0c,
+///     0, 0, 0, 1, 0x42, 0x01, 0x01,
+///     0, 0, 0, 1, 0x44, 0x01, 0xc1,
+///     0, 0, 1, 0x26, 0x01, 0xaf, 0, 0, 3, 1,
+///     0, 0,
+/// ];
+/// let mut hvcc = Vec::new();
+/// let param_sets = nal::to_len_prefixed(&annex_b, 4, nal::Codec::Hevc, &mut hvcc).unwrap();
+/// assert_eq!(param_sets, [&[0x40, 0x01, 0x0c][..], &[0x42, 0x01, 0x01], &[0x44, 0x01, 0xc1]]);
+/// assert_eq!(hvcc, [0, 0, 0, 7, 0x26, 0x01, 0xaf, 0, 0, 3, 1]);
+/// ```
+pub fn to_len_prefixed<'a>(
+    src: &'a [u8],
+    header_len: usize,
+    codec: Codec,
+    out: &mut Vec<u8>,
+) -> Result<Vec<&'a [u8]>, Error> {
+    check_header_len(header_len)?;
+    let mut param_sets = Vec::new();
+    for unit in annex_b_units(src) {
+        if codec.is_param_set(unit) {
+            param_sets.push(unit);
+        } else {
+            write_len_prefixed(unit, header_len, out)?;
+        }
+    }
+    Ok(param_sets)
+}
+
+/// NAL units of a length prefixed cm::BlockBuf.
+///
+/// Units that lie within one memory block are borrowed, only those
+/// spanning blocks are copied.
+#[cfg(target_vendor = "apple")]
+#[derive(Debug)]
+pub struct BlockBufUnits<'a> {
+    buf: &'a cm::BlockBuf,
+    offset: usize,
+    len: usize,
+    header_len: usize,
+}
+
+#[cfg(target_vendor = "apple")]
+impl<'a> BlockBufUnits<'a> {
+    fn read(&mut self) -> Result<Cow<'a, [u8]>, Error> {
+        let mut header = [0u8; 4];
+        let header = &mut header[..self.header_len];
+        if self.len - self.offset < header.len() {
+            return Err(Error::Truncated);
+        }
+        self.buf.copy_data_bytes(self.offset, header)?;
+        let start = self.offset + header.len();
+        let len = read_len(header);
+        if self.len - start < len {
+            return Err(Error::Truncated);
+        }
+        self.offset = start + len;
+        if self.buf.is_range_contiguous(start, len) {
+            let (data, _) = self.buf.data_ptr_at(start)?;
+            Ok(Cow::Borrowed(&data[..len]))
+        } else {
+            let mut data = vec![0u8; len];
+            self.buf.copy_data_bytes(start, &mut data)?;
+            Ok(Cow::Owned(data))
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl<'a> Iterator for BlockBufUnits<'a> {
+    type Item = Result<Cow<'a, [u8]>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.len {
+            return None;
+        }
+        let res = self.read();
+        if res.is_err() {
+            self.offset = self.len;
+        }
+        Some(res)
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl cm::BlockBuf {
+    /// Iterates NAL units of a compressed video sample.
+    ///
+    /// `header_len` comes from
+    /// [`cm::VideoFormatDesc::h264_params_count_and_header_len`] or
+    /// [`cm::VideoFormatDesc::hevc_params_count_and_header_len`].
+    pub fn nal_units(&self, header_len: usize) -> Result<BlockBufUnits<'_>, Error> {
+        check_header_len(header_len)?;
+        Ok(BlockBufUnits {
+            buf: self,
+            offset: 0,
+            len: self.data_len(),
+            header_len,
+        })
+    }
+
+    /// [`to_annex_b`] straight from a cm::BlockBuf.
+    pub fn write_annex_b(
+        &self,
+        header_len: usize,
+        codec: Codec,
+        param_sets: &[&[u8]],
+        out: &mut Vec<u8>,
+    ) -> Result<(), Error> {
+        write_annex_b(self.nal_units(header_len)?, codec, param_sets, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cm::nal::{self, Codec, Error};
+
+    /// AUD, SEI, IDR slice with an emulation prevention byte, then a second
+    /// slice of the same picture, with four byte lengths.
+    const AVCC_IDR: &[u8] = &[
+        0, 0, 0, 2, 0x09, 0x10, //
+        0, 0, 0, 5, 0x06, 0x05, 0x01, 0xaa, 0x80, //
+        0, 0, 0, 7, 0x65, 0x88, 0x80, 0, 0, 3, 1, //
+        0, 0, 0, 3, 0x65, 0x00, 0x44,
+    ];
+
+    const SPS: &[u8] = &[0x67, 0x42, 0x00, 0x1e, 0xf4, 0x0a, 0x0f, 0xc8];
+    const PPS: &[u8] = &[0x68, 0xce, 0x38, 0x80];
+
+    const ANNEX_B_IDR: &[u8] = &[
+        0, 0, 0, 1, 0x09, 0x10, //
+        0, 0, 0, 1, 0x06, 0x05, 0x01, 0xaa, 0x80, //
+        0, 0, 0, 1, 0x67, 0x42, 0x00, 0x1e, 0xf4, 0x0a, 0x0f, 0xc8, //
+        0, 0, 0, 1, 0x68, 0xce, 0x38, 0x80, //
+        0, 0, 0, 1, 0x65, 0x88, 0x80, 0, 0, 3, 1, //
+        0, 0, 0, 1, 0x65, 0x00, 0x44,
+    ];
+
+    #[test]
+    fn avcc_to_annex_b() {
+        let mut out = Vec::new();
+        nal::to_annex_b(AVCC_IDR, 4, Codec::H264, &[SPS, PPS], &mut out).unwrap();
+        assert_eq!(out, ANNEX_B_IDR);
+
+        // non-IDR pictures don't get parameter sets
+        let mut out = Vec::new();
+        let p = [0, 0, 0, 3, 0x41, 0x9a, 0x02];
+        nal::to_annex_b(&p, 4, Codec::H264, &[SPS, PPS], &mut out).unwrap();
+        assert_eq!(out, [0, 0, 0, 1, 0x41, 0x9a, 0x02]);
+
+        // nor do access units already carrying them
+        let mut in_band = Vec::new();
+        for unit in [SPS, &[0x65, 0x88]] {
+            nal::write_len_prefixed(unit, 4, &mut in_band).unwrap();
+        }
+        let mut out = Vec::new();
+        nal::to_annex_b(&in_band, 4, Codec::H264, &[SPS, PPS], &mut out).unwrap();
+        assert_eq!(nal::annex_b_units(&out).count(), 2);
+    }
+
+    #[test]
+    fn annex_b_to_avcc() {
+        let mut out = Vec::new();
+        let param_sets = nal::to_len_prefixed(ANNEX_B_IDR, 4, Codec::H264, &mut out).unwrap();
+        assert_eq!(param_sets, [SPS, PPS]);
+        assert_eq!(out, AVCC_IDR);
+    }
+
+    #[test]
+    fn header_lens() {
+        let units: Vec<_> = nal::annex_b_units(ANNEX_B_IDR).collect();
+        for header_len in [1, 2, 4] {
+            let mut data = Vec::new();
+            for unit in &units {
+                nal::write_len_prefixed(unit, header_len, &mut data).unwrap();
+            }
+            let back: Vec<_> = nal::len_prefixed_units(&data, header_len)
+                .unwrap()
+                .collect::<Result<_, _>>()
+                .unwrap();
+            assert_eq!(back, units);
+
+            let mut annex_b = Vec::new();
+            nal::to_annex_b(&data, header_len, Codec::H264, &[], &mut annex_b).unwrap();
+            assert_eq!(annex_b, ANNEX_B_IDR);
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(
+            nal::write_len_prefixed(&[0; 256], 1, &mut out),
+            Err(Error::TooLong(256))
+        );
+        nal::write_len_prefixed(&[0; 255], 1, &mut out).unwrap();
+        assert_eq!(out[0], 255);
+        assert_eq!(
+            nal::write_len_prefixed(&[0; 70000], 2, &mut out),
+            Err(Error::TooLong(70000))
+        );
+        assert_eq!(
+            nal::write_len_prefixed(&[], 0, &mut out),
+            Err(Error::HeaderLen(0))
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let units: Vec<_> = nal::len_prefixed_units(&AVCC_IDR[..20], 4)
+            .unwrap()
+            .collect();
+        assert_eq!(units.len(), 3);
+        assert_eq!(units[2], Err(Error::Truncated));
+        let mut out = Vec::new();
+        assert_eq!(
+            nal::to_annex_b(&AVCC_IDR[..2], 4, Codec::H264, &[], &mut out),
+            Err(Error::Truncated)
+        );
+    }
+
+    #[test]
+    fn annex_b_framing() {
+        // three and four byte start codes, leading zeros and trailing_zero_8bits
+        let stream = [
+            0, 0, 0, 0, 1, 0x09, 0xf0, 0, 0, 0, 0, 1, 0x65, 0x11, 0, 0, 1, 0x65, 0x22, 0, 0,
+        ];
+        let units: Vec<_> = nal::annex_b_units(&stream).collect();
+        assert_eq!(units, [&[0x09, 0xf0][..], &[0x65, 0x11], &[0x65, 0x22]]);
+        assert_eq!(nal::annex_b_units(&[]).count(), 0);
+        assert_eq!(nal::annex_b_units(&[0, 0, 1, 0, 0, 1]).count(), 0);
+    }
+
+    #[test]
+    fn escape() {
+        for unit in [
+            &[0x65, 0, 0, 0][..],
+            &[0x65, 0, 0, 2, 0, 0],
+            &[0x65, 0, 0, 1, 0, 0, 0, 1],
+        ] {
+            let escaped = nal::escape_start_codes(unit);
+            let framed: Vec<u8> = [&[0, 0, 1][..], &escaped, &[0, 0, 1, 0x41]].concat();
+            let units: Vec<_> = nal::annex_b_units(&framed).collect();
+            assert_eq!(units, [&escaped[..], &[0x41]]);
+            assert_eq!(&crate::cm::param_sets::rbsp(&escaped)[..unit.len()], unit);
+        }
+    }
+
+    #[test]
+    fn codec() {
+        assert!(Codec::H264.is_keyframe(&[0x65]));
+        assert!(!Codec::H264.is_keyframe(&[0x41]));
+        assert!(Codec::H264.is_param_set(&[0x67]));
+        assert!(Codec::Hevc.is_param_set(&[0x40, 0x01]));
+        assert!(Codec::Hevc.is_keyframe(&[0x26, 0x01]));
+        assert!(Codec::Hevc.is_keyframe(&[0x2a, 0x01]));
+        assert!(!Codec::Hevc.is_keyframe(&[0x02, 0x01]));
+        assert_eq!(Codec::Hevc.nal_type(&[]), None);
+    }
+}
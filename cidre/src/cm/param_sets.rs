@@ -0,0 +1,458 @@
+//! H.264 and HEVC parameter set decoding.
+//!
+//! Reads the fields of SPS, PPS and VPS NAL units needed to describe a
+//! stream (profile, level, chroma format, bit depth, cropped dimensions,
+//! VUI color and timing, reorder depth) without going through CoreMedia.
+//! Parsing stops once those are known, so trailing syntax isn't validated.
+
+use std::borrow::Cow;
+
+#[cfg(all(target_vendor = "apple", feature = "cv"))]
+use crate::{cf, cm, cv};
+
+pub mod h264;
+pub mod hevc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Bitstream ends before the syntax does.
+    Truncated,
+
+    /// NAL unit is not of the expected type.
+    NalType(u8),
+
+    /// A syntax element has a value the spec doesn't allow.
+    Invalid(&'static str),
+
+    /// Valid syntax this decoder doesn't handle.
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Truncated => f.write_str("parameter set is truncated"),
+            Self::NalType(t) => write!(f, "unexpected NAL unit type {t}"),
+            Self::Invalid(what) => write!(f, "invalid {what}"),
+            Self::Unsupported(what) => write!(f, "unsupported {what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Removes emulation prevention bytes (`00 00 03` → `00 00`), borrowing
+/// when there are none.
+///
+/// ```
+/// use cidre::cm::param_sets;
+///
+/// assert_eq!(&param_sets::rbsp(&[0, 0, 3, 1, 0, 0, 3])[..], [0, 0, 1, 0, 0]);
+/// assert!(matches!(param_sets::rbsp(&[1, 2, 3]), std::borrow::Cow::Borrowed(_)));
+/// ```
+pub fn rbsp(ebsp: &[u8]) -> Cow<'_, [u8]> {
+    let mut zeros = 0;
+    let Some(first) = ebsp.iter().position(|b| {
+        let found = zeros >= 2 && *b == 3;
+        zeros = if *b == 0 { zeros + 1 } else { 0 };
+        found
+    }) else {
+        return Cow::Borrowed(ebsp);
+    };
+    let mut res = Vec::with_capacity(ebsp.len());
+    res.extend_from_slice(&ebsp[..first]);
+    zeros = 0;
+    for b in &ebsp[first + 1..] {
+        if zeros >= 2 && *b == 3 {
+            zeros = 0;
+            continue;
+        }
+        zeros = if *b == 0 { zeros + 1 } else { 0 };
+        res.push(*b);
+    }
+    Cow::Owned(res)
+}
+
+/// MSB-first bit reader over RBSP bytes with exp-Golomb codes.
+///
+/// ```
+/// use cidre::cm::param_sets::BitReader;
+///
+/// // 1 | 010 | 011 | 00100 | 00101
+/// let mut r = BitReader::new(&[0b1010_0110, 0b0100_0010, 0b1000_0000]);
+/// assert_eq!(r.ue().unwrap(), 0);
+/// assert_eq!(r.ue().unwrap(), 1);
+/// assert_eq!(r.se().unwrap(), -1);
+/// assert_eq!(r.ue().unwrap(), 3);
+/// assert_eq!(r.se().unwrap(), -2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Bits read so far.
+    #[inline]
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    #[inline]
+    pub fn bits_left(&self) -> usize {
+        self.data.len() * 8 - self.pos
+    }
+
+    pub fn flag(&mut self) -> Result<bool, Error> {
+        let byte = *self.data.get(self.pos / 8).ok_or(Error::Truncated)?;
+        let bit = byte >> (7 - self.pos % 8) & 1;
+        self.pos += 1;
+        Ok(bit != 0)
+    }
+
+    /// `u(n)` for `n` up to 32.
+    pub fn bits(&mut self, n: u32) -> Result<u32, Error> {
+        debug_assert!(n <= 32);
+        if self.bits_left() < n as usize {
+            return Err(Error::Truncated);
+        }
+        let mut res = 0u64;
+        for _ in 0..n {
+            res = res << 1 | self.flag()? as u64;
+        }
+        Ok(res as u32)
+    }
+
+    pub fn skip(&mut self, n: usize) -> Result<(), Error> {
+        if self.bits_left() < n {
+            return Err(Error::Truncated);
+        }
+        self.pos += n;
+        Ok(())
+    }
+
+    /// Unsigned exp-Golomb, `ue(v)`.
+    pub fn ue(&mut self) -> Result<u32, Error> {
+        let mut zeros = 0;
+        while !self.flag()? {
+            zeros += 1;
+            if zeros > 31 {
+                return Err(Error::Invalid("exp-Golomb code"));
+            }
+        }
+        Ok(((1u64 << zeros) - 1 + self.bits(zeros)? as u64) as u32)
+    }
+
+    /// Signed exp-Golomb, `se(v)`.
+    pub fn se(&mut self) -> Result<i32, Error> {
+        let k = self.ue()? as i64;
+        Ok(if k & 1 == 1 { (k + 1) / 2 } else { -(k / 2) } as i32)
+    }
+
+    /// `ue(v)` that must not exceed `max`.
+    fn ue_max(&mut self, max: u32, what: &'static str) -> Result<u32, Error> {
+        match self.ue()? {
+            v if v <= max => Ok(v),
+            _ => Err(Error::Invalid(what)),
+        }
+    }
+
+    /// `more_rbsp_data()`: anything besides the stop bit and trailing zeros.
+    pub fn more_rbsp_data(&self) -> bool {
+        let Some(last) = self.data.iter().rposition(|b| *b != 0) else {
+            return false;
+        };
+        let stop = last * 8 + 7 - self.data[last].trailing_zeros() as usize;
+        self.pos < stop
+    }
+}
+
+/// `colour_primaries`, `transfer_characteristics` and `matrix_coeffs` code
+/// points of ITU-T H.273.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ColorDesc {
+    pub primaries: u8,
+    pub transfer: u8,
+    pub matrix: u8,
+}
+
+impl Default for ColorDesc {
+    /// All unspecified.
+    fn default() -> Self {
+        Self {
+            primaries: 2,
+            transfer: 2,
+            matrix: 2,
+        }
+    }
+}
+
+#[cfg(all(target_vendor = "apple", feature = "cv"))]
+impl ColorDesc {
+    /// Value for [`cm::FormatDescExtKey::color_primaries`].
+    pub fn primaries_value(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::color_primaries as p;
+        Some(match self.primaries {
+            1 => p::itu_r_709_2(),
+            5 => p::ebu_3213(),
+            6 | 7 => p::smpte_c(),
+            9 => p::itu_r_2020(),
+            11 => p::dci_p3(),
+            12 => p::p3_d65(),
+            22 => p::p22(),
+            _ => return None,
+        })
+    }
+
+    /// Value for [`cm::FormatDescExtKey::transfer_fn`].
+    pub fn transfer_value(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::transfer_fn as t;
+        Some(match self.transfer {
+            1 | 6 => t::itu_r_709_2(),
+            7 => t::smpte_240m_1995(),
+            8 => t::linear(),
+            13 => t::srgb(),
+            14 | 15 => t::itu_r_2020(),
+            16 => t::smpte_st_2084_pq(),
+            17 => t::smpte_st_428_1(),
+            18 => t::itu_r_2100_hlg(),
+            _ => return None,
+        })
+    }
+
+    /// Value for [`cm::FormatDescExtKey::ycbcr_matrix`].
+    pub fn matrix_value(&self) -> Option<&'static cf::String> {
+        use cv::image_buf_attachment::ycbcr_matrix as m;
+        Some(match self.matrix {
+            1 => m::itu_r_709_2(),
+            5 | 6 => m::itu_r_601_4(),
+            7 => m::smpte_240m_1995(),
+            9 => m::itu_r_2020(),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub num_units_in_tick: u32,
+    pub time_scale: u32,
+
+    /// `fixed_frame_rate_flag` for H.264, `vui_poc_proportional_to_timing_flag` for HEVC.
+    pub fixed: bool,
+}
+
+/// The parts of the VUI H.264 and HEVC share.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Vui {
+    /// Sample aspect ratio as `(width, height)`.
+    pub sar: Option<(u16, u16)>,
+    pub video_format: Option<u8>,
+    pub full_range: bool,
+    pub color: Option<ColorDesc>,
+    pub chroma_loc: Option<(u32, u32)>,
+    pub timing: Option<Timing>,
+}
+
+impl Vui {
+    /// Fields up to and including `chroma_loc_info`, common to both codecs.
+    fn read_head(r: &mut BitReader) -> Result<Self, Error> {
+        let mut vui = Self::default();
+        if r.flag()? {
+            const SAR: [(u16, u16); 17] = [
+                (0, 0),
+                (1, 1),
+                (12, 11),
+                (10, 11),
+                (16, 11),
+                (40, 33),
+                (24, 11),
+                (20, 11),
+                (32, 11),
+                (80, 33),
+                (18, 11),
+                (15, 11),
+                (64, 33),
+                (160, 99),
+                (4, 3),
+                (3, 2),
+                (2, 1),
+            ];
+            let idc = r.bits(8)? as usize;
+            vui.sar = if idc == 255 {
+                Some((r.bits(16)? as u16, r.bits(16)? as u16))
+            } else {
+                SAR.get(idc).copied().filter(|sar| sar.0 != 0)
+            };
+        }
+        if r.flag()? {
+            // overscan_appropriate_flag
+            r.skip(1)?;
+        }
+        if r.flag()? {
+            vui.video_format = Some(r.bits(3)? as u8);
+            vui.full_range = r.flag()?;
+            if r.flag()? {
+                vui.color = Some(ColorDesc {
+                    primaries: r.bits(8)? as u8,
+                    transfer: r.bits(8)? as u8,
+                    matrix: r.bits(8)? as u8,
+                });
+            }
+        }
+        if r.flag()? {
+            vui.chroma_loc = Some((r.ue()?, r.ue()?));
+        }
+        Ok(vui)
+    }
+
+    fn read_timing(r: &mut BitReader) -> Result<(u32, u32), Error> {
+        let num_units_in_tick = r.bits(32)?;
+        let time_scale = r.bits(32)?;
+        if num_units_in_tick == 0 || time_scale == 0 {
+            return Err(Error::Invalid("VUI timing"));
+        }
+        Ok((num_units_in_tick, time_scale))
+    }
+
+    /// `(key, value)` pairs of the format description extensions the VUI
+    /// determines, for building the dictionary passed to
+    /// [`cm::VideoFormatDesc::with_hevc_param_sets`] or VideoToolbox.
+    #[cfg(all(target_vendor = "apple", feature = "cv"))]
+    pub fn format_desc_exts(&self) -> Vec<(&'static cm::FormatDescExtKey, &'static cf::Type)> {
+        let mut res = Vec::with_capacity(4);
+        if let Some(color) = self.color {
+            if let Some(v) = color.primaries_value() {
+                res.push((cm::FormatDescExtKey::color_primaries(), v.as_type_ref()));
+            }
+            if let Some(v) = color.transfer_value() {
+                res.push((cm::FormatDescExtKey::transfer_fn(), v.as_type_ref()));
+            }
+            if let Some(v) = color.matrix_value() {
+                res.push((cm::FormatDescExtKey::ycbcr_matrix(), v.as_type_ref()));
+            }
+        }
+        if self.full_range {
+            res.push((
+                cm::FormatDescExtKey::full_range_video(),
+                cf::Boolean::value_true().as_type_ref(),
+            ));
+        }
+        res
+    }
+}
+
+/// Width and height after applying a conformance/cropping window given in
+/// chroma sample units.
+fn cropped(
+    width: u32,
+    height: u32,
+    unit: (u32, u32),
+    [left, right, top, bottom]: [u32; 4],
+) -> Result<(u32, u32), Error> {
+    let dx = unit.0 as u64 * (left as u64 + right as u64);
+    let dy = unit.1 as u64 * (top as u64 + bottom as u64);
+    if dx >= width as u64 || dy >= height as u64 {
+        return Err(Error::Invalid("cropping window"));
+    }
+    Ok((width - dx as u32, height - dy as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cm::param_sets::{rbsp, BitReader};
+
+    /// MSB-first writer for building test bitstreams.
+    #[derive(Default)]
+    pub(crate) struct BitWriter {
+        bytes: Vec<u8>,
+        len: usize,
+    }
+
+    impl BitWriter {
+        pub fn flag(&mut self, v: bool) -> &mut Self {
+            if self.len & 7 == 0 {
+                self.bytes.push(0);
+            }
+            if v {
+                *self.bytes.last_mut().unwrap() |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+            self
+        }
+
+        pub fn bits(&mut self, n: u32, v: u64) -> &mut Self {
+            for i in (0..n).rev() {
+                self.flag(v >> i & 1 != 0);
+            }
+            self
+        }
+
+        pub fn ue(&mut self, v: u32) -> &mut Self {
+            let v = v as u64 + 1;
+            let n = 64 - v.leading_zeros();
+            self.bits(n - 1, 0).bits(n, v)
+        }
+
+        pub fn se(&mut self, v: i32) -> &mut Self {
+            self.ue(if v > 0 {
+                2 * v as u32 - 1
+            } else {
+                2 * v.unsigned_abs()
+            })
+        }
+
+        /// Stop bit and alignment, then emulation prevention.
+        pub fn finish(&mut self, header: &[u8]) -> Vec<u8> {
+            self.flag(true);
+            let mut res = header.to_vec();
+            let mut zeros = 0;
+            for b in &self.bytes {
+                if zeros >= 2 && *b <= 3 {
+                    res.push(3);
+                    zeros = 0;
+                }
+                zeros = if *b == 0 { zeros + 1 } else { 0 };
+                res.push(*b);
+            }
+            res
+        }
+    }
+
+    #[test]
+    fn exp_golomb() {
+        let mut w = BitWriter::default();
+        let values = [0u32, 1, 2, 3, 7, 8, 254, 255, 65535, u32::MAX - 1];
+        for v in values {
+            w.ue(v);
+        }
+        for v in [0i32, 1, -1, 2, -2, 1000, -1000] {
+            w.se(v);
+        }
+        let bytes = w.finish(&[]);
+        let data = rbsp(&bytes);
+        let mut r = BitReader::new(&data);
+        for v in values {
+            assert_eq!(r.ue().unwrap(), v);
+        }
+        for v in [0i32, 1, -1, 2, -2, 1000, -1000] {
+            assert_eq!(r.se().unwrap(), v);
+        }
+        assert!(!r.more_rbsp_data());
+        assert!(r.bits(8).is_err());
+    }
+
+    #[test]
+    fn emulation_prevention() {
+        let mut w = BitWriter::default();
+        w.bits(32, 0).bits(8, 1).bits(16, 0).bits(8, 3);
+        let ebsp = w.finish(&[]);
+        assert_eq!(ebsp, [0, 0, 3, 0, 0, 3, 1, 0, 0, 3, 3, 0x80]);
+        assert_eq!(&rbsp(&ebsp)[..], [0, 0, 0, 0, 1, 0, 0, 3, 0x80]);
+    }
+}
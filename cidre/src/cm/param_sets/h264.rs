@@ -0,0 +1,520 @@
+//! H.264 sequence and picture parameter sets (ITU-T H.264 7.3.2.1 and 7.3.2.2).
+
+use crate::cm::param_sets::{cropped, rbsp, BitReader, Error, Timing, Vui};
+
+pub const NAL_SPS: u8 = 7;
+pub const NAL_PPS: u8 = 8;
+
+/// Strips the one byte NAL unit header after checking its type.
+fn payload(nal: &[u8], nal_type: u8) -> Result<&[u8], Error> {
+    let (header, rest) = nal.split_first().ok_or(Error::Truncated)?;
+    match header & 0x1f {
+        t if t == nal_type => Ok(rest),
+        t => Err(Error::NalType(t)),
+    }
+}
+
+fn scaling_list(r: &mut BitReader, size: usize) -> Result<(), Error> {
+    let mut last = 8i32;
+    let mut next = 8i32;
+    for _ in 0..size {
+        if next != 0 {
+            next = (last + r.se()? + 256) % 256;
+        }
+        if next != 0 {
+            last = next;
+        }
+    }
+    Ok(())
+}
+
+fn skip_hrd(r: &mut BitReader) -> Result<(), Error> {
+    let cpb_cnt = r.ue_max(31, "cpb_cnt_minus1")? + 1;
+    // bit_rate_scale, cpb_size_scale
+    r.skip(8)?;
+    for _ in 0..cpb_cnt {
+        r.ue()?;
+        r.ue()?;
+        r.skip(1)?;
+    }
+    // initial_cpb_removal_delay_length_minus1 .. time_offset_length
+    r.skip(20)
+}
+
+/// Largest DPB size in macroblocks for `level_idc` (Table A-1).
+fn max_dpb_mbs(level_idc: u8, constraint_set3: bool) -> Option<u32> {
+    Some(match level_idc {
+        9 | 10 => 396,
+        11 if constraint_set3 => 396,
+        11 => 900,
+        12 | 13 | 20 => 2376,
+        21 => 4752,
+        22 | 30 => 8100,
+        31 => 18000,
+        32 => 20480,
+        40 | 41 => 32768,
+        42 => 34816,
+        50 => 110400,
+        51 | 52 => 184320,
+        60..=62 => 696320,
+        _ => return None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sps {
+    pub profile_idc: u8,
+
+    /// `constraint_set0_flag` in the high bit through `constraint_set5_flag`.
+    pub constraint_flags: u8,
+    pub level_idc: u8,
+    pub id: u8,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub log2_max_frame_num: u8,
+    pub pic_order_cnt_type: u8,
+    pub max_num_ref_frames: u32,
+    pub frame_mbs_only: bool,
+
+    /// Size in whole macroblocks.
+    pub coded_width: u32,
+    pub coded_height: u32,
+
+    /// Size after the frame cropping rectangle.
+    pub width: u32,
+    pub height: u32,
+    pub vui: Option<Vui>,
+
+    /// `max_num_reorder_frames` and `max_dec_frame_buffering` from the VUI
+    /// bitstream restriction.
+    pub bitstream_restriction: Option<(u32, u32)>,
+}
+
+impl Sps {
+    /// Parses a complete SPS NAL unit, header byte included.
+    ///
+    /// ```
+    /// use cidre::cm::param_sets::h264;
+    ///
+    /// // Baseline 3.0, 320x240, no VUI
+    /// let sps = h264::Sps::parse(&[0x67, 0x42, 0x00, 0x1e, 0xf4, 0x0a, 0x0f, 0xc8]).unwrap();
+    /// assert_eq!(sps.profile_idc, 66);
+    /// assert_eq!((sps.width, sps.height), (320, 240));
+    /// assert_eq!(sps.reorder_depth(), 0);
+    /// assert_eq!(sps.fps(), None);
+    /// ```
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let data = rbsp(payload(nal, NAL_SPS)?);
+        let mut r = BitReader::new(&data);
+
+        let profile_idc = r.bits(8)? as u8;
+        let constraint_flags = r.bits(8)? as u8;
+        let level_idc = r.bits(8)? as u8;
+        let id = r.ue_max(31, "seq_parameter_set_id")? as u8;
+
+        let mut chroma_format_idc = 1;
+        let mut separate_colour_plane = false;
+        let mut bit_depth_luma = 8;
+        let mut bit_depth_chroma = 8;
+        if matches!(
+            profile_idc,
+            100 | 110 | 122 | 244 | 44 | 83 | 86 | 118 | 128 | 138 | 139 | 134 | 135
+        ) {
+            chroma_format_idc = r.ue_max(3, "chroma_format_idc")? as u8;
+            if chroma_format_idc == 3 {
+                separate_colour_plane = r.flag()?;
+            }
+            bit_depth_luma = r.ue_max(6, "bit_depth_luma_minus8")? as u8 + 8;
+            bit_depth_chroma = r.ue_max(6, "bit_depth_chroma_minus8")? as u8 + 8;
+            // qpprime_y_zero_transform_bypass_flag
+            r.skip(1)?;
+            if r.flag()? {
+                let lists = if chroma_format_idc == 3 { 12 } else { 8 };
+                for i in 0..lists {
+                    if r.flag()? {
+                        scaling_list(&mut r, if i < 6 { 16 } else { 64 })?;
+                    }
+                }
+            }
+        }
+
+        let log2_max_frame_num = r.ue_max(12, "log2_max_frame_num_minus4")? as u8 + 4;
+        let pic_order_cnt_type = r.ue_max(2, "pic_order_cnt_type")? as u8;
+        match pic_order_cnt_type {
+            0 => {
+                r.ue_max(12, "log2_max_pic_order_cnt_lsb_minus4")?;
+            }
+            1 => {
+                // delta_pic_order_always_zero_flag
+                r.skip(1)?;
+                r.se()?;
+                r.se()?;
+                for _ in 0..r.ue_max(255, "num_ref_frames_in_pic_order_cnt_cycle")? {
+                    r.se()?;
+                }
+            }
+            _ => {}
+        }
+
+        let max_num_ref_frames = r.ue()?;
+        // gaps_in_frame_num_value_allowed_flag
+        r.skip(1)?;
+        let width_in_mbs = r.ue()? as u64 + 1;
+        let height_in_map_units = r.ue()? as u64 + 1;
+        let frame_mbs_only = r.flag()?;
+        if !frame_mbs_only {
+            // mb_adaptive_frame_field_flag
+            r.skip(1)?;
+        }
+        // direct_8x8_inference_flag
+        r.skip(1)?;
+
+        let coded_width = width_in_mbs * 16;
+        let coded_height = (2 - frame_mbs_only as u64) * height_in_map_units * 16;
+        if coded_width > u32::MAX as u64 || coded_height > u32::MAX as u64 {
+            return Err(Error::Invalid("picture size"));
+        }
+        let (coded_width, coded_height) = (coded_width as u32, coded_height as u32);
+
+        let (mut width, mut height) = (coded_width, coded_height);
+        if r.flag()? {
+            let crop = [r.ue()?, r.ue()?, r.ue()?, r.ue()?];
+            let (sub_width, sub_height) = match (separate_colour_plane, chroma_format_idc) {
+                (true, _) | (_, 0) => (1, 1),
+                (_, 1) => (2, 2),
+                (_, 2) => (2, 1),
+                _ => (1, 1),
+            };
+            let unit = (sub_width, (2 - frame_mbs_only as u32) * sub_height);
+            (width, height) = cropped(coded_width, coded_height, unit, crop)?;
+        }
+
+        let mut vui = None;
+        let mut bitstream_restriction = None;
+        if r.flag()? {
+            let mut v = Vui::read_head(&mut r)?;
+            if r.flag()? {
+                let (num_units_in_tick, time_scale) = Vui::read_timing(&mut r)?;
+                v.timing = Some(Timing {
+                    num_units_in_tick,
+                    time_scale,
+                    fixed: r.flag()?,
+                });
+            }
+            let nal_hrd = r.flag()?;
+            if nal_hrd {
+                skip_hrd(&mut r)?;
+            }
+            let vcl_hrd = r.flag()?;
+            if vcl_hrd {
+                skip_hrd(&mut r)?;
+            }
+            if nal_hrd || vcl_hrd {
+                // low_delay_hrd_flag
+                r.skip(1)?;
+            }
+            // pic_struct_present_flag
+            r.skip(1)?;
+            if r.flag()? {
+                // motion_vectors_over_pic_boundaries_flag
+                r.skip(1)?;
+                for _ in 0..4 {
+                    r.ue()?;
+                }
+                bitstream_restriction = Some((r.ue()?, r.ue()?));
+            }
+            vui = Some(v);
+        }
+
+        Ok(Self {
+            profile_idc,
+            constraint_flags,
+            level_idc,
+            id,
+            chroma_format_idc,
+            separate_colour_plane,
+            bit_depth_luma,
+            bit_depth_chroma,
+            log2_max_frame_num,
+            pic_order_cnt_type,
+            max_num_ref_frames,
+            frame_mbs_only,
+            coded_width,
+            coded_height,
+            width,
+            height,
+            vui,
+            bitstream_restriction,
+        })
+    }
+
+    #[inline]
+    pub fn constraint_set(&self, i: u8) -> bool {
+        i < 8 && self.constraint_flags & (0x80 >> i) != 0
+    }
+
+    /// Frames per second from the VUI timing info.
+    ///
+    /// H.264 ticks are fields, so a frame is two of them.
+    pub fn fps(&self) -> Option<f64> {
+        let t = self.vui?.timing?;
+        Some(t.time_scale as f64 / (2.0 * t.num_units_in_tick as f64))
+    }
+
+    /// How many frames a decoder may have to hold back before output.
+    ///
+    /// Uses `max_num_reorder_frames` when the stream signals it, otherwise
+    /// the inference of H.264 E.2.1: zero for profiles without B slices and
+    /// for intra profiles, `MaxDpbFrames` for the rest.
+    pub fn reorder_depth(&self) -> u32 {
+        if let Some((reorder, _)) = self.bitstream_restriction {
+            return reorder;
+        }
+        let intra = self.constraint_set(3) && matches!(self.profile_idc, 100 | 110 | 122 | 244);
+        if self.profile_idc == 66 || self.profile_idc == 44 || intra {
+            return 0;
+        }
+        let frame_mbs = (self.coded_width / 16) * (self.coded_height / 16);
+        match max_dpb_mbs(self.level_idc, self.constraint_set(3)) {
+            Some(mbs) if frame_mbs > 0 => (mbs / frame_mbs).min(16),
+            _ => 16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pps {
+    pub id: u8,
+    pub sps_id: u8,
+
+    /// CABAC rather than CAVLC.
+    pub entropy_coding_mode: bool,
+    pub bottom_field_pic_order_in_frame_present: bool,
+    pub num_slice_groups: u32,
+    pub num_ref_idx_default_active: (u32, u32),
+    pub weighted_pred: bool,
+    pub weighted_bipred_idc: u8,
+    pub pic_init_qp: i32,
+    pub pic_init_qs: i32,
+    pub chroma_qp_index_offset: i32,
+    pub deblocking_filter_control_present: bool,
+    pub constrained_intra_pred: bool,
+    pub redundant_pic_cnt_present: bool,
+    pub transform_8x8_mode: bool,
+}
+
+impl Pps {
+    /// Parses a complete PPS NAL unit, header byte included.
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let data = rbsp(payload(nal, NAL_PPS)?);
+        let mut r = BitReader::new(&data);
+
+        let id = r.ue_max(255, "pic_parameter_set_id")? as u8;
+        let sps_id = r.ue_max(31, "seq_parameter_set_id")? as u8;
+        let entropy_coding_mode = r.flag()?;
+        let bottom_field_pic_order_in_frame_present = r.flag()?;
+        let num_slice_groups = r.ue_max(7, "num_slice_groups_minus1")? + 1;
+        if num_slice_groups > 1 {
+            match r.ue_max(6, "slice_group_map_type")? {
+                0 => {
+                    for _ in 0..num_slice_groups {
+                        r.ue()?;
+                    }
+                }
+                2 => {
+                    for _ in 1..num_slice_groups {
+                        r.ue()?;
+                        r.ue()?;
+                    }
+                }
+                3..=5 => {
+                    r.skip(1)?;
+                    r.ue()?;
+                }
+                6 => {
+                    let bits = 32 - (num_slice_groups - 1).leading_zeros();
+                    let units = r.ue()? as usize + 1;
+                    r.skip(units * bits as usize)?;
+                }
+                _ => {}
+            }
+        }
+        let num_ref_idx_default_active = (
+            r.ue_max(31, "num_ref_idx_l0_default_active_minus1")? + 1,
+            r.ue_max(31, "num_ref_idx_l1_default_active_minus1")? + 1,
+        );
+        let weighted_pred = r.flag()?;
+        let weighted_bipred_idc = r.bits(2)? as u8;
+        let pic_init_qp = r.se()? + 26;
+        let pic_init_qs = r.se()? + 26;
+        let chroma_qp_index_offset = r.se()?;
+        let deblocking_filter_control_present = r.flag()?;
+        let constrained_intra_pred = r.flag()?;
+        let redundant_pic_cnt_present = r.flag()?;
+        let transform_8x8_mode = r.more_rbsp_data() && r.flag()?;
+
+        Ok(Self {
+            id,
+            sps_id,
+            entropy_coding_mode,
+            bottom_field_pic_order_in_frame_present,
+            num_slice_groups,
+            num_ref_idx_default_active,
+            weighted_pred,
+            weighted_bipred_idc,
+            pic_init_qp,
+            pic_init_qs,
+            chroma_qp_index_offset,
+            deblocking_filter_control_present,
+            constrained_intra_pred,
+            redundant_pic_cnt_present,
+            transform_8x8_mode,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cm::param_sets::{h264, tests::BitWriter, ColorDesc, Error, Timing};
+
+    /// High 4.0, 1920x1088 cropped to 1080, BT.2020 PQ full range, 60000/1001 fields.
+    fn high_sps() -> Vec<u8> {
+        let mut w = BitWriter::default();
+        w.bits(8, 100).bits(8, 0).bits(8, 40).ue(0);
+        // 4:2:0, 10 bit, scaling matrix with one flat 4x4 list
+        w.ue(1).ue(2).ue(2).flag(false).flag(true);
+        w.flag(true);
+        for _ in 0..16 {
+            w.se(0);
+        }
+        for _ in 1..8 {
+            w.flag(false);
+        }
+        // log2_max_frame_num, poc type 0, lsb
+        w.ue(0).ue(0).ue(2);
+        w.ue(4).flag(false).ue(119).ue(67).flag(true).flag(true);
+        // crop 4 luma rows at the bottom
+        w.flag(true).ue(0).ue(0).ue(0).ue(4);
+        // vui
+        w.flag(true);
+        w.flag(true).bits(8, 1);
+        w.flag(false);
+        w.flag(true).bits(3, 5).flag(true).flag(true);
+        w.bits(8, 9).bits(8, 16).bits(8, 9);
+        w.flag(false);
+        w.flag(true).bits(32, 1001).bits(32, 120000).flag(true);
+        // nal hrd with one cpb, no vcl hrd
+        w.flag(true).ue(0).bits(4, 0).bits(4, 0);
+        w.ue(1000).ue(2000).flag(false).bits(20, 0);
+        w.flag(false).flag(false).flag(false);
+        w.flag(true)
+            .flag(true)
+            .ue(2)
+            .ue(1)
+            .ue(16)
+            .ue(16)
+            .ue(2)
+            .ue(4);
+        w.finish(&[0x67])
+    }
+
+    #[test]
+    fn sps_high() {
+        let sps = h264::Sps::parse(&high_sps()).unwrap();
+        assert_eq!(sps.profile_idc, 100);
+        assert_eq!(sps.level_idc, 40);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (10, 10));
+        assert_eq!(sps.max_num_ref_frames, 4);
+        assert_eq!((sps.coded_width, sps.coded_height), (1920, 1088));
+        assert_eq!((sps.width, sps.height), (1920, 1080));
+
+        let vui = sps.vui.unwrap();
+        assert_eq!(vui.sar, Some((1, 1)));
+        assert!(vui.full_range);
+        assert_eq!(
+            vui.color,
+            Some(ColorDesc {
+                primaries: 9,
+                transfer: 16,
+                matrix: 9
+            })
+        );
+        assert_eq!(
+            vui.timing,
+            Some(Timing {
+                num_units_in_tick: 1001,
+                time_scale: 120000,
+                fixed: true
+            })
+        );
+        assert!((sps.fps().unwrap() - 59.94).abs() < 0.001);
+        assert_eq!(sps.bitstream_restriction, Some((2, 4)));
+        assert_eq!(sps.reorder_depth(), 2);
+    }
+
+    #[test]
+    fn sps_inferred_reorder() {
+        // Main 3.1, 1280x720, interlaced with field cropping, no VUI
+        let mut w = BitWriter::default();
+        w.bits(8, 77).bits(8, 0).bits(8, 31).ue(1);
+        w.ue(0)
+            .ue(2)
+            .ue(2)
+            .flag(false)
+            .ue(79)
+            .ue(22)
+            .flag(false)
+            .flag(false)
+            .flag(true);
+        w.flag(true).ue(0).ue(0).ue(0).ue(4).flag(false);
+        let sps = h264::Sps::parse(&w.finish(&[0x67])).unwrap();
+        assert_eq!(sps.id, 1);
+        assert_eq!((sps.coded_width, sps.coded_height), (1280, 736));
+        assert_eq!((sps.width, sps.height), (1280, 720));
+        assert!(sps.vui.is_none());
+        assert_eq!(sps.reorder_depth(), 18000 / (80 * 46));
+    }
+
+    #[test]
+    fn sps_errors() {
+        assert_eq!(h264::Sps::parse(&[]), Err(Error::Truncated));
+        assert_eq!(h264::Sps::parse(&[0x68, 0xce]), Err(Error::NalType(8)));
+        let sps = high_sps();
+        assert_eq!(h264::Sps::parse(&sps[..12]), Err(Error::Truncated));
+    }
+
+    #[test]
+    fn pps() {
+        let mut w = BitWriter::default();
+        w.ue(3).ue(1).flag(true).flag(false).ue(0).ue(2).ue(0);
+        w.flag(true).bits(2, 2).se(-3).se(0).se(-2);
+        w.flag(true)
+            .flag(false)
+            .flag(false)
+            .flag(true)
+            .flag(false)
+            .se(-2);
+        let pps = h264::Pps::parse(&w.finish(&[0x68])).unwrap();
+        assert_eq!((pps.id, pps.sps_id), (3, 1));
+        assert!(pps.entropy_coding_mode);
+        assert_eq!(pps.num_ref_idx_default_active, (3, 1));
+        assert!(pps.weighted_pred);
+        assert_eq!(pps.weighted_bipred_idc, 2);
+        assert_eq!(pps.pic_init_qp, 23);
+        assert_eq!(pps.chroma_qp_index_offset, -2);
+        assert!(pps.deblocking_filter_control_present);
+        assert!(pps.transform_8x8_mode);
+
+        // without the High profile tail
+        let mut w = BitWriter::default();
+        w.ue(0).ue(0).flag(false).flag(false).ue(0).ue(0).ue(0);
+        w.flag(false).bits(2, 0).se(0).se(0).se(0);
+        w.flag(true).flag(false).flag(false);
+        let pps = h264::Pps::parse(&w.finish(&[0x68])).unwrap();
+        assert!(!pps.entropy_coding_mode);
+        assert!(!pps.transform_8x8_mode);
+    }
+}
@@ -0,0 +1,682 @@
+//! HEVC video, sequence and picture parameter sets (ITU-T H.265 7.3.2).
+
+use crate::cm::param_sets::{cropped, rbsp, BitReader, Error, Timing, Vui};
+
+pub const NAL_VPS: u8 = 32;
+pub const NAL_SPS: u8 = 33;
+pub const NAL_PPS: u8 = 34;
+
+/// Strips the two byte NAL unit header after checking its type.
+fn payload(nal: &[u8], nal_type: u8) -> Result<&[u8], Error> {
+    if nal.len() < 2 {
+        return Err(Error::Truncated);
+    }
+    match nal[0] >> 1 & 0x3f {
+        t if t == nal_type => Ok(&nal[2..]),
+        t => Err(Error::NalType(t)),
+    }
+}
+
+/// General profile, tier and level, the same fields as in `hvcC`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProfileTierLevel {
+    pub profile_space: u8,
+    pub tier: bool,
+    pub profile_idc: u8,
+    pub profile_compat_flags: u32,
+
+    /// The 48 bits from `general_progressive_source_flag` on.
+    pub constraint_indicator_flags: u64,
+    pub level_idc: u8,
+}
+
+impl ProfileTierLevel {
+    fn read(r: &mut BitReader, max_sub_layers_minus1: u8) -> Result<Self, Error> {
+        let ptl = Self {
+            profile_space: r.bits(2)? as u8,
+            tier: r.flag()?,
+            profile_idc: r.bits(5)? as u8,
+            profile_compat_flags: r.bits(32)?,
+            constraint_indicator_flags: (r.bits(16)? as u64) << 32 | r.bits(32)? as u64,
+            level_idc: r.bits(8)? as u8,
+        };
+        let mut present = [(false, false); 7];
+        for p in present.iter_mut().take(max_sub_layers_minus1 as usize) {
+            *p = (r.flag()?, r.flag()?);
+        }
+        if max_sub_layers_minus1 > 0 {
+            // reserved_zero_2bits
+            r.skip(2 * (8 - max_sub_layers_minus1 as usize))?;
+        }
+        for (profile, level) in present.into_iter().take(max_sub_layers_minus1 as usize) {
+            if profile {
+                r.skip(88)?;
+            }
+            if level {
+                r.skip(8)?;
+            }
+        }
+        Ok(ptl)
+    }
+}
+
+/// `sps_max_dec_pic_buffering_minus1 + 1`, `sps_max_num_reorder_pics` and
+/// `sps_max_latency_increase_plus1` of the highest sub-layer.
+fn read_sub_layer_ordering(
+    r: &mut BitReader,
+    max_sub_layers_minus1: u8,
+) -> Result<(u32, u32, u32), Error> {
+    let all = r.flag()?;
+    let mut res = (0, 0, 0);
+    for _ in if all { 0 } else { max_sub_layers_minus1 }..=max_sub_layers_minus1 {
+        res = (
+            r.ue_max(15, "max_dec_pic_buffering_minus1")? + 1,
+            r.ue()?,
+            r.ue()?,
+        );
+    }
+    if res.1 >= res.0 {
+        return Err(Error::Invalid("max_num_reorder_pics"));
+    }
+    Ok(res)
+}
+
+fn skip_scaling_list_data(r: &mut BitReader) -> Result<(), Error> {
+    for size_id in 0..4 {
+        for _ in (0..6).step_by(if size_id == 3 { 3 } else { 1 }) {
+            if !r.flag()? {
+                // scaling_list_pred_matrix_id_delta
+                r.ue()?;
+                continue;
+            }
+            let coefs = 64.min(1 << (4 + (size_id << 1)));
+            if size_id > 1 {
+                r.se()?;
+            }
+            for _ in 0..coefs {
+                r.se()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `DeltaPocS0` and `DeltaPocS1` of a short-term reference picture set.
+#[derive(Debug, Clone, Default)]
+struct StRps {
+    s0: Vec<i32>,
+    s1: Vec<i32>,
+}
+
+impl StRps {
+    /// `st_ref_pic_set(idx)` as it appears in the SPS, where prediction
+    /// always refers to the previous set (H.265 7.4.8).
+    fn read(r: &mut BitReader, prev: Option<&Self>) -> Result<Self, Error> {
+        let mut rps = Self::default();
+        let inter = prev.is_some() && r.flag()?;
+        if let Some(rf) = prev.filter(|_| inter) {
+            let sign = r.flag()?;
+            let abs = r.ue_max(1 << 15, "abs_delta_rps_minus1")? as i32 + 1;
+            let delta_rps = if sign { -abs } else { abs };
+            let n = rf.s0.len() + rf.s1.len();
+            let mut use_delta = Vec::with_capacity(n + 1);
+            for _ in 0..=n {
+                let used = r.flag()?;
+                use_delta.push(used || r.flag()?);
+            }
+            let (neg, pos) = (rf.s0.len(), rf.s1.len());
+            for j in (0..pos).rev() {
+                let d = rf.s1[j] + delta_rps;
+                if d < 0 && use_delta[neg + j] {
+                    rps.s0.push(d);
+                }
+            }
+            if delta_rps < 0 && use_delta[n] {
+                rps.s0.push(delta_rps);
+            }
+            for (d, used) in rf.s0.iter().zip(&use_delta[..neg]) {
+                let d = d + delta_rps;
+                if d < 0 && *used {
+                    rps.s0.push(d);
+                }
+            }
+            for (d, used) in rf.s0.iter().zip(&use_delta[..neg]).rev() {
+                let d = d + delta_rps;
+                if d > 0 && *used {
+                    rps.s1.push(d);
+                }
+            }
+            if delta_rps > 0 && use_delta[n] {
+                rps.s1.push(delta_rps);
+            }
+            for j in 0..pos {
+                let d = rf.s1[j] + delta_rps;
+                if d > 0 && use_delta[neg + j] {
+                    rps.s1.push(d);
+                }
+            }
+        } else {
+            let neg = r.ue_max(16, "num_negative_pics")?;
+            let pos = r.ue_max(16, "num_positive_pics")?;
+            let mut poc = 0i32;
+            for _ in 0..neg {
+                poc -= r.ue_max(1 << 15, "delta_poc_s0_minus1")? as i32 + 1;
+                r.skip(1)?;
+                rps.s0.push(poc);
+            }
+            poc = 0;
+            for _ in 0..pos {
+                poc += r.ue_max(1 << 15, "delta_poc_s1_minus1")? as i32 + 1;
+                r.skip(1)?;
+                rps.s1.push(poc);
+            }
+        }
+        if rps.s0.len() + rps.s1.len() > 16 {
+            return Err(Error::Invalid("short-term reference picture set"));
+        }
+        Ok(rps)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Vps {
+    pub id: u8,
+    pub max_layers: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+    pub ptl: ProfileTierLevel,
+    pub max_dec_pic_buffering: u32,
+    pub max_num_reorder_pics: u32,
+    pub timing: Option<Timing>,
+}
+
+impl Vps {
+    /// Parses a complete VPS NAL unit, header included.
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let data = rbsp(payload(nal, NAL_VPS)?);
+        let mut r = BitReader::new(&data);
+
+        let id = r.bits(4)? as u8;
+        // base_layer_internal_flag, base_layer_available_flag
+        r.skip(2)?;
+        let max_layers = r.bits(6)? as u8 + 1;
+        let max_sub_layers_minus1 = r.bits(3)? as u8;
+        if max_sub_layers_minus1 > 6 {
+            return Err(Error::Invalid("vps_max_sub_layers_minus1"));
+        }
+        let temporal_id_nesting = r.flag()?;
+        // vps_reserved_0xffff_16bits
+        r.skip(16)?;
+        let ptl = ProfileTierLevel::read(&mut r, max_sub_layers_minus1)?;
+        let (max_dec_pic_buffering, max_num_reorder_pics, _) =
+            read_sub_layer_ordering(&mut r, max_sub_layers_minus1)?;
+
+        let max_layer_id = r.bits(6)? as usize;
+        let num_layer_sets = r.ue_max(1023, "vps_num_layer_sets_minus1")? as usize + 1;
+        r.skip((num_layer_sets - 1) * (max_layer_id + 1))?;
+
+        let mut timing = None;
+        if r.flag()? {
+            let (num_units_in_tick, time_scale) = Vui::read_timing(&mut r)?;
+            timing = Some(Timing {
+                num_units_in_tick,
+                time_scale,
+                fixed: r.flag()?,
+            });
+        }
+
+        Ok(Self {
+            id,
+            max_layers,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            max_dec_pic_buffering,
+            max_num_reorder_pics,
+            timing,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sps {
+    pub vps_id: u8,
+    pub max_sub_layers: u8,
+    pub temporal_id_nesting: bool,
+    pub ptl: ProfileTierLevel,
+    pub id: u8,
+    pub chroma_format_idc: u8,
+    pub separate_colour_plane: bool,
+
+    /// `pic_width_in_luma_samples` and `pic_height_in_luma_samples`.
+    pub coded_width: u32,
+    pub coded_height: u32,
+
+    /// Size after the conformance window.
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth_luma: u8,
+    pub bit_depth_chroma: u8,
+    pub log2_max_pic_order_cnt_lsb: u8,
+    pub max_dec_pic_buffering: u32,
+    pub max_num_reorder_pics: u32,
+    pub max_latency_increase_plus1: u32,
+    pub num_short_term_ref_pic_sets: u8,
+    pub vui: Option<Vui>,
+}
+
+impl Sps {
+    /// Parses a complete SPS NAL unit, header included.
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let data = rbsp(payload(nal, NAL_SPS)?);
+        let mut r = BitReader::new(&data);
+
+        let vps_id = r.bits(4)? as u8;
+        let max_sub_layers_minus1 = r.bits(3)? as u8;
+        if max_sub_layers_minus1 > 6 {
+            return Err(Error::Invalid("sps_max_sub_layers_minus1"));
+        }
+        let temporal_id_nesting = r.flag()?;
+        let ptl = ProfileTierLevel::read(&mut r, max_sub_layers_minus1)?;
+        let id = r.ue_max(15, "sps_seq_parameter_set_id")? as u8;
+        let chroma_format_idc = r.ue_max(3, "chroma_format_idc")? as u8;
+        let separate_colour_plane = chroma_format_idc == 3 && r.flag()?;
+        let coded_width = r.ue()?;
+        let coded_height = r.ue()?;
+        if coded_width == 0 || coded_height == 0 {
+            return Err(Error::Invalid("picture size"));
+        }
+
+        let (mut width, mut height) = (coded_width, coded_height);
+        if r.flag()? {
+            let window = [r.ue()?, r.ue()?, r.ue()?, r.ue()?];
+            let unit = match (separate_colour_plane, chroma_format_idc) {
+                (false, 1) => (2, 2),
+                (false, 2) => (2, 1),
+                _ => (1, 1),
+            };
+            (width, height) = cropped(coded_width, coded_height, unit, window)?;
+        }
+
+        let bit_depth_luma = r.ue_max(8, "bit_depth_luma_minus8")? as u8 + 8;
+        let bit_depth_chroma = r.ue_max(8, "bit_depth_chroma_minus8")? as u8 + 8;
+        let log2_max_pic_order_cnt_lsb =
+            r.ue_max(12, "log2_max_pic_order_cnt_lsb_minus4")? as u8 + 4;
+        let (max_dec_pic_buffering, max_num_reorder_pics, max_latency_increase_plus1) =
+            read_sub_layer_ordering(&mut r, max_sub_layers_minus1)?;
+
+        // log2_min_luma_coding_block_size_minus3 .. max_transform_hierarchy_depth_intra
+        for _ in 0..6 {
+            r.ue()?;
+        }
+        if r.flag()? && r.flag()? {
+            skip_scaling_list_data(&mut r)?;
+        }
+        // amp_enabled_flag, sample_adaptive_offset_enabled_flag
+        r.skip(2)?;
+        if r.flag()? {
+            // pcm_sample_bit_depth_luma_minus1, pcm_sample_bit_depth_chroma_minus1
+            r.skip(8)?;
+            r.ue()?;
+            r.ue()?;
+            // pcm_loop_filter_disabled_flag
+            r.skip(1)?;
+        }
+
+        let num_short_term_ref_pic_sets = r.ue_max(64, "num_short_term_ref_pic_sets")? as u8;
+        let mut prev: Option<StRps> = None;
+        for _ in 0..num_short_term_ref_pic_sets {
+            prev = Some(StRps::read(&mut r, prev.as_ref())?);
+        }
+        if r.flag()? {
+            let num = r.ue_max(32, "num_long_term_ref_pics_sps")? as usize;
+            r.skip(num * (log2_max_pic_order_cnt_lsb as usize + 1))?;
+        }
+        // sps_temporal_mvp_enabled_flag, strong_intra_smoothing_enabled_flag
+        r.skip(2)?;
+
+        let mut vui = None;
+        if r.flag()? {
+            let mut v = Vui::read_head(&mut r)?;
+            // neutral_chroma_indication_flag, field_seq_flag, frame_field_info_present_flag
+            r.skip(3)?;
+            if r.flag()? {
+                // default display window
+                for _ in 0..4 {
+                    r.ue()?;
+                }
+            }
+            if r.flag()? {
+                let (num_units_in_tick, time_scale) = Vui::read_timing(&mut r)?;
+                let fixed = r.flag()?;
+                if fixed {
+                    // vui_num_ticks_poc_diff_one_minus1
+                    r.ue()?;
+                }
+                v.timing = Some(Timing {
+                    num_units_in_tick,
+                    time_scale,
+                    fixed,
+                });
+            }
+            vui = Some(v);
+        }
+
+        Ok(Self {
+            vps_id,
+            max_sub_layers: max_sub_layers_minus1 + 1,
+            temporal_id_nesting,
+            ptl,
+            id,
+            chroma_format_idc,
+            separate_colour_plane,
+            coded_width,
+            coded_height,
+            width,
+            height,
+            bit_depth_luma,
+            bit_depth_chroma,
+            log2_max_pic_order_cnt_lsb,
+            max_dec_pic_buffering,
+            max_num_reorder_pics,
+            max_latency_increase_plus1,
+            num_short_term_ref_pic_sets,
+            vui,
+        })
+    }
+
+    /// Frames per second from the VUI timing info.
+    pub fn fps(&self) -> Option<f64> {
+        let t = self.vui?.timing?;
+        Some(t.time_scale as f64 / t.num_units_in_tick as f64)
+    }
+
+    /// How many frames a decoder may have to hold back before output.
+    #[inline]
+    pub fn reorder_depth(&self) -> u32 {
+        self.max_num_reorder_pics
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pps {
+    pub id: u8,
+    pub sps_id: u8,
+    pub dependent_slice_segments_enabled: bool,
+    pub output_flag_present: bool,
+    pub num_extra_slice_header_bits: u8,
+    pub sign_data_hiding_enabled: bool,
+    pub cabac_init_present: bool,
+    pub num_ref_idx_default_active: (u32, u32),
+    pub init_qp: i32,
+    pub constrained_intra_pred: bool,
+    pub transform_skip_enabled: bool,
+    pub diff_cu_qp_delta_depth: Option<u32>,
+    pub cb_qp_offset: i32,
+    pub cr_qp_offset: i32,
+    pub slice_chroma_qp_offsets_present: bool,
+    pub weighted_pred: bool,
+    pub weighted_bipred: bool,
+    pub transquant_bypass_enabled: bool,
+    pub tiles_enabled: bool,
+    pub entropy_coding_sync_enabled: bool,
+}
+
+impl Pps {
+    /// Parses a complete PPS NAL unit, header included.
+    pub fn parse(nal: &[u8]) -> Result<Self, Error> {
+        let data = rbsp(payload(nal, NAL_PPS)?);
+        let mut r = BitReader::new(&data);
+
+        Ok(Self {
+            id: r.ue_max(63, "pps_pic_parameter_set_id")? as u8,
+            sps_id: r.ue_max(15, "pps_seq_parameter_set_id")? as u8,
+            dependent_slice_segments_enabled: r.flag()?,
+            output_flag_present: r.flag()?,
+            num_extra_slice_header_bits: r.bits(3)? as u8,
+            sign_data_hiding_enabled: r.flag()?,
+            cabac_init_present: r.flag()?,
+            num_ref_idx_default_active: (
+                r.ue_max(14, "num_ref_idx_l0_default_active_minus1")? + 1,
+                r.ue_max(14, "num_ref_idx_l1_default_active_minus1")? + 1,
+            ),
+            init_qp: r.se()? + 26,
+            constrained_intra_pred: r.flag()?,
+            transform_skip_enabled: r.flag()?,
+            diff_cu_qp_delta_depth: if r.flag()? { Some(r.ue()?) } else { None },
+            cb_qp_offset: r.se()?,
+            cr_qp_offset: r.se()?,
+            slice_chroma_qp_offsets_present: r.flag()?,
+            weighted_pred: r.flag()?,
+            weighted_bipred: r.flag()?,
+            transquant_bypass_enabled: r.flag()?,
+            tiles_enabled: r.flag()?,
+            entropy_coding_sync_enabled: r.flag()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cm::param_sets::{hevc, tests::BitWriter, ColorDesc, Error};
+
+    fn ptl(w: &mut BitWriter, max_sub_layers_minus1: u8) {
+        // Main 10, Main tier, level 5.1, progressive + frame only
+        w.bits(2, 0).flag(false).bits(5, 2).bits(32, 0x2000_0000);
+        w.bits(16, 0x9000).bits(32, 0).bits(8, 153);
+        for _ in 0..max_sub_layers_minus1 {
+            w.flag(false).flag(true);
+        }
+        if max_sub_layers_minus1 > 0 {
+            w.bits(2 * (8 - max_sub_layers_minus1 as u32), 0);
+        }
+        for _ in 0..max_sub_layers_minus1 {
+            w.bits(8, 120);
+        }
+    }
+
+    fn check_ptl(ptl: &hevc::ProfileTierLevel) {
+        assert_eq!(ptl.profile_idc, 2);
+        assert!(!ptl.tier);
+        assert_eq!(ptl.profile_compat_flags, 0x2000_0000);
+        assert_eq!(ptl.constraint_indicator_flags, 0x9000_0000_0000);
+        assert_eq!(ptl.level_idc, 153);
+    }
+
+    #[test]
+    fn vps() {
+        let mut w = BitWriter::default();
+        w.bits(4, 0)
+            .flag(true)
+            .flag(true)
+            .bits(6, 0)
+            .bits(3, 1)
+            .flag(false);
+        w.bits(16, 0xffff);
+        ptl(&mut w, 1);
+        w.flag(true).ue(4).ue(2).ue(0).ue(5).ue(3).ue(0);
+        w.bits(6, 0).ue(0);
+        w.flag(true).bits(32, 1).bits(32, 50).flag(false);
+        let vps = hevc::Vps::parse(&w.finish(&[0x40, 0x01])).unwrap();
+        assert_eq!(vps.max_sub_layers, 2);
+        check_ptl(&vps.ptl);
+        assert_eq!(
+            (vps.max_dec_pic_buffering, vps.max_num_reorder_pics),
+            (6, 3)
+        );
+        assert_eq!(vps.timing.unwrap().time_scale, 50);
+    }
+
+    /// Main 10 3840x2176 cropped to 2160, HLG, 50 fps, with inter predicted
+    /// short-term reference picture sets and scaling lists.
+    fn uhd_sps() -> Vec<u8> {
+        let mut w = BitWriter::default();
+        w.bits(4, 0).bits(3, 0).flag(true);
+        ptl(&mut w, 0);
+        w.ue(0).ue(1).ue(3840).ue(2176);
+        w.flag(true).ue(0).ue(0).ue(0).ue(8);
+        w.ue(2).ue(2).ue(4);
+        w.flag(false).ue(4).ue(2).ue(0);
+        w.ue(0).ue(3).ue(0).ue(3).ue(2).ue(2);
+        // explicit scaling lists: predicted for 4x4, 8x8 and the first 16x16
+        w.flag(true).flag(true);
+        for size_id in 0..4 {
+            for matrix_id in (0..6).step_by(if size_id == 3 { 3 } else { 1 }) {
+                if size_id < 2 || (size_id == 2 && matrix_id == 0) {
+                    w.flag(false).ue(0);
+                } else {
+                    w.flag(true);
+                    if size_id > 1 {
+                        w.se(8);
+                    }
+                    for _ in 0..64 {
+                        w.se(0);
+                    }
+                }
+            }
+        }
+        w.flag(false).flag(true).flag(false);
+        // three sets: {-1, -2}, predicted {-1, -2, -3} with deltaRps -1, then {+1} from it
+        w.ue(3);
+        w.ue(2).ue(0).ue(0).flag(true).ue(0).flag(true);
+        w.flag(true).flag(true).ue(0);
+        w.flag(true).flag(true).flag(true);
+        w.flag(true).flag(false).ue(1);
+        w.flag(false).flag(false);
+        w.flag(false).flag(false);
+        w.flag(false).flag(false);
+        w.flag(true);
+        // long-term refs
+        w.flag(true).ue(1).bits(8, 0).flag(true);
+        w.flag(true).flag(true);
+        // vui
+        w.flag(true);
+        w.flag(false).flag(false);
+        w.flag(true).bits(3, 5).flag(false).flag(true);
+        w.bits(8, 9).bits(8, 18).bits(8, 9);
+        w.flag(true).ue(2).ue(2);
+        w.flag(false).flag(false).flag(false);
+        w.flag(false);
+        w.flag(true).bits(32, 1).bits(32, 50).flag(true).ue(0);
+        w.flag(false);
+        w.finish(&[0x42, 0x01])
+    }
+
+    #[test]
+    fn sps() {
+        let sps = hevc::Sps::parse(&uhd_sps()).unwrap();
+        check_ptl(&sps.ptl);
+        assert_eq!(sps.chroma_format_idc, 1);
+        assert_eq!((sps.coded_width, sps.coded_height), (3840, 2176));
+        assert_eq!((sps.width, sps.height), (3840, 2160));
+        assert_eq!((sps.bit_depth_luma, sps.bit_depth_chroma), (10, 10));
+        assert_eq!(sps.log2_max_pic_order_cnt_lsb, 8);
+        assert_eq!(sps.max_dec_pic_buffering, 5);
+        assert_eq!(sps.reorder_depth(), 2);
+        assert_eq!(sps.num_short_term_ref_pic_sets, 3);
+
+        let vui = sps.vui.unwrap();
+        assert!(!vui.full_range);
+        assert_eq!(
+            vui.color,
+            Some(ColorDesc {
+                primaries: 9,
+                transfer: 18,
+                matrix: 9
+            })
+        );
+        assert_eq!(vui.chroma_loc, Some((2, 2)));
+        assert_eq!(sps.fps(), Some(50.0));
+    }
+
+    #[test]
+    fn st_rps_prediction() {
+        let mut w = BitWriter::default();
+        w.ue(2)
+            .ue(1)
+            .ue(0)
+            .flag(true)
+            .ue(1)
+            .flag(true)
+            .ue(0)
+            .flag(true);
+        // deltaRps = +2 over {-1, -3, +1}: keep all plus the ref itself
+        w.flag(true).flag(false).ue(1);
+        for _ in 0..4 {
+            w.flag(true);
+        }
+        let bytes = w.finish(&[]);
+        let mut r = super::BitReader::new(&bytes);
+        let first = super::StRps::read(&mut r, None).unwrap();
+        assert_eq!((&first.s0[..], &first.s1[..]), (&[-1, -3][..], &[1][..]));
+        let second = super::StRps::read(&mut r, Some(&first)).unwrap();
+        // -1 + 2 and 1 + 2 go forward, -3 + 2 stays back, 0 + 2 is the ref
+        assert_eq!(
+            (&second.s0[..], &second.s1[..]),
+            (&[-1][..], &[1, 2, 3][..])
+        );
+    }
+
+    #[test]
+    fn pps() {
+        let mut w = BitWriter::default();
+        w.ue(1)
+            .ue(0)
+            .flag(false)
+            .flag(false)
+            .bits(3, 0)
+            .flag(true)
+            .flag(true);
+        w.ue(0).ue(0).se(4).flag(false).flag(true).flag(true).ue(1);
+        w.se(-1)
+            .se(1)
+            .flag(false)
+            .flag(false)
+            .flag(false)
+            .flag(false)
+            .flag(false)
+            .flag(true);
+        let pps = hevc::Pps::parse(&w.finish(&[0x44, 0x01])).unwrap();
+        assert_eq!(pps.id, 1);
+        assert!(pps.sign_data_hiding_enabled);
+        assert_eq!(pps.init_qp, 30);
+        assert_eq!(pps.diff_cu_qp_delta_depth, Some(1));
+        assert_eq!((pps.cb_qp_offset, pps.cr_qp_offset), (-1, 1));
+        assert!(pps.entropy_coding_sync_enabled);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(hevc::Sps::parse(&[0x42]), Err(Error::Truncated));
+        assert_eq!(
+            hevc::Sps::parse(&[0x40, 0x01, 0x0c]),
+            Err(Error::NalType(32))
+        );
+        let sps = uhd_sps();
+        assert_eq!(hevc::Sps::parse(&sps[..20]), Err(Error::Truncated));
+    }
+
+    /// Parameter sets of the `hvcC` fixture in `cm::codec_config` tests.
+    #[test]
+    fn main_1080p() {
+        let vps = [
+            0x40, 0x01, 0x0c, 0x01, 0xff, 0xff, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00,
+            0x00, 0x03, 0x00, 0x00, 0x03, 0x00, 0x7b, 0xac, 0x09,
+        ];
+        let sps = [
+            0x42, 0x01, 0x01, 0x01, 0x60, 0x00, 0x00, 0x03, 0x00, 0xb0, 0x00, 0x00, 0x03, 0x00,
+            0x00, 0x03, 0x00, 0x7b, 0xa0, 0x03, 0xc0, 0x80, 0x10, 0xe5, 0x8d, 0xae, 0x49, 0x32,
+            0xf4, 0xdc, 0x04, 0x04, 0x04, 0x02,
+        ];
+        let pps = [0x44, 0x01, 0xc1, 0x73, 0xd1, 0x89];
+
+        let vps = hevc::Vps::parse(&vps).unwrap();
+        assert_eq!(vps.ptl.profile_idc, 1);
+        assert_eq!(vps.ptl.level_idc, 123);
+        let sps = hevc::Sps::parse(&sps).unwrap();
+        assert_eq!((sps.width, sps.height), (1920, 1080));
+        assert_eq!((sps.coded_width, sps.coded_height), (1920, 1080));
+        assert_eq!((sps.chroma_format_idc, sps.bit_depth_luma), (1, 8));
+        assert!(hevc::Pps::parse(&pps).is_ok());
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{arc, cf, define_opts};
+use std::{cmp::Ordering, hash::Hash, ops};
+
+use crate::define_opts;
+
+#[cfg(target_vendor = "apple")]
+use crate::{arc, cf};
 
 pub mod range;
 pub use range::Mapping as TimeMapping;
@@ -28,9 +33,13 @@ impl TimeFlags {
         Self(Self::POS_INFINITY.0 | Self::NEG_INFINITY.0 | Self::INDEFINITE.0);
 }
 
+/// The largest timescale used when the result of an operation needs a common timescale.
+#[doc(alias = "kCMTimeMaxTimescale")]
+pub const TIME_MAX_TIMESCALE: TimeScale = 1_000_000_000;
+
 #[doc(alias = "CMTime")]
 #[repr(C)]
-#[derive(Hash, Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug)]
 pub struct Time {
     pub value: TimeValue,
     pub scale: TimeScale,
@@ -38,25 +47,215 @@ pub struct Time {
     pub epoch: TimeEpoch,
 }
 
-#[derive(Copy, Clone, Debug)]
+#[doc(alias = "CMTimeRoundingMethod")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
 pub enum TimeRoundingMethod {
     RoundHalfAwayFromZero = 1,
     RoundTowardZero = 2,
     RoundAwayFromZero = 3,
+    /// Rounds toward zero when converting to a smaller timescale and away from zero
+    /// when converting to a larger one. Never rounds a negative value up to zero.
     QuickTime = 4,
     RoundTowardPositiveInfinity = 5,
     RoundTowardNegativeInfinity = 6,
 }
 
 impl Default for TimeRoundingMethod {
+    #[doc(alias = "kCMTimeRoundingMethod_Default")]
     #[inline]
     fn default() -> Self {
-        Self::RoundAwayFromZero
+        Self::RoundHalfAwayFromZero
+    }
+}
+
+impl TimeRoundingMethod {
+    /// Divides `num` by positive `den` and returns the rounded quotient
+    /// along with whether any rounding happened.
+    fn div(self, num: i128, den: i128, upscaling: bool) -> (i128, bool) {
+        debug_assert!(den > 0);
+        let q = num / den;
+        let r = num % den;
+        if r == 0 {
+            return (q, false);
+        }
+        let away = if num < 0 { q - 1 } else { q + 1 };
+        let res = match self {
+            Self::RoundHalfAwayFromZero => {
+                if r.unsigned_abs() * 2 >= den.unsigned_abs() {
+                    away
+                } else {
+                    q
+                }
+            }
+            Self::RoundTowardZero => q,
+            Self::RoundAwayFromZero => away,
+            Self::QuickTime => {
+                let res = if upscaling { away } else { q };
+                if res == 0 && num < 0 {
+                    -1
+                } else {
+                    res
+                }
+            }
+            Self::RoundTowardPositiveInfinity => q.max(away),
+            Self::RoundTowardNegativeInfinity => q.min(away),
+        };
+        (res, true)
     }
 }
 
+/// Ordering class of a time as defined by `CMTimeCompare`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+enum Class {
+    NegInfinity,
+    Numeric,
+    Indefinite,
+    PosInfinity,
+    Invalid,
+}
+
 impl Time {
+    #[doc(alias = "kCMTimeInvalid")]
+    pub const INVALID: Self = Self::non_numeric(TimeFlags(0));
+
+    #[doc(alias = "kCMTimeIndefinite")]
+    pub const INDEFINITE: Self =
+        Self::non_numeric(TimeFlags(TimeFlags::VALID.0 | TimeFlags::INDEFINITE.0));
+
+    #[doc(alias = "kCMTimePositiveInfinity")]
+    pub const POS_INFINITY: Self =
+        Self::non_numeric(TimeFlags(TimeFlags::VALID.0 | TimeFlags::POS_INFINITY.0));
+
+    #[doc(alias = "kCMTimeNegativeInfinity")]
+    pub const NEG_INFINITY: Self =
+        Self::non_numeric(TimeFlags(TimeFlags::VALID.0 | TimeFlags::NEG_INFINITY.0));
+
+    #[doc(alias = "kCMTimeZero")]
+    pub const ZERO: Self = Self::new(0, 1);
+
+    #[inline]
+    const fn non_numeric(flags: TimeFlags) -> Self {
+        Self {
+            value: 0,
+            scale: 0,
+            flags,
+            epoch: 0,
+        }
+    }
+
+    #[inline]
+    const fn is_rounded(&self) -> bool {
+        (self.flags.0 & TimeFlags::HAS_BEEN_ROUNDED.0) != 0
+    }
+
+    fn class(&self) -> Class {
+        if self.is_invalid() {
+            Class::Invalid
+        } else if self.is_neg_infinity() {
+            Class::NegInfinity
+        } else if self.is_pos_infinity() {
+            Class::PosInfinity
+        } else if self.is_indefinite() {
+            Class::Indefinite
+        } else {
+            Class::Numeric
+        }
+    }
+
+    /// Rounds the exact number of seconds `num / den` into `scale`. If the value overflows,
+    /// `scale` is repeatedly halved. If it still overflows at timescale 1, the result is
+    /// an infinity.
+    fn fit(num: i128, den: i128, mut scale: TimeScale, rounded: bool, epoch: TimeEpoch) -> Self {
+        loop {
+            let (value, r) = TimeRoundingMethod::default().div(num * scale as i128, den, false);
+            if let Ok(value) = TimeValue::try_from(value) {
+                let mut flags = TimeFlags::VALID;
+                if rounded || r {
+                    flags |= TimeFlags::HAS_BEEN_ROUNDED;
+                }
+                return Self {
+                    value,
+                    scale,
+                    flags,
+                    epoch,
+                };
+            }
+            if scale == 1 {
+                return if num < 0 {
+                    Self::NEG_INFINITY
+                } else {
+                    Self::POS_INFINITY
+                };
+            }
+            scale /= 2;
+        }
+    }
+
+    /// Shared implementation of `CMTimeAdd` and `CMTimeSubtract`.
+    fn add_signed(self, rhs: Time, negate_rhs: bool) -> Time {
+        let lc = self.class();
+        let mut rc = rhs.class();
+        if lc == Class::Invalid || rc == Class::Invalid {
+            return Self::INVALID;
+        }
+        if negate_rhs {
+            rc = match rc {
+                Class::PosInfinity => Class::NegInfinity,
+                Class::NegInfinity => Class::PosInfinity,
+                c => c,
+            };
+        }
+        match (lc, rc) {
+            (Class::PosInfinity, Class::NegInfinity) | (Class::NegInfinity, Class::PosInfinity) => {
+                return Self::INVALID
+            }
+            (Class::PosInfinity, _) | (_, Class::PosInfinity) => return Self::POS_INFINITY,
+            (Class::NegInfinity, _) | (_, Class::NegInfinity) => return Self::NEG_INFINITY,
+            (Class::Indefinite, _) | (_, Class::Indefinite) => return Self::INDEFINITE,
+            _ => {}
+        }
+
+        let epoch = if negate_rhs {
+            if rhs.epoch == 0 {
+                self.epoch
+            } else if self.epoch == rhs.epoch {
+                0
+            } else {
+                return Self::INVALID;
+            }
+        } else if rhs.epoch == 0 || self.epoch == rhs.epoch {
+            self.epoch
+        } else if self.epoch == 0 {
+            rhs.epoch
+        } else {
+            return Self::INVALID;
+        };
+
+        if self.scale <= 0 || rhs.scale <= 0 {
+            return Self::INVALID;
+        }
+
+        let rv = if negate_rhs {
+            -(rhs.value as i128)
+        } else {
+            rhs.value as i128
+        };
+        let rounded = self.is_rounded() || rhs.is_rounded();
+
+        if self.scale == rhs.scale {
+            let sum = self.value as i128 + rv;
+            return Self::fit(sum, self.scale as i128, self.scale, rounded, epoch);
+        }
+
+        let ls = self.scale as i128;
+        let rs = rhs.scale as i128;
+        let lcm = ls / gcd(ls, rs) * rs;
+        let sum = self.value as i128 * (lcm / ls) + rv * (lcm / rs);
+        let scale = lcm.min(TIME_MAX_TIMESCALE as i128) as TimeScale;
+        Self::fit(sum, lcm, scale, rounded, epoch)
+    }
+
     /// Returns the absolute value of a Time.
     /// ```
     /// use cidre::cm;
@@ -69,7 +268,11 @@ impl Time {
     #[doc(alias = "CMTimeAbsoluteValue")]
     #[inline]
     pub fn abs(self) -> Time {
-        unsafe { CMTimeAbsoluteValue(self) }
+        match self.class() {
+            Class::NegInfinity => Self::POS_INFINITY,
+            Class::Numeric if self.value < 0 => self.mul_i32(-1),
+            _ => self,
+        }
     }
 
     /// ```
@@ -82,9 +285,11 @@ impl Time {
     /// assert_eq!(t3.scale, 10);
     /// assert_eq!(t3.as_secs(), 300.0);
     /// ```
+    #[doc(alias = "CMTimeAdd")]
+    #[allow(clippy::should_implement_trait)]
     #[inline]
     pub fn add(self, rhs: Time) -> Time {
-        unsafe { CMTimeAdd(self, rhs) }
+        self.add_signed(rhs, false)
     }
 
     /// ```
@@ -93,40 +298,84 @@ impl Time {
     /// let time = cm::Time::default().convert_scale(100, cm::TimeRoundingMethod::default());
     /// assert!(time.is_valid());
     /// assert_eq!(time.scale, 100);
+    ///
+    /// let time = cm::Time::new(1, 3).convert_scale(10, cm::TimeRoundingMethod::RoundTowardZero);
+    /// assert_eq!(time.value, 3);
+    /// assert!(time.flags.contains(cm::TimeFlags::HAS_BEEN_ROUNDED));
     /// ```
-    #[inline]
+    #[doc(alias = "CMTimeConvertScale")]
     pub fn convert_scale(
         self,
         new_time_scale: TimeScale,
         rounding_method: TimeRoundingMethod,
     ) -> Time {
-        unsafe { CMTimeConvertScale(self, new_time_scale, rounding_method) }
+        if self.is_invalid() || new_time_scale <= 0 {
+            return Self::INVALID;
+        }
+        if !self.is_numeric() || self.scale == new_time_scale {
+            return self;
+        }
+        if self.scale <= 0 {
+            return Self::INVALID;
+        }
+        let (value, rounded) = rounding_method.div(
+            self.value as i128 * new_time_scale as i128,
+            self.scale as i128,
+            new_time_scale > self.scale,
+        );
+        let Ok(value) = TimeValue::try_from(value) else {
+            return if value < 0 {
+                Self::NEG_INFINITY
+            } else {
+                Self::POS_INFINITY
+            };
+        };
+        let mut flags = self.flags;
+        if rounded {
+            flags |= TimeFlags::HAS_BEEN_ROUNDED;
+        }
+        Self {
+            value,
+            scale: new_time_scale,
+            flags,
+            epoch: self.epoch,
+        }
     }
 
+    #[cfg(target_vendor = "apple")]
     #[inline]
     pub fn desc_in(self, allocator: Option<&cf::Allocator>) -> Option<arc::R<cf::String>> {
         unsafe { CMTimeCopyDescription(allocator, self) }
     }
 
+    #[cfg(target_vendor = "apple")]
     #[inline]
     pub fn desc(self) -> Option<arc::R<cf::String>> {
         unsafe { CMTimeCopyDescription(None, self) }
     }
 
     /// Converts a Time to seconds.
+    ///
+    /// Invalid and indefinite times are NaN, infinities are `f64` infinities.
+    #[doc(alias = "CMTimeGetSeconds")]
     #[inline]
     pub fn as_secs(self) -> f64 {
-        unsafe { CMTimeGetSeconds(self) }
+        match self.class() {
+            Class::Invalid | Class::Indefinite => f64::NAN,
+            Class::PosInfinity => f64::INFINITY,
+            Class::NegInfinity => f64::NEG_INFINITY,
+            Class::Numeric => self.value as f64 / self.scale as f64,
+        }
     }
 
     #[inline]
-    pub fn indefinit() -> Time {
-        unsafe { kCMTimeIndefinite }
+    pub const fn indefinit() -> Time {
+        Self::INDEFINITE
     }
 
     #[inline]
-    pub fn invalid() -> Time {
-        unsafe { kCMTimeInvalid }
+    pub const fn invalid() -> Time {
+        Self::INVALID
     }
 
     #[inline]
@@ -203,13 +452,124 @@ impl Time {
     #[doc(alias = "CMTimeMultiply")]
     #[inline]
     pub fn mul_i32(self, multiplier: i32) -> Time {
-        unsafe { CMTimeMultiply(self, multiplier) }
+        match self.class() {
+            Class::Numeric if self.scale > 0 => Self::fit(
+                self.value as i128 * multiplier as i128,
+                self.scale as i128,
+                self.scale,
+                self.is_rounded(),
+                self.epoch,
+            ),
+            Class::Numeric | Class::Invalid => Self::INVALID,
+            Class::Indefinite => self,
+            Class::PosInfinity | Class::NegInfinity => {
+                Self::infinity_mul(self.is_pos_infinity(), multiplier.signum())
+            }
+        }
+    }
+
+    fn infinity_mul(pos: bool, signum: i32) -> Time {
+        match (pos, signum) {
+            (_, 0) => Self::INVALID,
+            (true, 1) | (false, -1) => Self::POS_INFINITY,
+            _ => Self::NEG_INFINITY,
+        }
     }
 
     #[doc(alias = "CMTimeMultiplyByFloat64")]
-    #[inline]
     pub fn mul_f64(self, multiplier: f64) -> Time {
-        unsafe { CMTimeMultiplyByFloat64(self, multiplier) }
+        if multiplier.is_nan() {
+            return Self::INVALID;
+        }
+        match self.class() {
+            Class::Numeric if self.scale > 0 => {}
+            Class::Numeric | Class::Invalid => return Self::INVALID,
+            Class::Indefinite => return self,
+            Class::PosInfinity | Class::NegInfinity => {
+                return Self::infinity_mul(self.is_pos_infinity(), multiplier.signum() as i32)
+            }
+        }
+        if multiplier.is_infinite() {
+            return Self::infinity_mul(multiplier > 0.0, self.value.signum() as i32);
+        }
+        let exact = self.value as f64 * multiplier;
+        Self::with_f64_value(exact, self.scale, self.is_rounded(), self.epoch)
+    }
+
+    /// Rounds `exact` ticks of `scale` halving the timescale on overflow.
+    fn with_f64_value(
+        mut exact: f64,
+        mut scale: TimeScale,
+        rounded: bool,
+        epoch: TimeEpoch,
+    ) -> Self {
+        // 2^63 is the first value which doesn't fit i64
+        const LIMIT: f64 = 9_223_372_036_854_775_808.0;
+        loop {
+            let value = exact.round();
+            if value.abs() < LIMIT {
+                let mut flags = TimeFlags::VALID;
+                if rounded || value != exact {
+                    flags |= TimeFlags::HAS_BEEN_ROUNDED;
+                }
+                return Self {
+                    value: value as TimeValue,
+                    scale,
+                    flags,
+                    epoch,
+                };
+            }
+            if scale == 1 {
+                return if exact < 0.0 {
+                    Self::NEG_INFINITY
+                } else {
+                    Self::POS_INFINITY
+                };
+            }
+            exact *= (scale / 2) as f64 / scale as f64;
+            scale /= 2;
+        }
+    }
+
+    /// Returns the result of multiplying a Time by an integer ratio, keeping the timescale.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let t = cm::Time::new(10, 30).mul_ratio(2, 3);
+    /// assert_eq!(t.value, 7);
+    /// assert_eq!(t.scale, 30);
+    /// assert!(t.flags.contains(cm::TimeFlags::HAS_BEEN_ROUNDED));
+    /// ```
+    #[doc(alias = "CMTimeMultiplyByRatio")]
+    pub fn mul_ratio(self, multiplier: i32, divisor: i32) -> Time {
+        if divisor == 0 {
+            return Self::INVALID;
+        }
+        let signum = multiplier.signum() * divisor.signum();
+        match self.class() {
+            Class::Numeric if self.scale > 0 => {}
+            Class::Numeric | Class::Invalid => return Self::INVALID,
+            Class::Indefinite => return self,
+            Class::PosInfinity | Class::NegInfinity => {
+                return Self::infinity_mul(self.is_pos_infinity(), signum)
+            }
+        }
+        self.scale_into(multiplier as i128, divisor as i128, self.scale)
+    }
+
+    /// Multiplies numeric time by `num / den` and rounds the result into `scale`.
+    fn scale_into(self, num: i128, den: i128, scale: TimeScale) -> Time {
+        if self.scale <= 0 || scale <= 0 {
+            return Self::INVALID;
+        }
+        let mut num = self.value as i128 * num;
+        let mut den = den * self.scale as i128;
+        if den < 0 {
+            num = -num;
+            den = -den;
+        }
+        Self::fit(num, den, scale, self.is_rounded(), self.epoch)
     }
 
     /// Returns valid Time with value and timescale. Epoch is implied to be 0.
@@ -223,22 +583,23 @@ impl Time {
     /// ```
     #[doc(alias = "CMTimeMake")]
     #[inline]
-    pub fn new(value: TimeValue, timescale: i32) -> Time {
-        unsafe { CMTimeMake(value, timescale) }
+    pub const fn new(value: TimeValue, timescale: i32) -> Time {
+        Self::with_epoch(value, timescale, 0)
     }
 
     #[doc(alias = "kCMTimePositiveInfinity")]
     #[inline]
-    pub fn infinity() -> Time {
-        unsafe { kCMTimePositiveInfinity }
+    pub const fn infinity() -> Time {
+        Self::POS_INFINITY
     }
 
     #[doc(alias = "kCMTimeNegativeInfinity")]
     #[inline]
-    pub fn neg_infinity() -> Time {
-        unsafe { kCMTimeNegativeInfinity }
+    pub const fn neg_infinity() -> Time {
+        Self::NEG_INFINITY
     }
 
+    #[cfg(target_vendor = "apple")]
     #[inline]
     pub fn show(self) {
         unsafe { CMTimeShow(self) }
@@ -254,9 +615,11 @@ impl Time {
     /// assert_eq!(t3.scale, 10);
     /// assert_eq!(t3.as_secs(), 0.0);
     /// ```
+    #[doc(alias = "CMTimeSubtract")]
+    #[allow(clippy::should_implement_trait)]
     #[inline]
     pub fn sub(self, rhs: Time) -> Time {
-        unsafe { CMTimeSubtract(self, rhs) }
+        self.add_signed(rhs, true)
     }
 
     /// ```
@@ -266,13 +629,21 @@ impl Time {
     /// assert!(time.is_valid());
     /// assert_eq!(time.epoch, 5);
     /// ```
+    #[doc(alias = "CMTimeMakeWithEpoch")]
     #[inline]
-    pub fn with_epoch(value: TimeValue, timescale: i32, epoch: TimeEpoch) -> Time {
-        unsafe { CMTimeMakeWithEpoch(value, timescale, epoch) }
+    pub const fn with_epoch(value: TimeValue, timescale: i32, epoch: TimeEpoch) -> Time {
+        Self {
+            value,
+            scale: timescale,
+            flags: TimeFlags::VALID,
+            epoch,
+        }
     }
 
     /// Returns Time from a f64 number of seconds, and a preferred timescale.
     ///
+    /// If the value doesn't fit, the timescale is halved until it does.
+    ///
     /// ```
     /// use cidre::cm;
     ///
@@ -281,27 +652,60 @@ impl Time {
     /// assert_eq!(time.scale, 10);
     /// assert_eq!(time.as_secs(), 100.0);
     /// ```
+    #[doc(alias = "CMTimeMakeWithSeconds")]
     #[inline]
     pub fn with_secs(seconds: f64, preferred_timescale: TimeScale) -> Time {
-        unsafe { CMTimeMakeWithSeconds(seconds, preferred_timescale) }
+        if seconds.is_nan() || preferred_timescale <= 0 {
+            return Self::INVALID;
+        }
+        if seconds.is_infinite() {
+            return if seconds > 0.0 {
+                Self::POS_INFINITY
+            } else {
+                Self::NEG_INFINITY
+            };
+        }
+        Self::with_f64_value(
+            seconds * preferred_timescale as f64,
+            preferred_timescale,
+            false,
+            0,
+        )
     }
 
     #[inline]
     pub const fn zero() -> Time {
-        unsafe { kCMTimeZero }
+        Self::ZERO
     }
 
+    #[doc(alias = "CMTimeMaximum")]
     #[inline]
     pub fn max(l: Time, r: Time) -> Time {
-        unsafe { CMTimeMaximum(l, r) }
+        if l.cmp(&r) == Ordering::Less {
+            r
+        } else {
+            l
+        }
     }
 
+    #[doc(alias = "CMTimeMinimum")]
     #[inline]
     pub fn min(l: Time, r: Time) -> Time {
-        unsafe { CMTimeMinimum(l, r) }
+        if r.cmp(&l) == Ordering::Less {
+            r
+        } else {
+            l
+        }
     }
 }
 
+fn gcd(mut a: i128, mut b: i128) -> i128 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a.abs()
+}
+
 impl PartialEq for Time {
     /// ```
     /// use cidre::cm;
@@ -315,7 +719,7 @@ impl PartialEq for Time {
     /// ```
     #[inline]
     fn eq(&self, other: &Self) -> bool {
-        unsafe { CMTimeCompare(*self, *other) == 0 }
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -333,8 +737,47 @@ impl PartialOrd for Time {
     /// assert!(cm::Time::zero() < cm::Time::infinity());
     /// ```
     #[inline]
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        unsafe { std::mem::transmute(CMTimeCompare(*self, *other) as i8) }
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Time {
+    /// Same order as `CMTimeCompare`: negative infinity < numeric times < indefinite <
+    /// positive infinity < invalid. Numeric times in greater epochs are greater.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// assert!(cm::Time::new(1, 10) < cm::Time::indefinit());
+    /// assert!(cm::Time::infinity() < cm::Time::invalid());
+    /// assert!(cm::Time::with_epoch(0, 1, 1) > cm::Time::with_epoch(100, 1, 0));
+    /// ```
+    #[doc(alias = "CMTimeCompare")]
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (lc, rc) = (self.class(), other.class());
+        if lc != Class::Numeric || rc != Class::Numeric {
+            return lc.cmp(&rc);
+        }
+        self.epoch.cmp(&other.epoch).then_with(|| {
+            let l = self.value as i128 * other.scale as i128;
+            let r = other.value as i128 * self.scale as i128;
+            l.cmp(&r)
+        })
+    }
+}
+
+impl Hash for Time {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        let class = self.class();
+        class.hash(state);
+        if class == Class::Numeric {
+            // equal times with different timescales must hash the same
+            let g = gcd(self.value as i128, self.scale as i128).max(1);
+            self.epoch.hash(state);
+            (self.value as i128 / g).hash(state);
+            (self.scale as i128 / g).hash(state);
+        }
     }
 }
 
@@ -349,10 +792,96 @@ impl Default for Time {
     }
 }
 
+impl ops::Add for Time {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Self) -> Self {
+        Time::add(self, rhs)
+    }
+}
+
+impl ops::AddAssign for Time {
+    #[inline]
+    fn add_assign(&mut self, rhs: Self) {
+        *self = Time::add(*self, rhs)
+    }
+}
+
+impl ops::Sub for Time {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Self) -> Self {
+        Time::sub(self, rhs)
+    }
+}
+
+impl ops::SubAssign for Time {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = Time::sub(*self, rhs)
+    }
+}
+
+impl ops::Neg for Time {
+    type Output = Self;
+
+    #[inline]
+    fn neg(self) -> Self {
+        self.mul_i32(-1)
+    }
+}
+
+impl ops::Mul<i32> for Time {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: i32) -> Self {
+        self.mul_i32(rhs)
+    }
+}
+
+impl ops::MulAssign<i32> for Time {
+    #[inline]
+    fn mul_assign(&mut self, rhs: i32) {
+        *self = self.mul_i32(rhs)
+    }
+}
+
+impl ops::Mul<f64> for Time {
+    type Output = Self;
+
+    #[inline]
+    fn mul(self, rhs: f64) -> Self {
+        self.mul_f64(rhs)
+    }
+}
+
+impl ops::Div<i32> for Time {
+    type Output = Self;
+
+    #[inline]
+    fn div(self, rhs: i32) -> Self {
+        self.mul_ratio(1, rhs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::cm;
 
+    const V: u32 = 1;
+    const R: u32 = 3;
+
+    fn raw(t: cm::Time) -> (i64, i32, u32, i64) {
+        (t.value, t.scale, t.flags.0, t.epoch)
+    }
+
+    fn t(value: i64, scale: i32) -> cm::Time {
+        cm::Time::new(value, scale)
+    }
+
     #[test]
     fn basics() {
         let invalid = cm::Time::invalid();
@@ -363,36 +892,282 @@ mod tests {
         assert!(valid.is_valid());
         assert!(valid.is_numeric());
     }
+
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn constants() {
+        unsafe {
+            assert_eq!(raw(cm::Time::INVALID), raw(kCMTimeInvalid));
+            assert_eq!(raw(cm::Time::INDEFINITE), raw(kCMTimeIndefinite));
+            assert_eq!(raw(cm::Time::POS_INFINITY), raw(kCMTimePositiveInfinity));
+            assert_eq!(raw(cm::Time::NEG_INFINITY), raw(kCMTimeNegativeInfinity));
+            assert_eq!(raw(cm::Time::ZERO), raw(kCMTimeZero));
+        }
+    }
+
+    #[test]
+    fn golden_add_sub() {
+        let inf = cm::Time::infinity();
+        let ninf = cm::Time::neg_infinity();
+        let ind = cm::Time::indefinit();
+        let inv = cm::Time::invalid();
+        let max = i64::MAX;
+
+        #[rustfmt::skip]
+        let table = [
+            // lhs, rhs, lhs + rhs, lhs - rhs
+            (t(1, 10), t(2, 10), (3, 10, V, 0), (-1, 10, V, 0)),
+            (t(1, 2), t(1, 3), (5, 6, V, 0), (1, 6, V, 0)),
+            (t(1, 600), t(1, 44100), (149, 88200, V, 0), (145, 88200, V, 0)),
+            (t(1, 30000), t(1, 1_000_000_000), (33334, 1_000_000_000, R, 0), (33332, 1_000_000_000, R, 0)),
+            (t(1, 999_999_999), t(1, 1_000_000_000), (2, 1_000_000_000, R, 0), (0, 1_000_000_000, R, 0)),
+            (t(max, 10), t(max, 10), (max, 5, V, 0), (0, 10, V, 0)),
+            (t(max, 1), t(1, 1), (inf.value, inf.scale, inf.flags.0, 0), (max - 1, 1, V, 0)),
+            (t(1, 1), inf, raw(inf), raw(ninf)),
+            (inf, inf, raw(inf), raw(inv)),
+            (inf, ninf, raw(inv), raw(inf)),
+            (ninf, t(5, 1), raw(ninf), raw(ninf)),
+            (ind, t(5, 1), raw(ind), raw(ind)),
+            (ind, inf, raw(inf), raw(ninf)),
+            (inv, inf, raw(inv), raw(inv)),
+            (cm::Time::with_epoch(1, 1, 3), t(1, 1), (2, 1, V, 3), (0, 1, V, 3)),
+            (cm::Time::with_epoch(5, 1, 3), cm::Time::with_epoch(1, 1, 3), (6, 1, V, 3), (4, 1, V, 0)),
+            (cm::Time::with_epoch(5, 1, 3), cm::Time::with_epoch(1, 1, 4), raw(inv), raw(inv)),
+        ];
+
+        for (l, r, sum, diff) in table {
+            assert_eq!(raw(l + r), sum, "{l:?} + {r:?}");
+            assert_eq!(raw(l - r), diff, "{l:?} - {r:?}");
+        }
+    }
+
+    #[test]
+    fn golden_convert_scale() {
+        use cm::TimeRoundingMethod as M;
+        #[rustfmt::skip]
+        let table = [
+            (t(5, 10), 3, M::RoundHalfAwayFromZero, (2, 3, R, 0)),
+            (t(-5, 10), 3, M::RoundHalfAwayFromZero, (-2, 3, R, 0)),
+            (t(1, 2), 1, M::RoundHalfAwayFromZero, (1, 1, R, 0)),
+            (t(-1, 2), 1, M::RoundHalfAwayFromZero, (-1, 1, R, 0)),
+            (t(5, 10), 3, M::RoundTowardZero, (1, 3, R, 0)),
+            (t(-5, 10), 3, M::RoundTowardZero, (-1, 3, R, 0)),
+            (t(5, 10), 3, M::RoundAwayFromZero, (2, 3, R, 0)),
+            (t(-5, 10), 3, M::RoundAwayFromZero, (-2, 3, R, 0)),
+            (t(5, 10), 3, M::RoundTowardPositiveInfinity, (2, 3, R, 0)),
+            (t(-5, 10), 3, M::RoundTowardPositiveInfinity, (-1, 3, R, 0)),
+            (t(5, 10), 3, M::RoundTowardNegativeInfinity, (1, 3, R, 0)),
+            (t(-5, 10), 3, M::RoundTowardNegativeInfinity, (-2, 3, R, 0)),
+            (t(5, 10), 3, M::QuickTime, (1, 3, R, 0)),
+            (t(1, 3), 10, M::QuickTime, (4, 10, R, 0)),
+            (t(-1, 100), 10, M::QuickTime, (-1, 10, R, 0)),
+            (t(6, 10), 5, M::RoundTowardZero, (3, 5, V, 0)),
+            (t(i64::MAX, 1), 2, M::RoundTowardZero, raw(cm::Time::infinity())),
+            (cm::Time::indefinit(), 2, M::RoundTowardZero, raw(cm::Time::indefinit())),
+            (t(1, 1), 0, M::RoundTowardZero, raw(cm::Time::invalid())),
+        ];
+
+        for (time, scale, method, expected) in table {
+            assert_eq!(
+                raw(time.convert_scale(scale, method)),
+                expected,
+                "{time:?} -> {scale} {method:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn golden_mul() {
+        let max = i64::MAX;
+        #[rustfmt::skip]
+        let table = [
+            (t(3, 10), 2, (6, 10, V, 0)),
+            (t(3, 10), -2, (-6, 10, V, 0)),
+            (t(max, 10), 2, (max, 5, V, 0)),
+            (t(max, 1), 2, raw(cm::Time::infinity())),
+            (t(max, 1), -2, raw(cm::Time::neg_infinity())),
+            (cm::Time::infinity(), -1, raw(cm::Time::neg_infinity())),
+            (cm::Time::indefinit(), 7, raw(cm::Time::indefinit())),
+        ];
+        for (time, m, expected) in table {
+            assert_eq!(raw(time * m), expected, "{time:?} * {m}");
+        }
+
+        assert_eq!(raw(t(10, 30).mul_ratio(2, 3)), (7, 30, R, 0));
+        assert_eq!(raw(t(10, 30).mul_ratio(-2, 3)), (-7, 30, R, 0));
+        assert_eq!(raw(t(9, 30).mul_ratio(2, 3)), (6, 30, V, 0));
+        assert_eq!(raw(t(10, 30) / 4), (3, 30, R, 0));
+        assert_eq!(raw(t(10, 30).mul_f64(0.5)), (5, 30, V, 0));
+        assert_eq!(raw(t(10, 30).mul_f64(0.25)), (3, 30, R, 0));
+        assert_eq!(raw(cm::Time::with_secs(0.5, 3)), (2, 3, R, 0));
+        assert_eq!(raw(cm::Time::with_secs(-1.5, 2)), (-3, 2, V, 0));
+        assert_eq!(raw(-t(3, 10)), (-3, 10, V, 0));
+        assert_eq!(raw(t(-3, 10).abs()), (3, 10, V, 0));
+    }
+
+    #[test]
+    fn ordering() {
+        let mut times = [
+            cm::Time::invalid(),
+            cm::Time::infinity(),
+            cm::Time::with_epoch(1, 1, 1),
+            cm::Time::indefinit(),
+            t(1, 3),
+            t(1, 2),
+            cm::Time::neg_infinity(),
+        ];
+        times.sort();
+        assert_eq!(
+            times.iter().map(|t| raw(*t)).collect::<Vec<_>>(),
+            [
+                raw(cm::Time::neg_infinity()),
+                raw(t(1, 3)),
+                raw(t(1, 2)),
+                raw(cm::Time::with_epoch(1, 1, 1)),
+                raw(cm::Time::indefinit()),
+                raw(cm::Time::infinity()),
+                raw(cm::Time::invalid()),
+            ]
+        );
+        assert_eq!(t(1, 2), t(50, 100));
+        assert_eq!(cm::Time::max(t(1, 2), t(1, 3)), t(1, 2));
+        assert_eq!(cm::Time::min(t(1, 2), t(1, 3)), t(1, 3));
+
+        use std::hash::{BuildHasher, RandomState};
+        let s = RandomState::new();
+        assert_eq!(s.hash_one(t(1, 2)), s.hash_one(t(50, 100)));
+        assert_eq!(s.hash_one(t(0, 2)), s.hash_one(t(0, 7)));
+    }
+
+    /// Runs the same operations through CoreMedia and compares results bit for bit.
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn matches_core_media() {
+        use cm::TimeRoundingMethod as M;
+        let mut times = vec![
+            cm::Time::invalid(),
+            cm::Time::indefinit(),
+            cm::Time::infinity(),
+            cm::Time::neg_infinity(),
+            cm::Time::with_epoch(7, 3, 1),
+            cm::Time::with_epoch(-7, 3, 2),
+        ];
+        for value in [0, 1, -1, 7, -7, 1001, i64::MAX, i64::MIN + 1, i64::MAX / 3] {
+            for scale in [1, 2, 3, 10, 600, 1000, 30000, 44100, 48000, 999_999_937] {
+                times.push(t(value, scale));
+            }
+        }
+        let methods = [
+            M::RoundHalfAwayFromZero,
+            M::RoundTowardZero,
+            M::RoundAwayFromZero,
+            M::QuickTime,
+            M::RoundTowardPositiveInfinity,
+            M::RoundTowardNegativeInfinity,
+        ];
+
+        for &l in &times {
+            unsafe {
+                assert_eq!(raw(l.abs()), raw(CMTimeAbsoluteValue(l)), "abs {l:?}");
+                for m in [0, 1, -1, 2, 3, -1000, i32::MAX] {
+                    assert_eq!(raw(l.mul_i32(m)), raw(CMTimeMultiply(l, m)), "{l:?} * {m}");
+                    assert_eq!(
+                        raw(l.mul_ratio(m, 7)),
+                        raw(CMTimeMultiplyByRatio(l, m, 7)),
+                        "{l:?} * {m} / 7"
+                    );
+                }
+                for m in [
+                    0.0,
+                    0.5,
+                    -0.5,
+                    1.0 / 3.0,
+                    2.5,
+                    -1e-9,
+                    29.97,
+                    1e9,
+                    f64::INFINITY,
+                    f64::NEG_INFINITY,
+                    f64::NAN,
+                ] {
+                    let res = l.mul_f64(m);
+                    let ref_res = CMTimeMultiplyByFloat64(l, m);
+                    assert_eq!(raw(res), raw(ref_res), "{l:?} * {m}");
+                }
+                for scale in [1, 3, 30, 600, 48000, 1_000_000_000] {
+                    for method in methods {
+                        assert_eq!(
+                            raw(l.convert_scale(scale, method)),
+                            raw(CMTimeConvertScale(l, scale, method)),
+                            "{l:?} -> {scale} {method:?}"
+                        );
+                    }
+                }
+                let secs = l.as_secs();
+                let ref_secs = CMTimeGetSeconds(l);
+                assert!(secs == ref_secs || (secs.is_nan() && ref_secs.is_nan()));
+                for &r in &times {
+                    assert_eq!(raw(l + r), raw(CMTimeAdd(l, r)), "{l:?} + {r:?}");
+                    assert_eq!(raw(l - r), raw(CMTimeSubtract(l, r)), "{l:?} - {r:?}");
+                    assert_eq!(l.cmp(&r) as i32, CMTimeCompare(l, r), "{l:?} <=> {r:?}");
+                }
+            }
+        }
+
+        for secs in [
+            0.0,
+            0.1,
+            -0.1,
+            1.5,
+            -2.5,
+            1e12,
+            1e300,
+            f64::INFINITY,
+            f64::NAN,
+        ] {
+            for scale in [1, 10, 600, 44100, 1_000_000_000] {
+                unsafe {
+                    assert_eq!(
+                        raw(cm::Time::with_secs(secs, scale)),
+                        raw(CMTimeMakeWithSeconds(secs, scale)),
+                        "{secs} @ {scale}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(target_vendor = "apple")]
+    #[link(name = "CoreMedia", kind = "framework")]
+    extern "C-unwind" {
+        static kCMTimeInvalid: cm::Time;
+        static kCMTimeIndefinite: cm::Time;
+        static kCMTimePositiveInfinity: cm::Time;
+        static kCMTimeNegativeInfinity: cm::Time;
+        static kCMTimeZero: cm::Time;
+
+        fn CMTimeMakeWithSeconds(seconds: f64, preferred_timescale: cm::TimeScale) -> cm::Time;
+        fn CMTimeGetSeconds(time: cm::Time) -> f64;
+        fn CMTimeConvertScale(
+            time: cm::Time,
+            new_time_scale: cm::TimeScale,
+            rounding_method: cm::TimeRoundingMethod,
+        ) -> cm::Time;
+
+        fn CMTimeAdd(lhs: cm::Time, rhs: cm::Time) -> cm::Time;
+        fn CMTimeSubtract(lhs: cm::Time, rhs: cm::Time) -> cm::Time;
+        fn CMTimeMultiply(time: cm::Time, multiplier: i32) -> cm::Time;
+        fn CMTimeMultiplyByRatio(time: cm::Time, multiplier: i32, divisor: i32) -> cm::Time;
+        fn CMTimeMultiplyByFloat64(time: cm::Time, multiplier: f64) -> cm::Time;
+        fn CMTimeCompare(time1: cm::Time, time2: cm::Time) -> i32;
+        fn CMTimeAbsoluteValue(time: cm::Time) -> cm::Time;
+    }
 }
 
+#[cfg(target_vendor = "apple")]
 #[link(name = "CoreMedia", kind = "framework")]
 extern "C-unwind" {
-    static kCMTimeInvalid: Time;
-    static kCMTimeIndefinite: Time;
-    static kCMTimePositiveInfinity: Time;
-    static kCMTimeNegativeInfinity: Time;
-    static kCMTimeZero: Time;
-
-    fn CMTimeMake(value: TimeValue, timescale: TimeScale) -> Time;
-    fn CMTimeMakeWithEpoch(value: TimeValue, timescale: TimeScale, epoch: TimeEpoch) -> Time;
-    fn CMTimeMakeWithSeconds(seconds: f64, preferred_timescale: TimeScale) -> Time;
-    fn CMTimeGetSeconds(time: Time) -> f64;
-    fn CMTimeConvertScale(
-        time: Time,
-        new_time_scale: TimeScale,
-        rounding_method: TimeRoundingMethod,
-    ) -> Time;
-
-    fn CMTimeAdd(lhs: Time, rhs: Time) -> Time;
-    fn CMTimeSubtract(lhs: Time, rhs: Time) -> Time;
-    fn CMTimeMultiply(time: Time, multiplier: i32) -> Time;
-    fn CMTimeMultiplyByFloat64(time: Time, multiplier: f64) -> Time;
-
-    fn CMTimeCompare(time1: Time, time2: Time) -> i32;
-    fn CMTimeAbsoluteValue(time: Time) -> Time;
     fn CMTimeShow(time: Time);
-    fn CMTimeMaximum(time1: Time, time2: Time) -> Time;
-    fn CMTimeMinimum(time1: Time, time2: Time) -> Time;
 
     fn CMTimeCopyDescription(
         allocator: Option<&cf::Allocator>,
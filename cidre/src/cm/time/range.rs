@@ -9,7 +9,32 @@ pub struct Range {
 }
 
 impl Range {
-    #[doc(alias = "CMTIMERANGE_IS_INVALID")]
+    #[doc(alias = "kCMTimeRangeZero")]
+    pub const ZERO: Self = Self::new(cm::Time::ZERO, cm::Time::ZERO);
+
+    #[doc(alias = "kCMTimeRangeInvalid")]
+    pub const INVALID: Self = Self::new(cm::Time::INVALID, cm::Time::INVALID);
+
+    #[doc(alias = "CMTimeRangeMake")]
+    #[inline]
+    pub const fn new(start: cm::Time, duration: cm::Time) -> Self {
+        Self { start, duration }
+    }
+
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let range = cm::TimeRange::with_start_end(cm::Time::new(1, 10), cm::Time::new(1, 2));
+    /// assert_eq!(range.duration, cm::Time::new(4, 10));
+    /// assert_eq!(range.end(), cm::Time::new(1, 2));
+    /// ```
+    #[doc(alias = "CMTimeRangeFromTimeToTime")]
+    #[inline]
+    pub fn with_start_end(start: cm::Time, end: cm::Time) -> Self {
+        Self::new(start, end - start)
+    }
+
+    #[doc(alias = "CMTIMERANGE_IS_VALID")]
     #[inline]
     pub const fn is_valid(&self) -> bool {
         self.start.is_valid()
@@ -18,52 +43,266 @@ impl Range {
             && self.duration.value >= 0
     }
 
+    #[doc(alias = "CMTIMERANGE_IS_INVALID")]
+    #[inline]
+    pub const fn is_invalid(&self) -> bool {
+        !self.is_valid()
+    }
+
     #[doc(alias = "CMTIMERANGE_IS_INDEFINITE")]
     #[inline]
     pub const fn is_indefinite(&self) -> bool {
         self.is_valid() && (self.start.is_indefinite() || self.duration.is_indefinite())
     }
 
+    #[doc(alias = "CMTIMERANGE_IS_EMPTY")]
     pub fn is_empty(&self) -> bool {
         self.is_valid() && self.duration == cm::Time::zero()
     }
 
+    #[doc(alias = "CMTimeRangeGetEnd")]
+    #[inline]
+    pub fn end(&self) -> cm::Time {
+        if self.is_valid() {
+            self.start + self.duration
+        } else {
+            cm::Time::INVALID
+        }
+    }
+
+    /// Returns true if the time is in `[start, end)`.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let range = cm::TimeRange::new(cm::Time::new(1, 1), cm::Time::new(2, 1));
+    /// assert!(range.contains_time(&cm::Time::new(1, 1)));
+    /// assert!(range.contains_time(&cm::Time::new(5, 2)));
+    /// assert!(!range.contains_time(&cm::Time::new(3, 1)));
+    /// ```
     #[doc(alias = "CMTimeRangeContainsTime")]
     #[inline]
     pub fn contains_time(&self, time: &cm::Time) -> bool {
-        unsafe { CMTimeRangeContainsTime(*self, *time) }
+        self.is_valid() && time.is_valid() && *time >= self.start && *time < self.end()
+    }
+
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let outer = cm::TimeRange::new(cm::Time::new(0, 1), cm::Time::new(10, 1));
+    /// let inner = cm::TimeRange::new(cm::Time::new(2, 1), cm::Time::new(8, 1));
+    /// assert!(outer.contains_range(&inner));
+    /// assert!(!inner.contains_range(&outer));
+    /// ```
+    #[doc(alias = "CMTimeRangeContainsTimeRange")]
+    #[inline]
+    pub fn contains_range(&self, other: &Range) -> bool {
+        self.is_valid()
+            && other.is_valid()
+            && other.start >= self.start
+            && other.end() <= self.end()
+    }
+
+    /// Returns the smallest range that covers both ranges.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let a = cm::TimeRange::new(cm::Time::new(0, 1), cm::Time::new(2, 1));
+    /// let b = cm::TimeRange::new(cm::Time::new(5, 1), cm::Time::new(1, 1));
+    /// let u = a.union(&b);
+    /// assert_eq!(u.start, cm::Time::new(0, 1));
+    /// assert_eq!(u.duration, cm::Time::new(6, 1));
+    /// ```
+    #[doc(alias = "CMTimeRangeGetUnion")]
+    pub fn union(&self, other: &Range) -> Range {
+        if self.is_invalid() || other.is_invalid() {
+            return Self::INVALID;
+        }
+        let start = cm::Time::min(self.start, other.start);
+        let end = cm::Time::max(self.end(), other.end());
+        Self::with_start_end(start, end)
+    }
+
+    /// Returns the overlapping part of two ranges or an empty range if they don't overlap.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let a = cm::TimeRange::new(cm::Time::new(0, 1), cm::Time::new(4, 1));
+    /// let b = cm::TimeRange::new(cm::Time::new(3, 1), cm::Time::new(4, 1));
+    /// let i = a.intersection(&b);
+    /// assert_eq!(i.start, cm::Time::new(3, 1));
+    /// assert_eq!(i.duration, cm::Time::new(1, 1));
+    ///
+    /// let c = cm::TimeRange::new(cm::Time::new(10, 1), cm::Time::new(1, 1));
+    /// assert!(a.intersection(&c).is_empty());
+    /// ```
+    #[doc(alias = "CMTimeRangeGetIntersection")]
+    pub fn intersection(&self, other: &Range) -> Range {
+        if self.is_invalid() || other.is_invalid() {
+            return Self::INVALID;
+        }
+        let start = cm::Time::max(self.start, other.start);
+        let end = cm::Time::min(self.end(), other.end());
+        if end <= start {
+            return Self::ZERO;
+        }
+        Self::with_start_end(start, end)
+    }
+
+    /// Returns the time clamped to `[start, end]`.
+    #[doc(alias = "CMTimeClampToRange")]
+    pub fn clamp_time(&self, time: cm::Time) -> cm::Time {
+        if self.is_invalid() || time.is_invalid() {
+            return cm::Time::INVALID;
+        }
+        if time < self.start {
+            self.start
+        } else {
+            cm::Time::min(time, self.end())
+        }
+    }
+
+    /// Maps a time from one range to another.
+    ///
+    /// The offset from `from.start` is scaled by `to.duration / from.duration`
+    /// and added to `to.start`. Times outside of `from` are extrapolated.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// let from = cm::TimeRange::new(cm::Time::new(10, 1), cm::Time::new(10, 1));
+    /// let to = cm::TimeRange::new(cm::Time::new(0, 1), cm::Time::new(20, 1));
+    /// assert_eq!(from.map_time(cm::Time::new(15, 1), &to), cm::Time::new(10, 1));
+    /// ```
+    #[doc(alias = "CMTimeMapTimeFromRangeToRange")]
+    pub fn map_time(&self, time: cm::Time, to: &Range) -> cm::Time {
+        let offset = time - self.start;
+        let Some(scaled) = self.map_duration(offset, to) else {
+            return cm::Time::INVALID;
+        };
+        scaled + to.start
+    }
+
+    /// Scales a duration by `to.duration / self.duration`. The result uses the larger of
+    /// the duration's and `to.duration`'s timescales.
+    ///
+    /// Returns `None` if either range has a non-numeric or zero duration.
+    #[doc(alias = "CMTimeMapDurationFromRangeToRange")]
+    pub fn map_duration(&self, duration: cm::Time, to: &Range) -> Option<cm::Time> {
+        let (from, to) = (self.duration, to.duration);
+        if !from.is_numeric()
+            || !to.is_numeric()
+            || from.value == 0
+            || from.scale <= 0
+            || to.scale <= 0
+        {
+            return None;
+        }
+        if !duration.is_numeric() {
+            return Some(duration);
+        }
+        Some(duration.scale_into(
+            to.value as i128 * from.scale as i128,
+            from.value as i128 * to.scale as i128,
+            duration.scale.max(to.scale),
+        ))
     }
 
     #[doc(alias = "kCMTimeRangeZero")]
     #[inline]
     pub const fn zero() -> Self {
-        unsafe { kCMTimeRangeZero }
+        Self::ZERO
     }
 
     #[doc(alias = "kCMTimeRangeInvalid")]
     #[inline]
     pub const fn invalid() -> Self {
-        unsafe { kCMTimeRangeInvalid }
+        Self::INVALID
     }
 }
 
+#[doc(alias = "CMTimeMapping")]
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[repr(C)]
 pub struct Mapping {
     pub source: cm::TimeRange,
     pub target: cm::TimeRange,
 }
 
-#[link(name = "CoreMedia", kind = "framework")]
-extern "C-unwind" {
-    static kCMTimeRangeZero: Range;
-    static kCMTimeRangeInvalid: Range;
+impl Mapping {
+    #[doc(alias = "kCMTimeMappingInvalid")]
+    pub const INVALID: Self = Self::new(Range::INVALID, Range::INVALID);
+
+    #[doc(alias = "CMTimeMappingMake")]
+    #[inline]
+    pub const fn new(source: cm::TimeRange, target: cm::TimeRange) -> Self {
+        Self { source, target }
+    }
+
+    /// Mapping for an empty edit: target range with no source media.
+    #[doc(alias = "CMTimeMappingMakeEmpty")]
+    #[inline]
+    pub const fn empty(target: cm::TimeRange) -> Self {
+        Self::new(Range::INVALID, target)
+    }
+
+    #[doc(alias = "CMTIMEMAPPING_IS_VALID")]
+    #[inline]
+    pub const fn is_valid(&self) -> bool {
+        self.target.is_valid()
+    }
+
+    #[doc(alias = "CMTIMEMAPPING_IS_EMPTY")]
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        !self.source.start.is_numeric() && self.target.is_valid()
+    }
 
-    fn CMTimeRangeContainsTime(range: Range, time: cm::Time) -> bool;
+    /// Maps a source time into the target timeline.
+    ///
+    /// Returns invalid time for empty edits.
+    ///
+    /// ```
+    /// use cidre::cm;
+    ///
+    /// // source [0, 4) played at double speed starting at 10
+    /// let mapping = cm::TimeMapping::new(
+    ///     cm::TimeRange::new(cm::Time::new(0, 1), cm::Time::new(4, 1)),
+    ///     cm::TimeRange::new(cm::Time::new(10, 1), cm::Time::new(2, 1)),
+    /// );
+    /// assert_eq!(mapping.map_time(cm::Time::new(2, 1)), cm::Time::new(11, 1));
+    /// assert_eq!(mapping.map_time_to_source(cm::Time::new(11, 1)), cm::Time::new(2, 1));
+    /// ```
+    pub fn map_time(&self, source_time: cm::Time) -> cm::Time {
+        if self.is_empty() || !self.is_valid() {
+            return cm::Time::INVALID;
+        }
+        self.source.map_time(source_time, &self.target)
+    }
+
+    /// Maps a target time back to the source media timeline.
+    pub fn map_time_to_source(&self, target_time: cm::Time) -> cm::Time {
+        if self.is_empty() || !self.is_valid() {
+            return cm::Time::INVALID;
+        }
+        self.target.map_time(target_time, &self.source)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::cm;
 
+    fn t(value: i64, scale: i32) -> cm::Time {
+        cm::Time::new(value, scale)
+    }
+
+    fn r(start: cm::Time, duration: cm::Time) -> cm::TimeRange {
+        cm::TimeRange::new(start, duration)
+    }
+
     #[test]
     fn basics() {
         let range = cm::TimeRange::zero();
@@ -74,4 +313,105 @@ mod tests {
         assert!(!range.is_valid());
         assert!(!range.is_empty());
     }
+
+    #[test]
+    fn set_ops() {
+        let a = r(t(0, 1), t(10, 1));
+        let b = r(t(5, 1), t(10, 1));
+        let c = r(t(20, 1), t(1, 1));
+
+        assert_eq!(a.union(&b), r(t(0, 1), t(15, 1)));
+        assert_eq!(a.union(&c), r(t(0, 1), t(21, 1)));
+        assert_eq!(a.intersection(&b), r(t(5, 1), t(5, 1)));
+        assert_eq!(a.intersection(&c), cm::TimeRange::zero());
+        assert!(a.union(&cm::TimeRange::invalid()).is_invalid());
+
+        assert!(a.contains_time(&t(0, 1)));
+        assert!(!a.contains_time(&t(10, 1)));
+        assert!(!a.contains_time(&cm::Time::invalid()));
+        assert!(a.contains_range(&r(t(2, 1), t(8, 1))));
+        assert!(!a.contains_range(&b));
+
+        let inf = r(t(0, 1), cm::Time::infinity());
+        assert!(inf.contains_time(&t(i64::MAX, 1)));
+        assert!(inf.contains_range(&a));
+
+        assert_eq!(a.clamp_time(t(-1, 1)), t(0, 1));
+        assert_eq!(a.clamp_time(t(11, 1)), t(10, 1));
+        assert_eq!(a.clamp_time(t(3, 1)), t(3, 1));
+    }
+
+    #[test]
+    fn mapping() {
+        let from = r(t(0, 600), t(600, 600));
+        let to = r(t(100, 1), t(2, 1));
+        assert_eq!(from.map_time(t(300, 600), &to), t(101, 1));
+        assert_eq!(from.map_time(t(900, 600), &to), t(103, 1));
+        assert_eq!(from.map_duration(t(1, 1), &to), Some(t(2, 1)));
+        assert_eq!(
+            from.map_duration(t(1, 1), &cm::TimeRange::zero()),
+            Some(t(0, 1))
+        );
+        assert_eq!(cm::TimeRange::zero().map_duration(t(1, 1), &to), None);
+        assert!(cm::TimeRange::zero().map_time(t(1, 1), &to).is_invalid());
+
+        let empty = cm::TimeMapping::empty(to);
+        assert!(empty.is_valid());
+        assert!(empty.is_empty());
+        assert!(empty.map_time(t(1, 1)).is_invalid());
+
+        let edit = cm::TimeMapping::new(from, to);
+        assert!(!edit.is_empty());
+        assert_eq!(edit.map_time(t(1, 2)), t(101, 1));
+        assert_eq!(edit.map_time_to_source(t(101, 1)), t(1, 2));
+    }
+
+    /// Compares range math against CoreMedia.
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn matches_core_media() {
+        let ranges = [
+            cm::TimeRange::zero(),
+            cm::TimeRange::invalid(),
+            r(t(0, 1), t(10, 1)),
+            r(t(5, 2), t(7, 3)),
+            r(t(-3, 600), t(1001, 30000)),
+            r(t(20, 1), t(1, 1)),
+            r(t(0, 1), cm::Time::infinity()),
+        ];
+        let times = [t(0, 1), t(5, 2), t(10, 1), t(-1, 1000), cm::Time::invalid()];
+        for a in ranges {
+            for b in ranges {
+                unsafe {
+                    assert_eq!(a.union(&b), CMTimeRangeGetUnion(a, b), "{a:?} | {b:?}");
+                    assert_eq!(
+                        a.intersection(&b),
+                        CMTimeRangeGetIntersection(a, b),
+                        "{a:?} & {b:?}"
+                    );
+                    assert_eq!(
+                        a.contains_range(&b),
+                        CMTimeRangeContainsTimeRange(a, b),
+                        "{a:?} contains {b:?}"
+                    );
+                }
+            }
+            for time in times {
+                unsafe {
+                    assert_eq!(a.contains_time(&time), CMTimeRangeContainsTime(a, time));
+                    assert_eq!(a.end(), CMTimeRangeGetEnd(a));
+                }
+            }
+        }
+    }
+
+    #[cfg(target_vendor = "apple")]
+    #[link(name = "CoreMedia", kind = "framework")]
+    extern "C-unwind" {
+        fn CMTimeRangeContainsTime(range: cm::TimeRange, time: cm::Time) -> bool;
+        fn CMTimeRangeContainsTimeRange(range: cm::TimeRange, other: cm::TimeRange) -> bool;
+        fn CMTimeRangeGetEnd(range: cm::TimeRange) -> cm::Time;
+        fn CMTimeRangeGetUnion(a: cm::TimeRange, b: cm::TimeRange) -> cm::TimeRange;
+        fn CMTimeRangeGetIntersection(a: cm::TimeRange, b: cm::TimeRange) -> cm::TimeRange;
+    }
 }
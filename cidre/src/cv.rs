@@ -1,44 +1,85 @@
+#[cfg(target_vendor = "apple")]
 pub mod base;
+#[cfg(target_vendor = "apple")]
 pub use base::Flags as TimeFlags;
+#[cfg(target_vendor = "apple")]
 pub use base::OptionFlags;
+#[cfg(target_vendor = "apple")]
 pub use base::SmpteTime;
+#[cfg(target_vendor = "apple")]
 pub use base::Time;
+#[cfg(target_vendor = "apple")]
 pub use base::TimeStamp;
+#[cfg(target_vendor = "apple")]
 pub use base::TimeStampFlags;
 
 mod _return;
 pub use _return::Return;
 
+#[cfg(target_vendor = "apple")]
 pub mod buffer;
+#[cfg(target_vendor = "apple")]
 pub use buffer::AttachMode;
+#[cfg(target_vendor = "apple")]
 pub use buffer::Buf;
 
+#[cfg(target_vendor = "apple")]
 mod image_buffer;
+#[cfg(target_vendor = "apple")]
 pub use image_buffer::attachment as image_buf_attachment;
+#[cfg(target_vendor = "apple")]
 pub use image_buffer::ImageBuf;
 
+#[cfg(target_vendor = "apple")]
 pub mod pixel_buffer;
+#[cfg(target_vendor = "apple")]
 pub use pixel_buffer::keys as pixel_buffer_keys;
+#[cfg(target_vendor = "apple")]
 pub use pixel_buffer::PixelBuf;
-pub use pixel_buffer::PixelFormat;
 
+pub mod pixel_format;
+pub use pixel_format::PixelFormat;
+
+#[cfg(target_vendor = "apple")]
 pub mod pixel_buffer_pool;
+#[cfg(target_vendor = "apple")]
 pub use pixel_buffer_pool::FlushFlags as PixelBufPoolFlushFlags;
+#[cfg(target_vendor = "apple")]
 pub use pixel_buffer_pool::PixelBufPool;
 
+#[cfg(target_vendor = "apple")]
 pub mod pixel_format_description;
+#[cfg(target_vendor = "apple")]
 pub use pixel_format_description::all_pixel_formats as pixel_format_desc_array_with_all_pixel_formats;
+#[cfg(target_vendor = "apple")]
 pub use pixel_format_description::create as pixel_format_desc_create;
 
-#[cfg(feature = "mtl")]
+pub mod pixel_format_layout;
+pub use pixel_format_layout::ColorModel as PixelFormatColorModel;
+pub use pixel_format_layout::Component as PixelFormatComponent;
+pub use pixel_format_layout::Layout as PixelFormatLayout;
+pub use pixel_format_layout::PlaneLayout as PixelFormatPlaneLayout;
+pub use pixel_format_layout::Range as PixelFormatRange;
+
+pub mod pixel_convert;
+pub use pixel_convert::ChromaSiting;
+pub use pixel_convert::Converter as PixelConverter;
+pub use pixel_convert::Error as PixelConvertError;
+pub use pixel_convert::Image as PixelImage;
+pub use pixel_convert::ImageMut as PixelImageMut;
+pub use pixel_convert::Matrix as YCbCrMatrix;
+pub use pixel_convert::Plane as PixelPlane;
+pub use pixel_convert::PlaneMut as PixelPlaneMut;
+
+#[cfg(all(target_vendor = "apple", feature = "mtl"))]
 pub mod metal;
-#[cfg(feature = "mtl")]
+#[cfg(all(target_vendor = "apple", feature = "mtl"))]
 pub use metal::texture_cache_keys as metal_texture_cache_keys;
-#[cfg(feature = "mtl")]
+#[cfg(all(target_vendor = "apple", feature = "mtl"))]
 pub use metal::texture_keys as metal_texture_keys;
-#[cfg(feature = "mtl")]
+#[cfg(all(target_vendor = "apple", feature = "mtl"))]
 pub use metal::Texture as MetalTexture;
-#[cfg(feature = "mtl")]
+#[cfg(all(target_vendor = "apple", feature = "mtl"))]
 pub use metal::TextureCache as MetalTextureCache;
 
 #[cfg(target_os = "macos")]
@@ -48,10 +89,15 @@ pub use display_link::DisplayLink;
 #[cfg(target_os = "macos")]
 pub use display_link::OutputCb as DisplayLinkOutputCb;
 
+#[cfg(target_vendor = "apple")]
 mod host_time;
+#[cfg(target_vendor = "apple")]
 pub use host_time::current_host_time;
+#[cfg(target_vendor = "apple")]
 pub use host_time::host_clock_frequency;
+#[cfg(target_vendor = "apple")]
 pub use host_time::host_clock_minimum_time_delta;
 
+#[cfg(target_vendor = "apple")]
 #[link(name = "CoreVideo", kind = "framework")]
 extern "C" {}
@@ -4,42 +4,76 @@ pub struct Return(i32);
 
 impl Return {
     /// Function executed successfully without errors.
+    #[doc(alias = "kCVReturnSuccess")]
     pub const SUCCESS: Self = Self(0);
     /// Placeholder to mark the beginning of the range of cv::Return codes.
+    #[doc(alias = "kCVReturnFirst")]
     pub const FIRST: Self = Self(-6660);
 
+    #[doc(alias = "kCVReturnError")]
     pub const ERROR: Self = Self::FIRST;
 
     /// At least one of the arguments passed in is not valid. Either out of range or the wrong type.
+    #[doc(alias = "kCVReturnInvalidArgument")]
     pub const INVALID_ARGUMENT: Self = Self(-6661);
     /// The allocation for a buffer or buffer pool failed. Most likely because of lack of resources.
+    #[doc(alias = "kCVReturnAllocationFailed")]
     pub const ALLOCATION_FAILED: Self = Self(-6662);
 
+    /// The operation or feature is not supported.
+    #[doc(alias = "kCVReturnUnsupported")]
     pub const UNSUPPORTED: Self = Self(-6663);
 
     // DisplayLink related errors
 
+    /// The display specified is invalid.
+    #[doc(alias = "kCVReturnInvalidDisplay")]
     pub const INVALID_DISPLAY: Self = Self(-6670);
+    /// The display link is already running.
+    #[doc(alias = "kCVReturnDisplayLinkAlreadyRunning")]
     pub const DISPLAY_LINK_ALREADY_RUNNING: Self = Self(-6671);
+    /// The display link is not running.
+    #[doc(alias = "kCVReturnDisplayLinkNotRunning")]
     pub const DISPLAY_LINK_NOT_RUNNING: Self = Self(-6672);
+    /// The output callback is not set.
+    #[doc(alias = "kCVReturnDisplayLinkCallbacksNotSet")]
     pub const DISPLAY_LINK_CALLBACKS_NOT_SET: Self = Self(-6673);
 
     // Buffer related errors
 
+    /// The requested pixel format is not supported.
+    #[doc(alias = "kCVReturnInvalidPixelFormat")]
     pub const INVALID_PIXEL_FORMAT: Self = Self(-6680);
+    /// The requested size (most likely too big) is not supported.
+    #[doc(alias = "kCVReturnInvalidSize")]
     pub const INVALID_SIZE: Self = Self(-6681);
+    /// A cv::PixelBuf cannot be created with the requested attributes.
+    #[doc(alias = "kCVReturnInvalidPixelBufferAttributes")]
     pub const INVALID_PIXEL_BUFFER_ATTRIBUTES: Self = Self(-6682);
+    /// The pixel buffer is not compatible with OpenGL due to an unsupported buffer size, pixel format, or attribute.
+    #[doc(alias = "kCVReturnPixelBufferNotOpenGLCompatible")]
     pub const PIXEL_BUFFER_NOT_OPEN_GLCOMPATIBLE: Self = Self(-6683);
+    /// The pixel buffer is not compatible with Metal due to an unsupported buffer size, pixel format, or attribute.
+    #[doc(alias = "kCVReturnPixelBufferNotMetalCompatible")]
     pub const PIXEL_BUFFER_NOT_METAL_COMPATIBLE: Self = Self(-6684);
 
     // Buffer pool related errors
 
+    /// Allocation for a pixel buffer failed because the threshold value set in the pool attributes would be surpassed.
+    #[doc(alias = "kCVReturnWouldExceedAllocationThreshold")]
     pub const WOULD_EXCEED_ALLOCATION_THRESHOLD: Self = Self(-6689);
+    /// The allocation for the buffer pool failed.
+    #[doc(alias = "kCVReturnPoolAllocationFailed")]
     pub const POOL_ALLOCATION_FAILED: Self = Self(-6690);
+    /// A cv::PixelBufPool cannot be created with the requested attributes.
+    #[doc(alias = "kCVReturnInvalidPoolAttributes")]
     pub const INVALID_POOL_ATTRIBUTES: Self = Self(-6691);
+    /// A scan has not completed.
+    #[doc(alias = "kCVReturnRetry")]
     pub const RETRY: Self = Self(-6692);
 
     /// Placeholder to mark the end of the range of cv::Return codes.
+    #[doc(alias = "kCVReturnLast")]
     pub const LAST: Self = Self(-6699);
 
     #[inline]
@@ -47,6 +81,9 @@ impl Return {
         *self == Self::SUCCESS
     }
 
+    /// # Safety
+    ///
+    /// `option` must be `Some` when `self` is ok.
     #[inline]
     pub unsafe fn to_result_unchecked<T>(self, option: Option<T>) -> Result<T, Self> {
         if self.is_ok() {
@@ -64,6 +101,12 @@ impl Return {
             Err(self)
         }
     }
+
+    #[cfg(feature = "err_desc")]
+    #[inline]
+    pub fn describe(&self) -> Option<&'static crate::os::err_desc::Desc> {
+        crate::os::err_desc::find(self.0)
+    }
 }
 
 impl From<Return> for Result<(), Return> {
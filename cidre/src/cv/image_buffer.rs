@@ -173,6 +173,18 @@ pub mod attachment {
             unsafe { kCVImageBufferTransferFunctionKey }
         }
 
+        #[doc(alias = "kCVImageBufferChromaLocationTopFieldKey")]
+        #[inline]
+        pub fn chroma_location_top_field() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocationTopFieldKey }
+        }
+
+        #[doc(alias = "kCVImageBufferChromaLocationBottomFieldKey")]
+        #[inline]
+        pub fn chroma_location_bottom_field() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocationBottomFieldKey }
+        }
+
         extern "C" {
             static kCVImageBufferCGColorSpaceKey: &'static cf::String;
             static kCVImageBufferCleanApertureKey: &'static cf::String;
@@ -187,6 +199,73 @@ pub mod attachment {
             static kCVImageBufferYCbCrMatrixKey: &'static cf::String;
             static kCVImageBufferColorPrimariesKey: &'static cf::String;
             static kCVImageBufferTransferFunctionKey: &'static cf::String;
+            static kCVImageBufferChromaLocationTopFieldKey: &'static cf::String;
+            static kCVImageBufferChromaLocationBottomFieldKey: &'static cf::String;
+        }
+    }
+
+    pub mod chroma_location {
+        use crate::cf;
+
+        /// Chroma sample is horizontally co-sited with the left column of luma samples,
+        /// but centered vertically.
+        #[doc(alias = "kCVImageBufferChromaLocation_Left")]
+        #[inline]
+        pub fn left() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_Left }
+        }
+
+        /// Chroma sample is fully centered.
+        #[doc(alias = "kCVImageBufferChromaLocation_Center")]
+        #[inline]
+        pub fn center() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_Center }
+        }
+
+        /// Chroma sample is co-sited with the top-left luma sample.
+        #[doc(alias = "kCVImageBufferChromaLocation_TopLeft")]
+        #[inline]
+        pub fn top_left() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_TopLeft }
+        }
+
+        /// Chroma sample is horizontally centered, but co-sited with the top row.
+        #[doc(alias = "kCVImageBufferChromaLocation_Top")]
+        #[inline]
+        pub fn top() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_Top }
+        }
+
+        /// Chroma sample is co-sited with the bottom-left luma sample.
+        #[doc(alias = "kCVImageBufferChromaLocation_BottomLeft")]
+        #[inline]
+        pub fn bottom_left() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_BottomLeft }
+        }
+
+        /// Chroma sample is horizontally centered, but co-sited with the bottom row.
+        #[doc(alias = "kCVImageBufferChromaLocation_Bottom")]
+        #[inline]
+        pub fn bottom() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_Bottom }
+        }
+
+        /// Cr and Cb samples are alternately co-sited with the left luma samples
+        /// of the same field.
+        #[doc(alias = "kCVImageBufferChromaLocation_DV420")]
+        #[inline]
+        pub fn dv_420() -> &'static cf::String {
+            unsafe { kCVImageBufferChromaLocation_DV420 }
+        }
+
+        extern "C" {
+            static kCVImageBufferChromaLocation_Left: &'static cf::String;
+            static kCVImageBufferChromaLocation_Center: &'static cf::String;
+            static kCVImageBufferChromaLocation_TopLeft: &'static cf::String;
+            static kCVImageBufferChromaLocation_Top: &'static cf::String;
+            static kCVImageBufferChromaLocation_BottomLeft: &'static cf::String;
+            static kCVImageBufferChromaLocation_Bottom: &'static cf::String;
+            static kCVImageBufferChromaLocation_DV420: &'static cf::String;
         }
     }
 
@@ -0,0 +1,986 @@
+//! Portable software conversion between common [`cv::PixelFormat`] layouts.
+//!
+//! Works on raw plane slices with explicit strides, subsampling comes from
+//! [`cv::PixelFormatLayout`]. Supported formats are 8-bit RGB (`BGRA`, `RGBA`, `ARGB`, ...)
+//! and Y'CbCr with 8, 10 or 16-bit samples: bi-planar (`420v`, `420f`, `x420`, `xf20`, `x422`, ...),
+//! planar (`f420`), packed 4:2:2 (`2vuy`, `v216`) and 4:4:4:4 (`v408`, `y408`, `y416`).
+//!
+//! It is slow compared to `vt::PixelTransferSession` and meant for tests and
+//! machines without VideoToolbox.
+//!
+//! ```
+//! use cidre::cv;
+//!
+//! let (w, h) = (2, 2);
+//! let bgra = [0u8, 0, 255, 255].repeat(w * h);
+//! let src = cv::PixelImage::new(
+//!     cv::PixelFormat::_32_BGRA,
+//!     w,
+//!     h,
+//!     vec![cv::PixelPlane::new(&bgra, w * 4)],
+//! );
+//!
+//! let mut luma = vec![0u8; w * h];
+//! let mut chroma = vec![0u8; 2];
+//! let mut dst = cv::PixelImageMut::new(
+//!     cv::PixelFormat::_420V,
+//!     w,
+//!     h,
+//!     vec![
+//!         cv::PixelPlaneMut::new(&mut luma, w),
+//!         cv::PixelPlaneMut::new(&mut chroma, w),
+//!     ],
+//! );
+//!
+//! let converter = cv::PixelConverter::new(cv::YCbCrMatrix::Bt709, Default::default());
+//! converter.convert(&src, &mut dst).unwrap();
+//!
+//! assert_eq!(luma, [63; 4]);
+//! assert_eq!(chroma, [102, 240]);
+//! ```
+
+use crate::cv::{
+    self, PixelFormatColorModel as Model, PixelFormatComponent as C, PixelFormatLayout as Layout,
+    PixelFormatPlaneLayout as PlaneLayout, PixelFormatRange as Range,
+};
+
+#[cfg(target_vendor = "apple")]
+use crate::cf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Pixel format the converter can't read or write.
+    Unsupported(cv::PixelFormat),
+
+    /// Source and destination have different sizes.
+    Size,
+
+    /// Wrong number of planes for the pixel format.
+    Planes {
+        expected: usize,
+        found: usize,
+    },
+
+    /// Plane at index is smaller than its bytes per row and height require.
+    Plane(usize),
+
+    /// Plane at index has no base address.
+    NullPlane(usize),
+
+    Cv(cv::Return),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unsupported(format) => write!(f, "unsupported pixel format {format:?}"),
+            Self::Size => f.write_str("source and destination sizes differ"),
+            Self::Planes { expected, found } => {
+                write!(f, "expected {expected} planes, found {found}")
+            }
+            Self::Plane(index) => write!(f, "plane {index} is too small"),
+            Self::NullPlane(index) => write!(f, "plane {index} has no base address"),
+            Self::Cv(res) => write!(f, "{res:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Y'CbCr matrix, see [`cv::image_buf_attachment::ycbcr_matrix`]
+/// and [`crate::cm::FormatDescExtKey::ycbcr_matrix`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Matrix {
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_601_4")]
+    Bt601,
+
+    #[default]
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_709_2")]
+    Bt709,
+
+    #[doc(alias = "kCVImageBufferYCbCrMatrix_ITU_R_2020")]
+    Bt2020,
+}
+
+impl Matrix {
+    /// Luma coefficients of red and blue `(Kr, Kb)`.
+    pub const fn kr_kb(&self) -> (f32, f32) {
+        match self {
+            Self::Bt601 => (0.299, 0.114),
+            Self::Bt709 => (0.2126, 0.0722),
+            Self::Bt2020 => (0.2627, 0.0593),
+        }
+    }
+
+    fn rgb_to_ycbcr(&self, [r, g, b, a]: [f32; 4]) -> [f32; 4] {
+        let (kr, kb) = self.kr_kb();
+        let y = kr * r + (1.0 - kr - kb) * g + kb * b;
+        [y, (b - y) / (2.0 - 2.0 * kb), (r - y) / (2.0 - 2.0 * kr), a]
+    }
+
+    fn ycbcr_to_rgb(&self, [y, cb, cr, a]: [f32; 4]) -> [f32; 4] {
+        let (kr, kb) = self.kr_kb();
+        let r = y + (2.0 - 2.0 * kr) * cr;
+        let b = y + (2.0 - 2.0 * kb) * cb;
+        let g = (y - kr * r - kb * b) / (1.0 - kr - kb);
+        [r, g, b, a]
+    }
+}
+
+/// Position of subsampled chroma samples relative to luma,
+/// see [`cv::image_buf_attachment::chroma_location`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChromaSiting {
+    /// Co-sited with the left column, centered vertically (MPEG-2, H.264 default).
+    #[default]
+    #[doc(alias = "kCVImageBufferChromaLocation_Left")]
+    Left,
+
+    /// Centered (JPEG, MPEG-1).
+    #[doc(alias = "kCVImageBufferChromaLocation_Center")]
+    Center,
+
+    /// Co-sited with the top-left luma sample (HEVC 4:2:0 HDR).
+    #[doc(alias = "kCVImageBufferChromaLocation_TopLeft")]
+    TopLeft,
+
+    #[doc(alias = "kCVImageBufferChromaLocation_Top")]
+    Top,
+
+    #[doc(alias = "kCVImageBufferChromaLocation_BottomLeft")]
+    BottomLeft,
+
+    #[doc(alias = "kCVImageBufferChromaLocation_Bottom")]
+    Bottom,
+}
+
+impl ChromaSiting {
+    /// Chroma sample offset within its block as a fraction of the block size minus one.
+    const fn offset(&self) -> (f32, f32) {
+        match self {
+            Self::Left => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::TopLeft => (0.0, 0.0),
+            Self::Top => (0.5, 0.0),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::Bottom => (0.5, 1.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Plane<'a> {
+    pub data: &'a [u8],
+    pub bytes_per_row: usize,
+}
+
+impl<'a> Plane<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8], bytes_per_row: usize) -> Self {
+        Self {
+            data,
+            bytes_per_row,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PlaneMut<'a> {
+    pub data: &'a mut [u8],
+    pub bytes_per_row: usize,
+}
+
+impl<'a> PlaneMut<'a> {
+    #[inline]
+    pub fn new(data: &'a mut [u8], bytes_per_row: usize) -> Self {
+        Self {
+            data,
+            bytes_per_row,
+        }
+    }
+}
+
+/// Source image as a set of planes in the order [`cv::PixelFormatLayout::planes`] lists them.
+#[derive(Debug, Clone)]
+pub struct Image<'a> {
+    pub pixel_format: cv::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub planes: Vec<Plane<'a>>,
+}
+
+impl<'a> Image<'a> {
+    #[inline]
+    pub fn new(
+        pixel_format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: Vec<Plane<'a>>,
+    ) -> Self {
+        Self {
+            pixel_format,
+            width,
+            height,
+            planes,
+        }
+    }
+}
+
+/// Destination image as a set of planes in the order [`cv::PixelFormatLayout::planes`] lists them.
+#[derive(Debug)]
+pub struct ImageMut<'a> {
+    pub pixel_format: cv::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub planes: Vec<PlaneMut<'a>>,
+}
+
+impl<'a> ImageMut<'a> {
+    #[inline]
+    pub fn new(
+        pixel_format: cv::PixelFormat,
+        width: usize,
+        height: usize,
+        planes: Vec<PlaneMut<'a>>,
+    ) -> Self {
+        Self {
+            pixel_format,
+            width,
+            height,
+            planes,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Converter {
+    pub matrix: Matrix,
+    pub chroma_siting: ChromaSiting,
+}
+
+impl Converter {
+    #[inline]
+    pub fn new(matrix: Matrix, chroma_siting: ChromaSiting) -> Self {
+        Self {
+            matrix,
+            chroma_siting,
+        }
+    }
+
+    pub fn convert(&self, src: &Image, dst: &mut ImageMut) -> Result<(), Error> {
+        if src.width != dst.width || src.height != dst.height {
+            return Err(Error::Size);
+        }
+        let (w, h) = (src.width, src.height);
+        let src_fmt = Format::with_pixel_format(src.pixel_format)?;
+        let dst_fmt = Format::with_pixel_format(dst.pixel_format)?;
+
+        let planes = src.planes.iter().map(|p| (p.data.len(), p.bytes_per_row));
+        src_fmt.check(planes, w, h)?;
+        let planes = dst.planes.iter().map(|p| (p.data.len(), p.bytes_per_row));
+        dst_fmt.check(planes, w, h)?;
+        if w == 0 || h == 0 {
+            return Ok(());
+        }
+
+        let mut pixels = vec![[0.0, 0.0, 0.0, 1.0]; w * h];
+        for (fmt, plane) in src_fmt.planes.iter().zip(src.planes.iter()) {
+            for (slot, mut grid) in fmt.read(plane, &src_fmt, w, h) {
+                grid.upsample(&mut pixels, slot, w, h, self.chroma_siting);
+            }
+        }
+
+        match (src_fmt.model, dst_fmt.model) {
+            (Model::Rgb, Model::YCbCr) => pixels
+                .iter_mut()
+                .for_each(|p| *p = self.matrix.rgb_to_ycbcr(*p)),
+            (Model::YCbCr, Model::Rgb) => pixels
+                .iter_mut()
+                .for_each(|p| *p = self.matrix.ycbcr_to_rgb(*p)),
+            _ => {}
+        }
+
+        for (fmt, plane) in dst_fmt.planes.iter().zip(dst.planes.iter_mut()) {
+            fmt.write(plane, &dst_fmt, &pixels, w, h, self.chroma_siting);
+        }
+        Ok(())
+    }
+}
+
+/// Normalized samples are R, G, B, A for RGB and Y', Cb, Cr, A for Y'CbCr,
+/// with Cb and Cr centered at zero.
+#[inline]
+fn slot(component: C) -> usize {
+    match component {
+        C::R | C::Y => 0,
+        C::G | C::Cb => 1,
+        C::B | C::Cr => 2,
+        _ => 3,
+    }
+}
+
+struct Format {
+    model: Model,
+    range: Range,
+    bit_depth: u32,
+    planes: Vec<PlaneFormat>,
+}
+
+struct PlaneFormat {
+    layout: &'static PlaneLayout,
+    sample_size: usize,
+}
+
+/// Samples of one slot at the plane resolution.
+struct Grid {
+    width: usize,
+    height: usize,
+    sub_x: usize,
+    sub_y: usize,
+    data: Vec<f32>,
+}
+
+impl Format {
+    fn with_pixel_format(pixel_format: cv::PixelFormat) -> Result<Self, Error> {
+        let unsupported = Error::Unsupported(pixel_format);
+        let layout: &Layout = pixel_format.layout().ok_or(unsupported)?;
+        let supported = !layout.is_compressed
+            && !layout.is_float
+            && match layout.model {
+                Model::Rgb => layout.bit_depth == 8,
+                Model::YCbCr => layout.bit_depth >= 8,
+                _ => false,
+            };
+        if !supported {
+            return Err(unsupported);
+        }
+
+        let mut planes = Vec::with_capacity(layout.planes.len());
+        for pl in layout.planes {
+            let n = pl.components.len();
+            let bits = pl.bits_per_block as usize;
+            let sample_size = if bits == n * 8 {
+                1
+            } else if bits == n * 16 && layout.model == Model::YCbCr {
+                2
+            } else {
+                return Err(unsupported);
+            };
+            let luma = pl.components.iter().filter(|c| **c == C::Y).count();
+            if pl.block_height != 1 || (luma != 0 && luma != pl.block_width as usize) {
+                return Err(unsupported);
+            }
+            planes.push(PlaneFormat {
+                layout: pl,
+                sample_size,
+            });
+        }
+
+        Ok(Self {
+            model: layout.model,
+            range: layout.range,
+            bit_depth: layout.bit_depth as u32,
+            planes,
+        })
+    }
+
+    fn check(
+        &self,
+        planes: impl ExactSizeIterator<Item = (usize, usize)>,
+        width: usize,
+        height: usize,
+    ) -> Result<(), Error> {
+        if planes.len() != self.planes.len() {
+            return Err(Error::Planes {
+                expected: self.planes.len(),
+                found: planes.len(),
+            });
+        }
+        for (i, (fmt, (len, bytes_per_row))) in self.planes.iter().zip(planes).enumerate() {
+            let row = fmt.layout.bytes_per_row(width, 0);
+            let rows = fmt.layout.rows(height);
+            if bytes_per_row < row || (rows > 0 && len < bytes_per_row * (rows - 1) + row) {
+                return Err(Error::Plane(i));
+            }
+        }
+        Ok(())
+    }
+
+    fn normalize(&self, slot: usize, code: u32) -> f32 {
+        let max = ((1u32 << self.bit_depth) - 1) as f32;
+        let scale = (1u32 << (self.bit_depth - 8)) as f32;
+        let code = code as f32;
+        match (self.range, self.model, slot) {
+            (_, _, 3) | (Range::Full, Model::Rgb, _) | (Range::Full, _, 0) => code / max,
+            (Range::Full, _, _) => (code - (1u32 << (self.bit_depth - 1)) as f32) / max,
+            (Range::Video, Model::YCbCr, 1 | 2) => (code - 128.0 * scale) / (224.0 * scale),
+            (Range::Video, _, _) => (code - 16.0 * scale) / (219.0 * scale),
+        }
+    }
+
+    fn code(&self, slot: usize, value: f32) -> u32 {
+        let max = ((1u32 << self.bit_depth) - 1) as f32;
+        let scale = (1u32 << (self.bit_depth - 8)) as f32;
+        let code = match (self.range, self.model, slot) {
+            (_, _, 3) | (Range::Full, Model::Rgb, _) | (Range::Full, _, 0) => value * max,
+            (Range::Full, _, _) => value * max + (1u32 << (self.bit_depth - 1)) as f32,
+            (Range::Video, Model::YCbCr, 1 | 2) => value * 224.0 * scale + 128.0 * scale,
+            (Range::Video, _, _) => value * 219.0 * scale + 16.0 * scale,
+        };
+        code.round().clamp(0.0, max) as u32
+    }
+}
+
+impl PlaneFormat {
+    /// Grid size and subsampling of `component` relative to the image.
+    fn grid(&self, component: C, width: usize, height: usize) -> Grid {
+        let pl = self.layout;
+        let per_block = pl.components.iter().filter(|c| **c == component).count();
+        let blocks = pl.width(width).div_ceil(pl.block_width as usize);
+        Grid {
+            width: blocks * per_block,
+            height: pl.rows(height),
+            sub_x: pl.h_subsampling as usize * pl.block_width as usize / per_block,
+            sub_y: pl.v_subsampling as usize,
+            data: Vec::new(),
+        }
+    }
+
+    /// Distinct components of the block with their grids.
+    fn grids(&self, width: usize, height: usize) -> Vec<(C, Grid)> {
+        let mut res: Vec<(C, Grid)> = Vec::with_capacity(4);
+        for c in self.layout.components {
+            if res.iter().all(|(r, _)| r != c) {
+                res.push((*c, self.grid(*c, width, height)));
+            }
+        }
+        res
+    }
+
+    fn read(&self, plane: &Plane, fmt: &Format, width: usize, height: usize) -> Vec<(usize, Grid)> {
+        let mut grids = self.grids(width, height);
+        for (_, grid) in grids.iter_mut() {
+            grid.data = vec![0.0; grid.width * grid.height];
+        }
+        let shift = self.sample_size as u32 * 8 - fmt.bit_depth;
+        let block_size = self.layout.bits_per_block as usize / 8;
+        let blocks = self
+            .layout
+            .width(width)
+            .div_ceil(self.layout.block_width as usize);
+        let mut counts = [0usize; 4];
+        for row in 0..self.layout.rows(height) {
+            let data = &plane.data[row * plane.bytes_per_row..];
+            for b in 0..blocks {
+                let block = &data[b * block_size..];
+                counts.fill(0);
+                for (i, c) in self.layout.components.iter().enumerate() {
+                    let code = match self.sample_size {
+                        1 => block[i] as u32,
+                        _ => u16::from_le_bytes([block[i * 2], block[i * 2 + 1]]) as u32 >> shift,
+                    };
+                    let slot = slot(*c);
+                    let (_, grid) = grids.iter_mut().find(|(g, _)| g == c).unwrap();
+                    let per_block = grid.width / blocks;
+                    grid.data[row * grid.width + b * per_block + counts[slot]] =
+                        fmt.normalize(slot, code);
+                    counts[slot] += 1;
+                }
+            }
+        }
+        grids.into_iter().map(|(c, g)| (slot(c), g)).collect()
+    }
+
+    fn write(
+        &self,
+        plane: &mut PlaneMut,
+        fmt: &Format,
+        pixels: &[[f32; 4]],
+        width: usize,
+        height: usize,
+        siting: ChromaSiting,
+    ) {
+        let mut grids = self.grids(width, height);
+        for (c, grid) in grids.iter_mut() {
+            grid.downsample(pixels, slot(*c), width, height, siting);
+        }
+        let block_size = self.layout.bits_per_block as usize / 8;
+        let blocks = self
+            .layout
+            .width(width)
+            .div_ceil(self.layout.block_width as usize);
+        let shift = self.sample_size as u32 * 8 - fmt.bit_depth;
+        let mut counts = [0usize; 4];
+        for row in 0..self.layout.rows(height) {
+            let data = &mut plane.data[row * plane.bytes_per_row..];
+            for b in 0..blocks {
+                let block = &mut data[b * block_size..];
+                counts.fill(0);
+                for (i, c) in self.layout.components.iter().enumerate() {
+                    let slot = slot(*c);
+                    let (_, grid) = grids.iter().find(|(g, _)| g == c).unwrap();
+                    let per_block = grid.width / blocks;
+                    let value = grid.data[row * grid.width + b * per_block + counts[slot]];
+                    counts[slot] += 1;
+                    let code = fmt.code(slot, value);
+                    match self.sample_size {
+                        1 => block[i] = code as u8,
+                        _ => block[i * 2..i * 2 + 2]
+                            .copy_from_slice(&((code << shift) as u16).to_le_bytes()),
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Grid {
+    /// Position of sample `i` in image pixels along an axis with subsampling `sub`.
+    #[inline]
+    fn pos(i: usize, sub: usize, offset: f32) -> f32 {
+        (i * sub) as f32 + offset * (sub - 1) as f32
+    }
+
+    /// Bilinear interpolation to the image resolution.
+    fn upsample(
+        &mut self,
+        pixels: &mut [[f32; 4]],
+        slot: usize,
+        width: usize,
+        height: usize,
+        siting: ChromaSiting,
+    ) {
+        if self.width == 0 || self.height == 0 {
+            return;
+        }
+        let (ox, oy) = siting.offset();
+        let coord = |p: usize, sub: usize, offset: f32, len: usize| {
+            let pos = (p as f32 - offset * (sub - 1) as f32) / sub as f32;
+            let pos = pos.clamp(0.0, (len - 1) as f32);
+            let i = pos.floor() as usize;
+            (i, (i + 1).min(len - 1), pos - i as f32)
+        };
+        for y in 0..height {
+            let (y0, y1, fy) = coord(y, self.sub_y, oy, self.height);
+            for x in 0..width {
+                let (x0, x1, fx) = coord(x, self.sub_x, ox, self.width);
+                let at = |x: usize, y: usize| self.data[y * self.width + x];
+                let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fx;
+                let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fx;
+                pixels[y * width + x][slot] = top + (bottom - top) * fy;
+            }
+        }
+    }
+
+    /// Triangle filter centered at each sample site.
+    fn downsample(
+        &mut self,
+        pixels: &[[f32; 4]],
+        slot: usize,
+        width: usize,
+        height: usize,
+        siting: ChromaSiting,
+    ) {
+        let (ox, oy) = siting.offset();
+        let taps = |i: usize, sub: usize, offset: f32, len: usize| {
+            let center = Self::pos(i, sub, offset);
+            let start = (center - sub as f32).floor().max(0.0) as usize;
+            let end = ((center + sub as f32).ceil() as usize).min(len - 1);
+            (start..=end).filter_map(move |p| {
+                let w = 1.0 - (p as f32 - center).abs() / sub as f32;
+                (w > 0.0).then_some((p, w))
+            })
+        };
+        self.data = Vec::with_capacity(self.width * self.height);
+        for j in 0..self.height {
+            for i in 0..self.width {
+                let (mut sum, mut weight) = (0.0, 0.0);
+                for (y, wy) in taps(j, self.sub_y, oy, height) {
+                    for (x, wx) in taps(i, self.sub_x, ox, width) {
+                        sum += pixels[y * width + x][slot] * wx * wy;
+                        weight += wx * wy;
+                    }
+                }
+                self.data
+                    .push(if weight > 0.0 { sum / weight } else { 0.0 });
+            }
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl Matrix {
+    /// Parses [`cv::image_buf_attachment::ycbcr_matrix`] values.
+    pub fn from_cf_string(value: &cf::String) -> Option<Self> {
+        use cv::image_buf_attachment::ycbcr_matrix as m;
+        [
+            (m::itu_r_601_4(), Self::Bt601),
+            (m::itu_r_709_2(), Self::Bt709),
+            (m::itu_r_2020(), Self::Bt2020),
+        ]
+        .into_iter()
+        .find_map(|(s, m)| value.equal(s).then_some(m))
+    }
+
+    pub fn to_cf_string(self) -> &'static cf::String {
+        use cv::image_buf_attachment::ycbcr_matrix as m;
+        match self {
+            Self::Bt601 => m::itu_r_601_4(),
+            Self::Bt709 => m::itu_r_709_2(),
+            Self::Bt2020 => m::itu_r_2020(),
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl ChromaSiting {
+    /// Parses [`cv::image_buf_attachment::chroma_location`] values.
+    pub fn from_cf_string(value: &cf::String) -> Option<Self> {
+        use cv::image_buf_attachment::chroma_location as l;
+        [
+            (l::left(), Self::Left),
+            (l::center(), Self::Center),
+            (l::top_left(), Self::TopLeft),
+            (l::top(), Self::Top),
+            (l::bottom_left(), Self::BottomLeft),
+            (l::bottom(), Self::Bottom),
+        ]
+        .into_iter()
+        .find_map(|(s, siting)| value.equal(s).then_some(siting))
+    }
+
+    pub fn to_cf_string(self) -> &'static cf::String {
+        use cv::image_buf_attachment::chroma_location as l;
+        match self {
+            Self::Left => l::left(),
+            Self::Center => l::center(),
+            Self::TopLeft => l::top_left(),
+            Self::Top => l::top(),
+            Self::BottomLeft => l::bottom_left(),
+            Self::Bottom => l::bottom(),
+        }
+    }
+}
+
+#[cfg(target_vendor = "apple")]
+impl Converter {
+    /// Converts between two pixel buffers of the same size, locking their base addresses.
+    pub fn convert_pixel_buf(
+        &self,
+        src: &cv::PixelBuf,
+        dst: &mut cv::PixelBuf,
+    ) -> Result<(), Error> {
+        use cv::pixel_buffer::LockFlags;
+
+        if src.width() != dst.width() || src.height() != dst.height() {
+            return Err(Error::Size);
+        }
+        Format::with_pixel_format(src.pixel_format())?;
+        Format::with_pixel_format(dst.pixel_format())?;
+
+        let _src_lock = src
+            .base_address_lock(LockFlags::READ_ONLY)
+            .map_err(Error::Cv)?;
+        let _dst_lock = dst
+            .base_address_lock(LockFlags::DEFAULT)
+            .map_err(Error::Cv)?;
+
+        // every plane is checked before any slice is built
+        let src_planes = pixel_buf_planes(src)?;
+        let dst_planes = pixel_buf_planes(dst)?;
+
+        let src_planes = src_planes
+            .into_iter()
+            .map(|(ptr, len, bpr)| Plane::new(unsafe { std::slice::from_raw_parts(ptr, len) }, bpr))
+            .collect();
+        let dst_planes = dst_planes
+            .into_iter()
+            .map(|(ptr, len, bpr)| {
+                PlaneMut::new(unsafe { std::slice::from_raw_parts_mut(ptr, len) }, bpr)
+            })
+            .collect();
+
+        let src = Image::new(src.pixel_format(), src.width(), src.height(), src_planes);
+        let mut dst = ImageMut::new(dst.pixel_format(), dst.width(), dst.height(), dst_planes);
+        self.convert(&src, &mut dst)
+    }
+}
+
+/// `(base address, len, bytes per row)` of each plane of a locked buffer.
+#[cfg(target_vendor = "apple")]
+fn pixel_buf_planes(buf: &cv::PixelBuf) -> Result<Vec<(*mut u8, usize, usize)>, Error> {
+    let plane = |index, ptr: *mut u8, bpr: usize, height: usize| {
+        if ptr.is_null() {
+            return Err(Error::NullPlane(index));
+        }
+        let len = bpr.checked_mul(height).ok_or(Error::Plane(index))?;
+        Ok((ptr, len, bpr))
+    };
+    if !buf.is_planar() {
+        let ptr = buf.base_addr().cast();
+        return Ok(vec![plane(0, ptr, buf.bytes_per_row(), buf.height())?]);
+    }
+    (0..buf.plane_count())
+        .map(|i| {
+            let ptr = buf.plane_base_addr(i).cast();
+            plane(i, ptr, buf.plane_bytes_per_row(i), buf.plane_height(i))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cv;
+
+    /// Allocates tightly packed planes for `pixel_format`.
+    fn alloc(pixel_format: cv::PixelFormat, w: usize, h: usize) -> Vec<(Vec<u8>, usize)> {
+        let layout = pixel_format.layout().unwrap();
+        layout
+            .planes
+            .iter()
+            .map(|p| (vec![0u8; p.size(w, h, 0)], p.bytes_per_row(w, 0)))
+            .collect()
+    }
+
+    fn convert(
+        converter: &cv::PixelConverter,
+        src_format: cv::PixelFormat,
+        src: &[(Vec<u8>, usize)],
+        dst_format: cv::PixelFormat,
+        w: usize,
+        h: usize,
+    ) -> Vec<(Vec<u8>, usize)> {
+        let mut dst = alloc(dst_format, w, h);
+        let src_img = cv::PixelImage::new(
+            src_format,
+            w,
+            h,
+            src.iter()
+                .map(|(d, bpr)| cv::PixelPlane::new(d, *bpr))
+                .collect(),
+        );
+        let mut dst_img = cv::PixelImageMut::new(
+            dst_format,
+            w,
+            h,
+            dst.iter_mut()
+                .map(|(d, bpr)| cv::PixelPlaneMut::new(d, *bpr))
+                .collect(),
+        );
+        converter.convert(&src_img, &mut dst_img).unwrap();
+        dst
+    }
+
+    fn gradient(w: usize, h: usize) -> Vec<(Vec<u8>, usize)> {
+        let mut bgra = Vec::with_capacity(w * h * 4);
+        for y in 0..h {
+            for x in 0..w {
+                let r = (x * 255 / (w - 1)) as u8;
+                let g = (y * 255 / (h - 1)) as u8;
+                bgra.extend_from_slice(&[128, g, r, 255]);
+            }
+        }
+        vec![(bgra, w * 4)]
+    }
+
+    fn max_diff(a: &[u8], b: &[u8]) -> u8 {
+        a.iter().zip(b).map(|(a, b)| a.abs_diff(*b)).max().unwrap()
+    }
+
+    #[test]
+    fn roundtrips() {
+        let (w, h) = (64, 32);
+        let src = gradient(w, h);
+        let formats = [
+            cv::PixelFormat::_420V,
+            cv::PixelFormat::_420F,
+            cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            cv::PixelFormat::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+            cv::PixelFormat::_444_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+            cv::PixelFormat::_420_YP_CB_CR_8_PLANAR_FULL_RANGE,
+            cv::PixelFormat::_2VUY,
+            cv::PixelFormat::_4444_YP_CB_CR_A_8,
+        ];
+        let matrices = [
+            cv::YCbCrMatrix::Bt601,
+            cv::YCbCrMatrix::Bt709,
+            cv::YCbCrMatrix::Bt2020,
+        ];
+        for format in formats {
+            for matrix in matrices {
+                let converter = cv::PixelConverter::new(matrix, cv::ChromaSiting::Center);
+                let ycbcr = convert(&converter, cv::PixelFormat::_32_BGRA, &src, format, w, h);
+                let bgra = convert(&converter, format, &ycbcr, cv::PixelFormat::_32_BGRA, w, h);
+                // smooth gradient survives chroma subsampling, edges are off the most
+                let diff = max_diff(&src[0].0, &bgra[0].0);
+                assert!(diff <= 8, "{format:?} {matrix:?} {diff}");
+            }
+        }
+    }
+
+    #[test]
+    fn swizzle() {
+        let converter = cv::PixelConverter::default();
+        let src = vec![(vec![1, 2, 3, 4, 5, 6, 7, 8], 8)];
+        let rgba = convert(
+            &converter,
+            cv::PixelFormat::_32_BGRA,
+            &src,
+            cv::PixelFormat::_32_RGBA,
+            2,
+            1,
+        );
+        assert_eq!(rgba[0].0, [3, 2, 1, 4, 7, 6, 5, 8]);
+        let argb = convert(
+            &converter,
+            cv::PixelFormat::_32_RGBA,
+            &rgba,
+            cv::PixelFormat::_32_ARGB,
+            2,
+            1,
+        );
+        assert_eq!(argb[0].0, [4, 3, 2, 1, 8, 7, 6, 5]);
+    }
+
+    #[test]
+    fn codes() {
+        let converter = cv::PixelConverter::default();
+        let white = vec![(vec![255; 4 * 4], 8)];
+
+        let x420 = cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE;
+        let planes = convert(&converter, cv::PixelFormat::_32_BGRA, &white, x420, 2, 2);
+        let y = u16::from_le_bytes([planes[0].0[0], planes[0].0[1]]);
+        let cb = u16::from_le_bytes([planes[1].0[0], planes[1].0[1]]);
+        assert_eq!(y >> 6, 940);
+        assert_eq!(cb >> 6, 512);
+
+        let f420 = cv::PixelFormat::_420_YP_CB_CR_8_PLANAR_FULL_RANGE;
+        let planes = convert(&converter, cv::PixelFormat::_32_BGRA, &white, f420, 2, 2);
+        assert_eq!(planes[0].0, [255; 4]);
+        assert_eq!(planes[1].0, [128]);
+        assert_eq!(planes[2].0, [128]);
+
+        // 2vuy black and white pair
+        let src = vec![(vec![0, 0, 0, 255, 255, 255, 255, 255], 8)];
+        let yuyv = convert(
+            &converter,
+            cv::PixelFormat::_32_BGRA,
+            &src,
+            cv::PixelFormat::_2VUY,
+            2,
+            1,
+        );
+        assert_eq!(yuyv[0].0, [128, 16, 128, 235]);
+    }
+
+    #[test]
+    fn siting() {
+        // single chroma sample for 4 luma pixels in a row
+        let (w, h) = (4, 2);
+        let mut luma = vec![128u8; w * h];
+        let chroma = [16u8, 128, 240, 128];
+        luma.truncate(w * h);
+        let src = vec![(luma, w), (chroma.to_vec(), 4)];
+
+        let left = cv::PixelConverter::new(cv::YCbCrMatrix::Bt709, cv::ChromaSiting::Left);
+        let center = cv::PixelConverter::new(cv::YCbCrMatrix::Bt709, cv::ChromaSiting::Center);
+
+        let a = convert(
+            &left,
+            cv::PixelFormat::_420V,
+            &src,
+            cv::PixelFormat::_32_BGRA,
+            w,
+            h,
+        );
+        let b = convert(
+            &center,
+            cv::PixelFormat::_420V,
+            &src,
+            cv::PixelFormat::_32_BGRA,
+            w,
+            h,
+        );
+
+        // co-sited sample reproduces the chroma exactly at x = 0
+        // while the centered one interpolates towards the next sample
+        assert_eq!(a[0].0[..4], b[0].0[..4]);
+        assert_ne!(a[0].0[4..8], b[0].0[4..8]);
+    }
+
+    #[test]
+    fn errors() {
+        let converter = cv::PixelConverter::default();
+        let data = vec![0u8; 16];
+        let src = cv::PixelImage::new(
+            cv::PixelFormat::_32_BGRA,
+            2,
+            2,
+            vec![cv::PixelPlane::new(&data, 8)],
+        );
+
+        let mut out = vec![0u8; 16];
+        let mut dst = cv::PixelImageMut::new(
+            cv::PixelFormat::_422_YP_CB_CR_10,
+            2,
+            2,
+            vec![cv::PixelPlaneMut::new(&mut out, 8)],
+        );
+        assert_eq!(
+            converter.convert(&src, &mut dst),
+            Err(cv::PixelConvertError::Unsupported(
+                cv::PixelFormat::_422_YP_CB_CR_10
+            ))
+        );
+
+        dst.pixel_format = cv::PixelFormat::_420V;
+        assert_eq!(
+            converter.convert(&src, &mut dst),
+            Err(cv::PixelConvertError::Planes {
+                expected: 2,
+                found: 1
+            })
+        );
+
+        dst.pixel_format = cv::PixelFormat::_32_RGBA;
+        dst.height = 3;
+        assert_eq!(
+            converter.convert(&src, &mut dst),
+            Err(cv::PixelConvertError::Size)
+        );
+
+        let src = cv::PixelImage::new(
+            cv::PixelFormat::_32_BGRA,
+            2,
+            3,
+            vec![cv::PixelPlane::new(&data, 8)],
+        );
+        assert_eq!(
+            converter.convert(&src, &mut dst),
+            Err(cv::PixelConvertError::Plane(0))
+        );
+    }
+
+    #[cfg(target_vendor = "apple")]
+    #[test]
+    fn pixel_bufs() {
+        let converter = cv::PixelConverter::default();
+        let src = cv::PixelBuf::new(64, 32, cv::PixelFormat::_32_BGRA, None).unwrap();
+        let mut dst = cv::PixelBuf::new(64, 32, cv::PixelFormat::_420V, None).unwrap();
+        converter.convert_pixel_buf(&src, &mut dst).unwrap();
+
+        let mut small = cv::PixelBuf::new(32, 32, cv::PixelFormat::_420V, None).unwrap();
+        assert_eq!(
+            converter.convert_pixel_buf(&src, &mut small),
+            Err(cv::PixelConvertError::Size)
+        );
+
+        let mut v210 = cv::PixelBuf::new(64, 32, cv::PixelFormat::_422_YP_CB_CR_10, None).unwrap();
+        assert_eq!(
+            converter.convert_pixel_buf(&src, &mut v210),
+            Err(cv::PixelConvertError::Unsupported(
+                cv::PixelFormat::_422_YP_CB_CR_10
+            ))
+        );
+    }
+}
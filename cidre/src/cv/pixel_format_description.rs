@@ -80,6 +80,47 @@ pub mod keys {
         unsafe { kCVPixelFormatComponentRange }
     }
 
+    /// Array of per-plane description dictionaries for planar formats
+    #[inline]
+    pub fn planes() -> &'static cf::String {
+        unsafe { kCVPixelFormatPlanes }
+    }
+
+    #[inline]
+    pub fn block_width() -> &'static cf::String {
+        unsafe { kCVPixelFormatBlockWidth }
+    }
+
+    #[inline]
+    pub fn block_height() -> &'static cf::String {
+        unsafe { kCVPixelFormatBlockHeight }
+    }
+
+    #[inline]
+    pub fn bits_per_block() -> &'static cf::String {
+        unsafe { kCVPixelFormatBitsPerBlock }
+    }
+
+    #[inline]
+    pub fn block_horizontal_alignment() -> &'static cf::String {
+        unsafe { kCVPixelFormatBlockHorizontalAlignment }
+    }
+
+    #[inline]
+    pub fn block_vertical_alignment() -> &'static cf::String {
+        unsafe { kCVPixelFormatBlockVerticalAlignment }
+    }
+
+    #[inline]
+    pub fn horizontal_subsampling() -> &'static cf::String {
+        unsafe { kCVPixelFormatHorizontalSubsampling }
+    }
+
+    #[inline]
+    pub fn vertical_subsampling() -> &'static cf::String {
+        unsafe { kCVPixelFormatVerticalSubsampling }
+    }
+
     extern "C" {
         static kCVPixelFormatName: &'static cf::String;
         static kCVPixelFormatConstant: &'static cf::String;
@@ -90,6 +131,14 @@ pub mod keys {
         static kCVPixelFormatContainsRGB: &'static cf::String;
         static kCVPixelFormatContainsGrayscale: &'static cf::String;
         static kCVPixelFormatComponentRange: &'static cf::String;
+        static kCVPixelFormatPlanes: &'static cf::String;
+        static kCVPixelFormatBlockWidth: &'static cf::String;
+        static kCVPixelFormatBlockHeight: &'static cf::String;
+        static kCVPixelFormatBitsPerBlock: &'static cf::String;
+        static kCVPixelFormatBlockHorizontalAlignment: &'static cf::String;
+        static kCVPixelFormatBlockVerticalAlignment: &'static cf::String;
+        static kCVPixelFormatHorizontalSubsampling: &'static cf::String;
+        static kCVPixelFormatVerticalSubsampling: &'static cf::String;
     }
 }
 
@@ -101,8 +150,20 @@ pub mod component_range {
         unsafe { kCVPixelFormatComponentRange_VideoRange }
     }
 
+    #[inline]
+    pub fn component_range_full_range() -> &'static cf::String {
+        unsafe { kCVPixelFormatComponentRange_FullRange }
+    }
+
+    #[inline]
+    pub fn component_range_wide_range() -> &'static cf::String {
+        unsafe { kCVPixelFormatComponentRange_WideRange }
+    }
+
     extern "C" {
         static kCVPixelFormatComponentRange_VideoRange: &'static cf::String;
+        static kCVPixelFormatComponentRange_FullRange: &'static cf::String;
+        static kCVPixelFormatComponentRange_WideRange: &'static cf::String;
     }
 }
 
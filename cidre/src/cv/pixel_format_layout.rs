@@ -0,0 +1,657 @@
+//! Static memory layout descriptors for [`cv::PixelFormat`].
+//!
+//! [`cv::pixel_format_description::create`] gives the same information as an
+//! untyped dictionary and only where CoreVideo is around. This table lets
+//! allocation sizing, copy routines and IOSurface planning work anywhere.
+//!
+//! ```
+//! use cidre::cv;
+//!
+//! let layout = cv::PixelFormat::_420V.layout().unwrap();
+//! assert_eq!(layout.planes.len(), 2);
+//! assert_eq!(layout.range, cv::PixelFormatRange::Video);
+//!
+//! // 1920x1080 NV12 with 64 byte row alignment
+//! assert_eq!(layout.bytes_per_row(0, 1920, 64), Some(1920));
+//! assert_eq!(layout.plane_size(1, 1920, 1080, 64), Some(1920 * 540));
+//! assert_eq!(layout.size(1920, 1080, 64), Some(1920 * 1080 * 3 / 2));
+//! ```
+
+use crate::cv::{self, PixelFormat as F};
+
+use ColorModel::{Generic, Gray, Indexed, Rgb, YCbCr};
+use Component::*;
+use Range::{Full, Video};
+
+/// Single component of a pixel in memory order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Component {
+    R,
+    G,
+    B,
+    A,
+    Y,
+    Cb,
+    Cr,
+    /// Palette index
+    I,
+    /// Gray level
+    L,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ColorModel {
+    Indexed,
+    Gray,
+    Rgb,
+    YCbCr,
+    /// One or two component formats without color semantics
+    /// (`cv::PixelFormat::ONE_COMPONENT_8`, `cv::PixelFormat::TWO_COMPONENT_16_HALF` etc).
+    /// Components are named after metal channels (r8Unorm, rg16Float).
+    Generic,
+}
+
+/// Range of the color components. Alpha is always full range.
+#[doc(alias = "kCVPixelFormatComponentRange")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Range {
+    #[doc(alias = "kCVPixelFormatComponentRange_FullRange")]
+    Full,
+    #[doc(alias = "kCVPixelFormatComponentRange_VideoRange")]
+    Video,
+}
+
+/// Layout of a single plane.
+///
+/// A block is the smallest addressable group of pixels (`block_width` x `block_height`)
+/// taking `bits_per_block` bits. Subsampling is relative to the full image size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlaneLayout {
+    /// Components of a block in memory order (padding bits are omitted)
+    pub components: &'static [Component],
+
+    #[doc(alias = "kCVPixelFormatBitsPerBlock")]
+    pub bits_per_block: u16,
+
+    #[doc(alias = "kCVPixelFormatBlockWidth")]
+    pub block_width: u8,
+
+    #[doc(alias = "kCVPixelFormatBlockHeight")]
+    pub block_height: u8,
+
+    #[doc(alias = "kCVPixelFormatHorizontalSubsampling")]
+    pub h_subsampling: u8,
+
+    #[doc(alias = "kCVPixelFormatVerticalSubsampling")]
+    pub v_subsampling: u8,
+
+    /// Rows are padded to a multiple of this number of blocks
+    #[doc(alias = "kCVPixelFormatBlockHorizontalAlignment")]
+    pub block_h_alignment: u8,
+}
+
+impl PlaneLayout {
+    const fn new(components: &'static [Component], bits_per_block: u16) -> Self {
+        Self {
+            components,
+            bits_per_block,
+            block_width: 1,
+            block_height: 1,
+            h_subsampling: 1,
+            v_subsampling: 1,
+            block_h_alignment: 1,
+        }
+    }
+
+    const fn block(mut self, width: u8, height: u8) -> Self {
+        self.block_width = width;
+        self.block_height = height;
+        self
+    }
+
+    const fn subsampled(mut self, h: u8, v: u8) -> Self {
+        self.h_subsampling = h;
+        self.v_subsampling = v;
+        self
+    }
+
+    const fn aligned(mut self, blocks: u8) -> Self {
+        self.block_h_alignment = blocks;
+        self
+    }
+
+    /// Width of the plane in pixels for the image `width`.
+    #[inline]
+    pub fn width(&self, width: usize) -> usize {
+        width.div_ceil(self.h_subsampling as usize)
+    }
+
+    /// Height of the plane in pixels for the image `height`.
+    #[inline]
+    pub fn height(&self, height: usize) -> usize {
+        height.div_ceil(self.v_subsampling as usize)
+    }
+
+    /// Number of block rows for the image `height`.
+    #[inline]
+    pub fn rows(&self, height: usize) -> usize {
+        self.height(height).div_ceil(self.block_height as usize)
+    }
+
+    /// Bytes per row for the image `width` with rows padded to `align` bytes.
+    ///
+    /// `align` of 0 or 1 means tightly packed.
+    pub fn bytes_per_row(&self, width: usize, align: usize) -> usize {
+        let blocks = self.width(width).div_ceil(self.block_width as usize);
+        let blocks = round_up(blocks, self.block_h_alignment as usize);
+        let bytes = (blocks * self.bits_per_block as usize).div_ceil(8);
+        round_up(bytes, align)
+    }
+
+    /// Size of the plane in bytes for the image `width` x `height`.
+    #[inline]
+    pub fn size(&self, width: usize, height: usize, align: usize) -> usize {
+        self.bytes_per_row(width, align) * self.rows(height)
+    }
+}
+
+/// Memory layout of a [`cv::PixelFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Layout {
+    pub pixel_format: cv::PixelFormat,
+    pub planes: &'static [PlaneLayout],
+    pub model: ColorModel,
+    pub range: Range,
+
+    /// Bits per color component, the widest one for formats like 565
+    pub bit_depth: u8,
+
+    #[doc(alias = "kCVPixelFormatContainsAlpha")]
+    pub has_alpha: bool,
+
+    /// Components are IEEE half or single precision floats
+    pub is_float: bool,
+
+    /// Lossless or lossy compressed format. The planes describe the
+    /// uncompressed equivalent and can't be used for sizing.
+    pub is_compressed: bool,
+}
+
+impl Layout {
+    const fn new(
+        pixel_format: cv::PixelFormat,
+        model: ColorModel,
+        range: Range,
+        bit_depth: u8,
+        planes: &'static [PlaneLayout],
+    ) -> Self {
+        Self {
+            pixel_format,
+            planes,
+            model,
+            range,
+            bit_depth,
+            has_alpha: false,
+            is_float: false,
+            is_compressed: false,
+        }
+    }
+
+    const fn alpha(mut self) -> Self {
+        self.has_alpha = true;
+        self
+    }
+
+    const fn float(mut self) -> Self {
+        self.is_float = true;
+        self
+    }
+
+    const fn compressed(mut self, pixel_format: cv::PixelFormat) -> Self {
+        self.pixel_format = pixel_format;
+        self.is_compressed = true;
+        self
+    }
+
+    /// All known layouts.
+    #[inline]
+    pub fn all() -> &'static [Layout] {
+        &LAYOUTS
+    }
+
+    #[inline]
+    pub fn with_pixel_format(pixel_format: cv::PixelFormat) -> Option<&'static Self> {
+        LAYOUTS.iter().find(|l| l.pixel_format == pixel_format)
+    }
+
+    #[inline]
+    pub fn is_planar(&self) -> bool {
+        self.planes.len() > 1
+    }
+
+    #[inline]
+    pub fn is_rgb(&self) -> bool {
+        self.model == ColorModel::Rgb
+    }
+
+    #[inline]
+    pub fn is_ycbcr(&self) -> bool {
+        self.model == ColorModel::YCbCr
+    }
+
+    #[inline]
+    pub fn is_video_range(&self) -> bool {
+        self.range == Range::Video
+    }
+
+    #[inline]
+    pub fn plane(&self, index: usize) -> Option<&'static PlaneLayout> {
+        self.planes.get(index)
+    }
+
+    fn sizing_plane(&self, index: usize) -> Option<&'static PlaneLayout> {
+        if self.is_compressed {
+            return None;
+        }
+        self.plane(index)
+    }
+
+    /// Bytes per row of `plane` with rows padded to `align` bytes.
+    ///
+    /// Returns `None` for a missing plane or compressed format.
+    #[inline]
+    pub fn bytes_per_row(&self, plane: usize, width: usize, align: usize) -> Option<usize> {
+        Some(self.sizing_plane(plane)?.bytes_per_row(width, align))
+    }
+
+    /// Size of `plane` in bytes with rows padded to `align` bytes.
+    ///
+    /// Returns `None` for a missing plane or compressed format.
+    #[inline]
+    pub fn plane_size(
+        &self,
+        plane: usize,
+        width: usize,
+        height: usize,
+        align: usize,
+    ) -> Option<usize> {
+        Some(self.sizing_plane(plane)?.size(width, height, align))
+    }
+
+    /// Total size of all planes in bytes with rows padded to `align` bytes.
+    pub fn size(&self, width: usize, height: usize, align: usize) -> Option<usize> {
+        if self.is_compressed {
+            return None;
+        }
+        Some(
+            self.planes
+                .iter()
+                .map(|p| p.size(width, height, align))
+                .sum(),
+        )
+    }
+}
+
+impl cv::PixelFormat {
+    /// Static memory layout of the pixel format. Doesn't require CoreVideo.
+    #[inline]
+    pub fn layout(&self) -> Option<&'static Layout> {
+        Layout::with_pixel_format(*self)
+    }
+}
+
+#[inline]
+fn round_up(n: usize, align: usize) -> usize {
+    if align > 1 {
+        n.div_ceil(align) * align
+    } else {
+        n
+    }
+}
+
+const fn p(components: &'static [Component], bits_per_block: u16) -> PlaneLayout {
+    PlaneLayout::new(components, bits_per_block)
+}
+
+const INDEX_1: [PlaneLayout; 1] = [p(&[I], 1)];
+const INDEX_2: [PlaneLayout; 1] = [p(&[I], 2)];
+const INDEX_4: [PlaneLayout; 1] = [p(&[I], 4)];
+const INDEX_8: [PlaneLayout; 1] = [p(&[I], 8)];
+const GRAY_1: [PlaneLayout; 1] = [p(&[L], 1)];
+const GRAY_2: [PlaneLayout; 1] = [p(&[L], 2)];
+const GRAY_4: [PlaneLayout; 1] = [p(&[L], 4)];
+const GRAY_8: [PlaneLayout; 1] = [p(&[L], 8)];
+
+const RGB_16: [PlaneLayout; 1] = [p(&[R, G, B], 16)];
+const ARGB_16: [PlaneLayout; 1] = [p(&[A, R, G, B], 16)];
+const RGB_24: [PlaneLayout; 1] = [p(&[R, G, B], 24)];
+const BGR_24: [PlaneLayout; 1] = [p(&[B, G, R], 24)];
+const RGB_32: [PlaneLayout; 1] = [p(&[R, G, B], 32)];
+const ARGB_32: [PlaneLayout; 1] = [p(&[A, R, G, B], 32)];
+const BGRA_32: [PlaneLayout; 1] = [p(&[B, G, R, A], 32)];
+const ABGR_32: [PlaneLayout; 1] = [p(&[A, B, G, R], 32)];
+const RGBA_32: [PlaneLayout; 1] = [p(&[R, G, B, A], 32)];
+const ARGB_64: [PlaneLayout; 1] = [p(&[A, R, G, B], 64)];
+const RGBA_64: [PlaneLayout; 1] = [p(&[R, G, B, A], 64)];
+const RGBA_128: [PlaneLayout; 1] = [p(&[R, G, B, A], 128)];
+
+const R_8: [PlaneLayout; 1] = [p(&[R], 8)];
+const R_16: [PlaneLayout; 1] = [p(&[R], 16)];
+const R_32: [PlaneLayout; 1] = [p(&[R], 32)];
+const RG_32: [PlaneLayout; 1] = [p(&[R, G], 32)];
+const RG_64: [PlaneLayout; 1] = [p(&[R, G], 64)];
+
+const CB_Y_CR_Y_32: [PlaneLayout; 1] = [p(&[Cb, Y, Cr, Y], 32).block(2, 1)];
+const CB_Y_CR_Y_64: [PlaneLayout; 1] = [p(&[Cb, Y, Cr, Y], 64).block(2, 1)];
+const CB_Y_CR_A_32: [PlaneLayout; 1] = [p(&[Cb, Y, Cr, A], 32)];
+const A_Y_CB_CR_32: [PlaneLayout; 1] = [p(&[A, Y, Cb, Cr], 32)];
+const A_Y_CB_CR_64: [PlaneLayout; 1] = [p(&[A, Y, Cb, Cr], 64)];
+const A_Y_CB_CR_128: [PlaneLayout; 1] = [p(&[A, Y, Cb, Cr], 128)];
+const CR_Y_CB_24: [PlaneLayout; 1] = [p(&[Cr, Y, Cb], 24)];
+const CB_Y_CR_32: [PlaneLayout; 1] = [p(&[Cb, Y, Cr], 32)];
+
+/// 6 pixels in 4 little-endian 32-bit words, rows padded to 48 pixels (128 bytes)
+const V210: [PlaneLayout; 1] = [p(&[Cb, Y, Cr, Y, Cb, Y, Cr, Y, Cb, Y, Cr, Y], 128)
+    .block(6, 1)
+    .aligned(8)];
+
+const YUV_420_8_PLANAR: [PlaneLayout; 3] = [
+    p(&[Y], 8),
+    p(&[Cb], 8).subsampled(2, 2),
+    p(&[Cr], 8).subsampled(2, 2),
+];
+const YUV_422_8_ALPHA: [PlaneLayout; 2] = [p(&[Cb, Y, Cr, Y], 32).block(2, 1), p(&[A], 8)];
+const YUV_420_8_BI: [PlaneLayout; 2] = [p(&[Y], 8), p(&[Cb, Cr], 16).subsampled(2, 2)];
+const YUV_420_10_BI: [PlaneLayout; 2] = [p(&[Y], 16), p(&[Cb, Cr], 32).subsampled(2, 2)];
+const YUV_422_10_BI: [PlaneLayout; 2] = [p(&[Y], 16), p(&[Cb, Cr], 32).subsampled(2, 1)];
+const YUV_444_10_BI: [PlaneLayout; 2] = [p(&[Y], 16), p(&[Cb, Cr], 32)];
+const YUV_420_10_PACKED_BI: [PlaneLayout; 2] = [p(&[Y], 10), p(&[Cb, Cr], 20).subsampled(2, 2)];
+const YUV_422_10_PACKED_BI: [PlaneLayout; 2] = [p(&[Y], 10), p(&[Cb, Cr], 20).subsampled(2, 1)];
+
+const fn l(
+    pixel_format: cv::PixelFormat,
+    model: ColorModel,
+    range: Range,
+    bit_depth: u8,
+    planes: &'static [PlaneLayout],
+) -> Layout {
+    Layout::new(pixel_format, model, range, bit_depth, planes)
+}
+
+const BGRA: Layout = l(F::_32_BGRA, Rgb, Full, 8, &BGRA_32).alpha();
+const RGBA_HALF: Layout = l(F::_64_RGBA_HALF, Rgb, Full, 16, &RGBA_64).alpha().float();
+const YUV_420V: Layout = l(F::_420V, YCbCr, Video, 8, &YUV_420_8_BI);
+const YUV_420F: Layout = l(F::_420F, YCbCr, Full, 8, &YUV_420_8_BI);
+// packed 10-bit layouts only exist in compressed form
+const YUV_XV0: Layout = l(
+    F::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+    YCbCr,
+    Video,
+    10,
+    &YUV_420_10_PACKED_BI,
+);
+const YUV_XV2: Layout = l(
+    F::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+    YCbCr,
+    Video,
+    10,
+    &YUV_422_10_PACKED_BI,
+);
+
+static LAYOUTS: [Layout; 62] = [
+    l(F::_1_MONOCHROME, Indexed, Full, 1, &INDEX_1),
+    l(F::_2_INDEXED, Indexed, Full, 2, &INDEX_2),
+    l(F::_4_INDEXED, Indexed, Full, 4, &INDEX_4),
+    l(F::_8_INDEXED, Indexed, Full, 8, &INDEX_8),
+    l(F::_1_INDEXED_GREY_WHITE_IS_ZERO, Gray, Full, 1, &GRAY_1),
+    l(F::_2_INDEXED_GREY_WHITE_IS_ZERO, Gray, Full, 2, &GRAY_2),
+    l(F::_4_INDEXED_GREY_WHITE_IS_ZERO, Gray, Full, 4, &GRAY_4),
+    l(F::_8_INDEXED_GREY_WHITE_IS_ZERO, Gray, Full, 8, &GRAY_8),
+    l(F::_16_BE_555, Rgb, Full, 5, &RGB_16),
+    l(F::_16_LE_555, Rgb, Full, 5, &RGB_16),
+    l(F::_16_LE_5551, Rgb, Full, 5, &ARGB_16).alpha(),
+    l(F::_16_BE_565, Rgb, Full, 6, &RGB_16),
+    l(F::_16_LE_565, Rgb, Full, 6, &RGB_16),
+    l(F::_24_RGB, Rgb, Full, 8, &RGB_24),
+    l(F::_24_BGR, Rgb, Full, 8, &BGR_24),
+    l(F::_32_ARGB, Rgb, Full, 8, &ARGB_32).alpha(),
+    BGRA,
+    l(F::_32_ABGR, Rgb, Full, 8, &ABGR_32).alpha(),
+    l(F::_32_RGBA, Rgb, Full, 8, &RGBA_32).alpha(),
+    l(F::_64_ARGB, Rgb, Full, 16, &ARGB_64).alpha(),
+    l(F::_64_RGBALE, Rgb, Full, 16, &RGBA_64).alpha(),
+    l(F::_30_RGB, Rgb, Full, 10, &RGB_32),
+    l(F::_30_RGB_R210, Rgb, Video, 10, &RGB_32),
+    l(F::_422_YP_CB_CR_8, YCbCr, Video, 8, &CB_Y_CR_Y_32),
+    l(F::_4444_YP_CB_CR_A_8, YCbCr, Video, 8, &CB_Y_CR_A_32).alpha(),
+    l(F::_4444_YP_CB_CR_A_8_R, YCbCr, Full, 8, &A_Y_CB_CR_32).alpha(),
+    l(F::_4444_A_YP_CB_CR_8, YCbCr, Video, 8, &A_Y_CB_CR_32).alpha(),
+    l(F::_4444_A_YP_CB_CR_16, YCbCr, Video, 16, &A_Y_CB_CR_64).alpha(),
+    l(F::_4444_A_YP_CB_CR_FLOAT, YCbCr, Full, 32, &A_Y_CB_CR_128)
+        .alpha()
+        .float(),
+    l(F::_444_YP_CB_CR_8, YCbCr, Video, 8, &CR_Y_CB_24),
+    l(F::_422_YP_CB_CR_16, YCbCr, Video, 16, &CB_Y_CR_Y_64),
+    l(F::_422_YP_CB_CR_10, YCbCr, Video, 10, &V210),
+    l(F::_444_YP_CB_CR_10, YCbCr, Video, 10, &CB_Y_CR_32),
+    l(
+        F::_420_YP_CB_CR_8_PLANAR_FULL_RANGE,
+        YCbCr,
+        Full,
+        8,
+        &YUV_420_8_PLANAR,
+    ),
+    l(
+        F::_422_YP_CB_CR_4_A_8_BI_PLANAR,
+        YCbCr,
+        Video,
+        8,
+        &YUV_422_8_ALPHA,
+    )
+    .alpha(),
+    YUV_420V,
+    YUV_420F,
+    l(
+        F::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        YCbCr,
+        Video,
+        10,
+        &YUV_420_10_BI,
+    ),
+    l(
+        F::_422_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        YCbCr,
+        Video,
+        10,
+        &YUV_422_10_BI,
+    ),
+    l(
+        F::_444_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE,
+        YCbCr,
+        Video,
+        10,
+        &YUV_444_10_BI,
+    ),
+    l(
+        F::_420_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        YCbCr,
+        Full,
+        10,
+        &YUV_420_10_BI,
+    ),
+    l(
+        F::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        YCbCr,
+        Full,
+        10,
+        &YUV_422_10_BI,
+    ),
+    l(
+        F::_444_YP_CB_CR_10_BI_PLANAR_FULL_RANGE,
+        YCbCr,
+        Full,
+        10,
+        &YUV_444_10_BI,
+    ),
+    l(F::ARGB_2101010_LE_PACKED, Rgb, Full, 10, &ARGB_32).alpha(),
+    l(F::ONE_COMPONENT_8, Generic, Full, 8, &R_8),
+    l(F::ONE_COMPONENT_16_HALF, Generic, Full, 16, &R_16).float(),
+    l(F::ONE_COMPONENT_32_FLOAT, Generic, Full, 32, &R_32).float(),
+    l(F::TWO_COMPONENT_16_HALF, Generic, Full, 16, &RG_32).float(),
+    l(F::TWO_COMPONENT_32_FLOAT, Generic, Full, 32, &RG_64).float(),
+    RGBA_HALF,
+    l(F::_128_RGBA_FLOAT, Rgb, Full, 32, &RGBA_128)
+        .alpha()
+        .float(),
+    BGRA.compressed(F::LOSSLESS_32_BGRA),
+    RGBA_HALF.compressed(F::LOSSLESS_64_RGBA_HALF),
+    YUV_420V.compressed(F::LOSSLESS_420V),
+    YUV_420F.compressed(F::LOSSLESS_420F),
+    YUV_XV0.compressed(F::LOSSLESS_420_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE),
+    YUV_XV2.compressed(F::LOSSLESS_422_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE),
+    BGRA.compressed(F::LOSSY_32_BGRA),
+    YUV_420V.compressed(F::LOSSY_420V),
+    YUV_420F.compressed(F::LOSSY_420F),
+    YUV_XV0.compressed(F::LOSSY_420_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE),
+    YUV_XV2.compressed(F::LOSSY_422_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE),
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::cv;
+
+    #[test]
+    fn unique() {
+        let all = cv::PixelFormatLayout::all();
+        for (i, a) in all.iter().enumerate() {
+            assert!(
+                all[i + 1..]
+                    .iter()
+                    .all(|b| b.pixel_format != a.pixel_format),
+                "{:?}",
+                a.pixel_format
+            );
+            assert!(!a.planes.is_empty());
+            assert_eq!(a.pixel_format.layout(), Some(a));
+        }
+    }
+
+    #[test]
+    fn sizes() {
+        let bgra = cv::PixelFormat::_32_BGRA.layout().unwrap();
+        assert!(bgra.has_alpha && bgra.is_rgb() && !bgra.is_planar());
+        assert_eq!(bgra.bytes_per_row(0, 100, 0), Some(400));
+        assert_eq!(bgra.bytes_per_row(0, 100, 64), Some(448));
+        assert_eq!(bgra.bytes_per_row(1, 100, 64), None);
+        assert_eq!(bgra.size(100, 10, 64), Some(4480));
+
+        // odd sizes round chroma up
+        let nv12 = cv::PixelFormat::_420F.layout().unwrap();
+        assert_eq!(nv12.bytes_per_row(1, 7, 0), Some(8));
+        assert_eq!(nv12.plane_size(1, 7, 5, 0), Some(24));
+        assert_eq!(nv12.size(7, 5, 0), Some(35 + 24));
+
+        let p010 = cv::PixelFormat::_422_YP_CB_CR_10_BI_PLANAR_FULL_RANGE
+            .layout()
+            .unwrap();
+        assert_eq!(p010.bit_depth, 10);
+        assert_eq!(p010.plane_size(1, 1920, 1080, 0), Some(1920 * 2 * 1080));
+
+        let yuyv = cv::PixelFormat::_2VUY.layout().unwrap();
+        assert_eq!(yuyv.bytes_per_row(0, 3, 0), Some(8));
+        assert!(yuyv.is_video_range());
+
+        // v210 rows are 128 bytes per 48 pixels
+        let v210 = cv::PixelFormat::_422_YP_CB_CR_10.layout().unwrap();
+        assert_eq!(v210.bytes_per_row(0, 1280, 0), Some(3456));
+        assert_eq!(v210.bytes_per_row(0, 1920, 0), Some(5120));
+        assert_eq!(v210.bytes_per_row(0, 1, 0), Some(128));
+
+        let i420 = cv::PixelFormat::_420_YP_CB_CR_8_PLANAR_FULL_RANGE
+            .layout()
+            .unwrap();
+        assert_eq!(i420.planes.len(), 3);
+        assert_eq!(i420.size(640, 480, 0), Some(640 * 480 * 3 / 2));
+
+        let mono = cv::PixelFormat::_1_MONOCHROME.layout().unwrap();
+        assert_eq!(mono.bytes_per_row(0, 9, 0), Some(2));
+
+        let lossy = cv::PixelFormat::LOSSY_420V.layout().unwrap();
+        assert!(lossy.is_compressed);
+        assert_eq!(
+            lossy.planes,
+            cv::PixelFormat::_420V.layout().unwrap().planes
+        );
+        assert_eq!(lossy.size(16, 16, 0), None);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn matches_core_video() {
+        use crate::cf;
+        use cv::pixel_format_description::{component_range, keys};
+
+        type Desc = cf::DictionaryOf<cf::String, cf::Plist>;
+
+        fn num(desc: &Desc, key: &cf::String) -> Option<i32> {
+            desc.value(key)?.try_as_number()?.to_i32()
+        }
+
+        fn flag(desc: &Desc, key: &cf::String) -> Option<bool> {
+            Some(desc.value(key)?.try_as_boolean()?.value())
+        }
+
+        fn check_plane(f: cv::PixelFormat, desc: &Desc, plane: &cv::PixelFormatPlaneLayout) {
+            let bits = num(desc, keys::bits_per_block()).unwrap();
+            assert_eq!(bits, plane.bits_per_block as i32, "{f:?}");
+            let checks = [
+                (keys::block_width(), plane.block_width),
+                (keys::block_height(), plane.block_height),
+                (keys::horizontal_subsampling(), plane.h_subsampling),
+                (keys::vertical_subsampling(), plane.v_subsampling),
+                (keys::block_horizontal_alignment(), plane.block_h_alignment),
+            ];
+            for (key, val) in checks {
+                assert_eq!(num(desc, key).unwrap_or(1), val as i32, "{f:?} {key:?}");
+            }
+        }
+
+        for layout in cv::PixelFormatLayout::all() {
+            // compressed formats don't describe real memory layout
+            if layout.is_compressed {
+                continue;
+            }
+            let f = layout.pixel_format;
+            let Some(desc) = cv::pixel_format_description::create(f) else {
+                continue;
+            };
+            let desc: &Desc = unsafe { std::mem::transmute(desc.as_ref()) };
+
+            if let Some(planes) = desc.value(keys::planes()) {
+                let planes = planes.try_as_array().unwrap();
+                assert_eq!(planes.len(), layout.planes.len(), "{f:?}");
+                for (p, plane) in planes.iter().zip(layout.planes) {
+                    check_plane(f, p.try_as_dictionary().unwrap(), plane);
+                }
+            } else if desc.value(keys::bits_per_block()).is_some() {
+                assert_eq!(layout.planes.len(), 1, "{f:?}");
+                check_plane(f, desc, &layout.planes[0]);
+            }
+
+            if let Some(alpha) = flag(desc, keys::contains_alpha()) {
+                assert_eq!(alpha, layout.has_alpha, "{f:?}");
+            }
+            if let Some(rgb) = flag(desc, keys::contains_rgb()) {
+                assert_eq!(rgb, layout.is_rgb(), "{f:?}");
+            }
+            if let Some(ycbcr) = flag(desc, keys::contains_y_cb_cr()) {
+                assert_eq!(ycbcr, layout.is_ycbcr(), "{f:?}");
+            }
+            if let Some(range) = desc.value(keys::component_range()) {
+                let video = component_range::component_range_video_range();
+                let is_video = range.try_as_string().unwrap().equal(video);
+                assert_eq!(is_video, layout.is_video_range(), "{f:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! HTTP Live Streaming playlists (RFC 8216bis, low-latency extensions
+//! included) and a rolling-window segment writer for the fMP4 segments
+//! `av::AssetWriter` hands to its delegate. Only the adapter taking that
+//! delegate's arguments needs Apple's frameworks.
+//!
+//! ```
+//! use cidre::hls;
+//!
+//! let text = "#EXTM3U
+//! #EXT-X-VERSION:7
+//! #EXT-X-TARGETDURATION:6
+//! #EXT-X-MAP:URI=\"init.mp4\"
+//! #EXTINF:6,
+//! seg0.m4s
+//! #EXTINF:5.5,
+//! seg1.m4s
+//! #EXT-X-ENDLIST
+//! ";
+//! let playlist: hls::MediaPlaylist = text.parse().unwrap();
+//! assert_eq!(playlist.segments.len(), 2);
+//! assert_eq!(playlist.segments[0].map.as_ref().unwrap().uri, "init.mp4");
+//! assert_eq!(playlist.duration(), 11.5);
+//! assert_eq!(playlist.to_string(), text);
+//! ```
+
+use std::time::{Duration, SystemTime};
+
+mod playlist;
+pub use playlist::ByteRange;
+pub use playlist::Map;
+pub use playlist::MediaPlaylist;
+pub use playlist::MultivariantPlaylist;
+pub use playlist::Part;
+pub use playlist::Playlist;
+pub use playlist::PlaylistType;
+pub use playlist::PreloadHint;
+pub use playlist::PreloadHintType;
+pub use playlist::Rendition;
+pub use playlist::RenditionType;
+pub use playlist::Segment;
+pub use playlist::ServerControl;
+pub use playlist::Variant;
+
+mod parse;
+
+mod segmenter;
+pub use segmenter::Segmenter;
+pub use segmenter::SegmenterCfg;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Playlist doesn't start with `#EXTM3U`.
+    Header,
+
+    /// Malformed tag or attribute on a 1 based line.
+    Line(usize, &'static str),
+
+    /// A segment or part was pushed out of order or before the init segment.
+    State(&'static str),
+
+    /// Segment whose rounded duration exceeds the target duration.
+    SegmentDuration {
+        duration: f64,
+        target: u32,
+    },
+
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Header => write!(f, "playlist doesn't start with #EXTM3U"),
+            Self::Line(line, what) => write!(f, "line {line}: invalid {what}"),
+            Self::State(what) => write!(f, "{what}"),
+            Self::SegmentDuration { duration, target } => {
+                write!(
+                    f,
+                    "segment of {duration}s exceeds the {target}s target duration"
+                )
+            }
+            Self::Io(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date.
+fn days_from_civil(y: i64, m: u32, d: u32) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    (yoe + era * 400 + (m <= 2) as i64, m, d)
+}
+
+/// `EXT-X-PROGRAM-DATE-TIME` value in UTC with millisecond precision.
+fn fmt_date_time(time: SystemTime) -> String {
+    let ms = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(d) => d.as_millis() as i64,
+        Err(e) => -(e.duration().as_millis() as i64),
+    };
+    let (days, ms) = (ms.div_euclid(86_400_000), ms.rem_euclid(86_400_000));
+    let (y, m, d) = civil_from_days(days);
+    let s = ms / 1000;
+    format!(
+        "{y:04}-{m:02}-{d:02}T{:02}:{:02}:{:02}.{:03}Z",
+        s / 3600,
+        s / 60 % 60,
+        s % 60,
+        ms % 1000
+    )
+}
+
+/// ISO 8601 date and time with an optional fraction and a `Z` or `±hh:mm` offset.
+fn parse_date_time(s: &str) -> Option<SystemTime> {
+    let num = |r: std::ops::Range<usize>| -> Option<u32> {
+        let v = s.get(r)?;
+        v.bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| v.parse().ok())?
+    };
+    let b = s.as_bytes();
+    if b.len() < 19
+        || b[4] != b'-'
+        || b[7] != b'-'
+        || b[10] != b'T'
+        || b[13] != b':'
+        || b[16] != b':'
+    {
+        return None;
+    }
+    let (y, mo, d) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (h, mi, sec) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&mo) || !(1..=31).contains(&d) || h > 23 || mi > 59 || sec > 60 {
+        return None;
+    }
+
+    let mut rest = &s[19..];
+    let mut nanos = 0u32;
+    if let Some(frac) = rest.strip_prefix('.') {
+        let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        for (i, c) in frac.bytes().take(len.min(9)).enumerate() {
+            nanos += (c - b'0') as u32 * 10u32.pow(8 - i as u32);
+        }
+        rest = &frac[len..];
+    }
+    let offset = match rest.as_bytes() {
+        b"Z" | b"z" => 0,
+        [sign @ (b'+' | b'-'), ..] if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let h: i64 = rest[1..3].parse().ok()?;
+            let m: i64 = rest[4..6].parse().ok()?;
+            let off = h * 3600 + m * 60;
+            if *sign == b'+' {
+                off
+            } else {
+                -off
+            }
+        }
+        _ => return None,
+    };
+
+    let secs =
+        days_from_civil(y as i64, mo, d) * 86400 + (h * 3600 + mi * 60 + sec) as i64 - offset;
+    let time = if secs >= 0 {
+        SystemTime::UNIX_EPOCH + Duration::from_secs(secs as u64)
+    } else {
+        SystemTime::UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs())
+    };
+    Some(time + Duration::from_nanos(nanos as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::hls;
+
+    #[test]
+    fn date_time() {
+        let t = SystemTime::UNIX_EPOCH + Duration::from_millis(1_709_251_199_123);
+        assert_eq!(hls::fmt_date_time(t), "2024-02-29T23:59:59.123Z");
+        assert_eq!(hls::parse_date_time("2024-02-29T23:59:59.123Z"), Some(t));
+        assert_eq!(
+            hls::parse_date_time("2024-03-01T01:59:59.123+02:00"),
+            Some(t)
+        );
+        assert_eq!(
+            hls::parse_date_time("1969-12-31T23:59:59Z"),
+            Some(SystemTime::UNIX_EPOCH - Duration::from_secs(1))
+        );
+        assert_eq!(
+            hls::fmt_date_time(SystemTime::UNIX_EPOCH - Duration::from_secs(1)),
+            "1969-12-31T23:59:59.000Z"
+        );
+        assert_eq!(hls::parse_date_time("2024-02-29 23:59:59Z"), None);
+        assert_eq!(hls::parse_date_time("2024-13-01T00:00:00Z"), None);
+        assert_eq!(hls::parse_date_time("2024-01-01T00:00:00"), None);
+    }
+}
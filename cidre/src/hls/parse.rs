@@ -0,0 +1,474 @@
+//! M3U8 reading. Unknown tags are ignored, as clients are required to.
+
+use std::str::FromStr;
+
+use crate::hls::{
+    parse_date_time, ByteRange, Error, Map, MediaPlaylist, MultivariantPlaylist, Part, Playlist,
+    PlaylistType, PreloadHint, PreloadHintType, Rendition, RenditionType, Segment, ServerControl,
+    Variant,
+};
+
+/// `KEY=VALUE` pairs of an attribute list, quoted values without quotes.
+struct Attrs<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Attrs<'a> {
+    type Item = Option<(&'a str, &'a str)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = self.rest.trim_start_matches(',');
+        if s.is_empty() {
+            return None;
+        }
+        let Some((key, rest)) = s.split_once('=') else {
+            self.rest = "";
+            return Some(None);
+        };
+        let (val, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+            let Some(end) = quoted.find('"') else {
+                self.rest = "";
+                return Some(None);
+            };
+            (&quoted[..end], &quoted[end + 1..])
+        } else {
+            rest.split_at(rest.find(',').unwrap_or(rest.len()))
+        };
+        self.rest = rest;
+        Some(Some((key, val)))
+    }
+}
+
+/// One line of a playlist with its number for errors.
+struct Line<'a> {
+    n: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    fn err(&self, what: &'static str) -> Error {
+        Error::Line(self.n, what)
+    }
+
+    fn num<T: FromStr>(&self, s: &str, what: &'static str) -> Result<T, Error> {
+        s.trim().parse().map_err(|_| self.err(what))
+    }
+
+    fn attrs(&self, s: &'a str) -> impl Iterator<Item = Result<(&'a str, &'a str), Error>> + '_ {
+        Attrs { rest: s }.map(|kv| kv.ok_or(self.err("attribute list")))
+    }
+
+    fn byte_range(&self, s: &str) -> Result<ByteRange, Error> {
+        let (len, offset) = match s.split_once('@') {
+            Some((len, offset)) => (len, Some(self.num(offset, "byte range")?)),
+            None => (s, None),
+        };
+        Ok(ByteRange {
+            len: self.num(len, "byte range")?,
+            offset,
+        })
+    }
+}
+
+/// Lines after the `#EXTM3U` header, blank ones skipped.
+fn lines(s: &str) -> Result<impl Iterator<Item = Line<'_>>, Error> {
+    let mut lines = s
+        .lines()
+        .enumerate()
+        .map(|(i, l)| Line {
+            n: i + 1,
+            text: l.trim(),
+        })
+        .filter(|l| !l.text.is_empty());
+    match lines.next() {
+        Some(l) if l.text.trim_start_matches('\u{feff}') == "#EXTM3U" => Ok(lines),
+        _ => Err(Error::Header),
+    }
+}
+
+fn tag(text: &str) -> (&str, &str) {
+    text.split_once(':').unwrap_or((text, ""))
+}
+
+fn part(line: &Line, s: &str) -> Result<Part, Error> {
+    let mut duration = None;
+    let mut part = Part {
+        duration: 0.0,
+        uri: String::new(),
+        independent: false,
+        byte_range: None,
+        gap: false,
+    };
+    for kv in line.attrs(s) {
+        match kv? {
+            ("DURATION", v) => duration = Some(line.num(v, "part duration")?),
+            ("URI", v) => part.uri = v.to_string(),
+            ("INDEPENDENT", v) => part.independent = v == "YES",
+            ("BYTERANGE", v) => part.byte_range = Some(line.byte_range(v)?),
+            ("GAP", v) => part.gap = v == "YES",
+            _ => {}
+        }
+    }
+    if part.uri.is_empty() {
+        return Err(line.err("part URI"));
+    }
+    part.duration = duration.ok_or(line.err("part duration"))?;
+    Ok(part)
+}
+
+impl FromStr for MediaPlaylist {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut res = Self {
+            version: 1,
+            ..Default::default()
+        };
+        let mut next = Segment::new("", 0.0);
+        let mut has_inf = false;
+        for line in lines(s)? {
+            let text = line.text;
+            if !text.starts_with('#') {
+                if !has_inf {
+                    return Err(line.err("segment without EXTINF"));
+                }
+                next.uri = text.to_string();
+                next.parts = std::mem::take(&mut res.parts);
+                res.segments
+                    .push(std::mem::replace(&mut next, Segment::new("", 0.0)));
+                has_inf = false;
+                continue;
+            }
+            match tag(text) {
+                ("#EXT-X-VERSION", v) => res.version = line.num(v, "version")?,
+                ("#EXT-X-TARGETDURATION", v) => {
+                    res.target_duration = line.num(v, "target duration")?
+                }
+                ("#EXT-X-MEDIA-SEQUENCE", v) => {
+                    res.media_sequence = line.num(v, "media sequence")?
+                }
+                ("#EXT-X-DISCONTINUITY-SEQUENCE", v) => {
+                    res.discontinuity_sequence = line.num(v, "discontinuity sequence")?
+                }
+                ("#EXT-X-PLAYLIST-TYPE", "EVENT") => res.playlist_type = Some(PlaylistType::Event),
+                ("#EXT-X-PLAYLIST-TYPE", "VOD") => res.playlist_type = Some(PlaylistType::Vod),
+                ("#EXT-X-PLAYLIST-TYPE", _) => return Err(line.err("playlist type")),
+                ("#EXT-X-INDEPENDENT-SEGMENTS", _) => res.independent_segments = true,
+                ("#EXT-X-ENDLIST", _) => res.end_list = true,
+                ("#EXT-X-PART-INF", v) => {
+                    for kv in line.attrs(v) {
+                        if let ("PART-TARGET", v) = kv? {
+                            res.part_target = Some(line.num(v, "part target")?);
+                        }
+                    }
+                }
+                ("#EXT-X-SERVER-CONTROL", v) => {
+                    let mut sc = ServerControl::default();
+                    for kv in line.attrs(v) {
+                        match kv? {
+                            ("CAN-BLOCK-RELOAD", v) => sc.can_block_reload = v == "YES",
+                            ("CAN-SKIP-UNTIL", v) => {
+                                sc.can_skip_until = Some(line.num(v, "skip duration")?)
+                            }
+                            ("HOLD-BACK", v) => sc.hold_back = Some(line.num(v, "hold back")?),
+                            ("PART-HOLD-BACK", v) => {
+                                sc.part_hold_back = Some(line.num(v, "part hold back")?)
+                            }
+                            _ => {}
+                        }
+                    }
+                    res.server_control = Some(sc);
+                }
+                ("#EXTINF", v) => {
+                    let (duration, title) = v.split_once(',').unwrap_or((v, ""));
+                    next.duration = line.num(duration, "segment duration")?;
+                    next.title = title.to_string();
+                    has_inf = true;
+                }
+                ("#EXT-X-BYTERANGE", v) => next.byte_range = Some(line.byte_range(v)?),
+                ("#EXT-X-DISCONTINUITY", _) => next.discontinuity = true,
+                ("#EXT-X-GAP", _) => next.gap = true,
+                ("#EXT-X-PROGRAM-DATE-TIME", v) => {
+                    next.program_date_time =
+                        Some(parse_date_time(v).ok_or(line.err("program date time"))?)
+                }
+                ("#EXT-X-MAP", v) => {
+                    let mut map = Map {
+                        uri: String::new(),
+                        byte_range: None,
+                    };
+                    for kv in line.attrs(v) {
+                        match kv? {
+                            ("URI", v) => map.uri = v.to_string(),
+                            ("BYTERANGE", v) => map.byte_range = Some(line.byte_range(v)?),
+                            _ => {}
+                        }
+                    }
+                    if map.uri.is_empty() {
+                        return Err(line.err("map URI"));
+                    }
+                    next.map = Some(map);
+                }
+                ("#EXT-X-PART", v) => res.parts.push(part(&line, v)?),
+                ("#EXT-X-PRELOAD-HINT", v) => {
+                    let mut hint = PreloadHint {
+                        typ: PreloadHintType::Part,
+                        uri: String::new(),
+                        byte_range_start: None,
+                        byte_range_len: None,
+                    };
+                    for kv in line.attrs(v) {
+                        match kv? {
+                            ("TYPE", "PART") => hint.typ = PreloadHintType::Part,
+                            ("TYPE", "MAP") => hint.typ = PreloadHintType::Map,
+                            ("TYPE", _) => return Err(line.err("preload hint type")),
+                            ("URI", v) => hint.uri = v.to_string(),
+                            ("BYTERANGE-START", v) => {
+                                hint.byte_range_start = Some(line.num(v, "byte range")?)
+                            }
+                            ("BYTERANGE-LENGTH", v) => {
+                                hint.byte_range_len = Some(line.num(v, "byte range")?)
+                            }
+                            _ => {}
+                        }
+                    }
+                    res.preload_hints.push(hint);
+                }
+                ("#EXT-X-STREAM-INF", _) => return Err(line.err("tag for a media playlist")),
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl FromStr for MultivariantPlaylist {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let mut res = Self {
+            version: 1,
+            ..Default::default()
+        };
+        let mut variant: Option<Variant> = None;
+        for line in lines(s)? {
+            let text = line.text;
+            if !text.starts_with('#') {
+                let mut v = variant
+                    .take()
+                    .ok_or(line.err("URI without EXT-X-STREAM-INF"))?;
+                v.uri = text.to_string();
+                res.variants.push(v);
+                continue;
+            }
+            match tag(text) {
+                ("#EXT-X-VERSION", v) => res.version = line.num(v, "version")?,
+                ("#EXT-X-INDEPENDENT-SEGMENTS", _) => res.independent_segments = true,
+                ("#EXT-X-STREAM-INF", v) => {
+                    let mut var = Variant::default();
+                    let mut bandwidth = None;
+                    for kv in line.attrs(v) {
+                        let opt = |v: &str| Some(v.to_string());
+                        match kv? {
+                            ("BANDWIDTH", v) => bandwidth = Some(line.num(v, "bandwidth")?),
+                            ("AVERAGE-BANDWIDTH", v) => {
+                                var.average_bandwidth = Some(line.num(v, "bandwidth")?)
+                            }
+                            ("CODECS", v) => var.codecs = opt(v),
+                            ("RESOLUTION", v) => {
+                                let (w, h) = v.split_once('x').ok_or(line.err("resolution"))?;
+                                var.resolution =
+                                    Some((line.num(w, "resolution")?, line.num(h, "resolution")?));
+                            }
+                            ("FRAME-RATE", v) => var.frame_rate = Some(line.num(v, "frame rate")?),
+                            ("AUDIO", v) => var.audio = opt(v),
+                            ("VIDEO", v) => var.video = opt(v),
+                            ("SUBTITLES", v) => var.subtitles = opt(v),
+                            ("CLOSED-CAPTIONS", v) => var.closed_captions = opt(v),
+                            _ => {}
+                        }
+                    }
+                    var.bandwidth = bandwidth.ok_or(line.err("bandwidth"))?;
+                    variant = Some(var);
+                }
+                ("#EXT-X-MEDIA", v) => {
+                    let mut typ = None;
+                    let mut r = Rendition {
+                        typ: RenditionType::Audio,
+                        group_id: String::new(),
+                        name: String::new(),
+                        language: None,
+                        uri: None,
+                        default: false,
+                        autoselect: false,
+                        channels: None,
+                    };
+                    for kv in line.attrs(v) {
+                        match kv? {
+                            ("TYPE", v) => {
+                                typ = RenditionType::ALL
+                                    .iter()
+                                    .find(|(_, s)| *s == v)
+                                    .map(|(t, _)| *t);
+                            }
+                            ("GROUP-ID", v) => r.group_id = v.to_string(),
+                            ("NAME", v) => r.name = v.to_string(),
+                            ("LANGUAGE", v) => r.language = Some(v.to_string()),
+                            ("URI", v) => r.uri = Some(v.to_string()),
+                            ("DEFAULT", v) => r.default = v == "YES",
+                            ("AUTOSELECT", v) => r.autoselect = v == "YES",
+                            ("CHANNELS", v) => r.channels = Some(v.to_string()),
+                            _ => {}
+                        }
+                    }
+                    r.typ = typ.ok_or(line.err("rendition type"))?;
+                    res.renditions.push(r);
+                }
+                ("#EXTINF", _) => return Err(line.err("tag for a multivariant playlist")),
+                _ => {}
+            }
+        }
+        Ok(res)
+    }
+}
+
+impl FromStr for Playlist {
+    type Err = Error;
+
+    /// Multivariant if it has `EXT-X-STREAM-INF` or `EXT-X-MEDIA` tags.
+    fn from_str(s: &str) -> Result<Self, Error> {
+        let multivariant = s.lines().any(|l| {
+            let l = l.trim_start();
+            l.starts_with("#EXT-X-STREAM-INF:") || l.starts_with("#EXT-X-MEDIA:")
+        });
+        if multivariant {
+            s.parse().map(Self::Multivariant)
+        } else {
+            s.parse().map(Self::Media)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime};
+
+    use crate::hls::{self, PreloadHintType};
+
+    const LL_HLS: &str = "#EXTM3U
+#EXT-X-VERSION:9
+#EXT-X-TARGETDURATION:4
+#EXT-X-SERVER-CONTROL:CAN-BLOCK-RELOAD=YES,PART-HOLD-BACK=3
+#EXT-X-PART-INF:PART-TARGET=1
+#EXT-X-MEDIA-SEQUENCE:266
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-MAP:URI=\"init.mp4\"
+#EXT-X-PROGRAM-DATE-TIME:2024-01-01T00:00:00.000Z
+#EXTINF:4,
+fileSequence266.m4s
+#EXT-X-DISCONTINUITY
+#EXT-X-PART:DURATION=1,URI=\"filePart267.0.m4s\",INDEPENDENT=YES
+#EXT-X-PART:DURATION=1,URI=\"filePart267.1.m4s\"
+#EXTINF:2.5,live
+#EXT-X-BYTERANGE:1000@0
+fileSequence267.m4s
+#EXT-X-PART:DURATION=1,URI=\"filePart268.0.m4s\",INDEPENDENT=YES,BYTERANGE=\"500@20\"
+#EXT-X-PRELOAD-HINT:TYPE=PART,URI=\"filePart268.1.m4s\"
+";
+
+    #[test]
+    fn media() {
+        let p: hls::MediaPlaylist = LL_HLS.parse().unwrap();
+        assert_eq!(
+            (p.version, p.target_duration, p.media_sequence),
+            (9, 4, 266)
+        );
+        assert_eq!(p.part_target, Some(1.0));
+        let sc = p.server_control.unwrap();
+        assert!(sc.can_block_reload);
+        assert_eq!((sc.part_hold_back, sc.hold_back), (Some(3.0), None));
+
+        assert_eq!(p.segments.len(), 2);
+        let s = &p.segments[0];
+        assert_eq!(
+            s.program_date_time,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+        );
+        assert!(s.parts.is_empty() && !s.discontinuity);
+        let s = &p.segments[1];
+        assert!(s.discontinuity && s.map.is_none());
+        assert_eq!(p.map_of(1).unwrap().uri, "init.mp4");
+        assert_eq!((s.title.as_str(), s.duration), ("live", 2.5));
+        assert_eq!(s.parts.len(), 2);
+        assert!(s.parts[0].independent && !s.parts[1].independent);
+        assert_eq!(
+            s.byte_range,
+            Some(hls::ByteRange {
+                len: 1000,
+                offset: Some(0)
+            })
+        );
+
+        assert_eq!(p.parts[0].byte_range.unwrap().offset, Some(20));
+        assert_eq!(p.preload_hints[0].typ, PreloadHintType::Part);
+        assert_eq!(p.sequence_of(1), 267);
+        assert!(!p.end_list);
+
+        assert_eq!(p.to_string(), LL_HLS);
+    }
+
+    #[test]
+    fn multivariant() {
+        let text = "#EXTM3U
+#EXT-X-VERSION:7
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-MEDIA:TYPE=AUDIO,GROUP-ID=\"aac\",NAME=\"English\",LANGUAGE=\"en\",DEFAULT=YES,AUTOSELECT=YES,CHANNELS=\"2\",URI=\"audio/en.m3u8\"
+#EXT-X-STREAM-INF:BANDWIDTH=6000000,AVERAGE-BANDWIDTH=5000000,CODECS=\"hvc1.2.4.L123.B0,mp4a.40.2\",RESOLUTION=1920x1080,FRAME-RATE=29.970,AUDIO=\"aac\"
+video/1080p.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=1500000,RESOLUTION=640x360
+video/360p.m3u8
+";
+        let p = match text.parse().unwrap() {
+            hls::Playlist::Multivariant(p) => p,
+            hls::Playlist::Media(_) => panic!("expected multivariant"),
+        };
+        assert_eq!(p.renditions[0].typ, hls::RenditionType::Audio);
+        assert_eq!(p.renditions[0].channels.as_deref(), Some("2"));
+        assert_eq!(p.variants.len(), 2);
+        let v = &p.variants[0];
+        assert_eq!(v.codecs.as_deref(), Some("hvc1.2.4.L123.B0,mp4a.40.2"));
+        assert_eq!(v.resolution, Some((1920, 1080)));
+        assert_eq!(v.frame_rate, Some(29.97));
+        assert_eq!(p.variants[1].uri, "video/360p.m3u8");
+        assert_eq!(p.to_string(), text);
+    }
+
+    #[test]
+    fn errors() {
+        assert!(matches!(
+            "#EXTINF:1,\na.ts".parse::<hls::MediaPlaylist>(),
+            Err(hls::Error::Header)
+        ));
+        assert!(matches!(
+            "#EXTM3U\n\n#EXTINF:x,\na.ts".parse::<hls::MediaPlaylist>(),
+            Err(hls::Error::Line(3, "segment duration"))
+        ));
+        assert!(matches!(
+            "#EXTM3U\na.ts".parse::<hls::MediaPlaylist>(),
+            Err(hls::Error::Line(2, _))
+        ));
+        assert!(matches!(
+            "#EXTM3U\n#EXT-X-MAP:URI=\"init.mp4".parse::<hls::MediaPlaylist>(),
+            Err(hls::Error::Line(2, "attribute list"))
+        ));
+        assert!(matches!(
+            "#EXTM3U\n#EXT-X-STREAM-INF:CODECS=\"avc1\"\nv.m3u8"
+                .parse::<hls::MultivariantPlaylist>(),
+            Err(hls::Error::Line(2, "bandwidth"))
+        ));
+
+        // unknown tags are skipped
+        let p: hls::MediaPlaylist = "#EXTM3U\n#EXT-X-FOO:1\n#EXTINF:1,\na.ts\n".parse().unwrap();
+        assert_eq!(p.segments[0].uri, "a.ts");
+    }
+}
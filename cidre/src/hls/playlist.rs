@@ -0,0 +1,479 @@
+//! Typed M3U8 playlists. `Display` writes them, `FromStr` (in `parse`) reads them.
+
+use std::{
+    fmt::{self, Write},
+    time::SystemTime,
+};
+
+use crate::hls::fmt_date_time;
+
+/// `EXT-X-BYTERANGE` and `BYTERANGE` attribute, `len[@offset]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    pub len: u64,
+
+    /// Starts right after the previous range of the same resource when `None`.
+    pub offset: Option<u64>,
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.offset {
+            Some(offset) => write!(f, "{}@{offset}", self.len),
+            None => write!(f, "{}", self.len),
+        }
+    }
+}
+
+/// `EXT-X-MAP`, the init segment of fMP4 media segments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Map {
+    pub uri: String,
+    pub byte_range: Option<ByteRange>,
+}
+
+/// `EXT-X-PART`, a partial segment of Low-Latency HLS.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    /// Seconds.
+    pub duration: f64,
+    pub uri: String,
+    pub independent: bool,
+    pub byte_range: Option<ByteRange>,
+    pub gap: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreloadHintType {
+    Part,
+    Map,
+}
+
+/// `EXT-X-PRELOAD-HINT`, the resource the server will produce next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreloadHint {
+    pub typ: PreloadHintType,
+    pub uri: String,
+    pub byte_range_start: Option<u64>,
+    pub byte_range_len: Option<u64>,
+}
+
+/// Media segment with the tags that apply to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    /// `EXTINF` duration in seconds.
+    pub duration: f64,
+    pub title: String,
+    pub uri: String,
+    pub byte_range: Option<ByteRange>,
+
+    /// `EXT-X-DISCONTINUITY` before this segment.
+    pub discontinuity: bool,
+    pub program_date_time: Option<SystemTime>,
+
+    /// `EXT-X-MAP` before this segment, in effect until the next one.
+    pub map: Option<Map>,
+
+    /// Parts of this segment, kept only near the live edge.
+    pub parts: Vec<Part>,
+    pub gap: bool,
+}
+
+impl Segment {
+    pub fn new(uri: impl Into<String>, duration: f64) -> Self {
+        Self {
+            duration,
+            title: String::new(),
+            uri: uri.into(),
+            byte_range: None,
+            discontinuity: false,
+            program_date_time: None,
+            map: None,
+            parts: Vec::new(),
+            gap: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistType {
+    /// Segments are only appended.
+    Event,
+
+    /// The playlist won't change.
+    Vod,
+}
+
+/// `EXT-X-SERVER-CONTROL`. Durations are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ServerControl {
+    pub can_block_reload: bool,
+    pub can_skip_until: Option<f64>,
+    pub hold_back: Option<f64>,
+    pub part_hold_back: Option<f64>,
+}
+
+/// Media playlist, listing the segments of one rendition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    pub version: u8,
+
+    /// `EXT-X-TARGETDURATION`, at least every segment duration rounded to
+    /// the nearest second.
+    pub target_duration: u32,
+    pub media_sequence: u64,
+    pub discontinuity_sequence: u64,
+    pub playlist_type: Option<PlaylistType>,
+    pub independent_segments: bool,
+
+    /// `EXT-X-PART-INF` part target in seconds.
+    pub part_target: Option<f64>,
+    pub server_control: Option<ServerControl>,
+    pub segments: Vec<Segment>,
+
+    /// Parts of the segment still being produced.
+    pub parts: Vec<Part>,
+    pub preload_hints: Vec<PreloadHint>,
+    pub end_list: bool,
+}
+
+impl Default for MediaPlaylist {
+    fn default() -> Self {
+        Self {
+            version: 7,
+            target_duration: 0,
+            media_sequence: 0,
+            discontinuity_sequence: 0,
+            playlist_type: None,
+            independent_segments: false,
+            part_target: None,
+            server_control: None,
+            segments: Vec::new(),
+            parts: Vec::new(),
+            preload_hints: Vec::new(),
+            end_list: false,
+        }
+    }
+}
+
+impl MediaPlaylist {
+    /// Sum of segment durations in seconds.
+    pub fn duration(&self) -> f64 {
+        self.segments.iter().map(|s| s.duration).sum()
+    }
+
+    /// Smallest valid `EXT-X-TARGETDURATION` for the segments.
+    pub fn min_target_duration(&self) -> u32 {
+        self.segments
+            .iter()
+            .map(|s| s.duration.round() as u32)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Media sequence number of the segment at `index`.
+    #[inline]
+    pub fn sequence_of(&self, index: usize) -> u64 {
+        self.media_sequence + index as u64
+    }
+
+    /// `EXT-X-MAP` in effect for the segment at `index`.
+    pub fn map_of(&self, index: usize) -> Option<&Map> {
+        self.segments[..=index]
+            .iter()
+            .rev()
+            .find_map(|s| s.map.as_ref())
+    }
+}
+
+/// Decimal seconds without trailing zeros beyond the fifth fraction digit.
+pub(super) fn fmt_secs(secs: f64) -> String {
+    let mut s = format!("{secs:.5}");
+    let trimmed = s.trim_end_matches('0').trim_end_matches('.').len();
+    s.truncate(trimmed);
+    s
+}
+
+/// `"..."` attribute value, quotes in `s` can't be represented and are dropped.
+fn quoted(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    res.extend(s.chars().filter(|c| !matches!(c, '"' | '\r' | '\n')));
+    res.push('"');
+    res
+}
+
+fn yes(val: bool) -> &'static str {
+    if val {
+        "YES"
+    } else {
+        "NO"
+    }
+}
+
+/// Comma separated attribute list, skipping `None` values.
+struct Attrs(String);
+
+impl Attrs {
+    fn new() -> Self {
+        Self(String::new())
+    }
+
+    fn add(&mut self, key: &str, val: impl fmt::Display) -> &mut Self {
+        if !self.0.is_empty() {
+            self.0.push(',');
+        }
+        write!(self.0, "{key}={val}").unwrap();
+        self
+    }
+
+    fn opt(&mut self, key: &str, val: Option<impl fmt::Display>) -> &mut Self {
+        if let Some(val) = val {
+            self.add(key, val);
+        }
+        self
+    }
+
+    fn flag(&mut self, key: &str, val: bool) -> &mut Self {
+        if val {
+            self.add(key, "YES");
+        }
+        self
+    }
+}
+
+fn write_map(f: &mut fmt::Formatter<'_>, map: &Map) -> fmt::Result {
+    let mut a = Attrs::new();
+    a.add("URI", quoted(&map.uri))
+        .opt("BYTERANGE", map.byte_range.map(|r| quoted(&r.to_string())));
+    writeln!(f, "#EXT-X-MAP:{}", a.0)
+}
+
+fn write_part(f: &mut fmt::Formatter<'_>, part: &Part) -> fmt::Result {
+    let mut a = Attrs::new();
+    a.add("DURATION", fmt_secs(part.duration))
+        .add("URI", quoted(&part.uri))
+        .flag("INDEPENDENT", part.independent)
+        .opt("BYTERANGE", part.byte_range.map(|r| quoted(&r.to_string())))
+        .flag("GAP", part.gap);
+    writeln!(f, "#EXT-X-PART:{}", a.0)
+}
+
+impl fmt::Display for MediaPlaylist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#EXTM3U")?;
+        writeln!(f, "#EXT-X-VERSION:{}", self.version)?;
+        writeln!(f, "#EXT-X-TARGETDURATION:{}", self.target_duration)?;
+        if let Some(sc) = &self.server_control {
+            let mut a = Attrs::new();
+            a.flag("CAN-BLOCK-RELOAD", sc.can_block_reload)
+                .opt("CAN-SKIP-UNTIL", sc.can_skip_until.map(fmt_secs))
+                .opt("HOLD-BACK", sc.hold_back.map(fmt_secs))
+                .opt("PART-HOLD-BACK", sc.part_hold_back.map(fmt_secs));
+            writeln!(f, "#EXT-X-SERVER-CONTROL:{}", a.0)?;
+        }
+        if let Some(part_target) = self.part_target {
+            writeln!(f, "#EXT-X-PART-INF:PART-TARGET={}", fmt_secs(part_target))?;
+        }
+        if self.media_sequence != 0 {
+            writeln!(f, "#EXT-X-MEDIA-SEQUENCE:{}", self.media_sequence)?;
+        }
+        if self.discontinuity_sequence != 0 {
+            writeln!(
+                f,
+                "#EXT-X-DISCONTINUITY-SEQUENCE:{}",
+                self.discontinuity_sequence
+            )?;
+        }
+        match self.playlist_type {
+            Some(PlaylistType::Event) => writeln!(f, "#EXT-X-PLAYLIST-TYPE:EVENT")?,
+            Some(PlaylistType::Vod) => writeln!(f, "#EXT-X-PLAYLIST-TYPE:VOD")?,
+            None => {}
+        }
+        if self.independent_segments {
+            writeln!(f, "#EXT-X-INDEPENDENT-SEGMENTS")?;
+        }
+        for s in &self.segments {
+            if s.discontinuity {
+                writeln!(f, "#EXT-X-DISCONTINUITY")?;
+            }
+            if let Some(map) = &s.map {
+                write_map(f, map)?;
+            }
+            if let Some(time) = s.program_date_time {
+                writeln!(f, "#EXT-X-PROGRAM-DATE-TIME:{}", fmt_date_time(time))?;
+            }
+            for part in &s.parts {
+                write_part(f, part)?;
+            }
+            if s.gap {
+                writeln!(f, "#EXT-X-GAP")?;
+            }
+            writeln!(f, "#EXTINF:{},{}", fmt_secs(s.duration), s.title)?;
+            if let Some(range) = s.byte_range {
+                writeln!(f, "#EXT-X-BYTERANGE:{range}")?;
+            }
+            writeln!(f, "{}", s.uri)?;
+        }
+        for part in &self.parts {
+            write_part(f, part)?;
+        }
+        for hint in &self.preload_hints {
+            let mut a = Attrs::new();
+            let typ = match hint.typ {
+                PreloadHintType::Part => "PART",
+                PreloadHintType::Map => "MAP",
+            };
+            a.add("TYPE", typ)
+                .add("URI", quoted(&hint.uri))
+                .opt("BYTERANGE-START", hint.byte_range_start)
+                .opt("BYTERANGE-LENGTH", hint.byte_range_len);
+            writeln!(f, "#EXT-X-PRELOAD-HINT:{}", a.0)?;
+        }
+        if self.end_list {
+            writeln!(f, "#EXT-X-ENDLIST")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenditionType {
+    Audio,
+    Video,
+    Subtitles,
+    ClosedCaptions,
+}
+
+impl RenditionType {
+    pub(super) const ALL: [(Self, &'static str); 4] = [
+        (Self::Audio, "AUDIO"),
+        (Self::Video, "VIDEO"),
+        (Self::Subtitles, "SUBTITLES"),
+        (Self::ClosedCaptions, "CLOSED-CAPTIONS"),
+    ];
+
+    pub(super) fn as_str(self) -> &'static str {
+        Self::ALL.iter().find(|(t, _)| *t == self).unwrap().1
+    }
+}
+
+/// `EXT-X-MEDIA`, an alternative rendition in a group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendition {
+    pub typ: RenditionType,
+    pub group_id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub uri: Option<String>,
+    pub default: bool,
+    pub autoselect: bool,
+
+    /// Like `"2"` or `"16/JOC"`.
+    pub channels: Option<String>,
+}
+
+/// `EXT-X-STREAM-INF` and its media playlist URI.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Variant {
+    pub uri: String,
+
+    /// Peak bits per second.
+    pub bandwidth: u64,
+    pub average_bandwidth: Option<u64>,
+
+    /// Like `"hvc1.2.4.L123.B0,mp4a.40.2"`.
+    pub codecs: Option<String>,
+    pub resolution: Option<(u32, u32)>,
+    pub frame_rate: Option<f64>,
+    pub audio: Option<String>,
+    pub video: Option<String>,
+    pub subtitles: Option<String>,
+    pub closed_captions: Option<String>,
+}
+
+/// Multivariant (master) playlist, listing variants and renditions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultivariantPlaylist {
+    pub version: u8,
+    pub independent_segments: bool,
+    pub renditions: Vec<Rendition>,
+    pub variants: Vec<Variant>,
+}
+
+impl Default for MultivariantPlaylist {
+    fn default() -> Self {
+        Self {
+            version: 7,
+            independent_segments: false,
+            renditions: Vec::new(),
+            variants: Vec::new(),
+        }
+    }
+}
+
+impl fmt::Display for MultivariantPlaylist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "#EXTM3U")?;
+        writeln!(f, "#EXT-X-VERSION:{}", self.version)?;
+        if self.independent_segments {
+            writeln!(f, "#EXT-X-INDEPENDENT-SEGMENTS")?;
+        }
+        for r in &self.renditions {
+            let mut a = Attrs::new();
+            a.add("TYPE", r.typ.as_str())
+                .add("GROUP-ID", quoted(&r.group_id))
+                .add("NAME", quoted(&r.name))
+                .opt("LANGUAGE", r.language.as_deref().map(quoted))
+                .add("DEFAULT", yes(r.default))
+                .add("AUTOSELECT", yes(r.autoselect))
+                .opt("CHANNELS", r.channels.as_deref().map(quoted))
+                .opt("URI", r.uri.as_deref().map(quoted));
+            writeln!(f, "#EXT-X-MEDIA:{}", a.0)?;
+        }
+        for v in &self.variants {
+            let mut a = Attrs::new();
+            a.add("BANDWIDTH", v.bandwidth)
+                .opt("AVERAGE-BANDWIDTH", v.average_bandwidth)
+                .opt("CODECS", v.codecs.as_deref().map(quoted))
+                .opt("RESOLUTION", v.resolution.map(|(w, h)| format!("{w}x{h}")))
+                .opt("FRAME-RATE", v.frame_rate.map(|r| format!("{r:.3}")))
+                .opt("AUDIO", v.audio.as_deref().map(quoted))
+                .opt("VIDEO", v.video.as_deref().map(quoted))
+                .opt("SUBTITLES", v.subtitles.as_deref().map(quoted))
+                .opt("CLOSED-CAPTIONS", v.closed_captions.as_deref().map(quoted));
+            writeln!(f, "#EXT-X-STREAM-INF:{}", a.0)?;
+            writeln!(f, "{}", v.uri)?;
+        }
+        Ok(())
+    }
+}
+
+/// Either kind of playlist, for input that could be both.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Playlist {
+    Media(MediaPlaylist),
+    Multivariant(MultivariantPlaylist),
+}
+
+impl fmt::Display for Playlist {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Media(p) => p.fmt(f),
+            Self::Multivariant(p) => p.fmt(f),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::hls::playlist::fmt_secs;
+
+    #[test]
+    fn secs() {
+        assert_eq!(fmt_secs(6.0), "6");
+        assert_eq!(fmt_secs(6.006666), "6.00667");
+        assert_eq!(fmt_secs(0.5), "0.5");
+        assert_eq!(fmt_secs(10.0), "10");
+    }
+}
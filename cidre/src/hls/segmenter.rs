@@ -0,0 +1,688 @@
+//! Rolling-window segment and playlist writer.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use crate::{
+    cm,
+    hls::{
+        Error, Map, MediaPlaylist, Part, PlaylistType, PreloadHint, PreloadHintType, Segment,
+        ServerControl,
+    },
+};
+
+#[cfg(all(target_vendor = "apple", feature = "av"))]
+use crate::{av, ns};
+
+/// Settings of a [`Segmenter`].
+#[derive(Debug, Clone)]
+pub struct SegmenterCfg {
+    pub dir: PathBuf,
+
+    /// Prefix of every file: `{base_name}.m3u8`, `{base_name}.mp4`,
+    /// `{base_name}{seq}.m4s` and `{base_name}{seq}.{part}.m4s`.
+    pub base_name: String,
+
+    /// Seconds, what `av::AssetWriter::set_preferred_output_segment_interval` gets.
+    ///
+    /// Fixed for the whole stream, segments that round to a longer
+    /// duration are rejected. Leave headroom for keyframe placement.
+    pub target_duration: u32,
+
+    /// Segments kept in the playlist, `None` to keep all of them.
+    pub window: Option<usize>,
+    pub playlist_type: Option<PlaylistType>,
+
+    /// Wall clock time of the first segment, enables `EXT-X-PROGRAM-DATE-TIME`.
+    pub program_date_time: Option<SystemTime>,
+
+    /// Seconds, enables Low-Latency HLS parts.
+    pub part_target: Option<f64>,
+}
+
+impl SegmenterCfg {
+    /// Live playlist without a window limit, parts or date times.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        base_name: impl Into<String>,
+        target_duration: u32,
+    ) -> Self {
+        Self {
+            dir: dir.into(),
+            base_name: base_name.into(),
+            target_duration,
+            window: None,
+            playlist_type: None,
+            program_date_time: None,
+            part_target: None,
+        }
+    }
+}
+
+/// Writes init and media segments with their media playlist, replacing
+/// `#EXT-X-` bookkeeping every delegate of `av::AssetWriter` had to do.
+///
+/// Files are written to a temporary name and renamed, so a server never
+/// sees a partial segment or playlist. Segments that leave the window, and
+/// parts no longer listed, are deleted once clients loading older playlists are done with them, after
+/// the segment's duration plus that of the last playlist listing it
+/// (RFC 8216 §6.2.2) but never sooner than a target duration, counted in
+/// media time. Segments still waiting when the stream finishes stay on disk.
+///
+/// ```no_run
+/// use cidre::{cm, hls};
+///
+/// let mut cfg = hls::SegmenterCfg::new("/tmp/hls", "live", 6);
+/// cfg.window = Some(5);
+/// let mut segmenter = hls::Segmenter::new(cfg);
+///
+/// # let (init, segment) = (vec![], vec![]);
+/// segmenter.write_init(&init).unwrap();
+/// let range = cm::TimeRange::new(cm::Time::zero(), cm::Time::new(6006, 1000));
+/// segmenter.write_segment(&segment, range).unwrap();
+/// segmenter.finish().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Segmenter {
+    cfg: SegmenterCfg,
+    playlist: MediaPlaylist,
+    next_seq: u64,
+    init_count: u32,
+    map: Option<Map>,
+    discontinuity: bool,
+
+    /// Start of the first segment, for program date times.
+    start: Option<cm::Time>,
+
+    /// End of the last segment or part.
+    end: Option<cm::Time>,
+
+    /// Segment and part URIs that left the window, with the media time
+    /// they can be deleted at.
+    removals: Vec<(cm::Time, Vec<String>)>,
+}
+
+impl Segmenter {
+    pub fn new(cfg: SegmenterCfg) -> Self {
+        let playlist = MediaPlaylist {
+            target_duration: cfg.target_duration,
+            playlist_type: cfg.playlist_type,
+            independent_segments: true,
+            part_target: cfg.part_target,
+            server_control: cfg.part_target.map(|part_target| ServerControl {
+                part_hold_back: Some(3.0 * part_target),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        Self {
+            cfg,
+            playlist,
+            next_seq: 0,
+            init_count: 0,
+            map: None,
+            discontinuity: false,
+            start: None,
+            end: None,
+            removals: Vec::new(),
+        }
+    }
+
+    #[inline]
+    pub fn cfg(&self) -> &SegmenterCfg {
+        &self.cfg
+    }
+
+    /// The playlist as last written.
+    #[inline]
+    pub fn playlist(&self) -> &MediaPlaylist {
+        &self.playlist
+    }
+
+    pub fn playlist_path(&self) -> PathBuf {
+        self.path(&format!("{}.m3u8", self.cfg.base_name))
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.cfg.dir.join(name)
+    }
+
+    fn segment_name(&self, seq: u64) -> String {
+        format!("{}{seq}.m4s", self.cfg.base_name)
+    }
+
+    fn part_name(&self, seq: u64, part: usize) -> String {
+        format!("{}{seq}.{part}.m4s", self.cfg.base_name)
+    }
+
+    /// Marks the next segment as discontinuous, e.g. after an encoder restart.
+    pub fn discontinuity(&mut self) {
+        self.discontinuity = true;
+    }
+
+    /// Writes an init segment for the segments that follow.
+    ///
+    /// The first one is `{base_name}.mp4`. Later ones, after a format
+    /// change, get a number and start a discontinuity.
+    pub fn write_init(&mut self, data: &[u8]) -> Result<(), Error> {
+        let uri = match self.init_count {
+            0 => format!("{}.mp4", self.cfg.base_name),
+            n => format!("{}_{n}.mp4", self.cfg.base_name),
+        };
+        write_atomic(&self.path(&uri), data)?;
+        if self.init_count > 0 {
+            self.discontinuity = true;
+        }
+        self.init_count += 1;
+        self.map = Some(Map {
+            uri,
+            byte_range: None,
+        });
+        Ok(())
+    }
+
+    /// Checks `range` follows `end`, the end of the previous segment or
+    /// part, returning whether it jumps and starts a discontinuity.
+    fn check_range(&self, range: &cm::TimeRange, end: Option<cm::Time>) -> Result<bool, Error> {
+        if self.init_count == 0 {
+            return Err(Error::State("media segment before the init segment"));
+        }
+        if !range.start.is_numeric()
+            || !range.duration.is_numeric()
+            || range.duration <= cm::Time::zero()
+        {
+            return Err(Error::State("segment time range is not numeric"));
+        }
+        let Some(end) = end else {
+            return Ok(false);
+        };
+        if range.start < end {
+            return Err(Error::State("segment starts before the previous one ends"));
+        }
+        // more than a millisecond of missing media
+        Ok((range.start - end).as_secs() > 0.001)
+    }
+
+    /// Records a written segment or part checked by [`Self::check_range`].
+    fn advance(&mut self, range: &cm::TimeRange, jump: bool) {
+        self.discontinuity |= jump;
+        self.start.get_or_insert(range.start);
+        self.end = Some(range.end());
+    }
+
+    fn program_date_time(&self, time: cm::Time) -> Option<SystemTime> {
+        let base = self.cfg.program_date_time?;
+        let offset = (time - self.start?).as_secs();
+        Some(if offset >= 0.0 {
+            base + Duration::from_secs_f64(offset)
+        } else {
+            base - Duration::from_secs_f64(-offset)
+        })
+    }
+
+    /// Writes a Low-Latency HLS part of the segment in progress.
+    pub fn write_part(
+        &mut self,
+        data: &[u8],
+        range: cm::TimeRange,
+        independent: bool,
+    ) -> Result<(), Error> {
+        if self.cfg.part_target.is_none() {
+            return Err(Error::State("parts need a part target"));
+        }
+        let jump = self.check_range(&range, self.end)?;
+        let uri = self.part_name(self.next_seq, self.playlist.parts.len());
+        write_atomic(&self.path(&uri), data)?;
+        self.advance(&range, jump);
+        self.playlist.parts.push(Part {
+            duration: range.duration.as_secs(),
+            uri,
+            independent,
+            byte_range: None,
+            gap: false,
+        });
+        self.playlist.preload_hints = vec![PreloadHint {
+            typ: PreloadHintType::Part,
+            uri: self.part_name(self.next_seq, self.playlist.parts.len()),
+            byte_range_start: None,
+            byte_range_len: None,
+        }];
+        self.write_playlist()
+    }
+
+    /// Writes a media segment covering `range` and updates the playlist.
+    ///
+    /// With parts, `range` is that of the whole segment, so it starts
+    /// where the first part did.
+    pub fn write_segment(&mut self, data: &[u8], range: cm::TimeRange) -> Result<(), Error> {
+        // the parts already checked continuity
+        let end = if self.playlist.parts.is_empty() {
+            self.end
+        } else {
+            None
+        };
+        let jump = self.check_range(&range, end)?;
+        let duration = range.duration.as_secs();
+        let target = self.playlist.target_duration;
+        if duration.round() > target as f64 {
+            return Err(Error::SegmentDuration { duration, target });
+        }
+        let uri = self.segment_name(self.next_seq);
+        write_atomic(&self.path(&uri), data)?;
+
+        // nothing changes before the segment is on disk
+        self.advance(&range, jump);
+        self.next_seq += 1;
+        let mut segment = Segment::new(uri, duration);
+        segment.discontinuity = std::mem::take(&mut self.discontinuity);
+        segment.program_date_time = self.program_date_time(range.start);
+        segment.map = self.map.take();
+        segment.parts = std::mem::take(&mut self.playlist.parts);
+        self.playlist.segments.push(segment);
+
+        self.slide_window();
+        self.drop_old_parts();
+        if self.cfg.part_target.is_some() {
+            self.playlist.preload_hints = vec![PreloadHint {
+                typ: PreloadHintType::Part,
+                uri: self.part_name(self.next_seq, 0),
+                byte_range_start: None,
+                byte_range_len: None,
+            }];
+        }
+        self.write_playlist()
+    }
+
+    /// Removes segments beyond the window, keeping sequence numbers and
+    /// the init segment reference right, and queues their files for
+    /// deletion.
+    fn slide_window(&mut self) {
+        let (Some(window), Some(end)) = (self.cfg.window, self.end) else {
+            return;
+        };
+        let window = window.max(1);
+        while self.playlist.segments.len() > window {
+            // the previous playlist, without the segment just added, is the
+            // last one listing the oldest segment
+            let segments = &self.playlist.segments;
+            let listed = self.playlist.duration() - segments[segments.len() - 1].duration;
+            let hold = (segments[0].duration + listed).max(self.playlist.target_duration as f64);
+            let removed = self.playlist.segments.remove(0);
+            self.playlist.media_sequence += 1;
+            if removed.discontinuity {
+                self.playlist.discontinuity_sequence += 1;
+            }
+            let next = &mut self.playlist.segments[0];
+            if next.map.is_none() {
+                next.map = removed.map;
+            }
+            let mut uris: Vec<_> = removed.parts.into_iter().map(|p| p.uri).collect();
+            uris.push(removed.uri);
+            self.queue_removal(end, hold, uris);
+        }
+    }
+
+    /// Parts are only listed for segments in the last three target durations.
+    fn drop_old_parts(&mut self) {
+        let Some(end) = self.end else {
+            return;
+        };
+        let target = self.playlist.target_duration as f64;
+        let hold = self.playlist.duration().max(target);
+        let mut uris = Vec::new();
+        let mut age = 0.0;
+        for s in self.playlist.segments.iter_mut().rev() {
+            if age >= 3.0 * target {
+                uris.extend(s.parts.drain(..).map(|p| p.uri));
+            }
+            age += s.duration;
+        }
+        if !uris.is_empty() {
+            self.queue_removal(end, hold, uris);
+        }
+    }
+
+    /// Queues files clients may need for `hold` seconds after `end`.
+    fn queue_removal(&mut self, end: cm::Time, hold: f64, uris: Vec<String>) {
+        let at = end + cm::Time::with_secs(hold, end.scale);
+        self.removals.push((at, uris));
+    }
+
+    /// Deletes the queued files that are due. One that fails stays queued
+    /// for the next playlist update.
+    fn remove_expired(&mut self) -> io::Result<()> {
+        let Some(end) = self.end else {
+            return Ok(());
+        };
+        let mut i = 0;
+        while i < self.removals.len() {
+            let (at, uris) = &mut self.removals[i];
+            if *at > end {
+                i += 1;
+                continue;
+            }
+            while let Some(uri) = uris.last() {
+                remove_file(&self.cfg.dir.join(uri))?;
+                uris.pop();
+            }
+            self.removals.remove(i);
+        }
+        Ok(())
+    }
+
+    /// Writes the playlist, then deletes what no client can still need.
+    fn write_playlist(&mut self) -> Result<(), Error> {
+        write_atomic(&self.playlist_path(), self.playlist.to_string().as_bytes())?;
+        self.remove_expired()?;
+        Ok(())
+    }
+
+    /// Ends the playlist with `EXT-X-ENDLIST`.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        self.playlist.preload_hints.clear();
+        self.playlist.end_list = true;
+        self.write_playlist()
+    }
+}
+
+fn write_atomic(path: &Path, data: &[u8]) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+fn remove_file(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(all(target_vendor = "apple", feature = "av"))]
+impl Segmenter {
+    /// Writes what `av::AssetWriterDelegate` receives in
+    /// `asset_writer_did_output_segment_data_with_report`, the segment
+    /// range being the union of the track ranges in `report`.
+    pub fn write_asset_writer_segment(
+        &mut self,
+        data: &ns::Data,
+        typ: av::AssetSegmentType,
+        report: Option<&av::AssetSegmentReport>,
+    ) -> Result<(), Error> {
+        match typ {
+            av::AssetSegmentType::Initialization => self.write_init(data.as_slice()),
+            av::AssetSegmentType::Separable => {
+                let report = report.ok_or(Error::State("separable segment without a report"))?;
+                let range = report
+                    .track_reports()
+                    .iter()
+                    .map(|t| cm::TimeRange::new(t.earliest_pts(), t.duration()))
+                    .reduce(|a, b| a.union(&b))
+                    .ok_or(Error::State("segment report without tracks"))?;
+                self.write_segment(data.as_slice(), range)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        fs,
+        path::PathBuf,
+        time::{Duration, SystemTime},
+    };
+
+    use crate::{
+        cm::{
+            self,
+            iso_bmff::{self, IsoBox},
+        },
+        fcc,
+        hls::{self, Segmenter, SegmenterCfg},
+    };
+
+    fn tmp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cidre-hls-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// `styp`, `moof` and `mdat` like `av::AssetWriter` produces for one segment.
+    fn segment(seq: u32, decode_time: u64) -> Vec<u8> {
+        let mut out = Vec::new();
+        iso_bmff::Styp(iso_bmff::Ftyp {
+            major_brand: fcc!("msdh"),
+            minor_version: 0,
+            compatible_brands: vec![fcc!("msdh"), fcc!("msix")],
+        })
+        .write_to(&mut out);
+        let mut moof = iso_bmff::Moof {
+            mfhd: iso_bmff::Mfhd {
+                sequence_number: seq,
+            },
+            trafs: vec![iso_bmff::Traf {
+                tfhd: iso_bmff::Tfhd {
+                    track_id: 1,
+                    ..Default::default()
+                },
+                tfdt: Some(iso_bmff::Tfdt {
+                    version: 1,
+                    base_media_decode_time: decode_time,
+                }),
+                truns: vec![iso_bmff::Trun {
+                    samples: vec![iso_bmff::TrunSample {
+                        size: Some(4),
+                        ..Default::default()
+                    }],
+                    ..Default::default()
+                }],
+            }],
+        };
+        iso_bmff::write_fragment(&mut out, &mut moof, &[0, 0, 0, 0]).unwrap();
+        out
+    }
+
+    fn range(start: i64, duration: i64) -> cm::TimeRange {
+        cm::TimeRange::new(cm::Time::new(start, 600), cm::Time::new(duration, 600))
+    }
+
+    #[test]
+    fn rolling_window() {
+        let dir = tmp_dir("window");
+        let mut cfg = SegmenterCfg::new(&dir, "live", 6);
+        cfg.window = Some(3);
+        cfg.program_date_time = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_200));
+        let mut s = Segmenter::new(cfg);
+
+        assert!(matches!(
+            s.write_segment(&segment(0, 0), range(0, 3600)),
+            Err(hls::Error::State(_))
+        ));
+        s.write_init(b"init").unwrap();
+
+        // 6s, 6s, 5.5s, gap, 6s, 6s
+        for (i, (start, duration)) in [
+            (0, 3600),
+            (3600, 3600),
+            (7200, 3300),
+            (12000, 3600),
+            (15600, 3600),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let data = segment(i as u32, start as u64);
+            s.write_segment(&data, range(start, duration)).unwrap();
+            assert_eq!(fs::read(dir.join(format!("live{i}.m4s"))).unwrap(), data);
+        }
+        assert!(matches!(
+            s.write_segment(&segment(5, 0), range(0, 3600)),
+            Err(hls::Error::State(_))
+        ));
+        // 6.6s rounds past the target
+        assert!(matches!(
+            s.write_segment(&segment(5, 19200), range(19200, 3960)),
+            Err(hls::Error::SegmentDuration { target: 6, .. })
+        ));
+        assert!(!dir.join("live5.m4s").exists());
+        s.finish().unwrap();
+
+        let text = fs::read_to_string(s.playlist_path()).unwrap();
+        let p: hls::MediaPlaylist = text.parse().unwrap();
+        assert_eq!(&p, s.playlist());
+        assert_eq!(p.target_duration, 6);
+        assert_eq!((p.media_sequence, p.discontinuity_sequence), (2, 0));
+        let uris: Vec<_> = p.segments.iter().map(|s| s.uri.as_str()).collect();
+        assert_eq!(uris, ["live2.m4s", "live3.m4s", "live4.m4s"]);
+        assert_eq!(p.segments[0].map.as_ref().unwrap().uri, "live.mp4");
+        assert!(p.segments[1].discontinuity);
+        assert_eq!(
+            p.segments[1].program_date_time,
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1_704_067_220))
+        );
+        assert!(p.end_list);
+
+        // evicted, but clients may still load them
+        assert!(dir.join("live0.m4s").exists());
+        assert!(dir.join("live1.m4s").exists());
+        assert!(fs::read_dir(&dir).unwrap().all(|e| !e
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn delayed_removal() {
+        let dir = tmp_dir("removal");
+        let mut cfg = SegmenterCfg::new(&dir, "live", 2);
+        cfg.window = Some(2);
+        let mut s = Segmenter::new(cfg);
+        s.write_init(b"init").unwrap();
+        let exists = |i: usize| dir.join(format!("live{i}.m4s")).exists();
+
+        for i in 0..3 {
+            s.write_segment(b"seg", range(i * 1200, 1200)).unwrap();
+        }
+        // out of the playlist, but the previous one listed it
+        assert_eq!(s.playlist().media_sequence, 1);
+        assert!(exists(0));
+
+        // 2s segment plus the 4s playlist listing it after leaving at 6s
+        for i in 3..5 {
+            s.write_segment(b"seg", range(i * 1200, 1200)).unwrap();
+            assert!(exists(0));
+        }
+        s.write_segment(b"seg", range(6000, 1200)).unwrap();
+        assert!(!exists(0));
+        assert!(exists(1) && exists(4));
+
+        // a failed removal is retried on the next playlist update
+        fs::remove_file(dir.join("live1.m4s")).unwrap();
+        fs::create_dir(dir.join("live1.m4s")).unwrap();
+        fs::write(dir.join("live1.m4s/x"), b"x").unwrap();
+        assert!(matches!(
+            s.write_segment(b"seg", range(7200, 1200)),
+            Err(hls::Error::Io(_))
+        ));
+        assert_eq!(s.playlist().segments[1].uri, "live6.m4s");
+        fs::remove_dir_all(dir.join("live1.m4s")).unwrap();
+        fs::write(dir.join("live1.m4s"), b"seg").unwrap();
+        s.finish().unwrap();
+        assert!(!exists(1));
+        assert!(exists(2));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn write_failure() {
+        let dir = tmp_dir("failure");
+        let mut cfg = SegmenterCfg::new(&dir, "ll", 2);
+        cfg.part_target = Some(1.0);
+        let mut s = Segmenter::new(cfg);
+        s.write_init(b"init").unwrap();
+        s.write_part(b"p0", range(0, 600), true).unwrap();
+
+        // a directory in the way makes the rename fail
+        fs::create_dir(dir.join("ll0.1.m4s")).unwrap();
+        assert!(matches!(
+            s.write_part(b"p1", range(600, 600), false),
+            Err(hls::Error::Io(_))
+        ));
+        assert_eq!(s.playlist().parts.len(), 1);
+        fs::remove_dir(dir.join("ll0.1.m4s")).unwrap();
+        s.write_part(b"p1", range(600, 600), false).unwrap();
+
+        fs::create_dir(dir.join("ll0.m4s")).unwrap();
+        let before = s.playlist().clone();
+        assert!(matches!(
+            s.write_segment(b"p0p1", range(0, 1200)),
+            Err(hls::Error::Io(_))
+        ));
+        assert_eq!(s.playlist(), &before);
+        fs::remove_dir(dir.join("ll0.m4s")).unwrap();
+
+        // the retry keeps the parts, the sequence number and continuity
+        s.write_segment(b"p0p1", range(0, 1200)).unwrap();
+        s.write_part(b"p0", range(1200, 600), true).unwrap();
+        let p = s.playlist();
+        assert_eq!(p.segments.len(), 1);
+        assert_eq!(p.segments[0].uri, "ll0.m4s");
+        assert_eq!(p.segments[0].parts.len(), 2);
+        assert!(!p.segments[0].discontinuity);
+        assert_eq!(p.parts[0].uri, "ll1.0.m4s");
+        assert!(!s.discontinuity);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn parts() {
+        let dir = tmp_dir("parts");
+        let mut cfg = SegmenterCfg::new(&dir, "ll", 2);
+        cfg.part_target = Some(1.0);
+        cfg.playlist_type = Some(hls::PlaylistType::Event);
+        let mut s = Segmenter::new(cfg);
+        s.write_init(b"init").unwrap();
+
+        for i in 0..3 {
+            let start = i * 1200;
+            s.write_part(b"p0", range(start, 600), true).unwrap();
+            s.write_part(b"p1", range(start + 600, 600), false).unwrap();
+            assert_eq!(s.playlist().parts.len(), 2);
+            assert_eq!(s.playlist().preload_hints[0].uri, format!("ll{i}.2.m4s"));
+            s.write_segment(b"p0p1", range(start, 1200)).unwrap();
+            assert!(s.playlist().parts.is_empty());
+        }
+        // a format change
+        s.write_init(b"init2").unwrap();
+        s.write_part(b"p0", range(3600, 600), true).unwrap();
+
+        let text = fs::read_to_string(s.playlist_path()).unwrap();
+        let p: hls::MediaPlaylist = text.parse().unwrap();
+        assert_eq!(&p, s.playlist());
+        assert_eq!(p.server_control.unwrap().part_hold_back, Some(3.0));
+        assert_eq!(p.segments.len(), 3);
+        assert!(p.segments.iter().all(|s| s.parts.len() == 2));
+        assert_eq!(p.parts[0].uri, "ll3.0.m4s");
+        assert_eq!(fs::read(dir.join("ll_1.mp4")).unwrap(), b"init2");
+
+        s.write_segment(b"p0p1", range(3600, 1200)).unwrap();
+        let p = s.playlist();
+        assert!(p.segments[3].discontinuity);
+        assert_eq!(p.segments[3].map.as_ref().unwrap().uri, "ll_1.mp4");
+        // 4 segments of 2s, parts only for the last 6s
+        assert!(p.segments[0].parts.is_empty());
+        assert_eq!(p.segments[1].parts.len(), 2);
+        assert!(dir.join("ll0.0.m4s").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
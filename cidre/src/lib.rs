@@ -2,6 +2,8 @@ pub mod mac_types;
 
 pub use mac_types::four_cc_to_str;
 pub use mac_types::four_cc_to_string;
+pub use mac_types::FourCc;
+pub use mac_types::FourCcParseError;
 pub use mac_types::FourCharCode;
 pub use mac_types::ResType;
 pub use mac_types::UniChar;
@@ -11,28 +13,28 @@ pub use mac_types::UniChar;
 pub mod am;
 
 /// Audio Toolkit
-#[cfg(feature = "at")]
+#[cfg(all(target_vendor = "apple", feature = "at"))]
 pub mod at;
 
 /// AudioVisual Foundation (AVFoundation)
-#[cfg(feature = "av")]
+#[cfg(all(target_vendor = "apple", feature = "av"))]
 pub mod av;
 
 /// Core Animation
 #[cfg(not(target_os = "watchos"))]
-#[cfg(feature = "ca")]
+#[cfg(all(target_vendor = "apple", feature = "ca"))]
 pub mod ca;
 
 /// Core Image
-#[cfg(feature = "ci")]
+#[cfg(all(target_vendor = "apple", feature = "ci"))]
 pub mod ci;
 
 /// Core Foundation
-#[cfg(feature = "cf")]
+#[cfg(all(target_vendor = "apple", feature = "cf"))]
 pub mod cf;
 
 /// Core Text
-#[cfg(feature = "ct")]
+#[cfg(all(target_vendor = "apple", feature = "ct"))]
 pub mod ct;
 
 /// Core Graphics
@@ -43,9 +45,13 @@ pub mod cg;
 #[cfg(feature = "cm")]
 pub mod cm;
 
+/// HTTP Live Streaming
+#[cfg(feature = "hls")]
+pub mod hls;
+
 /// Core Motion
 #[cfg(not(target_os = "tvos"))]
-#[cfg(feature = "core_motion")]
+#[cfg(all(target_vendor = "apple", feature = "core_motion"))]
 pub mod core_motion;
 
 #[cfg(all(target_os = "macos", feature = "core_audio"))]
@@ -55,32 +61,33 @@ pub mod core_audio;
 #[cfg(feature = "cv")]
 pub mod cv;
 
-#[cfg(feature = "cl")]
+#[cfg(all(target_vendor = "apple", feature = "cl"))]
 pub mod cl;
 
 /// Grand Central Dispatch
-#[cfg(feature = "dispatch")]
+#[cfg(all(target_vendor = "apple", feature = "dispatch"))]
 pub mod dispatch;
 
 /// Disk Arbitration
 #[cfg(target_os = "macos")]
-#[cfg(feature = "da")]
+#[cfg(all(target_vendor = "apple", feature = "da"))]
 pub mod da;
 
 /// IOSurface
-#[cfg(feature = "io")]
+#[cfg(all(target_vendor = "apple", feature = "io"))]
 pub mod io;
 
 /// cidre vision of obj-c blocks impl in rust
-#[cfg(feature = "blocks")]
+#[cfg(all(target_vendor = "apple", feature = "blocks"))]
 pub mod blocks;
 
 /// mach
+#[cfg(target_vendor = "apple")]
 pub mod mach;
 
 /// MultipeerConnectivity
 #[cfg(not(target_os = "watchos"))]
-#[cfg(feature = "mc")]
+#[cfg(all(target_vendor = "apple", feature = "mc"))]
 pub mod mc;
 
 /// Metal
@@ -88,49 +95,68 @@ pub mod mc;
 pub mod mtl;
 
 /// MetalKit
-#[cfg(feature = "mtk")]
+#[cfg(all(target_vendor = "apple", feature = "mtk"))]
 pub mod mtk;
 
 /// MLCompute
-#[cfg(feature = "mlc")]
+#[cfg(all(target_vendor = "apple", feature = "mlc"))]
 pub mod mlc;
 
 /// Metal Performance Shaders
-#[cfg(feature = "mps")]
+#[cfg(all(target_vendor = "apple", feature = "mps"))]
 pub mod mps;
 
 /// Foundation
-#[cfg(feature = "ns")]
+#[cfg(all(target_vendor = "apple", feature = "ns"))]
 pub mod ns;
 
 /// Natural Language
-#[cfg(feature = "nl")]
+#[cfg(all(target_vendor = "apple", feature = "nl"))]
 pub mod nl;
 
 /// Network
-#[cfg(feature = "nw")]
+#[cfg(all(target_vendor = "apple", feature = "nw"))]
 pub mod nw;
 
-#[cfg(feature = "ns")]
+#[cfg(all(target_vendor = "apple", feature = "ns"))]
 pub mod objc;
 
 /// Game Controller
-#[cfg(feature = "gc")]
+#[cfg(all(target_vendor = "apple", feature = "gc"))]
 pub mod gc;
 
 pub mod os;
 pub mod sys;
 
+/// Property lists without CoreFoundation
+#[cfg(feature = "plist")]
+pub mod plist;
+
+/// usbmuxd client without MobileDevice.framework
+#[cfg(feature = "usbmux")]
+pub mod usbmux;
+
+/// lockdownd client without MobileDevice.framework
+#[cfg(feature = "lockdown")]
+pub mod lockdown;
+
+/// Apple File Conduit client without MobileDevice.framework
+#[cfg(feature = "afc")]
+pub mod afc;
+
 /// Security
-#[cfg(feature = "sec")]
+#[cfg(all(target_vendor = "apple", feature = "sec"))]
 pub mod sec;
 
 /// Video Toolbox
-#[cfg(feature = "vt")]
+#[cfg(all(target_vendor = "apple", feature = "vt"))]
 pub mod vt;
 
 /// Accelerate vecLib vDSP
-#[cfg(feature = "vdsp")]
+#[cfg(any(
+    all(target_vendor = "apple", feature = "vdsp"),
+    feature = "vdsp_portable"
+))]
 pub mod vdsp;
 
 /// Screen Capture Kit
@@ -138,7 +164,7 @@ pub mod vdsp;
 pub mod sc;
 
 /// Sound Analysis
-#[cfg(feature = "sn")]
+#[cfg(all(target_vendor = "apple", feature = "sn"))]
 pub mod sn;
 
 /// CoreAudioTypes
@@ -157,35 +183,39 @@ pub mod cat;
 pub mod ui;
 
 /// UniformTypeIdentifiers
-#[cfg(feature = "ut")]
+#[cfg(all(target_vendor = "apple", feature = "ut"))]
 pub mod ut;
 
-#[cfg(feature = "un")]
+#[cfg(all(target_vendor = "apple", feature = "un"))]
 pub mod un;
 
+#[cfg(target_vendor = "apple")]
 pub mod time;
 
+#[cfg(target_vendor = "apple")]
 pub mod dns_sd;
 
 #[cfg(feature = "simd")]
 pub mod simd;
 
 /// Vision
-#[cfg(feature = "vn")]
+#[cfg(all(target_vendor = "apple", feature = "vn"))]
 pub mod vn;
 
 /// WatchConnectivity
 #[cfg(target_os = "ios")]
-#[cfg(feature = "wc")]
+#[cfg(all(target_vendor = "apple", feature = "wc"))]
 pub mod wc;
 
 /// Web Kit
 #[cfg(not(any(target_os = "tvos", target_os = "watchos")))]
-#[cfg(feature = "wk")]
+#[cfg(all(target_vendor = "apple", feature = "wk"))]
 pub mod wk;
 
+#[cfg(target_vendor = "apple")]
 pub mod arc;
 
+#[cfg(target_vendor = "apple")]
 pub mod api;
 
 #[macro_export]
@@ -260,7 +290,7 @@ impl std::fmt::Debug for dyn Dyn {
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, target_vendor = "apple", feature = "cf"))]
 mod tests {
     use crate::cf;
 
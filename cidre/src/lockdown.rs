@@ -0,0 +1,758 @@
+//! lockdownd client without MobileDevice.framework.
+//!
+//! lockdownd listens on device port 62078, reachable through usbmuxd.
+//! Messages are property lists behind a big endian 32 bit length. Sessions
+//! authenticate with a pair record and switch the connection to TLS with a
+//! [`Tls`](crate::lockdown::Tls) implementation,
+//! [`Rustls`](crate::lockdown::Rustls) or another one, since certificates
+//! of devices don't pass usual verification.
+//!
+//! ```no_run
+//! use cidre::{lockdown, usbmux};
+//!
+//! let device = usbmux::Client::connect().unwrap().devices().unwrap().remove(0);
+//! let stream = usbmux::Client::connect()
+//!     .unwrap()
+//!     .connect_to(device.connection_id(), lockdown::PORT)
+//!     .unwrap();
+//! let mut client = lockdown::Client::new(stream);
+//! let name = client.value(Some("DeviceName")).unwrap();
+//! println!("{:?}", name.as_str());
+//! ```
+
+use std::io::{self, Read, Write};
+
+mod pki;
+
+mod tls;
+pub use tls::Rustls;
+
+pub use rsa::rand_core::{CryptoRngCore, OsRng};
+
+use crate::{
+    plist::{self, Dictionary, Value},
+    usbmux::PairRecord,
+};
+
+/// Device port of lockdownd.
+pub const PORT: u16 = 62078;
+
+/// `Type` of `QueryType` replies of lockdownd.
+pub const SERVICE_TYPE: &str = "com.apple.mobile.lockdown";
+
+/// RSA key size of new pairings.
+pub const KEY_BITS: usize = 2048;
+
+/// `ProtocolVersion` of pairing requests.
+const PAIRING_PROTOCOL: &str = "2";
+
+/// lockdownd messages are small, anything bigger is a broken stream.
+const MAX_MESSAGE_LEN: usize = 16 << 20;
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Plist(plist::Error),
+
+    /// Malformed message or reply of an unexpected shape.
+    Protocol(&'static str),
+
+    /// `Error` of a reply, like `InvalidHostID` or `PasswordProtected`.
+    Lockdown(String),
+
+    /// Key, certificate or TLS setup the crypto crates refused.
+    Crypto(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Error {
+    /// Error name lockdownd replied with.
+    pub fn lockdown_error(&self) -> Option<&str> {
+        match self {
+            Self::Lockdown(name) => Some(name),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Plist(e) => e.fmt(f),
+            Self::Protocol(what) => write!(f, "lockdown: {what}"),
+            Self::Lockdown(name) => write!(f, "lockdown: {name}"),
+            Self::Crypto(e) => write!(f, "lockdown: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Plist(e) => Some(e),
+            Self::Crypto(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<plist::Error> for Error {
+    fn from(value: plist::Error) -> Self {
+        Self::Plist(value)
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// Byte stream of a device connection, a usbmux tunnel, the socket of
+/// `am::ServiceConnection` or a TLS stream over either.
+pub trait Stream: Read + Write + Send {}
+
+impl<T: Read + Write + Send> Stream for T {}
+
+/// TLS client handshake of lockdownd sessions and SSL services.
+///
+/// Implementations authenticate with `host_certificate` and
+/// `host_private_key` of the record, both PEM, and trust
+/// `root_certificate` and `device_certificate` only. Devices before
+/// iOS 13 may need TLS 1.0 or 1.1.
+pub trait Tls {
+    fn connect(&self, stream: Box<dyn Stream>, record: &PairRecord) -> io::Result<Box<dyn Stream>>;
+}
+
+/// Random UUID in the uppercase form of `HostID`s, [`OsRng`] makes a
+/// good `rng`.
+pub fn new_host_id(rng: &mut impl CryptoRngCore) -> String {
+    let mut b = [0u8; 16];
+    rng.fill_bytes(&mut b);
+    b[6] = b[6] & 0x0f | 0x40;
+    b[8] = b[8] & 0x3f | 0x80;
+    let hex: String = b.iter().map(|b| format!("{b:02X}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Pair record of a new pairing, `device_public_key` is the PEM
+/// `DevicePublicKey` of the device.
+///
+/// Generates root and host keys of `key_bits` bits and certificates of
+/// them and the device key, the record is ready for [`Client::pair`].
+pub fn new_pair_record(
+    device_public_key: &[u8],
+    host_id: &str,
+    system_buid: &str,
+    key_bits: usize,
+    rng: &mut impl CryptoRngCore,
+) -> Result<PairRecord> {
+    let device_key = pki::public_key(device_public_key)
+        .map_err(|_| Error::Protocol("DevicePublicKey isn't an RSA key"))?;
+    let creds = pki::Credentials::generate(&device_key, key_bits, rng)?;
+    Ok(PairRecord {
+        host_id: host_id.to_string(),
+        system_buid: system_buid.to_string(),
+        host_certificate: creds.host_certificate,
+        host_private_key: creds.host_private_key,
+        device_certificate: creds.device_certificate,
+        root_certificate: creds.root_certificate,
+        root_private_key: creds.root_private_key,
+        wifi_mac_address: None,
+        escrow_bag: None,
+    })
+}
+
+/// Writes a length prefixed property list.
+pub fn write_message<W: Write + ?Sized>(w: &mut W, msg: &Value) -> Result {
+    let body = msg.to_bytes(plist::Format::XmlV1_0)?;
+    let len = u32::try_from(body.len()).map_err(|_| Error::Protocol("message too long"))?;
+    let mut buf = Vec::with_capacity(4 + body.len());
+    buf.extend_from_slice(&len.to_be_bytes());
+    buf.extend_from_slice(&body);
+    w.write_all(&buf)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a length prefixed property list.
+pub fn read_message<R: Read + ?Sized>(r: &mut R) -> Result<Value> {
+    let mut len = [0u8; 4];
+    r.read_exact(&mut len)?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_MESSAGE_LEN {
+        return Err(Error::Protocol("bad message length"));
+    }
+    let mut body = vec![0u8; len];
+    r.read_exact(&mut body)?;
+    Ok(Value::from_bytes(&body)?)
+}
+
+/// Session of [`Client::start_session`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    pub id: String,
+    /// Whether the connection switched to TLS.
+    pub ssl: bool,
+}
+
+/// Service of [`Client::start_service`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Service {
+    pub name: String,
+    /// Device port to connect to through usbmuxd.
+    pub port: u16,
+    /// Whether the service connection needs a [`Tls`] handshake with the
+    /// same pair record.
+    pub ssl: bool,
+}
+
+/// Blocking lockdownd connection.
+pub struct Client {
+    stream: Box<dyn Stream>,
+    label: String,
+    session: Option<Session>,
+}
+
+impl Client {
+    pub fn new(stream: impl Stream + 'static) -> Self {
+        Self::with_boxed(Box::new(stream))
+    }
+
+    pub fn with_boxed(stream: Box<dyn Stream>) -> Self {
+        Self {
+            stream,
+            label: "cidre".to_string(),
+            session: None,
+        }
+    }
+
+    /// `Label` lockdownd logs requests with.
+    pub fn set_label(&mut self, label: &str) {
+        self.label = label.to_string();
+    }
+
+    #[inline]
+    pub fn session(&self) -> Option<&Session> {
+        self.session.as_ref()
+    }
+
+    /// Sends a request and reads its reply, `Error` replies are errors.
+    pub fn request(&mut self, request: &str, mut msg: Dictionary) -> Result<Dictionary> {
+        msg.insert("Label".to_string(), self.label.as_str().into());
+        msg.insert("Request".to_string(), request.into());
+        write_message(&mut self.stream, &msg.into())?;
+        let Value::Dictionary(reply) = read_message(&mut self.stream)? else {
+            return Err(Error::Protocol("reply isn't a dictionary"));
+        };
+        if let Some(error) = reply.get("Error").and_then(Value::as_str) {
+            return Err(Error::Lockdown(error.to_string()));
+        }
+        if reply.get("Request").and_then(Value::as_str) != Some(request) {
+            return Err(Error::Protocol("reply to another request"));
+        }
+        Ok(reply)
+    }
+
+    /// `com.apple.mobile.lockdown` of lockdownd.
+    pub fn query_type(&mut self) -> Result<String> {
+        let reply = self.request("QueryType", Dictionary::new())?;
+        reply
+            .get("Type")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or(Error::Protocol("reply without Type"))
+    }
+
+    /// Value of a key of the default domain, all values without a key.
+    ///
+    /// Most keys need a session, `DeviceName`, `ProductVersion`,
+    /// `DevicePublicKey` and a few others don't.
+    pub fn value(&mut self, key: Option<&str>) -> Result<Value> {
+        self.get_value(None, key)
+    }
+
+    /// Value of a key of a domain like `com.apple.disk_usage`, all values
+    /// of the domain without a key.
+    pub fn domain_value(&mut self, domain: &str, key: Option<&str>) -> Result<Value> {
+        self.get_value(Some(domain), key)
+    }
+
+    fn get_value(&mut self, domain: Option<&str>, key: Option<&str>) -> Result<Value> {
+        let mut msg = Dictionary::new();
+        if let Some(domain) = domain {
+            msg.insert("Domain".to_string(), domain.into());
+        }
+        if let Some(key) = key {
+            msg.insert("Key".to_string(), key.into());
+        }
+        let mut reply = self.request("GetValue", msg)?;
+        reply
+            .remove("Value")
+            .ok_or(Error::Protocol("reply without Value"))
+    }
+
+    pub fn set_value(&mut self, domain: Option<&str>, key: &str, value: Value) -> Result {
+        let mut msg = Dictionary::new();
+        if let Some(domain) = domain {
+            msg.insert("Domain".to_string(), domain.into());
+        }
+        msg.insert("Key".to_string(), key.into());
+        msg.insert("Value".to_string(), value);
+        self.request("SetValue", msg).map(|_| ())
+    }
+
+    /// Starts a session of a paired host, switching to TLS with `tls` when
+    /// the device asks for it.
+    pub fn start_session(&mut self, record: &PairRecord, tls: &dyn Tls) -> Result<&Session> {
+        let mut msg = Dictionary::new();
+        msg.insert("HostID".to_string(), record.host_id.as_str().into());
+        msg.insert("SystemBUID".to_string(), record.system_buid.as_str().into());
+        let reply = self.request("StartSession", msg)?;
+        let id = reply
+            .get("SessionID")
+            .and_then(Value::as_str)
+            .ok_or(Error::Protocol("reply without SessionID"))?
+            .to_string();
+        let ssl = reply
+            .get("EnableSessionSSL")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        if ssl {
+            let stream = std::mem::replace(&mut self.stream, Box::new(io::Cursor::new(Vec::new())));
+            self.stream = tls.connect(stream, record)?;
+        }
+        Ok(self.session.insert(Session { id, ssl }))
+    }
+
+    /// Ends the session. lockdownd goes back to plain text after it, so a
+    /// TLS session leaves the connection unusable.
+    pub fn stop_session(&mut self) -> Result {
+        let Some(session) = self.session.take() else {
+            return Ok(());
+        };
+        let mut msg = Dictionary::new();
+        msg.insert("SessionID".to_string(), session.id.into());
+        self.request("StopSession", msg).map(|_| ())
+    }
+
+    /// Starts a service like `com.apple.afc`, most services need a session.
+    ///
+    /// Services of backups and restores want the `escrow_bag` of the pair
+    /// record to work with a locked device.
+    pub fn start_service(&mut self, name: &str, escrow_bag: Option<&[u8]>) -> Result<Service> {
+        let mut msg = Dictionary::new();
+        msg.insert("Service".to_string(), name.into());
+        if let Some(bag) = escrow_bag {
+            msg.insert("EscrowBag".to_string(), bag.into());
+        }
+        let reply = self.request("StartService", msg)?;
+        let port = reply
+            .get("Port")
+            .and_then(Value::as_u64)
+            .and_then(|v| u16::try_from(v).ok())
+            .ok_or(Error::Protocol("reply without Port"))?;
+        let ssl = reply
+            .get("EnableServiceSSL")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        Ok(Service {
+            name: name.to_string(),
+            port,
+            ssl,
+        })
+    }
+
+    /// [`new_pair_record`] of the `DevicePublicKey` and `WiFiAddress` of
+    /// the device with [`KEY_BITS`] keys.
+    pub fn new_pair_record(
+        &mut self,
+        host_id: &str,
+        system_buid: &str,
+        rng: &mut impl CryptoRngCore,
+    ) -> Result<PairRecord> {
+        let key = self.value(Some("DevicePublicKey"))?;
+        let key = key
+            .as_data()
+            .ok_or(Error::Protocol("DevicePublicKey isn't data"))?;
+        let mut record = new_pair_record(key, host_id, system_buid, KEY_BITS, rng)?;
+        if let Ok(Value::String(mac)) = self.value(Some("WiFiAddress")) {
+            record.wifi_mac_address = Some(mac);
+        }
+        Ok(record)
+    }
+
+    /// Pairs with the record, storing the escrow bag of the device in it.
+    ///
+    /// Fails with `PairingDialogResponsePending` until the user trusts the
+    /// host on the device and with `PasswordProtected` while it's locked,
+    /// retrying is up to the caller. Save the record with
+    /// `usbmux::Client::save_pair_record` for other tools to use it.
+    pub fn pair(&mut self, record: &mut PairRecord) -> Result {
+        let mut options = Dictionary::new();
+        options.insert("ExtendedPairingErrors".to_string(), true.into());
+        let mut msg = self.pairing_msg(record);
+        msg.insert("PairingOptions".to_string(), options.into());
+        let reply = self.request("Pair", msg)?;
+        if let Some(bag) = reply.get("EscrowBag").and_then(Value::as_data) {
+            record.escrow_bag = Some(bag.to_vec());
+        }
+        Ok(())
+    }
+
+    /// Checks the device still trusts the record.
+    pub fn validate_pair(&mut self, record: &PairRecord) -> Result {
+        let msg = self.pairing_msg(record);
+        self.request("ValidatePair", msg).map(|_| ())
+    }
+
+    /// Removes the pairing from the device.
+    pub fn unpair(&mut self, record: &PairRecord) -> Result {
+        let msg = self.pairing_msg(record);
+        self.request("Unpair", msg).map(|_| ())
+    }
+
+    /// Public part of the record pairing requests carry.
+    fn pairing_msg(&self, record: &PairRecord) -> Dictionary {
+        let mut pair = Dictionary::new();
+        let data = [
+            ("DeviceCertificate", &record.device_certificate),
+            ("HostCertificate", &record.host_certificate),
+            ("RootCertificate", &record.root_certificate),
+        ];
+        for (k, v) in data {
+            pair.insert(k.to_string(), v.as_slice().into());
+        }
+        pair.insert("HostID".to_string(), record.host_id.as_str().into());
+        pair.insert("SystemBUID".to_string(), record.system_buid.as_str().into());
+        if let Some(mac) = &record.wifi_mac_address {
+            pair.insert("WiFiMACAddress".to_string(), mac.as_str().into());
+        }
+        let mut msg = Dictionary::new();
+        msg.insert("PairRecord".to_string(), pair.into());
+        msg.insert("ProtocolVersion".to_string(), PAIRING_PROTOCOL.into());
+        msg
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> Box<dyn Stream> {
+        self.stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        net::{TcpListener, TcpStream},
+        sync::{Arc, Mutex},
+        thread,
+    };
+
+    use rsa::{
+        pkcs1::{EncodeRsaPublicKey, LineEnding},
+        RsaPrivateKey,
+    };
+
+    use crate::{
+        lockdown::{self, pki, pki::tests::TestRng, Client, Error, Stream, Tls},
+        plist::{Dictionary, Value},
+        usbmux::{tests::pair_record, PairRecord},
+    };
+
+    /// Scripted lockdownd, answers requests with `reply` until the client
+    /// hangs up.
+    fn stand_in(
+        mut reply: impl FnMut(&str, &Dictionary) -> Dictionary + Send + 'static,
+    ) -> TcpStream {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut s, _) = listener.accept().unwrap();
+            while let Ok(msg) = lockdown::read_message(&mut s) {
+                let msg = msg.as_dictionary().unwrap();
+                assert_eq!(msg.get("Label").and_then(Value::as_str), Some("cidre"));
+                let request = msg["Request"].as_str().unwrap();
+                let mut res = reply(request, msg);
+                res.insert("Request".to_string(), request.into());
+                lockdown::write_message(&mut s, &res.into()).unwrap();
+            }
+        });
+        TcpStream::connect(addr).unwrap()
+    }
+
+    fn dict(entries: &[(&str, Value)]) -> Dictionary {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    /// Records handshakes, keeps the stream plain.
+    #[derive(Default)]
+    struct Plain(Arc<Mutex<Vec<String>>>);
+
+    impl Tls for Plain {
+        fn connect(
+            &self,
+            stream: Box<dyn Stream>,
+            record: &PairRecord,
+        ) -> io::Result<Box<dyn Stream>> {
+            self.0.lock().unwrap().push(record.host_id.clone());
+            Ok(stream)
+        }
+    }
+
+    #[test]
+    fn values() {
+        let stream = stand_in(|request, msg| match request {
+            "QueryType" => dict(&[("Type", lockdown::SERVICE_TYPE.into())]),
+            "GetValue" => match (msg.get("Domain"), msg.get("Key").and_then(Value::as_str)) {
+                (None, Some("DeviceName")) => dict(&[("Value", "iPhone".into())]),
+                (Some(d), None) if d.as_str() == Some("com.apple.disk_usage") => {
+                    let v = dict(&[("TotalDiskCapacity", 64_000_000_000u64.into())]);
+                    dict(&[("Value", v.into())])
+                }
+                _ => dict(&[("Error", "MissingValue".into())]),
+            },
+            "SetValue" => {
+                assert_eq!(msg.get("Value").and_then(Value::as_bool), Some(true));
+                Dictionary::new()
+            }
+            _ => dict(&[("Error", "InvalidRequest".into())]),
+        });
+        let mut client = Client::new(stream);
+        assert_eq!(client.query_type().unwrap(), "com.apple.mobile.lockdown");
+        assert_eq!(
+            client.value(Some("DeviceName")).unwrap(),
+            Value::from("iPhone")
+        );
+        let usage = client.domain_value("com.apple.disk_usage", None).unwrap();
+        assert_eq!(
+            usage.get("TotalDiskCapacity").and_then(Value::as_u64),
+            Some(64_000_000_000)
+        );
+        let err = client.value(Some("Nope")).unwrap_err();
+        assert_eq!(err.lockdown_error(), Some("MissingValue"));
+        client
+            .set_value(
+                Some("com.apple.mobile.wireless_lockdown"),
+                "EnableWifiDebugging",
+                true.into(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn session() {
+        let record = pair_record();
+        let host_id = record.host_id.clone();
+        let stream = stand_in(move |request, msg| match request {
+            "StartSession" if msg["HostID"].as_str() == Some(&host_id) => dict(&[
+                ("SessionID", "F00D".into()),
+                ("EnableSessionSSL", true.into()),
+            ]),
+            "StartSession" => dict(&[("Error", "InvalidHostID".into())]),
+            "StartService" => {
+                assert!(msg.get("EscrowBag").is_none());
+                dict(&[
+                    ("Service", msg["Service"].clone()),
+                    ("Port", 49_152.into()),
+                    ("EnableServiceSSL", true.into()),
+                ])
+            }
+            "StopSession" => {
+                assert_eq!(msg["SessionID"].as_str(), Some("F00D"));
+                Dictionary::new()
+            }
+            _ => dict(&[("Error", "InvalidRequest".into())]),
+        });
+        let mut client = Client::new(stream);
+        let tls = Plain::default();
+
+        let mut unknown = record.clone();
+        unknown.host_id = "unknown".to_string();
+        let err = client.start_session(&unknown, &tls).unwrap_err();
+        assert_eq!(err.lockdown_error(), Some("InvalidHostID"));
+        assert!(tls.0.lock().unwrap().is_empty());
+
+        let session = client.start_session(&record, &tls).unwrap();
+        assert_eq!(session.id, "F00D");
+        assert!(session.ssl);
+        assert_eq!(*tls.0.lock().unwrap(), [record.host_id.as_str()]);
+
+        let service = client.start_service("com.apple.afc", None).unwrap();
+        assert_eq!(service.port, 49_152);
+        assert!(service.ssl);
+        client.stop_session().unwrap();
+        assert!(client.session().is_none());
+    }
+
+    #[test]
+    fn pair() {
+        let mut rng = TestRng(5);
+        let device_key = RsaPrivateKey::new(&mut rng, 512).unwrap().to_public_key();
+        let device_pem = device_key.to_pkcs1_pem(LineEnding::LF).unwrap();
+        let host_id = lockdown::new_host_id(&mut rng);
+        assert_eq!(host_id.len(), 36);
+        assert_eq!(&host_id[14..15], "4");
+
+        let mut record =
+            lockdown::new_pair_record(device_pem.as_bytes(), &host_id, "BUID", 512, &mut rng)
+                .unwrap();
+        let stream = stand_in(move |request, msg| {
+            let pair = msg["PairRecord"].as_dictionary().unwrap();
+            assert_eq!(msg["ProtocolVersion"].as_str(), Some("2"));
+            let der = |k: &str| pki::certificate_der(pair[k].as_data().unwrap()).unwrap();
+            let root = pki::subject_key(&der("RootCertificate")).unwrap();
+            let device_cert = der("DeviceCertificate");
+            assert!(pki::signed_by(&device_cert, &root));
+            assert_eq!(pki::subject_key(&device_cert).as_ref(), Some(&device_key));
+            assert!(pair.get("HostPrivateKey").is_none());
+            match request {
+                "Pair" => dict(&[("EscrowBag", b"bag".as_slice().into())]),
+                "ValidatePair" => Dictionary::new(),
+                _ => dict(&[("Error", "InvalidRequest".into())]),
+            }
+        });
+        let mut client = Client::new(stream);
+        client.pair(&mut record).unwrap();
+        assert_eq!(record.escrow_bag.as_deref(), Some(b"bag".as_slice()));
+        client.validate_pair(&record).unwrap();
+        let err = client.unpair(&record).unwrap_err();
+        assert!(matches!(err, Error::Lockdown(_)));
+
+        let err = lockdown::new_pair_record(b"key", &host_id, "BUID", 512, &mut rng).unwrap_err();
+        assert!(matches!(err, Error::Protocol(_)));
+    }
+
+    /// lockdownd switching to TLS after `StartSession`, the device side
+    /// serves `cert` with `key` and wants a client certificate of `root`.
+    fn tls_stand_in(
+        version: &'static rustls::SupportedProtocolVersion,
+        root: Vec<u8>,
+        cert: Vec<u8>,
+        key: Vec<u8>,
+    ) -> (TcpStream, thread::JoinHandle<io::Result<()>>) {
+        use rustls::{
+            crypto::ring,
+            pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer},
+            server::WebPkiClientVerifier,
+            RootCertStore, ServerConfig, ServerConnection, StreamOwned,
+        };
+
+        let provider = Arc::new(ring::default_provider());
+        let mut roots = RootCertStore::empty();
+        roots.add(CertificateDer::from(root)).unwrap();
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots.into(), provider.clone())
+            .build()
+            .unwrap();
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_protocol_versions(&[version])
+            .unwrap()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(
+                vec![CertificateDer::from(cert)],
+                PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key)),
+            )
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut s, _) = listener.accept()?;
+            let msg = lockdown::read_message(&mut s).unwrap();
+            assert_eq!(
+                msg.get("Request").and_then(Value::as_str),
+                Some("StartSession")
+            );
+            let reply = dict(&[
+                ("Request", "StartSession".into()),
+                ("SessionID", "F00D".into()),
+                ("EnableSessionSSL", true.into()),
+            ]);
+            lockdown::write_message(&mut s, &reply.into()).unwrap();
+
+            let conn = ServerConnection::new(Arc::new(config)).map_err(io::Error::other)?;
+            let mut tls = StreamOwned::new(conn, s);
+            let msg = lockdown::read_message(&mut tls).map_err(io::Error::other)?;
+            assert_eq!(msg.get("Key").and_then(Value::as_str), Some("DeviceName"));
+            assert_eq!(tls.conn.protocol_version(), Some(version.version));
+            let reply = dict(&[("Request", "GetValue".into()), ("Value", "iPhone".into())]);
+            lockdown::write_message(&mut tls, &reply.into()).map_err(io::Error::other)?;
+            Ok(())
+        });
+        (TcpStream::connect(addr).unwrap(), server)
+    }
+
+    #[test]
+    fn tls() {
+        use rsa::pkcs8::EncodePrivateKey;
+
+        let mut rng = TestRng(7);
+        // ring wants 2048 bit keys at least
+        let device_key = RsaPrivateKey::new(&mut rng, 2048).unwrap();
+        let device_pem = device_key
+            .to_public_key()
+            .to_pkcs1_pem(LineEnding::LF)
+            .unwrap();
+        let record =
+            lockdown::new_pair_record(device_pem.as_bytes(), "HOST", "BUID", 2048, &mut rng)
+                .unwrap();
+        let root = pki::certificate_der(&record.root_certificate).unwrap();
+        let device_cert = pki::certificate_der(&record.device_certificate).unwrap();
+        let device_der = device_key.to_pkcs8_der().unwrap().as_bytes().to_vec();
+
+        for version in rustls::ALL_VERSIONS {
+            let (stream, server) = tls_stand_in(
+                version,
+                root.clone(),
+                device_cert.clone(),
+                device_der.clone(),
+            );
+            let mut client = Client::new(stream);
+            let session = client.start_session(&record, &lockdown::Rustls).unwrap();
+            assert!(session.ssl);
+            assert_eq!(
+                client.value(Some("DeviceName")).unwrap(),
+                Value::from("iPhone")
+            );
+            server.join().unwrap().unwrap();
+        }
+
+        // a device certificate the pairing root didn't sign
+        let other =
+            pki::certificate(&device_key.to_public_key(), &device_key, false, true).unwrap();
+        let other = pki::certificate_der(&other).unwrap();
+        let (stream, server) = tls_stand_in(&rustls::version::TLS13, root, other, device_der);
+        let mut client = Client::new(stream);
+        let err = client
+            .start_session(&record, &lockdown::Rustls)
+            .unwrap_err();
+        let Error::Io(err) = err else {
+            panic!("{err:?}");
+        };
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(server.join().unwrap().is_err());
+    }
+
+    #[test]
+    fn framing() {
+        let msg: Value = dict(&[("Request", "QueryType".into())]).into();
+        let mut buf = Vec::new();
+        lockdown::write_message(&mut buf, &msg).unwrap();
+        assert_eq!(&buf[..4], &(buf.len() as u32 - 4).to_be_bytes());
+        assert_eq!(lockdown::read_message(&mut &buf[..]).unwrap(), msg);
+        assert!(lockdown::read_message(&mut &[0xffu8; 8][..]).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+//! [`Tls`] of rustls with the ring provider.
+
+use std::{io, sync::Arc};
+
+use rsa::RsaPublicKey;
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{self, ring, WebPkiSupportedAlgorithms},
+    pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName, UnixTime},
+    CertificateError, ClientConfig, ClientConnection, DigitallySignedStruct, SignatureScheme,
+    StreamOwned,
+};
+
+use crate::usbmux::PairRecord;
+
+use super::{pki, Error, Stream, Tls};
+
+/// TLS 1.2 and 1.3 client of lockdownd sessions and SSL services.
+///
+/// Authenticates with the host certificate and key of the record and
+/// accepts the device certificate of the record or any certificate its
+/// root certificate signed. Devices before iOS 13 that insist on TLS 1.0
+/// or 1.1 need another [`Tls`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Rustls;
+
+impl Rustls {
+    /// Client config of a pair record.
+    pub fn config(record: &PairRecord) -> super::Result<ClientConfig> {
+        let provider = Arc::new(ring::default_provider());
+        let verifier = PairVerifier {
+            device: pki::certificate_der(&record.device_certificate)?,
+            root: pki::subject_key(&pki::certificate_der(&record.root_certificate)?)
+                .ok_or(Error::Protocol("root certificate without an RSA key"))?,
+            algs: provider.signature_verification_algorithms,
+        };
+        let certs = vec![CertificateDer::from(pki::certificate_der(
+            &record.host_certificate,
+        )?)];
+        let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(pki::private_key_der(
+            &record.host_private_key,
+        )?));
+        let config = ClientConfig::builder_with_provider(provider)
+            .with_protocol_versions(rustls::ALL_VERSIONS)
+            .map_err(tls_error)?
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(verifier))
+            .with_client_auth_cert(certs, key)
+            .map_err(tls_error)?;
+        Ok(config)
+    }
+}
+
+impl Tls for Rustls {
+    fn connect(&self, stream: Box<dyn Stream>, record: &PairRecord) -> io::Result<Box<dyn Stream>> {
+        let config = Self::config(record).map_err(io::Error::other)?;
+        // names aren't checked, certificates of pairings have none
+        let name = ServerName::try_from("lockdown").map_err(io::Error::other)?;
+        let mut conn = ClientConnection::new(Arc::new(config), name).map_err(io::Error::other)?;
+        let mut stream = stream;
+        while conn.is_handshaking() {
+            conn.complete_io(&mut stream)?;
+        }
+        Ok(Box::new(StreamOwned::new(conn, stream)))
+    }
+}
+
+fn tls_error(e: rustls::Error) -> Error {
+    Error::Crypto(Box::new(e))
+}
+
+/// Trusts the device certificate of a pair record and what its root signed.
+#[derive(Debug)]
+struct PairVerifier {
+    device: Vec<u8>,
+    root: RsaPublicKey,
+    algs: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for PairVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let der = end_entity.as_ref();
+        if der == self.device || pki::signed_by(der, &self.root) {
+            Ok(ServerCertVerified::assertion())
+        } else {
+            Err(CertificateError::UnknownIssuer.into())
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(message, cert, dss, &self.algs)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(message, cert, dss, &self.algs)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algs.supported_schemes()
+    }
+}
@@ -7,7 +7,7 @@ pub fn four_cc_to_string(bytes: [u8; 4]) -> String {
     let mut res = String::with_capacity(4);
 
     for b in bytes {
-        let ch = if b >= b' ' && b <= b'~' {
+        let ch = if (b' '..=b'~').contains(&b) {
             unsafe { char::from_u32_unchecked(b as u32) }
         } else {
             '.'
@@ -21,21 +21,272 @@ pub fn four_cc_to_string(bytes: [u8; 4]) -> String {
 pub fn four_cc_to_str(bytes: &mut [u8; 4]) -> &str {
     for i in 0..4 {
         let b = unsafe { bytes.get_unchecked_mut(i) };
-        if b < &mut b' ' || b > &mut b'~' {
+        if !(b' '..=b'~').contains(b) {
             *b = b'.';
         };
     }
 
-    unsafe { &std::str::from_utf8_unchecked(bytes) }
+    unsafe { std::str::from_utf8_unchecked(bytes) }
+}
+
+/// A four character code, stored big-endian in a `u32` as Apple APIs expect.
+///
+/// ```
+/// use cidre::{fcc, FourCc};
+///
+/// const LPCM: FourCc = fcc!("lpcm");
+/// assert_eq!(LPCM.0, 0x6c70636d);
+/// assert_eq!(LPCM.to_string(), "lpcm");
+/// assert_eq!(format!("{LPCM:?}"), "'lpcm'");
+///
+/// assert_eq!("'lpcm'".parse(), Ok(LPCM));
+/// assert_eq!("0x6c70636d".parse(), Ok(LPCM));
+/// assert_eq!("1819304813".parse(), Ok(LPCM));
+///
+/// assert_eq!(FourCc(32).to_string(), r"\x00\x00\x00 ");
+/// ```
+#[doc(alias = "FourCharCode")]
+#[doc(alias = "OSType")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+#[repr(transparent)]
+pub struct FourCc(pub FourCharCode);
+
+impl FourCc {
+    #[inline]
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self(FourCharCode::from_be_bytes(bytes))
+    }
+
+    /// Const constructor behind [`fcc!`](crate::fcc!), panics if `str` is not 4 bytes long.
+    #[inline]
+    pub const fn from_str_lit(str: &str) -> Self {
+        let bytes = str.as_bytes();
+        assert!(bytes.len() == 4, "four char code must be 4 bytes long");
+        Self::from_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+
+    #[inline]
+    pub const fn to_bytes(self) -> [u8; 4] {
+        self.0.to_be_bytes()
+    }
+
+    /// All four bytes are printable ASCII.
+    #[inline]
+    pub const fn is_printable(self) -> bool {
+        let b = self.to_bytes();
+        is_printable(b[0]) && is_printable(b[1]) && is_printable(b[2]) && is_printable(b[3])
+    }
+
+    /// Parses the textual form only: four characters with optional `\xNN`
+    /// escapes, optionally quoted with `'`. Numeric forms are not accepted.
+    pub fn from_text(str: &str) -> Result<Self, FourCcParseError> {
+        let str = str
+            .strip_prefix('\'')
+            .and_then(|s| s.strip_suffix('\''))
+            .unwrap_or(str);
+        let mut bytes = [0u8; 4];
+        let mut len = 0;
+        let mut chars = str.chars();
+        while let Some(c) = chars.next() {
+            let b = match c {
+                '\\' => {
+                    if chars.next() != Some('x') {
+                        return Err(FourCcParseError::Escape);
+                    }
+                    let hex: String = chars.by_ref().take(2).collect();
+                    // from_str_radix takes a sign, `\x+1` isn't an escape
+                    if hex.len() != 2 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                        return Err(FourCcParseError::Escape);
+                    }
+                    u8::from_str_radix(&hex, 16).map_err(|_| FourCcParseError::Escape)?
+                }
+                c if c.is_ascii() => c as u8,
+                c => return Err(FourCcParseError::NonAscii(c)),
+            };
+            if len < 4 {
+                bytes[len] = b;
+            }
+            len += 1;
+        }
+        if len != 4 {
+            return Err(FourCcParseError::Len(len));
+        }
+        Ok(Self::from_bytes(bytes))
+    }
+}
+
+#[inline]
+const fn is_printable(b: u8) -> bool {
+    b >= b' ' && b <= b'~'
+}
+
+/// Four char code literal, evaluated at compile time.
+///
+/// ```
+/// use cidre::fcc;
+///
+/// assert_eq!(fcc!("avc1").to_bytes(), *b"avc1");
+/// ```
+#[macro_export]
+macro_rules! fcc {
+    ($lit:literal) => {{
+        const FCC: $crate::FourCc = $crate::FourCc::from_str_lit($lit);
+        FCC
+    }};
+}
+
+impl From<FourCharCode> for FourCc {
+    #[inline]
+    fn from(value: FourCharCode) -> Self {
+        Self(value)
+    }
+}
+
+impl From<FourCc> for FourCharCode {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        value.0
+    }
+}
+
+impl From<[u8; 4]> for FourCc {
+    #[inline]
+    fn from(value: [u8; 4]) -> Self {
+        Self::from_bytes(value)
+    }
+}
+
+impl From<FourCc> for [u8; 4] {
+    #[inline]
+    fn from(value: FourCc) -> Self {
+        value.to_bytes()
+    }
+}
+
+/// Printable ASCII as is, anything else (and `\`, `'`) as `\xNN`.
+impl std::fmt::Display for FourCc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::fmt::Write;
+        for b in self.to_bytes() {
+            if is_printable(b) && b != b'\\' && b != b'\'' {
+                f.write_char(b as char)?;
+            } else {
+                write!(f, "\\x{b:02x}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Quoted [`Display`](std::fmt::Display) form, which always parses back.
+impl std::fmt::Debug for FourCc {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "'{self}'")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FourCcParseError {
+    /// Number of bytes after unescaping
+    Len(usize),
+    /// Malformed `\xNN` escape
+    Escape,
+    NonAscii(char),
+    Int(std::num::ParseIntError),
+}
+
+impl std::fmt::Display for FourCcParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Len(len) => write!(f, "four char code must be 4 bytes, got {len}"),
+            Self::Escape => f.write_str("invalid escape, expected \\xNN"),
+            Self::NonAscii(c) => write!(f, "non ASCII character {c:?} in four char code"),
+            Self::Int(e) => write!(f, "invalid numeric four char code: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for FourCcParseError {}
+
+/// Accepts `'lpcm'`, `lpcm`, `0x6c70636d` and decimal `1819304813`.
+///
+/// Unquoted all-digit strings are numbers, so quote codes like `'1234'`.
+impl std::str::FromStr for FourCc {
+    type Err = FourCcParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+            return FourCharCode::from_str_radix(hex, 16)
+                .map(Self)
+                .map_err(FourCcParseError::Int);
+        }
+        if !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+            return s.parse().map(Self).map_err(FourCcParseError::Int);
+        }
+        Self::from_text(s)
+    }
+}
+
+/// Human readable formats use the [`Display`](std::fmt::Display) text,
+/// others the raw `u32`. Both representations are accepted on input.
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use super::{FourCc, FourCharCode};
+
+    impl serde::Serialize for FourCc {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(self)
+            } else {
+                serializer.serialize_u32(self.0)
+            }
+        }
+    }
+
+    struct Visitor;
+
+    impl serde::de::Visitor<'_> for Visitor {
+        type Value = FourCc;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            f.write_str("a four char code string or u32")
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<FourCc, E> {
+            FourCharCode::try_from(v)
+                .map(FourCc)
+                .map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(v), &self))
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<FourCc, E> {
+            FourCharCode::try_from(v)
+                .map(FourCc)
+                .map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(v), &self))
+        }
+
+        fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<FourCc, E> {
+            FourCc::from_text(v).map_err(E::custom)
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for FourCc {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_any(Visitor)
+            } else {
+                deserializer.deserialize_u32(Visitor)
+            }
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::four_cc_to_str;
+    use crate::{four_cc_to_str, FourCc, FourCcParseError};
 
     #[test]
     fn basics() {
-        let mut x: [u8; 4] = (*b"24BG").into();
+        let mut x: [u8; 4] = *b"24BG";
         let s = four_cc_to_str(&mut x);
         assert_eq!(s, "24BG");
 
@@ -43,4 +294,76 @@ mod tests {
         let s = four_cc_to_str(&mut bytes);
         assert_eq!(s, "....");
     }
+
+    #[test]
+    fn four_cc() {
+        const LPCM: FourCc = fcc!("lpcm");
+        assert_eq!(LPCM, FourCc::from_bytes(*b"lpcm"));
+        assert!(LPCM.is_printable());
+        assert!(!FourCc(0).is_printable());
+
+        assert_eq!(FourCc(0).to_string(), r"\x00\x00\x00\x00");
+        assert_eq!(fcc!("a\\bc").to_string(), r"a\x5cbc");
+        assert_eq!(format!("{:?}", FourCc(0x0000_0020)), r"'\x00\x00\x00 '");
+
+        for v in [
+            0u32,
+            32,
+            0x7f7f_7f7f,
+            LPCM.0,
+            u32::MAX,
+            fcc!("1234").0,
+            fcc!("'ab'").0,
+        ] {
+            let fcc = FourCc(v);
+            assert_eq!(format!("{fcc:?}").parse(), Ok(fcc));
+            assert_eq!(FourCc::from_text(&fcc.to_string()), Ok(fcc));
+        }
+
+        assert_eq!("1234".parse(), Ok(FourCc(1234)));
+        assert_eq!("'1234'".parse(), Ok(fcc!("1234")));
+        assert_eq!("0X6C70636D".parse(), Ok(LPCM));
+        assert_eq!("lpcm".parse(), Ok(LPCM));
+
+        assert_eq!("lpc".parse::<FourCc>(), Err(FourCcParseError::Len(3)));
+        assert_eq!("lpcmx".parse::<FourCc>(), Err(FourCcParseError::Len(5)));
+        assert_eq!(r"\x0".parse::<FourCc>(), Err(FourCcParseError::Escape));
+        assert_eq!(r"\y00abc".parse::<FourCc>(), Err(FourCcParseError::Escape));
+        assert_eq!(r"\x+1abc".parse::<FourCc>(), Err(FourCcParseError::Escape));
+        assert_eq!(FourCc::from_text(r"\x+1abc"), Err(FourCcParseError::Escape));
+        assert_eq!(FourCc::from_text(r"\x-1abc"), Err(FourCcParseError::Escape));
+        assert_eq!(
+            "lpcé".parse::<FourCc>(),
+            Err(FourCcParseError::NonAscii('é'))
+        );
+        assert!(matches!(
+            "0x1_0000_0000".parse::<FourCc>(),
+            Err(FourCcParseError::Int(_))
+        ));
+        assert!(matches!(
+            "4294967296".parse::<FourCc>(),
+            Err(FourCcParseError::Int(_))
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn four_cc_serde() {
+        use serde::{
+            de::{value, IntoDeserializer},
+            Deserialize,
+        };
+
+        let de: value::StrDeserializer<value::Error> = r"'\x00abc'".into_deserializer();
+        assert_eq!(FourCc::deserialize(de).unwrap(), fcc!("\0abc"));
+
+        let de: value::U32Deserializer<value::Error> = 0x6c70636du32.into_deserializer();
+        assert_eq!(FourCc::deserialize(de).unwrap(), fcc!("lpcm"));
+
+        let de: value::StrDeserializer<value::Error> = "1234".into_deserializer();
+        assert_eq!(FourCc::deserialize(de).unwrap(), fcc!("1234"));
+
+        let de: value::StrDeserializer<value::Error> = "lpc".into_deserializer();
+        assert!(FourCc::deserialize(de).is_err());
+    }
 }
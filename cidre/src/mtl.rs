@@ -6,295 +6,544 @@ pub use types::ResId;
 pub use types::SamplePos;
 pub use types::Size;
 
+#[cfg(target_vendor = "apple")]
 mod stage_input_output_descriptor;
+#[cfg(target_vendor = "apple")]
 pub use stage_input_output_descriptor::AttrFormat;
+#[cfg(target_vendor = "apple")]
 pub use stage_input_output_descriptor::IndexType;
 
+#[cfg(target_vendor = "apple")]
 mod allocation;
+#[cfg(target_vendor = "apple")]
 pub use allocation::Allocation;
 
+#[cfg(target_vendor = "apple")]
 mod acceleration_structure;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::AccelerationStruct;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::Desc as AccelerationStructDesc;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::GeometryDesc as AccelerationStructGeometryDesc;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::InstanceOpts as AccelerationStructInstanceOpts;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::MatrixLayout;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::MotionBorderMode;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::MotionBoundingBoxGeometryDesc;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure::Usage as AccelerationStructUsage;
 
+#[cfg(target_vendor = "apple")]
 mod acceleration_structure_types;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure_types::AxisAlignedBoundingBox;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure_types::ComponentTransform;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure_types::PackedF32Quaternion;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure_types::PackedF32x3;
+#[cfg(target_vendor = "apple")]
 pub use acceleration_structure_types::PackedF32x4x3;
 
 mod pixel_format;
+pub use pixel_format::ComponentType as PixelFormatComponentType;
 pub use pixel_format::PixelFormat;
+pub use pixel_format::PixelFormatInfo;
 
+#[cfg(target_vendor = "apple")]
 mod argument;
+#[cfg(target_vendor = "apple")]
 pub use argument::Access as ArgAccess;
+#[cfg(target_vendor = "apple")]
 pub use argument::ArrayType;
+#[cfg(target_vendor = "apple")]
 pub use argument::BaseType;
+#[cfg(target_vendor = "apple")]
 pub use argument::DType;
+#[cfg(target_vendor = "apple")]
 pub use argument::PointerType;
+#[cfg(target_vendor = "apple")]
 pub use argument::StructMember;
+#[cfg(target_vendor = "apple")]
 pub use argument::StructType;
+#[cfg(target_vendor = "apple")]
 pub use argument::TextureRefType;
+#[cfg(target_vendor = "apple")]
 pub use argument::Type as ArgType;
 
+#[cfg(target_vendor = "apple")]
 mod residency_set;
+#[cfg(target_vendor = "apple")]
 pub use residency_set::ResidencySet;
+#[cfg(target_vendor = "apple")]
 pub use residency_set::ResidencySetDesc;
 
+#[cfg(target_vendor = "apple")]
 mod resource;
+#[cfg(target_vendor = "apple")]
 pub use resource::Opts as ResOpts;
+#[cfg(target_vendor = "apple")]
 pub use resource::Res;
 
+#[cfg(target_vendor = "apple")]
 pub use resource::CpuCacheMode;
+#[cfg(target_vendor = "apple")]
 pub use resource::HazardTrackingMode;
+#[cfg(target_vendor = "apple")]
 pub use resource::PurgableState;
+#[cfg(target_vendor = "apple")]
 pub use resource::StorageMode;
 
+#[cfg(target_vendor = "apple")]
 pub use resource::CPU_CACHE_MODE_MASK;
+#[cfg(target_vendor = "apple")]
 pub use resource::CPU_CACHE_MODE_SHIFT;
+#[cfg(target_vendor = "apple")]
 pub use resource::HAZARD_TRACKING_MODE_MASK;
+#[cfg(target_vendor = "apple")]
 pub use resource::HAZARD_TRACKING_MODE_SHIFT;
+#[cfg(target_vendor = "apple")]
 pub use resource::STORAGE_MODE_MASK;
+#[cfg(target_vendor = "apple")]
 pub use resource::STORAGE_MODE_SHIFT;
 
+#[cfg(target_vendor = "apple")]
 mod heap;
+#[cfg(target_vendor = "apple")]
 pub use heap::Desc as HeapDesc;
+#[cfg(target_vendor = "apple")]
 pub use heap::Heap;
+#[cfg(target_vendor = "apple")]
 pub use heap::Type as HeapType;
 
+#[cfg(target_vendor = "apple")]
 mod library;
+#[cfg(target_vendor = "apple")]
 pub use library::Attr;
+#[cfg(target_vendor = "apple")]
 pub use library::CompileOpts;
+#[cfg(target_vendor = "apple")]
 pub use library::CompileSymbolVisibility;
+#[cfg(target_vendor = "apple")]
 pub use library::Error as LibError;
+#[cfg(target_vendor = "apple")]
 pub use library::ErrorDomain as LibErrorDomain;
+#[cfg(target_vendor = "apple")]
 pub use library::Fn;
+#[cfg(target_vendor = "apple")]
 pub use library::FnType;
+#[cfg(target_vendor = "apple")]
 pub use library::LangVersion;
+#[cfg(target_vendor = "apple")]
 pub use library::Lib;
+#[cfg(target_vendor = "apple")]
 pub use library::MathFloatingPointFns;
+#[cfg(target_vendor = "apple")]
 pub use library::MathMode;
+#[cfg(target_vendor = "apple")]
 pub use library::OptimizationLevel as LibOptimizationLevel;
+#[cfg(target_vendor = "apple")]
 pub use library::PatchType;
+#[cfg(target_vendor = "apple")]
 pub use library::Type as LibType;
+#[cfg(target_vendor = "apple")]
 pub use library::VertexAttr;
 
+#[cfg(target_vendor = "apple")]
 mod command_queue;
+#[cfg(target_vendor = "apple")]
 pub use command_queue::CmdQueue;
 
+#[cfg(target_vendor = "apple")]
 mod texture;
+#[cfg(target_vendor = "apple")]
 pub use texture::Compression as TextureCompression;
+#[cfg(target_vendor = "apple")]
 pub use texture::Desc as TextureDesc;
+#[cfg(target_vendor = "apple")]
 pub use texture::SharedTextureHandle;
+#[cfg(target_vendor = "apple")]
 pub use texture::Swizzle as TextureSwizzle;
+#[cfg(target_vendor = "apple")]
 pub use texture::SwizzleChannels as TextureSwizzleChannels;
+#[cfg(target_vendor = "apple")]
 pub use texture::Texture;
-pub use texture::Type as TextureType;
+#[cfg(target_vendor = "apple")]
 pub use texture::Usage as TextureUsage;
 
+mod texture_type;
+pub use texture_type::Type as TextureType;
+
+pub mod texture_file;
+pub use texture_file::Error as TextureFileError;
+
+#[cfg(target_vendor = "apple")]
 mod device;
+#[cfg(target_vendor = "apple")]
 pub use device::ArgBufsTier;
+#[cfg(target_vendor = "apple")]
 pub use device::Device;
+#[cfg(target_vendor = "apple")]
 pub use device::GpuFamily;
+#[cfg(target_vendor = "apple")]
 pub use device::PipelineOpt;
+#[cfg(target_vendor = "apple")]
 pub use device::ReadWriteTextureTier;
+#[cfg(target_vendor = "apple")]
 pub use device::SizeAlign;
+#[cfg(target_vendor = "apple")]
 pub use device::SparsePageSize;
+#[cfg(target_vendor = "apple")]
 pub use device::Timestamp;
 
+#[cfg(target_vendor = "apple")]
 mod drawable;
+#[cfg(target_vendor = "apple")]
 pub use drawable::Drawable;
 
+#[cfg(target_vendor = "apple")]
 mod function_constant_values;
+#[cfg(target_vendor = "apple")]
 pub use function_constant_values::FnConstValues;
 
+#[cfg(target_vendor = "apple")]
 mod function_handle;
+#[cfg(target_vendor = "apple")]
 pub use function_handle::FnHandle;
 
+#[cfg(target_vendor = "apple")]
 mod function_stitching;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchedLibDesc;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingAttr;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingAttrAlwaysInline;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingFnNode;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingGraph;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingInputNode;
+#[cfg(target_vendor = "apple")]
 pub use function_stitching::FnStitchingNode;
 
+#[cfg(target_vendor = "apple")]
 mod argument_encoder;
+#[cfg(target_vendor = "apple")]
 pub use argument_encoder::ArgEncoder;
 
+#[cfg(target_vendor = "apple")]
 mod buffer;
+#[cfg(target_vendor = "apple")]
 pub use buffer::Buf;
 
+#[cfg(target_vendor = "apple")]
 mod function_descriptor;
+#[cfg(target_vendor = "apple")]
 pub use function_descriptor::FnDesc;
+#[cfg(target_vendor = "apple")]
 pub use function_descriptor::FnOpts;
 
+#[cfg(target_vendor = "apple")]
 mod compute_pipeline;
+#[cfg(target_vendor = "apple")]
 pub use compute_pipeline::Desc as ComputePipelineDesc;
+#[cfg(target_vendor = "apple")]
 pub use compute_pipeline::Reflection as ComputePipelineReflection;
+#[cfg(target_vendor = "apple")]
 pub use compute_pipeline::State as ComputePipelineState;
 
+#[cfg(target_vendor = "apple")]
 mod command_buffer;
+#[cfg(target_vendor = "apple")]
 pub use command_buffer::CmdBuf;
+#[cfg(target_vendor = "apple")]
 pub use command_buffer::DispatchType;
+#[cfg(target_vendor = "apple")]
 pub use command_buffer::Error as CmdBufErr;
+#[cfg(target_vendor = "apple")]
 pub use command_buffer::Status as CmdBufStatus;
 
+#[cfg(target_vendor = "apple")]
 mod render_pipeline;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::BlendFactor;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::BlendOp;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::ColorAttachDesc as RenderPipelineColorAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::ColorAttachDescArray as RenderPipelineColorAttachDescArray;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::ColorWriteMask;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::Desc as RenderPipelineDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::FnsDesc as RenderPipelineFnsDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::MeshRenderPipelineDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::PrimitiveTopologyClass;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::Reflection as RenderPipelineReflection;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::State as RenderPipelineState;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TessellationControlPointIndexType;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TessellationFactorFormat;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TessellationFactorStepFn;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TessellationPartitionMode;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TileRenderPipelineColorAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TileRenderPipelineColorAttachDescArray;
+#[cfg(target_vendor = "apple")]
 pub use render_pipeline::TileRenderPipelineDesc;
 
+#[cfg(target_vendor = "apple")]
 mod fence;
+#[cfg(target_vendor = "apple")]
 pub use fence::Fence;
 
+#[cfg(target_vendor = "apple")]
 mod event;
+#[cfg(target_vendor = "apple")]
 pub use event::Event;
+#[cfg(target_vendor = "apple")]
 pub use event::SharedEvent;
+#[cfg(target_vendor = "apple")]
 pub use event::SharedEventHandle;
+#[cfg(target_vendor = "apple")]
 pub use event::SharedEventListener;
+#[cfg(target_vendor = "apple")]
 pub use event::SharedEventNotificationBlock;
 
+#[cfg(target_vendor = "apple")]
 mod blit_pass;
+#[cfg(target_vendor = "apple")]
 pub use blit_pass::Desc as BlitPassDesc;
+#[cfg(target_vendor = "apple")]
 pub use blit_pass::SampleBufAttachDesc as BlitPassSampleBufAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use blit_pass::SampleBufAttachDescArray as BlitPassSampleBufAttachDescArray;
 
+#[cfg(target_vendor = "apple")]
 mod command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use command_encoder::BarrierScope;
+#[cfg(target_vendor = "apple")]
 pub use command_encoder::CmdEncoder;
+#[cfg(target_vendor = "apple")]
 pub use command_encoder::ResUsage;
 
+#[cfg(target_vendor = "apple")]
 mod blit_command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use blit_command_encoder::BlitCmdEncoder;
+#[cfg(target_vendor = "apple")]
 pub use blit_command_encoder::BlitOpt;
 
+#[cfg(target_vendor = "apple")]
 mod render_command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::CullMode;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::DepthClipMode;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::DrawIndexedPrimitivesIndirectArgs;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::DrawPatchIndirectArgs;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::DrawPrimitivesIndirectArgs;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::Primitive;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::QuadTessellationFactorsHalf;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::RenderCmdEncoder;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::RenderStages;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::ScissorRect;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::TriangleFillMode;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::TriangleTessellationFactorsHalf;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::VertexAmplificationViewMapping;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::ViewPort;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::VisibilityResultMode;
+#[cfg(target_vendor = "apple")]
 pub use render_command_encoder::Winding;
 
+#[cfg(target_vendor = "apple")]
 mod depth_stencil;
+#[cfg(target_vendor = "apple")]
 pub use depth_stencil::CompareFn;
+#[cfg(target_vendor = "apple")]
 pub use depth_stencil::DepthStencilDesc;
+#[cfg(target_vendor = "apple")]
 pub use depth_stencil::State as DepthStencilState;
+#[cfg(target_vendor = "apple")]
 pub use depth_stencil::StencilDesc;
+#[cfg(target_vendor = "apple")]
 pub use depth_stencil::StencilOp;
 
+#[cfg(target_vendor = "apple")]
 mod compute_command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use compute_command_encoder::ComputeCmdEncoder;
 
+#[cfg(target_vendor = "apple")]
 mod parallel_render_command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use parallel_render_command_encoder::ParallelRenderCmdEncoder;
 
+#[cfg(target_vendor = "apple")]
 mod vertex_descriptor;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::Desc as VertexDesc;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexAttrDesc;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexAttrDescArray;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexBufLayoutDesc;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexBufLayoutDescArray;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexFormat;
+#[cfg(target_vendor = "apple")]
 pub use vertex_descriptor::VertexStepFn;
 
+#[cfg(target_vendor = "apple")]
 mod render_pass;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::AttachDesc as RenderPassAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::ClearColor;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::ColorAttachDesc as RenderPassColorAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::ColorAttachDescArray as RenderPassColorAttachDescArray;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::DepthAttachDesc as RenderPassDepthAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::Desc as RenderPassDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::LoadAction;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::StencilAttachDesc as RenderPassStencilAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::StoreAction;
+#[cfg(target_vendor = "apple")]
 pub use render_pass::StoreActionOpts;
 
+#[cfg(target_vendor = "apple")]
 mod compute_pass;
+#[cfg(target_vendor = "apple")]
 pub use compute_pass::Desc as ComputePassDesc;
+#[cfg(target_vendor = "apple")]
 pub use compute_pass::SampleBufAttachDesc as ComputePassSampleBufAttachDesc;
+#[cfg(target_vendor = "apple")]
 pub use compute_pass::SampleBufAttachDescArray as ComputePassSampleBufAttachDescArray;
 
+#[cfg(target_vendor = "apple")]
 mod counters;
+#[cfg(target_vendor = "apple")]
 pub use counters::Counter;
+#[cfg(target_vendor = "apple")]
 pub use counters::CounterResultStageUtilization;
+#[cfg(target_vendor = "apple")]
 pub use counters::CounterResultStatistic;
+#[cfg(target_vendor = "apple")]
 pub use counters::CounterResultTimestamp;
+#[cfg(target_vendor = "apple")]
 pub use counters::CounterSampleBuf;
+#[cfg(target_vendor = "apple")]
 pub use counters::CounterSet;
+#[cfg(target_vendor = "apple")]
 pub use counters::Desc as CounterSampleBufDesc;
 
+#[cfg(target_vendor = "apple")]
 mod indirect_command_buffer;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_buffer::Desc as IndirectCmdBufDesc;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_buffer::ExecutionRange as IndirectCmBufExecutionRange;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_buffer::IndirectCmdBuf;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_buffer::IndirectCmdType;
 
+#[cfg(target_vendor = "apple")]
 mod indirect_command_encoder;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_encoder::IndirectComputeCmd;
+#[cfg(target_vendor = "apple")]
 pub use indirect_command_encoder::IndirectRenderCmd;
 
+#[cfg(target_vendor = "apple")]
 mod sampler;
+#[cfg(target_vendor = "apple")]
 pub use sampler::AddrMode as SamplerAddrMode;
+#[cfg(target_vendor = "apple")]
 pub use sampler::BorderColor as SamplerBorderColor;
+#[cfg(target_vendor = "apple")]
 pub use sampler::Desc as SamplerDesc;
+#[cfg(target_vendor = "apple")]
 pub use sampler::MinMagFilter as SamplerMinMagFilter;
+#[cfg(target_vendor = "apple")]
 pub use sampler::MipFilter as SamplerMipFilter;
+#[cfg(target_vendor = "apple")]
 pub use sampler::State as SamplerState;
 
+#[cfg(target_vendor = "apple")]
 mod visible_function_table;
+#[cfg(target_vendor = "apple")]
 pub use visible_function_table::Desc as VisibleFnTableDesc;
+#[cfg(target_vendor = "apple")]
 pub use visible_function_table::VisibleFnTable;
 
+#[cfg(target_vendor = "apple")]
 mod intersection_function_table;
+#[cfg(target_vendor = "apple")]
 pub use intersection_function_table::Desc as IntersectionFnTableDesc;
+#[cfg(target_vendor = "apple")]
 pub use intersection_function_table::IntersectionFnSignature;
+#[cfg(target_vendor = "apple")]
 pub use intersection_function_table::IntersectionFnTable;
 
+#[cfg(target_vendor = "apple")]
 mod pipeline;
+#[cfg(target_vendor = "apple")]
 pub use pipeline::Mutability;
+#[cfg(target_vendor = "apple")]
 pub use pipeline::PipelineBufDesc;
+#[cfg(target_vendor = "apple")]
 pub use pipeline::PipelineBufDescArray;
 
+#[cfg(target_vendor = "apple")]
 mod capture_manager;
+#[cfg(target_vendor = "apple")]
 pub use capture_manager::CaptureDesc;
+#[cfg(target_vendor = "apple")]
 pub use capture_manager::CaptureDst;
+#[cfg(target_vendor = "apple")]
 pub use capture_manager::CaptureManager;
 
+#[cfg(target_vendor = "apple")]
 #[macro_export]
 macro_rules! define_mtl {
     (set_label) => {
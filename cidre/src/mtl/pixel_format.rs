@@ -337,6 +337,22 @@ pub enum PixelFormat {
     #[doc(alias = "MTLPixelFormatBC3_RGBA_sRGB")]
     Bc3RgbaSrgb = 135,
 
+    /// Compressed format with one normalized unsigned integer component.
+    #[doc(alias = "MTLPixelFormatBC4_RUnorm")]
+    Bc4RUNorm = 140,
+
+    /// Compressed format with one normalized signed integer component.
+    #[doc(alias = "MTLPixelFormatBC4_RSnorm")]
+    Bc4RSNorm = 141,
+
+    /// Compressed format with two normalized unsigned integer components.
+    #[doc(alias = "MTLPixelFormatBC5_RGUnorm")]
+    Bc5RgUNorm = 142,
+
+    /// Compressed format with two normalized signed integer components.
+    #[doc(alias = "MTLPixelFormatBC5_RGSnorm")]
+    Bc5RgSNorm = 143,
+
     /// Compressed format with four floating-point components.
     #[doc(alias = "MTLPixelFormatBC6H_RGBFloat")]
     Bc6HRgbFloat = 150,
@@ -354,6 +370,47 @@ pub enum PixelFormat {
     #[doc(alias = "MTLPixelFormatBC7_RGBAUnorm_sRGB")]
     Bc7RgbaUNormSrgb = 153,
 
+    /* PVRTC */
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_2BPP")]
+    PvrtcRgb2Bpp = 160,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_2BPP_sRGB")]
+    PvrtcRgb2BppSrgb = 161,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_4BPP")]
+    PvrtcRgb4Bpp = 162,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGB_4BPP_sRGB")]
+    PvrtcRgb4BppSrgb = 163,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_2BPP")]
+    PvrtcRgba2Bpp = 164,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_2BPP_sRGB")]
+    PvrtcRgba2BppSrgb = 165,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_4BPP")]
+    PvrtcRgba4Bpp = 166,
+    #[doc(alias = "MTLPixelFormatPVRTC_RGBA_4BPP_sRGB")]
+    PvrtcRgba4BppSrgb = 167,
+
+    /* EAC and ETC2 */
+    #[doc(alias = "MTLPixelFormatEAC_R11Unorm")]
+    EacR11UNorm = 170,
+    #[doc(alias = "MTLPixelFormatEAC_R11Snorm")]
+    EacR11SNorm = 172,
+    #[doc(alias = "MTLPixelFormatEAC_RG11Unorm")]
+    EacRg11UNorm = 174,
+    #[doc(alias = "MTLPixelFormatEAC_RG11Snorm")]
+    EacRg11SNorm = 176,
+    #[doc(alias = "MTLPixelFormatEAC_RGBA8")]
+    EacRgba8 = 178,
+    #[doc(alias = "MTLPixelFormatEAC_RGBA8_sRGB")]
+    EacRgba8Srgb = 179,
+
+    #[doc(alias = "MTLPixelFormatETC2_RGB8")]
+    Etc2Rgb8 = 180,
+    #[doc(alias = "MTLPixelFormatETC2_RGB8_sRGB")]
+    Etc2Rgb8Srgb = 181,
+    #[doc(alias = "MTLPixelFormatETC2_RGB8A1")]
+    Etc2Rgb8A1 = 182,
+    #[doc(alias = "MTLPixelFormatETC2_RGB8A1_sRGB")]
+    Etc2Rgb8A1Srgb = 183,
+
     /// ASTC-compressed format with low-dynamic-range content,
     /// conversion between sRGB and linear space, a block width of 4, and a block height of 4.
     #[doc(alias = "MTLPixelFormatASTC_4x4_sRGB")]
@@ -498,3 +555,585 @@ pub enum PixelFormat {
     #[doc(alias = "MTLPixelFormatX24_Stencil8")]
     X24Stencil8 = 262,
 }
+
+/// How the bits of a component are interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ComponentType {
+    /// `PixelFormat::Invalid`
+    Invalid,
+    UNorm,
+    SNorm,
+    UInt,
+    SInt,
+    Float,
+
+    /// Unsigned float with shared or reduced exponent (`Rg11B10Float`, `Rgb9E5Float`, `Bc6HRgbUFloat`)
+    UFloat,
+
+    /// Extended range fixed point (`Bgr10Xr`, `Bgra10Xr`)
+    Xr,
+}
+
+/// Size and capability metadata of a [`PixelFormat`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PixelFormatInfo {
+    /// Bytes per pixel, or per block for compressed and 4:2:2 formats
+    pub bytes_per_block: u8,
+    pub block_width: u8,
+    pub block_height: u8,
+
+    /// Number of components, depth and stencil count as one each
+    pub components: u8,
+    pub component_type: ComponentType,
+    pub is_srgb: bool,
+    pub is_depth: bool,
+    pub is_stencil: bool,
+
+    /// BC, PVRTC, EAC, ETC2 or ASTC block compressed
+    pub is_compressed: bool,
+}
+
+impl PixelFormatInfo {
+    const fn new(bytes_per_block: u8, components: u8, component_type: ComponentType) -> Self {
+        Self {
+            bytes_per_block,
+            block_width: 1,
+            block_height: 1,
+            components,
+            component_type,
+            is_srgb: false,
+            is_depth: false,
+            is_stencil: false,
+            is_compressed: false,
+        }
+    }
+
+    const fn compressed(mut self, block_width: u8, block_height: u8) -> Self {
+        self.block_width = block_width;
+        self.block_height = block_height;
+        self.is_compressed = true;
+        self
+    }
+
+    const fn srgb(mut self) -> Self {
+        self.is_srgb = true;
+        self
+    }
+
+    const fn depth(mut self) -> Self {
+        self.is_depth = true;
+        self
+    }
+
+    const fn stencil(mut self) -> Self {
+        self.is_stencil = true;
+        self
+    }
+}
+
+impl PixelFormat {
+    /// Size and capability metadata of the format.
+    ///
+    /// ```
+    /// use cidre::mtl;
+    ///
+    /// let info = mtl::PixelFormat::Astc8x6Ldr.info();
+    /// assert_eq!((info.block_width, info.block_height), (8, 6));
+    /// assert_eq!(mtl::PixelFormat::Astc8x6Ldr.bytes_per_row(1920), 240 * 16);
+    /// assert_eq!(mtl::PixelFormat::Bgra8UNorm.srgb(), Some(mtl::PixelFormat::Bgra8UNormSrgb));
+    /// ```
+    pub const fn info(self) -> PixelFormatInfo {
+        use ComponentType::*;
+        use PixelFormatInfo as I;
+
+        const fn astc(w: u8, h: u8) -> I {
+            I::new(16, 4, UNorm).compressed(w, h)
+        }
+
+        const fn astc_hdr(w: u8, h: u8) -> I {
+            I::new(16, 4, Float).compressed(w, h)
+        }
+
+        match self {
+            Self::Invalid => I::new(0, 0, Invalid),
+
+            Self::A8UNorm | Self::R8UNorm => I::new(1, 1, UNorm),
+            Self::R8UNormSrgb => I::new(1, 1, UNorm).srgb(),
+            Self::R8SNorm => I::new(1, 1, SNorm),
+            Self::R8UInt => I::new(1, 1, UInt),
+            Self::R8SInt => I::new(1, 1, SInt),
+
+            Self::R16UNorm => I::new(2, 1, UNorm),
+            Self::R16SNorm => I::new(2, 1, SNorm),
+            Self::R16UInt => I::new(2, 1, UInt),
+            Self::R16SInt => I::new(2, 1, SInt),
+            Self::R16Float => I::new(2, 1, Float),
+            Self::Rg8UNorm => I::new(2, 2, UNorm),
+            Self::Rg8UNormSrgb => I::new(2, 2, UNorm).srgb(),
+            Self::Rg8SNorm => I::new(2, 2, SNorm),
+            Self::Rg8UInt => I::new(2, 2, UInt),
+            Self::Rg8SInt => I::new(2, 2, SInt),
+            Self::B5G6R5UNorm => I::new(2, 3, UNorm),
+            Self::A1Bgr5UNorm | Self::Abgr4UNorm | Self::Bgr5A1UNorm => I::new(2, 4, UNorm),
+
+            Self::R32UInt => I::new(4, 1, UInt),
+            Self::R32SInt => I::new(4, 1, SInt),
+            Self::R32Float => I::new(4, 1, Float),
+            Self::Rg16UNorm => I::new(4, 2, UNorm),
+            Self::Rg16SNorm => I::new(4, 2, SNorm),
+            Self::Rg16UInt => I::new(4, 2, UInt),
+            Self::Rg16SInt => I::new(4, 2, SInt),
+            Self::Rg16Float => I::new(4, 2, Float),
+            Self::Rgba8UNorm | Self::Bgra8UNorm => I::new(4, 4, UNorm),
+            Self::Rgba8UNormSrgb | Self::Bgra8UNormSrgb => I::new(4, 4, UNorm).srgb(),
+            Self::Rgba8SNorm => I::new(4, 4, SNorm),
+            Self::Rgba8UInt => I::new(4, 4, UInt),
+            Self::Rgba8SInt => I::new(4, 4, SInt),
+            Self::Rgb10A2UNorm | Self::Bgr10A2UNorm => I::new(4, 4, UNorm),
+            Self::Rgb10A2UInt => I::new(4, 4, UInt),
+            Self::Rg11B10Float | Self::Rgb9E5Float => I::new(4, 3, UFloat),
+            Self::Bgr10Xr => I::new(4, 3, Xr),
+            Self::Bgr10XrSrgb => I::new(4, 3, Xr).srgb(),
+
+            Self::Rg32UInt => I::new(8, 2, UInt),
+            Self::Rg32SInt => I::new(8, 2, SInt),
+            Self::Rg32Float => I::new(8, 2, Float),
+            Self::Rgba16UNorm => I::new(8, 4, UNorm),
+            Self::Rgba16SNorm => I::new(8, 4, SNorm),
+            Self::Rgba16UInt => I::new(8, 4, UInt),
+            Self::Rgba16SInt => I::new(8, 4, SInt),
+            Self::Rgba16Float => I::new(8, 4, Float),
+            Self::Bgra10Xr => I::new(8, 4, Xr),
+            Self::Bgra10XrSrgb => I::new(8, 4, Xr).srgb(),
+
+            Self::Rgba32UInt => I::new(16, 4, UInt),
+            Self::Rgba32SInt => I::new(16, 4, SInt),
+            Self::Rgba32Float => I::new(16, 4, Float),
+
+            Self::Bc1Rgba => I::new(8, 4, UNorm).compressed(4, 4),
+            Self::Bc1RgbaSrgb => I::new(8, 4, UNorm).compressed(4, 4).srgb(),
+            Self::Bc2Rgba | Self::Bc3Rgba => I::new(16, 4, UNorm).compressed(4, 4),
+            Self::Bc2RgbaSrgb | Self::Bc3RgbaSrgb => I::new(16, 4, UNorm).compressed(4, 4).srgb(),
+            Self::Bc4RUNorm => I::new(8, 1, UNorm).compressed(4, 4),
+            Self::Bc4RSNorm => I::new(8, 1, SNorm).compressed(4, 4),
+            Self::Bc5RgUNorm => I::new(16, 2, UNorm).compressed(4, 4),
+            Self::Bc5RgSNorm => I::new(16, 2, SNorm).compressed(4, 4),
+            Self::Bc6HRgbFloat => I::new(16, 3, Float).compressed(4, 4),
+            Self::Bc6HRgbUFloat => I::new(16, 3, UFloat).compressed(4, 4),
+            Self::Bc7RgbaUNorm => I::new(16, 4, UNorm).compressed(4, 4),
+            Self::Bc7RgbaUNormSrgb => I::new(16, 4, UNorm).compressed(4, 4).srgb(),
+
+            Self::PvrtcRgb2Bpp => I::new(8, 3, UNorm).compressed(8, 4),
+            Self::PvrtcRgb2BppSrgb => I::new(8, 3, UNorm).compressed(8, 4).srgb(),
+            Self::PvrtcRgb4Bpp => I::new(8, 3, UNorm).compressed(4, 4),
+            Self::PvrtcRgb4BppSrgb => I::new(8, 3, UNorm).compressed(4, 4).srgb(),
+            Self::PvrtcRgba2Bpp => I::new(8, 4, UNorm).compressed(8, 4),
+            Self::PvrtcRgba2BppSrgb => I::new(8, 4, UNorm).compressed(8, 4).srgb(),
+            Self::PvrtcRgba4Bpp => I::new(8, 4, UNorm).compressed(4, 4),
+            Self::PvrtcRgba4BppSrgb => I::new(8, 4, UNorm).compressed(4, 4).srgb(),
+
+            Self::EacR11UNorm => I::new(8, 1, UNorm).compressed(4, 4),
+            Self::EacR11SNorm => I::new(8, 1, SNorm).compressed(4, 4),
+            Self::EacRg11UNorm => I::new(16, 2, UNorm).compressed(4, 4),
+            Self::EacRg11SNorm => I::new(16, 2, SNorm).compressed(4, 4),
+            Self::EacRgba8 => I::new(16, 4, UNorm).compressed(4, 4),
+            Self::EacRgba8Srgb => I::new(16, 4, UNorm).compressed(4, 4).srgb(),
+            Self::Etc2Rgb8 => I::new(8, 3, UNorm).compressed(4, 4),
+            Self::Etc2Rgb8Srgb => I::new(8, 3, UNorm).compressed(4, 4).srgb(),
+            Self::Etc2Rgb8A1 => I::new(8, 4, UNorm).compressed(4, 4),
+            Self::Etc2Rgb8A1Srgb => I::new(8, 4, UNorm).compressed(4, 4).srgb(),
+
+            Self::Astc4x4Srgb => astc(4, 4).srgb(),
+            Self::Astc5x4Srgb => astc(5, 4).srgb(),
+            Self::Astc5x5Srgb => astc(5, 5).srgb(),
+            Self::Astc6x5Srgb => astc(6, 5).srgb(),
+            Self::Astc6x6Srgb => astc(6, 6).srgb(),
+            Self::Astc8x5Srgb => astc(8, 5).srgb(),
+            Self::Astc8x6Srgb => astc(8, 6).srgb(),
+            Self::Astc8x8Srgb => astc(8, 8).srgb(),
+            Self::Astc10x5Srgb => astc(10, 5).srgb(),
+            Self::Astc10x6Srgb => astc(10, 6).srgb(),
+            Self::Astc10x8Srgb => astc(10, 8).srgb(),
+            Self::Astc10x10Srgb => astc(10, 10).srgb(),
+            Self::Astc12x10Srgb => astc(12, 10).srgb(),
+            Self::Astc12x12Srgb => astc(12, 12).srgb(),
+
+            Self::Astc4x4Ldr => astc(4, 4),
+            Self::Astc5x4Ldr => astc(5, 4),
+            Self::Astc5x5Ldr => astc(5, 5),
+            Self::Astc6x5Ldr => astc(6, 5),
+            Self::Astc6x6Ldr => astc(6, 6),
+            Self::Astc8x5Ldr => astc(8, 5),
+            Self::Astc8x6Ldr => astc(8, 6),
+            Self::Astc8x8Ldr => astc(8, 8),
+            Self::Astc10x5Ldr => astc(10, 5),
+            Self::Astc10x6Ldr => astc(10, 6),
+            Self::Astc10x8Ldr => astc(10, 8),
+            Self::Astc10x10Ldr => astc(10, 10),
+            Self::Astc12x10Ldr => astc(12, 10),
+            Self::Astc12x12Ldr => astc(12, 12),
+
+            Self::Astc4x4Hdr => astc_hdr(4, 4),
+            Self::Astc5x4Hdr => astc_hdr(5, 4),
+            Self::Astc5x5Hdr => astc_hdr(5, 5),
+            Self::Astc6x5Hdr => astc_hdr(6, 5),
+            Self::Astc6x6Hdr => astc_hdr(6, 6),
+            Self::Astc8x5Hdr => astc_hdr(8, 5),
+            Self::Astc8x6Hdr => astc_hdr(8, 6),
+            Self::Astc8x8Hdr => astc_hdr(8, 8),
+            Self::Astc10x5Hdr => astc_hdr(10, 5),
+            Self::Astc10x6Hdr => astc_hdr(10, 6),
+            Self::Astc10x8Hdr => astc_hdr(10, 8),
+            Self::Astc10x10Hdr => astc_hdr(10, 10),
+            Self::Astc12x10Hdr => astc_hdr(12, 10),
+            Self::Asrc12x12Hdr => astc_hdr(12, 12),
+
+            Self::Gbgr422 | Self::Bgrg422 => {
+                let mut info = I::new(4, 3, UNorm);
+                info.block_width = 2;
+                info
+            }
+
+            Self::Depth16Unorm => I::new(2, 1, UNorm).depth(),
+            Self::Depth32Float => I::new(4, 1, Float).depth(),
+            Self::Stencil8 => I::new(1, 1, UInt).stencil(),
+            Self::Depth24UnormStencil8 => I::new(4, 2, UNorm).depth().stencil(),
+            // 40 bits, padded to 64 on devices that store it interleaved
+            Self::Depth32FloatStencil8 => I::new(8, 2, Float).depth().stencil(),
+            Self::X32Stencil8 => I::new(8, 1, UInt).stencil(),
+            Self::X24Stencil8 => I::new(4, 1, UInt).stencil(),
+        }
+    }
+
+    /// Bytes per pixel, or per block for compressed and 4:2:2 formats.
+    #[inline]
+    pub const fn bytes_per_block(self) -> usize {
+        self.info().bytes_per_block as usize
+    }
+
+    /// Block width and height in pixels, `(1, 1)` for ordinary formats.
+    #[inline]
+    pub const fn block_size(self) -> (usize, usize) {
+        let info = self.info();
+        (info.block_width as usize, info.block_height as usize)
+    }
+
+    #[inline]
+    pub const fn components(self) -> usize {
+        self.info().components as usize
+    }
+
+    #[inline]
+    pub const fn component_type(self) -> ComponentType {
+        self.info().component_type
+    }
+
+    #[inline]
+    pub const fn is_srgb(self) -> bool {
+        self.info().is_srgb
+    }
+
+    #[inline]
+    pub const fn is_depth(self) -> bool {
+        self.info().is_depth
+    }
+
+    #[inline]
+    pub const fn is_stencil(self) -> bool {
+        self.info().is_stencil
+    }
+
+    #[inline]
+    pub const fn is_compressed(self) -> bool {
+        self.info().is_compressed
+    }
+
+    #[inline]
+    pub const fn is_integer(self) -> bool {
+        matches!(
+            self.component_type(),
+            ComponentType::UInt | ComponentType::SInt
+        )
+    }
+
+    #[inline]
+    pub const fn is_normalized(self) -> bool {
+        matches!(
+            self.component_type(),
+            ComponentType::UNorm | ComponentType::SNorm
+        )
+    }
+
+    #[inline]
+    pub const fn is_float(self) -> bool {
+        matches!(
+            self.component_type(),
+            ComponentType::Float | ComponentType::UFloat
+        )
+    }
+
+    /// Bytes of a tightly packed row of `width` pixels, the `bytes_per_row` of blit and replace calls.
+    #[inline]
+    pub const fn bytes_per_row(self, width: usize) -> usize {
+        let (block_width, _) = self.block_size();
+        if block_width == 0 {
+            return 0;
+        }
+        width.div_ceil(block_width) * self.bytes_per_block()
+    }
+
+    /// Bytes of a tightly packed `width` x `height` image.
+    #[inline]
+    pub const fn bytes_per_image(self, width: usize, height: usize) -> usize {
+        let (_, block_height) = self.block_size();
+        if block_height == 0 {
+            return 0;
+        }
+        height.div_ceil(block_height) * self.bytes_per_row(width)
+    }
+
+    /// sRGB sibling of a linear format, `self` for sRGB formats.
+    pub const fn srgb(self) -> Option<Self> {
+        Some(match self {
+            Self::R8UNorm => Self::R8UNormSrgb,
+            Self::Rg8UNorm => Self::Rg8UNormSrgb,
+            Self::Rgba8UNorm => Self::Rgba8UNormSrgb,
+            Self::Bgra8UNorm => Self::Bgra8UNormSrgb,
+            Self::Bgr10Xr => Self::Bgr10XrSrgb,
+            Self::Bgra10Xr => Self::Bgra10XrSrgb,
+            Self::Bc1Rgba => Self::Bc1RgbaSrgb,
+            Self::Bc2Rgba => Self::Bc2RgbaSrgb,
+            Self::Bc3Rgba => Self::Bc3RgbaSrgb,
+            Self::Bc7RgbaUNorm => Self::Bc7RgbaUNormSrgb,
+            Self::PvrtcRgb2Bpp => Self::PvrtcRgb2BppSrgb,
+            Self::PvrtcRgb4Bpp => Self::PvrtcRgb4BppSrgb,
+            Self::PvrtcRgba2Bpp => Self::PvrtcRgba2BppSrgb,
+            Self::PvrtcRgba4Bpp => Self::PvrtcRgba4BppSrgb,
+            Self::EacRgba8 => Self::EacRgba8Srgb,
+            Self::Etc2Rgb8 => Self::Etc2Rgb8Srgb,
+            Self::Etc2Rgb8A1 => Self::Etc2Rgb8A1Srgb,
+            Self::Astc4x4Ldr => Self::Astc4x4Srgb,
+            Self::Astc5x4Ldr => Self::Astc5x4Srgb,
+            Self::Astc5x5Ldr => Self::Astc5x5Srgb,
+            Self::Astc6x5Ldr => Self::Astc6x5Srgb,
+            Self::Astc6x6Ldr => Self::Astc6x6Srgb,
+            Self::Astc8x5Ldr => Self::Astc8x5Srgb,
+            Self::Astc8x6Ldr => Self::Astc8x6Srgb,
+            Self::Astc8x8Ldr => Self::Astc8x8Srgb,
+            Self::Astc10x5Ldr => Self::Astc10x5Srgb,
+            Self::Astc10x6Ldr => Self::Astc10x6Srgb,
+            Self::Astc10x8Ldr => Self::Astc10x8Srgb,
+            Self::Astc10x10Ldr => Self::Astc10x10Srgb,
+            Self::Astc12x10Ldr => Self::Astc12x10Srgb,
+            Self::Astc12x12Ldr => Self::Astc12x12Srgb,
+            s if s.is_srgb() => s,
+            _ => return None,
+        })
+    }
+
+    /// Linear sibling of an sRGB format, `self` for all others.
+    pub const fn linear(self) -> Self {
+        match self {
+            Self::R8UNormSrgb => Self::R8UNorm,
+            Self::Rg8UNormSrgb => Self::Rg8UNorm,
+            Self::Rgba8UNormSrgb => Self::Rgba8UNorm,
+            Self::Bgra8UNormSrgb => Self::Bgra8UNorm,
+            Self::Bgr10XrSrgb => Self::Bgr10Xr,
+            Self::Bgra10XrSrgb => Self::Bgra10Xr,
+            Self::Bc1RgbaSrgb => Self::Bc1Rgba,
+            Self::Bc2RgbaSrgb => Self::Bc2Rgba,
+            Self::Bc3RgbaSrgb => Self::Bc3Rgba,
+            Self::Bc7RgbaUNormSrgb => Self::Bc7RgbaUNorm,
+            Self::PvrtcRgb2BppSrgb => Self::PvrtcRgb2Bpp,
+            Self::PvrtcRgb4BppSrgb => Self::PvrtcRgb4Bpp,
+            Self::PvrtcRgba2BppSrgb => Self::PvrtcRgba2Bpp,
+            Self::PvrtcRgba4BppSrgb => Self::PvrtcRgba4Bpp,
+            Self::EacRgba8Srgb => Self::EacRgba8,
+            Self::Etc2Rgb8Srgb => Self::Etc2Rgb8,
+            Self::Etc2Rgb8A1Srgb => Self::Etc2Rgb8A1,
+            Self::Astc4x4Srgb => Self::Astc4x4Ldr,
+            Self::Astc5x4Srgb => Self::Astc5x4Ldr,
+            Self::Astc5x5Srgb => Self::Astc5x5Ldr,
+            Self::Astc6x5Srgb => Self::Astc6x5Ldr,
+            Self::Astc6x6Srgb => Self::Astc6x6Ldr,
+            Self::Astc8x5Srgb => Self::Astc8x5Ldr,
+            Self::Astc8x6Srgb => Self::Astc8x6Ldr,
+            Self::Astc8x8Srgb => Self::Astc8x8Ldr,
+            Self::Astc10x5Srgb => Self::Astc10x5Ldr,
+            Self::Astc10x6Srgb => Self::Astc10x6Ldr,
+            Self::Astc10x8Srgb => Self::Astc10x8Ldr,
+            Self::Astc10x10Srgb => Self::Astc10x10Ldr,
+            Self::Astc12x10Srgb => Self::Astc12x10Ldr,
+            Self::Astc12x12Srgb => Self::Astc12x12Ldr,
+            s => s,
+        }
+    }
+}
+
+#[cfg(feature = "cv")]
+impl PixelFormat {
+    /// Texture format CoreVideo uses for a non planar `cv::PixelFormat`
+    /// (`cv::MetalTextureCache`, `io::Surf` backed textures).
+    pub const fn from_cv(pixel_format: crate::cv::PixelFormat) -> Option<Self> {
+        use crate::cv::PixelFormat as F;
+        Some(match pixel_format {
+            F::_32_BGRA | F::LOSSLESS_32_BGRA | F::LOSSY_32_BGRA => Self::Bgra8UNorm,
+            F::_32_RGBA => Self::Rgba8UNorm,
+            F::_64_RGBALE => Self::Rgba16UNorm,
+            F::_64_RGBA_HALF | F::LOSSLESS_64_RGBA_HALF => Self::Rgba16Float,
+            F::_128_RGBA_FLOAT => Self::Rgba32Float,
+            F::ARGB_2101010_LE_PACKED => Self::Bgr10A2UNorm,
+            F::ONE_COMPONENT_8 => Self::R8UNorm,
+            F::ONE_COMPONENT_16_HALF => Self::R16Float,
+            F::ONE_COMPONENT_32_FLOAT => Self::R32Float,
+            F::TWO_COMPONENT_16_HALF => Self::Rg16Float,
+            F::TWO_COMPONENT_32_FLOAT => Self::Rg32Float,
+            F::_422_YP_CB_CR_8 => Self::Bgrg422,
+            _ => return None,
+        })
+    }
+
+    /// Texture format for a plane of a `cv::PixelFormat`.
+    ///
+    /// ```
+    /// use cidre::{cv, mtl};
+    ///
+    /// let f = cv::PixelFormat::_420V;
+    /// assert_eq!(mtl::PixelFormat::from_cv_plane(f, 0), Some(mtl::PixelFormat::R8UNorm));
+    /// assert_eq!(mtl::PixelFormat::from_cv_plane(f, 1), Some(mtl::PixelFormat::Rg8UNorm));
+    /// assert_eq!(mtl::PixelFormat::from_cv_plane(f, 2), None);
+    /// ```
+    pub fn from_cv_plane(pixel_format: crate::cv::PixelFormat, plane: usize) -> Option<Self> {
+        let layout = pixel_format.layout()?;
+        if !layout.is_planar() {
+            return if plane == 0 {
+                Self::from_cv(pixel_format)
+            } else {
+                None
+            };
+        }
+        let plane = layout.plane(plane)?;
+        if plane.block_width == 2 {
+            return (plane.bits_per_block == 32).then_some(Self::Bgrg422);
+        }
+        match (plane.components.len(), plane.bits_per_block) {
+            (1, 8) => Some(Self::R8UNorm),
+            (2, 16) => Some(Self::Rg8UNorm),
+            (1, 16) => Some(Self::R16UNorm),
+            (2, 32) => Some(Self::Rg16UNorm),
+            _ => None,
+        }
+    }
+
+    /// `cv::PixelFormat` with the same memory layout, for creating
+    /// `cv::PixelBuf`s shared with textures of this format.
+    pub const fn to_cv(self) -> Option<crate::cv::PixelFormat> {
+        use crate::cv::PixelFormat as F;
+        Some(match self.linear() {
+            Self::Bgra8UNorm => F::_32_BGRA,
+            Self::Rgba8UNorm => F::_32_RGBA,
+            Self::Rgba16UNorm => F::_64_RGBALE,
+            Self::Rgba16Float => F::_64_RGBA_HALF,
+            Self::Rgba32Float => F::_128_RGBA_FLOAT,
+            Self::Bgr10A2UNorm => F::ARGB_2101010_LE_PACKED,
+            Self::R8UNorm => F::ONE_COMPONENT_8,
+            Self::R16Float => F::ONE_COMPONENT_16_HALF,
+            Self::R32Float => F::ONE_COMPONENT_32_FLOAT,
+            Self::Rg16Float => F::TWO_COMPONENT_16_HALF,
+            Self::Rg32Float => F::TWO_COMPONENT_32_FLOAT,
+            Self::Bgrg422 => F::_422_YP_CB_CR_8,
+            _ => return None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mtl;
+
+    #[test]
+    fn sizes() {
+        use mtl::PixelFormat as F;
+
+        assert_eq!(F::Bgra8UNorm.bytes_per_row(100), 400);
+        assert_eq!(F::Rgba32Float.bytes_per_image(2, 2), 64);
+        assert_eq!(F::Bc1Rgba.bytes_per_row(10), 24);
+        assert_eq!(F::Bc7RgbaUNorm.bytes_per_image(10, 10), 9 * 16);
+        assert_eq!(F::PvrtcRgba2Bpp.bytes_per_image(16, 16), 8 * 8);
+        assert_eq!(F::Asrc12x12Hdr.bytes_per_image(1920, 1080), 160 * 90 * 16);
+        assert_eq!(F::Bgrg422.bytes_per_row(3), 8);
+        assert_eq!(F::Invalid.bytes_per_image(10, 10), 0);
+
+        assert!(F::Depth32FloatStencil8.is_depth() && F::Depth32FloatStencil8.is_stencil());
+        assert!(F::X24Stencil8.is_stencil() && !F::X24Stencil8.is_depth());
+        assert!(F::Rgba16UInt.is_integer());
+        assert!(F::Rg11B10Float.is_float());
+        assert!(F::R8SNorm.is_normalized());
+        assert!(F::EacRg11SNorm.is_compressed());
+        assert_eq!(F::Rgb9E5Float.components(), 3);
+    }
+
+    #[test]
+    fn siblings() {
+        use mtl::PixelFormat as F;
+
+        let linear = [
+            F::R8UNorm,
+            F::Bgra8UNorm,
+            F::Bgra10Xr,
+            F::Bc3Rgba,
+            F::PvrtcRgb4Bpp,
+            F::Etc2Rgb8A1,
+            F::Astc10x8Ldr,
+        ];
+        for f in linear {
+            let srgb = f.srgb().unwrap();
+            assert!(srgb.is_srgb() && !f.is_srgb(), "{f:?}");
+            assert_eq!(srgb.srgb(), Some(srgb));
+            assert_eq!(srgb.linear(), f);
+            assert_eq!(f.linear(), f);
+            assert_eq!(f.info().bytes_per_block, srgb.info().bytes_per_block);
+            assert_eq!(f.block_size(), srgb.block_size());
+        }
+        assert_eq!(F::Rgba16Float.srgb(), None);
+        assert_eq!(F::Astc4x4Hdr.srgb(), None);
+    }
+
+    #[cfg(feature = "cv")]
+    #[test]
+    fn cv() {
+        use crate::cv;
+        use mtl::PixelFormat as F;
+
+        let formats = [
+            cv::PixelFormat::_32_BGRA,
+            cv::PixelFormat::_64_RGBA_HALF,
+            cv::PixelFormat::ONE_COMPONENT_8,
+            cv::PixelFormat::_2VUY,
+        ];
+        for f in formats {
+            let mtl = F::from_cv(f).unwrap();
+            assert_eq!(mtl.to_cv(), Some(f));
+            assert_eq!(F::from_cv_plane(f, 0), Some(mtl));
+            assert_eq!(F::from_cv_plane(f, 1), None);
+        }
+
+        assert_eq!(F::Bgra8UNormSrgb.to_cv(), Some(cv::PixelFormat::_32_BGRA));
+        assert_eq!(F::from_cv(cv::PixelFormat::_420V), None);
+        assert_eq!(F::Bc1Rgba.to_cv(), None);
+
+        let x420 = cv::PixelFormat::_420_YP_CB_CR_10_BI_PLANAR_VIDEO_RANGE;
+        assert_eq!(F::from_cv_plane(x420, 0), Some(F::R16UNorm));
+        assert_eq!(F::from_cv_plane(x420, 1), Some(F::Rg16UNorm));
+
+        let f420 = cv::PixelFormat::_420_YP_CB_CR_8_PLANAR_FULL_RANGE;
+        assert_eq!(F::from_cv_plane(f420, 2), Some(F::R8UNorm));
+
+        let a2vy = cv::PixelFormat::_422_YP_CB_CR_4_A_8_BI_PLANAR;
+        assert_eq!(F::from_cv_plane(a2vy, 0), Some(F::Bgrg422));
+        assert_eq!(F::from_cv_plane(a2vy, 1), Some(F::R8UNorm));
+
+        let packed = cv::PixelFormat::LOSSLESS_420_YP_CB_CR_10_PACKED_BI_PLANAR_VIDEO_RANGE;
+        assert_eq!(F::from_cv_plane(packed, 0), None);
+    }
+}
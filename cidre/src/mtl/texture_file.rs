@@ -0,0 +1,727 @@
+//! Texture files without Metal.
+//!
+//! Reads and writes KTX2, DDS and `.astc` headers and mip level tables,
+//! mapping their formats to [`mtl::PixelFormat`] and their images to a
+//! [`Layout`] with the dimensions, level count, array length and per level
+//! offsets and row sizes that [`mtl::TextureDesc`] and texture uploads take.
+//!
+//! ```
+//! use cidre::mtl::{self, texture_file};
+//!
+//! let layout = texture_file::Layout::new_2d(mtl::PixelFormat::Bc1Rgba, 256, 128, true).unwrap();
+//! assert_eq!(layout.mipmap_level_count, 9);
+//! assert_eq!(layout.levels[0].bytes_per_row, 64 * 8);
+//!
+//! let data = vec![0u8; layout.data_len().unwrap()];
+//! let ktx2 = layout.write(texture_file::Container::Ktx2, &data).unwrap();
+//!
+//! let (container, read) = texture_file::Layout::read(&ktx2).unwrap();
+//! assert_eq!(container, texture_file::Container::Ktx2);
+//! assert_eq!(read.pixel_format, mtl::PixelFormat::Bc1Rgba);
+//! assert_eq!(read.mipmap_level_count, 9);
+//!
+//! // convert without touching the blocks
+//! let dds = read.write(texture_file::Container::Dds, &ktx2).unwrap();
+//! let (_, read) = texture_file::Layout::read(&dds).unwrap();
+//! assert_eq!(read.image(&dds, 8, 0), Some(&[0u8; 8][..]));
+//! ```
+
+use std::ops::Range;
+
+use crate::mtl;
+
+mod astc;
+mod dds;
+mod ktx2;
+
+pub use dds::dxgi_format;
+pub use dds::from_dxgi_format;
+pub use ktx2::from_vk_format;
+pub use ktx2::vk_format;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Not a KTX2, DDS or `.astc` file.
+    Container,
+
+    /// Header, level index or level data ends past the end of the file.
+    Truncated(Container),
+
+    Invalid(Container, &'static str),
+
+    /// Format code Metal has no pixel format for.
+    Format(Container, u32),
+
+    /// Pixel format, texture type or feature the container can't carry.
+    Unsupported(Container, &'static str),
+
+    /// Dimensions, level count or array length don't fit the texture type,
+    /// or the data is shorter than the layout.
+    Layout(&'static str),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Container => write!(f, "unknown texture file type"),
+            Self::Truncated(c) => write!(f, "{c:?} file is truncated"),
+            Self::Invalid(c, what) => write!(f, "{c:?} file has invalid {what}"),
+            Self::Format(c, code) => write!(f, "{c:?} format {code} has no Metal pixel format"),
+            Self::Unsupported(c, what) => write!(f, "{c:?} files can't carry {what}"),
+            Self::Layout(what) => write!(f, "invalid texture layout: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Sizes or offsets of the layout don't fit `usize`.
+const OVERFLOW: Error = Error::Layout("size overflow");
+const SHORT: Error = Error::Layout("data is shorter than layout");
+
+impl Error {
+    /// Layout errors of a parsed header are errors of the file.
+    fn in_file(self, container: Container) -> Self {
+        match self {
+            Self::Layout(what) => Self::Invalid(container, what),
+            e => e,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Container {
+    /// Khronos Texture 2.0.
+    Ktx2,
+
+    /// DirectDraw Surface, with or without the DX10 header.
+    Dds,
+
+    /// ARM `astcenc` output, a single ASTC image.
+    Astc,
+}
+
+impl Container {
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&ktx2::MAGIC) {
+            Some(Self::Ktx2)
+        } else if data.starts_with(&dds::MAGIC) {
+            Some(Self::Dds)
+        } else if data.starts_with(&astc::MAGIC) {
+            Some(Self::Astc)
+        } else {
+            None
+        }
+    }
+
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Ktx2 => "ktx2",
+            Self::Dds => "dds",
+            Self::Astc => "astc",
+        }
+    }
+}
+
+/// Placement of one mip level in a buffer.
+///
+/// Slices (array elements and cube faces, cube major) of a level are
+/// `slice_stride` bytes apart, each holds `depth` images of
+/// `bytes_per_image` bytes.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Level {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+
+    /// Bytes of a row of blocks, Metal expects 0 for PVRTC uploads.
+    pub bytes_per_row: usize,
+    pub bytes_per_image: usize,
+
+    /// Offset of the first slice.
+    pub offset: usize,
+    pub slice_stride: usize,
+}
+
+impl Level {
+    /// Bytes of one slice, all images of a 3D level, `None` on overflow.
+    #[inline]
+    pub const fn slice_len(&self) -> Option<usize> {
+        self.bytes_per_image.checked_mul(self.depth)
+    }
+
+    /// Bytes of `slice` in a buffer, `None` on overflow.
+    #[inline]
+    pub fn range(&self, slice: usize) -> Option<Range<usize>> {
+        let start = slice
+            .checked_mul(self.slice_stride)?
+            .checked_add(self.offset)?;
+        Some(start..start.checked_add(self.slice_len()?)?)
+    }
+
+    /// `mtl::Region` of the whole level.
+    pub const fn region(&self) -> mtl::Region {
+        mtl::Region {
+            origin: mtl::Origin { x: 0, y: 0, z: 0 },
+            size: mtl::Size {
+                width: self.width,
+                height: self.height,
+                depth: self.depth,
+            },
+        }
+    }
+}
+
+/// Plain counterpart of [`mtl::TextureDesc`] with the placement of every
+/// level in a file or buffer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    pub texture_type: mtl::TextureType,
+    pub pixel_format: mtl::PixelFormat,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub mipmap_level_count: usize,
+
+    /// Number of array elements, number of cubes for cube arrays.
+    pub array_len: usize,
+    pub levels: Vec<Level>,
+}
+
+impl Layout {
+    /// Tightly packed layout, levels in order, all slices of a level
+    /// next to each other, as KTX2 stores them.
+    pub fn new(
+        texture_type: mtl::TextureType,
+        pixel_format: mtl::PixelFormat,
+        width: usize,
+        height: usize,
+        depth: usize,
+        array_len: usize,
+        mipmap_level_count: usize,
+    ) -> Result<Self, Error> {
+        let mut res = Self {
+            texture_type,
+            pixel_format,
+            width,
+            height,
+            depth,
+            mipmap_level_count,
+            array_len,
+            levels: Vec::new(),
+        };
+        res.validate()?;
+        let slices = res.slices();
+        let mut offset = 0usize;
+        for i in 0..mipmap_level_count {
+            let mut level = res.level(i)?;
+            let slice_len = level.slice_len().ok_or(OVERFLOW)?;
+            level.offset = offset;
+            level.slice_stride = slice_len;
+            offset = slice_len
+                .checked_mul(slices)
+                .and_then(|len| offset.checked_add(len))
+                .ok_or(OVERFLOW)?;
+            res.levels.push(level);
+        }
+        Ok(res)
+    }
+
+    pub fn new_2d(
+        pixel_format: mtl::PixelFormat,
+        width: usize,
+        height: usize,
+        mipmapped: bool,
+    ) -> Result<Self, Error> {
+        let levels = if mipmapped {
+            max_mipmap_level_count(width, height, 1)
+        } else {
+            1
+        };
+        Self::new(
+            mtl::TextureType::_2d,
+            pixel_format,
+            width,
+            height,
+            1,
+            1,
+            levels,
+        )
+    }
+
+    pub fn new_cube(
+        pixel_format: mtl::PixelFormat,
+        size: usize,
+        mipmapped: bool,
+    ) -> Result<Self, Error> {
+        let levels = if mipmapped {
+            max_mipmap_level_count(size, size, 1)
+        } else {
+            1
+        };
+        Self::new(
+            mtl::TextureType::Cube,
+            pixel_format,
+            size,
+            size,
+            1,
+            1,
+            levels,
+        )
+    }
+
+    /// Reads the header and level table of a KTX2, DDS or `.astc` file.
+    ///
+    /// Level offsets of the result point into `data`.
+    pub fn read(data: &[u8]) -> Result<(Container, Self), Error> {
+        let container = Container::detect(data).ok_or(Error::Container)?;
+        let res = match container {
+            Container::Ktx2 => ktx2::read(data)?,
+            Container::Dds => dds::read(data)?,
+            Container::Astc => astc::read(data)?,
+        };
+        if res.data_len().map_err(|e| e.in_file(container))? > data.len() {
+            return Err(Error::Truncated(container));
+        }
+        Ok((container, res))
+    }
+
+    /// Writes `container` file with the images `self` places in `data`.
+    pub fn write(&self, container: Container, data: &[u8]) -> Result<Vec<u8>, Error> {
+        self.validate()?;
+        if self.levels.len() != self.mipmap_level_count {
+            return Err(Error::Layout("level count"));
+        }
+        for (i, level) in self.levels.iter().enumerate() {
+            let expected = self.level(i)?;
+            if (level.width, level.height, level.depth)
+                != (expected.width, expected.height, expected.depth)
+                || level.bytes_per_image != expected.bytes_per_image
+            {
+                return Err(Error::Layout("level size"));
+            }
+        }
+        if self.data_len()? > data.len() {
+            return Err(SHORT);
+        }
+        match container {
+            Container::Ktx2 => ktx2::write(self, data),
+            Container::Dds => dds::write(self, data),
+            Container::Astc => astc::write(self, data),
+        }
+    }
+
+    #[inline]
+    pub fn is_cube(&self) -> bool {
+        matches!(
+            self.texture_type,
+            mtl::TextureType::Cube | mtl::TextureType::CubeArray
+        )
+    }
+
+    #[inline]
+    pub fn is_array(&self) -> bool {
+        matches!(
+            self.texture_type,
+            mtl::TextureType::_1dArray | mtl::TextureType::_2dArray | mtl::TextureType::CubeArray
+        )
+    }
+
+    /// Number of 2D or 3D images per level, array elements times cube faces.
+    #[inline]
+    pub fn slices(&self) -> usize {
+        if self.is_cube() {
+            self.array_len.saturating_mul(6)
+        } else {
+            self.array_len
+        }
+    }
+
+    /// Bytes of `data` the levels span.
+    pub fn data_len(&self) -> Result<usize, Error> {
+        let last = self.slices().saturating_sub(1);
+        self.levels.iter().try_fold(0, |len, l| {
+            let end = l.range(last).ok_or(OVERFLOW)?.end;
+            Ok(len.max(end))
+        })
+    }
+
+    /// Bytes of one slice of a level.
+    pub fn image<'a>(&self, data: &'a [u8], level: usize, slice: usize) -> Option<&'a [u8]> {
+        if slice >= self.slices() {
+            return None;
+        }
+        data.get(self.levels.get(level)?.range(slice)?)
+    }
+
+    /// Texture descriptor with the type, pixel format, dimensions, level
+    /// count and array length of the layout.
+    #[cfg(target_vendor = "apple")]
+    pub fn texture_desc(&self) -> crate::arc::R<mtl::TextureDesc> {
+        let mut res = mtl::TextureDesc::new_2d(
+            self.pixel_format,
+            self.width,
+            self.height,
+            self.mipmap_level_count > 1,
+        );
+        res.set_texture_type(self.texture_type);
+        res.set_depth(self.depth);
+        res.set_mipmap_level_count(self.mipmap_level_count);
+        res.set_array_len(self.array_len);
+        res
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        use mtl::TextureType as T;
+
+        if self.pixel_format == mtl::PixelFormat::Invalid {
+            return Err(Error::Layout("pixel format"));
+        }
+        if self.width == 0 || self.height == 0 || self.depth == 0 || self.array_len == 0 {
+            return Err(Error::Layout("empty dimension"));
+        }
+        match self.texture_type {
+            T::_1d | T::_1dArray if self.height != 1 => return Err(Error::Layout("1D height")),
+            T::Cube | T::CubeArray if self.width != self.height => {
+                return Err(Error::Layout("cube faces are not square"))
+            }
+            T::_1d | T::_1dArray | T::_2d | T::_2dArray | T::Cube | T::CubeArray => {}
+            T::_3d => {}
+            T::_2dMultisample | T::_2dMultisampleArray | T::TextureBuffer => {
+                return Err(Error::Layout("texture type"))
+            }
+        }
+        if self.texture_type != T::_3d && self.depth != 1 {
+            return Err(Error::Layout("depth"));
+        }
+        if !self.is_array() && self.array_len != 1 {
+            return Err(Error::Layout("array length"));
+        }
+        if self.is_cube() && self.array_len.checked_mul(6).is_none() {
+            return Err(OVERFLOW);
+        }
+        let max = max_mipmap_level_count(self.width, self.height, self.depth);
+        if self.mipmap_level_count == 0 || self.mipmap_level_count > max {
+            return Err(Error::Layout("mipmap level count"));
+        }
+        Ok(())
+    }
+
+    /// Dimensions and sizes of level `i`, without placement.
+    fn level(&self, i: usize) -> Result<Level, Error> {
+        let width = (self.width >> i).max(1);
+        let height = (self.height >> i).max(1);
+        let depth = (self.depth >> i).max(1);
+        let (bytes_per_row, rows) = blocks(self.pixel_format, width, height).ok_or(OVERFLOW)?;
+        Ok(Level {
+            width,
+            height,
+            depth,
+            bytes_per_row,
+            bytes_per_image: bytes_per_row.checked_mul(rows).ok_or(OVERFLOW)?,
+            offset: 0,
+            slice_stride: 0,
+        })
+    }
+}
+
+/// Levels of a full mip chain down to 1x1x1.
+#[inline]
+pub fn max_mipmap_level_count(width: usize, height: usize, depth: usize) -> usize {
+    let max = width.max(height).max(depth);
+    if max == 0 {
+        0
+    } else {
+        max.ilog2() as usize + 1
+    }
+}
+
+/// Bytes per row of blocks and rows of blocks of a `width` x `height` image,
+/// `None` if a row doesn't fit `usize`.
+fn blocks(pixel_format: mtl::PixelFormat, width: usize, height: usize) -> Option<(usize, usize)> {
+    use mtl::PixelFormat as F;
+
+    let (block_width, block_height) = pixel_format.block_size();
+    let mut columns = width.div_ceil(block_width);
+    let mut rows = height.div_ceil(block_height);
+    // PVRTC decodes each block from its neighbours and needs at least 2x2 of them
+    if matches!(
+        pixel_format.linear(),
+        F::PvrtcRgb2Bpp | F::PvrtcRgb4Bpp | F::PvrtcRgba2Bpp | F::PvrtcRgba4Bpp
+    ) {
+        columns = columns.max(2);
+        rows = rows.max(2);
+    }
+    Some((columns.checked_mul(pixel_format.bytes_per_block())?, rows))
+}
+
+/// Little endian cursor over a file header.
+struct Reader<'a> {
+    container: Container,
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(container: Container, data: &'a [u8]) -> Self {
+        Self { container, data }
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], Error> {
+        if self.data.len() < n {
+            return Err(Error::Truncated(self.container));
+        }
+        let (res, rest) = self.data.split_at(n);
+        self.data = rest;
+        Ok(res)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, Error> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, Error> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mtl::{self, texture_file::*};
+
+    fn fill(layout: &Layout) -> Vec<u8> {
+        let mut data = vec![0u8; layout.data_len().unwrap()];
+        for (i, level) in layout.levels.iter().enumerate() {
+            for s in 0..layout.slices() {
+                for (j, b) in data[level.range(s).unwrap()].iter_mut().enumerate() {
+                    *b = (i * 31 + s * 7 + j) as u8;
+                }
+            }
+        }
+        data
+    }
+
+    fn roundtrip(layout: &Layout, container: Container) {
+        let data = fill(layout);
+        let file = layout.write(container, &data).unwrap();
+        let (c, read) = Layout::read(&file).unwrap();
+        assert_eq!(c, container);
+        assert_eq!(read.texture_type, layout.texture_type);
+        assert_eq!(read.pixel_format, layout.pixel_format);
+        assert_eq!(
+            (read.width, read.height, read.depth),
+            (layout.width, layout.height, layout.depth)
+        );
+        assert_eq!(read.mipmap_level_count, layout.mipmap_level_count);
+        assert_eq!(read.array_len, layout.array_len);
+        for level in 0..layout.mipmap_level_count {
+            assert_eq!(
+                read.levels[level].bytes_per_row,
+                layout.levels[level].bytes_per_row
+            );
+            for s in 0..layout.slices() {
+                assert_eq!(
+                    read.image(&file, level, s),
+                    layout.image(&data, level, s),
+                    "{container:?} level {level} slice {s}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn layout() {
+        let l = Layout::new_2d(mtl::PixelFormat::Rgba8UNorm, 5, 3, true).unwrap();
+        assert_eq!(l.mipmap_level_count, 3);
+        let sizes: Vec<_> = l.levels.iter().map(|l| (l.width, l.height)).collect();
+        assert_eq!(sizes, [(5, 3), (2, 1), (1, 1)]);
+        assert_eq!(l.levels[0].bytes_per_row, 20);
+        assert_eq!(l.levels[1].offset, 60);
+        assert_eq!(l.levels[2].offset, 68);
+        assert_eq!(l.data_len(), Ok(72));
+
+        let l = Layout::new_cube(mtl::PixelFormat::Astc6x6Ldr, 16, false).unwrap();
+        assert_eq!(l.slices(), 6);
+        assert_eq!(l.levels[0].bytes_per_image, 9 * 16);
+        assert_eq!(l.levels[0].range(5), Some(5 * 144..6 * 144));
+
+        let l = Layout::new_2d(mtl::PixelFormat::PvrtcRgba4Bpp, 4, 4, false).unwrap();
+        assert_eq!(l.levels[0].bytes_per_image, 32);
+
+        let l = Layout::new(
+            mtl::TextureType::_3d,
+            mtl::PixelFormat::R16Float,
+            8,
+            4,
+            2,
+            1,
+            4,
+        )
+        .unwrap();
+        let sizes: Vec<_> = l
+            .levels
+            .iter()
+            .map(|l| (l.width, l.height, l.depth))
+            .collect();
+        assert_eq!(sizes, [(8, 4, 2), (4, 2, 1), (2, 1, 1), (1, 1, 1)]);
+        assert_eq!(l.levels[1].offset, 128);
+
+        assert_eq!(max_mipmap_level_count(1, 1, 1), 1);
+        assert_eq!(max_mipmap_level_count(1920, 1080, 1), 11);
+    }
+
+    #[test]
+    fn invalid() {
+        let f = mtl::PixelFormat::Rgba8UNorm;
+        assert!(Layout::new_2d(f, 0, 1, false).is_err());
+        assert!(Layout::new_2d(mtl::PixelFormat::Invalid, 1, 1, false).is_err());
+        assert!(Layout::new(mtl::TextureType::_2d, f, 4, 4, 1, 1, 4).is_err());
+        assert!(Layout::new(mtl::TextureType::_2d, f, 4, 4, 1, 2, 1).is_err());
+        assert!(Layout::new(mtl::TextureType::_2d, f, 4, 4, 2, 1, 1).is_err());
+        assert!(Layout::new(mtl::TextureType::Cube, f, 4, 2, 1, 1, 1).is_err());
+        assert!(Layout::new(mtl::TextureType::_1d, f, 4, 2, 1, 1, 1).is_err());
+        assert!(Layout::new(mtl::TextureType::_2dMultisample, f, 4, 4, 1, 1, 1).is_err());
+
+        let l = Layout::new_2d(f, 4, 4, false).unwrap();
+        assert_eq!(
+            l.write(Container::Ktx2, &[0; 63]),
+            Err(Error::Layout("data is shorter than layout"))
+        );
+        assert_eq!(Layout::read(b"not a texture"), Err(Error::Container));
+
+        let file = l.write(Container::Dds, &[0; 64]).unwrap();
+        assert_eq!(
+            Layout::read(&file[..file.len() - 1]),
+            Err(Error::Truncated(Container::Dds))
+        );
+        let file = l.write(Container::Ktx2, &[0; 64]).unwrap();
+        assert_eq!(
+            Layout::read(&file[..file.len() - 1]),
+            Err(Error::Truncated(Container::Ktx2))
+        );
+    }
+
+    #[test]
+    fn overflow() {
+        use mtl::PixelFormat as F;
+        use mtl::TextureType as T;
+
+        let overflow = Err(Error::Layout("size overflow"));
+        assert_eq!(
+            Layout::new_2d(F::Rgba8UNorm, usize::MAX, 1, false),
+            overflow
+        );
+        assert_eq!(
+            Layout::new_2d(F::Rgba8UNorm, usize::MAX / 4, 2, false),
+            overflow
+        );
+        assert_eq!(
+            Layout::new(T::CubeArray, F::R8UNorm, 1, 1, 1, usize::MAX / 2, 1),
+            overflow
+        );
+
+        let l = Layout::new_2d(F::Rgba8UNorm, 4, 4, false).unwrap();
+        let mut level = l.levels[0];
+        level.offset = usize::MAX - 8;
+        assert_eq!(level.range(0), None);
+        level.depth = usize::MAX;
+        assert_eq!(level.slice_len(), None);
+
+        // width and height of u32::MAX, valid on their own
+        let mut ktx2 = l.write(Container::Ktx2, &[0; 64]).unwrap();
+        ktx2[20..28].fill(0xff);
+        assert_eq!(
+            Layout::read(&ktx2),
+            Err(Error::Invalid(Container::Ktx2, "size overflow"))
+        );
+        let mut dds = l.write(Container::Dds, &[0; 64]).unwrap();
+        dds[12..20].fill(0xff);
+        assert_eq!(
+            Layout::read(&dds),
+            Err(Error::Invalid(Container::Dds, "size overflow"))
+        );
+
+        // level offset at the end of the address space
+        let mut ktx2 = l.write(Container::Ktx2, &[0; 64]).unwrap();
+        ktx2[80..88].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            Layout::read(&ktx2),
+            Err(Error::Invalid(Container::Ktx2, "size overflow")
+                | Error::Truncated(Container::Ktx2))
+        ));
+    }
+
+    #[test]
+    fn roundtrips() {
+        use mtl::PixelFormat as F;
+        use mtl::TextureType as T;
+
+        let layouts = [
+            Layout::new_2d(F::Rgba8UNormSrgb, 17, 9, true).unwrap(),
+            Layout::new_2d(F::Bgra8UNorm, 8, 8, false).unwrap(),
+            Layout::new_2d(F::Bc7RgbaUNorm, 30, 20, true).unwrap(),
+            Layout::new_2d(F::Bc5RgSNorm, 16, 16, true).unwrap(),
+            Layout::new_2d(F::Rgba16Float, 3, 3, true).unwrap(),
+            Layout::new_cube(F::Bc3RgbaSrgb, 32, true).unwrap(),
+            Layout::new(T::CubeArray, F::Rg11B10Float, 4, 4, 1, 2, 3).unwrap(),
+            Layout::new(T::_2dArray, F::R8UNorm, 6, 2, 1, 3, 2).unwrap(),
+            Layout::new(T::_1d, F::R32Float, 16, 1, 1, 1, 5).unwrap(),
+            Layout::new(T::_1dArray, F::Rg16UInt, 16, 1, 1, 4, 1).unwrap(),
+            Layout::new(T::_3d, F::Rgba8UInt, 8, 4, 4, 1, 4).unwrap(),
+            Layout::new_2d(F::Rgb10A2UNorm, 4, 4, false).unwrap(),
+            Layout::new_2d(F::Depth32Float, 4, 4, false).unwrap(),
+        ];
+        for layout in &layouts {
+            roundtrip(layout, Container::Ktx2);
+            roundtrip(layout, Container::Dds);
+        }
+
+        let layouts = [
+            Layout::new_2d(F::Astc4x4Srgb, 20, 20, true).unwrap(),
+            Layout::new_2d(F::Etc2Rgb8A1, 20, 12, true).unwrap(),
+            Layout::new_2d(F::EacRg11SNorm, 8, 8, false).unwrap(),
+            Layout::new_2d(F::PvrtcRgba2Bpp, 32, 32, true).unwrap(),
+            Layout::new_2d(F::Depth24UnormStencil8, 4, 4, false).unwrap(),
+            Layout::new_2d(F::Astc10x8Hdr, 20, 20, true).unwrap(),
+        ];
+        for layout in &layouts {
+            roundtrip(layout, Container::Ktx2);
+        }
+
+        for f in [F::Astc4x4Ldr, F::Astc12x10Ldr, F::Astc8x5Ldr] {
+            let layout = Layout::new_2d(f, 50, 30, false).unwrap();
+            roundtrip(&layout, Container::Astc);
+        }
+        let layout = Layout::new(T::_3d, F::Astc6x6Ldr, 12, 12, 3, 1, 1).unwrap();
+        roundtrip(&layout, Container::Astc);
+    }
+
+    #[test]
+    fn unsupported() {
+        use mtl::PixelFormat as F;
+
+        let data = vec![0; 1024];
+        let l = Layout::new_2d(F::Bc1Rgba, 8, 8, true).unwrap();
+        assert_eq!(
+            l.write(Container::Astc, &data),
+            Err(Error::Unsupported(Container::Astc, "pixel format"))
+        );
+        let l = Layout::new_2d(F::Astc4x4Ldr, 8, 8, true).unwrap();
+        assert_eq!(
+            l.write(Container::Astc, &data),
+            Err(Error::Unsupported(Container::Astc, "mipmaps"))
+        );
+        let l = Layout::new_2d(F::Etc2Rgb8, 8, 8, false).unwrap();
+        assert_eq!(
+            l.write(Container::Dds, &data),
+            Err(Error::Unsupported(Container::Dds, "pixel format"))
+        );
+    }
+}
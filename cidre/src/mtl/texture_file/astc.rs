@@ -0,0 +1,94 @@
+//! `.astc` files of ARM `astcenc`, a single image of 2D blocks.
+
+use crate::mtl::{self, PixelFormat as F};
+
+use super::{Container, Error, Layout, Reader, SHORT};
+
+pub(super) const MAGIC: [u8; 4] = 0x5CA1AB13u32.to_le_bytes();
+
+const C: Container = Container::Astc;
+
+const HEADER_LEN: usize = 16;
+
+/// Block size and the LDR, sRGB and HDR formats using it.
+#[rustfmt::skip]
+const FORMATS: [(u8, u8, [F; 3]); 14] = [
+    (4, 4, [F::Astc4x4Ldr, F::Astc4x4Srgb, F::Astc4x4Hdr]),
+    (5, 4, [F::Astc5x4Ldr, F::Astc5x4Srgb, F::Astc5x4Hdr]),
+    (5, 5, [F::Astc5x5Ldr, F::Astc5x5Srgb, F::Astc5x5Hdr]),
+    (6, 5, [F::Astc6x5Ldr, F::Astc6x5Srgb, F::Astc6x5Hdr]),
+    (6, 6, [F::Astc6x6Ldr, F::Astc6x6Srgb, F::Astc6x6Hdr]),
+    (8, 5, [F::Astc8x5Ldr, F::Astc8x5Srgb, F::Astc8x5Hdr]),
+    (8, 6, [F::Astc8x6Ldr, F::Astc8x6Srgb, F::Astc8x6Hdr]),
+    (8, 8, [F::Astc8x8Ldr, F::Astc8x8Srgb, F::Astc8x8Hdr]),
+    (10, 5, [F::Astc10x5Ldr, F::Astc10x5Srgb, F::Astc10x5Hdr]),
+    (10, 6, [F::Astc10x6Ldr, F::Astc10x6Srgb, F::Astc10x6Hdr]),
+    (10, 8, [F::Astc10x8Ldr, F::Astc10x8Srgb, F::Astc10x8Hdr]),
+    (10, 10, [F::Astc10x10Ldr, F::Astc10x10Srgb, F::Astc10x10Hdr]),
+    (12, 10, [F::Astc12x10Ldr, F::Astc12x10Srgb, F::Astc12x10Hdr]),
+    (12, 12, [F::Astc12x12Ldr, F::Astc12x12Srgb, F::Asrc12x12Hdr]),
+];
+
+/// Reads the LDR format of the block size, the file doesn't tell
+/// LDR, sRGB and HDR content apart.
+pub(super) fn read(data: &[u8]) -> Result<Layout, Error> {
+    let mut r = Reader::new(C, data);
+    r.bytes(MAGIC.len())?;
+    let (block_x, block_y, block_z) = (r.u8()?, r.u8()?, r.u8()?);
+    let mut dims = [0usize; 3];
+    for d in dims.iter_mut() {
+        let [a, b, c] = r.array()?;
+        *d = u32::from_le_bytes([a, b, c, 0]) as usize;
+    }
+    if block_z != 1 {
+        return Err(Error::Unsupported(C, "3D blocks"));
+    }
+    let pixel_format = FORMATS
+        .iter()
+        .find(|(w, h, _)| (*w, *h) == (block_x, block_y))
+        .map(|(_, _, f)| f[0])
+        .ok_or(Error::Format(
+            C,
+            u32::from_le_bytes([block_x, block_y, block_z, 0]),
+        ))?;
+    let [width, height, depth] = dims;
+    let texture_type = if depth > 1 {
+        mtl::TextureType::_3d
+    } else {
+        mtl::TextureType::_2d
+    };
+    let mut res = Layout::new(texture_type, pixel_format, width, height, depth, 1, 1)
+        .map_err(|e| e.in_file(C))?;
+    res.levels[0].offset = HEADER_LEN;
+    Ok(res)
+}
+
+pub(super) fn write(layout: &Layout, data: &[u8]) -> Result<Vec<u8>, Error> {
+    use mtl::TextureType as TT;
+
+    let (block_x, block_y) = FORMATS
+        .iter()
+        .find(|(_, _, f)| f.contains(&layout.pixel_format))
+        .map(|(w, h, _)| (*w, *h))
+        .ok_or(Error::Unsupported(C, "pixel format"))?;
+    if layout.levels.len() > 1 {
+        return Err(Error::Unsupported(C, "mipmaps"));
+    }
+    if !matches!(layout.texture_type, TT::_2d | TT::_3d) {
+        return Err(Error::Unsupported(C, "texture type"));
+    }
+    let dims = [layout.width, layout.height, layout.depth];
+    if dims.iter().any(|d| *d >= 1 << 24) {
+        return Err(Error::Unsupported(C, "dimensions over 24 bits"));
+    }
+
+    let image = layout.image(data, 0, 0).ok_or(SHORT)?;
+    let mut res = Vec::with_capacity(HEADER_LEN + image.len());
+    res.extend_from_slice(&MAGIC);
+    res.extend_from_slice(&[block_x, block_y, 1]);
+    for d in dims {
+        res.extend_from_slice(&(d as u32).to_le_bytes()[..3]);
+    }
+    res.extend_from_slice(image);
+    Ok(res)
+}
@@ -0,0 +1,388 @@
+//! DirectDraw Surface with the legacy and the DX10 header.
+
+use crate::mtl::{self, PixelFormat as F};
+
+use super::{Container, Error, Layout, Reader, SHORT};
+
+pub(super) const MAGIC: [u8; 4] = *b"DDS ";
+
+const C: Container = Container::Dds;
+
+const HEADER_LEN: usize = 4 + 124;
+const DX10_HEADER_LEN: usize = 20;
+
+const DDSD_CAPS: u32 = 0x1;
+const DDSD_HEIGHT: u32 = 0x2;
+const DDSD_WIDTH: u32 = 0x4;
+const DDSD_PITCH: u32 = 0x8;
+const DDSD_PIXELFORMAT: u32 = 0x1000;
+const DDSD_MIPMAPCOUNT: u32 = 0x20000;
+const DDSD_LINEARSIZE: u32 = 0x80000;
+const DDSD_DEPTH: u32 = 0x800000;
+
+const DDPF_ALPHAPIXELS: u32 = 0x1;
+const DDPF_ALPHA: u32 = 0x2;
+const DDPF_FOURCC: u32 = 0x4;
+const DDPF_RGB: u32 = 0x40;
+const DDPF_LUMINANCE: u32 = 0x20000;
+
+const DDSCAPS_COMPLEX: u32 = 0x8;
+const DDSCAPS_TEXTURE: u32 = 0x1000;
+const DDSCAPS_MIPMAP: u32 = 0x400000;
+
+const DDSCAPS2_CUBEMAP: u32 = 0x200;
+#[doc(alias = "DDSCAPS2_CUBEMAP_ALLFACES")]
+const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xFC00;
+const DDSCAPS2_VOLUME: u32 = 0x200000;
+
+#[doc(alias = "D3D10_RESOURCE_DIMENSION_TEXTURE1D")]
+const DIMENSION_1D: u32 = 2;
+#[doc(alias = "D3D10_RESOURCE_DIMENSION_TEXTURE2D")]
+const DIMENSION_2D: u32 = 3;
+#[doc(alias = "D3D10_RESOURCE_DIMENSION_TEXTURE3D")]
+const DIMENSION_3D: u32 = 4;
+
+#[doc(alias = "DDS_RESOURCE_MISC_TEXTURECUBE")]
+const MISC_TEXTURE_CUBE: u32 = 0x4;
+
+const DX10: u32 = u32::from_le_bytes(*b"DX10");
+
+#[rustfmt::skip]
+const FORMATS: [(u32, F); 62] = [
+    (2, F::Rgba32Float), (3, F::Rgba32UInt), (4, F::Rgba32SInt),
+    (10, F::Rgba16Float), (11, F::Rgba16UNorm), (12, F::Rgba16UInt), (13, F::Rgba16SNorm),
+    (14, F::Rgba16SInt),
+    (16, F::Rg32Float), (17, F::Rg32UInt), (18, F::Rg32SInt),
+    (20, F::Depth32FloatStencil8),
+    (24, F::Rgb10A2UNorm), (25, F::Rgb10A2UInt), (26, F::Rg11B10Float),
+    (28, F::Rgba8UNorm), (29, F::Rgba8UNormSrgb), (30, F::Rgba8UInt), (31, F::Rgba8SNorm),
+    (32, F::Rgba8SInt),
+    (34, F::Rg16Float), (35, F::Rg16UNorm), (36, F::Rg16UInt), (37, F::Rg16SNorm),
+    (38, F::Rg16SInt),
+    (40, F::Depth32Float), (41, F::R32Float), (42, F::R32UInt), (43, F::R32SInt),
+    (45, F::Depth24UnormStencil8),
+    (49, F::Rg8UNorm), (50, F::Rg8UInt), (51, F::Rg8SNorm), (52, F::Rg8SInt),
+    (54, F::R16Float), (55, F::Depth16Unorm), (56, F::R16UNorm), (57, F::R16UInt),
+    (58, F::R16SNorm), (59, F::R16SInt),
+    (61, F::R8UNorm), (62, F::R8UInt), (63, F::R8SNorm), (64, F::R8SInt), (65, F::A8UNorm),
+    (67, F::Rgb9E5Float),
+    (71, F::Bc1Rgba), (72, F::Bc1RgbaSrgb), (74, F::Bc2Rgba), (75, F::Bc2RgbaSrgb),
+    (77, F::Bc3Rgba), (78, F::Bc3RgbaSrgb), (80, F::Bc4RUNorm), (81, F::Bc4RSNorm),
+    (83, F::Bc5RgUNorm), (84, F::Bc5RgSNorm),
+    (87, F::Bgra8UNorm), (91, F::Bgra8UNormSrgb),
+    (95, F::Bc6HRgbUFloat), (96, F::Bc6HRgbFloat), (98, F::Bc7RgbaUNorm),
+    (99, F::Bc7RgbaUNormSrgb),
+];
+
+/// `DXGI_FORMAT` of a pixel format.
+///
+/// ```
+/// use cidre::mtl::{self, texture_file};
+///
+/// assert_eq!(texture_file::dxgi_format(mtl::PixelFormat::Bc7RgbaUNorm), Some(98));
+/// assert_eq!(texture_file::from_dxgi_format(98), Some(mtl::PixelFormat::Bc7RgbaUNorm));
+/// ```
+pub fn dxgi_format(pixel_format: mtl::PixelFormat) -> Option<u32> {
+    FORMATS
+        .iter()
+        .find(|(_, f)| *f == pixel_format)
+        .map(|(dxgi, _)| *dxgi)
+}
+
+pub fn from_dxgi_format(dxgi_format: u32) -> Option<mtl::PixelFormat> {
+    FORMATS
+        .iter()
+        .find(|(dxgi, _)| *dxgi == dxgi_format)
+        .map(|(_, f)| *f)
+}
+
+/// Pixel format of a header without the DX10 extension.
+fn legacy_format(flags: u32, four_cc: u32, bits: u32, masks: [u32; 4]) -> Option<F> {
+    if flags & DDPF_FOURCC != 0 {
+        return Some(match &four_cc.to_le_bytes() {
+            b"DXT1" => F::Bc1Rgba,
+            // premultiplied variants, alpha mode is up to the caller
+            b"DXT2" | b"DXT3" => F::Bc2Rgba,
+            b"DXT4" | b"DXT5" => F::Bc3Rgba,
+            b"ATI1" | b"BC4U" => F::Bc4RUNorm,
+            b"BC4S" => F::Bc4RSNorm,
+            b"ATI2" | b"BC5U" => F::Bc5RgUNorm,
+            b"BC5S" => F::Bc5RgSNorm,
+            // D3DFORMAT values
+            _ => match four_cc {
+                36 => F::Rgba16UNorm,
+                110 => F::Rgba16SNorm,
+                111 => F::R16Float,
+                112 => F::Rg16Float,
+                113 => F::Rgba16Float,
+                114 => F::R32Float,
+                115 => F::Rg32Float,
+                116 => F::Rgba32Float,
+                _ => return None,
+            },
+        });
+    }
+    let alpha = if flags & DDPF_ALPHAPIXELS != 0 {
+        masks[3]
+    } else {
+        0
+    };
+    Some(match (bits, [masks[0], masks[1], masks[2], alpha]) {
+        (32, [0xff0000, 0xff00, 0xff, 0xff000000]) if flags & DDPF_RGB != 0 => F::Bgra8UNorm,
+        (32, [0xff, 0xff00, 0xff0000, 0xff000000]) if flags & DDPF_RGB != 0 => F::Rgba8UNorm,
+        (32, [0x3ff, 0xffc00, 0x3ff00000, 0xc0000000]) if flags & DDPF_RGB != 0 => F::Rgb10A2UNorm,
+        (32, [0x3ff00000, 0xffc00, 0x3ff, 0xc0000000]) if flags & DDPF_RGB != 0 => F::Bgr10A2UNorm,
+        (32, [0xffff, 0xffff0000, 0, 0]) if flags & DDPF_RGB != 0 => F::Rg16UNorm,
+        (8, [0xff, 0, 0, 0]) if flags & DDPF_LUMINANCE != 0 => F::R8UNorm,
+        (16, [0xffff, 0, 0, 0]) if flags & DDPF_LUMINANCE != 0 => F::R16UNorm,
+        (8, _) if flags & DDPF_ALPHA != 0 && masks[3] == 0xff => F::A8UNorm,
+        _ => return None,
+    })
+}
+
+pub(super) fn read(data: &[u8]) -> Result<Layout, Error> {
+    use mtl::TextureType as TT;
+
+    let mut r = Reader::new(C, data);
+    r.bytes(MAGIC.len())?;
+    if r.u32()? != 124 {
+        return Err(Error::Invalid(C, "header size"));
+    }
+    let _flags = r.u32()?;
+    let height = r.u32()? as usize;
+    let width = r.u32()? as usize;
+    let _pitch_or_linear_size = r.u32()?;
+    let depth = r.u32()? as usize;
+    let levels = r.u32()? as usize;
+    r.bytes(11 * 4)?;
+    if r.u32()? != 32 {
+        return Err(Error::Invalid(C, "pixel format size"));
+    }
+    let pf_flags = r.u32()?;
+    let four_cc = r.u32()?;
+    let bits = r.u32()?;
+    let masks = [r.u32()?, r.u32()?, r.u32()?, r.u32()?];
+    let _caps = r.u32()?;
+    let caps2 = r.u32()?;
+    r.bytes(3 * 4)?;
+
+    let mut start = HEADER_LEN;
+    let (texture_type, pixel_format, depth, array_len) = if pf_flags & DDPF_FOURCC != 0
+        && four_cc == DX10
+    {
+        start += DX10_HEADER_LEN;
+        let dxgi = r.u32()?;
+        let dimension = r.u32()?;
+        let misc = r.u32()?;
+        let array_len = r.u32()? as usize;
+        let _misc2 = r.u32()?;
+        let pixel_format = from_dxgi_format(dxgi).ok_or(Error::Format(C, dxgi))?;
+        let array = array_len > 1;
+        let texture_type = match dimension {
+            DIMENSION_1D if array => TT::_1dArray,
+            DIMENSION_1D => TT::_1d,
+            DIMENSION_2D if misc & MISC_TEXTURE_CUBE != 0 && array => TT::CubeArray,
+            DIMENSION_2D if misc & MISC_TEXTURE_CUBE != 0 => TT::Cube,
+            DIMENSION_2D if array => TT::_2dArray,
+            DIMENSION_2D => TT::_2d,
+            DIMENSION_3D if array => return Err(Error::Unsupported(C, "3D texture arrays")),
+            DIMENSION_3D => TT::_3d,
+            _ => return Err(Error::Invalid(C, "resource dimension")),
+        };
+        let depth = if texture_type == TT::_3d { depth } else { 1 };
+        (texture_type, pixel_format, depth, array_len)
+    } else {
+        let pixel_format = legacy_format(pf_flags, four_cc, bits, masks).ok_or(Error::Format(
+            C,
+            if pf_flags & DDPF_FOURCC != 0 {
+                four_cc
+            } else {
+                bits
+            },
+        ))?;
+        if caps2 & DDSCAPS2_CUBEMAP != 0 {
+            if caps2 & DDSCAPS2_CUBEMAP_ALL_FACES != DDSCAPS2_CUBEMAP_ALL_FACES {
+                return Err(Error::Unsupported(C, "partial cube maps"));
+            }
+            (TT::Cube, pixel_format, 1, 1)
+        } else if caps2 & DDSCAPS2_VOLUME != 0 {
+            (TT::_3d, pixel_format, depth, 1)
+        } else {
+            (TT::_2d, pixel_format, 1, 1)
+        }
+    };
+
+    let mut res = Layout::new(
+        texture_type,
+        pixel_format,
+        width,
+        height,
+        depth.max(1),
+        array_len.max(1),
+        levels.max(1),
+    )
+    .map_err(|e| e.in_file(C))?;
+
+    // each slice holds its whole mip chain, Layout::new checked the sizes
+    let overflow = Error::Invalid(C, "size overflow");
+    let slice_stride = res
+        .levels
+        .iter()
+        .try_fold(0usize, |sum, l| sum.checked_add(l.slice_len()?))
+        .ok_or(overflow.clone())?;
+    let mut offset = start;
+    for level in res.levels.iter_mut() {
+        level.offset = offset;
+        level.slice_stride = slice_stride;
+        offset = level
+            .slice_len()
+            .and_then(|len| offset.checked_add(len))
+            .ok_or(overflow.clone())?;
+    }
+    Ok(res)
+}
+
+pub(super) fn write(layout: &Layout, data: &[u8]) -> Result<Vec<u8>, Error> {
+    use mtl::TextureType as TT;
+
+    let dxgi = dxgi_format(layout.pixel_format).ok_or(Error::Unsupported(C, "pixel format"))?;
+    let levels = layout.levels.len();
+    let level0 = &layout.levels[0];
+    let compressed = layout.pixel_format.is_compressed();
+    let complex = levels > 1 || layout.is_cube() || layout.texture_type == TT::_3d;
+
+    let mut flags = DDSD_CAPS | DDSD_HEIGHT | DDSD_WIDTH | DDSD_PIXELFORMAT;
+    if levels > 1 {
+        flags |= DDSD_MIPMAPCOUNT;
+    }
+    if compressed {
+        flags |= DDSD_LINEARSIZE;
+    } else {
+        flags |= DDSD_PITCH;
+    }
+    if layout.texture_type == TT::_3d {
+        flags |= DDSD_DEPTH;
+    }
+    let mut caps = DDSCAPS_TEXTURE;
+    if complex {
+        caps |= DDSCAPS_COMPLEX;
+    }
+    if levels > 1 {
+        caps |= DDSCAPS_MIPMAP;
+    }
+    let mut caps2 = 0;
+    if layout.is_cube() {
+        caps2 |= DDSCAPS2_CUBEMAP | DDSCAPS2_CUBEMAP_ALL_FACES;
+    }
+    if layout.texture_type == TT::_3d {
+        caps2 |= DDSCAPS2_VOLUME;
+    }
+    let dimension = match layout.texture_type {
+        TT::_1d | TT::_1dArray => DIMENSION_1D,
+        TT::_3d => DIMENSION_3D,
+        _ => DIMENSION_2D,
+    };
+
+    let mut header = [0u32; 31 + 5];
+    header[0] = 124;
+    header[1] = flags;
+    header[2] = layout.height as u32;
+    header[3] = layout.width as u32;
+    header[4] = if compressed {
+        level0.bytes_per_image
+    } else {
+        level0.bytes_per_row
+    } as u32;
+    header[5] = layout.depth as u32;
+    header[6] = levels as u32;
+    // pixel format after 11 reserved
+    header[18] = 32;
+    header[19] = DDPF_FOURCC;
+    header[20] = DX10;
+    header[26] = caps;
+    header[27] = caps2;
+    // DX10 header after caps and reserved
+    header[31] = dxgi;
+    header[32] = dimension;
+    header[33] = if layout.is_cube() {
+        MISC_TEXTURE_CUBE
+    } else {
+        0
+    };
+    header[34] = layout.array_len as u32;
+
+    let mut res = Vec::with_capacity(HEADER_LEN + DX10_HEADER_LEN + layout.data_len()?);
+    res.extend_from_slice(&MAGIC);
+    for v in header {
+        res.extend_from_slice(&v.to_le_bytes());
+    }
+    for s in 0..layout.slices() {
+        for level in 0..levels {
+            res.extend_from_slice(layout.image(data, level, s).ok_or(SHORT)?);
+        }
+    }
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mtl::{self, texture_file::*};
+
+    #[test]
+    fn formats() {
+        for (dxgi, f) in super::FORMATS {
+            assert_eq!(from_dxgi_format(dxgi), Some(f));
+            assert_eq!(dxgi_format(f), Some(dxgi));
+        }
+        assert_eq!(dxgi_format(mtl::PixelFormat::Astc4x4Ldr), None);
+    }
+
+    #[test]
+    fn legacy() {
+        // 4x4 DXT5 with two levels, as older tools write it
+        let mut file = Vec::new();
+        file.extend_from_slice(b"DDS ");
+        let mut header = [0u32; 31];
+        header[0] = 124;
+        header[1] = 0x1 | 0x2 | 0x4 | 0x1000 | 0x20000 | 0x80000;
+        header[2] = 4;
+        header[3] = 4;
+        header[4] = 16;
+        header[6] = 2;
+        header[18] = 32;
+        header[19] = 0x4;
+        header[20] = u32::from_le_bytes(*b"DXT5");
+        header[26] = 0x1000 | 0x8 | 0x400000;
+        for v in header {
+            file.extend_from_slice(&v.to_le_bytes());
+        }
+        file.extend_from_slice(&[1; 16]);
+        file.extend_from_slice(&[2; 16]);
+
+        let (c, layout) = Layout::read(&file).unwrap();
+        assert_eq!(c, Container::Dds);
+        assert_eq!(layout.pixel_format, mtl::PixelFormat::Bc3Rgba);
+        assert_eq!(layout.texture_type, mtl::TextureType::_2d);
+        assert_eq!(layout.mipmap_level_count, 2);
+        assert_eq!(layout.image(&file, 0, 0), Some(&[1; 16][..]));
+        assert_eq!(layout.image(&file, 1, 0), Some(&[2; 16][..]));
+
+        // uncompressed BGRA cube
+        header[1] = 0x1 | 0x2 | 0x4 | 0x8 | 0x1000;
+        header[6] = 0;
+        header[19] = 0x40 | 0x1;
+        header[20] = 0;
+        header[21] = 32;
+        header[22..26].copy_from_slice(&[0xff0000, 0xff00, 0xff, 0xff000000]);
+        header[27] = 0x200 | 0xFC00;
+        let mut file = b"DDS ".to_vec();
+        for v in header {
+            file.extend_from_slice(&v.to_le_bytes());
+        }
+        file.resize(file.len() + 6 * 64, 0);
+        let (_, layout) = Layout::read(&file).unwrap();
+        assert_eq!(layout.pixel_format, mtl::PixelFormat::Bgra8UNorm);
+        assert_eq!(layout.texture_type, mtl::TextureType::Cube);
+        assert_eq!(layout.levels[0].range(5), Some(128 + 5 * 64..128 + 6 * 64));
+    }
+}
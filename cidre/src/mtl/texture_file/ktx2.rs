@@ -0,0 +1,431 @@
+//! Khronos Texture 2.0, without supercompression.
+
+use crate::mtl::{self, PixelFormat as F, PixelFormatComponentType as T};
+
+use super::{Container, Error, Layout, Reader, SHORT};
+
+pub(super) const MAGIC: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+
+const C: Container = Container::Ktx2;
+
+/// Header and index up to the level index.
+const HEADER_LEN: usize = 80;
+
+#[rustfmt::skip]
+const FORMATS: [(u32, F); 107] = [
+    (9, F::R8UNorm), (10, F::R8SNorm), (13, F::R8UInt), (14, F::R8SInt), (15, F::R8UNormSrgb),
+    (16, F::Rg8UNorm), (17, F::Rg8SNorm), (20, F::Rg8UInt), (21, F::Rg8SInt), (22, F::Rg8UNormSrgb),
+    (37, F::Rgba8UNorm), (38, F::Rgba8SNorm), (41, F::Rgba8UInt), (42, F::Rgba8SInt),
+    (43, F::Rgba8UNormSrgb), (44, F::Bgra8UNorm), (50, F::Bgra8UNormSrgb),
+    (58, F::Bgr10A2UNorm), (64, F::Rgb10A2UNorm), (68, F::Rgb10A2UInt),
+    (70, F::R16UNorm), (71, F::R16SNorm), (74, F::R16UInt), (75, F::R16SInt), (76, F::R16Float),
+    (77, F::Rg16UNorm), (78, F::Rg16SNorm), (81, F::Rg16UInt), (82, F::Rg16SInt), (83, F::Rg16Float),
+    (91, F::Rgba16UNorm), (92, F::Rgba16SNorm), (95, F::Rgba16UInt), (96, F::Rgba16SInt),
+    (97, F::Rgba16Float),
+    (98, F::R32UInt), (99, F::R32SInt), (100, F::R32Float),
+    (101, F::Rg32UInt), (102, F::Rg32SInt), (103, F::Rg32Float),
+    (107, F::Rgba32UInt), (108, F::Rgba32SInt), (109, F::Rgba32Float),
+    (122, F::Rg11B10Float), (123, F::Rgb9E5Float),
+    (124, F::Depth16Unorm), (126, F::Depth32Float), (127, F::Stencil8),
+    (129, F::Depth24UnormStencil8), (130, F::Depth32FloatStencil8),
+    (133, F::Bc1Rgba), (134, F::Bc1RgbaSrgb), (135, F::Bc2Rgba), (136, F::Bc2RgbaSrgb),
+    (137, F::Bc3Rgba), (138, F::Bc3RgbaSrgb), (139, F::Bc4RUNorm), (140, F::Bc4RSNorm),
+    (141, F::Bc5RgUNorm), (142, F::Bc5RgSNorm), (143, F::Bc6HRgbUFloat), (144, F::Bc6HRgbFloat),
+    (145, F::Bc7RgbaUNorm), (146, F::Bc7RgbaUNormSrgb),
+    (147, F::Etc2Rgb8), (148, F::Etc2Rgb8Srgb), (149, F::Etc2Rgb8A1), (150, F::Etc2Rgb8A1Srgb),
+    (151, F::EacRgba8), (152, F::EacRgba8Srgb), (153, F::EacR11UNorm), (154, F::EacR11SNorm),
+    (155, F::EacRg11UNorm), (156, F::EacRg11SNorm),
+    (157, F::Astc4x4Ldr), (158, F::Astc4x4Srgb), (159, F::Astc5x4Ldr), (160, F::Astc5x4Srgb),
+    (161, F::Astc5x5Ldr), (162, F::Astc5x5Srgb), (163, F::Astc6x5Ldr), (164, F::Astc6x5Srgb),
+    (165, F::Astc6x6Ldr), (166, F::Astc6x6Srgb), (167, F::Astc8x5Ldr), (168, F::Astc8x5Srgb),
+    (169, F::Astc8x6Ldr), (170, F::Astc8x6Srgb), (171, F::Astc8x8Ldr), (172, F::Astc8x8Srgb),
+    (173, F::Astc10x5Ldr), (174, F::Astc10x5Srgb), (175, F::Astc10x6Ldr), (176, F::Astc10x6Srgb),
+    (177, F::Astc10x8Ldr), (178, F::Astc10x8Srgb), (179, F::Astc10x10Ldr),
+    (180, F::Astc10x10Srgb), (181, F::Astc12x10Ldr), (182, F::Astc12x10Srgb),
+    (183, F::Astc12x12Ldr), (184, F::Astc12x12Srgb),
+    // VK_IMG_format_pvrtc, PVRTC1 is always RGBA there
+    (1000054000, F::PvrtcRgba2Bpp), (1000054001, F::PvrtcRgba4Bpp),
+    (1000054004, F::PvrtcRgba2BppSrgb), (1000054005, F::PvrtcRgba4BppSrgb),
+];
+
+/// VK_EXT_texture_compression_astc_hdr
+#[rustfmt::skip]
+const ASTC_HDR: [F; 14] = [
+    F::Astc4x4Hdr, F::Astc5x4Hdr, F::Astc5x5Hdr, F::Astc6x5Hdr, F::Astc6x6Hdr, F::Astc8x5Hdr,
+    F::Astc8x6Hdr, F::Astc8x8Hdr, F::Astc10x5Hdr, F::Astc10x6Hdr, F::Astc10x8Hdr,
+    F::Astc10x10Hdr, F::Astc12x10Hdr, F::Asrc12x12Hdr,
+];
+
+const ASTC_HDR_BASE: u32 = 1000066000;
+
+/// `VkFormat` of a pixel format.
+///
+/// ```
+/// use cidre::mtl::{self, texture_file};
+///
+/// assert_eq!(texture_file::vk_format(mtl::PixelFormat::Bgra8UNormSrgb), Some(50));
+/// assert_eq!(texture_file::from_vk_format(50), Some(mtl::PixelFormat::Bgra8UNormSrgb));
+/// ```
+pub fn vk_format(pixel_format: mtl::PixelFormat) -> Option<u32> {
+    if let Some(i) = ASTC_HDR.iter().position(|f| *f == pixel_format) {
+        return Some(ASTC_HDR_BASE + i as u32);
+    }
+    let pixel_format = match pixel_format {
+        F::PvrtcRgb2Bpp => F::PvrtcRgba2Bpp,
+        F::PvrtcRgb4Bpp => F::PvrtcRgba4Bpp,
+        F::PvrtcRgb2BppSrgb => F::PvrtcRgba2BppSrgb,
+        F::PvrtcRgb4BppSrgb => F::PvrtcRgba4BppSrgb,
+        f => f,
+    };
+    FORMATS
+        .iter()
+        .find(|(_, f)| *f == pixel_format)
+        .map(|(vk, _)| *vk)
+}
+
+pub fn from_vk_format(vk_format: u32) -> Option<mtl::PixelFormat> {
+    if let Some(i) = vk_format.checked_sub(ASTC_HDR_BASE) {
+        if let Some(f) = ASTC_HDR.get(i as usize) {
+            return Some(*f);
+        }
+    }
+    FORMATS
+        .iter()
+        .find(|(vk, _)| *vk == vk_format)
+        .map(|(_, f)| *f)
+}
+
+pub(super) fn read(data: &[u8]) -> Result<Layout, Error> {
+    use mtl::TextureType as TT;
+
+    let mut r = Reader::new(C, data);
+    r.bytes(MAGIC.len())?;
+    let vk = r.u32()?;
+    let _type_size = r.u32()?;
+    let width = r.u32()? as usize;
+    let height = r.u32()? as usize;
+    let depth = r.u32()? as usize;
+    let layers = r.u32()? as usize;
+    let faces = r.u32()?;
+    let levels = r.u32()? as usize;
+    let supercompression = r.u32()?;
+    let dfd_offset = r.u32()? as usize;
+    let dfd_len = r.u32()? as usize;
+    let kvd_offset = r.u32()? as usize;
+    let kvd_len = r.u32()? as usize;
+    let _sgd_offset = r.u64()?;
+    let _sgd_len = r.u64()?;
+
+    if supercompression != 0 {
+        return Err(Error::Unsupported(C, "supercompression"));
+    }
+    let pixel_format = from_vk_format(vk).ok_or(Error::Format(C, vk))?;
+    let fits =
+        |offset: usize, len: usize| offset.checked_add(len).is_some_and(|end| end <= data.len());
+    if !fits(dfd_offset, dfd_len) || !fits(kvd_offset, kvd_len) {
+        return Err(Error::Truncated(C));
+    }
+    let texture_type = match (faces, height, depth, layers) {
+        (1, 0, 0, 0) => TT::_1d,
+        (1, 0, 0, _) => TT::_1dArray,
+        (1, _, 0, 0) => TT::_2d,
+        (1, _, 0, _) => TT::_2dArray,
+        (1, _, _, 0) => TT::_3d,
+        (6, _, 0, 0) => TT::Cube,
+        (6, _, 0, _) => TT::CubeArray,
+        (1, _, _, _) => return Err(Error::Unsupported(C, "3D texture arrays")),
+        (6, _, _, _) => return Err(Error::Invalid(C, "cube depth")),
+        _ => return Err(Error::Invalid(C, "face count")),
+    };
+    // level count of 0 asks the loader to generate the mips
+    let mut res = Layout::new(
+        texture_type,
+        pixel_format,
+        width,
+        height.max(1),
+        depth.max(1),
+        layers.max(1),
+        levels.max(1),
+    )
+    .map_err(|e| e.in_file(C))?;
+
+    let slices = res.slices();
+    for level in res.levels.iter_mut() {
+        let offset = r.u64()?;
+        let len = r.u64()?;
+        let _uncompressed_len = r.u64()?;
+        let slice_len = level
+            .slice_len()
+            .ok_or(Error::Invalid(C, "size overflow"))?;
+        if slice_len.checked_mul(slices).map(|l| l as u64) != Some(len) {
+            return Err(Error::Invalid(C, "level length"));
+        }
+        level.offset = usize::try_from(offset).map_err(|_| Error::Truncated(C))?;
+        level.slice_stride = slice_len;
+    }
+    Ok(res)
+}
+
+pub(super) fn write(layout: &Layout, data: &[u8]) -> Result<Vec<u8>, Error> {
+    use mtl::TextureType as TT;
+
+    let pixel_format = layout.pixel_format;
+    let vk = vk_format(pixel_format).ok_or(Error::Unsupported(C, "pixel format"))?;
+    let dfd = dfd(pixel_format).ok_or(Error::Unsupported(C, "pixel format"))?;
+
+    let levels = layout.levels.len();
+    let dfd_offset = HEADER_LEN + levels * 24;
+    let mut res = Vec::with_capacity(dfd_offset + dfd.len() + layout.data_len()?);
+    res.extend_from_slice(&MAGIC);
+    let u32s = [
+        vk,
+        type_size(pixel_format),
+        layout.width as u32,
+        if matches!(layout.texture_type, TT::_1d | TT::_1dArray) {
+            0
+        } else {
+            layout.height as u32
+        },
+        if layout.texture_type == TT::_3d {
+            layout.depth as u32
+        } else {
+            0
+        },
+        if layout.is_array() {
+            layout.array_len as u32
+        } else {
+            0
+        },
+        if layout.is_cube() { 6 } else { 1 },
+        levels as u32,
+        // supercompression scheme
+        0,
+        dfd_offset as u32,
+        dfd.len() as u32,
+        // no key/value data
+        0,
+        0,
+    ];
+    for v in u32s {
+        res.extend_from_slice(&v.to_le_bytes());
+    }
+    // no supercompression global data
+    res.extend_from_slice(&[0; 16]);
+    res.resize(dfd_offset, 0);
+    res.extend_from_slice(&dfd);
+
+    // levels go smallest first, each aligned to both its block and 4 bytes
+    let align = lcm(pixel_format.bytes_per_block(), 4);
+    let slices = layout.slices();
+    for i in (0..levels).rev() {
+        res.resize(res.len().next_multiple_of(align), 0);
+        let offset = res.len() as u64;
+        for s in 0..slices {
+            res.extend_from_slice(layout.image(data, i, s).ok_or(SHORT)?);
+        }
+        let len = res.len() as u64 - offset;
+        let entry = HEADER_LEN + i * 24;
+        res[entry..entry + 8].copy_from_slice(&offset.to_le_bytes());
+        res[entry + 8..entry + 16].copy_from_slice(&len.to_le_bytes());
+        res[entry + 16..entry + 24].copy_from_slice(&len.to_le_bytes());
+    }
+    Ok(res)
+}
+
+fn lcm(a: usize, b: usize) -> usize {
+    let (mut x, mut y) = (a, b);
+    while y != 0 {
+        (x, y) = (y, x % y);
+    }
+    a / x * b
+}
+
+fn type_size(pixel_format: mtl::PixelFormat) -> u32 {
+    match pixel_format {
+        f if f.is_compressed() => 1,
+        F::Rgb10A2UNorm
+        | F::Rgb10A2UInt
+        | F::Bgr10A2UNorm
+        | F::Rg11B10Float
+        | F::Rgb9E5Float
+        | F::Depth24UnormStencil8
+        | F::Depth32FloatStencil8 => 4,
+        f => (f.bytes_per_block() / f.components()) as u32,
+    }
+}
+
+#[doc(alias = "KHR_DF_MODEL_RGBSDA")]
+const MODEL_RGBSDA: u8 = 1;
+
+const RED: u8 = 0;
+const GREEN: u8 = 1;
+const BLUE: u8 = 2;
+const STENCIL: u8 = 13;
+const DEPTH: u8 = 14;
+const ALPHA: u8 = 15;
+
+#[doc(alias = "KHR_DF_SAMPLE_DATATYPE_LINEAR")]
+const LINEAR: u8 = 0x10;
+
+#[doc(alias = "KHR_DF_SAMPLE_DATATYPE_SIGNED")]
+const SIGNED: u8 = 0x40;
+
+#[doc(alias = "KHR_DF_SAMPLE_DATATYPE_FLOAT")]
+const FLOAT: u8 = 0x80;
+
+/// Color model and `(channel, bits)` of the samples from the lowest bit up.
+fn samples(pixel_format: mtl::PixelFormat) -> Option<(u8, Vec<(u8, u8)>)> {
+    let model = match pixel_format.linear() {
+        F::Bc1Rgba => return Some((128, vec![(RED, 64)])),
+        F::Bc2Rgba => return Some((129, vec![(ALPHA, 64), (RED, 64)])),
+        F::Bc3Rgba => return Some((130, vec![(ALPHA, 64), (RED, 64)])),
+        F::Bc4RUNorm | F::Bc4RSNorm => return Some((131, vec![(RED, 64)])),
+        F::Bc5RgUNorm | F::Bc5RgSNorm => return Some((132, vec![(RED, 64), (GREEN, 64)])),
+        F::Bc6HRgbFloat | F::Bc6HRgbUFloat => return Some((133, vec![(RED, 128)])),
+        F::Bc7RgbaUNorm => return Some((134, vec![(RED, 128)])),
+        F::Etc2Rgb8 | F::Etc2Rgb8A1 => return Some((161, vec![(BLUE, 64)])),
+        F::EacRgba8 => return Some((161, vec![(ALPHA, 64), (BLUE, 64)])),
+        F::EacR11UNorm | F::EacR11SNorm => return Some((161, vec![(RED, 64)])),
+        F::EacRg11UNorm | F::EacRg11SNorm => return Some((161, vec![(RED, 64), (GREEN, 64)])),
+        F::PvrtcRgba2Bpp | F::PvrtcRgba4Bpp => return Some((164, vec![(RED, 64)])),
+        f if f.is_compressed() => return Some((162, vec![(RED, 128)])),
+        f => f,
+    };
+    let samples = match model {
+        F::Bgra8UNorm => vec![(BLUE, 8), (GREEN, 8), (RED, 8), (ALPHA, 8)],
+        F::Rgb10A2UNorm | F::Rgb10A2UInt => vec![(RED, 10), (GREEN, 10), (BLUE, 10), (ALPHA, 2)],
+        F::Bgr10A2UNorm => vec![(BLUE, 10), (GREEN, 10), (RED, 10), (ALPHA, 2)],
+        F::Rg11B10Float => vec![(RED, 11), (GREEN, 11), (BLUE, 10)],
+        F::Depth16Unorm => vec![(DEPTH, 16)],
+        F::Depth32Float => vec![(DEPTH, 32)],
+        F::Stencil8 => vec![(STENCIL, 8)],
+        F::Depth24UnormStencil8 => vec![(DEPTH, 24), (STENCIL, 8)],
+        F::Depth32FloatStencil8 => vec![(DEPTH, 32), (STENCIL, 8)],
+        // shared exponent needs samples of its own per channel
+        F::Rgb9E5Float => return None,
+        f => {
+            let n = f.components();
+            let bits = (f.bytes_per_block() * 8 / n) as u8;
+            [RED, GREEN, BLUE, ALPHA][..n]
+                .iter()
+                .map(|c| (*c, bits))
+                .collect()
+        }
+    };
+    Some((MODEL_RGBSDA, samples))
+}
+
+/// Data format descriptor with a single basic block.
+fn dfd(pixel_format: mtl::PixelFormat) -> Option<Vec<u8>> {
+    let (model, samples) = samples(pixel_format)?;
+    let block_len = 24 + 16 * samples.len();
+    let (block_width, block_height) = pixel_format.block_size();
+    let component_type = pixel_format.component_type();
+
+    let mut res = Vec::with_capacity(4 + block_len);
+    res.extend_from_slice(&((4 + block_len) as u32).to_le_bytes());
+    // vendor and descriptor type
+    res.extend_from_slice(&0u32.to_le_bytes());
+    // version
+    res.extend_from_slice(&2u16.to_le_bytes());
+    res.extend_from_slice(&(block_len as u16).to_le_bytes());
+    res.extend_from_slice(&[
+        model,
+        // BT.709 primaries
+        1,
+        // linear or sRGB transfer
+        if pixel_format.is_srgb() { 2 } else { 1 },
+        // straight alpha
+        0,
+        block_width as u8 - 1,
+        block_height as u8 - 1,
+        0,
+        0,
+    ]);
+    let mut planes = [0u8; 8];
+    planes[0] = pixel_format.bytes_per_block() as u8;
+    res.extend_from_slice(&planes);
+
+    let mut bit_offset = 0u16;
+    for (channel, bits) in samples {
+        let typ = if channel == STENCIL {
+            T::UInt
+        } else {
+            component_type
+        };
+        let signed = matches!(typ, T::SNorm | T::SInt | T::Float) && channel != DEPTH;
+        let mut qualifiers = match typ {
+            T::Float | T::UFloat => FLOAT,
+            _ => 0,
+        };
+        if signed {
+            qualifiers |= SIGNED;
+        }
+        if channel == ALPHA && pixel_format.is_srgb() {
+            qualifiers |= LINEAR;
+        }
+        let (lower, upper) = match typ {
+            T::Float if signed => ((-1.0f32).to_bits(), 1.0f32.to_bits()),
+            T::Float | T::UFloat => (0, 1.0f32.to_bits()),
+            _ if pixel_format.is_compressed() => {
+                if signed {
+                    (i32::MIN as u32, i32::MAX as u32)
+                } else {
+                    (0, u32::MAX)
+                }
+            }
+            T::UInt => (0, 1),
+            T::SInt => (-1i32 as u32, 1),
+            T::SNorm => {
+                let max = (1u32 << (bits - 1)) - 1;
+                (max.wrapping_neg(), max)
+            }
+            _ => (0, u32::MAX >> (32 - bits as u32)),
+        };
+        res.extend_from_slice(&bit_offset.to_le_bytes());
+        res.extend_from_slice(&[bits - 1, channel | qualifiers, 0, 0, 0, 0]);
+        res.extend_from_slice(&lower.to_le_bytes());
+        res.extend_from_slice(&upper.to_le_bytes());
+        bit_offset += bits as u16;
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mtl::{self, texture_file::*};
+
+    #[test]
+    fn formats() {
+        for (vk, f) in super::FORMATS {
+            assert_eq!(from_vk_format(vk), Some(f));
+            assert_eq!(vk_format(f), Some(vk));
+            assert!(super::dfd(f).is_some() || f == mtl::PixelFormat::Rgb9E5Float);
+        }
+        for f in super::ASTC_HDR {
+            assert_eq!(from_vk_format(vk_format(f).unwrap()), Some(f));
+        }
+        assert_eq!(vk_format(mtl::PixelFormat::PvrtcRgb4Bpp), Some(1000054001));
+        assert_eq!(vk_format(mtl::PixelFormat::Gbgr422), None);
+        assert_eq!(from_vk_format(0), None);
+    }
+
+    #[test]
+    fn dfd() {
+        let dfd = super::dfd(mtl::PixelFormat::Rgba8UNormSrgb).unwrap();
+        assert_eq!(dfd.len(), 4 + 24 + 4 * 16);
+        assert_eq!(&dfd[..4], &(92u32).to_le_bytes());
+        // model, primaries, transfer
+        assert_eq!(&dfd[12..15], &[1, 1, 2]);
+        // alpha sample at bit 24 is linear
+        let alpha = &dfd[28 + 3 * 16..];
+        assert_eq!(&alpha[..4], &[24, 0, 7, 15 | 0x10]);
+        assert_eq!(&alpha[12..16], &255u32.to_le_bytes());
+
+        let dfd = super::dfd(mtl::PixelFormat::Astc8x6Ldr).unwrap();
+        assert_eq!(&dfd[12..20], &[162, 1, 1, 0, 7, 5, 0, 0]);
+        assert_eq!(dfd[20], 16);
+    }
+}
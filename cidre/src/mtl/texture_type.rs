@@ -0,0 +1,35 @@
+/// Describes the dimensionality of each image, and if multiple images are arranged into an array or cube.
+#[doc(alias = "MTLTextureType")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd)]
+#[repr(usize)]
+pub enum Type {
+    #[doc(alias = "MTLTextureType1D")]
+    _1d = 0,
+
+    #[doc(alias = "MTLTextureType1DArray")]
+    _1dArray = 1,
+
+    #[doc(alias = "MTLTextureType2D")]
+    _2d = 2,
+
+    #[doc(alias = "MTLTextureType2DArray")]
+    _2dArray = 3,
+
+    #[doc(alias = "MTLTextureType2DMultisample")]
+    _2dMultisample = 4,
+
+    #[doc(alias = "MTLTextureTypeCube")]
+    Cube = 5,
+
+    #[doc(alias = "MTLTextureTypeCubeArray")]
+    CubeArray = 6,
+
+    #[doc(alias = "MTLTextureType3D")]
+    _3d = 7,
+
+    #[doc(alias = "MTLTextureType2DMultisampleArray")]
+    _2dMultisampleArray = 8,
+
+    #[doc(alias = "MTLTextureTypeTextureBuffer")]
+    TextureBuffer = 9,
+}
@@ -1,11 +1,9 @@
-use crate::ns;
-
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 #[repr(C)]
 pub struct Size {
-    pub width: ns::UInteger,
-    pub height: ns::UInteger,
-    pub depth: ns::UInteger,
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
 }
 
 impl Size {
@@ -208,6 +208,8 @@ pub use operation::OpQueue;
 
 mod coder;
 pub use coder::Coder;
+pub use coder::Coding;
+pub use coder::CodingImpl;
 pub use coder::DecodingFailurePolicy;
 
 mod locale;
@@ -24,7 +24,26 @@ define_obj_type!(
     pub Coder(ns::Id), NS_CODER
 );
 
-impl Coder {}
+impl Coder {
+    #[objc::msg_send(encodeObject:forKey:)]
+    pub fn encode_obj_for_key(&mut self, obj: Option<&ns::Id>, key: &ns::String);
+
+    #[objc::msg_send(encodeBool:forKey:)]
+    pub fn encode_bool_for_key(&mut self, val: bool, key: &ns::String);
+
+    #[objc::msg_send(encodeInt64:forKey:)]
+    pub fn encode_i64_for_key(&mut self, val: i64, key: &ns::String);
+
+    #[objc::msg_send(encodeDouble:forKey:)]
+    pub fn encode_f64_for_key(&mut self, val: f64, key: &ns::String);
+}
+
+/// Archiving side of `NSCoding`, enough for objects written with [`ns::KeyedArchiver`].
+#[objc::protocol(NSCoding)]
+pub trait Coding: objc::Obj {
+    #[objc::msg_send(encodeWithCoder:)]
+    fn encode_with_coder(&self, coder: &mut ns::Coder);
+}
 
 #[link(name = "ns", kind = "static")]
 extern "C" {
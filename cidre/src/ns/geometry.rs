@@ -6,5 +6,6 @@ pub type Point = cg::Point;
 #[doc(alias = "NSSize")]
 pub type Size = cg::Size;
 
+/// Same type as [`cg::Rect`], so all of its geometry applies.
 #[doc(alias = "NSRect")]
 pub type Rect = cg::Rect;
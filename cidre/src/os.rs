@@ -40,6 +40,21 @@ impl Error {
     pub const fn status(self) -> Status {
         Status(self.0.get())
     }
+
+    /// Symbol, framework and description of the error if cidre defines it.
+    ///
+    /// ```
+    /// use cidre::cat;
+    ///
+    /// let desc = cat::audio::err::BAD_FILE_PATH.describe().unwrap();
+    /// assert_eq!(desc.symbol, "kAudio_BadFilePathError");
+    /// assert_eq!(desc.framework, "CoreAudioTypes");
+    /// ```
+    #[cfg(feature = "err_desc")]
+    #[inline]
+    pub fn describe(&self) -> Option<&'static err_desc::Desc> {
+        err_desc::find(self.0.get())
+    }
 }
 
 impl From<Error> for Status {
@@ -48,32 +63,54 @@ impl From<Error> for Status {
     }
 }
 
+fn debug_code(name: &str, val: i32, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut fcc = val.to_be_bytes();
+    let mut s = f.debug_struct(name);
+    s.field("raw", &val).field("fcc", &four_cc_to_str(&mut fcc));
+
+    #[cfg(feature = "err_desc")]
+    if let Some(desc) = err_desc::find(val) {
+        return s
+            .field("symbol", &desc.symbol)
+            .field("framework", &desc.framework)
+            .field("desc", &desc.desc)
+            .finish();
+    }
+
+    s.field("help", &format!("https://www.osstatus.com?search={}", val))
+        .finish()
+}
+
 impl std::fmt::Debug for Status {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let val = self.0;
-        let mut fcc = val.to_be_bytes();
-        f.debug_struct("os::Status")
-            .field("raw", &val)
-            .field("fcc", &four_cc_to_str(&mut fcc))
-            .field("help", &format!("https://www.osstatus.com?search={}", val))
-            .finish()
+        debug_code("os::Status", self.0, f)
     }
 }
 
 impl std::fmt::Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let val = self.0.get();
-        let mut fcc = val.to_be_bytes();
-        f.debug_struct("os::Error")
-            .field("raw", &val)
-            .field("fcc", &four_cc_to_str(&mut fcc))
-            .field("help", &format!("https://www.osstatus.com?search={}", val))
-            .finish()
+        debug_code("os::Error", self.0.get(), f)
     }
 }
 
 impl std::fmt::Display for Error {
+    /// Renders `symbol (framework): description` for known codes.
+    ///
+    /// ```
+    /// use cidre::os;
+    ///
+    /// // at::audio::file_err::UNSUPPORTED_FILE_TYPE
+    /// # #[cfg(feature = "err_desc")]
+    /// assert_eq!(
+    ///     os::Error::from_be_bytes(*b"typ?").to_string(),
+    ///     "kAudioFileUnsupportedFileTypeError (AudioToolbox): The file type is not supported."
+    /// );
+    /// ```
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[cfg(feature = "err_desc")]
+        if let Some(desc) = self.describe() {
+            return std::fmt::Display::fmt(desc, f);
+        }
         std::fmt::Debug::fmt(&self, f)
     }
 }
@@ -82,6 +119,7 @@ impl std::error::Error for Error {}
 
 pub type Result<Ok = ()> = std::result::Result<Ok, Error>;
 
+#[cfg_attr(not(target_vendor = "apple"), allow(dead_code))]
 #[inline]
 pub(crate) unsafe fn result_unchecked<T, R>(op: impl FnOnce(&mut Option<T>) -> R) -> Result<T>
 where
@@ -92,6 +130,7 @@ where
     Ok(unsafe { option.unwrap_unchecked() })
 }
 
+#[cfg_attr(not(target_vendor = "apple"), allow(dead_code))]
 #[inline]
 pub(crate) fn result_init<T, R>(op: impl FnOnce(*mut T) -> R) -> Result<T>
 where
@@ -106,6 +145,9 @@ pub type Type = FourCharCode;
 
 pub mod lock;
 
+#[cfg(feature = "err_desc")]
+pub mod err_desc;
+
 impl PartialEq<i32> for Status {
     fn eq(&self, other: &i32) -> bool {
         self.0.eq(other)
@@ -134,6 +176,9 @@ impl Status {
         !self.is_ok()
     }
 
+    /// # Safety
+    ///
+    /// `option` must be `Some` when `self` is ok.
     #[inline]
     pub unsafe fn to_result_unchecked<T>(self, option: Option<T>) -> Result<T> {
         if self.is_ok() {
@@ -162,6 +207,12 @@ impl Status {
         let code = NonZeroI32::new(self.0)?;
         Some(Error(code))
     }
+
+    #[cfg(feature = "err_desc")]
+    #[inline]
+    pub fn describe(&self) -> Option<&'static err_desc::Desc> {
+        err_desc::find(self.0)
+    }
 }
 
 impl From<Status> for Result {
@@ -0,0 +1,862 @@
+//! Symbolic names for the error codes defined across cidre.
+//!
+//! The same code can be defined by several frameworks (`-50` is both `kAudio_ParamError`
+//! and `errSecParam`). [`find`] returns the first registered one, [`find_all`] returns all of them.
+
+/// Symbol, owning framework and a one-line description of an error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Desc {
+    pub code: i32,
+    pub symbol: &'static str,
+    pub framework: &'static str,
+    pub desc: &'static str,
+}
+
+impl std::fmt::Display for Desc {
+    /// ```
+    /// use cidre::os;
+    ///
+    /// // at::audio::file_err::UNSUPPORTED_FILE_TYPE
+    /// let desc = os::Error::from_be_bytes(*b"typ?").describe().unwrap();
+    /// assert_eq!(
+    ///     desc.to_string(),
+    ///     "kAudioFileUnsupportedFileTypeError (AudioToolbox): The file type is not supported."
+    /// );
+    /// ```
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({}): {}", self.symbol, self.framework, self.desc)
+    }
+}
+
+/// Returns the first registered description for the code.
+///
+/// ```
+/// use cidre::os;
+///
+/// let desc = os::err_desc::find(-12909).unwrap();
+/// assert_eq!(desc.symbol, "kVTVideoDecoderBadDataErr");
+/// assert_eq!(desc.framework, "VideoToolbox");
+/// assert!(os::err_desc::find(1).is_none());
+/// ```
+pub fn find(code: i32) -> Option<&'static Desc> {
+    ALL.iter().find(|d| d.code == code)
+}
+
+/// Returns all registered descriptions for the code.
+pub fn find_all(code: i32) -> impl Iterator<Item = &'static Desc> {
+    ALL.iter().filter(move |d| d.code == code)
+}
+
+const fn fcc(bytes: &[u8; 4]) -> i32 {
+    i32::from_be_bytes(*bytes)
+}
+
+macro_rules! descs {
+    ($($framework:literal { $($code:expr, $symbol:literal, $desc:literal;)* })*) => {
+        &[$($(Desc {
+            code: $code,
+            symbol: $symbol,
+            framework: $framework,
+            desc: $desc,
+        },)*)*]
+    };
+}
+
+/// Every error code cidre defines.
+#[rustfmt::skip]
+pub static ALL: &[Desc] = descs! {
+    "CoreAudioTypes" {
+        -4, "kAudio_UnimplementedError", "Unimplemented core routine.";
+        -43, "kAudio_FileNotFoundError", "File not found.";
+        -54, "kAudio_FilePermissionError", "File cannot be opened due to either file, directory, or sandbox permissions.";
+        -42, "kAudio_TooManyFilesOpenError", "File cannot be opened because too many files are already open.";
+        fcc(b"!pth"), "kAudio_BadFilePathError", "File cannot be opened because the specified path is malformed.";
+        -50, "kAudio_ParamError", "Error in user parameter list.";
+        -108, "kAudio_MemFullError", "Not enough room in heap zone.";
+    }
+
+    "AudioToolbox" {
+        fcc(b"wht?"), "kAudioFileUnspecifiedError", "An unspecified error has occurred.";
+        fcc(b"typ?"), "kAudioFileUnsupportedFileTypeError", "The file type is not supported.";
+        fcc(b"fmt?"), "kAudioFileUnsupportedDataFormatError", "The data format is not supported by this file type.";
+        fcc(b"pty?"), "kAudioFileUnsupportedPropertyError", "The property is not supported.";
+        fcc(b"!siz"), "kAudioFileBadPropertySizeError", "The size of the property data was not correct.";
+        fcc(b"!prm"), "kAudioFilePermissionsError", "The operation violated the file permissions.";
+        fcc(b"optm"), "kAudioFileNotOptimizedError", "The chunks following the audio data chunk are preventing the extension of the audio data chunk.";
+        fcc(b"chk?"), "kAudioFileInvalidChunkError", "Either the chunk does not exist in the file or it is not supported by the file.";
+        fcc(b"off?"), "kAudioFileDoesNotAllow64BitDataSizeError", "The file offset was too large for the file type.";
+        fcc(b"pck?"), "kAudioFileInvalidPacketOffsetError", "A packet offset was past the end of the file, or not at the end of the file when a VBR format was written, or a corrupt packet size was read when the packet table was built.";
+        fcc(b"dep?"), "kAudioFileInvalidPacketDependencyError", "Either the packet dependency info that's necessary for the audio format has not been provided, or the provided packet dependency info indicates dependency on a packet that's unavailable.";
+        fcc(b"dta?"), "kAudioFileInvalidFileError", "The file is malformed, or otherwise not a valid instance of an audio file of its type.";
+        fcc(b"op??"), "kAudioFileOperationNotSupportedError", "The operation cannot be performed.";
+        -38, "kAudioFileNotOpenError", "The file is closed.";
+        -39, "kAudioFileEndOfFileError", "End of file.";
+        -40, "kAudioFilePositionError", "Invalid file position.";
+        -43, "kAudioFileFileNotFoundError", "File not found.";
+        -66687, "kAudioQueueErr_InvalidBuffer", "The specified buffer does not belong to the audio queue.";
+        -66686, "kAudioQueueErr_BufferEmpty", "The buffer is empty (that is, the mAudioDataByteSize field = 0).";
+        -66685, "kAudioQueueErr_DisposalPending", "The function cannot act on the audio queue because it is being asynchronously disposed of.";
+        -66684, "kAudioQueueErr_InvalidProperty", "The specified property ID is invalid.";
+        -66683, "kAudioQueueErr_InvalidPropertySize", "The size of the specified property is invalid.";
+        -66682, "kAudioQueueErr_InvalidParameter", "The specified parameter ID is invalid.";
+        -66681, "kAudioQueueErr_CannotStart", "The audio queue has encountered a problem and cannot start.";
+        -66680, "kAudioQueueErr_InvalidDevice", "The device assigned to the queue could not be located, or is not properly configured.";
+        -66679, "kAudioQueueErr_BufferInQueue", "The buffer cannot be disposed of when it is enqueued.";
+        -66678, "kAudioQueueErr_InvalidRunState", "The queue is running but the function can only operate on the queue when it is stopped, or vice versa.";
+        -66677, "kAudioQueueErr_InvalidQueueType", "The queue is an input queue but the function can only operate on an output queue, or vice versa.";
+        -66676, "kAudioQueueErr_Permissions", "You do not have the required permissions to call the function.";
+        -66675, "kAudioQueueErr_InvalidPropertyValue", "The specified property value is invalid.";
+        -66674, "kAudioQueueErr_PrimeTimedOut", "During Prime, the queue's AudioConverter failed to convert the requested number of sample frames.";
+        -66673, "kAudioQueueErr_CodecNotFound", "The required audio codec was not found.";
+        -66672, "kAudioQueueErr_InvalidCodecAccess", "Access to the required codec is not permitted.";
+        -66671, "kAudioQueueErr_QueueInvalidated", "The audio server has exited, causing the audio queue to become invalid.";
+        -66670, "kAudioQueueErr_TooManyTaps", "There can only be one processing tap per audio queue.";
+        -66669, "kAudioQueueErr_InvalidTapContext", "GetSourceAudio can only be called from the tap's callback.";
+        -66668, "kAudioQueueErr_RecordUnderrun", "During recording, data was lost because there was no enqueued buffer into which to store it.";
+        -66667, "kAudioQueueErr_InvalidTapType", "The tap type is not supported for this queue.";
+        -66666, "kAudioQueueErr_BufferEnqueuedTwice", "A buffer was enqueued a second time before it was returned to the client.";
+        -66665, "kAudioQueueErr_CannotStartYet", "The audio queue cannot start because of an audio session interruption.";
+        -66632, "kAudioQueueErr_EnqueueDuringReset", "A buffer was enqueued during a reset, stop, or dispose operation.";
+        -66626, "kAudioQueueErr_InvalidOfflineMode", "The operation requires the queue to be in offline mode but it is not, or vice versa.";
+        fcc(b"fmt?"), "kAudioConverterErr_FormatNotSupported", "The data format is not supported.";
+        fcc(b"op??"), "kAudioConverterErr_OperationNotSupported", "The operation is not supported.";
+        fcc(b"prop"), "kAudioConverterErr_PropertyNotSupported", "The property is not supported.";
+        fcc(b"insz"), "kAudioConverterErr_InvalidInputSize", "The input size is invalid.";
+        fcc(b"otsz"), "kAudioConverterErr_InvalidOutputSize", "The output size is invalid.";
+        fcc(b"what"), "kAudioConverterErr_UnspecifiedError", "Unspecified error, e.g. byte size is not a multiple of the frame size.";
+        fcc(b"!siz"), "kAudioConverterErr_BadPropertySizeError", "The property data size is wrong.";
+        fcc(b"!pkd"), "kAudioConverterErr_RequiresPacketDescriptionsError", "The input data requires packet descriptions.";
+        fcc(b"!isr"), "kAudioConverterErr_InputSampleRateOutOfRange", "The input sample rate is out of range.";
+        fcc(b"!osr"), "kAudioConverterErr_OutputSampleRateOutOfRange", "The output sample rate is out of range.";
+        fcc(b"hwiu"), "kAudioConverterErr_HardwareInUse", "The hardware codec became unavailable, probably due to an interruption.";
+        fcc(b"perm"), "kAudioConverterErr_NoHardwarePermission", "The application does not have permission to use the hardware codec.";
+        -10879, "kAudioUnitErr_InvalidProperty", "The property is not supported.";
+        -10878, "kAudioUnitErr_InvalidParameter", "The parameter is not supported.";
+        -10877, "kAudioUnitErr_InvalidElement", "The specified element is not valid.";
+        -10876, "kAudioUnitErr_NoConnection", "There is no connection (generally an audio unit is asked to render but it has not input from which to gather data).";
+        -10875, "kAudioUnitErr_FailedInitialization", "The audio unit is unable to be initialized.";
+        -10874, "kAudioUnitErr_TooManyFramesToProcess", "When an audio unit is initialized it has a value which specifies the max number of frames it will be asked to render at any given time.";
+        -10871, "kAudioUnitErr_InvalidFile", "If an audio unit uses external files as a data source, this error is returned if a file is invalid (Apple's DLS synth returns this error).";
+        -10870, "kAudioUnitErr_UnknownFileType", "If an audio unit uses external files as a data source, this error is returned if a file is invalid (Apple's DLS synth returns this error).";
+        -10869, "kAudioUnitErr_FileNotSpecified", "If an audio unit uses external files as a data source, this error is returned if a file hasn't been set on it (Apple's DLS synth returns this error).";
+        -10868, "kAudioUnitErr_FormatNotSupported", "Returned if an input or output format is not supported.";
+        -10867, "kAudioUnitErr_Uninitialized", "Returned if an operation requires an audio unit to be initialized and it is not.";
+        -10866, "kAudioUnitErr_InvalidScope", "The specified scope is invalid.";
+        -10865, "kAudioUnitErr_PropertyNotWritable", "The property cannot be written.";
+        -10863, "kAudioUnitErr_CannotDoInCurrentContext", "Returned when an audio unit is in a state where it can't perform the requested action now - but it could later.";
+        -10851, "kAudioUnitErr_InvalidPropertyValue", "The property is valid, but the value of the property being provided is not.";
+        -10850, "kAudioUnitErr_PropertyNotInUse", "Returned when a property is valid, but it hasn't been set to a valid value at this time.";
+        -10849, "kAudioUnitErr_Initialized", "Indicates the operation cannot be performed because the audio unit is initialized.";
+        -10848, "kAudioUnitErr_InvalidOfflineRender", "Used to indicate that the offline render operation is invalid.";
+        -10847, "kAudioUnitErr_Unauthorized", "Returned by either Open or Initialize, this error is used to indicate that the audio unit is not authorised, that it cannot be used.";
+        -66753, "kAudioUnitErr_MIDIOutputBufferFull", "Returned during the render call, if the audio unit produces more MIDI output, than the default allocated buffer.";
+        -66745, "kAudioUnitErr_RenderTimeout", "The audio unit did not satisfy the render request in time.";
+        -66744, "kAudioUnitErr_ExtensionNotFound", "The specified identifier did not match any Audio Unit Extensions.";
+        -66743, "kAudioUnitErr_InvalidParameterValue", "The parameter value is not supported, e.g.";
+        -66742, "kAudioUnitErr_InvalidFilePath", "The file path that was passed is not supported.";
+        -66741, "kAudioUnitErr_MissingKey", "A required key is missing from a dictionary object.";
+        -66740, "kAudioUnitErr_ComponentManagerNotSupported", "The operation can not be performed for a component instance instantiated using the deprecated Component Manager.";
+        -66635, "kAudioUnitErr_MultipleVoiceProcessors", "On some platforms, this error is returned when a client attempts to initialize a voice processor instance while another is initialized.";
+        -66754, "kAudioComponentErr_InstanceTimedOut", "The audio component instance timed out.";
+        -66749, "kAudioComponentErr_InstanceInvalidated", "The audio component instance was invalidated.";
+        -66752, "kAudioComponentErr_DuplicateDescription", "A non-unique component description was provided to AudioOutputUnitPublish.";
+        -66751, "kAudioComponentErr_UnsupportedType", "An unsupported component type was provided to AudioOutputUnitPublish.";
+        -66750, "kAudioComponentErr_TooManyInstances", "Components published via AudioOutputUnitPublish may only have one instance.";
+        -66748, "kAudioComponentErr_NotPermitted", "App needs \"inter-app-audio\" entitlement or host app needs \"audio\" in its UIBackgroundModes.";
+        -66747, "kAudioComponentErr_InitializationTimedOut", "Host did not render in a timely manner; must uninitialize and reinitialize.";
+        -66746, "kAudioComponentErr_InvalidFormat", "Inter-app AU element formats must have sample rates matching the hardware.";
+    }
+
+    "CoreMedia" {
+        -12730, "kCMSampleBufferError_AllocationFailed", "An allocation failed.";
+        -12731, "kCMSampleBufferError_RequiredParameterMissing", "NULL or 0 was passed for a required parameter.";
+        -12732, "kCMSampleBufferError_AlreadyHasDataBuffer", "Attempt was made to set a dataBuffer on a cm::SampleBuffer that already has one.";
+        -12733, "kCMSampleBufferError_BufferNotReady", "Buffer could not be made ready.";
+        -12734, "kCMSampleBufferError_SampleIndexOutOfRange", "Sample index was not between 0 and numSamples-1, inclusive.";
+        -12735, "kCMSampleBufferError_BufferHasNoSampleSizes", "Attempt to get sample size information when there was none.";
+        -12736, "kCMSampleBufferError_BufferHasNoSampleTimingInfo", "Attempt to get sample timing information when there was none.";
+        -12737, "kCMSampleBufferError_ArrayTooSmall", "Output array was not large enough for the array being requested.";
+        -12738, "kCMSampleBufferError_InvalidEntryCount", "Timing info or size array entry count was not 0, 1, or numSamples.";
+        -12739, "kCMSampleBufferError_CannotSubdivide", "Sample buffer does not contain sample sizes.";
+        -12740, "kCMSampleBufferError_SampleTimingInfoInvalid", "Buffer unexpectedly contains a non-numeric sample timing info.";
+        -12741, "kCMSampleBufferError_InvalidMediaTypeForOperation", "The media type specified by a format description is not valid for the given operation (eg.";
+        -12742, "kCMSampleBufferError_InvalidSampleData", "Buffer contains bad data.";
+        -12743, "kCMSampleBufferError_InvalidMediaFormat", "The format of the given media does not match the given format description (eg.";
+        -12744, "kCMSampleBufferError_Invalidated", "The sample buffer was invalidated.";
+        -16750, "kCMSampleBufferError_DataFailed", "The sample buffer's data loading operation failed (generic error).";
+        -16751, "kCMSampleBufferError_DataCanceled", "The sample buffer's data loading operation was canceled.";
+        -12700, "kCMBlockBufferStructureAllocationFailedErr", "Returned when a cm::BlockBuffer-creating API gets a failure from the cf::Allocator provided for cm::BlockBuffer construction.";
+        -12701, "kCMBlockBufferBlockAllocationFailedErr", "Returned when the allocator provided to allocate a memory block (as distinct from cm::BlockBuffer structures) fails.";
+        -12702, "kCMBlockBufferBadCustomBlockSourceErr", "The custom block source’s Allocate() routine was NULL when an allocation was attempted.";
+        -12703, "kCMBlockBufferBadOffsetParameterErr", "The offset provided to an API is out of the range of the relevent cm::BlockBuffer.";
+        -12704, "kCMBlockBufferBadLengthParameterErr", "The length provided to an API is out of the range of the relevent cm::BlockBuffer, or is not allowed to be zero.";
+        -12705, "kCMBlockBufferBadPointerParameterErr", "A pointer parameter (e.g.";
+        -12706, "kCMBlockBufferEmptyBBufErr", "Expected a non-empty cm::BlockBuffer.";
+        -12707, "kCMBlockBufferUnallocatedBlockErr", "An unallocated memory block was encountered.";
+        -12708, "kCMBlockBufferInsufficientSpaceErr", "The requested operation needs more space than is available.";
+        -12770, "kCMSimpleQueueError_AllocationFailed", "An allocation failed.";
+        -12771, "kCMSimpleQueueError_RequiredParameterMissing", "NULL or 0 was passed for a required parameter.";
+        -12772, "kCMSimpleQueueError_ParameterOutOfRange", "An out-of-range value was passed for a parameter with a restricted valid range.";
+        -12773, "kCMSimpleQueueError_QueueIsFull", "Operation failed because queue was full.";
+        -12712, "kCMFormatDescriptionBridgeError_InvalidParameter", "Invalid parameter.";
+        -12713, "kCMFormatDescriptionBridgeError_AllocationFailed", "Returned when an allocation fails.";
+        -12714, "kCMFormatDescriptionBridgeError_InvalidSerializedSampleDescription", "Returned when the sample description is invalid (e.g.";
+        -12715, "kCMFormatDescriptionBridgeError_InvalidFormatDescription", "Returned when the format description is invalid (e.g.";
+        -12716, "kCMFormatDescriptionBridgeError_IncompatibleFormatDescription", "Returned when the format description has an incompatible format (e.g.";
+        -12717, "kCMFormatDescriptionBridgeError_UnsupportedSampleDescriptionFlavor", "Returned when the sample description is unsupported for the specified format flavor.";
+        -12719, "kCMFormatDescriptionBridgeError_InvalidSlice", "Returned when the slice has an invalid value.";
+        -12745, "kCMClockError_MissingRequiredParameter", "A required parameter is missing.";
+        -12746, "kCMClockError_InvalidParameter", "A parameter is invalid.";
+        -12747, "kCMClockError_AllocationFailed", "An allocation failed.";
+        -12756, "kCMClockError_UnsupportedOperation", "The operation is not supported by the clock.";
+        -12748, "kCMTimebaseError_MissingRequiredParameter", "A required parameter is missing.";
+        -12749, "kCMTimebaseError_InvalidParameter", "A parameter is invalid.";
+        -12750, "kCMTimebaseError_AllocationFailed", "An allocation failed.";
+        -12751, "kCMTimebaseError_TimerIntervalTooShort", "The timer interval is too short.";
+        -12757, "kCMTimebaseError_ReadOnly", "The timebase is read only.";
+        -12752, "kCMSyncError_MissingRequiredParameter", "A required parameter is missing.";
+        -12753, "kCMSyncError_InvalidParameter", "A parameter is invalid.";
+        -12754, "kCMSyncError_AllocationFailed", "An allocation failed.";
+        -12755, "kCMSyncError_RateMustBeNonZero", "The rate must be non-zero.";
+    }
+
+    "VideoToolbox" {
+        -12900, "kVTPropertyNotSupportedErr", "The property is not supported by the session.";
+        -12901, "kVTPropertyReadOnlyErr", "The property is read only.";
+        -12902, "kVTParameterErr", "A parameter is invalid.";
+        -12903, "kVTInvalidSessionErr", "The session is invalid.";
+        -12904, "kVTAllocationFailedErr", "A memory allocation failed.";
+        -12905, "kVTPixelTransferNotSupportedErr", "The pixel transfer between the given formats is not supported.";
+        -12906, "kVTCouldNotFindVideoDecoderErr", "No video decoder was found for the format.";
+        -12907, "kVTCouldNotCreateInstanceErr", "The codec instance could not be created.";
+        -12908, "kVTCouldNotFindVideoEncoderErr", "No video encoder was found for the codec type.";
+        -12909, "kVTVideoDecoderBadDataErr", "The video decoder encountered bad data.";
+        -12910, "kVTVideoDecoderUnsupportedDataFormatErr", "The video decoder does not support the data format.";
+        -12911, "kVTVideoDecoderMalfunctionErr", "The video decoder malfunctioned.";
+        -12912, "kVTVideoEncoderMalfunctionErr", "The video encoder malfunctioned.";
+        -12913, "kVTVideoDecoderNotAvailableNowErr", "The video decoder is not available at this time.";
+        -12914, "kVTImageRotationNotSupportedErr", "The requested image rotation is not supported.";
+        -12915, "kVTVideoEncoderNotAvailableNowErr", "The video encoder is not available at this time.";
+        -12916, "kVTFormatDescriptionChangeNotSupportedErr", "The decoder does not support a change of format description.";
+        -12917, "kVTInsufficientSourceColorDataErr", "The source buffer lacks the color attachments needed for conversion.";
+        -12918, "kVTCouldNotCreateColorCorrectionDataErr", "Color correction data could not be created.";
+        -12919, "kVTColorSyncTransformConvertFailedErr", "A ColorSync transform failed to convert.";
+        -12210, "kVTVideoDecoderAuthorizationErr", "The video decoder is not authorized for this content.";
+        -12211, "kVTVideoEncoderAuthorizationErr", "The video encoder is not authorized for this content.";
+        -12212, "kVTColorCorrectionPixelTransferFailedErr", "The pixel transfer for color correction failed.";
+        -12213, "kVTMultiPassStorageIdentifierMismatchErr", "The multi-pass storage identifier does not match the session.";
+        -12214, "kVTMultiPassStorageInvalidErr", "The multi-pass storage is invalid.";
+        -12215, "kVTFrameSiloInvalidTimeStampErr", "The frame silo received a time stamp outside the expected range.";
+        -12216, "kVTFrameSiloInvalidTimeRangeErr", "The frame silo time range is invalid.";
+        -12217, "kVTCouldNotFindTemporalFilterErr", "No temporal filter was found.";
+        -12218, "kVTPixelTransferNotPermittedErr", "The pixel transfer is not permitted.";
+        -12219, "kVTColorCorrectionImageRotationFailedErr", "Image rotation during color correction failed.";
+        -17690, "kVTVideoDecoderRemovedErr", "The video decoder was removed.";
+        -17691, "kVTSessionMalfunctionErr", "The session malfunctioned.";
+        -17692, "kVTVideoDecoderNeedsRosettaErr", "The video decoder requires Rosetta.";
+        -17693, "kVTVideoEncoderNeedsRosettaErr", "The video encoder requires Rosetta.";
+        -17694, "kVTVideoDecoderReferenceMissingErr", "A reference frame needed by the video decoder is missing.";
+        -17695, "kVTVideoDecoderCallbackMessagingErr", "The video decoder failed to message its callback.";
+    }
+
+    "CoreVideo" {
+        -6660, "kCVReturnFirst", "Placeholder to mark the beginning of the range of cv::Return codes.";
+        -6661, "kCVReturnInvalidArgument", "At least one of the arguments passed in is not valid.";
+        -6662, "kCVReturnAllocationFailed", "The allocation for a buffer or buffer pool failed.";
+        -6663, "kCVReturnUnsupported", "The operation or feature is not supported.";
+        -6670, "kCVReturnInvalidDisplay", "The display specified is invalid.";
+        -6671, "kCVReturnDisplayLinkAlreadyRunning", "The display link is already running.";
+        -6672, "kCVReturnDisplayLinkNotRunning", "The display link is not running.";
+        -6673, "kCVReturnDisplayLinkCallbacksNotSet", "The output callback is not set.";
+        -6680, "kCVReturnInvalidPixelFormat", "The requested pixel format is not supported.";
+        -6681, "kCVReturnInvalidSize", "The requested size (most likely too big) is not supported.";
+        -6682, "kCVReturnInvalidPixelBufferAttributes", "A cv::PixelBuf cannot be created with the requested attributes.";
+        -6683, "kCVReturnPixelBufferNotOpenGLCompatible", "The pixel buffer is not compatible with OpenGL due to an unsupported buffer size, pixel format, or attribute.";
+        -6684, "kCVReturnPixelBufferNotMetalCompatible", "The pixel buffer is not compatible with Metal due to an unsupported buffer size, pixel format, or attribute.";
+        -6689, "kCVReturnWouldExceedAllocationThreshold", "Allocation for a pixel buffer failed because the threshold value set in the pool attributes would be surpassed.";
+        -6690, "kCVReturnPoolAllocationFailed", "The allocation for the buffer pool failed.";
+        -6691, "kCVReturnInvalidPoolAttributes", "A cv::PixelBufPool cannot be created with the requested attributes.";
+        -6692, "kCVReturnRetry", "A scan has not completed.";
+        -6699, "kCVReturnLast", "Placeholder to mark the end of the range of cv::Return codes.";
+    }
+
+    "ImageIO" {
+        -22140, "kCGImageAnimationStatus_ParameterError", "NULL or invalid parameter passed to API.";
+        -22141, "kCGImageAnimationStatus_CorruptInputImage", "An image cannot be read from the given source.";
+        -22142, "kCGImageAnimationStatus_UnsupportedFormat", "The image format is not applicable to animation.";
+        -22143, "kCGImageAnimationStatus_IncompleteInputImage", "An image can be read from the given source, but it is incomplete.";
+        -22144, "kCGImageAnimationStatus_AllocationFailure", "A required resource could not be created.";
+    }
+
+    "Security" {
+        -4, "errSecUnimplemented", "Function or operation not implemented.";
+        -34, "errSecDiskFull", "Disk Full error.";
+        -36, "errSecIO", "I/O error.";
+        -49, "errSecOpWr", "File already open with write permission.";
+        -50, "errSecParam", "One or more parameters passed to a function were not valid.";
+        -61, "errSecWrPerm", "Write permissions error.";
+        -108, "errSecAllocate", "Failed to allocate memory.";
+        -128, "errSecUserCanceled", "User canceled the operation.";
+        -909, "errSecBadReq", "Bad parameter or invalid state for operation.";
+        -2070, "errSecInternalComponent", "An internal component failed.";
+        -4960, "errSecCoreFoundationUnknown", "An unknown Core Foundation error occurred.";
+        -34018, "errSecMissingEntitlement", "A required entitlement isn't present.";
+        -34020, "errSecRestrictedAPI", "Client is restricted and is not permitted to perform this operation.";
+        -25291, "errSecNotAvailable", "No keychain is available.";
+        -25292, "errSecReadOnly", "This keychain cannot be modified.";
+        -25293, "errSecAuthFailed", "The user name or passphrase you entered is not correct.";
+        -25294, "errSecNoSuchKeychain", "The specified keychain could not be found.";
+        -25295, "errSecInvalidKeychain", "The specified keychain is not a valid keychain file.";
+        -25296, "errSecDuplicateKeychain", "A keychain with the same name already exists.";
+        -25297, "errSecDuplicateCallback", "The specified callback function is already installed.";
+        -25298, "errSecInvalidCallback", "The specified callback function is not valid.";
+        -25299, "errSecDuplicateItem", "The specified item already exists in the keychain.";
+        -25300, "errSecItemNotFound", "The specified item could not be found in the keychain.";
+        -25301, "errSecBufferTooSmall", "There is not enough memory available to use the specified item.";
+        -25302, "errSecDataTooLarge", "This item contains information which is too large or in a format that cannot be displayed.";
+        -25303, "errSecNoSuchAttr", "The specified attribute does not exist.";
+        -25304, "errSecInvalidItemRef", "The specified item is no longer valid.";
+        -25305, "errSecInvalidSearchRef", "Unable to search the current keychain.";
+        -25306, "errSecNoSuchClass", "The specified item does not appear to be a valid keychain item.";
+        -25307, "errSecNoDefaultKeychain", "A default keychain could not be found.";
+        -25308, "errSecInteractionNotAllowed", "User interaction is not allowed.";
+        -25309, "errSecReadOnlyAttr", "The specified attribute could not be modified.";
+        -25310, "errSecWrongSecVersion", "This keychain was created by a different version of the system software and cannot be opened.";
+        -25311, "errSecKeySizeNotAllowed", "This item specifies a key size which is too large or too small.";
+        -25312, "errSecNoStorageModule", "A required component (data storage module) could not be loaded.";
+        -25313, "errSecNoCertificateModule", "A required component (certificate module) could not be loaded.";
+        -25314, "errSecNoPolicyModule", "A required component (policy module) could not be loaded.";
+        -25315, "errSecInteractionRequired", "User interaction is required, but is currently not allowed.";
+        -25316, "errSecDataNotAvailable", "The contents of this item cannot be retrieved.";
+        -25317, "errSecDataNotModifiable", "The contents of this item cannot be modified.";
+        -25318, "errSecCreateChainFailed", "One or more certificates required to validate this certificate cannot be found.";
+        -25319, "errSecInvalidPrefsDomain", "The specified preferences domain is not valid.";
+        -25320, "errSecInDarkWake", "In dark wake, no UI possible.";
+        -25240, "errSecACLNotSimple", "The specified access control list is not in standard (simple) form.";
+        -25241, "errSecPolicyNotFound", "The specified policy cannot be found.";
+        -25242, "errSecInvalidTrustSetting", "The specified trust setting is invalid.";
+        -25243, "errSecNoAccessForItem", "The specified item has no access control.";
+        -25244, "errSecInvalidOwnerEdit", "Invalid attempt to change the owner of this item.";
+        -25245, "errSecTrustNotAvailable", "No trust results are available.";
+        -25256, "errSecUnsupportedFormat", "Import/Export format unsupported.";
+        -25257, "errSecUnknownFormat", "Unknown format in import.";
+        -25258, "errSecKeyIsSensitive", "Key material must be wrapped for export.";
+        -25259, "errSecMultiplePrivKeys", "An attempt was made to import multiple private keys.";
+        -25260, "errSecPassphraseRequired", "Passphrase is required for import/export.";
+        -25261, "errSecInvalidPasswordRef", "The password reference was invalid.";
+        -25262, "errSecInvalidTrustSettings", "The Trust Settings Record was corrupted.";
+        -25263, "errSecNoTrustSettings", "No Trust Settings were found.";
+        -25264, "errSecPkcs12VerifyFailure", "MAC verification failed during PKCS12 import (wrong password?).";
+        -26267, "errSecNotSigner", "A certificate was not signed by its proposed parent.";
+        -26275, "errSecDecode", "Unable to decode the provided data.";
+        -67585, "errSecServiceNotAvailable", "The required service is not available.";
+        -67586, "errSecInsufficientClientID", "The client ID is not correct.";
+        -67587, "errSecDeviceReset", "A device reset has occurred.";
+        -67588, "errSecDeviceFailed", "A device failure has occurred.";
+        -67589, "errSecAppleAddAppACLSubject", "Adding an application ACL subject failed.";
+        -67590, "errSecApplePublicKeyIncomplete", "The public key is incomplete.";
+        -67591, "errSecAppleSignatureMismatch", "A signature mismatch has occurred.";
+        -67592, "errSecAppleInvalidKeyStartDate", "The specified key has an invalid start date.";
+        -67593, "errSecAppleInvalidKeyEndDate", "The specified key has an invalid end date.";
+        -67594, "errSecConversionError", "A conversion error has occurred.";
+        -67595, "errSecAppleSSLv2Rollback", "A SSLv2 rollback error has occurred.";
+        -67596, "errSecQuotaExceeded", "The quota was exceeded.";
+        -67597, "errSecFileTooBig", "The file is too big.";
+        -67598, "errSecInvalidDatabaseBlob", "The specified database has an invalid blob.";
+        -67599, "errSecInvalidKeyBlob", "The specified database has an invalid key blob.";
+        -67600, "errSecIncompatibleDatabaseBlob", "The specified database has an incompatible blob.";
+        -67601, "errSecIncompatibleKeyBlob", "The specified database has an incompatible key blob.";
+        -67602, "errSecHostNameMismatch", "A host name mismatch has occurred.";
+        -67603, "errSecUnknownCriticalExtensionFlag", "There is an unknown critical extension flag.";
+        -67604, "errSecNoBasicConstraints", "No basic constraints were found.";
+        -67605, "errSecNoBasicConstraintsCA", "No basic CA constraints were found.";
+        -67606, "errSecInvalidAuthorityKeyID", "The authority key ID is not valid.";
+        -67607, "errSecInvalidSubjectKeyID", "The subject key ID is not valid.";
+        -67608, "errSecInvalidKeyUsageForPolicy", "The key usage is not valid for the specified policy.";
+        -67609, "errSecInvalidExtendedKeyUsage", "The extended key usage is not valid.";
+        -67610, "errSecInvalidIDLinkage", "The ID linkage is not valid.";
+        -67611, "errSecPathLengthConstraintExceeded", "The path length constraint was exceeded.";
+        -67612, "errSecInvalidRoot", "The root or anchor certificate is not valid.";
+        -67613, "errSecCRLExpired", "The CRL has expired.";
+        -67614, "errSecCRLNotValidYet", "The CRL is not yet valid.";
+        -67615, "errSecCRLNotFound", "The CRL was not found.";
+        -67616, "errSecCRLServerDown", "The CRL server is down.";
+        -67617, "errSecCRLBadURI", "The CRL has a bad Uniform Resource Identifier.";
+        -67618, "errSecUnknownCertExtension", "An unknown certificate extension was encountered.";
+        -67619, "errSecUnknownCRLExtension", "An unknown CRL extension was encountered.";
+        -67620, "errSecCRLNotTrusted", "The CRL is not trusted.";
+        -67621, "errSecCRLPolicyFailed", "The CRL policy failed.";
+        -67622, "errSecIDPFailure", "The issuing distribution point was not valid.";
+        -67623, "errSecSMIMEEmailAddressesNotFound", "An email address mismatch was encountered.";
+        -67624, "errSecSMIMEBadExtendedKeyUsage", "The appropriate extended key usage for SMIME was not found.";
+        -67625, "errSecSMIMEBadKeyUsage", "The key usage is not compatible with SMIME.";
+        -67626, "errSecSMIMEKeyUsageNotCritical", "The key usage extension is not marked as critical.";
+        -67627, "errSecSMIMENoEmailAddress", "No email address was found in the certificate.";
+        -67628, "errSecSMIMESubjAltNameNotCritical", "The subject alternative name extension is not marked as critical.";
+        -67629, "errSecSSLBadExtendedKeyUsage", "The appropriate extended key usage for SSL was not found.";
+        -67630, "errSecOCSPBadResponse", "The OCSP response was incorrect or could not be parsed.";
+        -67631, "errSecOCSPBadRequest", "The OCSP request was incorrect or could not be parsed.";
+        -67632, "errSecOCSPUnavailable", "OCSP service is unavailable.";
+        -67633, "errSecOCSPStatusUnrecognized", "The OCSP server did not recognize this certificate.";
+        -67634, "errSecEndOfData", "An end-of-data was detected.";
+        -67635, "errSecIncompleteCertRevocationCheck", "An incomplete certificate revocation check occurred.";
+        -67636, "errSecNetworkFailure", "A network failure occurred.";
+        -67637, "errSecOCSPNotTrustedToAnchor", "The OCSP response was not trusted to a root or anchor certificate.";
+        -67638, "errSecRecordModified", "The record was modified.";
+        -67639, "errSecOCSPSignatureError", "The OCSP response had an invalid signature.";
+        -67640, "errSecOCSPNoSigner", "The OCSP response had no signer.";
+        -67641, "errSecOCSPResponderMalformedReq", "The OCSP responder was given a malformed request.";
+        -67642, "errSecOCSPResponderInternalError", "The OCSP responder encountered an internal error.";
+        -67643, "errSecOCSPResponderTryLater", "The OCSP responder is busy, try again later.";
+        -67644, "errSecOCSPResponderSignatureRequired", "The OCSP responder requires a signature.";
+        -67645, "errSecOCSPResponderUnauthorized", "The OCSP responder rejected this request as unauthorized.";
+        -67646, "errSecOCSPResponseNonceMismatch", "The OCSP response nonce did not match the request.";
+        -67647, "errSecCodeSigningBadCertChainLength", "Code signing encountered an incorrect certificate chain length.";
+        -67648, "errSecCodeSigningNoBasicConstraints", "Code signing found no basic constraints.";
+        -67649, "errSecCodeSigningBadPathLengthConstraint", "Code signing encountered an incorrect path length constraint.";
+        -67650, "errSecCodeSigningNoExtendedKeyUsage", "Code signing found no extended key usage.";
+        -67651, "errSecCodeSigningDevelopment", "Code signing indicated use of a development-only certificate.";
+        -67652, "errSecResourceSignBadCertChainLength", "Resource signing has encountered an incorrect certificate chain length.";
+        -67653, "errSecResourceSignBadExtKeyUsage", "Resource signing has encountered an error in the extended key usage.";
+        -67654, "errSecTrustSettingDeny", "The trust setting for this policy was set to Deny.";
+        -67655, "errSecInvalidSubjectName", "An invalid certificate subject name was encountered.";
+        -67656, "errSecUnknownQualifiedCertStatement", "An unknown qualified certificate statement was encountered.";
+        -67657, "errSecMobileMeRequestQueued", "The MobileMe request was queued.";
+        -67658, "errSecMobileMeRequestRedirected", "The MobileMe request was redirected.";
+        -67659, "errSecMobileMeServerError", "A MobileMe server error occurred.";
+        -67660, "errSecMobileMeServerNotAvailable", "The MobileMe server is not available.";
+        -67661, "errSecMobileMeServerAlreadyExists", "The MobileMe server reported that the item already exists.";
+        -67662, "errSecMobileMeServerServiceErr", "A MobileMe service error occurred.";
+        -67663, "errSecMobileMeRequestAlreadyPending", "A MobileMe request is already pending.";
+        -67664, "errSecMobileMeNoRequestPending", "No MobileMe request is pending.";
+        -67665, "errSecMobileMeCSRVerifyFailure", "The MobileMe signature verification failed.";
+        -67666, "errSecMobileMeFailedConsistencyCheck", "The MobileMe consistency check failed.";
+        -67667, "errSecNotInitialized", "A function was called without initializing CSSM.";
+        -67668, "errSecInvalidHandleUsage", "The CSSM handle does not match with the service type.";
+        -67669, "errSecPVCReferentNotFound", "A reference to the calling module was not found in the list of authorized callers.";
+        -67670, "errSecFunctionIntegrityFail", "A function address was not within the verified module.";
+        -67671, "errSecInternalError", "An internal error has occurred.";
+        -67672, "errSecMemoryError", "A memory error has occurred.";
+        -67673, "errSecInvalidData", "Invalid data was encountered.";
+        -67674, "errSecMDSError", "A Module Directory Service error has occurred.";
+        -67675, "errSecInvalidPointer", "An invalid pointer was encountered.";
+        -67676, "errSecSelfCheckFailed", "Self-check has failed.";
+        -67677, "errSecFunctionFailed", "A function has failed.";
+        -67678, "errSecModuleManifestVerifyFailed", "A module manifest verification failure has occurred.";
+        -67679, "errSecInvalidGUID", "An invalid GUID was encountered.";
+        -67680, "errSecInvalidHandle", "An invalid handle was encountered.";
+        -67681, "errSecInvalidDBList", "An invalid DB list was encountered.";
+        -67682, "errSecInvalidPassthroughID", "An invalid passthrough ID was encountered.";
+        -67683, "errSecInvalidNetworkAddress", "An invalid network address was encountered.";
+        -67684, "errSecCRLAlreadySigned", "The certificate revocation list is already signed.";
+        -67685, "errSecInvalidNumberOfFields", "An invalid number of fields were encountered.";
+        -67686, "errSecVerificationFailure", "A verification failure occurred.";
+        -67687, "errSecUnknownTag", "An unknown tag was encountered.";
+        -67688, "errSecInvalidSignature", "An invalid signature was encountered.";
+        -67689, "errSecInvalidName", "An invalid name was encountered.";
+        -67690, "errSecInvalidCertificateRef", "An invalid certificate reference was encountered.";
+        -67691, "errSecInvalidCertificateGroup", "An invalid certificate group was encountered.";
+        -67692, "errSecTagNotFound", "The specified tag was not found.";
+        -67693, "errSecInvalidQuery", "The specified query was not valid.";
+        -67694, "errSecInvalidValue", "An invalid value was detected.";
+        -67695, "errSecCallbackFailed", "A callback has failed.";
+        -67696, "errSecACLDeleteFailed", "An ACL delete operation has failed.";
+        -67697, "errSecACLReplaceFailed", "An ACL replace operation has failed.";
+        -67698, "errSecACLAddFailed", "An ACL add operation has failed.";
+        -67699, "errSecACLChangeFailed", "An ACL change operation has failed.";
+        -67700, "errSecInvalidAccessCredentials", "The access credentials are invalid.";
+        -67701, "errSecInvalidRecord", "An invalid record was encountered.";
+        -67702, "errSecInvalidACL", "An invalid ACL was encountered.";
+        -67703, "errSecInvalidSampleValue", "An invalid sample value was encountered.";
+        -67704, "errSecIncompatibleVersion", "An incompatible version was encountered.";
+        -67705, "errSecPrivilegeNotGranted", "The privilege was not granted.";
+        -67706, "errSecInvalidScope", "An invalid scope was encountered.";
+        -67707, "errSecPVCAlreadyConfigured", "The PVC is already configured.";
+        -67708, "errSecInvalidPVC", "An invalid PVC was encountered.";
+        -67709, "errSecEMMLoadFailed", "The EMM load has failed.";
+        -67710, "errSecEMMUnloadFailed", "The EMM unload has failed.";
+        -67711, "errSecAddinLoadFailed", "The add-in load operation has failed.";
+        -67712, "errSecInvalidKeyRef", "An invalid key was encountered.";
+        -67713, "errSecInvalidKeyHierarchy", "An invalid key hierarchy was encountered.";
+        -67714, "errSecAddinUnloadFailed", "The add-in unload operation has failed.";
+        -67715, "errSecLibraryReferenceNotFound", "A library reference was not found.";
+        -67716, "errSecInvalidAddinFunctionTable", "An invalid add-in function table was encountered.";
+        -67717, "errSecInvalidServiceMask", "An invalid service mask was encountered.";
+        -67718, "errSecModuleNotLoaded", "A module was not loaded.";
+        -67719, "errSecInvalidSubServiceID", "An invalid subservice ID was encountered.";
+        -67720, "errSecAttributeNotInContext", "An attribute was not in the context.";
+        -67721, "errSecModuleManagerInitializeFailed", "A module failed to initialize.";
+        -67722, "errSecModuleManagerNotFound", "A module was not found.";
+        -67723, "errSecEventNotificationCallbackNotFound", "An event notification callback was not found.";
+        -67724, "errSecInputLengthError", "An input length error was encountered.";
+        -67725, "errSecOutputLengthError", "An output length error was encountered.";
+        -67726, "errSecPrivilegeNotSupported", "The privilege is not supported.";
+        -67727, "errSecDeviceError", "A device error was encountered.";
+        -67728, "errSecAttachHandleBusy", "The CSP handle was busy.";
+        -67729, "errSecNotLoggedIn", "You are not logged in.";
+        -67730, "errSecAlgorithmMismatch", "An algorithm mismatch was encountered.";
+        -67731, "errSecKeyUsageIncorrect", "The key usage is incorrect.";
+        -67732, "errSecKeyBlobTypeIncorrect", "The key blob type is incorrect.";
+        -67733, "errSecKeyHeaderInconsistent", "The key header is inconsistent.";
+        -67734, "errSecUnsupportedKeyFormat", "The key header format is not supported.";
+        -67735, "errSecUnsupportedKeySize", "The key size is not supported.";
+        -67736, "errSecInvalidKeyUsageMask", "The key usage mask is not valid.";
+        -67737, "errSecUnsupportedKeyUsageMask", "The key usage mask is not supported.";
+        -67738, "errSecInvalidKeyAttributeMask", "The key attribute mask is not valid.";
+        -67739, "errSecUnsupportedKeyAttributeMask", "The key attribute mask is not supported.";
+        -67740, "errSecInvalidKeyLabel", "The key label is not valid.";
+        -67741, "errSecUnsupportedKeyLabel", "The key label is not supported.";
+        -67742, "errSecInvalidKeyFormat", "The key format is not valid.";
+        -67743, "errSecUnsupportedVectorOfBuffers", "The vector of buffers is not supported.";
+        -67744, "errSecInvalidInputVector", "The input vector is not valid.";
+        -67745, "errSecInvalidOutputVector", "The output vector is not valid.";
+        -67746, "errSecInvalidContext", "An invalid context was encountered.";
+        -67747, "errSecInvalidAlgorithm", "An invalid algorithm was encountered.";
+        -67748, "errSecInvalidAttributeKey", "A key attribute was not valid.";
+        -67749, "errSecMissingAttributeKey", "A key attribute was missing.";
+        -67750, "errSecInvalidAttributeInitVector", "An init vector attribute was not valid.";
+        -67751, "errSecMissingAttributeInitVector", "An init vector attribute was missing.";
+        -67752, "errSecInvalidAttributeSalt", "A salt attribute was not valid.";
+        -67753, "errSecMissingAttributeSalt", "A salt attribute was missing.";
+        -67754, "errSecInvalidAttributePadding", "A padding attribute was not valid.";
+        -67755, "errSecMissingAttributePadding", "A padding attribute was missing.";
+        -67756, "errSecInvalidAttributeRandom", "A random number attribute was not valid.";
+        -67757, "errSecMissingAttributeRandom", "A random number attribute was missing.";
+        -67758, "errSecInvalidAttributeSeed", "A seed attribute was not valid.";
+        -67759, "errSecMissingAttributeSeed", "A seed attribute was missing.";
+        -67760, "errSecInvalidAttributePassphrase", "A passphrase attribute was not valid.";
+        -67761, "errSecMissingAttributePassphrase", "A passphrase attribute was missing.";
+        -67762, "errSecInvalidAttributeKeyLength", "A key length attribute was not valid.";
+        -67763, "errSecMissingAttributeKeyLength", "A key length attribute was missing.";
+        -67764, "errSecInvalidAttributeBlockSize", "A block size attribute was not valid.";
+        -67765, "errSecMissingAttributeBlockSize", "A block size attribute was missing.";
+        -67766, "errSecInvalidAttributeOutputSize", "An output size attribute was not valid.";
+        -67767, "errSecMissingAttributeOutputSize", "An output size attribute was missing.";
+        -67768, "errSecInvalidAttributeRounds", "The number of rounds attribute was not valid.";
+        -67769, "errSecMissingAttributeRounds", "The number of rounds attribute was missing.";
+        -67770, "errSecInvalidAlgorithmParms", "An algorithm parameters attribute was not valid.";
+        -67771, "errSecMissingAlgorithmParms", "An algorithm parameters attribute was missing.";
+        -67772, "errSecInvalidAttributeLabel", "A label attribute was not valid.";
+        -67773, "errSecMissingAttributeLabel", "A label attribute was missing.";
+        -67774, "errSecInvalidAttributeKeyType", "A key type attribute was not valid.";
+        -67775, "errSecMissingAttributeKeyType", "A key type attribute was missing.";
+        -67776, "errSecInvalidAttributeMode", "A mode attribute was not valid.";
+        -67777, "errSecMissingAttributeMode", "A mode attribute was missing.";
+        -67778, "errSecInvalidAttributeEffectiveBits", "An effective bits attribute was not valid.";
+        -67779, "errSecMissingAttributeEffectiveBits", "An effective bits attribute was missing.";
+        -67780, "errSecInvalidAttributeStartDate", "A start date attribute was not valid.";
+        -67781, "errSecMissingAttributeStartDate", "A start date attribute was missing.";
+        -67782, "errSecInvalidAttributeEndDate", "An end date attribute was not valid.";
+        -67783, "errSecMissingAttributeEndDate", "An end date attribute was missing.";
+        -67784, "errSecInvalidAttributeVersion", "A version attribute was not valid.";
+        -67785, "errSecMissingAttributeVersion", "A version attribute was missing.";
+        -67786, "errSecInvalidAttributePrime", "A prime attribute was not valid.";
+        -67787, "errSecMissingAttributePrime", "A prime attribute was missing.";
+        -67788, "errSecInvalidAttributeBase", "A base attribute was not valid.";
+        -67789, "errSecMissingAttributeBase", "A base attribute was missing.";
+        -67790, "errSecInvalidAttributeSubprime", "A subprime attribute was not valid.";
+        -67791, "errSecMissingAttributeSubprime", "A subprime attribute was missing.";
+        -67792, "errSecInvalidAttributeIterationCount", "An iteration count attribute was not valid.";
+        -67793, "errSecMissingAttributeIterationCount", "An iteration count attribute was missing.";
+        -67794, "errSecInvalidAttributeDLDBHandle", "A database handle attribute was not valid.";
+        -67795, "errSecMissingAttributeDLDBHandle", "A database handle attribute was missing.";
+        -67796, "errSecInvalidAttributeAccessCredentials", "An access credentials attribute was not valid.";
+        -67797, "errSecMissingAttributeAccessCredentials", "An access credentials attribute was missing.";
+        -67798, "errSecInvalidAttributePublicKeyFormat", "A public key format attribute was not valid.";
+        -67799, "errSecMissingAttributePublicKeyFormat", "A public key format attribute was missing.";
+        -67800, "errSecInvalidAttributePrivateKeyFormat", "A private key format attribute was not valid.";
+        -67801, "errSecMissingAttributePrivateKeyFormat", "A private key format attribute was missing.";
+        -67802, "errSecInvalidAttributeSymmetricKeyFormat", "A symmetric key format attribute was not valid.";
+        -67803, "errSecMissingAttributeSymmetricKeyFormat", "A symmetric key format attribute was missing.";
+        -67804, "errSecInvalidAttributeWrappedKeyFormat", "A wrapped key format attribute was not valid.";
+        -67805, "errSecMissingAttributeWrappedKeyFormat", "A wrapped key format attribute was missing.";
+        -67806, "errSecStagedOperationInProgress", "A staged operation is in progress.";
+        -67807, "errSecStagedOperationNotStarted", "A staged operation was not started.";
+        -67808, "errSecVerifyFailed", "A cryptographic verification failure has occurred.";
+        -67809, "errSecQuerySizeUnknown", "The query size is unknown.";
+        -67810, "errSecBlockSizeMismatch", "A block size mismatch occurred.";
+        -67811, "errSecPublicKeyInconsistent", "The public key was inconsistent.";
+        -67812, "errSecDeviceVerifyFailed", "A device verification failure has occurred.";
+        -67813, "errSecInvalidLoginName", "An invalid login name was detected.";
+        -67814, "errSecAlreadyLoggedIn", "The user is already logged in.";
+        -67815, "errSecInvalidDigestAlgorithm", "An invalid digest algorithm was detected.";
+        -67816, "errSecInvalidCRLGroup", "An invalid CRL group was detected.";
+        -67817, "errSecCertificateCannotOperate", "The certificate cannot operate.";
+        -67818, "errSecCertificateExpired", "An expired certificate was detected.";
+        -67819, "errSecCertificateNotValidYet", "The certificate is not yet valid.";
+        -67820, "errSecCertificateRevoked", "The certificate was revoked.";
+        -67821, "errSecCertificateSuspended", "The certificate was suspended.";
+        -67822, "errSecInsufficientCredentials", "Insufficient credentials were detected.";
+        -67823, "errSecInvalidAction", "The action was not valid.";
+        -67824, "errSecInvalidAuthority", "The authority was not valid.";
+        -67825, "errSecVerifyActionFailed", "A verify action has failed.";
+        -67826, "errSecInvalidCertAuthority", "The certificate authority was not valid.";
+        -67827, "errSecInvalidCRLAuthority", "The CRL authority was not valid.";
+        -67828, "errSecInvalidCRLEncoding", "The CRL encoding was not valid.";
+        -67829, "errSecInvalidCRLType", "The CRL type was not valid.";
+        -67830, "errSecInvalidCRL", "The CRL was not valid.";
+        -67831, "errSecInvalidFormType", "The form type was not valid.";
+        -67832, "errSecInvalidID", "The ID was not valid.";
+        -67833, "errSecInvalidIdentifier", "The identifier was not valid.";
+        -67834, "errSecInvalidIndex", "The index was not valid.";
+        -67835, "errSecInvalidPolicyIdentifiers", "The policy identifiers are not valid.";
+        -67836, "errSecInvalidTimeString", "The time specified was not valid.";
+        -67837, "errSecInvalidReason", "The trust policy reason was not valid.";
+        -67838, "errSecInvalidRequestInputs", "The request inputs are not valid.";
+        -67839, "errSecInvalidResponseVector", "The response vector was not valid.";
+        -67840, "errSecInvalidStopOnPolicy", "The stop-on policy was not valid.";
+        -67841, "errSecInvalidTuple", "The tuple was not valid.";
+        -67842, "errSecMultipleValuesUnsupported", "Multiple values are not supported.";
+        -67843, "errSecNotTrusted", "The certificate was not trusted.";
+        -67844, "errSecNoDefaultAuthority", "No default authority was detected.";
+        -67845, "errSecRejectedForm", "The trust policy had a rejected form.";
+        -67846, "errSecRequestLost", "The request was lost.";
+        -67847, "errSecRequestRejected", "The request was rejected.";
+        -67848, "errSecUnsupportedAddressType", "The address type is not supported.";
+        -67849, "errSecUnsupportedService", "The service is not supported.";
+        -67850, "errSecInvalidTupleGroup", "The tuple group was not valid.";
+        -67851, "errSecInvalidBaseACLs", "The base ACLs are not valid.";
+        -67852, "errSecInvalidTupleCredentials", "The tuple credentials are not valid.";
+        -67853, "errSecInvalidEncoding", "The encoding was not valid.";
+        -67854, "errSecInvalidValidityPeriod", "The validity period was not valid.";
+        -67855, "errSecInvalidRequestor", "The requestor was not valid.";
+        -67856, "errSecRequestDescriptor", "The request descriptor was not valid.";
+        -67857, "errSecInvalidBundleInfo", "The bundle information was not valid.";
+        -67858, "errSecInvalidCRLIndex", "The CRL index was not valid.";
+        -67859, "errSecNoFieldValues", "No field values were detected.";
+        -67860, "errSecUnsupportedFieldFormat", "The field format is not supported.";
+        -67861, "errSecUnsupportedIndexInfo", "The index information is not supported.";
+        -67862, "errSecUnsupportedLocality", "The locality is not supported.";
+        -67863, "errSecUnsupportedNumAttributes", "The number of attributes is not supported.";
+        -67864, "errSecUnsupportedNumIndexes", "The number of indexes is not supported.";
+        -67865, "errSecUnsupportedNumRecordTypes", "The number of record types is not supported.";
+        -67866, "errSecFieldSpecifiedMultiple", "Too many fields were specified.";
+        -67867, "errSecIncompatibleFieldFormat", "The field format was incompatible.";
+        -67868, "errSecInvalidParsingModule", "The parsing module was not valid.";
+        -67869, "errSecDatabaseLocked", "The database is locked.";
+        -67870, "errSecDatastoreIsOpen", "The data store is open.";
+        -67871, "errSecMissingValue", "A missing value was detected.";
+        -67872, "errSecUnsupportedQueryLimits", "The query limits are not supported.";
+        -67873, "errSecUnsupportedNumSelectionPreds", "The number of selection predicates is not supported.";
+        -67874, "errSecUnsupportedOperator", "The operator is not supported.";
+        -67875, "errSecInvalidDBLocation", "The database location is not valid.";
+        -67876, "errSecInvalidAccessRequest", "The access request is not valid.";
+        -67877, "errSecInvalidIndexInfo", "The index information is not valid.";
+        -67878, "errSecInvalidNewOwner", "The new owner is not valid.";
+        -67879, "errSecInvalidModifyMode", "The modify mode is not valid.";
+        -67880, "errSecMissingRequiredExtension", "A required certificate extension is missing.";
+        -67881, "errSecExtendedKeyUsageNotCritical", "The extended key usage extension was not marked critical.";
+        -67882, "errSecTimestampMissing", "A timestamp was expected but was not found.";
+        -67883, "errSecTimestampInvalid", "The timestamp was not valid.";
+        -67884, "errSecTimestampNotTrusted", "The timestamp was not trusted.";
+        -67885, "errSecTimestampServiceNotAvailable", "The timestamp service is not available.";
+        -67886, "errSecTimestampBadAlg", "An unrecognized or unsupported Algorithm Identifier in timestamp.";
+        -67887, "errSecTimestampBadRequest", "The timestamp transaction is not permitted or supported.";
+        -67888, "errSecTimestampBadDataFormat", "The timestamp data submitted has the wrong format.";
+        -67889, "errSecTimestampTimeNotAvailable", "The time source for the Timestamp Authority is not available.";
+        -67890, "errSecTimestampUnacceptedPolicy", "The requested policy is not supported by the Timestamp Authority.";
+        -67891, "errSecTimestampUnacceptedExtension", "The requested extension is not supported by the Timestamp Authority.";
+        -67892, "errSecTimestampAddInfoNotAvailable", "The additional information requested is not available.";
+        -67893, "errSecTimestampSystemFailure", "The timestamp request cannot be handled due to system failure.";
+        -67894, "errSecSigningTimeMissing", "A signing time was expected but was not found.";
+        -67895, "errSecTimestampRejection", "A timestamp transaction was rejected.";
+        -67896, "errSecTimestampWaiting", "A timestamp transaction is waiting.";
+        -67897, "errSecTimestampRevocationWarning", "A timestamp authority revocation warning was issued.";
+        -67898, "errSecTimestampRevocationNotification", "A timestamp authority revocation notification was issued.";
+        -67899, "errSecCertificatePolicyNotAllowed", "The requested policy is not allowed for this certificate.";
+        -67900, "errSecCertificateNameNotAllowed", "The requested name is not allowed for this certificate.";
+        -67901, "errSecCertificateValidityPeriodTooLong", "The validity period in the certificate exceeds the maximum allowed.";
+        -67902, "errSecCertificateIsCA", "The verified certificate is a CA rather than an end-entity.";
+        -67903, "errSecCertificateDuplicateExtension", "The certificate contains multiple extensions with the same extension ID.";
+        -9800, "errSSLProtocol", "SSL protocol error.";
+        -9801, "errSSLNegotiation", "Cipher Suite negotiation failure.";
+        -9802, "errSSLFatalAlert", "Fatal alert.";
+        -9803, "errSSLWouldBlock", "I/O would block (not fatal).";
+        -9804, "errSSLSessionNotFound", "Attempt to restore an unknown session.";
+        -9805, "errSSLClosedGraceful", "Connection closed gracefully.";
+        -9806, "errSSLClosedAbort", "Connection closed via error.";
+        -9807, "errSSLXCertChainInvalid", "Invalid certificate chain.";
+        -9808, "errSSLBadCert", "Bad certificate format.";
+        -9809, "errSSLCrypto", "Underlying cryptographic error.";
+        -9810, "errSSLInternal", "Internal error.";
+        -9811, "errSSLModuleAttach", "Module attach failure.";
+        -9812, "errSSLUnknownRootCert", "Valid cert chain, untrusted root.";
+        -9813, "errSSLNoRootCert", "Cert chain not verified by root.";
+        -9814, "errSSLCertExpired", "Chain had an expired cert.";
+        -9815, "errSSLCertNotYetValid", "Chain had a cert not yet valid.";
+        -9816, "errSSLClosedNoNotify", "Server closed session with no notification.";
+        -9817, "errSSLBufferOverflow", "Insufficient buffer provided.";
+        -9818, "errSSLBadCipherSuite", "Bad SSLCipherSuite.";
+        -9819, "errSSLPeerUnexpectedMsg", "Unexpected message received.";
+        -9820, "errSSLPeerBadRecordMac", "Bad MAC.";
+        -9821, "errSSLPeerDecryptionFail", "Decryption failed.";
+        -9822, "errSSLPeerRecordOverflow", "Record overflow.";
+        -9823, "errSSLPeerDecompressFail", "Decompression failure.";
+        -9824, "errSSLPeerHandshakeFail", "Handshake failure.";
+        -9825, "errSSLPeerBadCert", "Misc.";
+        -9826, "errSSLPeerUnsupportedCert", "Bad unsupported cert format.";
+        -9827, "errSSLPeerCertRevoked", "Certificate revoked.";
+        -9828, "errSSLPeerCertExpired", "Certificate expired.";
+        -9829, "errSSLPeerCertUnknown", "Unknown certificate.";
+        -9830, "errSSLIllegalParam", "Illegal parameter.";
+        -9831, "errSSLPeerUnknownCA", "Unknown Cert Authority.";
+        -9832, "errSSLPeerAccessDenied", "Access denied.";
+        -9833, "errSSLPeerDecodeError", "Decoding error.";
+        -9834, "errSSLPeerDecryptError", "Decryption error.";
+        -9835, "errSSLPeerExportRestriction", "Export restriction.";
+        -9836, "errSSLPeerProtocolVersion", "Bad protocol version.";
+        -9837, "errSSLPeerInsufficientSecurity", "Insufficient security.";
+        -9838, "errSSLPeerInternalError", "Internal error.";
+        -9839, "errSSLPeerUserCancelled", "User canceled.";
+        -9840, "errSSLPeerNoRenegotiation", "No renegotiation allowed.";
+        -9841, "errSSLPeerAuthCompleted", "Peer cert is valid, or was ignored if verification disabled.";
+        -9842, "errSSLClientCertRequested", "Server has requested a client cert.";
+        -9843, "errSSLHostNameMismatch", "Peer host name mismatch.";
+        -9844, "errSSLConnectionRefused", "Peer dropped connection before responding.";
+        -9845, "errSSLDecryptionFail", "Decryption failure.";
+        -9846, "errSSLBadRecordMac", "Bad MAC.";
+        -9847, "errSSLRecordOverflow", "Record overflow.";
+        -9848, "errSSLBadConfiguration", "Configuration error.";
+        -9849, "errSSLUnexpectedRecord", "Unexpected (skipped) record in DTLS.";
+        -9850, "errSSLWeakPeerEphemeralDHKey", "Weak ephemeral dh key.";
+        -9851, "errSSLClientHelloReceived", "SNI.";
+        -9852, "errSSLTransportReset", "Transport (socket) shutdown, e.g., TCP RST or FIN.";
+        -9853, "errSSLNetworkTimeout", "Network timeout triggered.";
+        -9854, "errSSLConfigurationFailed", "TLS configuration failed.";
+        -9855, "errSSLUnsupportedExtension", "Unsupported TLS extension.";
+        -9856, "errSSLUnexpectedMessage", "Peer rejected unexpected message.";
+        -9857, "errSSLDecompressFail", "Decompression failed.";
+        -9858, "errSSLHandshakeFail", "Handshake failed.";
+        -9859, "errSSLDecodeError", "Decode failed.";
+        -9860, "errSSLInappropriateFallback", "Inappropriate fallback.";
+        -9861, "errSSLMissingExtension", "Missing extension.";
+        -9862, "errSSLBadCertificateStatusResponse", "Bad OCSP response.";
+        -9863, "errSSLCertificateRequired", "Certificate required.";
+        -9864, "errSSLUnknownPSKIdentity", "Unknown PSK identity.";
+        -9865, "errSSLUnrecognizedName", "Unknown or unrecognized name.";
+        -9880, "errSSLATSViolation", "ATS violation.";
+        -9881, "errSSLATSMinimumVersionViolation", "ATS violation: minimum protocol version is not ATS compliant.";
+        -9882, "errSSLATSCiphersuiteViolation", "ATS violation: selected ciphersuite is not ATS compliant.";
+        -9883, "errSSLATSMinimumKeySizeViolation", "ATS violation: peer key size is not ATS compliant.";
+        -9884, "errSSLATSLeafCertificateHashAlgorithmViolation", "ATS violation: peer leaf certificate hash algorithm is not ATS compliant.";
+        -9885, "errSSLATSCertificateHashAlgorithmViolation", "ATS violation: peer certificate hash algorithm is not ATS compliant.";
+        -9886, "errSSLATSCertificateTrustViolation", "ATS violation: peer certificate is not issued by trusted peer.";
+        -9890, "errSSLEarlyDataRejected", "Early application data rejected by peer.";
+    }
+};
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use crate::os;
+
+    fn parse_code(expr: &str) -> Option<i32> {
+        if let Some(rest) = expr.strip_prefix("Error::new_unchecked(") {
+            let lit = rest.strip_suffix(")")?;
+            return match lit.strip_prefix("0x") {
+                Some(hex) => i32::from_str_radix(hex, 16).ok(),
+                None => lit.parse().ok(),
+            };
+        }
+        if let Some(rest) = expr.strip_prefix("Error::from_be_bytes(*b\"") {
+            let bytes = rest.strip_suffix("\")")?.as_bytes();
+            return Some(i32::from_be_bytes(bytes.try_into().ok()?));
+        }
+        if let Some(rest) = expr.strip_prefix("Self(") {
+            return rest.strip_suffix(")")?.parse().ok();
+        }
+        None
+    }
+
+    /// Collects `(symbol, code)` of every error constant declared in the file.
+    fn consts(path: &Path, out: &mut Vec<(String, i32)>) {
+        let src = fs::read_to_string(path).unwrap();
+        let is_return = path.ends_with("cv/_return.rs");
+        let mut alias = None;
+        for line in src.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("#[doc(alias = \"") {
+                alias = rest.strip_suffix("\")]").map(str::to_string);
+                continue;
+            }
+            if line.starts_with("///") || line.starts_with("#[") {
+                continue;
+            }
+            let alias = alias.take();
+            let Some(rest) = line.strip_prefix("pub const ") else {
+                continue;
+            };
+            let Some((_, rest)) = rest.split_once(": ") else {
+                continue;
+            };
+            let expr = if let Some(expr) = rest.strip_prefix("Error = ") {
+                expr
+            } else if let Some(expr) = rest.strip_prefix("Self = ").filter(|_| is_return) {
+                expr
+            } else {
+                continue;
+            };
+            let expr = expr.split(';').next().unwrap();
+            let Some(code) = parse_code(expr) else {
+                continue;
+            };
+            if code == 0 {
+                continue;
+            }
+            let alias = alias.unwrap_or_else(|| panic!("{path:?}: {line} has no doc alias"));
+            out.push((alias, code));
+        }
+    }
+
+    fn visit(dir: &Path, out: &mut Vec<(String, i32)>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                visit(&path, out);
+            } else if path.extension().is_some_and(|e| e == "rs")
+                && !path.ends_with("os/err_desc.rs")
+            {
+                consts(&path, out);
+            }
+        }
+    }
+
+    #[test]
+    fn every_const_registered() {
+        let mut all = Vec::new();
+        visit(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut all);
+        assert!(all.len() > 600);
+        for (symbol, code) in all {
+            assert!(
+                os::err_desc::find_all(code).any(|d| d.symbol == symbol),
+                "{symbol} ({code}) is not registered"
+            );
+        }
+    }
+
+    #[test]
+    fn symbols_unique() {
+        for (i, a) in os::err_desc::ALL.iter().enumerate() {
+            for b in &os::err_desc::ALL[i + 1..] {
+                assert!(a.symbol != b.symbol || a.code == b.code, "{}", a.symbol);
+            }
+        }
+    }
+
+    #[test]
+    fn display() {
+        let err = os::Error::new_unchecked(-12909);
+        assert_eq!(
+            err.to_string(),
+            "kVTVideoDecoderBadDataErr (VideoToolbox): The video decoder encountered bad data."
+        );
+        let err = os::Error::new_unchecked(1);
+        assert!(err.describe().is_none());
+        assert!(err.to_string().contains("osstatus.com"));
+        assert_eq!(os::err_desc::find_all(-50).count(), 2);
+        assert_eq!(
+            os::Status(-50).describe().unwrap().symbol,
+            "kAudio_ParamError"
+        );
+    }
+}
@@ -0,0 +1,759 @@
+//! Property lists without CoreFoundation.
+//!
+//! [`Value`](crate::plist::Value) mirrors the `cf::Plist` types (string,
+//! data, number, boolean, date, array and dictionary) plus the `CF$UID`
+//! references of keyed archives. It reads and writes `bplist00`, XML 1.0
+//! and OpenStep text, converts to and from `cf::Plist` with the `cf`
+//! feature and to and from any serde type with the `serde` feature.
+//!
+//! ```
+//! use cidre::plist;
+//!
+//! let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+//! <!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+//! <plist version="1.0">
+//! <dict>
+//!     <key>CFBundleIdentifier</key>
+//!     <string>com.example.app</string>
+//!     <key>LSRequiresIPhoneOS</key>
+//!     <true/>
+//! </dict>
+//! </plist>"#;
+//!
+//! let (info, format) = plist::Value::from_bytes_with_format(xml).unwrap();
+//! assert_eq!(format, plist::Format::XmlV1_0);
+//! assert_eq!(info.get("CFBundleIdentifier").and_then(|v| v.as_str()), Some("com.example.app"));
+//!
+//! let binary = info.to_bytes(plist::Format::BinaryV1_0).unwrap();
+//! assert!(binary.starts_with(b"bplist00"));
+//! assert_eq!(plist::Value::from_bytes(&binary).unwrap(), info);
+//! ```
+
+use std::{collections::BTreeMap, time::SystemTime};
+
+pub mod archive;
+mod binary;
+mod open_step;
+mod xml;
+
+#[cfg(all(target_vendor = "apple", feature = "cf"))]
+mod cf_impl;
+
+#[cfg(feature = "serde")]
+mod de;
+#[cfg(feature = "serde")]
+mod ser;
+
+#[cfg(feature = "serde")]
+pub use de::from_bytes;
+#[cfg(feature = "serde")]
+pub use de::from_value;
+#[cfg(feature = "serde")]
+pub use ser::to_bytes;
+#[cfg(feature = "serde")]
+pub use ser::to_value;
+#[cfg(feature = "serde")]
+pub use ser::Serializer;
+
+/// Key and newtype name `Uid` goes by through serde, the way XML plists
+/// spell UIDs.
+pub const UID_KEY: &str = "CF$UID";
+
+/// Key and newtype name `Date` goes by through serde.
+pub const DATE_KEY: &str = "CF$Date";
+
+/// Nesting limit of arrays and dictionaries the readers accept.
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    /// Not a binary, XML or OpenStep property list.
+    Format,
+
+    /// Offset, length or object reference past the end of a binary plist.
+    Truncated,
+
+    /// Malformed input at byte `pos`.
+    Syntax {
+        format: Format,
+        pos: usize,
+        what: &'static str,
+    },
+
+    /// Value the format can't carry.
+    Unsupported(Format, &'static str),
+
+    /// `None` or unit where a plist can't leave the value out, plists have
+    /// no null.
+    None,
+
+    /// Serde error of the type being (de)serialized.
+    Custom(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format => write!(f, "unknown property list format"),
+            Self::Truncated => write!(f, "binary property list is truncated"),
+            Self::Syntax { format, pos, what } => {
+                write!(f, "{format:?} property list: {what} at byte {pos}")
+            }
+            Self::Unsupported(format, what) => {
+                write!(f, "{format:?} property lists can't carry {what}")
+            }
+            Self::None => write!(f, "property lists have no null"),
+            Self::Custom(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Same cases as `cf::PlistFormat`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Format {
+    #[doc(alias = "kCFPropertyListOpenStepFormat")]
+    OpenStep,
+
+    #[doc(alias = "kCFPropertyListXMLFormat_v1_0")]
+    XmlV1_0,
+
+    #[doc(alias = "kCFPropertyListBinaryFormat_v1_0")]
+    BinaryV1_0,
+}
+
+impl Format {
+    /// Format of the data by its first bytes. Anything that isn't binary
+    /// or XML is taken for OpenStep.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(binary::MAGIC) {
+            return Self::BinaryV1_0;
+        }
+        let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+        let start = data
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .unwrap_or(data.len());
+        let data = &data[start..];
+        if data.starts_with(b"<?") || data.starts_with(b"<!") || data.starts_with(b"<plist") {
+            Self::XmlV1_0
+        } else {
+            Self::OpenStep
+        }
+    }
+}
+
+/// Integer or floating point `cf::Number`.
+///
+/// Integers are kept as `I64` whenever they fit, `U64` only holds values
+/// over `i64::MAX`.
+#[derive(Debug, Copy, Clone, PartialEq, PartialOrd)]
+pub enum Number {
+    I64(i64),
+    U64(u64),
+    F64(f64),
+}
+
+impl Number {
+    #[inline]
+    pub fn is_float(&self) -> bool {
+        matches!(self, Self::F64(_))
+    }
+
+    #[inline]
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            Self::I64(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            Self::I64(v) => u64::try_from(v).ok(),
+            Self::U64(v) => Some(v),
+            Self::F64(_) => None,
+        }
+    }
+
+    /// Value of the number, integers may round.
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Self::I64(v) => v as f64,
+            Self::U64(v) => v as f64,
+            Self::F64(v) => v,
+        }
+    }
+}
+
+impl std::fmt::Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::I64(v) => v.fmt(f),
+            Self::U64(v) => v.fmt(f),
+            Self::F64(v) => v.fmt(f),
+        }
+    }
+}
+
+macro_rules! number_from {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Number {
+            #[inline]
+            fn from(value: $t) -> Self {
+                Self::I64(value as i64)
+            }
+        }
+    )*};
+}
+
+number_from!(i8, i16, i32, i64, u8, u16, u32);
+
+impl From<u64> for Number {
+    #[inline]
+    fn from(value: u64) -> Self {
+        match i64::try_from(value) {
+            Ok(v) => Self::I64(v),
+            Err(_) => Self::U64(value),
+        }
+    }
+}
+
+impl From<f32> for Number {
+    #[inline]
+    fn from(value: f32) -> Self {
+        Self::F64(value as f64)
+    }
+}
+
+impl From<f64> for Number {
+    #[inline]
+    fn from(value: f64) -> Self {
+        Self::F64(value)
+    }
+}
+
+/// Seconds since 2001-01-01 00:00:00 UTC, the `cf::AbsTime` of a `cf::Date`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, PartialOrd)]
+pub struct Date(pub f64);
+
+impl Date {
+    /// Seconds from the Unix epoch to the reference date.
+    pub const UNIX_EPOCH_OFFSET: f64 = 978_307_200.0;
+
+    #[inline]
+    pub fn from_unix(secs: f64) -> Self {
+        Self(secs - Self::UNIX_EPOCH_OFFSET)
+    }
+
+    #[inline]
+    pub fn unix(&self) -> f64 {
+        self.0 + Self::UNIX_EPOCH_OFFSET
+    }
+
+    #[inline]
+    pub fn now() -> Self {
+        SystemTime::now().into()
+    }
+
+    /// Date of an ISO 8601 `YYYY-MM-DDTHH:MM:SS[.fff]Z` string, the XML
+    /// plist form.
+    pub fn from_iso8601(s: &str) -> Option<Self> {
+        let b = s.as_bytes();
+        if b.len() < 20 || b[4] != b'-' || b[7] != b'-' || b[10] != b'T' {
+            return None;
+        }
+        if b[13] != b':' || b[16] != b':' || *b.last()? != b'Z' {
+            return None;
+        }
+        let num = |r: std::ops::Range<usize>| -> Option<i64> {
+            let s = s.get(r)?;
+            if !s.bytes().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            s.parse().ok()
+        };
+        let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+        let (hour, min, sec) = (num(11..13)?, num(14..16)?, num(17..19)?);
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || min > 59 {
+            return None;
+        }
+        // leap seconds
+        if sec > 60 {
+            return None;
+        }
+        let frac = match &s[19..s.len() - 1] {
+            "" => 0.0,
+            f if f.starts_with('.')
+                && f.len() > 1
+                && f[1..].bytes().all(|c| c.is_ascii_digit()) =>
+            {
+                format!("0{f}").parse::<f64>().ok()?
+            }
+            _ => return None,
+        };
+        let days = days_from_civil(year, month, day);
+        let secs = days * 86_400 + hour * 3_600 + min * 60 + sec;
+        Some(Self::from_unix(secs as f64 + frac))
+    }
+
+    /// `YYYY-MM-DDTHH:MM:SSZ` of the date, rounded down to whole seconds
+    /// as CoreFoundation writes XML dates.
+    pub fn to_iso8601(&self) -> String {
+        let secs = self.unix().floor() as i64;
+        let (days, secs) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+        let (year, month, day) = civil_from_days(days);
+        format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            secs / 3_600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+impl From<SystemTime> for Date {
+    fn from(value: SystemTime) -> Self {
+        match value.duration_since(SystemTime::UNIX_EPOCH) {
+            Ok(d) => Self::from_unix(d.as_secs_f64()),
+            Err(e) => Self::from_unix(-e.duration().as_secs_f64()),
+        }
+    }
+}
+
+impl From<Date> for SystemTime {
+    fn from(value: Date) -> Self {
+        let secs = value.unix();
+        let d = std::time::Duration::from_secs_f64(secs.abs());
+        if secs >= 0.0 {
+            SystemTime::UNIX_EPOCH + d
+        } else {
+            SystemTime::UNIX_EPOCH - d
+        }
+    }
+}
+
+/// Days since 1970-01-01 of a proleptic Gregorian date.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let doy = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Object reference of keyed archives, `CFKeyedArchiverUID`.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Uid(pub u64);
+
+pub type Dictionary = BTreeMap<String, Value>;
+
+/// Property list object.
+#[doc(alias = "CFPropertyListRef")]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Data(Vec<u8>),
+    Number(Number),
+    Boolean(bool),
+    Date(Date),
+    Array(Vec<Value>),
+    Dictionary(Dictionary),
+    Uid(Uid),
+}
+
+impl Value {
+    /// Reads a property list of any format.
+    #[inline]
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        Self::from_bytes_with_format(data).map(|(v, _)| v)
+    }
+
+    pub fn from_bytes_with_format(data: &[u8]) -> Result<(Self, Format), Error> {
+        let format = Format::detect(data);
+        let res = match format {
+            Format::BinaryV1_0 => binary::read(data)?,
+            Format::XmlV1_0 => xml::read(data)?,
+            Format::OpenStep => open_step::read(data)?,
+        };
+        Ok((res, format))
+    }
+
+    /// OpenStep output uses the GNUstep `<*I…>`, `<*R…>`, `<*B…>` and
+    /// `<*D…>` forms for numbers, booleans and dates.
+    pub fn to_bytes(&self, format: Format) -> Result<Vec<u8>, Error> {
+        match format {
+            Format::BinaryV1_0 => binary::write(self),
+            Format::XmlV1_0 => xml::write(self),
+            Format::OpenStep => open_step::write(self),
+        }
+    }
+
+    /// Value of a dictionary key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.as_dictionary()?.get(key)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_data(&self) -> Option<&[u8]> {
+        match self {
+            Self::Data(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<Number> {
+        match self {
+            Self::Number(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number()?.as_i64()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number()?.as_u64()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        Some(self.as_number()?.as_f64())
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_date(&self) -> Option<Date> {
+        match self {
+            Self::Date(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Value]> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Self::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_dictionary(&self) -> Option<&Dictionary> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_dictionary_mut(&mut self) -> Option<&mut Dictionary> {
+        match self {
+            Self::Dictionary(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_uid(&self) -> Option<Uid> {
+        match self {
+            Self::Uid(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Name of the type for error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::String(_) => "string",
+            Self::Data(_) => "data",
+            Self::Number(_) => "number",
+            Self::Boolean(_) => "boolean",
+            Self::Date(_) => "date",
+            Self::Array(_) => "array",
+            Self::Dictionary(_) => "dictionary",
+            Self::Uid(_) => "uid",
+        }
+    }
+}
+
+impl From<String> for Value {
+    #[inline]
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    #[inline]
+    fn from(value: &str) -> Self {
+        Self::String(value.to_string())
+    }
+}
+
+impl From<Vec<u8>> for Value {
+    #[inline]
+    fn from(value: Vec<u8>) -> Self {
+        Self::Data(value)
+    }
+}
+
+impl From<&[u8]> for Value {
+    #[inline]
+    fn from(value: &[u8]) -> Self {
+        Self::Data(value.to_vec())
+    }
+}
+
+impl From<bool> for Value {
+    #[inline]
+    fn from(value: bool) -> Self {
+        Self::Boolean(value)
+    }
+}
+
+impl From<Number> for Value {
+    #[inline]
+    fn from(value: Number) -> Self {
+        Self::Number(value)
+    }
+}
+
+macro_rules! value_from_number {
+    ($($t:ty),*) => {$(
+        impl From<$t> for Value {
+            #[inline]
+            fn from(value: $t) -> Self {
+                Self::Number(value.into())
+            }
+        }
+    )*};
+}
+
+value_from_number!(i8, i16, i32, i64, u8, u16, u32, u64, f32, f64);
+
+impl From<Date> for Value {
+    #[inline]
+    fn from(value: Date) -> Self {
+        Self::Date(value)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    #[inline]
+    fn from(value: Vec<Value>) -> Self {
+        Self::Array(value)
+    }
+}
+
+impl From<Dictionary> for Value {
+    #[inline]
+    fn from(value: Dictionary) -> Self {
+        Self::Dictionary(value)
+    }
+}
+
+impl From<Uid> for Value {
+    #[inline]
+    fn from(value: Uid) -> Self {
+        Self::Uid(value)
+    }
+}
+
+/// Standard base64 with padding, what XML `<data>` holds.
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    const TABLE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut res = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, b[0], b[1], b[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                res.push(TABLE[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                res.push('=');
+            }
+        }
+    }
+    res
+}
+
+/// Decodes base64 skipping whitespace, `None` on other characters.
+pub(crate) fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut res = Vec::with_capacity(text.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut padding = false;
+    for c in text.bytes() {
+        let v = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => {
+                padding = true;
+                continue;
+            }
+            c if c.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+        if padding {
+            return None;
+        }
+        acc = acc << 6 | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            res.push((acc >> bits) as u8);
+        }
+    }
+    Some(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plist::{self, Date, Dictionary, Format, Number, Uid, Value};
+
+    pub(super) fn sample() -> Value {
+        let mut nested = Dictionary::new();
+        nested.insert("uid".into(), Uid(7).into());
+        nested.insert("empty".into(), Value::Array(vec![]));
+        nested.insert("none".into(), Value::Dictionary(Dictionary::new()));
+
+        let mut dict = Dictionary::new();
+        dict.insert("string".into(), "hello <world> & \"you\"".into());
+        dict.insert("unicode".into(), "Grüße, 世界 🦀".into());
+        dict.insert("empty string".into(), "".into());
+        dict.insert("data".into(), vec![0u8, 1, 2, 254, 255].into());
+        dict.insert("int".into(), (-42).into());
+        dict.insert("big".into(), u64::MAX.into());
+        dict.insert("min".into(), i64::MIN.into());
+        dict.insert("real".into(), 1.5.into());
+        dict.insert("true".into(), true.into());
+        dict.insert("false".into(), false.into());
+        dict.insert("date".into(), Date(-1234.0).into());
+        dict.insert(
+            "array".into(),
+            vec![Value::from(1), "two".into(), vec![Value::from(3.25)].into()].into(),
+        );
+        dict.insert("nested".into(), nested.into());
+        dict.into()
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(Number::from(5u64), Number::I64(5));
+        assert_eq!(Number::from(u64::MAX), Number::U64(u64::MAX));
+        assert_eq!(Number::I64(-1).as_u64(), None);
+        assert_eq!(Value::from(3u8).as_i64(), Some(3));
+        assert_eq!(Value::from(3u8).as_f64(), Some(3.0));
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(Date(0.0).to_iso8601(), "2001-01-01T00:00:00Z");
+        assert_eq!(Date::from_iso8601("2001-01-01T00:00:00Z"), Some(Date(0.0)));
+        assert_eq!(
+            Date::from_iso8601("1970-01-01T00:00:00Z"),
+            Some(Date::from_unix(0.0))
+        );
+        let d = Date::from_iso8601("2024-02-29T12:34:56Z").unwrap();
+        assert_eq!(d.unix(), 1_709_210_096.0);
+        assert_eq!(d.to_iso8601(), "2024-02-29T12:34:56Z");
+        assert_eq!(Date(-0.5).to_iso8601(), "2000-12-31T23:59:59Z");
+        assert_eq!(
+            Date::from_iso8601("2001-01-01T00:00:00.25Z"),
+            Some(Date(0.25))
+        );
+        assert_eq!(Date::from_iso8601("2001-13-01T00:00:00Z"), None);
+        assert_eq!(Date::from_iso8601("2001-01-01 00:00:00Z"), None);
+
+        let now = Date::now();
+        let st: std::time::SystemTime = now.into();
+        assert!((Date::from(st).0 - now.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn base64() {
+        for len in 0..10 {
+            let data: Vec<u8> = (0..len).map(|i| (i * 37) as u8).collect();
+            let text = plist::base64_encode(&data);
+            assert_eq!(text.len() % 4, 0);
+            assert_eq!(plist::base64_decode(&text).unwrap(), data);
+        }
+        assert_eq!(plist::base64_encode(b"Man"), "TWFu");
+        assert_eq!(plist::base64_decode("TW\n Fu").unwrap(), b"Man");
+        assert_eq!(plist::base64_decode("TW=u"), None);
+        assert_eq!(plist::base64_decode("TW*u"), None);
+    }
+
+    #[test]
+    fn detect() {
+        assert_eq!(Format::detect(b"bplist00"), Format::BinaryV1_0);
+        assert_eq!(Format::detect(b"\xEF\xBB\xBF <?xml"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"<plist>"), Format::XmlV1_0);
+        assert_eq!(Format::detect(b"<0011>"), Format::OpenStep);
+        assert_eq!(Format::detect(b"{}"), Format::OpenStep);
+    }
+
+    #[test]
+    fn roundtrips() {
+        let value = sample();
+        for format in [Format::BinaryV1_0, Format::XmlV1_0] {
+            let bytes = value.to_bytes(format).unwrap();
+            let (read, f) = Value::from_bytes_with_format(&bytes).unwrap();
+            assert_eq!(f, format);
+            assert_eq!(read, value, "{format:?}");
+        }
+        assert_eq!(
+            value.to_bytes(Format::OpenStep),
+            Err(plist::Error::Unsupported(Format::OpenStep, "UIDs"))
+        );
+        let mut value = value;
+        let nested = value
+            .as_dictionary_mut()
+            .unwrap()
+            .get_mut("nested")
+            .unwrap();
+        nested.as_dictionary_mut().unwrap().remove("uid");
+        let bytes = value.to_bytes(Format::OpenStep).unwrap();
+        assert_eq!(Value::from_bytes(&bytes).unwrap(), value);
+    }
+}
@@ -0,0 +1,1084 @@
+//! `NSKeyedArchiver` archives without Foundation.
+//!
+//! An archive is a property list dictionary with `$archiver`, `$version`,
+//! `$top` and `$objects` keys. `$objects` holds every archived object once,
+//! class instances refer to each other and to their class descriptions by
+//! [`Uid`] and index 0 is the `$null` placeholder of nil references.
+//!
+//! [`Unarchiver`] resolves the graph into an [`Object`] tree. Foundation
+//! classes of property list data are built in, other classes come back as
+//! [`Object::Instance`] unless a [`ClassDecoder`] is registered for them.
+//! [`Archiver`] writes the tree back the way `NSKeyedArchiver` lays it out.
+//!
+//! ```
+//! use cidre::plist::archive::{self, Object};
+//!
+//! let root = Object::Dictionary(vec![(
+//!     Object::String("tags".to_string()),
+//!     Object::Array(vec![Object::String("a".to_string()), Object::Null]),
+//! )]);
+//! let bytes = archive::to_bytes(&root).unwrap();
+//! assert_eq!(archive::from_bytes(&bytes).unwrap(), root);
+//! ```
+
+use std::collections::{BTreeMap, HashMap};
+
+use super::{Date, Dictionary, Format, Number, Uid, Value};
+
+/// `$archiver` of `NSKeyedArchiver` archives.
+pub const ARCHIVER: &str = "NSKeyedArchiver";
+
+/// `$version` Foundation writes.
+pub const VERSION: u64 = 100_000;
+
+/// `$top` key of the root object.
+pub const ROOT_KEY: &str = "root";
+
+/// `$objects[0]`, what nil references point at.
+pub const NULL: &str = "$null";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Error {
+    Plist(super::Error),
+
+    /// Not a keyed archive, or its `$top`/`$objects` are malformed.
+    Format(&'static str),
+
+    /// Reference past the end of `$objects`.
+    Ref(u64),
+
+    /// Object containing itself, the tree can't hold it.
+    Cycle(u64),
+
+    /// Instance of `class` missing or mistyping a key.
+    Class {
+        class: String,
+        what: &'static str,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Plist(e) => e.fmt(f),
+            Self::Format(what) => write!(f, "keyed archive: {what}"),
+            Self::Ref(uid) => write!(f, "keyed archive: object {uid} out of range"),
+            Self::Cycle(uid) => write!(f, "keyed archive: object {uid} refers to itself"),
+            Self::Class { class, what } => write!(f, "keyed archive: {class} {what}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<super::Error> for Error {
+    fn from(value: super::Error) -> Self {
+        Self::Plist(value)
+    }
+}
+
+/// Archived object graph as a tree, objects referred to more than once
+/// are repeated.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Object {
+    /// `$null`, a nil reference.
+    Nil,
+
+    /// `NSNull`
+    Null,
+
+    /// `NSString` and `NSMutableString`
+    String(String),
+
+    /// `NSData` and `NSMutableData`
+    Data(Vec<u8>),
+
+    /// `NSNumber`
+    Number(Number),
+
+    /// `NSNumber` of a `BOOL`
+    Boolean(bool),
+
+    /// `NSDate`
+    Date(Date),
+
+    /// `NSUUID`
+    Uuid([u8; 16]),
+
+    /// `NSURL`, `base` is `Nil` or another URL.
+    Url { base: Box<Object>, relative: String },
+
+    /// `NSArray` and `NSMutableArray`
+    Array(Vec<Object>),
+
+    /// `NSSet` and `NSMutableSet`
+    Set(Vec<Object>),
+
+    /// `NSDictionary` and `NSMutableDictionary` in archive order.
+    Dictionary(Vec<(Object, Object)>),
+
+    /// `NSError`, `user_info` is `Nil` or a dictionary.
+    Error {
+        domain: String,
+        code: i64,
+        user_info: Box<Object>,
+    },
+
+    /// Instance of a class without a decoder.
+    Instance(Instance),
+}
+
+impl Object {
+    /// Value of a dictionary key string.
+    pub fn get(&self, key: &str) -> Option<&Object> {
+        let Self::Dictionary(entries) = self else {
+            return None;
+        };
+        entries
+            .iter()
+            .find(|(k, _)| matches!(k, Self::String(s) if s == key))
+            .map(|(_, v)| v)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Object]> {
+        match self {
+            Self::Array(v) | Self::Set(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_nil(&self) -> bool {
+        matches!(self, Self::Nil)
+    }
+}
+
+/// Keyed values of a class instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Instance {
+    /// `$classes`, the class first and its superclasses after it.
+    pub classes: Vec<String>,
+    pub fields: BTreeMap<String, Field>,
+}
+
+impl Instance {
+    pub fn class_name(&self) -> &str {
+        self.classes.first().map_or("", String::as_str)
+    }
+}
+
+/// Value of an instance key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Field {
+    /// Stored in the instance, what `encodeInt:forKey:`, `encodeDouble:`,
+    /// `encodeBool:` and `encodeBytes:` write.
+    Value(Value),
+
+    /// Referenced object of `encodeObject:forKey:`.
+    Object(Object),
+}
+
+/// Decodes the instance `coder` is at, `$classes` are tried in order so
+/// a decoder registered for a class also takes its subclasses.
+pub type ClassDecoder = fn(&mut Coder) -> Result<Object, Error>;
+
+/// Decoder state of one class instance, what `initWithCoder:` sees.
+pub struct Coder<'a, 'b> {
+    graph: &'b mut Graph<'a>,
+    classes: Vec<String>,
+    fields: &'a Dictionary,
+}
+
+impl<'a> Coder<'a, '_> {
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn class_name(&self) -> &str {
+        &self.classes[0]
+    }
+
+    /// Keys of the instance, without `$class`.
+    pub fn keys(&self) -> impl Iterator<Item = &'a str> {
+        let fields: &'a Dictionary = self.fields;
+        fields.keys().map(String::as_str).filter(|k| *k != "$class")
+    }
+
+    #[inline]
+    pub fn contains(&self, key: &str) -> bool {
+        self.fields.contains_key(key)
+    }
+
+    /// Raw value of a key as stored in the instance.
+    #[inline]
+    pub fn value(&self, key: &str) -> Option<&'a Value> {
+        self.fields.get(key)
+    }
+
+    pub fn decode_i64(&self, key: &str) -> Option<i64> {
+        self.value(key)?.as_i64()
+    }
+
+    pub fn decode_f64(&self, key: &str) -> Option<f64> {
+        self.value(key)?.as_f64()
+    }
+
+    pub fn decode_bool(&self, key: &str) -> Option<bool> {
+        self.value(key)?.as_bool()
+    }
+
+    pub fn decode_bytes(&self, key: &str) -> Option<&'a [u8]> {
+        self.value(key)?.as_data()
+    }
+
+    /// Referenced object of a key, [`Object::Nil`] when the key is missing.
+    pub fn decode_object(&mut self, key: &str) -> Result<Object, Error> {
+        match self.fields.get(key) {
+            None => Ok(Object::Nil),
+            Some(Value::Uid(uid)) => self.graph.object(uid.0),
+            Some(_) => Err(self.invalid("object key holds a value")),
+        }
+    }
+
+    /// Objects of an array of references, `NS.objects` of collections.
+    pub fn decode_objects(&mut self, key: &str) -> Result<Vec<Object>, Error> {
+        let Some(value) = self.fields.get(key) else {
+            return Ok(Vec::new());
+        };
+        let refs = value
+            .as_array()
+            .ok_or_else(|| self.invalid("object array key holds a value"))?;
+        let mut res = Vec::with_capacity(refs.len());
+        for r in refs {
+            let uid = r
+                .as_uid()
+                .ok_or_else(|| self.invalid("object array holds a value"))?;
+            res.push(self.graph.object(uid.0)?);
+        }
+        Ok(res)
+    }
+
+    /// Objects of `<prefix>0`, `<prefix>1`... keys up to the first missing
+    /// one, how some collections archive small contents.
+    pub fn decode_numbered_objects(&mut self, prefix: &str) -> Result<Vec<Object>, Error> {
+        let mut res = Vec::new();
+        loop {
+            let key = format!("{prefix}{}", res.len());
+            if !self.contains(&key) {
+                return Ok(res);
+            }
+            res.push(self.decode_object(&key)?);
+        }
+    }
+
+    /// Error of a malformed instance of this class.
+    pub fn invalid(&self, what: &'static str) -> Error {
+        Error::Class {
+            class: self.class_name().to_string(),
+            what,
+        }
+    }
+}
+
+/// Resolves archives into [`Object`] trees.
+#[derive(Clone)]
+pub struct Unarchiver {
+    decoders: HashMap<String, ClassDecoder>,
+}
+
+impl Default for Unarchiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Unarchiver {
+    /// Unarchiver decoding the Foundation classes of [`Object`].
+    pub fn new() -> Self {
+        let builtin: [(&str, ClassDecoder); 9] = [
+            ("NSArray", decode_array),
+            ("NSSet", decode_set),
+            ("NSDictionary", decode_dictionary),
+            ("NSString", decode_string),
+            ("NSData", decode_data),
+            ("NSDate", decode_date),
+            ("NSUUID", decode_uuid),
+            ("NSURL", decode_url),
+            ("NSError", decode_error),
+        ];
+        let mut decoders: HashMap<String, ClassDecoder> = builtin
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect();
+        decoders.insert("NSNull".to_string(), |_| Ok(Object::Null));
+        Self { decoders }
+    }
+
+    /// Unarchiver without any class decoders, every instance comes back as
+    /// [`Object::Instance`].
+    pub fn raw() -> Self {
+        Self {
+            decoders: HashMap::new(),
+        }
+    }
+
+    /// Sets the decoder of `class` and its subclasses, replacing the
+    /// built in one.
+    pub fn register(&mut self, class: &str, decoder: ClassDecoder) -> &mut Self {
+        self.decoders.insert(class.to_string(), decoder);
+        self
+    }
+
+    /// Root object of an archive.
+    pub fn decode(&self, archive: &Value) -> Result<Object, Error> {
+        let mut top = self.decode_top(archive)?;
+        top.remove(ROOT_KEY)
+            .ok_or(Error::Format("no root object in $top"))
+    }
+
+    /// Root object of an archive in any property list format.
+    pub fn decode_bytes(&self, data: &[u8]) -> Result<Object, Error> {
+        self.decode(&Value::from_bytes(data)?)
+    }
+
+    /// Every `$top` object by its key.
+    pub fn decode_top(&self, archive: &Value) -> Result<BTreeMap<String, Object>, Error> {
+        if archive.get("$archiver").and_then(Value::as_str) != Some(ARCHIVER) {
+            return Err(Error::Format("no NSKeyedArchiver $archiver"));
+        }
+        let top = archive
+            .get("$top")
+            .and_then(Value::as_dictionary)
+            .ok_or(Error::Format("no $top dictionary"))?;
+        let objects = archive
+            .get("$objects")
+            .and_then(Value::as_array)
+            .ok_or(Error::Format("no $objects array"))?;
+        let mut graph = Graph {
+            objects,
+            decoders: &self.decoders,
+            done: HashMap::new(),
+            stack: Vec::new(),
+        };
+        let mut res = BTreeMap::new();
+        for (k, v) in top {
+            let uid = v.as_uid().ok_or(Error::Format("$top value isn't a UID"))?;
+            res.insert(k.clone(), graph.object(uid.0)?);
+        }
+        Ok(res)
+    }
+}
+
+struct Graph<'a> {
+    objects: &'a [Value],
+    decoders: &'a HashMap<String, ClassDecoder>,
+
+    /// Decoded instances, objects referred to more than once are decoded
+    /// once.
+    done: HashMap<u64, Object>,
+
+    /// Instances being decoded.
+    stack: Vec<u64>,
+}
+
+impl<'a> Graph<'a> {
+    fn object(&mut self, uid: u64) -> Result<Object, Error> {
+        if uid == 0 {
+            return Ok(Object::Nil);
+        }
+        let value = self.objects.get(uid as usize).ok_or(Error::Ref(uid))?;
+        let res = match value {
+            Value::String(v) => Object::String(v.clone()),
+            Value::Data(v) => Object::Data(v.clone()),
+            Value::Number(v) => Object::Number(*v),
+            Value::Boolean(v) => Object::Boolean(*v),
+            Value::Date(v) => Object::Date(*v),
+            Value::Dictionary(fields) => {
+                if let Some(done) = self.done.get(&uid) {
+                    return Ok(done.clone());
+                }
+                if self.stack.contains(&uid) {
+                    return Err(Error::Cycle(uid));
+                }
+                if self.stack.len() >= super::MAX_DEPTH {
+                    return Err(Error::Format("objects nested too deeply"));
+                }
+                self.stack.push(uid);
+                let res = self.instance(fields);
+                self.stack.pop();
+                let res = res?;
+                self.done.insert(uid, res.clone());
+                res
+            }
+            Value::Array(_) | Value::Uid(_) => {
+                return Err(Error::Format("$objects entry isn't an object"));
+            }
+        };
+        Ok(res)
+    }
+
+    fn instance(&mut self, fields: &'a Dictionary) -> Result<Object, Error> {
+        let class = fields
+            .get("$class")
+            .and_then(Value::as_uid)
+            .ok_or(Error::Format("instance without $class"))?;
+        let class = self
+            .objects
+            .get(class.0 as usize)
+            .ok_or(Error::Ref(class.0))?;
+        let name = class
+            .get("$classname")
+            .and_then(Value::as_str)
+            .ok_or(Error::Format("class without $classname"))?;
+        let classes = match class.get("$classes").and_then(Value::as_array) {
+            Some(classes) => classes
+                .iter()
+                .map(|c| c.as_str().map(str::to_string))
+                .collect::<Option<Vec<_>>>()
+                .ok_or(Error::Format("non string $classes"))?,
+            None => vec![name.to_string()],
+        };
+        if classes.first().map(String::as_str) != Some(name) {
+            return Err(Error::Format("$classes doesn't start with $classname"));
+        }
+
+        let decoder = classes.iter().find_map(|c| self.decoders.get(c)).copied();
+        let mut coder = Coder {
+            graph: self,
+            classes,
+            fields,
+        };
+        if let Some(decoder) = decoder {
+            return decoder(&mut coder);
+        }
+        let mut res = BTreeMap::new();
+        for key in coder.keys() {
+            let field = match &fields[key] {
+                Value::Uid(uid) => Field::Object(coder.graph.object(uid.0)?),
+                v => Field::Value(v.clone()),
+            };
+            res.insert(key.to_string(), field);
+        }
+        Ok(Object::Instance(Instance {
+            classes: coder.classes,
+            fields: res,
+        }))
+    }
+}
+
+fn decode_collection(c: &mut Coder) -> Result<Vec<Object>, Error> {
+    if c.contains("NS.objects") {
+        c.decode_objects("NS.objects")
+    } else {
+        c.decode_numbered_objects("NS.object.")
+    }
+}
+
+fn decode_array(c: &mut Coder) -> Result<Object, Error> {
+    decode_collection(c).map(Object::Array)
+}
+
+fn decode_set(c: &mut Coder) -> Result<Object, Error> {
+    decode_collection(c).map(Object::Set)
+}
+
+fn decode_dictionary(c: &mut Coder) -> Result<Object, Error> {
+    let (keys, values) = if c.contains("NS.keys") {
+        (
+            c.decode_objects("NS.keys")?,
+            c.decode_objects("NS.objects")?,
+        )
+    } else {
+        (
+            c.decode_numbered_objects("NS.key.")?,
+            c.decode_numbered_objects("NS.object.")?,
+        )
+    };
+    if keys.len() != values.len() {
+        return Err(c.invalid("has more keys than values"));
+    }
+    Ok(Object::Dictionary(keys.into_iter().zip(values).collect()))
+}
+
+/// `NSMutableString` keeps the string in the instance.
+fn decode_string(c: &mut Coder) -> Result<Object, Error> {
+    if let Some(s) = c.value("NS.string").and_then(Value::as_str) {
+        return Ok(Object::String(s.to_string()));
+    }
+    let bytes = c
+        .decode_bytes("NS.bytes")
+        .ok_or_else(|| c.invalid("has no NS.string"))?;
+    String::from_utf8(bytes.to_vec())
+        .map(Object::String)
+        .map_err(|_| c.invalid("isn't UTF-8"))
+}
+
+fn decode_data(c: &mut Coder) -> Result<Object, Error> {
+    match c.value("NS.data") {
+        Some(Value::Data(d)) => Ok(Object::Data(d.clone())),
+        Some(Value::Uid(_)) => c.decode_object("NS.data"),
+        _ => Err(c.invalid("has no NS.data")),
+    }
+}
+
+fn decode_date(c: &mut Coder) -> Result<Object, Error> {
+    c.decode_f64("NS.time")
+        .map(|t| Object::Date(Date(t)))
+        .ok_or_else(|| c.invalid("has no NS.time"))
+}
+
+fn decode_uuid(c: &mut Coder) -> Result<Object, Error> {
+    c.decode_bytes("NS.uuidbytes")
+        .and_then(|b| b.try_into().ok())
+        .map(Object::Uuid)
+        .ok_or_else(|| c.invalid("has no 16 byte NS.uuidbytes"))
+}
+
+fn decode_url(c: &mut Coder) -> Result<Object, Error> {
+    let base = c.decode_object("NS.base")?;
+    if !matches!(base, Object::Nil | Object::Url { .. }) {
+        return Err(c.invalid("base isn't a URL"));
+    }
+    let Object::String(relative) = c.decode_object("NS.relative")? else {
+        return Err(c.invalid("has no NS.relative string"));
+    };
+    Ok(Object::Url {
+        base: Box::new(base),
+        relative,
+    })
+}
+
+fn decode_error(c: &mut Coder) -> Result<Object, Error> {
+    let Object::String(domain) = c.decode_object("NSDomain")? else {
+        return Err(c.invalid("has no NSDomain string"));
+    };
+    let code = c.decode_i64("NSCode").unwrap_or(0);
+    let user_info = c.decode_object("NSUserInfo")?;
+    if !matches!(user_info, Object::Nil | Object::Dictionary(_)) {
+        return Err(c.invalid("user info isn't a dictionary"));
+    }
+    Ok(Object::Error {
+        domain,
+        code,
+        user_info: Box::new(user_info),
+    })
+}
+
+/// Root object of an archive in any property list format.
+pub fn from_bytes(data: &[u8]) -> Result<Object, Error> {
+    Unarchiver::new().decode_bytes(data)
+}
+
+/// Binary archive of `root`, what `archivedDataWithRootObject:` returns.
+pub fn to_bytes(root: &Object) -> Result<Vec<u8>, Error> {
+    let mut archiver = Archiver::new();
+    archiver.encode_top(ROOT_KEY, root);
+    Ok(archiver.finish().to_bytes(Format::BinaryV1_0)?)
+}
+
+/// Property list key of scalars archived once.
+#[derive(PartialEq, Eq, Hash)]
+enum Scalar {
+    String(String),
+    I64(i64),
+    U64(u64),
+    F64(u64),
+    Boolean(bool),
+}
+
+/// Lays out objects as `NSKeyedArchiver` does: an instance first, the
+/// objects it encodes after it, then its class. Equal strings, numbers
+/// and classes are archived once.
+pub struct Archiver {
+    objects: Vec<Value>,
+    top: Dictionary,
+    scalars: HashMap<Scalar, Uid>,
+    classes: HashMap<Vec<String>, Uid>,
+}
+
+impl Default for Archiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Archiver {
+    pub fn new() -> Self {
+        Self {
+            objects: vec![Value::String(NULL.to_string())],
+            top: Dictionary::new(),
+            scalars: HashMap::new(),
+            classes: HashMap::new(),
+        }
+    }
+
+    /// Archives `object` under a `$top` key.
+    pub fn encode_top(&mut self, key: &str, object: &Object) {
+        let uid = self.encode(object);
+        self.top.insert(key.to_string(), Value::Uid(uid));
+    }
+
+    /// The archive as a property list.
+    pub fn finish(self) -> Value {
+        let mut res = Dictionary::new();
+        res.insert("$archiver".to_string(), ARCHIVER.into());
+        res.insert("$version".to_string(), VERSION.into());
+        res.insert("$top".to_string(), self.top.into());
+        res.insert("$objects".to_string(), self.objects.into());
+        res.into()
+    }
+
+    fn push(&mut self, value: Value) -> Uid {
+        self.objects.push(value);
+        Uid(self.objects.len() as u64 - 1)
+    }
+
+    fn scalar(&mut self, key: Scalar, value: Value) -> Uid {
+        if let Some(uid) = self.scalars.get(&key) {
+            return *uid;
+        }
+        let uid = self.push(value);
+        self.scalars.insert(key, uid);
+        uid
+    }
+
+    fn class(&mut self, classes: &[&str]) -> Uid {
+        let classes: Vec<String> = classes.iter().map(|c| c.to_string()).collect();
+        self.class_of(classes)
+    }
+
+    fn class_of(&mut self, classes: Vec<String>) -> Uid {
+        if let Some(uid) = self.classes.get(&classes) {
+            return *uid;
+        }
+        let mut desc = Dictionary::new();
+        desc.insert("$classname".to_string(), classes[0].clone().into());
+        let list = classes
+            .iter()
+            .map(|c| Value::from(c.as_str()))
+            .collect::<Vec<_>>();
+        desc.insert("$classes".to_string(), list.into());
+        let uid = self.push(desc.into());
+        self.classes.insert(classes, uid);
+        uid
+    }
+
+    fn refs(&mut self, objects: &[Object]) -> Value {
+        let refs = objects
+            .iter()
+            .map(|o| Value::Uid(self.encode(o)))
+            .collect::<Vec<_>>();
+        refs.into()
+    }
+
+    /// Reserves the instance slot, encodes `fields` into it, then adds the
+    /// class.
+    fn instance(
+        &mut self,
+        classes: &[&str],
+        fields: impl FnOnce(&mut Self, &mut Dictionary),
+    ) -> Uid {
+        let uid = self.push(Value::Dictionary(Dictionary::new()));
+        let mut dict = Dictionary::new();
+        fields(self, &mut dict);
+        dict.insert("$class".to_string(), Value::Uid(self.class(classes)));
+        self.objects[uid.0 as usize] = dict.into();
+        uid
+    }
+
+    fn encode(&mut self, object: &Object) -> Uid {
+        match object {
+            Object::Nil => Uid(0),
+            Object::String(s) => self.scalar(Scalar::String(s.clone()), s.as_str().into()),
+            Object::Number(n) => {
+                let key = match *n {
+                    Number::I64(v) => Scalar::I64(v),
+                    Number::U64(v) => Scalar::U64(v),
+                    Number::F64(v) => Scalar::F64(v.to_bits()),
+                };
+                self.scalar(key, (*n).into())
+            }
+            Object::Boolean(b) => self.scalar(Scalar::Boolean(*b), (*b).into()),
+            Object::Data(d) => self.push(d.as_slice().into()),
+            Object::Null => self.instance(&["NSNull", "NSObject"], |_, _| {}),
+            Object::Date(d) => self.instance(&["NSDate", "NSObject"], |_, f| {
+                f.insert("NS.time".to_string(), d.0.into());
+            }),
+            Object::Uuid(bytes) => self.instance(&["NSUUID", "NSObject"], |_, f| {
+                f.insert("NS.uuidbytes".to_string(), bytes.as_slice().into());
+            }),
+            Object::Url { base, relative } => self.instance(&["NSURL", "NSObject"], |a, f| {
+                let base = a.encode(base);
+                let relative = a.encode(&Object::String(relative.clone()));
+                f.insert("NS.base".to_string(), Value::Uid(base));
+                f.insert("NS.relative".to_string(), Value::Uid(relative));
+            }),
+            Object::Array(items) => self.instance(&["NSArray", "NSObject"], |a, f| {
+                f.insert("NS.objects".to_string(), a.refs(items));
+            }),
+            Object::Set(items) => self.instance(&["NSSet", "NSObject"], |a, f| {
+                f.insert("NS.objects".to_string(), a.refs(items));
+            }),
+            Object::Dictionary(entries) => self.instance(&["NSDictionary", "NSObject"], |a, f| {
+                let keys: Vec<_> = entries.iter().map(|(k, _)| k.clone()).collect();
+                let values: Vec<_> = entries.iter().map(|(_, v)| v.clone()).collect();
+                f.insert("NS.keys".to_string(), a.refs(&keys));
+                f.insert("NS.objects".to_string(), a.refs(&values));
+            }),
+            Object::Error {
+                domain,
+                code,
+                user_info,
+            } => self.instance(&["NSError", "NSObject"], |a, f| {
+                let domain = a.encode(&Object::String(domain.clone()));
+                let user_info = a.encode(user_info);
+                f.insert("NSCode".to_string(), (*code).into());
+                f.insert("NSDomain".to_string(), Value::Uid(domain));
+                f.insert("NSUserInfo".to_string(), Value::Uid(user_info));
+            }),
+            Object::Instance(instance) => {
+                let uid = self.push(Value::Dictionary(Dictionary::new()));
+                let mut dict = Dictionary::new();
+                for (k, field) in instance.fields.iter() {
+                    let v = match field {
+                        Field::Value(v) => v.clone(),
+                        Field::Object(o) => Value::Uid(self.encode(o)),
+                    };
+                    dict.insert(k.clone(), v);
+                }
+                let class = self.class_of(instance.classes.clone());
+                dict.insert("$class".to_string(), Value::Uid(class));
+                self.objects[uid.0 as usize] = dict.into();
+                uid
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::plist::{
+        archive::{self, Archiver, Coder, Error, Field, Instance, Object, Unarchiver},
+        Date, Number, Value,
+    };
+
+    /// Fixtures laid out as `NSKeyedArchiver` writes them, see
+    /// `fixtures/README.md`.
+    const COLLECTIONS: &[u8] = include_bytes!("fixtures/archive_collections.bplist");
+    const ERROR: &[u8] = include_bytes!("fixtures/archive_error.bplist");
+    const CUSTOM: &[u8] = include_bytes!("fixtures/archive_custom.plist");
+
+    fn s(v: &str) -> Object {
+        Object::String(v.to_string())
+    }
+
+    fn collections() -> Object {
+        Object::Dictionary(vec![
+            (s("name"), s("cidre")),
+            (s("count"), Object::Number(Number::I64(3))),
+            (s("ratio"), Object::Number(Number::F64(0.5))),
+            (s("enabled"), Object::Boolean(true)),
+            (s("tags"), Object::Array(vec![s("a"), s("b")])),
+            (
+                s("unique"),
+                Object::Set(vec![Object::Number(Number::I64(7))]),
+            ),
+            (s("blob"), Object::Data(vec![0, 1, 0xff])),
+            (s("when"), Object::Date(Date(700_000_000.0))),
+            (s("nothing"), Object::Null),
+        ])
+    }
+
+    fn error() -> Object {
+        let mut uuid = [0u8; 16];
+        uuid.iter_mut()
+            .enumerate()
+            .for_each(|(i, b)| *b = i as u8 * 17);
+        Object::Error {
+            domain: "NSCocoaErrorDomain".to_string(),
+            code: 4,
+            user_info: Box::new(Object::Dictionary(vec![
+                (
+                    s("NSURL"),
+                    Object::Url {
+                        base: Box::new(Object::Nil),
+                        relative: "file:///tmp/x".to_string(),
+                    },
+                ),
+                (s("uuid"), Object::Uuid(uuid)),
+            ])),
+        }
+    }
+
+    #[test]
+    fn fixtures() {
+        for (bytes, expected) in [(COLLECTIONS, collections()), (ERROR, error())] {
+            let decoded = archive::from_bytes(bytes).unwrap();
+            assert_eq!(decoded, expected);
+
+            // same objects in the same order
+            let mut archiver = Archiver::new();
+            archiver.encode_top(archive::ROOT_KEY, &decoded);
+            assert_eq!(archiver.finish(), Value::from_bytes(bytes).unwrap());
+        }
+    }
+
+    #[test]
+    fn instances() {
+        let decoded = archive::from_bytes(CUSTOM).unwrap();
+        let Object::Instance(point) = &decoded else {
+            panic!("{decoded:?}");
+        };
+        assert_eq!(point.class_name(), "CDPoint");
+        assert_eq!(point.classes, ["CDPoint", "NSObject"]);
+        assert_eq!(point.fields["x"], Field::Value(3.into()));
+        assert_eq!(point.fields["y"], Field::Value((-4).into()));
+        // NSMutableString through the NSString decoder
+        assert_eq!(point.fields["label"], Field::Object(s("origin")));
+
+        let bytes = archive::to_bytes(&decoded).unwrap();
+        assert_eq!(archive::from_bytes(&bytes).unwrap(), decoded);
+
+        // with a decoder of the class
+        fn decode_point(c: &mut Coder) -> Result<Object, Error> {
+            let x = c.decode_i64("x").ok_or_else(|| c.invalid("has no x"))?;
+            let y = c.decode_i64("y").ok_or_else(|| c.invalid("has no y"))?;
+            let label = c.decode_object("label")?;
+            Ok(Object::Dictionary(vec![
+                (s("x"), Object::Number(x.into())),
+                (s("y"), Object::Number(y.into())),
+                (s("label"), label),
+            ]))
+        }
+        let mut unarchiver = Unarchiver::new();
+        unarchiver.register("CDPoint", decode_point);
+        let decoded = unarchiver.decode_bytes(CUSTOM).unwrap();
+        assert_eq!(decoded.get("y"), Some(&Object::Number(Number::I64(-4))));
+        assert_eq!(decoded.get("label"), Some(&s("origin")));
+
+        // registered for the superclass
+        let mut unarchiver = Unarchiver::new();
+        unarchiver.register("NSObject", |c| Ok(s(c.class_name())));
+        assert_eq!(unarchiver.decode_bytes(CUSTOM).unwrap(), s("CDPoint"));
+
+        let raw = Unarchiver::raw().decode_bytes(CUSTOM).unwrap();
+        let Object::Instance(Instance { fields, .. }) = raw else {
+            panic!("{raw:?}");
+        };
+        let Field::Object(Object::Instance(label)) = &fields["label"] else {
+            panic!("{fields:?}");
+        };
+        assert_eq!(label.class_name(), "NSMutableString");
+    }
+
+    #[test]
+    fn shared() {
+        let shared = Object::Array(vec![s("x")]);
+        let root = Object::Array(vec![shared.clone(), shared.clone(), s("x")]);
+        let value = {
+            let mut archiver = Archiver::new();
+            archiver.encode_top("root", &root);
+            archiver.finish()
+        };
+        let objects = value.get("$objects").and_then(Value::as_array).unwrap();
+        // $null, root, two inner arrays, "x" once, NSArray class once
+        assert_eq!(objects.len(), 6);
+        assert_eq!(Unarchiver::new().decode(&value).unwrap(), root);
+    }
+
+    #[test]
+    fn malformed() {
+        let archive = |objects: Vec<Value>| {
+            let mut top = BTreeMap::new();
+            top.insert("root".to_string(), Value::Uid(crate::plist::Uid(1)));
+            let mut res = BTreeMap::new();
+            res.insert("$archiver".to_string(), Value::from("NSKeyedArchiver"));
+            res.insert("$top".to_string(), top.into());
+            res.insert("$objects".to_string(), objects.into());
+            Value::from(res)
+        };
+        let class = |name: &str| {
+            let mut res = BTreeMap::new();
+            res.insert("$classname".to_string(), Value::from(name));
+            Value::from(res)
+        };
+        let instance = |fields: &[(&str, Value)]| {
+            let mut res: BTreeMap<_, _> = fields
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect();
+            res.insert("$class".to_string(), Value::Uid(crate::plist::Uid(2)));
+            Value::from(res)
+        };
+        let null = Value::from("$null");
+        let uid = |v| Value::Uid(crate::plist::Uid(v));
+        let u = Unarchiver::new();
+
+        assert_eq!(
+            u.decode(&Value::from(1)),
+            Err(Error::Format("no NSKeyedArchiver $archiver"))
+        );
+        let cycle = archive(vec![
+            null.clone(),
+            instance(&[("NS.objects", vec![uid(1)].into())]),
+            class("NSArray"),
+        ]);
+        assert_eq!(u.decode(&cycle), Err(Error::Cycle(1)));
+        let dangling = archive(vec![
+            null.clone(),
+            instance(&[("NS.objects", vec![uid(9)].into())]),
+            class("NSArray"),
+        ]);
+        assert_eq!(u.decode(&dangling), Err(Error::Ref(9)));
+        let uuid = archive(vec![
+            null.clone(),
+            instance(&[("NS.uuidbytes", vec![0u8; 4].into())]),
+            class("NSUUID"),
+        ]);
+        assert_eq!(
+            u.decode(&uuid),
+            Err(Error::Class {
+                class: "NSUUID".to_string(),
+                what: "has no 16 byte NS.uuidbytes"
+            })
+        );
+    }
+
+    /// Archives of Foundation read natively and the other way around.
+    #[cfg(all(target_vendor = "apple", feature = "ns"))]
+    mod foundation {
+        use crate::{
+            cf, define_obj_type, ns, objc,
+            plist::{
+                archive::{self, Archiver, Field, Object},
+                Date, Number, Value,
+            },
+        };
+
+        use super::s;
+
+        struct Point {
+            x: i64,
+            y: i64,
+            label: crate::arc::R<ns::String>,
+        }
+
+        define_obj_type!(CdPoint + ns::CodingImpl, Point, CDPoint);
+
+        impl ns::Coding for CdPoint {}
+
+        #[objc::add_methods]
+        impl ns::CodingImpl for CdPoint {
+            extern "C" fn impl_encode_with_coder(
+                &self,
+                _cmd: Option<&objc::Sel>,
+                coder: &mut ns::Coder,
+            ) {
+                let point = self.inner();
+                coder.encode_i64_for_key(point.x, ns::str!(c"x"));
+                coder.encode_i64_for_key(point.y, ns::str!(c"y"));
+                coder.encode_obj_for_key(Some(point.label.as_id_ref()), ns::str!(c"label"));
+            }
+        }
+
+        #[test]
+        fn basics() {
+            let value = ns::String::with_str("value");
+            let data = ns::KeyedArchiver::archived_data_with_root_obj(&value, true).unwrap();
+            assert_eq!(archive::from_bytes(data.as_slice()).unwrap(), s("value"));
+
+            let one = ns::Number::with_i64(1);
+            let two = ns::String::with_str("two");
+            let arr = ns::Array::<ns::Id>::from_slice(&[one.as_id_ref(), two.as_id_ref()]);
+            let data = ns::KeyedArchiver::archived_data_with_root_obj(&arr, false).unwrap();
+            assert_eq!(
+                archive::from_bytes(data.as_slice()).unwrap(),
+                Object::Array(vec![Object::Number(Number::I64(1)), s("two")])
+            );
+
+            let bytes = archive::to_bytes(&s("value")).unwrap();
+            let data = cf::Data::from_slice(&bytes).unwrap();
+            let read = ns::KeyedUnarchiver::unarchived_obj_of_cls(ns::String::cls(), data.as_ns())
+                .unwrap();
+            assert_eq!(read.to_string(), "value");
+        }
+
+        /// A graph of the common classes and an `NSCoding` class of our own.
+        #[test]
+        fn graph() {
+            let name = ns::String::with_str("cidre");
+            let tags = ns::Array::<ns::String>::from_slice(&[ns::str!(c"a"), ns::str!(c"b")]);
+            let blob = cf::Data::from_slice(&[0, 1, 0xff]).unwrap();
+            let when = ns::Date::with_time_interval_since_1970(
+                700_000_000.0 + ns::TIME_INTERVAL_SINCE_1970,
+            );
+            let uuid = ns::Uuid::new();
+            let point = CdPoint::with(Point {
+                x: 3,
+                y: -4,
+                label: ns::String::with_str("origin"),
+            });
+            let dict = ns::Dictionary::<ns::String, ns::Id>::with_keys_values(
+                &[
+                    ns::str!(c"name"),
+                    ns::str!(c"tags"),
+                    ns::str!(c"blob"),
+                    ns::str!(c"when"),
+                    ns::str!(c"uuid"),
+                    ns::str!(c"point"),
+                ],
+                &[
+                    name.as_id_ref(),
+                    tags.as_id_ref(),
+                    blob.as_ns().as_id_ref(),
+                    when.as_id_ref(),
+                    uuid.as_id_ref(),
+                    point.as_id_ref(),
+                ],
+            );
+            let data = ns::KeyedArchiver::archived_data_with_root_obj(&dict, false).unwrap();
+            let decoded = archive::from_bytes(data.as_slice()).unwrap();
+
+            assert_eq!(decoded.get("name"), Some(&s("cidre")));
+            assert_eq!(
+                decoded.get("tags"),
+                Some(&Object::Array(vec![s("a"), s("b")]))
+            );
+            assert_eq!(decoded.get("blob"), Some(&Object::Data(vec![0, 1, 0xff])));
+            assert_eq!(
+                decoded.get("when"),
+                Some(&Object::Date(Date(700_000_000.0)))
+            );
+            let Some(Object::Uuid(bytes)) = decoded.get("uuid") else {
+                panic!("{decoded:?}");
+            };
+            let hex: String = bytes.iter().map(|b| format!("{b:02X}")).collect();
+            assert_eq!(hex, uuid.string().to_string().replace('-', ""));
+
+            let Some(Object::Instance(point)) = decoded.get("point") else {
+                panic!("{decoded:?}");
+            };
+            assert_eq!(point.classes, ["CDPoint", "NSObject"]);
+            assert_eq!(point.fields["x"], Field::Value(3.into()));
+            assert_eq!(point.fields["y"], Field::Value((-4).into()));
+            assert_eq!(point.fields["label"], Field::Object(s("origin")));
+
+            // same objects in the same order as Foundation wrote them
+            let mut archiver = Archiver::new();
+            archiver.encode_top(archive::ROOT_KEY, &decoded);
+            assert_eq!(
+                archiver.finish(),
+                Value::from_bytes(data.as_slice()).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,470 @@
+//! `bplist00`, CoreFoundation's binary property list.
+//!
+//! Objects follow the 8 byte magic, then a table of their offsets and a
+//! 32 byte trailer telling the table position and the byte sizes of
+//! offsets and object references.
+
+use std::collections::HashMap;
+
+use super::{Date, Dictionary, Error, Format, Number, Uid, Value, MAX_DEPTH};
+
+pub(super) const MAGIC: &[u8] = b"bplist00";
+
+const TRAILER_LEN: usize = 32;
+
+const FALSE: u8 = 0x08;
+const TRUE: u8 = 0x09;
+const INT: u8 = 0x10;
+const REAL: u8 = 0x20;
+const DATE: u8 = 0x33;
+const DATA: u8 = 0x40;
+const ASCII_STRING: u8 = 0x50;
+const UTF16_STRING: u8 = 0x60;
+const UID: u8 = 0x80;
+const ARRAY: u8 = 0xA0;
+const SET: u8 = 0xC0;
+const DICT: u8 = 0xD0;
+
+fn syntax(pos: usize, what: &'static str) -> Error {
+    Error::Syntax {
+        format: Format::BinaryV1_0,
+        pos,
+        what,
+    }
+}
+
+/// Big endian unsigned integer of 1 to 8 bytes.
+fn uint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0, |acc, b| acc << 8 | *b as u64)
+}
+
+/// Fewest bytes holding `n`.
+fn uint_len(n: u64) -> usize {
+    (n.max(1).ilog2() as usize) / 8 + 1
+}
+
+pub(super) fn read(data: &[u8]) -> Result<Value, Error> {
+    if data.len() < MAGIC.len() + TRAILER_LEN {
+        return Err(Error::Truncated);
+    }
+    let trailer = &data[data.len() - TRAILER_LEN..];
+    let offset_size = trailer[6] as usize;
+    let ref_size = trailer[7] as usize;
+    let count = uint(&trailer[8..16]);
+    let top = uint(&trailer[16..24]);
+    let table = uint(&trailer[24..32]);
+    let pos = data.len() - TRAILER_LEN;
+    if !(1..=8).contains(&offset_size) || !(1..=8).contains(&ref_size) {
+        return Err(syntax(pos, "invalid offset or reference size"));
+    }
+    if top >= count {
+        return Err(syntax(pos, "top object out of range"));
+    }
+    let table_len = count
+        .checked_mul(offset_size as u64)
+        .and_then(|l| l.checked_add(table))
+        .ok_or(Error::Truncated)?;
+    if table < MAGIC.len() as u64 || table_len > pos as u64 {
+        return Err(Error::Truncated);
+    }
+    let mut reader = Reader {
+        data: &data[..pos],
+        table: table as usize,
+        offset_size,
+        ref_size,
+        count,
+        stack: Vec::new(),
+    };
+    reader.value(top)
+}
+
+struct Reader<'a> {
+    /// File without the trailer.
+    data: &'a [u8],
+    table: usize,
+    offset_size: usize,
+    ref_size: usize,
+    count: u64,
+
+    /// Containers being read, to catch reference cycles.
+    stack: Vec<u64>,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&self, pos: usize, len: usize) -> Result<&'a [u8], Error> {
+        let end = pos.checked_add(len).ok_or(Error::Truncated)?;
+        self.data.get(pos..end).ok_or(Error::Truncated)
+    }
+
+    fn offset(&self, index: u64) -> Result<usize, Error> {
+        if index >= self.count {
+            return Err(Error::Truncated);
+        }
+        let pos = self.table + index as usize * self.offset_size;
+        let offset = uint(self.bytes(pos, self.offset_size)?) as usize;
+        if offset < MAGIC.len() || offset >= self.table {
+            return Err(Error::Truncated);
+        }
+        Ok(offset)
+    }
+
+    /// Count in the marker's low nibble, or in the int object after it
+    /// when the nibble is 0xF. Returns the count and where the payload
+    /// starts.
+    fn count_at(&self, pos: usize) -> Result<(usize, usize), Error> {
+        let marker = self.bytes(pos, 1)?[0];
+        if marker & 0x0F != 0x0F {
+            return Ok(((marker & 0x0F) as usize, pos + 1));
+        }
+        let int = self.bytes(pos + 1, 1)?[0];
+        if int & 0xF0 != INT || int & 0x0F > 3 {
+            return Err(syntax(pos + 1, "invalid object count"));
+        }
+        let len = 1 << (int & 0x0F);
+        let count = uint(self.bytes(pos + 2, len)?);
+        let count = usize::try_from(count).map_err(|_| Error::Truncated)?;
+        Ok((count, pos + 2 + len))
+    }
+
+    fn refs(&self, pos: usize, count: usize) -> Result<Vec<u64>, Error> {
+        let len = count.checked_mul(self.ref_size).ok_or(Error::Truncated)?;
+        let bytes = self.bytes(pos, len)?;
+        Ok(bytes.chunks(self.ref_size).map(uint).collect())
+    }
+
+    fn value(&mut self, index: u64) -> Result<Value, Error> {
+        let pos = self.offset(index)?;
+        let marker = self.bytes(pos, 1)?[0];
+        let nibble = (marker & 0x0F) as usize;
+        let res = match marker & 0xF0 {
+            0x00 => match marker {
+                FALSE => Value::Boolean(false),
+                TRUE => Value::Boolean(true),
+                _ => return Err(syntax(pos, "unsupported object")),
+            },
+            INT => {
+                let bytes = self.bytes(pos + 1, 1 << nibble.min(5))?;
+                let num = match nibble {
+                    // 1, 2 and 4 byte ints are unsigned, 8 byte ones signed
+                    0..=3 => Number::I64(uint(bytes) as i64),
+                    4 => {
+                        let high = uint(&bytes[..8]) as i64;
+                        let low = uint(&bytes[8..]);
+                        match high {
+                            0 => Number::from(low),
+                            -1 if (low as i64) < 0 => Number::I64(low as i64),
+                            _ => return Err(syntax(pos, "integer over 64 bits")),
+                        }
+                    }
+                    _ => return Err(syntax(pos, "invalid integer size")),
+                };
+                Value::Number(num)
+            }
+            REAL => Value::Number(Number::F64(match nibble {
+                2 => f32::from_be_bytes(self.bytes(pos + 1, 4)?.try_into().unwrap()) as f64,
+                3 => f64::from_be_bytes(self.bytes(pos + 1, 8)?.try_into().unwrap()),
+                _ => return Err(syntax(pos, "invalid real size")),
+            })),
+            0x30 if marker == DATE => {
+                let bytes = self.bytes(pos + 1, 8)?;
+                Value::Date(Date(f64::from_be_bytes(bytes.try_into().unwrap())))
+            }
+            DATA => {
+                let (len, start) = self.count_at(pos)?;
+                Value::Data(self.bytes(start, len)?.to_vec())
+            }
+            ASCII_STRING => {
+                let (len, start) = self.count_at(pos)?;
+                let bytes = self.bytes(start, len)?;
+                if !bytes.is_ascii() {
+                    return Err(syntax(pos, "non ASCII string"));
+                }
+                Value::String(String::from_utf8(bytes.to_vec()).unwrap())
+            }
+            UTF16_STRING => {
+                let (len, start) = self.count_at(pos)?;
+                let len = len.checked_mul(2).ok_or(Error::Truncated)?;
+                let units = self
+                    .bytes(start, len)?
+                    .chunks(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]));
+                let s = char::decode_utf16(units)
+                    .collect::<Result<String, _>>()
+                    .map_err(|_| syntax(pos, "invalid UTF-16 string"))?;
+                Value::String(s)
+            }
+            UID if nibble < 8 => Value::Uid(Uid(uint(self.bytes(pos + 1, nibble + 1)?))),
+            ARRAY | SET | DICT => {
+                if self.stack.contains(&index) {
+                    return Err(syntax(pos, "object reference cycle"));
+                }
+                if self.stack.len() >= MAX_DEPTH {
+                    return Err(syntax(pos, "objects nested too deeply"));
+                }
+                self.stack.push(index);
+                let res = self.container(marker & 0xF0, pos);
+                self.stack.pop();
+                res?
+            }
+            _ => return Err(syntax(pos, "unsupported object")),
+        };
+        Ok(res)
+    }
+
+    fn container(&mut self, kind: u8, pos: usize) -> Result<Value, Error> {
+        let (count, start) = self.count_at(pos)?;
+        if kind != DICT {
+            let refs = self.refs(start, count)?;
+            let items = refs
+                .into_iter()
+                .map(|r| self.value(r))
+                .collect::<Result<_, _>>()?;
+            return Ok(Value::Array(items));
+        }
+        let keys = self.refs(start, count)?;
+        let values = self.refs(start + count * self.ref_size, count)?;
+        let mut res = Dictionary::new();
+        for (k, v) in keys.into_iter().zip(values) {
+            let Value::String(key) = self.value(k)? else {
+                return Err(syntax(pos, "non string dictionary key"));
+            };
+            let value = self.value(v)?;
+            res.insert(key, value);
+        }
+        Ok(Value::Dictionary(res))
+    }
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>, Error> {
+    let mut objects = Objects::default();
+    objects.add(value);
+    let count = objects.list.len() as u64;
+    let ref_size = uint_len(count - 1);
+
+    let mut res = MAGIC.to_vec();
+    let mut offsets = Vec::with_capacity(objects.list.len());
+    for obj in objects.list.iter() {
+        offsets.push(res.len() as u64);
+        match obj {
+            Object::Value(v) => write_scalar(&mut res, v),
+            Object::String(s) => write_string(&mut res, s),
+            Object::Array(refs) => {
+                write_marker(&mut res, ARRAY, refs.len());
+                write_refs(&mut res, refs, ref_size);
+            }
+            Object::Dictionary(keys, values) => {
+                write_marker(&mut res, DICT, keys.len());
+                write_refs(&mut res, keys, ref_size);
+                write_refs(&mut res, values, ref_size);
+            }
+        }
+    }
+
+    let table = res.len() as u64;
+    let offset_size = uint_len(table);
+    write_refs(&mut res, &offsets, offset_size);
+
+    res.extend_from_slice(&[0; 6]);
+    res.push(offset_size as u8);
+    res.push(ref_size as u8);
+    res.extend_from_slice(&count.to_be_bytes());
+    res.extend_from_slice(&0u64.to_be_bytes());
+    res.extend_from_slice(&table.to_be_bytes());
+    Ok(res)
+}
+
+enum Object<'a> {
+    Value(&'a Value),
+    String(&'a str),
+    Array(Vec<u64>),
+    Dictionary(Vec<u64>, Vec<u64>),
+}
+
+/// Values flattened into the object list, the top value first.
+#[derive(Default)]
+struct Objects<'a> {
+    list: Vec<Object<'a>>,
+    strings: HashMap<&'a str, u64>,
+}
+
+impl<'a> Objects<'a> {
+    fn push(&mut self, obj: Object<'a>) -> u64 {
+        self.list.push(obj);
+        self.list.len() as u64 - 1
+    }
+
+    fn add_str(&mut self, s: &'a str) -> u64 {
+        if let Some(index) = self.strings.get(s) {
+            return *index;
+        }
+        let index = self.push(Object::String(s));
+        self.strings.insert(s, index);
+        index
+    }
+
+    fn add(&mut self, value: &'a Value) -> u64 {
+        match value {
+            Value::String(s) => self.add_str(s),
+            Value::Array(items) => {
+                let index = self.push(Object::Array(Vec::new()));
+                let refs = items.iter().map(|v| self.add(v)).collect();
+                self.list[index as usize] = Object::Array(refs);
+                index
+            }
+            Value::Dictionary(dict) => {
+                let index = self.push(Object::Array(Vec::new()));
+                let keys = dict.keys().map(|k| self.add_str(k)).collect();
+                let values = dict.values().map(|v| self.add(v)).collect();
+                self.list[index as usize] = Object::Dictionary(keys, values);
+                index
+            }
+            v => self.push(Object::Value(v)),
+        }
+    }
+}
+
+fn write_int(buf: &mut Vec<u8>, v: u64) {
+    let len = uint_len(v).next_power_of_two();
+    buf.push(INT | len.ilog2() as u8);
+    buf.extend_from_slice(&v.to_be_bytes()[8 - len..]);
+}
+
+fn write_marker(buf: &mut Vec<u8>, kind: u8, count: usize) {
+    if count < 0x0F {
+        buf.push(kind | count as u8);
+    } else {
+        buf.push(kind | 0x0F);
+        write_int(buf, count as u64);
+    }
+}
+
+fn write_refs(buf: &mut Vec<u8>, refs: &[u64], size: usize) {
+    for r in refs {
+        buf.extend_from_slice(&r.to_be_bytes()[8 - size..]);
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    if s.is_ascii() {
+        write_marker(buf, ASCII_STRING, s.len());
+        buf.extend_from_slice(s.as_bytes());
+    } else {
+        let units: Vec<u16> = s.encode_utf16().collect();
+        write_marker(buf, UTF16_STRING, units.len());
+        for u in units {
+            buf.extend_from_slice(&u.to_be_bytes());
+        }
+    }
+}
+
+fn write_scalar(buf: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Boolean(false) => buf.push(FALSE),
+        Value::Boolean(true) => buf.push(TRUE),
+        Value::Number(Number::I64(v)) if *v < 0 => {
+            buf.push(INT | 3);
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        Value::Number(Number::I64(v)) => write_int(buf, *v as u64),
+        Value::Number(Number::U64(v)) => {
+            buf.push(INT | 4);
+            buf.extend_from_slice(&0u64.to_be_bytes());
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        Value::Number(Number::F64(v)) => {
+            buf.push(REAL | 3);
+            buf.extend_from_slice(&v.to_be_bytes());
+        }
+        Value::Date(d) => {
+            buf.push(DATE);
+            buf.extend_from_slice(&d.0.to_be_bytes());
+        }
+        Value::Data(d) => {
+            write_marker(buf, DATA, d.len());
+            buf.extend_from_slice(d);
+        }
+        Value::Uid(Uid(v)) => {
+            let len = uint_len(*v);
+            buf.push(UID | (len - 1) as u8);
+            buf.extend_from_slice(&v.to_be_bytes()[8 - len..]);
+        }
+        Value::String(_) | Value::Array(_) | Value::Dictionary(_) => {
+            unreachable!("flattened into objects")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plist::{Dictionary, Error, Format, Value};
+
+    /// `{"a" = (1, "b"); "c" = <00ff>;}` with the objects in the order
+    /// CoreFoundation flattens them.
+    const FLAT: &[u8] = b"bplist00\xd2\x01\x02\x03\x06QaQc\xa2\x04\x05\x10\x01QbB\x00\xff\
+        \x08\x0d\x0f\x11\x14\x16\x18\
+        \x00\x00\x00\x00\x00\x00\x01\x01\
+        \x00\x00\x00\x00\x00\x00\x00\x07\
+        \x00\x00\x00\x00\x00\x00\x00\x00\
+        \x00\x00\x00\x00\x00\x00\x00\x1b";
+
+    fn expected() -> Value {
+        let mut dict = Dictionary::new();
+        dict.insert("a".into(), vec![Value::from(1), "b".into()].into());
+        dict.insert("c".into(), vec![0u8, 0xff].into());
+        dict.into()
+    }
+
+    #[test]
+    fn flat() {
+        assert_eq!(Value::from_bytes(FLAT).unwrap(), expected());
+        let written = expected().to_bytes(Format::BinaryV1_0).unwrap();
+        assert_eq!(Value::from_bytes(&written).unwrap(), expected());
+        assert_eq!(written, FLAT);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(super::uint_len(0), 1);
+        assert_eq!(super::uint_len(255), 1);
+        assert_eq!(super::uint_len(256), 2);
+        assert_eq!(super::uint_len(u64::MAX), 8);
+
+        let long: Vec<Value> = (0..300).map(|i| Value::from(i * 1000)).collect();
+        let value = Value::from(long);
+        let bytes = value.to_bytes(Format::BinaryV1_0).unwrap();
+        assert_eq!(bytes[bytes.len() - 25], 2);
+        assert_eq!(Value::from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn shared_strings() {
+        let value = Value::from(vec![Value::from("same"); 3]);
+        let bytes = value.to_bytes(Format::BinaryV1_0).unwrap();
+        // array and one string
+        assert_eq!(bytes[bytes.len() - 17], 2);
+        assert_eq!(Value::from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(Value::from_bytes(b"bplist00"), Err(Error::Truncated));
+        let mut bytes = FLAT.to_vec();
+        let len = bytes.len();
+        bytes[len - 1] = 0xff;
+        assert_eq!(Value::from_bytes(&bytes), Err(Error::Truncated));
+
+        // array holding itself
+        let mut cycle = b"bplist00\xa1\x00\x08".to_vec();
+        cycle.extend_from_slice(&[0, 0, 0, 0, 0, 0, 1, 1]);
+        cycle.extend_from_slice(&1u64.to_be_bytes());
+        cycle.extend_from_slice(&0u64.to_be_bytes());
+        cycle.extend_from_slice(&10u64.to_be_bytes());
+        assert!(matches!(
+            Value::from_bytes(&cycle),
+            Err(Error::Syntax {
+                what: "object reference cycle",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{arc, cf};
+
+use super::{Date, Dictionary, Format, Number, Value};
+
+impl From<Format> for cf::PlistFormat {
+    #[inline]
+    fn from(value: Format) -> Self {
+        match value {
+            Format::OpenStep => Self::OpenStep,
+            Format::XmlV1_0 => Self::XmlV1_0,
+            Format::BinaryV1_0 => Self::BinaryV1_0,
+        }
+    }
+}
+
+impl From<cf::PlistFormat> for Format {
+    #[inline]
+    fn from(value: cf::PlistFormat) -> Self {
+        match value {
+            cf::PlistFormat::OpenStep => Self::OpenStep,
+            cf::PlistFormat::XmlV1_0 => Self::XmlV1_0,
+            cf::PlistFormat::BinaryV1_0 => Self::BinaryV1_0,
+        }
+    }
+}
+
+/// Upcast of any property list type, like `cf::Boolean::as_prop_list`.
+fn plist<T: arc::Release>(value: arc::R<T>) -> arc::R<cf::Plist> {
+    unsafe { std::mem::transmute(value) }
+}
+
+impl Value {
+    /// `cf::Plist` of the value.
+    ///
+    /// Integers over `i64::MAX` and UIDs need the `private` feature, they
+    /// are `CFNumber`s of `kCFNumberSInt128Type` and `CFKeyedArchiverUID`s.
+    /// UIDs over `u32::MAX` have no CoreFoundation counterpart. Both give
+    /// `None` when not representable.
+    pub fn to_cf(&self) -> Option<arc::R<cf::Plist>> {
+        let res = match self {
+            Self::String(v) => plist(cf::String::from_str(v)),
+            Self::Data(v) => plist(cf::Data::from_slice(v)?),
+            Self::Number(Number::I64(v)) => plist(cf::Number::from_i64(*v)),
+            #[cfg(feature = "private")]
+            Self::Number(Number::U64(v)) => plist(cf::Number::from_i128(*v as i128)),
+            #[cfg(not(feature = "private"))]
+            Self::Number(Number::U64(_)) => return None,
+            Self::Number(Number::F64(v)) => plist(cf::Number::from_f64(*v)),
+            Self::Boolean(v) => {
+                let b = if *v {
+                    cf::Boolean::value_true()
+                } else {
+                    cf::Boolean::value_false()
+                };
+                b.as_prop_list().retained()
+            }
+            Self::Date(v) => plist(cf::Date::new_at(v.0)),
+            Self::Array(items) => {
+                let items = items.iter().map(Value::to_cf).collect::<Option<Vec<_>>>()?;
+                let refs: Vec<&cf::Plist> = items.iter().map(|v| &**v).collect();
+                plist(cf::ArrayOf::<cf::Plist>::from_slice(&refs))
+            }
+            Self::Dictionary(dict) => {
+                let mut res = cf::DictionaryMut::with_capacity(dict.len());
+                for (k, v) in dict {
+                    res.insert(&cf::String::from_str(k), &v.to_cf()?);
+                }
+                plist(res)
+            }
+            #[cfg(feature = "private")]
+            Self::Uid(uid) => unsafe {
+                _CFKeyedArchiverUIDCreate(None, u32::try_from(uid.0).ok()?)?
+            },
+            #[cfg(not(feature = "private"))]
+            Self::Uid(_) => return None,
+        };
+        Some(res)
+    }
+
+    /// Value of a `cf::Plist`, `None` for types that aren't property list
+    /// types, 128-bit integers over 64 bits, and UIDs without the
+    /// `private` feature.
+    pub fn from_cf(value: &cf::Plist) -> Option<Self> {
+        if let Some(v) = value.try_as_string() {
+            return Some(Self::String(v.to_string()));
+        }
+        if let Some(v) = value.try_as_data() {
+            return Some(Self::Data(v.as_slice().to_vec()));
+        }
+        if let Some(v) = value.try_as_number() {
+            if v.is_float_type() {
+                return Some(Self::Number(Number::F64(v.to_f64()?)));
+            }
+            #[cfg(feature = "private")]
+            if v.number_type() == cf::NumberType::I128 {
+                let v = v.to_i128()?;
+                return match i64::try_from(v) {
+                    Ok(v) => Some(Self::Number(Number::I64(v))),
+                    Err(_) => u64::try_from(v).ok().map(|v| Self::Number(v.into())),
+                };
+            }
+            return Some(Self::Number(Number::I64(v.to_i64()?)));
+        }
+        if let Some(v) = value.try_as_boolean() {
+            return Some(Self::Boolean(v.value()));
+        }
+        if let Some(v) = value.try_as_date() {
+            return Some(Self::Date(Date(v.abs_time())));
+        }
+        if let Some(v) = value.try_as_array() {
+            let items = v.iter().map(Self::from_cf).collect::<Option<_>>()?;
+            return Some(Self::Array(items));
+        }
+        if let Some(v) = value.try_as_raw_dictionary() {
+            let (keys, values) = v.keys_with_values();
+            let mut res = Dictionary::new();
+            for (k, v) in keys.into_iter().zip(values) {
+                if k.get_type_id() != cf::String::type_id() {
+                    return None;
+                }
+                let k: &cf::String = unsafe { std::mem::transmute(k) };
+                let v: &cf::Plist = unsafe { std::mem::transmute(v) };
+                res.insert(k.to_string(), Self::from_cf(v)?);
+            }
+            return Some(Self::Dictionary(res));
+        }
+        #[cfg(feature = "private")]
+        if value.get_type_id() == unsafe { _CFKeyedArchiverUIDGetTypeID() } {
+            let uid = unsafe { _CFKeyedArchiverUIDGetValue(value) };
+            return Some(Self::Uid(super::Uid(uid as u64)));
+        }
+        None
+    }
+}
+
+#[cfg(feature = "private")]
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C-unwind" {
+    fn _CFKeyedArchiverUIDCreate(
+        allocator: Option<&cf::Allocator>,
+        value: u32,
+    ) -> Option<arc::R<cf::Plist>>;
+    fn _CFKeyedArchiverUIDGetValue(uid: &cf::Plist) -> u32;
+    fn _CFKeyedArchiverUIDGetTypeID() -> cf::TypeId;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{cf, plist};
+
+    #[test]
+    fn basics() {
+        let mut value = plist::tests::sample();
+        if !cfg!(feature = "private") {
+            let dict = value.as_dictionary_mut().unwrap();
+            dict.remove("big");
+            let nested = dict.get_mut("nested").unwrap();
+            nested.as_dictionary_mut().unwrap().remove("uid");
+        }
+        let cf = value.to_cf().unwrap();
+        assert_eq!(plist::Value::from_cf(&cf).unwrap(), value);
+
+        // bytes written by CoreFoundation read natively and back
+        for format in [cf::PlistFormat::XmlV1_0, cf::PlistFormat::BinaryV1_0] {
+            let data = cf.to_cf_data(format).unwrap();
+            let (read, f) = plist::Value::from_bytes_with_format(data.as_slice()).unwrap();
+            assert_eq!(f, format.into());
+            assert_eq!(read, value);
+
+            let bytes = value.to_bytes(f).unwrap();
+            let data = cf::Data::from_slice(&bytes).unwrap();
+            let cf_read = cf::Plist::from_data(&data, Default::default()).unwrap();
+            assert!(cf_read.equal(&cf));
+        }
+    }
+}
@@ -0,0 +1,464 @@
+use serde::{
+    de::{self, value::MapDeserializer, value::SeqDeserializer, IntoDeserializer, Visitor},
+    forward_to_deserialize_any, Deserialize,
+};
+
+use super::{Date, Dictionary, Error, Number, Uid, Value, DATE_KEY, UID_KEY};
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Deserializes `T` out of a property list [`Value`].
+///
+/// Enums are read from strings and single key dictionaries, the way
+/// [`to_value`](super::to_value) writes them.
+pub fn from_value<T: de::DeserializeOwned>(value: Value) -> Result<T, Error> {
+    T::deserialize(value)
+}
+
+/// Deserializes `T` out of a property list of any format.
+pub fn from_bytes<T: de::DeserializeOwned>(data: &[u8]) -> Result<T, Error> {
+    from_value(Value::from_bytes(data)?)
+}
+
+/// Single key map of `key` to `value`, how dates and UIDs look to
+/// `deserialize_any`.
+fn visit_tagged<'de, V, T>(visitor: V, key: &'static str, value: T) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+    T: IntoDeserializer<'de, Error>,
+{
+    let mut map = MapDeserializer::new(std::iter::once((key, value)));
+    let res = visitor.visit_map(&mut map)?;
+    map.end()?;
+    Ok(res)
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self {
+            Value::String(v) => visitor.visit_string(v),
+            Value::Data(v) => visitor.visit_byte_buf(v),
+            Value::Number(Number::I64(v)) => visitor.visit_i64(v),
+            Value::Number(Number::U64(v)) => visitor.visit_u64(v),
+            Value::Number(Number::F64(v)) => visitor.visit_f64(v),
+            Value::Boolean(v) => visitor.visit_bool(v),
+            Value::Date(v) => visit_tagged(visitor, DATE_KEY, v.0),
+            Value::Array(v) => {
+                let mut seq = SeqDeserializer::new(v.into_iter());
+                let res = visitor.visit_seq(&mut seq)?;
+                seq.end()?;
+                Ok(res)
+            }
+            Value::Dictionary(v) => {
+                let mut map = MapDeserializer::new(v.into_iter());
+                let res = visitor.visit_map(&mut map)?;
+                map.end()?;
+                Ok(res)
+            }
+            Value::Uid(v) => visit_tagged(visitor, UID_KEY, v.0),
+        }
+    }
+
+    /// Values present in a plist are always `Some`.
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match (name, self) {
+            (DATE_KEY, Value::Date(d)) => visitor.visit_f64(d.0),
+            (UID_KEY, Value::Uid(u)) => visitor.visit_u64(u.0),
+            (_, v) => visitor.visit_newtype_struct(v),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Dictionary(dict) if dict.len() == 1 => {
+                let (variant, value) = dict.into_iter().next().unwrap();
+                visitor.visit_enum(Enum { variant, value })
+            }
+            v => Err(de::Error::invalid_type(
+                v.unexpected(),
+                &"string or single key dictionary",
+            )),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+impl Value {
+    fn unexpected(&self) -> de::Unexpected<'_> {
+        match self {
+            Value::String(v) => de::Unexpected::Str(v),
+            Value::Data(v) => de::Unexpected::Bytes(v),
+            Value::Number(Number::I64(v)) => de::Unexpected::Signed(*v),
+            Value::Number(Number::U64(v)) => de::Unexpected::Unsigned(*v),
+            Value::Number(Number::F64(v)) => de::Unexpected::Float(*v),
+            Value::Boolean(v) => de::Unexpected::Bool(*v),
+            Value::Date(_) => de::Unexpected::Other("date"),
+            Value::Array(_) => de::Unexpected::Seq,
+            Value::Dictionary(_) => de::Unexpected::Map,
+            Value::Uid(_) => de::Unexpected::Other("uid"),
+        }
+    }
+}
+
+/// Variant of a single key dictionary.
+struct Enum {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> de::EnumAccess<'de> for Enum {
+    type Error = Error;
+    type Variant = Value;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Value), Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Value {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(de::Error::invalid_type(self.unexpected(), &"unit variant"))
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Error> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a property list value")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(v.into())
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, d: D) -> Result<Value, D::Error> {
+        Value::deserialize(d)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Value, D::Error> {
+        Value::deserialize(d)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut res = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+        while let Some(v) = seq.next_element()? {
+            res.push(v);
+        }
+        Ok(Value::Array(res))
+    }
+
+    /// Single key `CF$UID` and `CF$Date` dictionaries of numbers come back
+    /// as UIDs and dates.
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut dict = Dictionary::new();
+        while let Some((k, v)) = map.next_entry::<String, Value>()? {
+            dict.insert(k, v);
+        }
+        if dict.len() == 1 {
+            let (k, v) = dict.iter().next().unwrap();
+            match (k.as_str(), v) {
+                (UID_KEY, Value::Number(n)) => {
+                    if let Some(uid) = n.as_u64() {
+                        return Ok(Value::Uid(Uid(uid)));
+                    }
+                }
+                (DATE_KEY, Value::Number(n)) => return Ok(Value::Date(Date(n.as_f64()))),
+                _ => {}
+            }
+        }
+        Ok(Value::Dictionary(dict))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_any(ValueVisitor)
+    }
+}
+
+struct DateVisitor;
+
+impl<'de> Visitor<'de> for DateVisitor {
+    type Value = Date;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("seconds since 2001-01-01")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Date, E> {
+        Ok(Date(v as f64))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Date, E> {
+        Ok(Date(v as f64))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Date, E> {
+        Ok(Date(v))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Date, D::Error> {
+        f64::deserialize(d).map(Date)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Date, A::Error> {
+        match map.next_entry::<String, f64>()? {
+            Some((k, v)) if k == DATE_KEY && map.next_key::<String>()?.is_none() => Ok(Date(v)),
+            _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+        }
+    }
+}
+
+/// Reads plist dates, `CF$Date` newtypes and plain numbers of seconds.
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_newtype_struct(DATE_KEY, DateVisitor)
+    }
+}
+
+struct UidVisitor;
+
+impl<'de> Visitor<'de> for UidVisitor {
+    type Value = Uid;
+
+    fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("a CF$UID")
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Uid, E> {
+        u64::try_from(v)
+            .map(Uid)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Uid, E> {
+        Ok(Uid(v))
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(self, d: D) -> Result<Uid, D::Error> {
+        u64::deserialize(d).map(Uid)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Uid, A::Error> {
+        match map.next_entry::<String, u64>()? {
+            Some((k, v)) if k == UID_KEY && map.next_key::<String>()?.is_none() => Ok(Uid(v)),
+            _ => Err(de::Error::invalid_type(de::Unexpected::Map, &self)),
+        }
+    }
+}
+
+/// Reads plist UIDs, `CF$UID` newtypes and dictionaries, and plain numbers.
+impl<'de> Deserialize<'de> for Uid {
+    fn deserialize<D: de::Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+        d.deserialize_newtype_struct(UID_KEY, UidVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use crate::plist::{self, Date, Dictionary, Error, Format, Uid, Value};
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Kind {
+        App,
+        Extension(String),
+        Framework { version: u32 },
+        Pair(u8, u8),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "PascalCase")]
+    struct Entry {
+        name: String,
+        #[serde(with = "serde_bytes_vec")]
+        hash: Vec<u8>,
+        size: u64,
+        ratio: f32,
+        hidden: bool,
+        created: Date,
+        object: Uid,
+        kinds: Vec<Kind>,
+        parent: Option<Box<Entry>>,
+        extra: std::collections::BTreeMap<String, Value>,
+    }
+
+    /// `Vec<u8>` as plist data rather than an array of numbers.
+    mod serde_bytes_vec {
+        use serde::Deserialize;
+
+        use crate::plist::Value;
+
+        pub fn serialize<S: serde::Serializer>(v: &[u8], s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_bytes(v)
+        }
+
+        pub fn deserialize<'de, D: serde::Deserializer<'de>>(d: D) -> Result<Vec<u8>, D::Error> {
+            match Value::deserialize(d)? {
+                Value::Data(v) => Ok(v),
+                v => Err(serde::de::Error::custom(format!(
+                    "{} is not data",
+                    v.type_name()
+                ))),
+            }
+        }
+    }
+
+    fn entry() -> Entry {
+        let mut extra = Dictionary::new();
+        extra.insert("k".into(), vec![Value::from(1), "v".into()].into());
+        Entry {
+            name: "root".into(),
+            hash: vec![1, 2, 3],
+            size: u64::MAX,
+            ratio: 0.5,
+            hidden: false,
+            created: Date(100.0),
+            object: Uid(9),
+            kinds: vec![
+                Kind::App,
+                Kind::Extension("share".into()),
+                Kind::Framework { version: 3 },
+                Kind::Pair(1, 2),
+            ],
+            parent: None,
+            extra,
+        }
+    }
+
+    #[test]
+    fn to_value() {
+        let value = plist::to_value(&entry()).unwrap();
+        let dict = value.as_dictionary().unwrap();
+        assert!(!dict.contains_key("Parent"));
+        assert_eq!(dict["Hash"], Value::Data(vec![1, 2, 3]));
+        assert_eq!(dict["Created"], Value::Date(Date(100.0)));
+        assert_eq!(dict["Object"], Value::Uid(Uid(9)));
+        let kinds = dict["Kinds"].as_array().unwrap();
+        assert_eq!(kinds[0], Value::from("App"));
+        assert_eq!(kinds[1].get("Extension"), Some(&Value::from("share")));
+        assert_eq!(
+            kinds[2].get("Framework").and_then(|f| f.get("version")),
+            Some(&Value::from(3))
+        );
+        assert_eq!(plist::to_value(&None::<u8>), Err(Error::None));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let mut nested = entry();
+        nested.parent = Some(Box::new(entry()));
+        for format in [Format::BinaryV1_0, Format::XmlV1_0] {
+            let bytes = plist::to_bytes(&nested, format).unwrap();
+            let read: Entry = plist::from_bytes(&bytes).unwrap();
+            assert_eq!(read, nested, "{format:?}");
+        }
+    }
+
+    #[test]
+    fn value_through_serde() {
+        let value = crate::plist::tests::sample();
+        assert_eq!(plist::to_value(&value).unwrap(), value);
+        let back: Value = plist::from_value(value.clone()).unwrap();
+        assert_eq!(back, value);
+    }
+
+    #[test]
+    fn type_errors() {
+        let res = plist::from_value::<u8>(Value::from(300));
+        assert!(matches!(res, Err(Error::Custom(_))));
+        let res = plist::from_value::<Kind>(Value::from(vec![Value::from(1)]));
+        assert!(matches!(res, Err(Error::Custom(_))));
+        let res = plist::from_value::<Uid>(Value::from(-1));
+        assert!(matches!(res, Err(Error::Custom(_))));
+        assert_eq!(plist::from_value::<Uid>(Value::from(4)), Ok(Uid(4)));
+        assert_eq!(plist::from_value::<Date>(Value::from(4)), Ok(Date(4.0)));
+    }
+}
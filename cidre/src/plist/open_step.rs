@@ -0,0 +1,485 @@
+//! OpenStep (NeXTSTEP) text property lists and `.strings` files.
+//!
+//! The format only knows strings, data, arrays and dictionaries. Numbers,
+//! booleans and dates use the GNUstep `<*I…>`, `<*R…>`, `<*BY>`/`<*BN>`
+//! and `<*D…>` forms, and there is no way to write UIDs.
+
+use super::{xml, Date, Dictionary, Error, Format, Number, Value, MAX_DEPTH};
+
+fn syntax(pos: usize, what: &'static str) -> Error {
+    Error::Syntax {
+        format: Format::OpenStep,
+        pos,
+        what,
+    }
+}
+
+/// Characters of strings that don't need quotes.
+fn is_unquoted(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '+' | '/' | ':' | '.' | '-')
+}
+
+pub(super) fn read(data: &[u8]) -> Result<Value, Error> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let text = std::str::from_utf8(data).map_err(|e| syntax(e.valid_up_to(), "invalid UTF-8"))?;
+    let mut parser = Parser { text, pos: 0 };
+
+    parser.skip()?;
+    // an empty `.strings` file
+    if parser.rest().is_empty() {
+        return Ok(Value::Dictionary(Dictionary::new()));
+    }
+    let start = parser.pos;
+    let res = parser.value(0)?;
+    parser.skip()?;
+    if matches!(res, Value::String(_)) && matches!(parser.peek(), Some('=' | ';')) {
+        // `.strings` file, a dictionary without braces
+        parser.pos = start;
+        let mut dict = Dictionary::new();
+        parser.entries(&mut dict, None)?;
+        return Ok(Value::Dictionary(dict));
+    }
+    if !parser.rest().is_empty() {
+        return Err(syntax(parser.pos, "content after the top value"));
+    }
+    Ok(res)
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    /// Skips whitespace and `//`, `/* */` comments.
+    fn skip(&mut self) -> Result<(), Error> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                let Some(end) = comment.find("*/") else {
+                    return Err(syntax(self.pos, "unterminated comment"));
+                };
+                self.pos += end + 4;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), Error> {
+        self.skip()?;
+        if self.peek() != Some(c) {
+            return Err(syntax(
+                self.pos,
+                match c {
+                    ';' => "expected ';'",
+                    '=' => "expected '='",
+                    _ => "unexpected character",
+                },
+            ));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        self.skip()?;
+        let start = self.pos;
+        let Some(c) = self.peek() else {
+            return Err(syntax(start, "unexpected end"));
+        };
+        if matches!(c, '{' | '(') && depth >= MAX_DEPTH {
+            return Err(syntax(start, "values nested too deeply"));
+        }
+        match c {
+            '{' => {
+                self.pos += 1;
+                let mut dict = Dictionary::new();
+                self.entries(&mut dict, Some(depth))?;
+                Ok(Value::Dictionary(dict))
+            }
+            '(' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip()?;
+                    if self.peek() == Some(')') {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.value(depth + 1)?);
+                    self.skip()?;
+                    match self.peek() {
+                        Some(',') => self.pos += 1,
+                        Some(')') => {}
+                        _ => return Err(syntax(self.pos, "expected ',' or ')'")),
+                    }
+                }
+                Ok(Value::Array(items))
+            }
+            '<' => self.angled(),
+            '"' | '\'' => self.quoted(c).map(Value::String),
+            c if is_unquoted(c) => {
+                let len = self
+                    .rest()
+                    .find(|c| !is_unquoted(c))
+                    .unwrap_or(self.rest().len());
+                let s = &self.rest()[..len];
+                self.pos += len;
+                Ok(Value::String(s.to_string()))
+            }
+            _ => Err(syntax(start, "unexpected character")),
+        }
+    }
+
+    /// `key = value;` pairs up to `}`, or to the end of the text for the
+    /// top level of `.strings` files, when `depth` is `None`.
+    fn entries(&mut self, dict: &mut Dictionary, depth: Option<usize>) -> Result<(), Error> {
+        loop {
+            self.skip()?;
+            match (self.peek(), depth) {
+                (Some('}'), Some(_)) => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                (None, None) => return Ok(()),
+                (None, Some(_)) => return Err(syntax(self.pos, "unterminated dictionary")),
+                _ => {}
+            }
+            let start = self.pos;
+            let depth = depth.unwrap_or(0) + 1;
+            let Value::String(key) = self.value(depth)? else {
+                return Err(syntax(start, "non string dictionary key"));
+            };
+            self.skip()?;
+            // `"key";` in `.strings` files means `"key" = "key";`
+            let value = if self.peek() == Some(';') {
+                Value::String(key.clone())
+            } else {
+                self.expect('=')?;
+                self.value(depth)?
+            };
+            self.expect(';')?;
+            dict.insert(key, value);
+        }
+    }
+
+    fn quoted(&mut self, quote: char) -> Result<String, Error> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut res = String::new();
+        let mut chars = self.rest().char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                c if c == quote => {
+                    self.pos += i + 1;
+                    return Ok(res);
+                }
+                '\\' => {
+                    let Some((_, e)) = chars.next() else { break };
+                    let c = match e {
+                        'a' => '\x07',
+                        'b' => '\x08',
+                        'f' => '\x0C',
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        'v' => '\x0B',
+                        'u' | 'U' => {
+                            let hex: String = chars.by_ref().take(4).map(|(_, c)| c).collect();
+                            let unit = u16::from_str_radix(&hex, 16)
+                                .map_err(|_| syntax(self.pos + i, "invalid \\U escape"))?;
+                            // surrogate pairs come as two escapes
+                            let mut units = vec![unit];
+                            if (0xD800..0xDC00).contains(&unit) {
+                                let rest = chars.as_str();
+                                if let Some(low) = rest
+                                    .strip_prefix("\\U")
+                                    .or(rest.strip_prefix("\\u"))
+                                    .and_then(|r| r.get(..4))
+                                    .and_then(|h| u16::from_str_radix(h, 16).ok())
+                                {
+                                    units.push(low);
+                                    chars.nth(5);
+                                }
+                            }
+                            let s = String::from_utf16(&units)
+                                .map_err(|_| syntax(self.pos + i, "invalid \\U escape"))?;
+                            res.push_str(&s);
+                            continue;
+                        }
+                        '0'..='7' => {
+                            let mut code = e.to_digit(8).unwrap();
+                            for _ in 0..2 {
+                                match chars.clone().next() {
+                                    Some((_, d @ '0'..='7')) => {
+                                        code = code * 8 + d.to_digit(8).unwrap();
+                                        chars.next();
+                                    }
+                                    _ => break,
+                                }
+                            }
+                            char::from_u32(code).unwrap()
+                        }
+                        e => e,
+                    };
+                    res.push(c);
+                }
+                c => res.push(c),
+            }
+        }
+        Err(syntax(start, "unterminated string"))
+    }
+
+    /// `<hex data>` or a GNUstep `<*…>` value.
+    fn angled(&mut self) -> Result<Value, Error> {
+        let start = self.pos;
+        let Some(end) = self.rest().find('>') else {
+            return Err(syntax(start, "unterminated data"));
+        };
+        let body = &self.rest()[1..end];
+        self.pos += end + 1;
+        if let Some(typed) = body.strip_prefix('*') {
+            let (kind, text) = typed.split_at(typed.len().min(1));
+            let res = match kind {
+                "I" => xml::parse_integer(text.trim()).map(Value::Number),
+                "R" => xml::parse_real(text.trim()).map(|v| Value::Number(Number::F64(v))),
+                "B" => match text {
+                    "Y" => Some(Value::Boolean(true)),
+                    "N" => Some(Value::Boolean(false)),
+                    _ => None,
+                },
+                "D" => parse_date(text).map(Value::Date),
+                _ => None,
+            };
+            return res.ok_or(syntax(start, "invalid typed value"));
+        }
+        let digits: Vec<u8> = body
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace())
+            .map(|b| (b as char).to_digit(16).map(|d| d as u8))
+            .collect::<Option<_>>()
+            .ok_or(syntax(start, "invalid hex data"))?;
+        if digits.len() & 1 != 0 {
+            return Err(syntax(start, "odd number of hex digits"));
+        }
+        Ok(Value::Data(
+            digits.chunks(2).map(|d| d[0] << 4 | d[1]).collect(),
+        ))
+    }
+}
+
+/// `YYYY-MM-DD HH:MM:SS ±HHMM`
+fn parse_date(text: &str) -> Option<Date> {
+    let (time, zone) = text.trim().split_at(text.trim().len().checked_sub(5)?);
+    let time = time.trim_end();
+    if time.len() != 19 || !time.is_char_boundary(10) || time.as_bytes()[10] != b' ' {
+        return None;
+    }
+    let utc = Date::from_iso8601(&format!("{}T{}Z", &time[..10], &time[11..]))?;
+    let sign = match zone.as_bytes()[0] {
+        b'+' => 1.0,
+        b'-' => -1.0,
+        _ => return None,
+    };
+    if !zone[1..].bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: f64 = zone[1..3].parse().ok()?;
+    let mins: f64 = zone[3..].parse().ok()?;
+    Some(Date(utc.0 - sign * (hours * 3600.0 + mins * 60.0)))
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>, Error> {
+    let mut res = String::new();
+    write_value(&mut res, value, 0)?;
+    res.push('\n');
+    Ok(res.into_bytes())
+}
+
+fn indent(buf: &mut String, depth: usize) {
+    buf.push_str(&"\t".repeat(depth));
+}
+
+fn write_string(buf: &mut String, s: &str) {
+    if !s.is_empty() && s.chars().all(is_unquoted) {
+        buf.push_str(s);
+        return;
+    }
+    buf.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => buf.push_str("\\\""),
+            '\\' => buf.push_str("\\\\"),
+            '\n' => buf.push_str("\\n"),
+            '\t' => buf.push_str("\\t"),
+            '\r' => buf.push_str("\\r"),
+            c if c.is_control() => {
+                for u in c.encode_utf16(&mut [0; 2]) {
+                    buf.push_str(&format!("\\U{u:04x}"));
+                }
+            }
+            c => buf.push(c),
+        }
+    }
+    buf.push('"');
+}
+
+fn write_value(buf: &mut String, value: &Value, depth: usize) -> Result<(), Error> {
+    match value {
+        Value::String(s) => write_string(buf, s),
+        Value::Data(d) => {
+            buf.push('<');
+            for (i, chunk) in d.chunks(4).enumerate() {
+                if i > 0 {
+                    buf.push(' ');
+                }
+                for b in chunk {
+                    buf.push_str(&format!("{b:02x}"));
+                }
+            }
+            buf.push('>');
+        }
+        Value::Number(Number::F64(v)) => buf.push_str(&format!("<*R{}>", xml::real_str(*v))),
+        Value::Number(n) => buf.push_str(&format!("<*I{n}>")),
+        Value::Boolean(b) => buf.push_str(if *b { "<*BY>" } else { "<*BN>" }),
+        Value::Date(d) => {
+            let iso = d.to_iso8601();
+            buf.push_str(&format!("<*D{} {} +0000>", &iso[..10], &iso[11..19]));
+        }
+        Value::Array(items) if items.is_empty() => buf.push_str("()"),
+        Value::Array(items) => {
+            buf.push_str("(\n");
+            for (i, item) in items.iter().enumerate() {
+                indent(buf, depth + 1);
+                write_value(buf, item, depth + 1)?;
+                buf.push_str(if i + 1 < items.len() { ",\n" } else { "\n" });
+            }
+            indent(buf, depth);
+            buf.push(')');
+        }
+        Value::Dictionary(dict) if dict.is_empty() => buf.push_str("{}"),
+        Value::Dictionary(dict) => {
+            buf.push_str("{\n");
+            for (k, v) in dict {
+                indent(buf, depth + 1);
+                write_string(buf, k);
+                buf.push_str(" = ");
+                write_value(buf, v, depth + 1)?;
+                buf.push_str(";\n");
+            }
+            indent(buf, depth);
+            buf.push('}');
+        }
+        Value::Uid(_) => return Err(Error::Unsupported(Format::OpenStep, "UIDs")),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plist::{Date, Error, Format, Value};
+
+    #[test]
+    fn project_style() {
+        let text = r#"// !$*UTF8*$!
+            {
+                archiveVersion = 1;
+                objects = {
+                    /* Begin PBXBuildFile section */
+                    1A2B = {isa = PBXBuildFile; fileRef = "3C4D"; };
+                };
+                list = (a, "b c", <0001 02>, );
+                empty = ();
+                typed = (<*I-5>, <*R0.5>, <*BY>, <*D2001-01-01 01:00:00 +0100>);
+                "esc" = "q\"\n\101\U00e9\\";
+            }"#;
+        let value = Value::from_bytes(text.as_bytes()).unwrap();
+        assert_eq!(
+            value.get("archiveVersion").and_then(Value::as_str),
+            Some("1")
+        );
+        let obj = value.get("objects").and_then(|o| o.get("1A2B")).unwrap();
+        assert_eq!(obj.get("fileRef").and_then(Value::as_str), Some("3C4D"));
+
+        let list = value.get("list").and_then(Value::as_array).unwrap();
+        assert_eq!(list[1].as_str(), Some("b c"));
+        assert_eq!(list[2].as_data(), Some(&[0u8, 1, 2][..]));
+        assert_eq!(value.get("empty").and_then(Value::as_array), Some(&[][..]));
+
+        let typed = value.get("typed").and_then(Value::as_array).unwrap();
+        assert_eq!(typed[0].as_i64(), Some(-5));
+        assert_eq!(typed[1].as_f64(), Some(0.5));
+        assert_eq!(typed[2].as_bool(), Some(true));
+        assert_eq!(typed[3].as_date(), Some(Date(0.0)));
+        assert_eq!(value.get("esc").and_then(Value::as_str), Some("q\"\nAé\\"));
+    }
+
+    #[test]
+    fn strings_file() {
+        let text = "/* greeting */\n\"bye\";\n\"hello\" = \"Hallo\";\n";
+        let value = Value::from_bytes(text.as_bytes()).unwrap();
+        let dict = value.as_dictionary().unwrap();
+        assert_eq!(dict.len(), 2);
+        assert_eq!(dict["hello"].as_str(), Some("Hallo"));
+        assert_eq!(dict["bye"].as_str(), Some("bye"));
+
+        let empty = Value::from_bytes(b" // nothing\n").unwrap();
+        assert_eq!(empty.as_dictionary().map(|d| d.len()), Some(0));
+    }
+
+    #[test]
+    fn write() {
+        let value = Value::from(vec![
+            Value::from("plain"),
+            "needs quotes\t\u{1}".into(),
+            vec![0xdeu8, 0xad, 0xbe, 0xef, 1].into(),
+            true.into(),
+            Value::from(vec![Value::from(1)]),
+        ]);
+        let bytes = value.to_bytes(Format::OpenStep).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&bytes).unwrap(),
+            "(\n\
+             \tplain,\n\
+             \t\"needs quotes\\t\\U0001\",\n\
+             \t<deadbeef 01>,\n\
+             \t<*BY>,\n\
+             \t(\n\
+             \t\t<*I1>\n\
+             \t)\n\
+             )\n"
+        );
+        assert_eq!(Value::from_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn malformed() {
+        let cases: [&str; 6] = ["{a = b}", "(a b)", "\"open", "<0g>", "<*Ix>", "{(a) = b;}"];
+        for case in cases {
+            let res = Value::from_bytes(case.as_bytes());
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::Syntax {
+                        format: Format::OpenStep,
+                        ..
+                    })
+                ),
+                "{case}: {res:?}"
+            );
+        }
+    }
+}
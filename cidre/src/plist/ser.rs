@@ -0,0 +1,452 @@
+use serde::ser::{self, Serialize};
+
+use super::{Date, Dictionary, Error, Format, Number, Uid, Value, DATE_KEY, UID_KEY};
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// Serializes `value` into a property list [`Value`].
+///
+/// `None` struct fields and map values are left out, plists have no null.
+/// Unit variants become strings and other enum variants single key
+/// dictionaries.
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, Error> {
+    value.serialize(Serializer)
+}
+
+/// Serializes `value` into a property list of `format`.
+///
+/// ```
+/// use cidre::plist;
+///
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, PartialEq)]
+/// struct Info {
+///     #[serde(rename = "CFBundleIdentifier")]
+///     id: String,
+///     #[serde(rename = "UIDeviceFamily")]
+///     families: Vec<u32>,
+///     #[serde(rename = "NSHighResolutionCapable")]
+///     high_res: Option<bool>,
+/// }
+///
+/// let info = Info {
+///     id: "com.example.app".to_string(),
+///     families: vec![1, 2],
+///     high_res: None,
+/// };
+/// let bytes = plist::to_bytes(&info, plist::Format::BinaryV1_0).unwrap();
+/// let read: Info = plist::from_bytes(&bytes).unwrap();
+/// assert_eq!(read, info);
+/// ```
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T, format: Format) -> Result<Vec<u8>, Error> {
+    to_value(value)?.to_bytes(format)
+}
+
+impl Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::String(v) => serializer.serialize_str(v),
+            Self::Data(v) => serializer.serialize_bytes(v),
+            Self::Number(Number::I64(v)) => serializer.serialize_i64(*v),
+            Self::Number(Number::U64(v)) => serializer.serialize_u64(*v),
+            Self::Number(Number::F64(v)) => serializer.serialize_f64(*v),
+            Self::Boolean(v) => serializer.serialize_bool(*v),
+            Self::Date(v) => v.serialize(serializer),
+            Self::Array(v) => v.serialize(serializer),
+            Self::Dictionary(v) => v.serialize(serializer),
+            Self::Uid(v) => v.serialize(serializer),
+        }
+    }
+}
+
+/// Newtype struct named [`DATE_KEY`] around the seconds since 2001.
+impl Serialize for Date {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(DATE_KEY, &self.0)
+    }
+}
+
+/// Newtype struct named [`UID_KEY`].
+impl Serialize for Uid {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_newtype_struct(UID_KEY, &self.0)
+    }
+}
+
+/// Serializer into [`Value`].
+pub struct Serializer;
+
+fn dict_of(key: &'static str, value: Value) -> Value {
+    let mut res = Dictionary::new();
+    res.insert(key.to_string(), value);
+    Value::Dictionary(res)
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = Error;
+
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeDictionary;
+    type SerializeStruct = SerializeDictionary;
+    type SerializeStructVariant = SerializeDictionary;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, Error> {
+        Ok(Value::Boolean(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, Error> {
+        if let Ok(v) = i64::try_from(v) {
+            Ok(v.into())
+        } else if let Ok(v) = u64::try_from(v) {
+            Ok(v.into())
+        } else {
+            Err(Error::Custom(format!("{v} doesn't fit in 64 bits")))
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, Error> {
+        u64::try_from(v)
+            .map(Value::from)
+            .map_err(|_| Error::Custom(format!("{v} doesn't fit in 64 bits")))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, Error> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, Error> {
+        Ok(v.into())
+    }
+
+    fn serialize_none(self) -> Result<Value, Error> {
+        Err(Error::None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, Error> {
+        Err(Error::None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, Error> {
+        Err(Error::None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, Error> {
+        Ok(variant.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        let res = value.serialize(self)?;
+        match (name, &res) {
+            (UID_KEY, Value::Number(n)) => n
+                .as_u64()
+                .map(|v| Value::Uid(Uid(v)))
+                .ok_or(Error::Custom(format!("invalid {UID_KEY} {n}"))),
+            (DATE_KEY, Value::Number(n)) => Ok(Value::Date(Date(n.as_f64()))),
+            _ => Ok(res),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, Error> {
+        Ok(dict_of(variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len.unwrap_or(0)),
+            variant: None,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, Error> {
+        Ok(SerializeArray {
+            items: Vec::with_capacity(len),
+            variant: Some(variant),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeDictionary, Error> {
+        Ok(SerializeDictionary {
+            dict: Dictionary::new(),
+            key: None,
+            variant: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeDictionary, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeDictionary, Error> {
+        Ok(SerializeDictionary {
+            dict: Dictionary::new(),
+            key: None,
+            variant: Some(variant),
+        })
+    }
+}
+
+pub struct SerializeArray {
+    items: Vec<Value>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeArray {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.items.push(value.serialize(Serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let res = Value::Array(self.items);
+        Ok(match self.variant {
+            Some(variant) => dict_of(variant, res),
+            None => res,
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+pub struct SerializeDictionary {
+    dict: Dictionary,
+    key: Option<String>,
+    variant: Option<&'static str>,
+}
+
+impl SerializeDictionary {
+    /// Inserts the value unless it is `None`.
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        match value.serialize(Serializer) {
+            Ok(value) => {
+                self.dict.insert(key, value);
+                Ok(())
+            }
+            Err(Error::None) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn finish(self) -> Result<Value, Error> {
+        let res = Value::Dictionary(self.dict);
+        Ok(match self.variant {
+            Some(variant) => dict_of(variant, res),
+            None => res,
+        })
+    }
+}
+
+impl ser::SerializeMap for SerializeDictionary {
+    type Ok = Value;
+    type Error = Error;
+
+    /// Keys have to be strings, numbers and booleans are written as text.
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        let key = match key.serialize(Serializer)? {
+            Value::String(s) => s,
+            Value::Number(n) => n.to_string(),
+            Value::Boolean(b) => b.to_string(),
+            v => {
+                return Err(Error::Custom(format!(
+                    "dictionary keys can't be {}s",
+                    v.type_name()
+                )))
+            }
+        };
+        self.key = Some(key);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error::Custom("value without a key".to_string()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeDictionary {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeDictionary {
+    type Ok = Value;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Value, Error> {
+        self.finish()
+    }
+}
@@ -0,0 +1,505 @@
+//! XML 1.0 property lists of the Apple PropertyList-1.0 DTD.
+
+use super::{Date, Dictionary, Error, Format, Number, Uid, Value, MAX_DEPTH, UID_KEY};
+
+const HEADER: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" ",
+    "\"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n",
+    "<plist version=\"1.0\">\n",
+);
+
+/// Base64 characters per `<data>` line.
+const DATA_LINE_LEN: usize = 68;
+
+pub(super) fn read(data: &[u8]) -> Result<Value, Error> {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let text =
+        std::str::from_utf8(data).map_err(|e| Parser::error(e.valid_up_to(), "invalid UTF-8"))?;
+    let mut parser = Parser { text, pos: 0 };
+    let tag = parser.tag()?;
+    let res = if tag.name == "plist" && !tag.end {
+        if tag.empty {
+            return Err(parser.err("empty plist"));
+        }
+        let tag = parser.tag()?;
+        let res = parser.value(tag, 0)?;
+        parser.end("plist")?;
+        res
+    } else {
+        parser.value(tag, 0)?
+    };
+    parser.skip_misc()?;
+    if parser.pos != text.len() {
+        return Err(parser.err("content after the top value"));
+    }
+    Ok(res)
+}
+
+struct Tag<'a> {
+    name: &'a str,
+    /// `</name>`
+    end: bool,
+    /// `<name/>`
+    empty: bool,
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(pos: usize, what: &'static str) -> Error {
+        Error::Syntax {
+            format: Format::XmlV1_0,
+            pos,
+            what,
+        }
+    }
+
+    fn err(&self, what: &'static str) -> Error {
+        Self::error(self.pos, what)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Moves past `end`, failing with `what` when it doesn't occur.
+    fn skip_past(&mut self, end: &str, what: &'static str) -> Result<&'a str, Error> {
+        let Some(i) = self.rest().find(end) else {
+            return Err(self.err(what));
+        };
+        let skipped = &self.rest()[..i];
+        self.pos += i + end.len();
+        Ok(skipped)
+    }
+
+    /// Skips whitespace, comments, processing instructions and the doctype.
+    fn skip_misc(&mut self) -> Result<(), Error> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else if trimmed.starts_with("<?") {
+                self.skip_past("?>", "unterminated processing instruction")?;
+            } else if trimmed.starts_with("<!DOCTYPE") {
+                self.skip_doctype()?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn skip_doctype(&mut self) -> Result<(), Error> {
+        let mut depth = 0;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.pos += i + 1;
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(self.err("unterminated doctype"))
+    }
+
+    fn tag(&mut self) -> Result<Tag<'a>, Error> {
+        self.skip_misc()?;
+        if !self.rest().starts_with('<') {
+            return Err(self.err(if self.rest().is_empty() {
+                "unexpected end"
+            } else {
+                "expected a tag"
+            }));
+        }
+        self.pos += 1;
+        let end = self.rest().starts_with('/');
+        if end {
+            self.pos += 1;
+        }
+        let body = self.skip_past(">", "unterminated tag")?;
+        let (body, empty) = match body.strip_suffix('/') {
+            Some(b) if !end => (b, true),
+            _ => (body, false),
+        };
+        let name = body.split_ascii_whitespace().next().unwrap_or("");
+        if name.is_empty() {
+            return Err(self.err("empty tag name"));
+        }
+        Ok(Tag { name, end, empty })
+    }
+
+    fn end(&mut self, name: &str) -> Result<(), Error> {
+        let tag = self.tag()?;
+        if !tag.end || tag.name != name {
+            return Err(self.err("mismatched closing tag"));
+        }
+        Ok(())
+    }
+
+    /// Character data up to `</name>`, with entities and CDATA sections
+    /// resolved.
+    fn text(&mut self, name: &str) -> Result<String, Error> {
+        let mut res = String::new();
+        loop {
+            let rest = self.rest();
+            let Some(i) = rest.find(['<', '&']) else {
+                return Err(self.err("unexpected end"));
+            };
+            res.push_str(&rest[..i]);
+            self.pos += i;
+            let rest = self.rest();
+            if rest.starts_with('&') {
+                res.push(self.entity()?);
+            } else if rest.starts_with("<![CDATA[") {
+                self.pos += "<![CDATA[".len();
+                res.push_str(self.skip_past("]]>", "unterminated CDATA")?);
+            } else if rest.starts_with("<!--") {
+                self.skip_past("-->", "unterminated comment")?;
+            } else {
+                self.end(name)?;
+                return Ok(res);
+            }
+        }
+    }
+
+    fn entity(&mut self) -> Result<char, Error> {
+        let start = self.pos;
+        let name = self.skip_past(";", "unterminated entity")?;
+        let c = match &name[1..] {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            n => {
+                let code = if let Some(hex) = n.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(dec) = n.strip_prefix('#') {
+                    dec.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(char::from_u32)
+            }
+        };
+        c.ok_or(Self::error(start, "unknown entity"))
+    }
+
+    fn value(&mut self, tag: Tag<'a>, depth: usize) -> Result<Value, Error> {
+        if tag.end {
+            return Err(self.err("unexpected closing tag"));
+        }
+        let start = self.pos;
+        let text = |p: &mut Self| -> Result<String, Error> {
+            if tag.empty {
+                Ok(String::new())
+            } else {
+                p.text(tag.name)
+            }
+        };
+        let res = match tag.name {
+            "string" => Value::String(text(self)?),
+            "data" => Value::Data(
+                super::base64_decode(&text(self)?).ok_or(Self::error(start, "invalid base64"))?,
+            ),
+            "date" => Value::Date(
+                Date::from_iso8601(text(self)?.trim()).ok_or(Self::error(start, "invalid date"))?,
+            ),
+            "integer" => Value::Number(
+                parse_integer(text(self)?.trim()).ok_or(Self::error(start, "invalid integer"))?,
+            ),
+            "real" => Value::Number(Number::F64(
+                parse_real(text(self)?.trim()).ok_or(Self::error(start, "invalid real"))?,
+            )),
+            "true" | "false" => {
+                if !tag.empty {
+                    self.end(tag.name)?;
+                }
+                Value::Boolean(tag.name == "true")
+            }
+            "array" | "dict" if depth >= MAX_DEPTH => {
+                return Err(self.err("values nested too deeply"));
+            }
+            "array" => {
+                let mut items = Vec::new();
+                if !tag.empty {
+                    loop {
+                        let tag = self.tag()?;
+                        if tag.end && tag.name == "array" {
+                            break;
+                        }
+                        items.push(self.value(tag, depth + 1)?);
+                    }
+                }
+                Value::Array(items)
+            }
+            "dict" => {
+                let mut dict = Dictionary::new();
+                if !tag.empty {
+                    loop {
+                        let tag = self.tag()?;
+                        if tag.end && tag.name == "dict" {
+                            break;
+                        }
+                        if tag.name != "key" || tag.end {
+                            return Err(self.err("expected a key"));
+                        }
+                        let key = if tag.empty {
+                            String::new()
+                        } else {
+                            self.text("key")?
+                        };
+                        let tag = self.tag()?;
+                        let value = self.value(tag, depth + 1)?;
+                        dict.insert(key, value);
+                    }
+                }
+                if dict.len() == 1 {
+                    if let Some(uid) = dict.get(UID_KEY).and_then(Value::as_u64) {
+                        return Ok(Value::Uid(Uid(uid)));
+                    }
+                }
+                Value::Dictionary(dict)
+            }
+            _ => return Err(Self::error(start, "unknown tag")),
+        };
+        Ok(res)
+    }
+}
+
+/// Decimal or `0x` hex integer with an optional sign.
+pub(super) fn parse_integer(s: &str) -> Option<Number> {
+    let (neg, digits) = match s.as_bytes().first()? {
+        b'-' => (true, &s[1..]),
+        b'+' => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let magnitude = match digits.strip_prefix("0x").or(digits.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok()?,
+        None if digits.bytes().all(|b| b.is_ascii_digit()) => digits.parse().ok()?,
+        None => return None,
+    };
+    if neg {
+        if magnitude > i64::MIN.unsigned_abs() {
+            return None;
+        }
+        Some(Number::I64((magnitude as i64).wrapping_neg()))
+    } else {
+        Some(Number::from(magnitude))
+    }
+}
+
+pub(super) fn parse_real(s: &str) -> Option<f64> {
+    match s.to_ascii_lowercase().as_str() {
+        "nan" => Some(f64::NAN),
+        "inf" | "+inf" | "infinity" | "+infinity" => Some(f64::INFINITY),
+        "-inf" | "-infinity" => Some(f64::NEG_INFINITY),
+        s => s.parse().ok(),
+    }
+}
+
+pub(super) fn real_str(v: f64) -> String {
+    if v.is_nan() {
+        "nan".to_string()
+    } else if v.is_infinite() {
+        if v > 0.0 { "+infinity" } else { "-infinity" }.to_string()
+    } else {
+        v.to_string()
+    }
+}
+
+pub(super) fn write(value: &Value) -> Result<Vec<u8>, Error> {
+    let mut res = String::from(HEADER);
+    write_value(&mut res, value, 0);
+    res.push_str("</plist>\n");
+    Ok(res.into_bytes())
+}
+
+fn indent(buf: &mut String, depth: usize) {
+    buf.push_str(&"\t".repeat(depth));
+}
+
+fn escape(buf: &mut String, s: &str) {
+    for c in s.chars() {
+        match c {
+            '<' => buf.push_str("&lt;"),
+            '>' => buf.push_str("&gt;"),
+            '&' => buf.push_str("&amp;"),
+            c => buf.push(c),
+        }
+    }
+}
+
+fn element(buf: &mut String, depth: usize, name: &str, text: &str) {
+    indent(buf, depth);
+    buf.push('<');
+    buf.push_str(name);
+    buf.push('>');
+    escape(buf, text);
+    buf.push_str("</");
+    buf.push_str(name);
+    buf.push_str(">\n");
+}
+
+fn write_value(buf: &mut String, value: &Value, depth: usize) {
+    match value {
+        Value::String(s) => element(buf, depth, "string", s),
+        Value::Data(d) => {
+            indent(buf, depth);
+            buf.push_str("<data>\n");
+            let text = super::base64_encode(d);
+            for line in text.as_bytes().chunks(DATA_LINE_LEN) {
+                indent(buf, depth);
+                buf.push_str(std::str::from_utf8(line).unwrap());
+                buf.push('\n');
+            }
+            indent(buf, depth);
+            buf.push_str("</data>\n");
+        }
+        Value::Number(Number::F64(v)) => element(buf, depth, "real", &real_str(*v)),
+        Value::Number(n) => element(buf, depth, "integer", &n.to_string()),
+        Value::Boolean(b) => {
+            indent(buf, depth);
+            buf.push_str(if *b { "<true/>\n" } else { "<false/>\n" });
+        }
+        Value::Date(d) => element(buf, depth, "date", &d.to_iso8601()),
+        Value::Array(items) if items.is_empty() => {
+            indent(buf, depth);
+            buf.push_str("<array/>\n");
+        }
+        Value::Array(items) => {
+            indent(buf, depth);
+            buf.push_str("<array>\n");
+            for item in items {
+                write_value(buf, item, depth + 1);
+            }
+            indent(buf, depth);
+            buf.push_str("</array>\n");
+        }
+        Value::Dictionary(dict) if dict.is_empty() => {
+            indent(buf, depth);
+            buf.push_str("<dict/>\n");
+        }
+        Value::Dictionary(dict) => {
+            indent(buf, depth);
+            buf.push_str("<dict>\n");
+            for (k, v) in dict {
+                element(buf, depth + 1, "key", k);
+                write_value(buf, v, depth + 1);
+            }
+            indent(buf, depth);
+            buf.push_str("</dict>\n");
+        }
+        Value::Uid(Uid(v)) => {
+            indent(buf, depth);
+            buf.push_str("<dict>\n");
+            element(buf, depth + 1, "key", UID_KEY);
+            element(buf, depth + 1, "integer", &v.to_string());
+            indent(buf, depth);
+            buf.push_str("</dict>\n");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::plist::{Dictionary, Error, Format, Number, Uid, Value};
+
+    #[test]
+    fn layout() {
+        let mut dict = Dictionary::new();
+        dict.insert("a".into(), vec![Value::from(1), true.into()].into());
+        dict.insert("b".into(), vec![1u8, 2, 3].into());
+        dict.insert("u".into(), Uid(3).into());
+        let bytes = Value::from(dict).to_bytes(Format::XmlV1_0).unwrap();
+        let text = std::str::from_utf8(&bytes).unwrap();
+        let body = text.split_once("<plist version=\"1.0\">\n").unwrap().1;
+        assert_eq!(
+            body,
+            "<dict>\n\
+             \t<key>a</key>\n\
+             \t<array>\n\
+             \t\t<integer>1</integer>\n\
+             \t\t<true/>\n\
+             \t</array>\n\
+             \t<key>b</key>\n\
+             \t<data>\n\
+             \tAQID\n\
+             \t</data>\n\
+             \t<key>u</key>\n\
+             \t<dict>\n\
+             \t\t<key>CF$UID</key>\n\
+             \t\t<integer>3</integer>\n\
+             \t</dict>\n\
+             </dict>\n\
+             </plist>\n"
+        );
+    }
+
+    #[test]
+    fn lenient() {
+        let text = r#"<?xml version="1.0"?>
+            <!-- leading comment -->
+            <plist version="1.0"><array>
+                <string>a &lt;&#x42;&#67;&gt; <![CDATA[<d>]]></string>
+                <string/>
+                <integer> -0x10 </integer>
+                <integer>18446744073709551615</integer>
+                <real>-infinity</real>
+                <real>2</real>
+                <date>2001-01-01T00:01:00Z</date>
+                <data>AA
+                      ==</data>
+                <dict><key>CF$UID</key><integer>1</integer><key>x</key><true/></dict>
+                <false></false>
+            </array></plist>
+        "#;
+        let value = Value::from_bytes(text.as_bytes()).unwrap();
+        let items = value.as_array().unwrap();
+        assert_eq!(items[0].as_str(), Some("a <BC> <d>"));
+        assert_eq!(items[1].as_str(), Some(""));
+        assert_eq!(items[2].as_i64(), Some(-16));
+        assert_eq!(items[3].as_number(), Some(Number::U64(u64::MAX)));
+        assert_eq!(items[4].as_f64(), Some(f64::NEG_INFINITY));
+        assert_eq!(items[5].as_number(), Some(Number::F64(2.0)));
+        assert_eq!(items[6].as_date().unwrap().0, 60.0);
+        assert_eq!(items[7].as_data(), Some(&[0u8][..]));
+        assert_eq!(items[8].as_dictionary().unwrap().len(), 2);
+        assert_eq!(items[9].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn malformed() {
+        let cases: [&str; 6] = [
+            "<plist><string>a</plist>",
+            "<plist><integer>1.5</integer></plist>",
+            "<plist><dict><string>a</string></dict></plist>",
+            "<plist><foo/></plist>",
+            "<plist><string>&bogus;</string></plist>",
+            "<plist><true/><true/></plist>",
+        ];
+        for case in cases {
+            let res = Value::from_bytes(case.as_bytes());
+            assert!(
+                matches!(
+                    res,
+                    Err(Error::Syntax {
+                        format: Format::XmlV1_0,
+                        ..
+                    })
+                ),
+                "{case}: {res:?}"
+            );
+        }
+    }
+}
@@ -37,6 +37,11 @@ pub type f32x3 = Simd<f32, 4, 3>;
 #[allow(non_camel_case_types)]
 pub type f32x4 = Simd<f32, 4, 4>;
 
+#[allow(non_camel_case_types)]
+pub type f64x2 = Simd<f64, 2, 2>;
+#[allow(non_camel_case_types)]
+pub type f64x4 = Simd<f64, 4, 4>;
+
 #[derive(Debug, Copy, Clone)]
 #[allow(non_camel_case_types)]
 #[repr(transparent)]
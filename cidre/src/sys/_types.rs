@@ -1,5 +1,6 @@
 use std::ffi::c_long;
 
+#[cfg(target_vendor = "apple")]
 pub use crate::mach::Port as MachPort;
 
 pub type Pid = i32;
@@ -0,0 +1,921 @@
+//! usbmuxd client without MobileDevice.framework.
+//!
+//! usbmuxd multiplexes TCP connections to iOS devices over USB and the
+//! network. It listens on `/var/run/usbmuxd` on macOS and Linux and on
+//! `127.0.0.1:27015` elsewhere, `USBMUXD_SOCKET_ADDRESS` overrides both.
+//! Requests and replies are property lists behind a 16 byte header.
+//!
+//! ```no_run
+//! use cidre::usbmux;
+//!
+//! let mut client = usbmux::Client::connect().unwrap();
+//! for device in client.devices().unwrap() {
+//!     println!("{} {:?}", device.id(), device.iface_type());
+//! }
+//! ```
+
+use std::{
+    io::{self, Read, Write},
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    path::PathBuf,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use crate::plist::{self, Dictionary, Value};
+
+/// Default socket of usbmuxd on macOS and Linux.
+#[cfg(unix)]
+pub const SOCKET_PATH: &str = "/var/run/usbmuxd";
+
+/// Default TCP port of usbmuxd where there are no unix sockets.
+pub const TCP_PORT: u16 = 27015;
+
+/// Environment variable with the usbmuxd address, `UNIX:<path>` or
+/// `<host>:<port>`.
+pub const ADDRESS_ENV: &str = "USBMUXD_SOCKET_ADDRESS";
+
+/// Protocol version of property list messages.
+const VERSION: u32 = 1;
+
+/// Message type of property list messages.
+const PLIST_MESSAGE: u32 = 8;
+
+const HEADER_LEN: usize = 16;
+
+/// usbmuxd keeps messages small, anything bigger is a broken stream.
+const MAX_MESSAGE_LEN: usize = 16 << 20;
+
+/// `kLibUSBMuxVersion` clients send.
+const LIB_VERSION: u64 = 3;
+
+const CLIENT_VERSION: &str = concat!("cidre-", env!("CARGO_PKG_VERSION"));
+
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Plist(plist::Error),
+
+    /// Malformed message or reply of an unexpected shape.
+    Protocol(&'static str),
+
+    /// `Result` reply with a non zero number.
+    Reply(ReplyCode),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => e.fmt(f),
+            Self::Plist(e) => e.fmt(f),
+            Self::Protocol(what) => write!(f, "usbmux: {what}"),
+            Self::Reply(code) => write!(f, "usbmux: request failed with {}", code.0),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Plist(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<plist::Error> for Error {
+    fn from(value: plist::Error) -> Self {
+        Self::Plist(value)
+    }
+}
+
+pub type Result<T = ()> = std::result::Result<T, Error>;
+
+/// `Number` of `Result` replies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct ReplyCode(pub u64);
+
+impl ReplyCode {
+    pub const OK: Self = Self(0);
+    pub const BAD_COMMAND: Self = Self(1);
+    pub const BAD_DEVICE: Self = Self(2);
+    pub const CONNECTION_REFUSED: Self = Self(3);
+    pub const BAD_VERSION: Self = Self(6);
+}
+
+/// The interface connection type, `am::DeviceIfaceConnectionType` of
+/// hosts without MobileDevice.framework.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+#[repr(i32)]
+pub enum IfaceConnectionType {
+    Invalid = -1,
+    Any = 0,
+    /// USB
+    Direct = 1,
+    /// Network
+    Inderect = 2,
+    Proxied = 3,
+}
+
+impl IfaceConnectionType {
+    /// Type of a `ConnectionType` property.
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "USB" => Self::Direct,
+            "Network" => Self::Inderect,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "am"))]
+impl From<IfaceConnectionType> for crate::am::DeviceIfaceConnectionType {
+    fn from(value: IfaceConnectionType) -> Self {
+        match value {
+            IfaceConnectionType::Invalid => Self::Invalid,
+            IfaceConnectionType::Any => Self::Any,
+            IfaceConnectionType::Direct => Self::Direct,
+            IfaceConnectionType::Inderect => Self::Inderect,
+            IfaceConnectionType::Proxied => Self::Proxied,
+        }
+    }
+}
+
+#[cfg(all(target_os = "macos", feature = "am"))]
+impl From<crate::am::DeviceIfaceConnectionType> for IfaceConnectionType {
+    fn from(value: crate::am::DeviceIfaceConnectionType) -> Self {
+        use crate::am::DeviceIfaceConnectionType as T;
+        match value {
+            T::Any => Self::Any,
+            T::Direct => Self::Direct,
+            T::Inderect => Self::Inderect,
+            T::Proxied => Self::Proxied,
+            _ => Self::Invalid,
+        }
+    }
+}
+
+/// Attached device as usbmuxd reports it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Device {
+    connection_id: u32,
+    id: String,
+    iface_type: IfaceConnectionType,
+    properties: Dictionary,
+}
+
+impl Device {
+    /// Device of `Properties` of a device list entry or attach event.
+    pub fn from_properties(properties: Dictionary) -> Result<Self> {
+        let connection_id = properties
+            .get("DeviceID")
+            .and_then(Value::as_u64)
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or(Error::Protocol("device without DeviceID"))?;
+        let id = properties
+            .get("SerialNumber")
+            .and_then(Value::as_str)
+            .ok_or(Error::Protocol("device without SerialNumber"))?
+            .to_string();
+        let iface_type = properties
+            .get("ConnectionType")
+            .and_then(Value::as_str)
+            .map_or(IfaceConnectionType::Invalid, IfaceConnectionType::from_name);
+        Ok(Self {
+            connection_id,
+            id,
+            iface_type,
+            properties,
+        })
+    }
+
+    /// `DeviceID`, what `Client::connect_to` takes.
+    #[inline]
+    pub fn connection_id(&self) -> u32 {
+        self.connection_id
+    }
+
+    /// UDID
+    #[inline]
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    #[inline]
+    pub fn iface_type(&self) -> IfaceConnectionType {
+        self.iface_type
+    }
+
+    /// USB product id, `None` for network devices.
+    pub fn product_id(&self) -> Option<u16> {
+        let v = self.properties.get("ProductID")?.as_u64()?;
+        u16::try_from(v).ok()
+    }
+
+    /// USB location id, `None` for network devices.
+    pub fn location_id(&self) -> Option<u32> {
+        let v = self.properties.get("LocationID")?.as_u64()?;
+        u32::try_from(v).ok()
+    }
+
+    /// Link speed in bits per second.
+    pub fn speed(&self) -> Option<u64> {
+        self.properties.get("ConnectionSpeed")?.as_u64()
+    }
+
+    /// All properties of the device.
+    #[inline]
+    pub fn properties(&self) -> &Dictionary {
+        &self.properties
+    }
+}
+
+/// Notification of a listening connection, see `am::DeviceAction`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    Attached(Device),
+    /// Connection id of the device.
+    Detached(u32),
+    /// Connection id of the device.
+    Paired(u32),
+}
+
+/// Pairing of the host with a device, what lockdownd sessions need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairRecord {
+    pub host_id: String,
+    pub system_buid: String,
+    pub host_certificate: Vec<u8>,
+    pub host_private_key: Vec<u8>,
+    pub device_certificate: Vec<u8>,
+    pub root_certificate: Vec<u8>,
+    pub root_private_key: Vec<u8>,
+    pub wifi_mac_address: Option<String>,
+    pub escrow_bag: Option<Vec<u8>>,
+}
+
+impl PairRecord {
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let str = |key| {
+            value
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or(Error::Protocol("pair record without a string key"))
+        };
+        let data = |key| {
+            value
+                .get(key)
+                .and_then(Value::as_data)
+                .map(<[u8]>::to_vec)
+                .ok_or(Error::Protocol("pair record without a data key"))
+        };
+        Ok(Self {
+            host_id: str("HostID")?,
+            system_buid: str("SystemBUID")?,
+            host_certificate: data("HostCertificate")?,
+            host_private_key: data("HostPrivateKey")?,
+            device_certificate: data("DeviceCertificate")?,
+            root_certificate: data("RootCertificate")?,
+            root_private_key: data("RootPrivateKey")?,
+            wifi_mac_address: str("WiFiMACAddress").ok(),
+            escrow_bag: data("EscrowBag").ok(),
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        let mut res = Dictionary::new();
+        res.insert("HostID".to_string(), self.host_id.as_str().into());
+        res.insert("SystemBUID".to_string(), self.system_buid.as_str().into());
+        let data = [
+            ("HostCertificate", &self.host_certificate),
+            ("HostPrivateKey", &self.host_private_key),
+            ("DeviceCertificate", &self.device_certificate),
+            ("RootCertificate", &self.root_certificate),
+            ("RootPrivateKey", &self.root_private_key),
+        ];
+        for (k, v) in data {
+            res.insert(k.to_string(), v.as_slice().into());
+        }
+        if let Some(v) = &self.wifi_mac_address {
+            res.insert("WiFiMACAddress".to_string(), v.as_str().into());
+        }
+        if let Some(v) = &self.escrow_bag {
+            res.insert("EscrowBag".to_string(), v.as_slice().into());
+        }
+        res.into()
+    }
+}
+
+/// Where usbmuxd listens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Addr {
+    Unix(PathBuf),
+    Tcp(SocketAddr),
+}
+
+impl Addr {
+    /// `USBMUXD_SOCKET_ADDRESS` when set, the platform default otherwise.
+    pub fn from_env() -> io::Result<Self> {
+        match std::env::var(ADDRESS_ENV) {
+            Ok(v) if !v.is_empty() => Self::parse(&v),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Address in the `USBMUXD_SOCKET_ADDRESS` syntax.
+    pub fn parse(s: &str) -> io::Result<Self> {
+        if let Some(path) = s.strip_prefix("UNIX:") {
+            return Ok(Self::Unix(path.into()));
+        }
+        s.to_socket_addrs()?
+            .next()
+            .map(Self::Tcp)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "no usbmuxd address"))
+    }
+
+    pub fn connect(&self) -> io::Result<Stream> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(path) => UnixStream::connect(path).map(Stream::Unix),
+            #[cfg(not(unix))]
+            Self::Unix(_) => Err(io::ErrorKind::Unsupported.into()),
+            Self::Tcp(addr) => {
+                let s = TcpStream::connect(addr)?;
+                s.set_nodelay(true)?;
+                Ok(Stream::Tcp(s))
+            }
+        }
+    }
+}
+
+impl Default for Addr {
+    fn default() -> Self {
+        #[cfg(unix)]
+        {
+            Self::Unix(SOCKET_PATH.into())
+        }
+        #[cfg(not(unix))]
+        {
+            Self::Tcp(SocketAddr::from(([127, 0, 0, 1], TCP_PORT)))
+        }
+    }
+}
+
+/// Connection to usbmuxd, after `Client::connect_to` a tunnel to the
+/// device port.
+#[derive(Debug)]
+pub enum Stream {
+    #[cfg(unix)]
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl Stream {
+    pub fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(s) => s.try_clone().map(Self::Unix),
+            Self::Tcp(s) => s.try_clone().map(Self::Tcp),
+        }
+    }
+
+    pub fn set_read_timeout(&self, dur: Option<std::time::Duration>) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(s) => s.set_read_timeout(dur),
+            Self::Tcp(s) => s.set_read_timeout(dur),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(s) => s.read(buf),
+            Self::Tcp(s) => s.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(s) => s.write(buf),
+            Self::Tcp(s) => s.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Self::Unix(s) => s.flush(),
+            Self::Tcp(s) => s.flush(),
+        }
+    }
+}
+
+/// Writes a property list message.
+pub fn write_message<W: Write>(w: &mut W, tag: u32, msg: &Value) -> Result {
+    let body = msg.to_bytes(plist::Format::XmlV1_0)?;
+    let len =
+        u32::try_from(HEADER_LEN + body.len()).map_err(|_| Error::Protocol("message too long"))?;
+    let mut buf = Vec::with_capacity(len as usize);
+    for v in [len, VERSION, PLIST_MESSAGE, tag] {
+        buf.extend_from_slice(&v.to_le_bytes());
+    }
+    buf.extend_from_slice(&body);
+    w.write_all(&buf)?;
+    w.flush()?;
+    Ok(())
+}
+
+/// Reads a property list message and its tag.
+pub fn read_message<R: Read>(r: &mut R) -> Result<(u32, Value)> {
+    let mut header = [0u8; HEADER_LEN];
+    r.read_exact(&mut header)?;
+    let field = |i: usize| u32::from_le_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+    let len = field(0) as usize;
+    if !(HEADER_LEN..=MAX_MESSAGE_LEN).contains(&len) {
+        return Err(Error::Protocol("bad message length"));
+    }
+    if field(1) != VERSION || field(2) != PLIST_MESSAGE {
+        return Err(Error::Protocol("not a property list message"));
+    }
+    let mut body = vec![0u8; len - HEADER_LEN];
+    r.read_exact(&mut body)?;
+    Ok((field(3), Value::from_bytes(&body)?))
+}
+
+/// Blocking usbmuxd connection, each connection serves one `Listen` or
+/// `Connect` and any number of other requests before them.
+pub struct Client<S = Stream> {
+    stream: S,
+    tag: u32,
+    prog_name: String,
+}
+
+impl Client {
+    /// Connects to usbmuxd at [`Addr::from_env`].
+    pub fn connect() -> Result<Self> {
+        Self::connect_addr(&Addr::from_env()?)
+    }
+
+    pub fn connect_addr(addr: &Addr) -> Result<Self> {
+        Ok(Self::new(addr.connect()?))
+    }
+}
+
+impl<S: Read + Write> Client<S> {
+    pub fn new(stream: S) -> Self {
+        let prog_name = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.file_name()?.to_str().map(str::to_string))
+            .unwrap_or_else(|| "cidre".to_string());
+        Self {
+            stream,
+            tag: 0,
+            prog_name,
+        }
+    }
+
+    /// `ProgName` usbmuxd logs requests with.
+    pub fn set_prog_name(&mut self, name: &str) {
+        self.prog_name = name.to_string();
+    }
+
+    /// Sends a request with the client keys usbmuxd expects and reads its
+    /// reply.
+    pub fn request(&mut self, msg_type: &str, mut msg: Dictionary) -> Result<Dictionary> {
+        msg.insert("MessageType".to_string(), msg_type.into());
+        msg.insert("ClientVersionString".to_string(), CLIENT_VERSION.into());
+        msg.insert("ProgName".to_string(), self.prog_name.as_str().into());
+        msg.insert("kLibUSBMuxVersion".to_string(), LIB_VERSION.into());
+        self.tag = self.tag.wrapping_add(1).max(1);
+        write_message(&mut self.stream, self.tag, &msg.into())?;
+        loop {
+            let (tag, reply) = read_message(&mut self.stream)?;
+            // tag 0 is of events, never of replies
+            if tag == 0 {
+                continue;
+            }
+            if tag != self.tag {
+                return Err(Error::Protocol("reply to another request"));
+            }
+            return match reply {
+                Value::Dictionary(reply) => Ok(reply),
+                _ => Err(Error::Protocol("reply isn't a dictionary")),
+            };
+        }
+    }
+
+    /// Request answered with a `Result` reply.
+    fn command(&mut self, msg_type: &str, msg: Dictionary) -> Result {
+        let reply = self.request(msg_type, msg)?;
+        check_result(&reply)
+    }
+
+    /// Currently attached devices.
+    pub fn devices(&mut self) -> Result<Vec<Device>> {
+        let reply = self.request("ListDevices", Dictionary::new())?;
+        let Some(list) = reply.get("DeviceList").and_then(Value::as_array) else {
+            check_result(&reply)?;
+            return Err(Error::Protocol("reply without DeviceList"));
+        };
+        list.iter()
+            .map(|entry| {
+                let props = entry
+                    .get("Properties")
+                    .and_then(Value::as_dictionary)
+                    .ok_or(Error::Protocol("device entry without Properties"))?;
+                Device::from_properties(props.clone())
+            })
+            .collect()
+    }
+
+    /// Pair record of a device by its UDID.
+    pub fn pair_record(&mut self, udid: &str) -> Result<PairRecord> {
+        let mut msg = Dictionary::new();
+        msg.insert("PairRecordID".to_string(), udid.into());
+        let reply = self.request("ReadPairRecord", msg)?;
+        let Some(data) = reply.get("PairRecordData").and_then(Value::as_data) else {
+            check_result(&reply)?;
+            return Err(Error::Protocol("reply without PairRecordData"));
+        };
+        PairRecord::from_value(&Value::from_bytes(data)?)
+    }
+
+    /// Stores a pair record for other usbmuxd clients, after pairing.
+    pub fn save_pair_record(
+        &mut self,
+        udid: &str,
+        connection_id: u32,
+        record: &PairRecord,
+    ) -> Result {
+        let data = record.to_value().to_bytes(plist::Format::XmlV1_0)?;
+        let mut msg = Dictionary::new();
+        msg.insert("PairRecordID".to_string(), udid.into());
+        msg.insert("PairRecordData".to_string(), data.into());
+        msg.insert("DeviceID".to_string(), connection_id.into());
+        self.command("SavePairRecord", msg)
+    }
+
+    /// `SystemBUID` of the host, what pair records carry.
+    pub fn buid(&mut self) -> Result<String> {
+        let reply = self.request("ReadBUID", Dictionary::new())?;
+        let Some(buid) = reply.get("BUID").and_then(Value::as_str) else {
+            check_result(&reply)?;
+            return Err(Error::Protocol("reply without BUID"));
+        };
+        Ok(buid.to_string())
+    }
+
+    /// Turns the connection into a stream of attach and detach events,
+    /// currently attached devices come first as `Attached`.
+    pub fn listen(mut self) -> Result<Listener<S>> {
+        self.command("Listen", Dictionary::new())?;
+        Ok(Listener {
+            stream: self.stream,
+        })
+    }
+
+    /// Turns the connection into a tunnel to a TCP port of a device.
+    pub fn connect_to(mut self, connection_id: u32, port: u16) -> Result<S> {
+        let mut msg = Dictionary::new();
+        msg.insert("DeviceID".to_string(), connection_id.into());
+        // in network byte order like the C API
+        msg.insert("PortNumber".to_string(), port.to_be().into());
+        self.command("Connect", msg)?;
+        Ok(self.stream)
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+fn check_result(reply: &Dictionary) -> Result {
+    if reply.get("MessageType").and_then(Value::as_str) != Some("Result") {
+        return Err(Error::Protocol("unexpected reply"));
+    }
+    let code = reply
+        .get("Number")
+        .and_then(Value::as_u64)
+        .map(ReplyCode)
+        .ok_or(Error::Protocol("result without Number"))?;
+    if code == ReplyCode::OK {
+        Ok(())
+    } else {
+        Err(Error::Reply(code))
+    }
+}
+
+/// Listening connection, an iterator of device events.
+pub struct Listener<S = Stream> {
+    stream: S,
+}
+
+impl<S: Read> Listener<S> {
+    /// Blocks until the next event.
+    pub fn next_event(&mut self) -> Result<Event> {
+        loop {
+            let (_, msg) = read_message(&mut self.stream)?;
+            let id = || {
+                msg.get("DeviceID")
+                    .and_then(Value::as_u64)
+                    .and_then(|v| u32::try_from(v).ok())
+                    .ok_or(Error::Protocol("event without DeviceID"))
+            };
+            match msg.get("MessageType").and_then(Value::as_str) {
+                Some("Attached") => {
+                    let props = msg
+                        .get("Properties")
+                        .and_then(Value::as_dictionary)
+                        .ok_or(Error::Protocol("attach without Properties"))?;
+                    return Device::from_properties(props.clone()).map(Event::Attached);
+                }
+                Some("Detached") => return id().map(Event::Detached),
+                Some("Paired") => return id().map(Event::Paired),
+                // newer usbmuxd versions send more, skip them
+                Some(_) => continue,
+                None => return Err(Error::Protocol("event without MessageType")),
+            }
+        }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S: Read> Iterator for Listener<S> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.next_event() {
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof => None,
+            res => Some(res),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{
+        io::{Read, Write},
+        net::{TcpListener, TcpStream},
+        thread,
+    };
+
+    use crate::{
+        plist::{Dictionary, Value},
+        usbmux::{self, Addr, Client, Event, IfaceConnectionType, PairRecord, ReplyCode},
+    };
+
+    fn dict(entries: &[(&str, Value)]) -> Dictionary {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    fn properties(id: u32, udid: &str, connection: &str) -> Dictionary {
+        dict(&[
+            ("DeviceID", id.into()),
+            ("SerialNumber", udid.into()),
+            ("ConnectionType", connection.into()),
+            ("ProductID", 0x12a8.into()),
+            ("LocationID", 0x0110_0000.into()),
+            ("ConnectionSpeed", 480_000_000.into()),
+        ])
+    }
+
+    pub(crate) fn pair_record() -> PairRecord {
+        PairRecord {
+            host_id: "2E3B8A0C-0000-4000-8000-000000000001".to_string(),
+            system_buid: "5F2C9E6A-0000-4000-8000-000000000002".to_string(),
+            host_certificate: b"host cert".to_vec(),
+            host_private_key: b"host key".to_vec(),
+            device_certificate: b"device cert".to_vec(),
+            root_certificate: b"root cert".to_vec(),
+            root_private_key: b"root key".to_vec(),
+            wifi_mac_address: Some("aa:bb:cc:dd:ee:ff".to_string()),
+            escrow_bag: None,
+        }
+    }
+
+    fn reply(code: u64) -> Value {
+        dict(&[("MessageType", "Result".into()), ("Number", code.into())]).into()
+    }
+
+    /// usbmuxd stand-in serving one connection.
+    fn mock(serve: impl FnOnce(&mut TcpStream) + Send + 'static) -> Addr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut s, _) = listener.accept().unwrap();
+            serve(&mut s);
+        });
+        Addr::Tcp(addr)
+    }
+
+    fn expect(s: &mut TcpStream, msg_type: &str) -> (u32, Value) {
+        let (tag, msg) = usbmux::read_message(s).unwrap();
+        assert_eq!(
+            msg.get("MessageType").and_then(Value::as_str),
+            Some(msg_type)
+        );
+        assert_eq!(
+            msg.get("kLibUSBMuxVersion").and_then(Value::as_u64),
+            Some(3)
+        );
+        (tag, msg)
+    }
+
+    #[test]
+    fn devices() {
+        let addr = mock(|s| {
+            let (tag, _) = expect(s, "ListDevices");
+            let list: Vec<Value> = [
+                properties(3, "00008101-000A", "USB"),
+                properties(9, "00008101-000B", "Network"),
+            ]
+            .into_iter()
+            .map(|p| {
+                let id = p["DeviceID"].clone();
+                dict(&[
+                    ("DeviceID", id),
+                    ("MessageType", "Attached".into()),
+                    ("Properties", p.into()),
+                ])
+                .into()
+            })
+            .collect();
+            let res = dict(&[("DeviceList", list.into())]).into();
+            usbmux::write_message(s, tag, &res).unwrap();
+
+            let (tag, msg) = expect(s, "ReadPairRecord");
+            assert_eq!(
+                msg.get("PairRecordID").and_then(Value::as_str),
+                Some("00008101-000A")
+            );
+            let data = pair_record()
+                .to_value()
+                .to_bytes(crate::plist::Format::XmlV1_0)
+                .unwrap();
+            let res = dict(&[("PairRecordData", data.into())]).into();
+            usbmux::write_message(s, tag, &res).unwrap();
+
+            let (tag, _) = expect(s, "ReadPairRecord");
+            usbmux::write_message(s, tag, &reply(2)).unwrap();
+
+            let (tag, msg) = expect(s, "SavePairRecord");
+            assert_eq!(msg.get("DeviceID").and_then(Value::as_u64), Some(3));
+            let data = msg.get("PairRecordData").and_then(Value::as_data).unwrap();
+            let saved = PairRecord::from_value(&Value::from_bytes(data).unwrap()).unwrap();
+            assert_eq!(saved, pair_record());
+            usbmux::write_message(s, tag, &reply(0)).unwrap();
+
+            let (tag, _) = expect(s, "ReadBUID");
+            let res = dict(&[("BUID", "5F2C9E6A-0000-4000-8000-000000000002".into())]).into();
+            usbmux::write_message(s, tag, &res).unwrap();
+        });
+
+        let mut client = Client::connect_addr(&addr).unwrap();
+        let devices = client.devices().unwrap();
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].id(), "00008101-000A");
+        assert_eq!(devices[0].connection_id(), 3);
+        assert_eq!(devices[0].iface_type(), IfaceConnectionType::Direct);
+        assert_eq!(devices[0].product_id(), Some(0x12a8));
+        assert_eq!(devices[0].speed(), Some(480_000_000));
+        assert_eq!(devices[1].iface_type(), IfaceConnectionType::Inderect);
+
+        assert_eq!(client.pair_record("00008101-000A").unwrap(), pair_record());
+        let err = client.pair_record("unknown").unwrap_err();
+        assert!(matches!(err, usbmux::Error::Reply(ReplyCode::BAD_DEVICE)));
+
+        client
+            .save_pair_record("00008101-000A", 3, &pair_record())
+            .unwrap();
+        assert_eq!(client.buid().unwrap(), pair_record().system_buid);
+    }
+
+    #[test]
+    fn listen() {
+        let addr = mock(|s| {
+            let (tag, _) = expect(s, "Listen");
+            usbmux::write_message(s, tag, &reply(0)).unwrap();
+            let events = [
+                dict(&[
+                    ("MessageType", "Attached".into()),
+                    ("DeviceID", 5.into()),
+                    ("Properties", properties(5, "udid", "USB").into()),
+                ]),
+                dict(&[("MessageType", "Paired".into()), ("DeviceID", 5.into())]),
+                dict(&[("MessageType", "Unknown".into())]),
+                dict(&[("MessageType", "Detached".into()), ("DeviceID", 5.into())]),
+            ];
+            for e in events {
+                usbmux::write_message(s, 0, &e.into()).unwrap();
+            }
+        });
+
+        let listener = Client::connect_addr(&addr).unwrap().listen().unwrap();
+        let events: Vec<_> = listener.map(Result::unwrap).collect();
+        assert_eq!(events.len(), 3);
+        let Event::Attached(device) = &events[0] else {
+            panic!("{events:?}");
+        };
+        assert_eq!(device.id(), "udid");
+        assert_eq!(events[1], Event::Paired(5));
+        assert_eq!(events[2], Event::Detached(5));
+    }
+
+    #[test]
+    fn connect() {
+        let addr = mock(|s| {
+            let (tag, msg) = expect(s, "Connect");
+            assert_eq!(msg.get("DeviceID").and_then(Value::as_u64), Some(7));
+            assert_eq!(msg.get("PortNumber").and_then(Value::as_u64), Some(0x7ef2));
+            usbmux::write_message(s, tag, &reply(0)).unwrap();
+            // echo of the device port
+            let mut buf = [0u8; 4];
+            s.read_exact(&mut buf).unwrap();
+            s.write_all(&buf).unwrap();
+        });
+        let mut tunnel = Client::connect_addr(&addr)
+            .unwrap()
+            .connect_to(7, 62078)
+            .unwrap();
+        tunnel.write_all(b"ping").unwrap();
+        let mut buf = [0u8; 4];
+        tunnel.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"ping");
+
+        let addr = mock(|s| {
+            let (tag, _) = expect(s, "Connect");
+            usbmux::write_message(s, tag, &reply(3)).unwrap();
+        });
+        let err = Client::connect_addr(&addr)
+            .unwrap()
+            .connect_to(7, 1)
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            usbmux::Error::Reply(ReplyCode::CONNECTION_REFUSED)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket() {
+        let path = std::env::temp_dir().join(format!("cidre-usbmuxd-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = std::os::unix::net::UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            let (mut s, _) = listener.accept().unwrap();
+            let (tag, _) = usbmux::read_message(&mut s).unwrap();
+            let res = dict(&[("DeviceList", Vec::<Value>::new().into())]).into();
+            usbmux::write_message(&mut s, tag, &res).unwrap();
+        });
+        let addr = Addr::parse(&format!("UNIX:{}", path.display())).unwrap();
+        let mut client = Client::connect_addr(&addr).unwrap();
+        assert!(client.devices().unwrap().is_empty());
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn framing() {
+        let msg: Value = dict(&[("MessageType", "Listen".into())]).into();
+        let mut buf = Vec::new();
+        usbmux::write_message(&mut buf, 42, &msg).unwrap();
+        assert_eq!(&buf[..4], &(buf.len() as u32).to_le_bytes());
+        assert_eq!(&buf[4..16], &[1, 0, 0, 0, 8, 0, 0, 0, 42, 0, 0, 0]);
+        assert_eq!(usbmux::read_message(&mut &buf[..]).unwrap(), (42, msg));
+
+        buf[8] = 7;
+        assert!(usbmux::read_message(&mut &buf[..]).is_err());
+        assert!(usbmux::read_message(&mut &[0u8; 16][..]).is_err());
+
+        assert_eq!(
+            Addr::parse("127.0.0.1:27015").unwrap(),
+            Addr::Tcp(([127, 0, 0, 1], 27015).into())
+        );
+    }
+}
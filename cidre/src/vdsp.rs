@@ -88,26 +88,26 @@ pub struct FftSetup<T>(std::ffi::c_void, std::marker::PhantomData<T>);
 
 struct FftVt<T> {
     transform_io: unsafe extern "C-unwind" fn(
-        __Setup: *mut FftSetup<T>,
-        __C: *mut SplitComplex<T>,
-        __IC: Stride,
-        __Log2N: Len,
-        __Direction: FftDirection,
+        setup: *mut FftSetup<T>,
+        c: *mut SplitComplex<T>,
+        ic: Stride,
+        log2n: Len,
+        direction: FftDirection,
     ),
     transform: unsafe extern "C-unwind" fn(
-        __Setup: *mut FftSetup<T>,
-        __C: *const SplitComplex<T>,
-        __IC: Stride,
-        __Buffer: *mut SplitComplex<T>,
-        __Log2N: Len,
-        __Direction: FftDirection,
+        setup: *mut FftSetup<T>,
+        c: *const SplitComplex<T>,
+        ic: Stride,
+        buffer: *mut SplitComplex<T>,
+        log2n: Len,
+        direction: FftDirection,
     ),
     zrip: unsafe extern "C-unwind" fn(
-        __Setup: *mut FftSetup<T>,
-        __C: *const SplitComplex<T>,
-        __IC: Stride,
-        __Log2N: Len,
-        __Direction: FftDirection,
+        setup: *mut FftSetup<T>,
+        c: *const SplitComplex<T>,
+        ic: Stride,
+        log2n: Len,
+        direction: FftDirection,
     ),
 
     destroy: unsafe extern "C-unwind" fn(*mut FftSetup<T>),
@@ -1269,6 +1269,12 @@ pub fn u16_f32(a: &[u16], c: &mut [f32]) {
     unsafe { _u16_f32(a.as_ptr(), 1, c.as_mut_ptr(), 1, n) };
 }
 
+#[cfg(feature = "vdsp_portable")]
+mod portable;
+#[cfg(feature = "vdsp_portable")]
+pub use portable::*;
+
+#[cfg(not(feature = "vdsp_portable"))]
 #[link(name = "Accelerate", kind = "framework")]
 extern "C-unwind" {
     #[link_name = "vDSP_vadd"]
@@ -2129,9 +2135,9 @@ mod tests {
     fn synth_signal(freq_amp_pairs: &[(f32, f32)], len: usize) -> Vec<f32> {
         let mut res = vec![0.0f32; len];
 
-        for i in 0..len {
+        for (i, v) in res.iter_mut().enumerate() {
             let n_index = (i as f32) / ((len - 1) as f32);
-            res[i] = freq_amp_pairs.iter().fold(0.0f32, |acc, pair| {
+            *v = freq_amp_pairs.iter().fold(0.0f32, |acc, pair| {
                 println!("{pair:?}");
                 let r = acc + (n_index + pair.0 * TAU).sin() * pair.1;
                 println!("{r:?}");
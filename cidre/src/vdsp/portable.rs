@@ -0,0 +1,1560 @@
+//! Pure Rust implementation of the vDSP entry points used by [`crate::vdsp`].
+//!
+//! Every function here mirrors the signature and semantics of its Accelerate
+//! counterpart (strides, argument order, empty-input results and FFT scaling),
+//! so the safe wrappers in `vdsp.rs` work unchanged on any platform.
+
+// Safety contracts are those of the vDSP functions being replaced.
+#![allow(clippy::missing_safety_doc, clippy::too_many_arguments)]
+
+use std::{
+    ops::{Add, Div, Mul, Neg, Sub},
+    ptr::NonNull,
+};
+
+use super::{Complex, FftDirection, FftRadix, FftSetup, Len, SplitComplex, Stride};
+
+trait Real:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn from_f64(val: f64) -> Self;
+}
+
+macro_rules! impl_real {
+    ($t:ty) => {
+        impl Real for $t {
+            const ZERO: Self = 0.0;
+            const ONE: Self = 1.0;
+
+            #[inline(always)]
+            fn from_f64(val: f64) -> Self {
+                val as _
+            }
+        }
+    };
+}
+
+impl_real!(f32);
+impl_real!(f64);
+
+#[inline(always)]
+unsafe fn at<T>(p: *const T, stride: Stride, i: usize) -> *const T {
+    p.offset(stride * i as isize)
+}
+
+#[inline(always)]
+unsafe fn at_mut<T>(p: *mut T, stride: Stride, i: usize) -> *mut T {
+    p.offset(stride * i as isize)
+}
+
+/// `C[i] = f(A[i])`. The unit stride branch is kept separate so it vectorises.
+#[inline(always)]
+unsafe fn map1<A: Copy, C>(
+    a: *const A,
+    ia: Stride,
+    c: *mut C,
+    ic: Stride,
+    n: Len,
+    f: impl Fn(A) -> C,
+) {
+    if ia == 1 && ic == 1 {
+        for i in 0..n {
+            *c.add(i) = f(*a.add(i));
+        }
+    } else {
+        for i in 0..n {
+            *at_mut(c, ic, i) = f(*at(a, ia, i));
+        }
+    }
+}
+
+/// `C[i] = f(A[i], B[i])`
+#[inline(always)]
+unsafe fn map2<T: Copy>(
+    a: *const T,
+    ia: Stride,
+    b: *const T,
+    ib: Stride,
+    c: *mut T,
+    ic: Stride,
+    n: Len,
+    f: impl Fn(T, T) -> T,
+) {
+    if ia == 1 && ib == 1 && ic == 1 {
+        for i in 0..n {
+            *c.add(i) = f(*a.add(i), *b.add(i));
+        }
+    } else {
+        for i in 0..n {
+            *at_mut(c, ic, i) = f(*at(a, ia, i), *at(b, ib, i));
+        }
+    }
+}
+
+/// `D[i] = f(A[i], B[i], C[i])`
+#[inline(always)]
+unsafe fn map3<T: Copy>(
+    a: *const T,
+    ia: Stride,
+    b: *const T,
+    ib: Stride,
+    c: *const T,
+    ic: Stride,
+    d: *mut T,
+    id: Stride,
+    n: Len,
+    f: impl Fn(T, T, T) -> T,
+) {
+    for i in 0..n {
+        *at_mut(d, id, i) = f(*at(a, ia, i), *at(b, ib, i), *at(c, ic, i));
+    }
+}
+
+const LANES: usize = 8;
+
+/// Sum of `f(A[i], B[i])` using independent partial sums so the
+/// unit stride loop can be vectorised without fast-math.
+#[inline(always)]
+unsafe fn sum2<T: Real>(
+    a: *const T,
+    ia: Stride,
+    b: *const T,
+    ib: Stride,
+    n: Len,
+    f: impl Fn(T, T) -> T,
+) -> T {
+    let mut acc = [T::ZERO; LANES];
+    let mut i = 0;
+    if ia == 1 && ib == 1 {
+        while i + LANES <= n {
+            for (l, acc) in acc.iter_mut().enumerate() {
+                *acc = *acc + f(*a.add(i + l), *b.add(i + l));
+            }
+            i += LANES;
+        }
+    }
+    let mut tail = T::ZERO;
+    while i < n {
+        tail = tail + f(*at(a, ia, i), *at(b, ib, i));
+        i += 1;
+    }
+    acc.iter().fold(T::ZERO, |s, v| s + *v) + tail
+}
+
+#[inline(always)]
+unsafe fn sum1<T: Real>(a: *const T, ia: Stride, n: Len, f: impl Fn(T) -> T) -> T {
+    sum2(a, ia, a, ia, n, |x, _| f(x))
+}
+
+#[inline(always)]
+unsafe fn fold1<T: Real>(a: *const T, ia: Stride, n: Len, init: T, f: impl Fn(T, T) -> T) -> T {
+    let mut res = init;
+    for i in 0..n {
+        res = f(res, *at(a, ia, i));
+    }
+    res
+}
+
+#[inline(always)]
+fn max<T: Real>(a: T, b: T) -> T {
+    if b > a {
+        b
+    } else {
+        a
+    }
+}
+
+#[inline(always)]
+fn min<T: Real>(a: T, b: T) -> T {
+    if b < a {
+        b
+    } else {
+        a
+    }
+}
+
+macro_rules! real_fns {
+    (
+        $t:ty,
+        $add:ident,
+        $sub:ident,
+        $mul:ident,
+        $div:ident,
+        $smul:ident,
+        $sq:ident,
+        $ssq:ident,
+        $mean:ident,
+        $meansq:ident,
+        $distance_sq:ident,
+        $dotpr:ident,
+        $am:ident,
+        $ma:ident,
+        $abs:ident,
+        $fill:ident,
+        $clr:ident,
+        $sadd:ident,
+        $se:ident,
+        $semg:ident,
+        $sesq:ident,
+        $svs:ident,
+        $maxmg:ident,
+        $minmg:ident,
+        $max:ident,
+        $min:ident,
+        $gen:ident,
+        $ramp:ident,
+        $rampmul:ident,
+        $rampmul2:ident,
+        $neg:ident,
+        $tmerg:ident,
+        $ctoz:ident,
+        $ztoc:ident,
+        $zaspec:ident,
+        $dbcon:ident,
+        $clip:ident
+    ) => {
+        pub unsafe extern "C-unwind" fn $add(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map2(a, ia, b, ib, c, ic, n, |a, b| a + b)
+        }
+
+        /// `C[i] = A[i] - B[i]`, note that `B` comes first as in vDSP.
+        pub unsafe extern "C-unwind" fn $sub(
+            b: *const $t,
+            ib: Stride,
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map2(a, ia, b, ib, c, ic, n, |a, b| a - b)
+        }
+
+        pub unsafe extern "C-unwind" fn $mul(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map2(a, ia, b, ib, c, ic, n, |a, b| a * b)
+        }
+
+        /// `C[i] = A[i] / B[i]`, note that `B` comes first as in vDSP.
+        pub unsafe extern "C-unwind" fn $div(
+            b: *const $t,
+            ib: Stride,
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map2(a, ia, b, ib, c, ic, n, |a, b| a / b)
+        }
+
+        pub unsafe extern "C-unwind" fn $smul(
+            a: *const $t,
+            ia: Stride,
+            b: &$t,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            let b = *b;
+            map1(a, ia, c, ic, n, |a| a * b)
+        }
+
+        pub unsafe extern "C-unwind" fn $sq(
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map1(a, ia, c, ic, n, |a| a * a)
+        }
+
+        pub unsafe extern "C-unwind" fn $ssq(
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map1(a, ia, c, ic, n, |a| a * a.abs())
+        }
+
+        /// Mean of an empty vector is NaN.
+        pub unsafe extern "C-unwind" fn $mean(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a) / n as $t;
+        }
+
+        pub unsafe extern "C-unwind" fn $meansq(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a * a) / n as $t;
+        }
+
+        pub unsafe extern "C-unwind" fn $distance_sq(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            n: Len,
+        ) {
+            *c = sum2(a, ia, b, ib, n, |a, b| (a - b) * (a - b));
+        }
+
+        pub unsafe extern "C-unwind" fn $dotpr(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            n: Len,
+        ) {
+            *c = sum2(a, ia, b, ib, n, |a, b| a * b);
+        }
+
+        /// `D[i] = (A[i] + B[i]) * C[i]`
+        pub unsafe extern "C-unwind" fn $am(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *const $t,
+            ic: Stride,
+            d: *mut $t,
+            id: Stride,
+            n: Len,
+        ) {
+            map3(a, ia, b, ib, c, ic, d, id, n, |a, b, c| (a + b) * c)
+        }
+
+        /// `D[i] = A[i] * B[i] + C[i]`
+        pub unsafe extern "C-unwind" fn $ma(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *const $t,
+            ic: Stride,
+            d: *mut $t,
+            id: Stride,
+            n: Len,
+        ) {
+            map3(a, ia, b, ib, c, ic, d, id, n, |a, b, c| a * b + c)
+        }
+
+        pub unsafe extern "C-unwind" fn $abs(
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map1(a, ia, c, ic, n, |a| a.abs())
+        }
+
+        pub unsafe extern "C-unwind" fn $fill(a: &$t, c: *mut $t, ic: Stride, n: Len) {
+            for i in 0..n {
+                *at_mut(c, ic, i) = *a;
+            }
+        }
+
+        pub unsafe extern "C-unwind" fn $clr(c: *mut $t, ic: Stride, n: Len) {
+            $fill(&0.0, c, ic, n)
+        }
+
+        pub unsafe extern "C-unwind" fn $sadd(
+            a: *const $t,
+            ia: Stride,
+            b: &$t,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            let b = *b;
+            map1(a, ia, c, ic, n, |a| a + b)
+        }
+
+        pub unsafe extern "C-unwind" fn $se(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a);
+        }
+
+        pub unsafe extern "C-unwind" fn $semg(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a.abs());
+        }
+
+        pub unsafe extern "C-unwind" fn $sesq(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a * a);
+        }
+
+        pub unsafe extern "C-unwind" fn $svs(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = sum1(a, ia, n, |a| a * a.abs());
+        }
+
+        /// Maximum magnitude of an empty vector is 0.
+        pub unsafe extern "C-unwind" fn $maxmg(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = fold1(a, ia, n, 0.0, |r, a| max(r, a.abs()));
+        }
+
+        /// Minimum magnitude of an empty vector is +inf.
+        pub unsafe extern "C-unwind" fn $minmg(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = fold1(a, ia, n, <$t>::INFINITY, |r, a| min(r, a.abs()));
+        }
+
+        /// Maximum of an empty vector is -inf.
+        pub unsafe extern "C-unwind" fn $max(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = fold1(a, ia, n, -<$t>::INFINITY, max);
+        }
+
+        /// Minimum of an empty vector is +inf.
+        pub unsafe extern "C-unwind" fn $min(a: *const $t, ia: Stride, c: *mut $t, n: Len) {
+            *c = fold1(a, ia, n, <$t>::INFINITY, min);
+        }
+
+        /// `C[i] = A + (B - A) * i / (N - 1)`
+        pub unsafe extern "C-unwind" fn $gen(a: &$t, b: &$t, c: *mut $t, ic: Stride, n: Len) {
+            let (a, b) = (*a, *b);
+            let last = if n > 1 { (n - 1) as $t } else { 1.0 };
+            for i in 0..n {
+                *at_mut(c, ic, i) = a + (b - a) * i as $t / last;
+            }
+        }
+
+        /// `C[i] = A + i * B`
+        pub unsafe extern "C-unwind" fn $ramp(a: &$t, b: &$t, c: *mut $t, ic: Stride, n: Len) {
+            let (a, b) = (*a, *b);
+            for i in 0..n {
+                *at_mut(c, ic, i) = a + i as $t * b;
+            }
+        }
+
+        pub unsafe extern "C-unwind" fn $rampmul(
+            i: *const $t,
+            is: Stride,
+            start: &mut $t,
+            step: &$t,
+            o: *mut $t,
+            os: Stride,
+            n: Len,
+        ) {
+            for k in 0..n {
+                *at_mut(o, os, k) = *start * *at(i, is, k);
+                *start += *step;
+            }
+        }
+
+        pub unsafe extern "C-unwind" fn $rampmul2(
+            i0: *const $t,
+            i1: *const $t,
+            is: Stride,
+            start: &mut $t,
+            step: &$t,
+            o0: *mut $t,
+            o1: *mut $t,
+            os: Stride,
+            n: Len,
+        ) {
+            for k in 0..n {
+                *at_mut(o0, os, k) = *start * *at(i0, is, k);
+                *at_mut(o1, os, k) = *start * *at(i1, is, k);
+                *start += *step;
+            }
+        }
+
+        pub unsafe extern "C-unwind" fn $neg(
+            a: *const $t,
+            ia: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            map1(a, ia, c, ic, n, |a| -a)
+        }
+
+        /// `C[i] = A[i] + (B[i] - A[i]) * i / (N - 1)`
+        pub unsafe extern "C-unwind" fn $tmerg(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            ib: Stride,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+        ) {
+            let last = if n > 1 { (n - 1) as $t } else { 1.0 };
+            for i in 0..n {
+                let (a, b) = (*at(a, ia, i), *at(b, ib, i));
+                *at_mut(c, ic, i) = a + (b - a) * i as $t / last;
+            }
+        }
+
+        /// `ic` is measured in scalars, so 2 means contiguous complex values.
+        pub unsafe extern "C-unwind" fn $ctoz(
+            c: *const Complex<$t>,
+            ic: Stride,
+            z: *const SplitComplex<$t>,
+            iz: Stride,
+            n: Len,
+        ) {
+            let z = &*z;
+            for i in 0..n {
+                let v = *at(c, ic / 2, i);
+                *at_mut(z.re, iz, i) = v.re;
+                *at_mut(z.im, iz, i) = v.im;
+            }
+        }
+
+        /// `ic` is measured in scalars, so 2 means contiguous complex values.
+        pub unsafe extern "C-unwind" fn $ztoc(
+            z: *const SplitComplex<$t>,
+            iz: Stride,
+            c: *mut Complex<$t>,
+            ic: Stride,
+            n: Len,
+        ) {
+            let z = &*z;
+            for i in 0..n {
+                *at_mut(c, ic / 2, i) = Complex {
+                    re: *at(z.re, iz, i),
+                    im: *at(z.im, iz, i),
+                };
+            }
+        }
+
+        pub unsafe extern "C-unwind" fn $zaspec(a: *const SplitComplex<$t>, c: *mut $t, n: Len) {
+            let a = &*a;
+            for i in 0..n {
+                let (re, im) = (*a.re.add(i), *a.im.add(i));
+                *c.add(i) += re * re + im * im;
+            }
+        }
+
+        /// `C[i] = alpha * log10(A[i] / B)` where alpha is 20 for `f == 1`
+        /// (amplitude) and 10 otherwise (power).
+        pub unsafe extern "C-unwind" fn $dbcon(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            c: *mut $t,
+            ic: Stride,
+            n: Len,
+            f: u32,
+        ) {
+            let b = *b;
+            let alpha: $t = if f == 1 { 20.0 } else { 10.0 };
+            map1(a, ia, c, ic, n, |a| alpha * (a / b).log10())
+        }
+
+        /// `D[i] = clamp(A[i], B, C)`
+        pub unsafe extern "C-unwind" fn $clip(
+            a: *const $t,
+            ia: Stride,
+            b: *const $t,
+            c: *const $t,
+            d: *mut $t,
+            id: Stride,
+            n: Len,
+        ) {
+            let (lo, hi) = (*b, *c);
+            map1(a, ia, d, id, n, |a| min(max(a, lo), hi))
+        }
+    };
+}
+
+real_fns!(
+    f32,
+    _add_f32,
+    _sub_f32,
+    _mul_f32,
+    _div_f32,
+    _smul_f32,
+    _sq_f32,
+    _ssq_f32,
+    _mean_f32,
+    _meansq_f32,
+    _distance_sq_f32,
+    _dotpr_f32,
+    _am_f32,
+    _ma_f32,
+    _abs_f32,
+    _fill_f32,
+    _clr_f32,
+    _sadd_f32,
+    _se_f32,
+    _semg_f32,
+    _sesq_f32,
+    _svs_f32,
+    _maxmg_f32,
+    _minmg_f32,
+    _max_f32,
+    _min_f32,
+    _gen_f32,
+    _ramp_f32,
+    _rampmul_f32,
+    _rampmul2_f32,
+    _neg_f32,
+    _tmerg_f32,
+    _ctoz_f32,
+    _ztoc_f32,
+    _zaspec_f32,
+    _dbcon_f32,
+    _clip_f32
+);
+
+real_fns!(
+    f64,
+    _add_f64,
+    _sub_f64,
+    _mul_f64,
+    _div_f64,
+    _smul_f64,
+    _sq_f64,
+    _ssq_f64,
+    _mean_f64,
+    _meansq_f64,
+    _distance_sq_f64,
+    _dotpr_f64,
+    _am_f64,
+    _ma_f64,
+    _abs_f64,
+    _fill_f64,
+    _clr_f64,
+    _sadd_f64,
+    _se_f64,
+    _semg_f64,
+    _sesq_f64,
+    _svs_f64,
+    _maxmg_f64,
+    _minmg_f64,
+    _max_f64,
+    _min_f64,
+    _gen_f64,
+    _ramp_f64,
+    _rampmul_f64,
+    _rampmul2_f64,
+    _neg_f64,
+    _tmerg_f64,
+    _ctoz_f64,
+    _ztoc_f64,
+    _zaspec_f64,
+    _dbcon_f64,
+    _clip_f64
+);
+
+/// Integer ops wrap on overflow, like the vectorised Accelerate kernels.
+pub unsafe extern "C-unwind" fn _add_i32(
+    a: *const i32,
+    ia: Stride,
+    b: *const i32,
+    ib: Stride,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    map2(a, ia, b, ib, c, ic, n, i32::wrapping_add)
+}
+
+pub unsafe extern "C-unwind" fn _sub_i32(
+    b: *const i32,
+    ib: Stride,
+    a: *const i32,
+    ia: Stride,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    map2(a, ia, b, ib, c, ic, n, i32::wrapping_sub)
+}
+
+pub unsafe extern "C-unwind" fn _div_i32(
+    b: *const i32,
+    ib: Stride,
+    a: *const i32,
+    ia: Stride,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    map2(a, ia, b, ib, c, ic, n, i32::wrapping_div)
+}
+
+pub unsafe extern "C-unwind" fn _abs_i32(
+    a: *const i32,
+    ia: Stride,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    map1(a, ia, c, ic, n, i32::wrapping_abs)
+}
+
+pub unsafe extern "C-unwind" fn _eqv_i32(
+    a: *const i32,
+    ia: Stride,
+    b: *const i32,
+    ib: Stride,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    map2(a, ia, b, ib, c, ic, n, |a, b| !(a ^ b))
+}
+
+pub unsafe extern "C-unwind" fn _fill_i32(a: &i32, c: *mut i32, ic: Stride, n: Len) {
+    for i in 0..n {
+        *at_mut(c, ic, i) = *a;
+    }
+}
+
+pub unsafe extern "C-unwind" fn _sadd_i32(
+    a: *const i32,
+    ia: Stride,
+    b: &i32,
+    c: *mut i32,
+    ic: Stride,
+    n: Len,
+) {
+    let b = *b;
+    map1(a, ia, c, ic, n, |a| a.wrapping_add(b))
+}
+
+pub unsafe extern "C-unwind" fn _f64_f32(
+    a: *const f64,
+    ia: Stride,
+    c: *mut f32,
+    ic: Stride,
+    n: Len,
+) {
+    map1(a, ia, c, ic, n, |a| a as f32)
+}
+
+pub unsafe extern "C-unwind" fn _f32_f64(
+    a: *const f32,
+    ia: Stride,
+    c: *mut f64,
+    ic: Stride,
+    n: Len,
+) {
+    map1(a, ia, c, ic, n, |a| a as f64)
+}
+
+pub unsafe extern "C-unwind" fn _i16_f32(
+    a: *const i16,
+    ia: Stride,
+    c: *mut f32,
+    ic: Stride,
+    n: Len,
+) {
+    map1(a, ia, c, ic, n, |a| a as f32)
+}
+
+pub unsafe extern "C-unwind" fn _u16_f32(
+    a: *const u16,
+    ia: Stride,
+    c: *mut f32,
+    ic: Stride,
+    n: Len,
+) {
+    map1(a, ia, c, ic, n, |a| a as f32)
+}
+
+/// Largest supported `log2n`, keeps twiddle tables addressable.
+const MAX_LOG2N: Len = 30;
+
+/// Twiddle factors `e^(2πij/N)` for `j < N/2`, where `N` is the setup size.
+///
+/// Accelerate's radix 3 and 5 setups also serve radix 2 transforms, and only
+/// those are exposed through [`crate::vdsp::Fft`], so the radix is ignored.
+struct Setup<T> {
+    log2n: Len,
+    cos: Vec<T>,
+    sin: Vec<T>,
+}
+
+impl<T: Real> Setup<T> {
+    fn new(log2n: Len) -> Self {
+        let n = 1usize << log2n;
+        let (cos, sin) = (0..n / 2)
+            .map(|j| {
+                let phase = std::f64::consts::TAU * j as f64 / n as f64;
+                (T::from_f64(phase.cos()), T::from_f64(phase.sin()))
+            })
+            .unzip();
+        Self { log2n, cos, sin }
+    }
+
+    /// `e^(∓2πik/2^log2n)` for forward and inverse directions respectively.
+    #[inline(always)]
+    fn twiddle(&self, k: usize, log2n: Len, dir: FftDirection) -> (T, T) {
+        let j = k << (self.log2n - log2n);
+        match dir {
+            FftDirection::Forward => (self.cos[j], -self.sin[j]),
+            FftDirection::Inverse => (self.cos[j], self.sin[j]),
+        }
+    }
+}
+
+unsafe fn create_setup<T: Real>(log2n: Len) -> Option<NonNull<FftSetup<T>>> {
+    if log2n > MAX_LOG2N {
+        return None;
+    }
+    NonNull::new(Box::into_raw(Box::new(Setup::<T>::new(log2n))) as *mut FftSetup<T>)
+}
+
+unsafe fn destroy_setup<T>(setup: *mut FftSetup<T>) {
+    if !setup.is_null() {
+        drop(Box::from_raw(setup as *mut Setup<T>));
+    }
+}
+
+unsafe fn setup<'a, T>(setup: *mut FftSetup<T>, log2n: Len) -> &'a Setup<T> {
+    let setup = &*(setup as *const Setup<T>);
+    assert!(
+        log2n <= setup.log2n,
+        "log2n {log2n} exceeds fft setup size {}",
+        setup.log2n
+    );
+    setup
+}
+
+/// In-place iterative radix-2 complex FFT. Unscaled in both directions.
+unsafe fn fft<T: Real>(
+    s: &Setup<T>,
+    re: *mut T,
+    im: *mut T,
+    ic: Stride,
+    log2n: Len,
+    dir: FftDirection,
+) {
+    let n = 1usize << log2n;
+    let re = |i: usize| at_mut(re, ic, i);
+    let im = |i: usize| at_mut(im, ic, i);
+
+    let mut j = 0usize;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j ^= bit;
+        if i < j {
+            std::ptr::swap(re(i), re(j));
+            std::ptr::swap(im(i), im(j));
+        }
+    }
+
+    let mut log2len = 1;
+    while log2len <= log2n {
+        let half = 1usize << (log2len - 1);
+        for start in (0..n).step_by(half * 2) {
+            for k in 0..half {
+                let (wr, wi) = s.twiddle(k, log2len, dir);
+                let (a, b) = (start + k, start + k + half);
+                let (br, bi) = (*re(b), *im(b));
+                let tr = br * wr - bi * wi;
+                let ti = br * wi + bi * wr;
+                let (ar, ai) = (*re(a), *im(a));
+                *re(b) = ar - tr;
+                *im(b) = ai - ti;
+                *re(a) = ar + tr;
+                *im(a) = ai + ti;
+            }
+        }
+        log2len += 1;
+    }
+}
+
+/// In-place real FFT of `2^log2n` values packed as even/odd split complex.
+///
+/// Forward output is `2·X[k]` for `0 < k < N/2`, with `2·X[0]` in `re[0]` and
+/// `2·X[N/2]` in `im[0]`. Inverse takes that layout back, so a round trip
+/// scales the signal by `2N`, exactly as in vDSP.
+unsafe fn fft_real<T: Real>(
+    s: &Setup<T>,
+    re: *mut T,
+    im: *mut T,
+    ic: Stride,
+    log2n: Len,
+    dir: FftDirection,
+) {
+    if log2n == 0 {
+        return;
+    }
+    let m = 1usize << (log2n - 1);
+    let two = T::ONE + T::ONE;
+    let r = |i: usize| at_mut(re, ic, i);
+    let i_ = |i: usize| at_mut(im, ic, i);
+
+    match dir {
+        FftDirection::Forward => {
+            fft(s, re, im, ic, log2n - 1, dir);
+            let (z0r, z0i) = (*r(0), *i_(0));
+            *r(0) = two * (z0r + z0i);
+            *i_(0) = two * (z0r - z0i);
+            for k in 1..=m / 2 {
+                let mk = m - k;
+                let (kr, ki) = (*r(k), *i_(k));
+                let (mr, mi) = (*r(mk), *i_(mk));
+                // Y[k] = (Z[k] + Z*[m-k]) - i·W^k·(Z[k] - Z*[m-k])
+                let post = |ar: T, ai: T, br: T, bi: T, j: usize| {
+                    let (c, s) = s.twiddle(j, log2n, dir);
+                    let (sr, si) = (ar + br, ai + bi);
+                    let (dr, di) = (ar - br, ai - bi);
+                    let (wr, wi) = (c * dr - s * di, c * di + s * dr);
+                    (sr + wi, si - wr)
+                };
+                let yk = post(kr, ki, mr, -mi, k);
+                let ym = post(mr, mi, kr, -ki, mk);
+                (*r(k), *i_(k)) = yk;
+                (*r(mk), *i_(mk)) = ym;
+            }
+        }
+        FftDirection::Inverse => {
+            let (y0r, y0i) = (*r(0), *i_(0));
+            *r(0) = y0r + y0i;
+            *i_(0) = y0r - y0i;
+            for k in 1..=m / 2 {
+                let mk = m - k;
+                let (kr, ki) = (*r(k), *i_(k));
+                let (mr, mi) = (*r(mk), *i_(mk));
+                // Z[k] = (Y[k] + Y*[m-k]) + i·W^-k·(Y[k] - Y*[m-k])
+                let pre = |ar: T, ai: T, br: T, bi: T, j: usize| {
+                    let (c, s) = s.twiddle(j, log2n, dir);
+                    let (sr, si) = (ar + br, ai + bi);
+                    let (dr, di) = (ar - br, ai - bi);
+                    let (wr, wi) = (c * dr - s * di, c * di + s * dr);
+                    (sr - wi, si + wr)
+                };
+                let zk = pre(kr, ki, mr, -mi, k);
+                let zm = pre(mr, mi, kr, -ki, mk);
+                (*r(k), *i_(k)) = zk;
+                (*r(mk), *i_(mk)) = zm;
+            }
+            fft(s, re, im, ic, log2n - 1, dir);
+        }
+    }
+}
+
+macro_rules! fft_fns {
+    ($t:ty, $create:ident, $destroy:ident, $zip:ident, $zipt:ident, $zrip:ident) => {
+        pub unsafe extern "C-unwind" fn $create(
+            log2n: Len,
+            _radix: FftRadix,
+        ) -> Option<NonNull<FftSetup<$t>>> {
+            create_setup(log2n)
+        }
+
+        pub unsafe extern "C-unwind" fn $destroy(setup: *mut FftSetup<$t>) {
+            destroy_setup(setup)
+        }
+
+        pub unsafe extern "C-unwind" fn $zip(
+            setup_: *mut FftSetup<$t>,
+            c: *mut SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            dir: FftDirection,
+        ) {
+            let c = &*c;
+            fft(setup(setup_, log2n), c.re, c.im, ic, log2n, dir)
+        }
+
+        /// The temporary buffer is not needed by this implementation.
+        pub unsafe extern "C-unwind" fn $zipt(
+            setup_: *mut FftSetup<$t>,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            _buffer: *mut SplitComplex<$t>,
+            log2n: Len,
+            dir: FftDirection,
+        ) {
+            let c = &*c;
+            fft(setup(setup_, log2n), c.re, c.im, ic, log2n, dir)
+        }
+
+        pub unsafe extern "C-unwind" fn $zrip(
+            setup_: *mut FftSetup<$t>,
+            c: *const SplitComplex<$t>,
+            ic: Stride,
+            log2n: Len,
+            dir: FftDirection,
+        ) {
+            let c = &*c;
+            fft_real(setup(setup_, log2n), c.re, c.im, ic, log2n, dir)
+        }
+    };
+}
+
+fft_fns!(
+    f32,
+    _create_fftsetup_f32,
+    _destroy_fftsetup_f32,
+    _fft_zip_f32,
+    _fft_zipt_f32,
+    _fft_zrip_f32
+);
+
+fft_fns!(
+    f64,
+    _create_fftsetup_f64,
+    _destroy_fftsetup_f64,
+    _fft_zip_f64,
+    _fft_zipt_f64,
+    _fft_zrip_f64
+);
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, ptr::NonNull};
+
+    use crate::vdsp::{self, Complex, FftDirection, FftRadix, FftSetup, Len, SplitComplex, Stride};
+
+    /// Expected vDSP outputs, one case per line: `name v0 v1 ...`. The header
+    /// says where they come from, `record_fixtures` rewrites them on macOS.
+    const FIXTURES: &str = include_str!("fixtures.txt");
+    #[cfg(target_os = "macos")]
+    const FIXTURES_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/vdsp/fixtures.txt");
+
+    type Reduce = unsafe extern "C-unwind" fn(*const f32, Stride, *mut f32, Len);
+    type Reduce2 =
+        unsafe extern "C-unwind" fn(*const f32, Stride, *const f32, Stride, *mut f32, Len);
+
+    /// The subset of entry points exercised by the fixtures.
+    struct Backend {
+        create_fftsetup:
+            unsafe extern "C-unwind" fn(Len, FftRadix) -> Option<NonNull<FftSetup<f32>>>,
+        destroy_fftsetup: unsafe extern "C-unwind" fn(*mut FftSetup<f32>),
+        fft_zip: unsafe extern "C-unwind" fn(
+            *mut FftSetup<f32>,
+            *mut SplitComplex<f32>,
+            Stride,
+            Len,
+            FftDirection,
+        ),
+        fft_zrip: unsafe extern "C-unwind" fn(
+            *mut FftSetup<f32>,
+            *const SplitComplex<f32>,
+            Stride,
+            Len,
+            FftDirection,
+        ),
+        ctoz: unsafe extern "C-unwind" fn(
+            *const Complex<f32>,
+            Stride,
+            *const SplitComplex<f32>,
+            Stride,
+            Len,
+        ),
+        dotpr: Reduce2,
+        distance_sq: Reduce2,
+        meansq: Reduce,
+        svs: Reduce,
+        max: Reduce,
+        min: Reduce,
+        maxmg: Reduce,
+        minmg: Reduce,
+        gen: unsafe extern "C-unwind" fn(&f32, &f32, *mut f32, Stride, Len),
+        tmerg: unsafe extern "C-unwind" fn(
+            *const f32,
+            Stride,
+            *const f32,
+            Stride,
+            *mut f32,
+            Stride,
+            Len,
+        ),
+        rampmul:
+            unsafe extern "C-unwind" fn(*const f32, Stride, &mut f32, &f32, *mut f32, Stride, Len),
+        dbcon:
+            unsafe extern "C-unwind" fn(*const f32, Stride, *const f32, *mut f32, Stride, Len, u32),
+        clip: unsafe extern "C-unwind" fn(
+            *const f32,
+            Stride,
+            *const f32,
+            *const f32,
+            *mut f32,
+            Stride,
+            Len,
+        ),
+    }
+
+    const PORTABLE: Backend = Backend {
+        create_fftsetup: super::_create_fftsetup_f32,
+        destroy_fftsetup: super::_destroy_fftsetup_f32,
+        fft_zip: super::_fft_zip_f32,
+        fft_zrip: super::_fft_zrip_f32,
+        ctoz: super::_ctoz_f32,
+        dotpr: super::_dotpr_f32,
+        distance_sq: super::_distance_sq_f32,
+        meansq: super::_meansq_f32,
+        svs: super::_svs_f32,
+        max: super::_max_f32,
+        min: super::_min_f32,
+        maxmg: super::_maxmg_f32,
+        minmg: super::_minmg_f32,
+        gen: super::_gen_f32,
+        tmerg: super::_tmerg_f32,
+        rampmul: super::_rampmul_f32,
+        dbcon: super::_dbcon_f32,
+        clip: super::_clip_f32,
+    };
+
+    #[cfg(target_os = "macos")]
+    mod accelerate {
+        use std::ptr::NonNull;
+
+        use crate::vdsp::{Complex, FftDirection, FftRadix, FftSetup, Len, SplitComplex, Stride};
+
+        #[repr(C)]
+        pub struct DftSetup {
+            _private: [u8; 0],
+        }
+
+        /// `vDSP_DFT_Execute` of a complex (`zop`) or a packed real (`zrop`)
+        /// transform on the inputs `Backend::zip` and `Backend::zrip` use.
+        pub fn dft(log2n: Len, real: bool, seed: u64, dir: FftDirection) -> Vec<f32> {
+            let n = 1 << log2n;
+            let (re, im) = if real {
+                let x = super::signal(seed, n);
+                x.chunks(2).map(|p| (p[0], p[1])).unzip()
+            } else {
+                (super::signal(seed, n), super::signal(seed + 1, n))
+            };
+            let mut out_re = vec![0.0f32; re.len()];
+            let mut out_im = vec![0.0f32; im.len()];
+            unsafe {
+                let setup = if real {
+                    vDSP_DFT_zrop_CreateSetup(None, n, dir)
+                } else {
+                    vDSP_DFT_zop_CreateSetup(None, n, dir)
+                }
+                .expect("dft setup");
+                vDSP_DFT_Execute(
+                    setup.as_ptr(),
+                    re.as_ptr(),
+                    im.as_ptr(),
+                    out_re.as_mut_ptr(),
+                    out_im.as_mut_ptr(),
+                );
+                vDSP_DFT_DestroySetup(setup.as_ptr());
+            }
+            out_re.extend(out_im);
+            out_re
+        }
+
+        #[link(name = "Accelerate", kind = "framework")]
+        extern "C-unwind" {
+            pub fn vDSP_create_fftsetup(
+                log2n: Len,
+                radix: FftRadix,
+            ) -> Option<NonNull<FftSetup<f32>>>;
+            pub fn vDSP_destroy_fftsetup(setup: *mut FftSetup<f32>);
+            pub fn vDSP_fft_zip(
+                setup: *mut FftSetup<f32>,
+                c: *mut SplitComplex<f32>,
+                ic: Stride,
+                log2n: Len,
+                dir: FftDirection,
+            );
+            pub fn vDSP_fft_zrip(
+                setup: *mut FftSetup<f32>,
+                c: *const SplitComplex<f32>,
+                ic: Stride,
+                log2n: Len,
+                dir: FftDirection,
+            );
+            pub fn vDSP_ctoz(
+                c: *const Complex<f32>,
+                ic: Stride,
+                z: *const SplitComplex<f32>,
+                iz: Stride,
+                n: Len,
+            );
+            pub fn vDSP_dotpr(
+                a: *const f32,
+                ia: Stride,
+                b: *const f32,
+                ib: Stride,
+                c: *mut f32,
+                n: Len,
+            );
+            pub fn vDSP_distancesq(
+                a: *const f32,
+                ia: Stride,
+                b: *const f32,
+                ib: Stride,
+                c: *mut f32,
+                n: Len,
+            );
+            pub fn vDSP_measqv(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_svs(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_maxv(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_minv(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_maxmgv(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_minmgv(a: *const f32, ia: Stride, c: *mut f32, n: Len);
+            pub fn vDSP_vgen(a: &f32, b: &f32, c: *mut f32, ic: Stride, n: Len);
+            pub fn vDSP_vtmerg(
+                a: *const f32,
+                ia: Stride,
+                b: *const f32,
+                ib: Stride,
+                c: *mut f32,
+                ic: Stride,
+                n: Len,
+            );
+            pub fn vDSP_vrampmul(
+                i: *const f32,
+                is: Stride,
+                start: &mut f32,
+                step: &f32,
+                o: *mut f32,
+                os: Stride,
+                n: Len,
+            );
+            pub fn vDSP_vdbcon(
+                a: *const f32,
+                ia: Stride,
+                b: *const f32,
+                c: *mut f32,
+                ic: Stride,
+                n: Len,
+                f: u32,
+            );
+            pub fn vDSP_vclip(
+                a: *const f32,
+                ia: Stride,
+                b: *const f32,
+                c: *const f32,
+                d: *mut f32,
+                id: Stride,
+                n: Len,
+            );
+            pub fn vDSP_DFT_zop_CreateSetup(
+                previous: Option<NonNull<DftSetup>>,
+                len: Len,
+                dir: FftDirection,
+            ) -> Option<NonNull<DftSetup>>;
+            pub fn vDSP_DFT_zrop_CreateSetup(
+                previous: Option<NonNull<DftSetup>>,
+                len: Len,
+                dir: FftDirection,
+            ) -> Option<NonNull<DftSetup>>;
+            pub fn vDSP_DFT_Execute(
+                setup: *const DftSetup,
+                ir: *const f32,
+                ii: *const f32,
+                or: *mut f32,
+                oi: *mut f32,
+            );
+            pub fn vDSP_DFT_DestroySetup(setup: *mut DftSetup);
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    const ACCELERATE: Backend = Backend {
+        create_fftsetup: accelerate::vDSP_create_fftsetup,
+        destroy_fftsetup: accelerate::vDSP_destroy_fftsetup,
+        fft_zip: accelerate::vDSP_fft_zip,
+        fft_zrip: accelerate::vDSP_fft_zrip,
+        ctoz: accelerate::vDSP_ctoz,
+        dotpr: accelerate::vDSP_dotpr,
+        distance_sq: accelerate::vDSP_distancesq,
+        meansq: accelerate::vDSP_measqv,
+        svs: accelerate::vDSP_svs,
+        max: accelerate::vDSP_maxv,
+        min: accelerate::vDSP_minv,
+        maxmg: accelerate::vDSP_maxmgv,
+        minmg: accelerate::vDSP_minmgv,
+        gen: accelerate::vDSP_vgen,
+        tmerg: accelerate::vDSP_vtmerg,
+        rampmul: accelerate::vDSP_vrampmul,
+        dbcon: accelerate::vDSP_vdbcon,
+        clip: accelerate::vDSP_vclip,
+    };
+
+    /// Deterministic values in [-1, 1) with 24 significant bits, exact in f32.
+    fn signal(seed: u64, n: usize) -> Vec<f32> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (x >> 40) as f32 / (1u32 << 23) as f32 - 1.0
+            })
+            .collect()
+    }
+
+    impl Backend {
+        fn fft(&self, log2n: Len, f: impl FnOnce(*mut FftSetup<f32>)) {
+            let setup = unsafe { (self.create_fftsetup)(log2n, FftRadix::_2) }.unwrap();
+            f(setup.as_ptr());
+            unsafe { (self.destroy_fftsetup)(setup.as_ptr()) };
+        }
+
+        fn zip(&self, log2n: Len, stride: usize, seed: u64, dir: FftDirection) -> Vec<f32> {
+            let n = (1 << log2n) * stride;
+            let mut re = signal(seed, n);
+            let mut im = signal(seed + 1, n);
+            self.fft(log2n, |setup| {
+                let mut split = SplitComplex::new_mut(&mut re, &mut im);
+                unsafe { (self.fft_zip)(setup, &mut split, stride as _, log2n, dir) };
+            });
+            re.extend(im);
+            re
+        }
+
+        fn zrip(&self, log2n: Len, seed: u64, dir: FftDirection) -> Vec<f32> {
+            let n = 1 << log2n;
+            let x = signal(seed, n);
+            let mut re = vec![0.0f32; n / 2];
+            let mut im = vec![0.0f32; n / 2];
+            let split = SplitComplex::new_mut(&mut re, &mut im);
+            unsafe { (self.ctoz)(x.as_ptr() as _, 2, &split, 1, n / 2) };
+            self.fft(log2n, |setup| unsafe {
+                (self.fft_zrip)(setup, &split, 1, log2n, dir)
+            });
+            re.extend(im);
+            re
+        }
+
+        fn reduce(&self, f: Reduce, n: usize, stride: usize) -> f32 {
+            let a = signal(11, n * stride);
+            let mut r = 0.0f32;
+            unsafe { f(a.as_ptr(), stride as _, &mut r, n) };
+            r
+        }
+
+        fn reduce2(&self, f: Reduce2, n: usize) -> f32 {
+            let a = signal(12, n);
+            let b = signal(13, n);
+            let mut r = 0.0f32;
+            unsafe { f(a.as_ptr(), 1, b.as_ptr(), 1, &mut r, n) };
+            r
+        }
+    }
+
+    type Case = fn(&Backend) -> Vec<f32>;
+
+    const CASES: &[(&str, Case)] = &[
+        ("fft_zip_forward_64", |b| {
+            b.zip(6, 1, 1, FftDirection::Forward)
+        }),
+        ("fft_zip_inverse_64", |b| {
+            b.zip(6, 1, 3, FftDirection::Inverse)
+        }),
+        ("fft_zip_stride2_16", |b| {
+            b.zip(4, 2, 5, FftDirection::Forward)
+        }),
+        ("fft_zrip_forward_128", |b| {
+            b.zrip(7, 7, FftDirection::Forward)
+        }),
+        ("fft_zrip_inverse_128", |b| {
+            b.zrip(7, 8, FftDirection::Inverse)
+        }),
+        ("dotpr_1000", |b| vec![b.reduce2(b.dotpr, 1000)]),
+        ("distance_sq_1000", |b| vec![b.reduce2(b.distance_sq, 1000)]),
+        ("meansq_1000", |b| vec![b.reduce(b.meansq, 1000, 1)]),
+        ("svs_1000", |b| vec![b.reduce(b.svs, 1000, 1)]),
+        ("minmax_stride3", |b| {
+            [b.max, b.min, b.maxmg, b.minmg]
+                .into_iter()
+                .map(|f| b.reduce(f, 30, 3))
+                .collect()
+        }),
+        ("gen_5", |b| {
+            let mut c = vec![0.0f32; 5];
+            unsafe { (b.gen)(&-1.0, &3.0, c.as_mut_ptr(), 1, c.len()) };
+            c
+        }),
+        ("tmerg_9", |b| {
+            let a = signal(21, 9);
+            let bb = signal(22, 9);
+            let mut c = vec![0.0f32; 9];
+            unsafe { (b.tmerg)(a.as_ptr(), 1, bb.as_ptr(), 1, c.as_mut_ptr(), 1, 9) };
+            c
+        }),
+        ("rampmul_8", |b| {
+            let i = signal(23, 8);
+            let mut o = vec![0.0f32; 8];
+            let mut start = 0.5f32;
+            unsafe { (b.rampmul)(i.as_ptr(), 1, &mut start, &0.25, o.as_mut_ptr(), 1, 8) };
+            o.push(start);
+            o
+        }),
+        ("dbcon_8", |b| {
+            let a: Vec<f32> = signal(24, 8).iter().map(|v| v.abs() + 0.125).collect();
+            let mut c = vec![0.0f32; 16];
+            unsafe {
+                (b.dbcon)(a.as_ptr(), 1, &0.5, c.as_mut_ptr(), 1, 8, 0);
+                (b.dbcon)(a.as_ptr(), 1, &0.5, c.as_mut_ptr().add(8), 1, 8, 1);
+            }
+            c
+        }),
+        ("clip_16", |b| {
+            let a = signal(25, 16);
+            let mut d = vec![0.0f32; 16];
+            unsafe { (b.clip)(a.as_ptr(), 1, &-0.25, &0.5, d.as_mut_ptr(), 1, 16) };
+            d
+        }),
+    ];
+
+    fn fixtures() -> HashMap<&'static str, Vec<f32>> {
+        FIXTURES
+            .lines()
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                let mut it = l.split_whitespace();
+                let name = it.next().unwrap();
+                (name, it.map(|v| v.parse().unwrap()).collect())
+            })
+            .collect()
+    }
+
+    fn assert_close(name: &str, actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{name}: length");
+        let scale = expected.iter().fold(1.0f32, |m, v| m.max(v.abs()));
+        for (i, (a, e)) in actual.iter().zip(expected).enumerate() {
+            assert!(
+                (a - e).abs() <= 2e-5 * scale,
+                "{name}[{i}]: {a} != {e} (scale {scale})"
+            );
+        }
+    }
+
+    fn check(backend: &Backend) {
+        let fixtures = fixtures();
+        for (name, case) in CASES {
+            let expected = fixtures
+                .get(name)
+                .unwrap_or_else(|| panic!("missing fixture {name}"));
+            assert_close(name, &case(backend), expected);
+        }
+    }
+
+    #[test]
+    fn portable_matches_fixtures() {
+        check(&PORTABLE);
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn accelerate_matches_fixtures() {
+        check(&ACCELERATE);
+    }
+
+    /// Rewrites the fixtures from Accelerate:
+    /// `cargo test --features vdsp_portable record_fixtures -- --ignored`.
+    #[cfg(target_os = "macos")]
+    #[test]
+    #[ignore = "rewrites src/vdsp/fixtures.txt"]
+    fn record_fixtures() {
+        let version = std::process::Command::new("sw_vers")
+            .arg("-productVersion")
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .unwrap_or_default();
+        let mut out = format!(
+            "# Expected vDSP outputs for `vdsp::portable` tests, one case per line: `name v0 v1 ...`.\n\
+             # Recorded from Accelerate on macOS {version} {} by the `record_fixtures` test.\n\
+             # FFTs are vDSP_fft_zip and vDSP_fft_zrip output as is: zrip packs DC and Nyquist\n\
+             # into element 0 and scales forward transforms by 2, inverse transforms are unscaled.\n",
+            std::env::consts::ARCH
+        );
+        for (name, case) in CASES {
+            out.push_str(name);
+            for v in case(&ACCELERATE) {
+                out.push_str(&format!(" {v:?}"));
+            }
+            out.push('\n');
+        }
+        std::fs::write(FIXTURES_PATH, out).unwrap();
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn portable_matches_accelerate() {
+        for (name, case) in CASES {
+            assert_close(name, &case(&PORTABLE), &case(&ACCELERATE));
+        }
+    }
+
+    /// Transforms of every size from 16 to 4096 points against both
+    /// `vDSP_fft_zrip` / `vDSP_fft_zip` and `vDSP_DFT_Execute`.
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn portable_matches_accelerate_sizes() {
+        for log2n in 4..=12 {
+            let seed = 40 + log2n as u64;
+            for dir in [FftDirection::Forward, FftDirection::Inverse] {
+                let name = format!("zip {log2n} {dir:?}");
+                let zip = PORTABLE.zip(log2n, 1, seed, dir);
+                assert_close(&name, &zip, &ACCELERATE.zip(log2n, 1, seed, dir));
+                assert_close(&name, &zip, &accelerate::dft(log2n, false, seed, dir));
+
+                let name = format!("zrip {log2n} {dir:?}");
+                let zrip = PORTABLE.zrip(log2n, seed, dir);
+                assert_close(&name, &zrip, &ACCELERATE.zrip(log2n, seed, dir));
+                assert_close(&name, &zrip, &accelerate::dft(log2n, true, seed, dir));
+            }
+        }
+    }
+
+    #[test]
+    fn fft_scaling() {
+        let x = signal(31, 32);
+
+        let mut re = x[..16].to_vec();
+        let mut im = x[16..].to_vec();
+        let mut fft = vdsp::Fft::new_f32(4, vdsp::FftRadix::_2).unwrap();
+        fft.forward_io(&mut re, &mut im);
+        fft.inverse_io(&mut re, &mut im);
+        let expected: Vec<f32> = x.iter().map(|v| v * 16.0).collect();
+        re.extend(im);
+        assert_close("zip round trip", &re, &expected);
+
+        let mut re = vec![0.0f64; 16];
+        let mut im = vec![0.0f64; 16];
+        for (i, v) in x.iter().enumerate() {
+            if i % 2 == 0 {
+                re[i / 2] = *v as f64;
+            } else {
+                im[i / 2] = *v as f64;
+            }
+        }
+        let setup = unsafe { super::_create_fftsetup_f64(5, FftRadix::_2) }.unwrap();
+        let split = SplitComplex::new_mut(&mut re, &mut im);
+        unsafe {
+            super::_fft_zrip_f64(setup.as_ptr(), &split, 1, 5, FftDirection::Forward);
+            super::_fft_zrip_f64(setup.as_ptr(), &split, 1, 5, FftDirection::Inverse);
+            super::_destroy_fftsetup_f64(setup.as_ptr());
+        }
+        for (i, v) in x.iter().enumerate() {
+            let r = if i % 2 == 0 { re[i / 2] } else { im[i / 2] };
+            assert!((r - *v as f64 * 64.0).abs() < 1e-9, "zrip round trip [{i}]");
+        }
+    }
+}
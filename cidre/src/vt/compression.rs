@@ -10,8 +10,12 @@ pub use properties::profile_level;
 #[cfg(test)]
 mod tests {
     use std::ffi::c_void;
+    #[cfg(target_os = "macos")]
+    use std::sync::Mutex;
 
     use crate::{cf, cm::VideoCodec, cv};
+    #[cfg(target_os = "macos")]
+    use crate::{cm, os};
 
     use super::*;
 
@@ -70,4 +74,63 @@ mod tests {
 
         session.show();
     }
+
+    /// Stores `avcC` or `hvcC` of the first encoded sample.
+    #[cfg(target_os = "macos")]
+    extern "C" fn keep_cfg(
+        cfg: *mut Mutex<Option<Vec<u8>>>,
+        _frame: *mut c_void,
+        status: os::Status,
+        _flags: crate::vt::EncodeInfoFlags,
+        buf: Option<&cm::SampleBuf>,
+    ) {
+        assert!(status.is_ok());
+        let Some(desc) = buf.and_then(|b| b.format_desc()) else {
+            return;
+        };
+        let mut cfg = unsafe { &*cfg }.lock().unwrap();
+        if cfg.is_none() {
+            *cfg = desc.avcc().or_else(|| desc.hvcc());
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn encoded_cfg(codec: VideoCodec) -> Vec<u8> {
+        let mut cfg = Mutex::new(None);
+        let mut session = Session::new(640, 360, codec, None, None, None, Some(keep_cfg), &mut cfg)
+            .expect("encoder");
+        session.prepare().unwrap();
+
+        let frame = cv::PixelBuf::new(640, 360, cv::PixelFormat::_32_BGRA, None).unwrap();
+        for i in 0..3 {
+            session
+                .enc_frame(
+                    &frame,
+                    cm::Time::new(i, 30),
+                    cm::Time::new(1, 30),
+                    std::ptr::null_mut(),
+                )
+                .unwrap();
+        }
+        session.complete_frames(cm::Time::invalid()).unwrap();
+        session.invalidate();
+        cfg.into_inner()
+            .unwrap()
+            .expect("format desc with codec config")
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn codec_config_round_trip() {
+        let bytes = encoded_cfg(VideoCodec::H264);
+        let avcc = cm::Avcc::parse(&bytes).unwrap();
+        let desc = cm::VideoFormatDesc::with_avcc(&avcc).unwrap();
+        assert_eq!(desc.avcc(), Some(avcc.to_bytes().unwrap()));
+
+        let bytes = encoded_cfg(VideoCodec::HEVC);
+        let hvcc = cm::Hvcc::parse(&bytes).unwrap();
+        assert_eq!(hvcc.param_sets().count(), 3);
+        let desc = cm::VideoFormatDesc::with_hvcc(&hvcc, None).unwrap();
+        assert_eq!(desc.hvcc(), Some(hvcc.to_bytes().unwrap()));
+    }
 }
@@ -1,40 +1,112 @@
 use crate::{define_opts, os::Error};
 
+/// The property is not supported by the session.
+#[doc(alias = "kVTPropertyNotSupportedErr")]
 pub const PROPERTY_NOT_SUPPORTED: Error = Error::new_unchecked(-12900);
+/// The property is read only.
+#[doc(alias = "kVTPropertyReadOnlyErr")]
 pub const PROPERTY_READ_ONLY: Error = Error::new_unchecked(-12901);
+/// A parameter is invalid.
+#[doc(alias = "kVTParameterErr")]
 pub const PARAMETER: Error = Error::new_unchecked(-12902);
+/// The session is invalid.
+#[doc(alias = "kVTInvalidSessionErr")]
 pub const INVALID_SESSION: Error = Error::new_unchecked(-12903);
+/// A memory allocation failed.
+#[doc(alias = "kVTAllocationFailedErr")]
 pub const ALLOCATION_FAILED: Error = Error::new_unchecked(-12904);
+/// The pixel transfer between the given formats is not supported.
+#[doc(alias = "kVTPixelTransferNotSupportedErr")]
 pub const PIXEL_TRANSFER_NOT_SUPPORTED: Error = Error::new_unchecked(-12905); // c.f. -8961 ?
+/// No video decoder was found for the format.
+#[doc(alias = "kVTCouldNotFindVideoDecoderErr")]
 pub const COULD_NOT_FIND_VIDEO_DECODER: Error = Error::new_unchecked(-12906);
+/// The codec instance could not be created.
+#[doc(alias = "kVTCouldNotCreateInstanceErr")]
 pub const COULD_NOT_CREATE_INSTANCE: Error = Error::new_unchecked(-12907);
+/// No video encoder was found for the codec type.
+#[doc(alias = "kVTCouldNotFindVideoEncoderErr")]
 pub const COULD_NOT_FIND_VIDEO_ENCODER: Error = Error::new_unchecked(-12908);
+/// The video decoder encountered bad data.
+#[doc(alias = "kVTVideoDecoderBadDataErr")]
 pub const VIDEO_DECODER_BAD_DATA: Error = Error::new_unchecked(-12909); // c.f. -8969
+/// The video decoder does not support the data format.
+#[doc(alias = "kVTVideoDecoderUnsupportedDataFormatErr")]
 pub const VIDEO_DECODER_UNSUPPORTED_DATA_FORMAT: Error = Error::new_unchecked(-12910); // c.f. -8970
+/// The video decoder malfunctioned.
+#[doc(alias = "kVTVideoDecoderMalfunctionErr")]
 pub const VIDEO_DECODER_MALFUNCTION: Error = Error::new_unchecked(-12911); // c.f. -8960
+/// The video encoder malfunctioned.
+#[doc(alias = "kVTVideoEncoderMalfunctionErr")]
 pub const VIDEO_ENCODER_MALFUNCTION: Error = Error::new_unchecked(-12912);
+/// The video decoder is not available at this time.
+#[doc(alias = "kVTVideoDecoderNotAvailableNowErr")]
 pub const VIDEO_DECODER_NOT_AVAILABLE_NOW: Error = Error::new_unchecked(-12913);
+/// The requested image rotation is not supported.
+#[doc(alias = "kVTImageRotationNotSupportedErr")]
 pub const IMAGE_ROTATION_NOT_SUPPORTED: Error = Error::new_unchecked(-12914);
+/// The video encoder is not available at this time.
+#[doc(alias = "kVTVideoEncoderNotAvailableNowErr")]
 pub const VIDEO_ENCODER_NOT_AVAILABLE_NOW: Error = Error::new_unchecked(-12915);
+/// The decoder does not support a change of format description.
+#[doc(alias = "kVTFormatDescriptionChangeNotSupportedErr")]
 pub const FORMAT_DESCRIPTION_CHANGE_NOT_SUPPORTED: Error = Error::new_unchecked(-12916);
+/// The source buffer lacks the color attachments needed for conversion.
+#[doc(alias = "kVTInsufficientSourceColorDataErr")]
 pub const INSUFFICIENT_SOURCE_COLOR_DATA: Error = Error::new_unchecked(-12917);
+/// Color correction data could not be created.
+#[doc(alias = "kVTCouldNotCreateColorCorrectionDataErr")]
 pub const COULD_NOT_CREATE_COLOR_CORRECTION_DATA: Error = Error::new_unchecked(-12918);
+/// A ColorSync transform failed to convert.
+#[doc(alias = "kVTColorSyncTransformConvertFailedErr")]
 pub const COLOR_SYNC_TRANSFORM_CONVERT_FAILED: Error = Error::new_unchecked(-12919);
+/// The video decoder is not authorized for this content.
+#[doc(alias = "kVTVideoDecoderAuthorizationErr")]
 pub const VIDEO_DECODER_AUTHORIZATION: Error = Error::new_unchecked(-12210);
+/// The video encoder is not authorized for this content.
+#[doc(alias = "kVTVideoEncoderAuthorizationErr")]
 pub const VIDEO_ENCODER_AUTHORIZATION: Error = Error::new_unchecked(-12211);
+/// The pixel transfer for color correction failed.
+#[doc(alias = "kVTColorCorrectionPixelTransferFailedErr")]
 pub const COLOR_CORRECTION_PIXEL_TRANSFER_FAILED: Error = Error::new_unchecked(-12212);
+/// The multi-pass storage identifier does not match the session.
+#[doc(alias = "kVTMultiPassStorageIdentifierMismatchErr")]
 pub const MULTI_PASS_STORAGE_IDENTIFIER_MISMATCH: Error = Error::new_unchecked(-12213);
+/// The multi-pass storage is invalid.
+#[doc(alias = "kVTMultiPassStorageInvalidErr")]
 pub const MULTI_PASS_STORAGE_INVALID: Error = Error::new_unchecked(-12214);
+/// The frame silo received a time stamp outside the expected range.
+#[doc(alias = "kVTFrameSiloInvalidTimeStampErr")]
 pub const FRAME_SILO_INVALID_TIME_STAMP: Error = Error::new_unchecked(-12215);
+/// The frame silo time range is invalid.
+#[doc(alias = "kVTFrameSiloInvalidTimeRangeErr")]
 pub const FRAME_SILO_INVALID_TIME_RANGE: Error = Error::new_unchecked(-12216);
+/// No temporal filter was found.
+#[doc(alias = "kVTCouldNotFindTemporalFilterErr")]
 pub const COULD_NOT_FIND_TEMPORAL_FILTER: Error = Error::new_unchecked(-12217);
+/// The pixel transfer is not permitted.
+#[doc(alias = "kVTPixelTransferNotPermittedErr")]
 pub const PIXEL_TRANSFER_NOT_PERMITTED: Error = Error::new_unchecked(-12218);
+/// Image rotation during color correction failed.
+#[doc(alias = "kVTColorCorrectionImageRotationFailedErr")]
 pub const COLOR_CORRECTION_IMAGE_ROTATION_FAILED: Error = Error::new_unchecked(-12219);
+/// The video decoder was removed.
+#[doc(alias = "kVTVideoDecoderRemovedErr")]
 pub const VIDEO_DECODER_REMOVED: Error = Error::new_unchecked(-17690);
+/// The session malfunctioned.
+#[doc(alias = "kVTSessionMalfunctionErr")]
 pub const SESSION_MALFUNCTION: Error = Error::new_unchecked(-17691);
+/// The video decoder requires Rosetta.
+#[doc(alias = "kVTVideoDecoderNeedsRosettaErr")]
 pub const VIDEO_DECODER_NEEDS_ROSETTA: Error = Error::new_unchecked(-17692);
+/// The video encoder requires Rosetta.
+#[doc(alias = "kVTVideoEncoderNeedsRosettaErr")]
 pub const VIDEO_ENCODER_NEEDS_ROSETTA: Error = Error::new_unchecked(-17693);
+/// A reference frame needed by the video decoder is missing.
+#[doc(alias = "kVTVideoDecoderReferenceMissingErr")]
 pub const VIDEO_DECODER_REFERENCE_MISSING: Error = Error::new_unchecked(-17694);
+/// The video decoder failed to message its callback.
+#[doc(alias = "kVTVideoDecoderCallbackMessagingErr")]
 pub const VIDEO_DECODER_CALLBACK_MESSAGING: Error = Error::new_unchecked(-17695);
 
 define_opts!(